@@ -0,0 +1,19 @@
+//! Process-wide backup retention
+//!
+//! `--backup-retention N` applies to every config the command writes: each
+//! write prunes that file's backups down to the newest N.
+
+use std::sync::OnceLock;
+
+/// Retention given on the command line, if any
+static RETENTION: OnceLock<Option<usize>> = OnceLock::new();
+
+/// Set the retention for this process
+pub fn init(retention: Option<usize>) {
+    let _ = RETENTION.set(retention);
+}
+
+/// Get the retention (`None` keeps the default count)
+pub fn get() -> Option<usize> {
+    RETENTION.get().copied().flatten()
+}
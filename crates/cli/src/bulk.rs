@@ -0,0 +1,70 @@
+//! Partial-failure handling for bulk commands
+//!
+//! Commands that work across many projects keep going when a single config
+//! is unreadable, then print a failure summary and exit with
+//! [`PARTIAL_FAILURE_EXIT_CODE`]. With `--strict` any failure aborts the
+//! command with a regular error instead.
+//!
+//! Archived projects are skipped unless `--include-archived` is given.
+
+use crate::timings;
+use anyhow::{bail, Result};
+use claude_config_manager_core::{
+    paths::get_registry_path, MultiResult, ProjectInfo, ProjectRegistry, ProjectScanner,
+};
+use std::path::PathBuf;
+
+/// Exit status when some, but not all, files could not be processed
+///
+/// Distinct from 1 (command failed) and 2 (usage error).
+pub const PARTIAL_FAILURE_EXIT_CODE: i32 = 3;
+
+/// Project scanner that marks archived projects using the registry
+pub fn project_scanner(depth: Option<usize>) -> Result<ProjectScanner> {
+    let registry = ProjectRegistry::load(get_registry_path())?;
+    Ok(ProjectScanner::new(depth, false)
+        .with_timings(timings::collector())
+        .with_registry(registry))
+}
+
+/// Add discovered projects to the registry, so `--all` commands find them
+/// and later scans read their archived flag from it
+pub fn register_projects(projects: &[ProjectInfo]) -> Result<()> {
+    let mut registry = ProjectRegistry::load(get_registry_path())?;
+    if registry.register_projects(projects) {
+        registry.save()?;
+    }
+    Ok(())
+}
+
+/// Config files of the projects a bulk command should visit
+pub fn config_paths(
+    projects: &[ProjectInfo],
+    include_archived: bool,
+) -> impl Iterator<Item = &PathBuf> {
+    projects
+        .iter()
+        .filter(move |project| project.has_config && (include_archived || !project.archived))
+        .map(|project| &project.config_path)
+}
+
+/// Abort before producing output if `--strict` was given and anything failed
+pub fn check_strict<T>(result: &MultiResult<T>, strict: bool) -> Result<()> {
+    if strict && result.has_failures() {
+        bail!("{}\nAborted because of --strict", result.failure_summary());
+    }
+    Ok(())
+}
+
+/// Print the failure summary and exit with the partial-failure status
+///
+/// Returns normally when nothing failed.
+pub fn finish<T>(result: &MultiResult<T>) {
+    if !result.has_failures() {
+        return;
+    }
+
+    eprintln!("\n{}", result.failure_summary());
+    timings::print_footer();
+    std::process::exit(PARTIAL_FAILURE_EXIT_CODE);
+}
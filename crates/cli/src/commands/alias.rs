@@ -0,0 +1,111 @@
+//! Alias command implementation
+//!
+//! Manages project path aliases, used as `@name` wherever a project path is
+//! accepted
+
+use crate::managers::config_manager;
+use crate::project_arg::load_settings;
+use anyhow::{bail, Result};
+use clap::{Parser, Subcommand};
+use std::path::PathBuf;
+
+/// Project path alias commands
+#[derive(Parser, Debug)]
+pub struct AliasArgs {
+    #[command(subcommand)]
+    command: AliasCommand,
+}
+
+/// Project path alias commands
+#[derive(Subcommand, Debug)]
+pub enum AliasCommand {
+    /// Define an alias, e.g. `ccm alias add api ~/work/api-service`
+    Add {
+        /// Alias name (used as @NAME)
+        name: String,
+        /// Project directory
+        path: PathBuf,
+    },
+    /// List defined aliases
+    List,
+    /// Remove an alias
+    Remove {
+        /// Alias name
+        name: String,
+    },
+}
+
+impl AliasArgs {
+    /// Execute the alias command
+    pub fn execute(&self) -> Result<()> {
+        match &self.command {
+            AliasCommand::Add { name, path } => add(name, path),
+            AliasCommand::List => list(),
+            AliasCommand::Remove { name } => remove(name),
+        }
+    }
+}
+
+/// Define an alias, storing the absolute target path
+fn add(name: &str, path: &std::path::Path) -> Result<()> {
+    let target = match path.canonicalize() {
+        Ok(target) => target,
+        Err(_) => std::env::current_dir()?.join(path),
+    };
+    if !target.is_dir() {
+        eprintln!("Warning: {} is not an existing directory", target.display());
+    }
+
+    let mut settings = load_settings()?;
+    settings.add_alias(name, &target)?;
+    save_settings(&settings)?;
+
+    println!("✓ @{name} -> {}", target.display());
+    Ok(())
+}
+
+/// Print every alias and its target
+fn list() -> Result<()> {
+    let settings = load_settings()?;
+    if settings.aliases.is_empty() {
+        println!("No aliases defined. Add one with `ccm alias add <NAME> <PATH>`.");
+        return Ok(());
+    }
+
+    let stale: Vec<&str> = settings
+        .stale_aliases()
+        .into_iter()
+        .map(|(name, _)| name)
+        .collect();
+    for (name, target) in &settings.aliases {
+        let marker = if stale.contains(&name.as_str()) {
+            "  (missing)"
+        } else {
+            ""
+        };
+        println!("@{name:<20} {target}{marker}");
+    }
+    Ok(())
+}
+
+/// Remove an alias
+fn remove(name: &str) -> Result<()> {
+    let mut settings = load_settings()?;
+    let Some(target) = settings.remove_alias(name) else {
+        let name = name.trim_start_matches('@');
+        bail!("No alias named '@{name}'");
+    };
+    save_settings(&settings)?;
+
+    println!("✓ Removed @{} ({target})", name.trim_start_matches('@'));
+    Ok(())
+}
+
+/// Write ccm's settings back to the global config
+fn save_settings(settings: &claude_config_manager_core::CcmSettings) -> Result<()> {
+    let manager = config_manager();
+    let mut config = manager.get_global_config()?;
+    config.set_ccm_settings(settings);
+    manager.update_global_config(&config)?;
+    Ok(())
+}
@@ -1,21 +1,60 @@
 //! Configuration management commands
 //!
-//! Implements `config get` and `config set` commands
+//! Implements `config get`, `config set`, `config apply`, `config init`,
+//! `config validate`, `config fix`, `config normalize`,
+//! `config canonicalize`, `config migrate-embedded`, `config edit`, `config open`, `config shadows`,
+//! `config wait`, `config which` and related commands
 
-use crate::key_path::set_value_by_path;
-use crate::output::{format_json, format_table};
+use crate::bulk;
+use crate::editor;
+use crate::file_arg::{FileArg, FileFormat};
+use crate::key_path::{
+    check_index, parse_settings, remove_index_by_path, remove_value_by_path, set_value_by_path,
+    SetMode,
+};
+use crate::managers::{config_manager, config_manager_in};
+use crate::output::{
+    format_json, format_table, format_yaml, to_yaml_string, OutputFormat, TableOptions,
+};
+use crate::timings;
 use anyhow::Result;
 use clap::Parser;
 use claude_config_manager_core::{
-    paths::get_global_config_path, ConfigDiff, ConfigManager, ConfigScope,
+    archive,
+    backup::RiskyOperation,
+    blank_value_warnings,
+    config::{
+        diff::overridden_key_paths,
+        embedded, key_path, limits,
+        normalize::{canonical_json, canonical_json_strict, Normalizer},
+        schema,
+    },
+    create_project_config, env_key_warnings, expand_env, expand_tilde, find_shadows,
+    paths::{
+        find_project_config, get_backup_dir, get_global_config_path, get_registry_path,
+        project_config_path,
+    },
+    platform_warnings, save_profile,
+    settings::SETTINGS_KEY,
+    validate_against_schema_version, validate_config, wait_for_with, ClaudeConfig, ConfigDiff,
+    ConfigError, ConfigManager, ConfigScope, ConfigSource, EditSession, ImportExportOptions,
+    OverwritePolicy, ProjectRegistry, ShadowState, SourceMap, SystemPoller, WaitOptions,
+    WaitOutcome, WriteOptions,
 };
-use std::path::PathBuf;
+use serde_json::Value;
+use similar::TextDiff;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Exit status of `config wait` when the timeout expires (as in `timeout(1)`)
+const WAIT_TIMEOUT_EXIT_CODE: i32 = 124;
 
 /// Configuration management commands
 #[derive(Parser, Debug)]
 pub struct ConfigArgs {
-    /// Project path (default: auto-detect)
-    #[arg(short, long)]
+    /// Project path or @alias (default: auto-detect)
+    #[arg(short, long, value_parser = crate::project_arg::parse_project)]
     project: Option<PathBuf>,
 
     /// Output format
@@ -26,15 +65,6 @@ pub struct ConfigArgs {
     command: ConfigCommand,
 }
 
-/// Output format for configuration display
-#[derive(clap::ValueEnum, Clone, Debug, PartialEq)]
-enum OutputFormat {
-    /// Human-readable table format
-    Table,
-    /// Machine-readable JSON format
-    Json,
-}
-
 /// Configuration subcommands
 #[derive(Parser, Debug)]
 enum ConfigCommand {
@@ -43,105 +73,513 @@ enum ConfigCommand {
         /// Configuration key (e.g., "mcpServers.npx.enabled")
         /// If omitted, shows all configuration
         key: Option<String>,
+        /// Expand `~` and environment variables in allowed paths before display
+        #[arg(long)]
+        resolve_paths: bool,
+        /// Show only the values the project sets differently from global
+        #[arg(long, conflicts_with = "key")]
+        only_overrides: bool,
+        /// In table output, list long arrays and deep values in full
+        #[arg(long)]
+        full: bool,
     },
     /// Set configuration value
     Set {
-        /// Configuration key (e.g., "mcpServers.npx.enabled")
+        /// Configuration key (e.g., "mcpServers.npx.enabled", "customInstructions[2]"
+        /// to replace an element or "customInstructions[+]" to append one)
         key: String,
         /// Configuration value (JSON for objects/arrays)
-        value: String,
+        #[arg(required_unless_present = "remove")]
+        value: Option<String>,
+        /// Add the value to an array field instead of replacing the array
+        #[arg(long)]
+        append: bool,
+        /// With --append, skip values that are already in the array
+        #[arg(long, requires = "append")]
+        dedupe: bool,
+        /// Remove matching elements from an array field; with an indexed key
+        /// (e.g. `allowedPaths[1]`), remove that element and omit VALUE
+        #[arg(
+            long,
+            value_name = "VALUE",
+            num_args = 0..=1,
+            conflicts_with_all = ["value", "append"]
+        )]
+        remove: Option<Option<String>>,
+        /// With --remove, do nothing (instead of failing) if the value isn't present
+        #[arg(long, requires = "remove")]
+        ignore_missing: bool,
+        /// Rewrite the file (with a backup) even if the value is already set
+        #[arg(long)]
+        force_write: bool,
+        /// Remove exact-duplicate customInstructions entries before writing
+        #[arg(long)]
+        dedupe_instructions: bool,
+    },
+    /// Set several values at once from a key=value file or a JSON object of key paths
+    ///
+    /// All settings are applied in one write with one backup; if any setting
+    /// is invalid, nothing is written.
+    Apply {
+        /// Settings file (`key=value` lines, or `{"keyPath": value, ...}`)
+        file: PathBuf,
+    },
+    /// Create the project's .claude/config.json, optionally copying another project or a profile
+    Init {
+        /// Copy from this project's config (path or @alias)
+        #[arg(
+            long,
+            value_name = "PATH",
+            conflicts_with = "from_profile",
+            value_parser = crate::project_arg::parse_project
+        )]
+        from_project: Option<PathBuf>,
+        /// Copy from a profile saved as <config dir>/profiles/<NAME>.json
+        #[arg(long, value_name = "NAME")]
+        from_profile: Option<String>,
+        /// Copy only these sections (comma-separated, e.g. mcpServers,allowedPaths)
+        #[arg(long, value_delimiter = ',')]
+        only: Vec<String>,
+        /// Replace an existing project config (it is backed up first)
+        #[arg(long)]
+        force: bool,
+    },
+    /// Save the global config (or the project's, with --project) as a profile for init
+    SaveProfile {
+        /// Profile name; saved as <config dir>/profiles/<NAME>.json
+        name: String,
+        /// Save only these key paths (comma-separated, e.g. mcpServers,allowedPaths)
+        #[arg(long, value_delimiter = ',')]
+        only: Vec<String>,
+        /// Replace an existing profile (it is backed up first)
+        #[arg(long)]
+        force: bool,
     },
     /// Show differences between global and project configuration
     Diff {
-        /// Project path (default: auto-detect if not provided via --project flag)
+        /// Project path or @alias (default: auto-detect if not provided via --project flag)
+        #[arg(value_parser = crate::project_arg::parse_project)]
         project_path: Option<PathBuf>,
+        /// Exit with status 1 if there are differences (like `git diff --exit-code`)
+        #[arg(long)]
+        exit_code: bool,
+        /// Compare against this config file (e.g. a team template) instead of
+        /// the global config
+        #[arg(long, value_name = "FILE")]
+        base: Option<PathBuf>,
+        /// With --base, compare the merged global and project config instead
+        /// of the project config alone
+        #[arg(long, requires = "base")]
+        merged: bool,
     },
     /// Export configuration to a file
     Export {
-        /// Output file path
-        output_file: PathBuf,
+        /// Output file path (or directory, with --auto-name); `-` for stdout
+        output_file: FileArg,
+        /// Overwrite the output file if it exists with different content
+        #[arg(long)]
+        force: bool,
+        /// Append the date and a short content hash to the file name
+        #[arg(long)]
+        auto_name: bool,
+        /// Export format (only JSON can be exported)
+        #[arg(long, default_value = "json", value_parser = FileFormat::export_parser())]
+        format: FileFormat,
     },
     /// Import configuration from a file
     Import {
-        /// Input file path
-        input_file: PathBuf,
+        /// Input file path; `-` for stdin
+        input_file: FileArg,
         /// Skip validation
         #[arg(long)]
         no_validate: bool,
+        /// Input format (default: from the file extension, JSON for stdin)
+        #[arg(long, value_enum)]
+        format: Option<FileFormat>,
+    },
+    /// Check the config (global, or the --project config) for problems
+    Validate {
+        /// Also check the whole file against the bundled JSON Schema
+        #[arg(long)]
+        schema: bool,
+        /// Bundled schema version to check against
+        #[arg(long, requires = "schema", value_name = "VERSION", default_value_t = schema::LATEST_SCHEMA_VERSION)]
+        schema_version: u32,
+        /// Validate the merged (global + project) config and warn about values the merge discards
+        #[arg(long, conflicts_with = "schema")]
+        merged: bool,
+    },
+    /// Automatically fix trivial configuration issues
+    Fix {
+        /// Show what would change without writing
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Rewrite a config file in canonical form
+    Normalize {
+        /// Config file to normalize (default: global, or the --project config)
+        #[arg(long)]
+        file: Option<PathBuf>,
+        /// Show what would change without writing
+        #[arg(long)]
+        dry_run: bool,
+        /// Don't apply this rule (can be repeated)
+        #[arg(long, value_name = "RULE")]
+        skip: Vec<String>,
     },
+    /// Rewrite the config in strict canonical form, for git-tracked configs
+    ///
+    /// Sorted keys, sorted unordered arrays (allowedPaths, but not args),
+    /// 2-space indentation and a trailing newline.
+    Canonicalize {
+        /// Show what would change without writing
+        #[arg(long)]
+        dry_run: bool,
+        /// Keep every later write to this config in canonical form
+        /// (sets "ccm": {"canonicalOnWrite": true})
+        #[arg(long, conflicts_with = "disable")]
+        enable: bool,
+        /// Stop canonicalizing writes and restore the default formatting
+        #[arg(long)]
+        disable: bool,
+    },
+    /// Create .claude/config.json from a config embedded in package.json or pyproject.toml
+    MigrateEmbedded {
+        /// Remove the embedded section from the manifest afterwards
+        #[arg(long)]
+        remove: bool,
+    },
+    /// Archive the global config and all backups into a .tar.gz file
+    Archive {
+        /// Output archive path
+        #[arg(short, long, default_value = "ccm-archive.tar.gz")]
+        out: PathBuf,
+    },
+    /// Restore the global config and backups from an archive
+    Unarchive {
+        /// Archive created by 'ccm config archive'
+        archive: PathBuf,
+        /// Overwrite an existing global config
+        #[arg(long)]
+        force: bool,
+    },
+    /// Open the config in $VISUAL or $EDITOR, then validate the result
+    Edit {
+        /// Create a missing config, and restore an invalid edit, without asking
+        #[arg(short, long)]
+        yes: bool,
+    },
+    /// Open the config in $EDITOR (or $VISUAL), creating it if missing, then validate it
+    Open,
+    /// Show which projects override a global key
+    Shadows {
+        /// Configuration key (e.g., "mcpServers.fetch.command")
+        key: String,
+        /// Directory to scan for projects (default: current directory)
+        #[arg(long, conflicts_with = "all")]
+        path: Option<PathBuf>,
+        /// Check every project in the project registry instead of scanning
+        #[arg(long)]
+        all: bool,
+    },
+    /// Block until the config (or one key) changes; exits 124 on timeout
+    Wait {
+        /// Configuration key to watch (e.g., "mcpServers.npx.enabled")
+        #[arg(long)]
+        key: Option<String>,
+        /// Wait until the key has this JSON value instead of any change
+        #[arg(long, requires = "key", value_name = "JSON")]
+        until: Option<String>,
+        /// Give up after this long (e.g. "30s", "5m", "500ms"; default: never)
+        #[arg(long, value_parser = parse_duration)]
+        timeout: Option<Duration>,
+        /// Time between checks of the config file
+        #[arg(long, value_parser = parse_duration, default_value = "200ms")]
+        interval: Duration,
+    },
+    /// List the files making up the effective config, including the bases
+    /// the global config extends
+    Which,
 }
 
 impl ConfigArgs {
     /// Execute the configuration command
     pub fn execute(&self) -> Result<()> {
         match &self.command {
-            ConfigCommand::Get { key } => {
-                self.cmd_get(key.as_deref())?;
+            ConfigCommand::Get {
+                key,
+                resolve_paths,
+                only_overrides,
+                full,
+            } => {
+                self.cmd_get(key.as_deref(), *resolve_paths, *only_overrides, *full)?;
             }
-            ConfigCommand::Set { key, value } => {
-                self.cmd_set(key, value)?;
+            ConfigCommand::Set {
+                key,
+                value,
+                append,
+                dedupe,
+                remove,
+                ignore_missing,
+                force_write,
+                dedupe_instructions,
+            } => {
+                if let Some(element) = remove {
+                    self.cmd_remove(key, element.as_deref(), *ignore_missing)?;
+                } else {
+                    let mode = if *append {
+                        SetMode::Append { dedupe: *dedupe }
+                    } else {
+                        SetMode::Replace
+                    };
+                    // clap requires a value unless --remove is given
+                    self.cmd_set(
+                        key,
+                        value.as_deref().unwrap_or_default(),
+                        mode,
+                        WriteOptions::new()
+                            .with_force(*force_write)
+                            .with_dedupe_instructions(*dedupe_instructions),
+                    )?;
+                }
             }
-            ConfigCommand::Diff { project_path } => {
-                self.cmd_diff(project_path.as_ref())?;
+            ConfigCommand::Apply { file } => {
+                self.cmd_apply(file)?;
             }
-            ConfigCommand::Export { output_file } => {
-                self.cmd_export(output_file)?;
+            ConfigCommand::Init {
+                from_project,
+                from_profile,
+                only,
+                force,
+            } => {
+                let source = match (from_project, from_profile) {
+                    (Some(project), _) => ConfigSource::Project(project.clone()),
+                    (None, Some(profile)) => ConfigSource::Profile(profile.clone()),
+                    (None, None) => ConfigSource::Empty,
+                };
+                self.cmd_init(&source, only, *force)?;
+            }
+            ConfigCommand::SaveProfile { name, only, force } => {
+                self.cmd_save_profile(name, only, *force)?;
+            }
+            ConfigCommand::Diff {
+                project_path,
+                exit_code,
+                base,
+                merged,
+            } => {
+                let diff_count = match base {
+                    Some(base) => self.cmd_diff_base(project_path.as_ref(), base, *merged)?,
+                    None => self.cmd_diff(project_path.as_ref())?,
+                };
+                if *exit_code && diff_count > 0 {
+                    std::process::exit(1);
+                }
+            }
+            ConfigCommand::Export {
+                output_file,
+                force,
+                auto_name,
+                format,
+            } => {
+                let options = ImportExportOptions {
+                    format: (*format).into(),
+                    overwrite: if *force {
+                        OverwritePolicy::Force
+                    } else {
+                        OverwritePolicy::Refuse
+                    },
+                    auto_name: *auto_name,
+                    ..Default::default()
+                };
+                self.cmd_export(output_file, options)?;
             }
             ConfigCommand::Import {
                 input_file,
                 no_validate,
+                format,
             } => {
-                self.cmd_import(input_file, !no_validate)?;
+                let options = ImportExportOptions {
+                    validate: !no_validate,
+                    format: format.unwrap_or(FileFormat::Json).into(),
+                    ..Default::default()
+                };
+                self.cmd_import(input_file, options, format.is_some())?;
+            }
+            ConfigCommand::Validate {
+                schema,
+                schema_version,
+                merged,
+            } => {
+                if *merged {
+                    self.cmd_validate_merged()?;
+                } else {
+                    self.cmd_validate(schema.then_some(*schema_version))?;
+                }
+            }
+            ConfigCommand::Fix { dry_run } => {
+                self.cmd_fix(*dry_run)?;
+            }
+            ConfigCommand::Normalize {
+                file,
+                dry_run,
+                skip,
+            } => {
+                self.cmd_normalize(file.as_deref(), *dry_run, skip)?;
+            }
+            ConfigCommand::Canonicalize {
+                dry_run,
+                enable,
+                disable,
+            } => {
+                self.cmd_canonicalize(*dry_run, *enable, *disable)?;
+            }
+            ConfigCommand::MigrateEmbedded { remove } => {
+                self.cmd_migrate_embedded(*remove)?;
+            }
+            ConfigCommand::Archive { out } => {
+                self.cmd_archive(out)?;
+            }
+            ConfigCommand::Unarchive { archive, force } => {
+                self.cmd_unarchive(archive, *force)?;
+            }
+            ConfigCommand::Edit { yes } => {
+                self.cmd_edit(*yes)?;
+            }
+            ConfigCommand::Open => {
+                self.cmd_open()?;
+            }
+            ConfigCommand::Shadows { key, path, all } => {
+                self.cmd_shadows(key, path.as_deref(), *all)?;
+            }
+            ConfigCommand::Wait {
+                key,
+                until,
+                timeout,
+                interval,
+            } => {
+                let options = WaitOptions::new()
+                    .with_timeout(*timeout)
+                    .with_interval(*interval);
+                if !self.cmd_wait(key.as_deref(), until.as_deref(), options)? {
+                    timings::print_footer();
+                    std::process::exit(WAIT_TIMEOUT_EXIT_CODE);
+                }
+            }
+            ConfigCommand::Which => {
+                self.cmd_which()?;
             }
         }
         Ok(())
     }
 
     /// Get configuration value(s)
-    fn cmd_get(&self, key: Option<&str>) -> Result<()> {
+    fn cmd_get(
+        &self,
+        key: Option<&str>,
+        resolve_paths: bool,
+        only_overrides: bool,
+        full: bool,
+    ) -> Result<()> {
         // Create backup directory (use global config dir for backups)
         let backup_dir = get_global_config_path()
             .parent()
             .map(|p| p.join("backups"))
             .unwrap_or_else(|| PathBuf::from(".backups"));
 
-        let manager = ConfigManager::new(&backup_dir);
+        let manager = config_manager_in(&backup_dir);
 
         // Get configuration
-        let config = if let Some(project_path) = &self.project {
-            manager.get_merged_config(Some(project_path))?
-        } else {
-            manager.get_merged_config(None)?
-        };
+        let (mut config, notes) = manager.get_merged_config_with_notes(self.project.as_deref())?;
+        if let Some(key) = key {
+            check_index(&config, key)?;
+        }
+
+        print_large_config_hint(self.project.as_deref());
+        for note in &notes {
+            eprintln!("Note: {note}");
+        }
+
+        // Resolve allowed paths for display only (stored config is untouched)
+        if resolve_paths {
+            if let Some(paths) = config.allowed_paths.as_mut() {
+                for path in paths.iter_mut() {
+                    *path = expand_tilde(Path::new(&expand_env(path)))
+                        .to_string_lossy()
+                        .to_string();
+                }
+            }
+        }
+
+        if only_overrides {
+            return self.print_overrides(&manager, &config);
+        }
 
         // Output based on format
         match self.output {
             OutputFormat::Json => {
                 format_json(&config, key)?;
             }
+            OutputFormat::Yaml => {
+                format_yaml(&config, key)?;
+            }
             OutputFormat::Table => {
-                format_table(&config, key)?;
+                format_table(&config, key, TableOptions { full })?;
             }
         }
 
         Ok(())
     }
 
-    /// Set configuration value
-    fn cmd_set(&self, key: &str, value: &str) -> Result<()> {
-        // Determine which config file to modify
-        let config_path = if let Some(project_path) = &self.project {
-            project_path.join(".claude").join("config.json")
-        } else {
-            get_global_config_path()
-        };
+    /// Print the key paths the project overrides, with their effective values
+    fn print_overrides(&self, manager: &ConfigManager, merged: &ClaudeConfig) -> Result<()> {
+        if manager
+            .get_project_config(self.project.as_deref())?
+            .is_none()
+        {
+            println!("No project configuration found; every value comes from the global config.");
+            return Ok(());
+        }
 
-        let backup_dir = config_path
-            .parent()
-            .map(|p| p.join("backups"))
-            .unwrap_or_else(|| PathBuf::from(".backups"));
+        let (diffs, _) = manager.diff_configs(self.project.as_deref())?;
+        let merged = serde_json::to_value(merged)?;
+        let overrides: serde_json::Map<String, Value> = overridden_key_paths(&diffs)
+            .into_iter()
+            .map(|path| {
+                let value = key_path::get_element(&merged, &path)
+                    .cloned()
+                    .unwrap_or(Value::Null);
+                (path, value)
+            })
+            .collect();
 
-        let manager = ConfigManager::new(&backup_dir);
+        match self.output {
+            OutputFormat::Json => {
+                println!(
+                    "{}",
+                    serde_json::to_string_pretty(&Value::Object(overrides))?
+                );
+            }
+            OutputFormat::Yaml => {
+                print!("{}", to_yaml_string(&Value::Object(overrides))?);
+            }
+            OutputFormat::Table if overrides.is_empty() => {
+                println!("The project doesn't override any global values.");
+            }
+            OutputFormat::Table => {
+                println!("Project overrides:");
+                for (key_path, value) in &overrides {
+                    println!("  {key_path}: {value}");
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Set configuration value
+    fn cmd_set(&self, key: &str, value: &str, mode: SetMode, options: WriteOptions) -> Result<()> {
+        let (config_path, manager) = self.set_target()?;
 
         // Read existing config or create new one
         let mut config = if config_path.exists() {
@@ -151,10 +589,14 @@ impl ConfigArgs {
         };
 
         // Set the value using key path
-        set_value_by_path(&mut config, key, value)?;
+        set_value_by_path(&mut config, key, value, mode)?;
 
-        // Write config with backup
-        manager.write_config_with_backup(&config_path, &config)?;
+        // Write config with backup, unless the file already holds it
+        let written = manager.write_config_with_options(&config_path, &config, options)?;
+        if !written {
+            println!("Configuration unchanged: {key} already has that value.");
+            return Ok(());
+        }
 
         // Success message
         if config_path.exists() {
@@ -168,15 +610,187 @@ impl ConfigArgs {
         Ok(())
     }
 
+    /// Apply every setting in a settings file in one write
+    fn cmd_apply(&self, file: &Path) -> Result<()> {
+        let content = std::fs::read_to_string(file)
+            .map_err(|e| anyhow::anyhow!("Failed to read {}: {e}", file.display()))?;
+        let settings =
+            parse_settings(&content).map_err(|e| anyhow::anyhow!("{}: {e}", file.display()))?;
+
+        let (config_path, manager) = self.set_target()?;
+        let mut config = if config_path.exists() {
+            manager.read_config(&config_path)?
+        } else {
+            ClaudeConfig::new()
+        };
+
+        // Try every setting so all problems are reported at once
+        let errors: Vec<String> = settings
+            .iter()
+            .filter_map(|(key, value)| {
+                set_value_by_path(&mut config, key, value, SetMode::Replace)
+                    .err()
+                    .map(|e| format!("  {key}: {e}"))
+            })
+            .collect();
+        if !errors.is_empty() {
+            anyhow::bail!(
+                "No settings were applied; {} invalid setting(s):\n{}",
+                errors.len(),
+                errors.join("\n")
+            );
+        }
+        validate_config(&config).map_err(|e| anyhow::anyhow!("No settings were applied: {e}"))?;
+
+        if manager.write_config_with_options(&config_path, &config, WriteOptions::new())? {
+            println!(
+                "Applied {} setting(s) to {}",
+                settings.len(),
+                config_path.display()
+            );
+        } else {
+            println!(
+                "Configuration unchanged: {} already has all {} setting(s).",
+                config_path.display(),
+                settings.len()
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Create a project config from a source
+    fn cmd_init(&self, source: &ConfigSource, sections: &[String], force: bool) -> Result<()> {
+        let project_dir = match &self.project {
+            Some(path) => path.clone(),
+            None => std::env::current_dir()?,
+        };
+        let manager = config_manager();
+
+        let created = create_project_config(&manager, &project_dir, source, sections, force)
+            .map_err(|e| match e {
+                ConfigError::ValidationFailed { ref rule, .. } if rule == "ConfigExists" => {
+                    e.with_suggestion("Pass --force to replace it (a backup is kept)")
+                }
+                e => e,
+            })?;
+
+        println!("✓ Created {}", created.path.display());
+        for (section, count) in &created.section_counts {
+            println!("  {section}: {count}");
+        }
+        for warning in &created.warnings {
+            eprintln!("Warning: {warning}");
+        }
+
+        Ok(())
+    }
+
+    /// Save the global or project config as a profile
+    fn cmd_save_profile(&self, name: &str, key_paths: &[String], force: bool) -> Result<()> {
+        let manager = config_manager();
+        let config = match &self.project {
+            Some(project) => manager
+                .get_project_config(Some(project))?
+                .ok_or_else(|| anyhow::anyhow!("{} has no project config", project.display()))?,
+            None => manager.get_global_config()?,
+        };
+
+        let (path, missing) =
+            save_profile(&manager, name, &config, key_paths, force).map_err(|e| match e {
+                ConfigError::ValidationFailed { ref rule, .. } if rule == "ProfileExists" => {
+                    e.with_suggestion("Pass --force to replace it (a backup is kept)")
+                }
+                e => e,
+            })?;
+
+        println!("✓ Saved profile '{name}' to {}", path.display());
+        for key in &missing {
+            eprintln!("Warning: the config has no '{key}'");
+        }
+        println!("Use it with: ccm config init --from-profile {name}");
+
+        Ok(())
+    }
+
+    /// Remove elements from an array field, by value or by index
+    fn cmd_remove(&self, key: &str, value: Option<&str>, ignore_missing: bool) -> Result<()> {
+        let (config_path, manager) = self.set_target()?;
+
+        let mut config = if config_path.exists() {
+            manager.read_config(&config_path)?
+        } else {
+            claude_config_manager_core::ClaudeConfig::new()
+        };
+
+        let has_index = key_path::split_index(key)?.1.is_some();
+        let value = match (value, has_index) {
+            (None, true) => {
+                let removed = remove_index_by_path(&mut config, key)?;
+                manager.write_config_with_backup(&config_path, &config)?;
+                println!("Removed '{key}' ({removed}).");
+                return Ok(());
+            }
+            (Some(_), true) => {
+                anyhow::bail!("'{key}' names one element; pass --remove without a value")
+            }
+            (None, false) => {
+                anyhow::bail!("--remove needs a value, or an indexed key such as '{key}[0]'")
+            }
+            (Some(value), false) => value,
+        };
+
+        let removed = remove_value_by_path(&mut config, key, value)?;
+        if removed == 0 {
+            if ignore_missing {
+                println!("'{value}' is not in '{key}'; nothing to remove.");
+                return Ok(());
+            }
+            anyhow::bail!(
+                "'{value}' is not in '{key}'\nPass --ignore-missing to treat this as a no-op"
+            );
+        }
+
+        manager.write_config_with_backup(&config_path, &config)?;
+        println!("Removed {removed} element(s) from '{key}'.");
+
+        Ok(())
+    }
+
+    /// Config file selected by `--project` (default: the global config)
+    fn config_path(&self) -> Result<PathBuf> {
+        let scope = if self.project.is_some() {
+            ConfigScope::Project
+        } else {
+            ConfigScope::Global
+        };
+        Ok(config_manager().config_path_for_scope(&scope, self.project.as_deref())?)
+    }
+
+    /// Config file modified by `config set`, and a manager to write it
+    fn set_target(&self) -> Result<(PathBuf, ConfigManager)> {
+        let config_path = self.config_path()?;
+
+        let backup_dir = config_path
+            .parent()
+            .map(|p| p.join("backups"))
+            .unwrap_or_else(|| PathBuf::from(".backups"));
+
+        let manager = config_manager_in(&backup_dir);
+        Ok((config_path, manager))
+    }
+
     /// Show configuration differences
-    fn cmd_diff(&self, project_path: Option<&PathBuf>) -> Result<()> {
+    ///
+    /// Returns the number of differences found
+    fn cmd_diff(&self, project_path: Option<&PathBuf>) -> Result<usize> {
         // Create backup directory
         let backup_dir = get_global_config_path()
             .parent()
             .map(|p| p.join("backups"))
             .unwrap_or_else(|| PathBuf::from(".backups"));
 
-        let manager = ConfigManager::new(&backup_dir);
+        let manager = config_manager_in(&backup_dir);
 
         // Determine project path
         let project = if let Some(p) = project_path {
@@ -193,17 +807,113 @@ impl ConfigArgs {
         // Display results
         if diffs.is_empty() {
             println!("No differences found between global and project configuration.");
-            return Ok(());
+            return Ok(0);
         }
 
         println!("Configuration differences ({} total):\n", diffs.len());
+        self.print_diff_groups(
+            &diffs,
+            [
+                "Additions (project-specific):",
+                "Removals (missing in project):",
+                "Modifications (different values):",
+            ],
+            Some(&source_map),
+        )?;
+
+        // Display source summary
+        println!("Source summary:");
+        let mut global_count = 0;
+        let mut project_count = 0;
+        let mut base_count = 0;
+        for scope in source_map.sources.values() {
+            match scope {
+                ConfigScope::Global => global_count += 1,
+                ConfigScope::Project => project_count += 1,
+                ConfigScope::Base => base_count += 1,
+            }
+        }
+        if base_count > 0 {
+            println!("  Values from base: {base_count}");
+        }
+        println!("  Values from global: {global_count}");
+        println!("  Values from project: {project_count}");
+
+        Ok(diffs.len())
+    }
+
+    /// Diff the project (or merged) config against a base config file
+    ///
+    /// # Returns
+    /// The number of differences found
+    fn cmd_diff_base(
+        &self,
+        project_path: Option<&PathBuf>,
+        base: &Path,
+        merged: bool,
+    ) -> Result<usize> {
+        let manager = config_manager();
+        let project = project_path
+            .or(self.project.as_ref())
+            .map_or(Path::new("."), PathBuf::as_path);
+
+        let (label, config) = if merged {
+            ("merged", manager.get_merged_config(Some(project))?)
+        } else {
+            let config = manager.get_project_config(Some(project))?.ok_or_else(|| {
+                anyhow::anyhow!(
+                    "No project configuration found in {}; use --merged to compare the global config",
+                    project.display()
+                )
+            })?;
+            ("project", config)
+        };
+        let (diffs, _) = manager.diff_against_file(base, &config)?;
+
+        if diffs.is_empty() {
+            println!(
+                "No differences found between {} and the {label} configuration.",
+                base.display()
+            );
+            return Ok(0);
+        }
+
+        println!(
+            "Differences from {} ({} total):\n",
+            base.display(),
+            diffs.len()
+        );
+        self.print_diff_groups(
+            &diffs,
+            [
+                "Additions (not in base):",
+                "Removals (missing compared to base):",
+                "Modifications (different from base):",
+            ],
+            None,
+        )?;
+
+        Ok(diffs.len())
+    }
+
+    /// Print diffs grouped into additions, removals and modifications
+    ///
+    /// `headings` name the three groups. Removals of values the global config
+    /// inherits from its `ccm.extends` base are marked when `sources` says so.
+    fn print_diff_groups(
+        &self,
+        diffs: &[ConfigDiff],
+        headings: [&str; 3],
+        sources: Option<&SourceMap>,
+    ) -> Result<()> {
+        let [added_heading, removed_heading, modified_heading] = headings;
 
         // Group diffs by type
         let mut additions = Vec::new();
         let mut removals = Vec::new();
         let mut modifications = Vec::new();
 
-        for diff in &diffs {
+        for diff in diffs {
             match diff {
                 ConfigDiff::Added { .. } => additions.push(diff),
                 ConfigDiff::Removed { .. } => removals.push(diff),
@@ -213,11 +923,11 @@ impl ConfigArgs {
 
         // Display additions (green)
         if !additions.is_empty() {
-            println!("Additions (project-specific):");
+            println!("{added_heading}");
             for diff in additions {
                 if let ConfigDiff::Added { key_path, value } = diff {
                     println!("  + {key_path}");
-                    if matches!(self.output, OutputFormat::Json) {
+                    if self.output != OutputFormat::Table {
                         println!("    {}", serde_json::to_string_pretty(value)?);
                     }
                 }
@@ -227,10 +937,16 @@ impl ConfigArgs {
 
         // Display removals (red)
         if !removals.is_empty() {
-            println!("Removals (missing in project):");
+            println!("{removed_heading}");
             for diff in removals {
                 if let ConfigDiff::Removed { key_path, .. } = diff {
-                    println!("  - {key_path}");
+                    if sources.and_then(|sources| sources.lookup(key_path))
+                        == Some(&ConfigScope::Base)
+                    {
+                        println!("  - {key_path} (from base)");
+                    } else {
+                        println!("  - {key_path}");
+                    }
                 }
             }
             println!();
@@ -238,7 +954,7 @@ impl ConfigArgs {
 
         // Display modifications (yellow)
         if !modifications.is_empty() {
-            println!("Modifications (different values):");
+            println!("{modified_heading}");
             for diff in modifications {
                 if let ConfigDiff::Modified {
                     key_path,
@@ -247,7 +963,7 @@ impl ConfigArgs {
                 } = diff
                 {
                     println!("  ~ {key_path}");
-                    if matches!(self.output, OutputFormat::Json) {
+                    if self.output != OutputFormat::Table {
                         println!("    old: {}", serde_json::to_string_pretty(old_value)?);
                         println!("    new: {}", serde_json::to_string_pretty(new_value)?);
                     }
@@ -256,30 +972,20 @@ impl ConfigArgs {
             println!();
         }
 
-        // Display source summary
-        println!("Source summary:");
-        let mut global_count = 0;
-        let mut project_count = 0;
-        for scope in source_map.sources.values() {
-            match scope {
-                ConfigScope::Global => global_count += 1,
-                ConfigScope::Project => project_count += 1,
-            }
-        }
-        println!("  Values from global: {global_count}");
-        println!("  Values from project: {project_count}");
-
         Ok(())
     }
 
-    /// Export configuration to a file
-    fn cmd_export(&self, output_file: &PathBuf) -> Result<()> {
+    /// Export configuration to a file, or to stdout for `-`
+    ///
+    /// Only the configuration goes to stdout; status messages go to stderr.
+    fn cmd_export(&self, output_file: &FileArg, options: ImportExportOptions) -> Result<()> {
         let backup_dir = get_global_config_path()
             .parent()
             .map(|p| p.join("backups"))
             .unwrap_or_else(|| PathBuf::from(".backups"));
 
-        let manager = ConfigManager::new(&backup_dir);
+        // Exports keep servers for every platform, as configured
+        let manager = config_manager_in(&backup_dir).with_platform(None);
 
         // Get configuration to export
         let config = if let Some(project_path) = &self.project {
@@ -288,42 +994,686 @@ impl ConfigArgs {
             manager.get_merged_config(None)?
         };
 
+        let output_file = match output_file {
+            FileArg::Path(path) => path,
+            FileArg::Stdio => {
+                if options.auto_name {
+                    anyhow::bail!("--auto-name needs an output file or directory, not '-'");
+                }
+                let hash = manager.export_config_to_writer(
+                    &config,
+                    &mut std::io::stdout().lock(),
+                    options,
+                )?;
+                eprintln!("Configuration exported to standard output");
+                eprintln!("Content hash: {hash}");
+                return Ok(());
+            }
+        };
+
         // Export configuration
-        let exported_path = manager.export_config(&config, output_file)?;
+        let exported = manager.export_config_with_options(&config, output_file, options)?;
 
-        println!("Configuration exported to: {}", exported_path.display());
+        if exported.unchanged {
+            println!(
+                "Configuration already exported to: {} (unchanged)",
+                exported.path.display()
+            );
+        } else {
+            println!("Configuration exported to: {}", exported.path.display());
+        }
+        println!("Content hash: {}", exported.content_hash);
 
         Ok(())
     }
 
-    /// Import configuration from a file
-    fn cmd_import(&self, input_file: &PathBuf, validate: bool) -> Result<()> {
+    /// Rewrite a config file in canonical form
+    fn cmd_normalize(&self, file: Option<&Path>, dry_run: bool, skip: &[String]) -> Result<()> {
+        let config_path = match (file, &self.project) {
+            (Some(file), _) => file.to_path_buf(),
+            (None, _) => self.config_path()?,
+        };
+
+        let mut normalizer = Normalizer::new();
+        for rule in skip {
+            normalizer = normalizer.skip(rule)?;
+        }
+
+        if !config_path.exists() {
+            return Err(ConfigError::not_found(&config_path).into());
+        }
+        let original = std::fs::read_to_string(&config_path)
+            .map_err(|e| ConfigError::filesystem("read config file", &config_path, e))?;
+        let normalized = normalizer.normalize_str(&original)?;
+
+        if normalized.canonical == original {
+            println!("{} is already normalized", config_path.display());
+            return Ok(());
+        }
+
+        let verb = if dry_run { "Would apply" } else { "Applied" };
+        println!("{verb} to {}:", config_path.display());
+        for change in &normalized.changes {
+            println!("  - [{}] {}", change.rule, change.description);
+        }
+        println!("  - reformatted to canonical style (sorted keys, 2-space indent)");
+
+        if let Err(e) = validate_config(&normalized.config) {
+            println!("\nNormalized config still fails validation:\n{e}");
+            anyhow::bail!("Configuration still fails validation");
+        }
+
+        println!(
+            "\n{}",
+            TextDiff::from_lines(&original, &normalized.canonical)
+                .unified_diff()
+                .header("original", "normalized")
+        );
+
+        if !dry_run {
+            let backup_dir = config_path
+                .parent()
+                .map(|p| p.join("backups"))
+                .unwrap_or_else(|| PathBuf::from(".backups"));
+            config_manager_in(&backup_dir).write_config_with_options(
+                &config_path,
+                &normalized.config,
+                // Reformatting alone leaves the parsed config unchanged
+                WriteOptions::new().with_force(true),
+            )?;
+            println!("✓ Normalized {}", config_path.display());
+        }
+
+        Ok(())
+    }
+
+    /// Rewrite a config in strict canonical form, optionally toggling
+    /// `canonicalOnWrite`
+    fn cmd_canonicalize(&self, dry_run: bool, enable: bool, disable: bool) -> Result<()> {
+        let (config_path, manager) = self.set_target()?;
+        if !config_path.exists() {
+            return Err(ConfigError::not_found(&config_path).into());
+        }
+        let original = std::fs::read_to_string(&config_path)
+            .map_err(|e| ConfigError::filesystem("read config file", &config_path, e))?;
+        let mut config = manager.read_config(&config_path)?;
+
+        // Edit the raw section, so no other settings get spelled out
+        if enable || disable {
+            let section = config
+                .unknown
+                .entry(SETTINGS_KEY.to_string())
+                .or_insert_with(|| Value::Object(Default::default()));
+            if let Some(section) = section.as_object_mut() {
+                if enable {
+                    section.insert("canonicalOnWrite".to_string(), Value::Bool(true));
+                } else {
+                    section.remove("canonicalOnWrite");
+                }
+                if section.is_empty() {
+                    config.unknown.remove(SETTINGS_KEY);
+                }
+            }
+        }
+
+        let strict = !disable;
+        let rendered = if strict {
+            canonical_json_strict(&config)?
+        } else {
+            canonical_json(&config)?
+        };
+        if rendered == original {
+            println!("{} is already canonical", config_path.display());
+            return Ok(());
+        }
+
+        println!(
+            "{}",
+            TextDiff::from_lines(&original, &rendered)
+                .unified_diff()
+                .header("original", "canonical")
+        );
+
+        if dry_run {
+            println!("Dry run: {} not modified.", config_path.display());
+            return Ok(());
+        }
+
+        manager.write_config_with_options(
+            &config_path,
+            &config,
+            WriteOptions::new().with_force(true).with_canonical(strict),
+        )?;
+        if disable {
+            println!("✓ Restored default formatting of {}", config_path.display());
+        } else {
+            println!("✓ Canonicalized {}", config_path.display());
+        }
+
+        Ok(())
+    }
+
+    /// Move an embedded project config into .claude/config.json
+    fn cmd_migrate_embedded(&self, remove: bool) -> Result<()> {
+        let project_dir = match &self.project {
+            Some(path) => path.clone(),
+            None => std::env::current_dir()?,
+        };
+
+        let (source, config_path) = embedded::migrate_embedded(&project_dir, remove)?;
+
+        println!(
+            "✓ Migrated {} to {}",
+            source.describe(),
+            config_path.display()
+        );
+        if remove {
+            println!(
+                "  Removed the {} from {}",
+                source.kind.section(),
+                source.path.display()
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Archive the global config and backups
+    fn cmd_archive(&self, out: &Path) -> Result<()> {
+        let count = archive::create_archive(out, &get_global_config_path(), &get_backup_dir())?;
+
+        println!("Archived {count} file(s) to: {}", out.display());
+
+        Ok(())
+    }
+
+    /// Restore the global config and backups from an archive
+    fn cmd_unarchive(&self, archive_path: &Path, force: bool) -> Result<()> {
+        let config_path = get_global_config_path();
+        if config_path.exists() && !force {
+            anyhow::bail!(
+                "Global config already exists: {}\nUse --force to overwrite it.",
+                config_path.display()
+            );
+        }
+
+        let count = archive::extract_archive(archive_path, &config_path, &get_backup_dir())?;
+
+        println!("Restored {count} file(s) from: {}", archive_path.display());
+
+        Ok(())
+    }
+
+    /// Edit the config by hand, restoring the previous version if the edit is invalid
+    fn cmd_edit(&self, yes: bool) -> Result<()> {
+        let (config_path, manager) = self.set_target()?;
+
+        if !config_path.exists()
+            && !yes
+            && !confirm(&format!(
+                "{} doesn't exist. Create it?",
+                config_path.display()
+            ))?
+        {
+            println!("Edit cancelled.");
+            return Ok(());
+        }
+
+        let session = edit_session(&manager, &config_path, None)?;
+        match session.finish() {
+            Ok(_) if !session.is_changed()? => {
+                println!("No changes to {}", config_path.display());
+            }
+            Ok(_) => println!("✓ Saved {}", config_path.display()),
+            Err(e) => {
+                println!("The edited config is invalid:\n{e}\n");
+                if yes || confirm("Restore the version from before the edit?")? {
+                    session.restore()?;
+                    println!("✓ Restored {}", config_path.display());
+                } else {
+                    anyhow::bail!(
+                        "{} was left invalid; the previous version is in {}",
+                        config_path.display(),
+                        session.backup().display()
+                    );
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Open the config in the configured editor and validate it afterwards
+    fn cmd_open(&self) -> Result<()> {
+        let editor = editor::configured_editor()?
+            .ok_or_else(|| anyhow::anyhow!("No editor configured: set $EDITOR or $VISUAL"))?;
+
+        let (config_path, manager) = self.set_target()?;
+        let session = edit_session(&manager, &config_path, Some(editor))?;
+        if let Err(e) = session.finish() {
+            println!("{e}");
+            anyhow::bail!("{} fails validation", config_path.display());
+        }
+        println!("✓ {} is valid", config_path.display());
+
+        Ok(())
+    }
+
+    /// Report which projects override, match or inherit a global key
+    ///
+    /// Unreadable project configs are listed as failures instead of aborting.
+    fn cmd_shadows(&self, key: &str, path: Option<&Path>, all: bool) -> Result<()> {
+        let manager = config_manager();
+        let global = manager.get_global_config()?;
+
+        let config_paths: Vec<PathBuf> = if all {
+            let registry = ProjectRegistry::load(get_registry_path())?;
+            registry
+                .roots()
+                .filter(|root| !registry.get(root).is_some_and(|entry| entry.archived))
+                .map(project_config_path)
+                .filter(|config_path| config_path.exists())
+                .collect()
+        } else {
+            let scan_path = path.unwrap_or_else(|| Path::new("."));
+            let projects = bulk::project_scanner(None)?.scan_directory(scan_path)?;
+            bulk::config_paths(&projects, false).cloned().collect()
+        };
+        let configs = manager.read_configs(&config_paths);
+        let reports = find_shadows(&global, &configs.successes, key)?;
+
+        let global_json = serde_json::to_value(&global)?;
+        let global_value = key_path::get(&global_json, key);
+
+        match self.output {
+            OutputFormat::Json | OutputFormat::Yaml => {
+                let failures: Vec<Value> = configs
+                    .failures
+                    .iter()
+                    .map(|(path, error)| {
+                        serde_json::json!({"config_path": path, "error": error.to_string()})
+                    })
+                    .collect();
+                let output = serde_json::json!({
+                    "key": key,
+                    "global": global_value,
+                    "projects": reports,
+                    "failures": failures,
+                });
+                if self.output == OutputFormat::Yaml {
+                    print!("{}", to_yaml_string(&output)?);
+                } else {
+                    println!("{}", serde_json::to_string_pretty(&output)?);
+                }
+            }
+            OutputFormat::Table => {
+                match global_value {
+                    Some(value) => println!("Global {key} = {value}"),
+                    None => println!("Global {key} is not set"),
+                }
+                println!();
+
+                if reports.is_empty() {
+                    println!("No project configs found");
+                }
+                for report in &reports {
+                    let project = report
+                        .config_path
+                        .parent()
+                        .and_then(Path::parent)
+                        .unwrap_or(&report.config_path);
+                    match &report.state {
+                        ShadowState::Overrides { value } => {
+                            println!("  overrides  {} = {value}", project.display())
+                        }
+                        ShadowState::Matches => println!("  matches    {}", project.display()),
+                        ShadowState::Inherits => println!("  inherits   {}", project.display()),
+                    }
+                }
+            }
+        }
+
+        bulk::finish(&configs);
+        Ok(())
+    }
+
+    /// Wait for the config to change, or for a key to reach a value
+    ///
+    /// Returns false if the timeout expired first.
+    fn cmd_wait(
+        &self,
+        key: Option<&str>,
+        until: Option<&str>,
+        options: WaitOptions,
+    ) -> Result<bool> {
+        let config_path = self.config_path()?;
+
+        // The watched value: one key, or the whole config
+        let watched = |config: &ClaudeConfig| -> Option<Value> {
+            let value = serde_json::to_value(config).ok()?;
+            match key {
+                Some(key) => key_path::get_element(&value, key).cloned(),
+                None => Some(value),
+            }
+        };
+
+        let target = until.map(|raw| {
+            serde_json::from_str(raw).unwrap_or_else(|_| Value::String(raw.to_string()))
+        });
+
+        // Without --until, wait for the watched value to differ from now
+        let manager = config_manager();
+        let baseline = match &target {
+            Some(_) => None,
+            None => manager
+                .read_config(&config_path)
+                .ok()
+                .map(|config| watched(&config)),
+        };
+
+        let condition = |config: &ClaudeConfig| {
+            let current = watched(config);
+            match (&target, &baseline) {
+                (Some(target), _) => current.as_ref() == Some(target),
+                (None, Some(baseline)) => &current != baseline,
+                // The file didn't exist (or was unreadable): any readable config counts
+                (None, None) => key.is_none() || current.is_some(),
+            }
+        };
+
+        let outcome = timings::collector().time("wait", || {
+            wait_for_with(&config_path, condition, options, &mut SystemPoller::new())
+        })?;
+
+        match outcome {
+            WaitOutcome::Satisfied(config) => {
+                match key {
+                    Some(key) => println!("{key} = {}", watched(&config).unwrap_or(Value::Null)),
+                    None => println!("Config changed: {}", config_path.display()),
+                }
+                Ok(true)
+            }
+            WaitOutcome::TimedOut => {
+                eprintln!("Timed out waiting for {}", config_path.display());
+                Ok(false)
+            }
+        }
+    }
+
+    /// Import configuration from a file, or from stdin for `-`
+    ///
+    /// With `explicit_format`, `options.format` wins over the file extension.
+    fn cmd_import(
+        &self,
+        input_file: &FileArg,
+        options: ImportExportOptions,
+        explicit_format: bool,
+    ) -> Result<()> {
         let backup_dir = get_global_config_path()
             .parent()
             .map(|p| p.join("backups"))
             .unwrap_or_else(|| PathBuf::from(".backups"));
 
-        let manager = ConfigManager::new(&backup_dir);
+        let manager = config_manager_in(&backup_dir);
 
         // Import configuration
-        let mut options = claude_config_manager_core::ImportExportOptions::default();
-        options.validate = validate;
-
-        let imported_config = manager.import_config_with_options(input_file, options)?;
+        let imported_config = match input_file {
+            FileArg::Path(path) if explicit_format => manager.import_config_as(path, options)?,
+            FileArg::Path(path) => manager.import_config_with_options(path, options)?,
+            FileArg::Stdio => {
+                manager.import_config_from_reader(&mut std::io::stdin().lock(), options)?
+            }
+        };
 
         // Determine target path
-        let target_path = if let Some(project_path) = &self.project {
-            project_path.join(".claude").join("config.json")
-        } else {
-            get_global_config_path()
-        };
+        let target_path = self.config_path()?;
 
-        // Write imported configuration
-        manager.write_config_with_backup(&target_path, &imported_config)?;
+        // Write imported configuration, snapshotting the replaced one
+        manager.write_config_with_options(
+            &target_path,
+            &imported_config,
+            WriteOptions::default().with_risk(RiskyOperation::Import),
+        )?;
 
-        println!("Configuration imported from: {}", input_file.display());
+        match input_file {
+            FileArg::Path(path) => {
+                println!("Configuration imported from: {}", path.display())
+            }
+            FileArg::Stdio => println!("Configuration imported from standard input"),
+        }
         println!("Written to: {}", target_path.display());
 
         Ok(())
     }
+
+    /// List the base, global and project config files, lowest precedence first
+    fn cmd_which(&self) -> Result<()> {
+        let manager = config_manager();
+
+        let global_path = get_global_config_path();
+        let chain = manager.base_chain(&manager.get_global_config()?)?;
+        let project_path = find_project_config(self.project.as_deref());
+
+        println!("Configuration files, lowest precedence first:");
+        for link in chain.links.iter().rev() {
+            if link.is_remote() {
+                println!(
+                    "  base     {} (cached at {})",
+                    link.source,
+                    link.path.display()
+                );
+            } else {
+                println!("  base     {}", link.path.display());
+            }
+        }
+        let missing = |path: &Path| if path.exists() { "" } else { " (missing)" };
+        println!(
+            "  global   {}{}",
+            global_path.display(),
+            missing(&global_path)
+        );
+        if let Some(path) = &project_path {
+            println!("  project  {}", path.display());
+        }
+
+        for warning in &chain.warnings {
+            println!("Warning: {warning}");
+        }
+        Ok(())
+    }
+
+    /// Validate the config, optionally against a bundled schema version
+    fn cmd_validate(&self, schema_version: Option<u32>) -> Result<()> {
+        let config_path = self.config_path()?;
+        let manager = config_manager();
+
+        let config = manager.read_config(&config_path)?;
+        validate_config(&config)?;
+        println!("✓ {} is valid", config_path.display());
+        for warning in platform_warnings(&config)
+            .into_iter()
+            .chain(blank_value_warnings(&config))
+            .chain(env_key_warnings(&config))
+        {
+            println!("Warning: {warning}");
+        }
+
+        let Some(version) = schema_version else {
+            return Ok(());
+        };
+        let raw: Value = serde_json::from_str(&std::fs::read_to_string(&config_path)?)?;
+        let violations = validate_against_schema_version(&raw, version)?;
+        if violations.is_empty() {
+            println!("✓ Matches schema version {version}");
+            return Ok(());
+        }
+
+        println!("Schema version {version} violations:");
+        for violation in &violations {
+            println!("  {violation}");
+        }
+        anyhow::bail!(
+            "{} does not match schema version {version} ({} violation(s))",
+            config_path.display(),
+            violations.len()
+        )
+    }
+
+    /// Validate the merged config, warning about values the merge discards
+    fn cmd_validate_merged(&self) -> Result<()> {
+        let manager = config_manager();
+
+        let (config, notes) = manager.get_merged_config_with_notes(self.project.as_deref())?;
+        validate_config(&config)?;
+        println!("✓ Merged configuration is valid");
+
+        for note in &notes {
+            println!("Warning: {}: {}", note.key_path, note.message);
+            println!("  {}", note.suggestion);
+        }
+        Ok(())
+    }
+
+    /// Automatically fix trivial configuration issues
+    fn cmd_fix(&self, dry_run: bool) -> Result<()> {
+        // Determine which config file to fix
+        let config_path = self.config_path()?;
+
+        let backup_dir = config_path
+            .parent()
+            .map(|p| p.join("backups"))
+            .unwrap_or_else(|| PathBuf::from(".backups"));
+
+        let manager = config_manager_in(&backup_dir);
+
+        let mut config = manager.read_config(&config_path)?;
+        let changes = config.validate_and_fix();
+
+        if changes.is_empty() {
+            println!("No fixable issues found in {}", config_path.display());
+        } else {
+            let verb = if dry_run { "Would fix" } else { "Fixed" };
+            println!(
+                "{verb} {} issue(s) in {}:",
+                changes.len(),
+                config_path.display()
+            );
+            for change in &changes {
+                println!("  - {change}");
+            }
+        }
+
+        // Report anything that still needs manual attention
+        if let Err(e) = validate_config(&config) {
+            println!("\nRemaining issues must be fixed manually:\n{e}");
+            anyhow::bail!("Configuration still fails validation");
+        }
+
+        if !dry_run && !changes.is_empty() {
+            manager.write_config_with_backup(&config_path, &config)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Print a hint (once per run) if any loaded config file is large
+fn print_large_config_hint(project: Option<&Path>) {
+    let project_config = match project {
+        Some(path) => Some(project_config_path(path)),
+        None => find_project_config(None),
+    };
+
+    let large: Vec<PathBuf> = std::iter::once(get_global_config_path())
+        .chain(project_config)
+        .filter(|path| {
+            std::fs::metadata(path).is_ok_and(|metadata| limits::is_large_config(metadata.len()))
+        })
+        .collect();
+
+    if !large.is_empty() {
+        let files: Vec<String> = large.iter().map(|p| p.display().to_string()).collect();
+        eprintln!(
+            "Hint: config files over {} MB load slowly ({}). Consider moving embedded data out of the config.",
+            limits::LARGE_CONFIG_FILE_SIZE / (1024 * 1024),
+            files.join(", ")
+        );
+    }
+}
+
+/// Parse a duration like "500ms", "30s", "5m" or "1h" (bare numbers are seconds)
+fn parse_duration(raw: &str) -> std::result::Result<Duration, String> {
+    let raw = raw.trim();
+    let split = raw
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(raw.len());
+    let (number, unit) = raw.split_at(split);
+    let number: f64 = number
+        .parse()
+        .map_err(|_| format!("invalid duration '{raw}' (expected e.g. 30s, 5m, 500ms)"))?;
+
+    let seconds = match unit.trim() {
+        "ms" => number / 1000.0,
+        "" | "s" => number,
+        "m" => number * 60.0,
+        "h" => number * 3600.0,
+        other => {
+            return Err(format!(
+                "unknown duration unit '{other}' (use ms, s, m or h)"
+            ))
+        }
+    };
+
+    Duration::try_from_secs_f64(seconds).map_err(|_| format!("duration '{raw}' is out of range"))
+}
+
+/// Back up `config_path`, creating it if missing, and open it in an editor
+///
+/// Uses `editor` if given, otherwise the configured or platform default
+/// editor (see [`editor::edit_file`]). Returns once the editor exits, with
+/// the session ready to validate the edit.
+fn edit_session(
+    manager: &ConfigManager,
+    config_path: &Path,
+    editor: Option<(String, Vec<String>)>,
+) -> Result<EditSession> {
+    let session = EditSession::begin(manager, config_path)?;
+    if session.was_created() {
+        println!("Created {}", config_path.display());
+    }
+
+    match editor {
+        Some(editor) => editor::run_editor(editor, session.path())?,
+        None => editor::edit_file(session.path())?,
+    }
+    Ok(session)
+}
+
+/// Ask a yes/no question on stdin (default: no)
+fn confirm(question: &str) -> Result<bool> {
+    print!("{question} [y/N] ");
+    std::io::stdout().flush()?;
+
+    let mut input = String::new();
+    std::io::stdin().read_line(&mut input)?;
+
+    let input = input.trim().to_lowercase();
+    Ok(input == "y" || input == "yes")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("30s"), Ok(Duration::from_secs(30)));
+        assert_eq!(parse_duration("30"), Ok(Duration::from_secs(30)));
+        assert_eq!(parse_duration("5m"), Ok(Duration::from_secs(300)));
+        assert_eq!(parse_duration("1h"), Ok(Duration::from_secs(3600)));
+        assert_eq!(parse_duration("250ms"), Ok(Duration::from_millis(250)));
+        assert_eq!(parse_duration("1.5s"), Ok(Duration::from_millis(1500)));
+        assert!(parse_duration("soon").is_err());
+        assert!(parse_duration("3d").is_err());
+    }
 }
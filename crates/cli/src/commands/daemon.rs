@@ -0,0 +1,77 @@
+//! Daemon command implementation
+//!
+//! Runs the config daemon in the foreground, or talks to a running one.
+//! The daemon listens on a Unix domain socket, or a named pipe on Windows.
+
+use crate::managers::config_manager;
+use anyhow::Result;
+use clap::{Parser, Subcommand};
+use claude_config_manager_core::daemon::{serve, Client, Handler};
+use claude_config_manager_core::paths::get_daemon_socket_path;
+use claude_config_manager_core::CancellationToken;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Config daemon for editor integrations
+#[derive(Parser, Debug)]
+pub struct DaemonArgs {
+    /// Socket path (default: <config dir>/daemon/ccm.sock; on Windows a
+    /// named pipe, \\.\pipe\ccm-<hash>)
+    #[arg(long, global = true)]
+    socket: Option<PathBuf>,
+
+    /// Stop after this many seconds without requests (0: run until stopped)
+    #[arg(long, default_value_t = 1800)]
+    idle_timeout: u64,
+
+    #[command(subcommand)]
+    command: Option<DaemonCommand>,
+}
+
+/// Commands for a running daemon
+#[derive(Subcommand, Debug)]
+pub enum DaemonCommand {
+    /// Show whether a daemon is running
+    Status,
+    /// Ask a running daemon to stop
+    Stop,
+}
+
+impl DaemonArgs {
+    /// Execute the daemon command
+    pub fn execute(&self) -> Result<()> {
+        let socket = self.socket.clone().unwrap_or_else(get_daemon_socket_path);
+
+        match self.command {
+            None => {
+                let handler = Handler::new(config_manager(), CancellationToken::new());
+                let idle_timeout =
+                    (self.idle_timeout > 0).then(|| Duration::from_secs(self.idle_timeout));
+                println!("Daemon listening on {}", socket.display());
+                serve(handler, &socket, idle_timeout)?;
+                println!("Daemon stopped.");
+            }
+            Some(DaemonCommand::Status) => match Client::connect(&socket) {
+                Ok(mut client) => {
+                    let status = client.call("status", serde_json::json!({}))?;
+                    println!("Daemon running on {}", socket.display());
+                    println!("  pid:              {}", status["pid"]);
+                    println!("  version:          {}", status["ccmVersion"]);
+                    println!("  protocol version: {}", status["protocolVersion"]);
+                    println!("  uptime:           {}s", status["uptimeSecs"]);
+                    println!("  cached files:     {}", status["cachedFiles"]);
+                }
+                Err(_) => println!("No daemon running on {}", socket.display()),
+            },
+            Some(DaemonCommand::Stop) => match Client::connect(&socket) {
+                Ok(mut client) => {
+                    client.call("shutdown", serde_json::json!({}))?;
+                    println!("Daemon on {} is stopping.", socket.display());
+                }
+                Err(_) => println!("No daemon running on {}", socket.display()),
+            },
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,68 @@
+//! Doctor command implementation
+//!
+//! Runs the installation self-checks and optionally repairs what it safely can
+
+use crate::managers::config_manager;
+use anyhow::Result;
+use clap::Parser;
+use claude_config_manager_core::{
+    doctor::run_self_checks,
+    paths::{find_project_config, get_global_config_path},
+    CheckStatus, DoctorOptions,
+};
+use std::path::PathBuf;
+
+/// Installation self-checks
+#[derive(Parser, Debug)]
+pub struct DoctorArgs {
+    /// Also check this project's config (path or @alias)
+    #[arg(short, long, value_parser = crate::project_arg::parse_project)]
+    project: Option<PathBuf>,
+
+    /// Also check config files against the bundled JSON Schema
+    #[arg(long)]
+    strict: bool,
+
+    /// Fix problems that can be repaired safely (e.g. create a missing global
+    /// config, tighten backup permissions)
+    #[arg(long)]
+    fix: bool,
+}
+
+impl DoctorArgs {
+    /// Execute the doctor command
+    pub fn execute(&self) -> Result<()> {
+        let project_config = self
+            .project
+            .as_deref()
+            .and_then(|p| find_project_config(Some(p)));
+        let options = DoctorOptions::new()
+            .with_strict(self.strict)
+            .with_fix(self.fix);
+
+        if self.fix && !get_global_config_path().exists() {
+            let path = config_manager().ensure_global_config()?;
+            println!("✓ Created empty global config {}", path.display());
+        }
+
+        let report = run_self_checks(project_config.as_deref(), options);
+
+        for check in &report.checks {
+            let marker = match check.status {
+                CheckStatus::Ok => "✓",
+                CheckStatus::Warning => "!",
+                CheckStatus::Error => "✗",
+            };
+            println!("{marker} {}: {}", check.name, check.message);
+        }
+
+        if report.has_errors() {
+            if !get_global_config_path().exists() {
+                println!("Run `ccm doctor --fix` to create an empty global config.");
+            }
+            anyhow::bail!("doctor found problems");
+        }
+
+        Ok(())
+    }
+}
@@ -1,12 +1,27 @@
 //! History command implementation
 //!
-//! Provides backup listing and restoration functionality
-
+//! Provides backup listing, inspection and restoration functionality, and
+//! snapshots of whole project `.claude` directories
+
+use crate::managers::{backup_manager, config_manager_in};
+use crate::output::{
+    backup_record, format_json, format_porcelain, format_table, format_yaml, ListFormat,
+    OutputFormat, TableOptions,
+};
 use anyhow::Result;
 use camino::Utf8PathBuf;
 use clap::{Parser, Subcommand};
-use claude_config_manager_core::{backup::BackupManager, paths::get_backup_dir};
-use std::path::PathBuf;
+use claude_config_manager_core::{
+    backup::{
+        execute_plan, read_backup, BackupManager, Decision, RetentionPolicy, RiskyOperation,
+        SnapshotOptions,
+    },
+    format_timestamp,
+    paths::{get_backup_dir, project_config_path},
+    types::ConfigDiff,
+    ConfigManager, RestoreKeysOptions,
+};
+use std::path::{Path, PathBuf};
 
 /// History management commands
 #[derive(Parser, Debug)]
@@ -28,24 +43,124 @@ pub enum HistoryCommand {
         #[arg(short, long)]
         limit: Option<usize>,
 
-        /// Project path (for project-specific backups)
-        #[arg(short, long)]
+        /// Project path or @alias (for project-specific backups)
+        #[arg(short, long, value_parser = crate::project_arg::parse_utf8_project)]
         project: Option<Utf8PathBuf>,
 
         /// Show relative timestamps (e.g., "2 hours ago")
         #[arg(short = 'r', long)]
         relative: bool,
+
+        /// Print backups as JSON (timestamps in RFC 3339)
+        #[arg(long)]
+        json: bool,
+
+        /// Print the stable, tab-separated porcelain v1 format for scripts
+        #[arg(long, conflicts_with_all = ["json", "verbose", "relative"])]
+        porcelain: bool,
+    },
+
+    /// Print the contents of a backup without restoring it
+    Show {
+        /// Backup file path (`.gz` backups are decompressed)
+        backup: PathBuf,
+
+        /// Output format
+        #[arg(short, long, default_value = "table")]
+        output: OutputFormat,
+
+        /// In table output, list long arrays and deep values in full
+        #[arg(long)]
+        full: bool,
     },
 
     /// Restore a backup
     Restore {
         /// Backup file path or index (from list command)
-        backup: String,
+        #[arg(required_unless_present = "last_risk")]
+        backup: Option<String>,
+
+        /// Restore the newest risk snapshot (taken automatically before
+        /// imports, restores, `mcp disable-all` and large changes)
+        #[arg(long, conflicts_with = "backup")]
+        last_risk: bool,
 
-        /// Project path (for project-specific backups)
+        /// Project path or @alias (for project-specific backups)
+        #[arg(short, long, value_parser = crate::project_arg::parse_utf8_project)]
+        project: Option<Utf8PathBuf>,
+
+        /// Don't ask for confirmation before restoring
         #[arg(short, long)]
+        yes: bool,
+
+        /// Restore only these key paths (comma-separated, e.g. mcpServers.fetch,customInstructions)
+        #[arg(long, value_delimiter = ',')]
+        only: Vec<String>,
+
+        /// Show the changes --only would make without writing them
+        #[arg(long, requires = "only")]
+        dry_run: bool,
+
+        /// Remove --only keys that are missing from the backup
+        #[arg(long, requires = "only")]
+        allow_remove: bool,
+    },
+
+    /// Delete old backups according to a retention policy
+    Prune {
+        /// Project path or @alias (for project-specific backups)
+        #[arg(short, long, value_parser = crate::project_arg::parse_utf8_project)]
         project: Option<Utf8PathBuf>,
 
+        /// Number of backups to keep (default: 10)
+        #[arg(short, long)]
+        keep: Option<usize>,
+
+        /// Delete backups older than this many days
+        #[arg(long)]
+        max_age_days: Option<i64>,
+
+        /// Maximum total size of kept backups, in bytes
+        #[arg(long)]
+        max_size: Option<u64>,
+
+        /// Never delete this backup (file path or name; can be repeated)
+        #[arg(long)]
+        protect: Vec<String>,
+
+        /// Show what would be deleted without deleting anything
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Don't ask for confirmation before deleting
+        #[arg(short, long)]
+        yes: bool,
+    },
+
+    /// Snapshot a project's whole .claude directory as a .tar.gz
+    SnapshotDir {
+        /// Project path or @alias
+        #[arg(short, long, value_parser = crate::project_arg::parse_utf8_project)]
+        project: Utf8PathBuf,
+
+        /// Refuse to archive more than this many bytes (default: 50 MiB)
+        #[arg(long)]
+        max_size: Option<u64>,
+
+        /// Also skip directories with this name (can be repeated)
+        #[arg(long)]
+        exclude: Vec<String>,
+    },
+
+    /// Restore a project's .claude directory from a snapshot
+    RestoreDir {
+        /// Snapshot file path or index (newest first)
+        snapshot: String,
+
+        /// Project path or @alias
+        #[arg(short, long, value_parser = crate::project_arg::parse_utf8_project)]
+        project: Utf8PathBuf,
+
         /// Don't ask for confirmation before restoring
         #[arg(short, long)]
         yes: bool,
@@ -68,12 +183,96 @@ impl HistoryCommand {
                 limit,
                 project,
                 relative,
-            } => self.list_backups(*verbose, *limit, project.as_deref(), *relative),
+                json,
+                porcelain,
+            } => self.list_backups(
+                *verbose,
+                *limit,
+                project.as_deref(),
+                *relative,
+                ListFormat::from_flags(*json, *porcelain),
+            ),
+            HistoryCommand::Show {
+                backup,
+                output,
+                full,
+            } => {
+                let config = read_backup(backup)?;
+                match output {
+                    OutputFormat::Json => format_json(&config, None),
+                    OutputFormat::Yaml => format_yaml(&config, None),
+                    OutputFormat::Table => {
+                        format_table(&config, None, TableOptions { full: *full })
+                    }
+                }
+            }
             HistoryCommand::Restore {
                 backup,
+                last_risk,
                 project,
                 yes,
-            } => self.restore_backup(backup, project.as_deref(), *yes),
+                only,
+                dry_run,
+                allow_remove,
+            } => {
+                let options = RestoreKeysOptions::new()
+                    .with_dry_run(*dry_run)
+                    .with_allow_remove(*allow_remove);
+                let backup = match backup {
+                    Some(backup) => BackupSpec::Given(backup),
+                    None if *last_risk => BackupSpec::LastRisk,
+                    None => unreachable!("clap requires a backup or --last-risk"),
+                };
+                self.restore_backup(backup, project.as_deref(), *yes, only, options)
+            }
+            HistoryCommand::Prune {
+                project,
+                keep,
+                max_age_days,
+                max_size,
+                protect,
+                dry_run,
+                yes,
+            } => {
+                let backup_dir = backup_dir_for(project.as_deref());
+                let manager =
+                    BackupManager::new(&backup_dir, keep.or(crate::backup_retention::get()));
+                let original_file = original_file_for(&backup_dir, project.as_deref());
+                let mut policy = match config_manager_in(&backup_dir).read_config(&original_file) {
+                    Ok(config) => manager.retention_policy_for(&config),
+                    Err(_) => manager.retention_policy(),
+                };
+                if let Some(days) = max_age_days {
+                    policy = policy.with_max_age_days(*days)?;
+                }
+                if let Some(max_size) = max_size {
+                    policy = policy.with_max_total_size(*max_size);
+                }
+                for path in protect {
+                    policy = policy.protect(resolve_backup_path(&backup_dir, path));
+                }
+
+                self.prune_backups(&manager, project.as_deref(), &policy, *dry_run, *yes)
+            }
+            HistoryCommand::SnapshotDir {
+                project,
+                max_size,
+                exclude,
+            } => {
+                let mut options = SnapshotOptions::new();
+                if let Some(max_size) = max_size {
+                    options = options.with_max_size(*max_size);
+                }
+                for name in exclude {
+                    options = options.exclude(name);
+                }
+                self.snapshot_dir(project, &options)
+            }
+            HistoryCommand::RestoreDir {
+                snapshot,
+                project,
+                yes,
+            } => self.restore_dir(snapshot, project, *yes),
         }
     }
 
@@ -84,31 +283,34 @@ impl HistoryCommand {
         limit: Option<usize>,
         project_path: Option<&camino::Utf8Path>,
         relative: bool,
+        format: ListFormat,
     ) -> Result<()> {
-        // Determine backup directory
-        let backup_dir = if let Some(project) = project_path {
-            get_backup_dir().join(project.join(".claude"))
-        } else {
-            get_backup_dir()
-        };
-
-        let manager = BackupManager::new(&backup_dir, None);
-
-        // Determine the original config file path
-        let original_file: PathBuf = if let Some(project) = project_path {
-            project
-                .join(".claude")
-                .join("config.json")
-                .into_std_path_buf()
-        } else {
-            // Global config is in parent of backup dir
-            backup_dir
-                .parent()
-                .unwrap_or(&backup_dir)
-                .join("config.json")
-        };
+        let backup_dir = backup_dir_for(project_path);
+        let manager = backup_manager(&backup_dir);
+        let original_file = original_file_for(&backup_dir, project_path);
 
         let backups = manager.list_backups(original_file.as_ref())?;
+        let shown = &backups[..limit.unwrap_or(backups.len()).min(backups.len())];
+
+        match format {
+            ListFormat::Json => {
+                println!("{}", serde_json::to_string_pretty(shown)?);
+                return Ok(());
+            }
+            ListFormat::Porcelain => {
+                print!(
+                    "{}",
+                    format_porcelain(
+                        shown
+                            .iter()
+                            .enumerate()
+                            .map(|(index, backup)| backup_record(index, backup))
+                    )
+                );
+                return Ok(());
+            }
+            ListFormat::Human => {}
+        }
 
         if backups.is_empty() {
             println!("No backups found.");
@@ -132,7 +334,14 @@ impl HistoryCommand {
 
         for (index, backup) in backups_to_show.iter().enumerate() {
             // Print index for easy reference
-            println!("  [{}]  {}", index, backup_path_display(&backup.path));
+            match &backup.tag {
+                Some(tag) => println!(
+                    "  [{}]  {}  ({tag})",
+                    index,
+                    backup_path_display(&backup.path)
+                ),
+                None => println!("  [{}]  {}", index, backup_path_display(&backup.path)),
+            }
 
             if verbose {
                 if relative {
@@ -165,49 +374,44 @@ impl HistoryCommand {
     /// Restore a backup
     fn restore_backup(
         &self,
-        backup_spec: &str,
+        backup_spec: BackupSpec,
         project_path: Option<&camino::Utf8Path>,
         yes: bool,
+        only: &[String],
+        options: RestoreKeysOptions,
     ) -> Result<()> {
-        // Determine backup directory
-        let backup_dir = if let Some(project) = project_path {
-            get_backup_dir().join(project.join(".claude"))
-        } else {
-            get_backup_dir()
-        };
-
-        let manager = BackupManager::new(&backup_dir, None);
-
-        // Determine the original config file path
-        let original_file: PathBuf = if let Some(project) = project_path {
-            project
-                .join(".claude")
-                .join("config.json")
-                .into_std_path_buf()
-        } else {
-            backup_dir
-                .parent()
-                .unwrap_or(&backup_dir)
-                .join("config.json")
-        };
-
-        // Parse backup_spec as either index or path
-        let backup_path = if let Ok(index) = backup_spec.parse::<usize>() {
-            // It's an index - list backups and get the one at this index
-            let backups = manager.list_backups(original_file.as_ref())?;
-
-            if index >= backups.len() {
-                anyhow::bail!(
-                    "Invalid backup index: {}. Only {} backups available.",
-                    index,
-                    backups.len()
-                );
-            }
-
-            std::path::PathBuf::from(&backups[index].path)
-        } else {
-            // It's a path - use it directly
-            std::path::PathBuf::from(backup_spec)
+        let backup_dir = backup_dir_for(project_path);
+        let manager = backup_manager(&backup_dir);
+        let original_file = original_file_for(&backup_dir, project_path);
+
+        let backup_path = match backup_spec {
+            BackupSpec::LastRisk => manager
+                .latest_risk_snapshot(original_file.as_ref())?
+                .map(|backup| PathBuf::from(backup.path))
+                .ok_or_else(|| {
+                    anyhow::anyhow!(
+                        "No risk snapshots of {} found",
+                        original_file.as_path().display()
+                    )
+                })?,
+            BackupSpec::Given(spec) => match spec.parse::<usize>() {
+                // It's an index - list backups and get the one at this index
+                Ok(index) => {
+                    let backups = manager.list_backups(original_file.as_ref())?;
+
+                    if index >= backups.len() {
+                        anyhow::bail!(
+                            "Invalid backup index: {}. Only {} backups available.",
+                            index,
+                            backups.len()
+                        );
+                    }
+
+                    PathBuf::from(&backups[index].path)
+                }
+                // It's a path - use it directly
+                Err(_) => PathBuf::from(spec),
+            },
         };
 
         // Verify backup exists
@@ -215,6 +419,11 @@ impl HistoryCommand {
             anyhow::bail!("Backup not found: {}", backup_path.display());
         }
 
+        if !only.is_empty() {
+            let manager = config_manager_in(&backup_dir);
+            return restore_keys(&manager, &backup_path, &original_file, only, options, yes);
+        }
+
         // Show what will be restored
         println!("Backup to restore: {}", backup_path.display());
         println!("Target file: {}", original_file.as_path().display());
@@ -236,7 +445,10 @@ impl HistoryCommand {
             }
         }
 
-        // Restore the backup
+        // Keep the current state restorable, then restore the backup
+        if original_file.exists() {
+            manager.create_risk_snapshot(&original_file, RiskyOperation::Restore)?;
+        }
         let restored_path = manager.restore_backup(&backup_path)?;
 
         println!(
@@ -246,6 +458,268 @@ impl HistoryCommand {
 
         Ok(())
     }
+
+    /// Snapshot a project's .claude directory
+    fn snapshot_dir(
+        &self,
+        project_path: &camino::Utf8Path,
+        options: &SnapshotOptions,
+    ) -> Result<()> {
+        let manager = backup_manager(backup_dir_for(Some(project_path)));
+        let claude_dir = project_path.join(".claude");
+
+        let snapshot = manager.create_dir_backup_with_options(claude_dir.as_ref(), options)?;
+
+        println!("✓ Snapshot created: {}", snapshot.display());
+
+        Ok(())
+    }
+
+    /// Restore a project's .claude directory from a snapshot
+    fn restore_dir(
+        &self,
+        snapshot_spec: &str,
+        project_path: &camino::Utf8Path,
+        yes: bool,
+    ) -> Result<()> {
+        let manager = backup_manager(backup_dir_for(Some(project_path)));
+        let claude_dir = project_path.join(".claude").into_std_path_buf();
+
+        let snapshot_path = if let Ok(index) = snapshot_spec.parse::<usize>() {
+            let snapshots = manager.list_dir_backups(&claude_dir)?;
+            let Some(snapshot) = snapshots.get(index) else {
+                anyhow::bail!(
+                    "Invalid snapshot index: {}. Only {} snapshots available.",
+                    index,
+                    snapshots.len()
+                );
+            };
+            PathBuf::from(&snapshot.path)
+        } else {
+            PathBuf::from(snapshot_spec)
+        };
+
+        if !snapshot_path.exists() {
+            anyhow::bail!("Snapshot not found: {}", snapshot_path.display());
+        }
+
+        println!("Snapshot to restore: {}", snapshot_path.display());
+        println!("Target directory: {}", claude_dir.display());
+        println!();
+
+        if !yes {
+            print!("Are you sure you want to restore this snapshot? [y/N] ");
+            use std::io::Write;
+            std::io::stdout().flush()?;
+
+            let mut input = String::new();
+            std::io::stdin().read_line(&mut input)?;
+
+            let input = input.trim().to_lowercase();
+            if input != "y" && input != "yes" {
+                println!("Restore cancelled.");
+                return Ok(());
+            }
+        }
+
+        let restored = manager.restore_dir_backup(&snapshot_path, &claude_dir)?;
+
+        if let Some(safety) = &restored.safety_snapshot {
+            println!(
+                "Safety snapshot of the previous state: {}",
+                safety.display()
+            );
+        }
+        println!(
+            "✓ Restored {} file(s) into {}",
+            restored.files,
+            claude_dir.display()
+        );
+
+        Ok(())
+    }
+
+    /// Prune backups, or preview what pruning would delete
+    fn prune_backups(
+        &self,
+        manager: &BackupManager,
+        project_path: Option<&camino::Utf8Path>,
+        policy: &RetentionPolicy,
+        dry_run: bool,
+        yes: bool,
+    ) -> Result<()> {
+        let original_file = original_file_for(manager.backup_dir(), project_path);
+        let plan = manager.cleanup_plan(&original_file, policy)?;
+
+        if plan.is_empty() {
+            println!("No backups found.");
+            return Ok(());
+        }
+
+        println!("Retention plan for {}:\n", original_file.display());
+        for (backup, decision) in &plan {
+            let (action, reason) = match decision {
+                Decision::Keep(reason) => ("KEEP", reason.to_string()),
+                Decision::Delete(reason) => ("DELETE", reason.to_string()),
+            };
+            println!(
+                "  {:<7} {}  ({}, {} bytes, {})",
+                action,
+                backup_path_display(&backup.path),
+                format_timestamp(&backup.created_at),
+                backup.size,
+                reason
+            );
+        }
+
+        let delete_count = plan.iter().filter(|(_, d)| d.is_delete()).count();
+        println!();
+
+        if dry_run {
+            println!("Dry run: {delete_count} backup(s) would be deleted.");
+            return Ok(());
+        }
+
+        if delete_count == 0 {
+            println!("Nothing to prune.");
+            return Ok(());
+        }
+
+        if !yes {
+            print!("Delete {delete_count} backup(s)? [y/N] ");
+            use std::io::Write;
+            std::io::stdout().flush()?;
+
+            let mut input = String::new();
+            std::io::stdin().read_line(&mut input)?;
+
+            let input = input.trim().to_lowercase();
+            if input != "y" && input != "yes" {
+                println!("Prune cancelled.");
+                return Ok(());
+            }
+        }
+
+        let removed = execute_plan(&plan)?;
+        println!("✓ Removed {removed} backup(s)");
+
+        Ok(())
+    }
+}
+
+/// Backup picked by `history restore`
+enum BackupSpec<'a> {
+    /// Index (from `history list`) or path
+    Given(&'a str),
+    /// Newest risk snapshot
+    LastRisk,
+}
+
+/// Restore selected keys from a backup, showing the changes first
+fn restore_keys(
+    manager: &ConfigManager,
+    backup_path: &Path,
+    target: &Path,
+    keys: &[String],
+    options: RestoreKeysOptions,
+    yes: bool,
+) -> Result<()> {
+    println!("Backup: {}", backup_path.display());
+    println!("Target file: {}", target.display());
+    println!("Keys: {}", keys.join(", "));
+    println!();
+
+    let preview =
+        manager.restore_keys_with_options(backup_path, target, keys, options.with_dry_run(true))?;
+
+    for key in &preview.missing {
+        if options.allow_remove {
+            println!("Not in backup (will be removed): {key}");
+        } else {
+            println!("Not in backup (kept; use --allow-remove to remove): {key}");
+        }
+    }
+
+    if preview.diffs.is_empty() {
+        println!("Nothing to restore: the selected keys already match the backup.");
+        return Ok(());
+    }
+
+    println!("Changes ({}):", preview.diffs.len());
+    for diff in &preview.diffs {
+        match diff {
+            ConfigDiff::Added { key_path, .. } => println!("  + {key_path}"),
+            ConfigDiff::Removed { key_path, .. } => println!("  - {key_path}"),
+            ConfigDiff::Modified { key_path, .. } => println!("  ~ {key_path}"),
+        }
+    }
+    println!();
+
+    if options.dry_run {
+        println!("Dry run: no changes written.");
+        return Ok(());
+    }
+
+    if !yes {
+        print!("Apply these changes? [y/N] ");
+        use std::io::Write;
+        std::io::stdout().flush()?;
+
+        let mut input = String::new();
+        std::io::stdin().read_line(&mut input)?;
+
+        let input = input.trim().to_lowercase();
+        if input != "y" && input != "yes" {
+            println!("Restore cancelled.");
+            return Ok(());
+        }
+    }
+
+    if target.exists() {
+        manager
+            .backup_manager()
+            .create_risk_snapshot(target, RiskyOperation::Restore)?;
+    }
+    let restored = manager.restore_keys_with_options(backup_path, target, keys, options)?;
+    println!(
+        "✓ Restored {} key(s) from backup ({} change(s))",
+        keys.len() - restored.missing.len(),
+        restored.diffs.len()
+    );
+
+    Ok(())
+}
+
+/// Backup directory for the global config or a project
+fn backup_dir_for(project_path: Option<&camino::Utf8Path>) -> PathBuf {
+    if let Some(project) = project_path {
+        get_backup_dir().join(project.join(".claude"))
+    } else {
+        get_backup_dir()
+    }
+}
+
+/// Original config file whose backups live in `backup_dir`
+fn original_file_for(backup_dir: &Path, project_path: Option<&camino::Utf8Path>) -> PathBuf {
+    if let Some(project) = project_path {
+        project_config_path(project.as_std_path())
+    } else {
+        // Global config is in parent of backup dir
+        backup_dir
+            .parent()
+            .unwrap_or(backup_dir)
+            .join("config.json")
+    }
+}
+
+/// Resolve a backup given as a file name to its path in the backup directory
+fn resolve_backup_path(backup_dir: &Path, backup: &str) -> String {
+    let path = Path::new(backup);
+    if path.components().count() == 1 {
+        backup_dir.join(path).to_string_lossy().to_string()
+    } else {
+        backup.to_string()
+    }
 }
 
 /// Format backup path for display (shorten if needed)
@@ -260,12 +734,6 @@ fn backup_path_display(path: &str) -> String {
     }
 }
 
-/// Format timestamp for display
-fn format_timestamp(dt: &chrono::DateTime<chrono::Utc>) -> String {
-    // Format as: 2025-01-20 14:30:45
-    dt.format("%Y-%m-%d %H:%M:%S UTC").to_string()
-}
-
 /// Format relative time (e.g., "2 hours ago")
 fn format_relative_time(dt: &chrono::DateTime<chrono::Utc>) -> String {
     let now = chrono::Utc::now();
@@ -0,0 +1,138 @@
+//! Instructions command implementation
+//!
+//! Edits `customInstructions` by position, through the same indexed key
+//! paths as `config set customInstructions[N]`
+
+use crate::key_path::{insert_value_by_path, remove_index_by_path, set_value_by_path, SetMode};
+use crate::managers::{config_manager, config_manager_in};
+use anyhow::Result;
+use clap::{Parser, Subcommand};
+use claude_config_manager_core::{ClaudeConfig, ConfigManager, ConfigScope};
+use std::path::PathBuf;
+
+/// Key path of the instructions array
+const FIELD: &str = "customInstructions";
+
+/// Custom instructions commands
+#[derive(Parser, Debug)]
+pub struct InstructionsArgs {
+    /// Project path or @alias (default: the global config)
+    #[arg(short, long, value_parser = crate::project_arg::parse_project)]
+    project: Option<PathBuf>,
+
+    #[command(subcommand)]
+    command: InstructionsCommand,
+}
+
+/// Custom instructions commands
+///
+/// Positions start at 0, as in `customInstructions[0]`.
+#[derive(Subcommand, Debug)]
+pub enum InstructionsCommand {
+    /// List the instructions with their positions
+    List,
+    /// Append an instruction
+    Add {
+        /// Instruction text
+        text: String,
+    },
+    /// Insert an instruction, shifting later ones down
+    Insert {
+        /// Position of the new instruction (the current count appends)
+        index: usize,
+        /// Instruction text
+        text: String,
+    },
+    /// Replace the instruction at a position
+    Replace {
+        /// Position of the instruction to replace
+        index: usize,
+        /// New instruction text
+        text: String,
+    },
+    /// Remove the instruction at a position, shifting later ones up
+    Remove {
+        /// Position of the instruction to remove
+        index: usize,
+    },
+}
+
+impl InstructionsArgs {
+    /// Execute the instructions command
+    pub fn execute(&self) -> Result<()> {
+        let (config_path, manager) = self.target()?;
+        let mut config = if config_path.exists() {
+            manager.read_config(&config_path)?
+        } else {
+            ClaudeConfig::new()
+        };
+
+        let message = match &self.command {
+            InstructionsCommand::List => {
+                print_instructions(&config);
+                return Ok(());
+            }
+            InstructionsCommand::Add { text } => {
+                set_value_by_path(&mut config, &format!("{FIELD}[+]"), text, SetMode::Replace)?;
+                format!("Added instruction {}", instruction_count(&config) - 1)
+            }
+            InstructionsCommand::Insert { index, text } => {
+                insert_value_by_path(&mut config, FIELD, *index, text)?;
+                format!("Inserted instruction {index}")
+            }
+            InstructionsCommand::Replace { index, text } => {
+                set_value_by_path(
+                    &mut config,
+                    &format!("{FIELD}[{index}]"),
+                    text,
+                    SetMode::Replace,
+                )?;
+                format!("Replaced instruction {index}")
+            }
+            InstructionsCommand::Remove { index } => {
+                let removed = remove_index_by_path(&mut config, &format!("{FIELD}[{index}]"))?;
+                format!("Removed instruction {index}: {removed}")
+            }
+        };
+
+        manager.write_config_with_backup(&config_path, &config)?;
+        println!("✓ {message}");
+
+        Ok(())
+    }
+
+    /// Config file selected by `--project`, and a manager to write it
+    fn target(&self) -> Result<(PathBuf, ConfigManager)> {
+        let scope = if self.project.is_some() {
+            ConfigScope::Project
+        } else {
+            ConfigScope::Global
+        };
+        let config_path =
+            config_manager().config_path_for_scope(&scope, self.project.as_deref())?;
+
+        let backup_dir = config_path
+            .parent()
+            .map(|p| p.join("backups"))
+            .unwrap_or_else(|| PathBuf::from(".backups"));
+        let manager = config_manager_in(&backup_dir);
+        Ok((config_path, manager))
+    }
+}
+
+/// Number of instructions in a config
+fn instruction_count(config: &ClaudeConfig) -> usize {
+    config.custom_instructions.as_ref().map_or(0, Vec::len)
+}
+
+/// Print each instruction with its position
+fn print_instructions(config: &ClaudeConfig) {
+    match config.custom_instructions.as_deref() {
+        None | Some([]) => println!("No custom instructions."),
+        Some(instructions) => {
+            for (index, instruction) in instructions.iter().enumerate() {
+                println!("{index:>3}  {instruction}");
+            }
+        }
+    }
+}
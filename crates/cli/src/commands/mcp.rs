@@ -1,17 +1,26 @@
 //! MCP Server management commands
 //!
-//! Implements `mcp list`, `mcp enable`, `mcp disable`, `mcp add`, `mcp remove`, and `mcp show` commands
-
+//! Implements `mcp list`, `mcp enable`, `mcp disable`, `mcp disable-all`,
+//! `mcp revert-enabled`, `mcp add`, `mcp remove`, `mcp move`, `mcp show`,
+//! `mcp usage`, `mcp export` and `mcp import` commands
+
+use crate::{
+    managers::{config_manager_in, mcp_manager},
+    output::{format_porcelain, mcp_server_record},
+};
 use anyhow::Result;
 use clap::Parser;
-use claude_config_manager_core::{ConfigScope, McpManager, McpServer};
+use claude_config_manager_core::{
+    collate, paths::Platform, redact::REDACTED, server_references, unreferenced_servers,
+    ConfigScope, McpServer,
+};
 use std::path::{Path, PathBuf};
 
 /// MCP server management commands
 #[derive(Parser, Debug)]
 pub struct McpArgs {
-    /// Project path (default: auto-detect)
-    #[arg(short, long)]
+    /// Project path or @alias (default: auto-detect)
+    #[arg(short, long, value_parser = crate::project_arg::parse_project)]
     project: Option<PathBuf>,
 
     /// Configuration scope (global or project)
@@ -30,6 +39,15 @@ enum McpCommand {
         /// Show detailed information
         #[arg(short, long)]
         verbose: bool,
+
+        /// Only show servers that run on this platform (macos, linux or
+        /// windows), with the command used there
+        #[arg(long)]
+        platform: Option<Platform>,
+
+        /// Print the stable, tab-separated porcelain v1 format for scripts
+        #[arg(long, conflicts_with = "verbose")]
+        porcelain: bool,
     },
     /// Enable an MCP server
     Enable {
@@ -41,6 +59,16 @@ enum McpCommand {
         /// Server name
         name: String,
     },
+    /// Disable every MCP server at once, remembering which were enabled
+    ///
+    /// Undo with `mcp revert-enabled`.
+    DisableAll {
+        /// Servers to leave as they are (comma-separated)
+        #[arg(long, value_delimiter = ',', value_name = "NAMES")]
+        except: Vec<String>,
+    },
+    /// Restore the enabled flags saved by the last `mcp disable-all`
+    RevertEnabled,
     /// Add a new MCP server
     Add {
         /// Server name
@@ -54,16 +82,100 @@ enum McpCommand {
         /// Environment variables (KEY=VALUE format)
         #[arg(short, long)]
         env: Vec<String>,
+        /// Startup grace period in milliseconds
+        #[arg(long)]
+        timeout_ms: Option<u64>,
+        /// Leave an existing server with this name as it is instead of failing
+        #[arg(long)]
+        if_missing: bool,
     },
     /// Remove an MCP server
     Remove {
         /// Server name
         name: String,
     },
+    /// Move an MCP server between the global and project configs
+    ///
+    /// Both configs are updated together or not at all.
+    Move {
+        /// Server name
+        name: String,
+
+        /// Scope the server is configured in (global or project)
+        #[arg(long)]
+        from: String,
+
+        /// Scope to move it to (global or project)
+        #[arg(long)]
+        to: String,
+    },
+    /// Show where each server is mentioned in instructions and skill parameters
+    ///
+    /// Servers nothing mentions are listed as cleanup candidates. This is a
+    /// heuristic: Claude can use a server without any instruction naming it.
+    Usage,
     /// Show detailed server information
     Show {
         /// Server name
         name: String,
+
+        /// Resolve the server across global and project scopes
+        #[arg(long)]
+        effective: bool,
+    },
+    /// Export the servers of the scope to a JSON file (just the mcpServers map)
+    Export {
+        /// Output file (replaced if it exists)
+        file: PathBuf,
+
+        /// Write env values and secret-looking arguments instead of `<redacted>`
+        #[arg(long)]
+        include_secrets: bool,
+    },
+    /// Add servers from a file written by `mcp export`
+    Import {
+        /// Input file
+        file: PathBuf,
+
+        /// Replace servers configured with a different definition
+        #[arg(long)]
+        overwrite: bool,
+    },
+    /// Set, remove or list the environment variables of a server
+    Env {
+        #[command(subcommand)]
+        command: EnvCommand,
+    },
+}
+
+/// `mcp env` subcommands
+#[derive(Parser, Debug)]
+enum EnvCommand {
+    /// Set one or more variables, replacing existing values
+    Set {
+        /// Server name
+        name: String,
+
+        /// Variables to set
+        #[arg(required = true, value_name = "KEY=VALUE", value_parser = parse_env_pair)]
+        pairs: Vec<(String, String)>,
+    },
+    /// Remove a variable
+    Unset {
+        /// Server name
+        name: String,
+
+        /// Variable name
+        key: String,
+    },
+    /// List the variables of a server (values are hidden)
+    List {
+        /// Server name
+        name: String,
+
+        /// Show the values
+        #[arg(long)]
+        show_values: bool,
     },
 }
 
@@ -71,8 +183,12 @@ impl McpArgs {
     /// Execute the MCP command
     pub fn execute(&self) -> Result<()> {
         match &self.command {
-            McpCommand::List { verbose } => {
-                self.cmd_list(*verbose)?;
+            McpCommand::List {
+                verbose,
+                platform,
+                porcelain,
+            } => {
+                self.cmd_list(*verbose, *platform, *porcelain)?;
             }
             McpCommand::Enable { name } => {
                 self.cmd_enable(name)?;
@@ -80,19 +196,45 @@ impl McpArgs {
             McpCommand::Disable { name } => {
                 self.cmd_disable(name)?;
             }
+            McpCommand::DisableAll { except } => {
+                self.cmd_disable_all(except)?;
+            }
+            McpCommand::RevertEnabled => {
+                self.cmd_revert_enabled()?;
+            }
             McpCommand::Add {
                 name,
                 command,
                 args,
                 env,
+                timeout_ms,
+                if_missing,
             } => {
-                self.cmd_add(name, command, args, env)?;
+                self.cmd_add(name, command, args, env, *timeout_ms, *if_missing)?;
             }
             McpCommand::Remove { name } => {
                 self.cmd_remove(name)?;
             }
-            McpCommand::Show { name } => {
-                self.cmd_show(name)?;
+            McpCommand::Move { name, from, to } => {
+                self.cmd_move(name, from, to)?;
+            }
+            McpCommand::Usage => {
+                self.cmd_usage()?;
+            }
+            McpCommand::Show { name, effective } => {
+                self.cmd_show(name, *effective)?;
+            }
+            McpCommand::Export {
+                file,
+                include_secrets,
+            } => {
+                self.cmd_export(file, *include_secrets)?;
+            }
+            McpCommand::Import { file, overwrite } => {
+                self.cmd_import(file, *overwrite)?;
+            }
+            McpCommand::Env { command } => {
+                self.cmd_env(command)?;
             }
         }
         Ok(())
@@ -100,11 +242,7 @@ impl McpArgs {
 
     /// Parse scope from string
     fn parse_scope(&self) -> Result<ConfigScope> {
-        match self.scope.to_lowercase().as_str() {
-            "global" => Ok(ConfigScope::Global),
-            "project" => Ok(ConfigScope::Project),
-            _ => anyhow::bail!("Invalid scope '{}'. Use 'global' or 'project'.", self.scope),
-        }
+        parse_scope_name(&self.scope)
     }
 
     /// Get project path for the command
@@ -114,33 +252,67 @@ impl McpArgs {
 
     /// Create backup directory path
     fn get_backup_dir() -> PathBuf {
-        // Use a simple relative path for backups
-        PathBuf::from(".backups")
+        claude_config_manager_core::paths::get_backup_dir()
     }
 
-    /// List MCP servers
-    fn cmd_list(&self, verbose: bool) -> Result<()> {
+    /// List MCP servers, optionally only those running on `platform`
+    fn cmd_list(&self, verbose: bool, platform: Option<Platform>, porcelain: bool) -> Result<()> {
         let scope = self.parse_scope()?;
         let project_path = self.get_project_path();
         let backup_dir = Self::get_backup_dir();
 
-        let manager = McpManager::new(&backup_dir);
-        let servers = manager.list_servers(&scope, project_path)?;
+        let manager = mcp_manager(&backup_dir);
+        let mut servers = manager.list_servers(&scope, project_path)?;
+        if let Some(platform) = platform {
+            servers.retain(|_, server| server.runs_on(platform));
+        }
+
+        let mut names: Vec<&String> = servers.keys().collect();
+        collate::sort(&mut names);
+
+        if porcelain {
+            print!(
+                "{}",
+                format_porcelain(names.iter().map(|name| mcp_server_record(
+                    name,
+                    &servers[*name],
+                    platform
+                )))
+            );
+            return Ok(());
+        }
 
         if servers.is_empty() {
-            println!("No MCP servers configured.");
+            match platform {
+                Some(platform) => println!("No MCP servers configured for {}.", platform.key()),
+                None => println!("No MCP servers configured."),
+            }
             return Ok(());
         }
 
-        println!("MCP Servers ({}):\n", servers.len());
+        match platform {
+            Some(platform) => println!("MCP Servers for {} ({}):\n", platform.key(), servers.len()),
+            None => println!("MCP Servers ({}):\n", servers.len()),
+        }
 
-        for (name, server) in servers.iter() {
-            println!("  {name}:");
+        for name in names {
+            let server = &servers[name];
+            match &server.platforms {
+                Some(platforms) => println!("  {name} [{}]:", platforms.join(", ")),
+                None => println!("  {name}:"),
+            }
             println!("    Enabled: {}", if server.enabled { "yes" } else { "no" });
-            println!(
-                "    Command: {}",
-                server.command.as_deref().unwrap_or("(default)")
-            );
+            let command = match platform {
+                Some(platform) => server.command_for(platform),
+                None => server.command.as_deref(),
+            };
+            println!("    Command: {}", command.unwrap_or("(default)"));
+
+            if platform.is_none() {
+                for (key, command) in server.command_by_platform.iter().flatten() {
+                    println!("    Command on {key}: {command}");
+                }
+            }
 
             if !server.args.is_empty() {
                 println!("    Args: {}", server.args.join(" "));
@@ -169,10 +341,12 @@ impl McpArgs {
         let project_path = self.get_project_path();
         let backup_dir = Self::get_backup_dir();
 
-        let manager = McpManager::new(&backup_dir);
-        manager.enable_server(name, &scope, project_path)?;
-
-        println!("MCP server '{name}' enabled successfully.");
+        let manager = mcp_manager(&backup_dir);
+        if manager.enable_server(name, &scope, project_path)? {
+            println!("MCP server '{name}' enabled successfully.");
+        } else {
+            println!("MCP server '{name}' is already enabled; unchanged.");
+        }
         Ok(())
     }
 
@@ -182,15 +356,92 @@ impl McpArgs {
         let project_path = self.get_project_path();
         let backup_dir = Self::get_backup_dir();
 
-        let manager = McpManager::new(&backup_dir);
-        manager.disable_server(name, &scope, project_path)?;
+        let manager = mcp_manager(&backup_dir);
+        if manager.disable_server(name, &scope, project_path)? {
+            println!("MCP server '{name}' disabled successfully.");
+        } else {
+            println!("MCP server '{name}' is already disabled; unchanged.");
+        }
+        Ok(())
+    }
 
-        println!("MCP server '{name}' disabled successfully.");
+    /// Disable all servers except `except`, saving the previous flags
+    fn cmd_disable_all(&self, except: &[String]) -> Result<()> {
+        let scope = self.parse_scope()?;
+        let project_path = self.get_project_path();
+        let backup_dir = Self::get_backup_dir();
+
+        let manager = mcp_manager(&backup_dir);
+        let snapshot = manager.disable_all(except, &scope, project_path)?;
+        let Some(saved) = &snapshot.path else {
+            println!("No enabled MCP servers to disable; unchanged.");
+            return Ok(());
+        };
+
+        let servers = manager.list_servers(&scope, project_path)?;
+        let mut disabled: Vec<&String> = snapshot
+            .enabled
+            .iter()
+            .filter(|(name, enabled)| **enabled && servers.get(*name).is_some_and(|s| !s.enabled))
+            .map(|(name, _)| name)
+            .collect();
+        collate::sort(&mut disabled);
+
+        println!("Disabled {} MCP server(s):", disabled.len());
+        for name in disabled {
+            println!("  {name}");
+        }
+        println!("\nPrevious state saved to {}", saved.display());
+        println!("Run 'ccm mcp revert-enabled' (with the same --scope/--project) to restore it.");
+        Ok(())
+    }
+
+    /// Restore the enabled flags of the latest `disable-all` snapshot
+    fn cmd_revert_enabled(&self) -> Result<()> {
+        let scope = self.parse_scope()?;
+        let project_path = self.get_project_path();
+        let backup_dir = Self::get_backup_dir();
+
+        let manager = mcp_manager(&backup_dir);
+        let Some(snapshot) = manager.latest_enabled_snapshot(&scope, project_path)? else {
+            anyhow::bail!(
+                "No saved enabled-set for the {} config; run 'ccm mcp disable-all' first",
+                scope.display_name()
+            );
+        };
+        let restore = manager.restore_enabled(&snapshot, &scope, project_path)?;
+
+        println!(
+            "Restoring the enabled-set saved {}",
+            snapshot.created_at.format("%Y-%m-%d %H:%M:%S UTC")
+        );
+        if restore.restored.is_empty() {
+            println!("All servers already have their saved flags; unchanged.");
+        }
+        for name in &restore.restored {
+            let state = if snapshot.enabled[name] {
+                "enabled"
+            } else {
+                "disabled"
+            };
+            println!("  {name}: {state}");
+        }
+        for name in &restore.missing {
+            println!("Note: '{name}' was removed since the snapshot; skipped.");
+        }
         Ok(())
     }
 
     /// Add a new MCP server
-    fn cmd_add(&self, name: &str, command: &str, args: &str, env_vars: &[String]) -> Result<()> {
+    fn cmd_add(
+        &self,
+        name: &str,
+        command: &str,
+        args: &str,
+        env_vars: &[String],
+        timeout_ms: Option<u64>,
+        if_missing: bool,
+    ) -> Result<()> {
         let scope = self.parse_scope()?;
         let project_path = self.get_project_path();
         let backup_dir = Self::get_backup_dir();
@@ -214,11 +465,25 @@ impl McpArgs {
         // Create server
         let mut server = McpServer::new(name, command, args_vec);
         server.env = env_map;
+        server.timeout_ms = timeout_ms;
+
+        let manager = mcp_manager(&backup_dir);
+        if if_missing {
+            if let Ok(existing) = manager.get_server(name, &scope, project_path) {
+                if !existing.semantically_equal(&server) {
+                    println!(
+                        "MCP server '{name}' already exists with a different definition; unchanged."
+                    );
+                    return Ok(());
+                }
+            }
+        }
 
-        let manager = McpManager::new(&backup_dir);
-        manager.add_server(name, server, &scope, project_path)?;
-
-        println!("MCP server '{name}' added successfully.");
+        if manager.add_server(name, server, &scope, project_path)? {
+            println!("MCP server '{name}' added successfully.");
+        } else {
+            println!("MCP server '{name}' is already configured; unchanged.");
+        }
         Ok(())
     }
 
@@ -228,23 +493,142 @@ impl McpArgs {
         let project_path = self.get_project_path();
         let backup_dir = Self::get_backup_dir();
 
-        let manager = McpManager::new(&backup_dir);
+        let manager = mcp_manager(&backup_dir);
         manager.remove_server(name, &scope, project_path)?;
 
         println!("MCP server '{name}' removed successfully.");
         Ok(())
     }
 
-    /// Show detailed server information
-    fn cmd_show(&self, name: &str) -> Result<()> {
+    /// Move a server between scopes
+    fn cmd_move(&self, name: &str, from: &str, to: &str) -> Result<()> {
+        let from = parse_scope_name(from)?;
+        let to = parse_scope_name(to)?;
+        let project_path = self.get_project_path();
+        let backup_dir = Self::get_backup_dir();
+
+        let manager = mcp_manager(&backup_dir);
+        manager.move_server(name, &from, &to, project_path)?;
+
+        println!(
+            "MCP server '{name}' moved from {} to {} config.",
+            from.display_name(),
+            to.display_name()
+        );
+        Ok(())
+    }
+
+    /// Export the servers of the scope to a file
+    fn cmd_export(&self, file: &Path, include_secrets: bool) -> Result<()> {
         let scope = self.parse_scope()?;
         let project_path = self.get_project_path();
         let backup_dir = Self::get_backup_dir();
 
-        let manager = McpManager::new(&backup_dir);
-        let server = manager.get_server(name, &scope, project_path)?;
+        let manager = mcp_manager(&backup_dir);
+        let count = manager.export_servers(&scope, project_path, file, include_secrets)?;
+
+        println!(
+            "Exported {count} MCP server(s) from {} config to {}",
+            scope.display_name(),
+            file.display()
+        );
+        Ok(())
+    }
+
+    /// Add servers from an export file
+    fn cmd_import(&self, file: &Path, overwrite: bool) -> Result<()> {
+        let scope = self.parse_scope()?;
+        let project_path = self.get_project_path();
+        let backup_dir = Self::get_backup_dir();
+
+        let manager = mcp_manager(&backup_dir);
+        let imported = manager.import_servers(file, &scope, project_path, overwrite)?;
+
+        for (label, names) in [
+            ("Added", &imported.added),
+            ("Replaced", &imported.replaced),
+            ("Unchanged", &imported.unchanged),
+        ] {
+            if !names.is_empty() {
+                println!("{label}: {}", names.join(", "));
+            }
+        }
+        if imported.added.is_empty() && imported.replaced.is_empty() {
+            println!(
+                "All servers already configured in {} config; unchanged.",
+                scope.display_name()
+            );
+        }
+        Ok(())
+    }
+
+    /// Report server references and unreferenced servers
+    fn cmd_usage(&self) -> Result<()> {
+        let scope = self.parse_scope()?;
+        let backup_dir = Self::get_backup_dir();
+        let manager = config_manager_in(&backup_dir);
+
+        // Project instructions and servers combine with the global ones
+        let config = match scope {
+            ConfigScope::Global | ConfigScope::Base => manager.get_inherited_global_config()?.0,
+            ConfigScope::Project => manager.get_merged_config(self.get_project_path())?,
+        };
+
+        let references = server_references(&config);
+        if references.is_empty() {
+            println!("No MCP servers configured.");
+            return Ok(());
+        }
+
+        let mut names: Vec<&String> = references.keys().collect();
+        collate::sort(&mut names);
+        for name in names {
+            let refs = &references[name];
+            if refs.is_empty() {
+                println!("{name}: not referenced");
+                continue;
+            }
+            println!(
+                "{name}: {} reference{}",
+                refs.len(),
+                if refs.len() == 1 { "" } else { "s" }
+            );
+            for reference in refs {
+                println!("  {}: {}", reference.key_path, reference.snippet);
+            }
+        }
+
+        let unreferenced = unreferenced_servers(&references);
+        if !unreferenced.is_empty() {
+            println!();
+            println!("Cleanup candidates: {}", unreferenced.join(", "));
+            println!(
+                "Note: this is a heuristic. Claude can use servers that no instruction or skill mentions."
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Show detailed server information
+    fn cmd_show(&self, name: &str, effective: bool) -> Result<()> {
+        let project_path = self.get_project_path();
+        let backup_dir = Self::get_backup_dir();
+
+        let (server, source) = if effective {
+            let manager = config_manager_in(&backup_dir);
+            let (server, scope) = manager.get_effective_server(name, project_path)?;
+            (server, Some(scope))
+        } else {
+            let scope = self.parse_scope()?;
+            let manager = mcp_manager(&backup_dir);
+            (manager.get_server(name, &scope, project_path)?, None)
+        };
 
         println!("Server: {name}");
+        if let Some(scope) = source {
+            println!("  Source: {}", scope.display_name());
+        }
         println!("  Enabled: {}", if server.enabled { "yes" } else { "no" });
         println!(
             "  Command: {}",
@@ -257,6 +641,9 @@ impl McpArgs {
             server.args.join(" ")
         };
         println!("  Args: {args_str}");
+        if let Some(command_line) = server.command_line() {
+            println!("  Command line: {command_line}");
+        }
 
         let env_str = if server.env.is_empty() {
             "(none)".to_string()
@@ -270,6 +657,72 @@ impl McpArgs {
         };
         println!("  Environment: {env_str}");
 
+        if let Some(timeout_ms) = server.timeout_ms {
+            println!("  Timeout: {timeout_ms} ms");
+        }
+
         Ok(())
     }
+
+    /// Set, remove or list the environment variables of a server
+    fn cmd_env(&self, command: &EnvCommand) -> Result<()> {
+        let scope = self.parse_scope()?;
+        let project_path = self.get_project_path();
+        let backup_dir = Self::get_backup_dir();
+        let manager = mcp_manager(&backup_dir);
+
+        match command {
+            EnvCommand::Set { name, pairs } => {
+                let replaced = manager.set_env_vars(name, pairs, &scope, project_path)?;
+                for (key, _) in &replaced {
+                    println!("Note: replaced existing value of {key} (was {REDACTED})");
+                }
+                println!(
+                    "Set {} env variable(s) on MCP server '{name}'.",
+                    pairs.len()
+                );
+            }
+            EnvCommand::Unset { name, key } => {
+                manager.unset_env_var(name, key, &scope, project_path)?;
+                println!("Removed env variable {key} from MCP server '{name}'.");
+            }
+            EnvCommand::List { name, show_values } => {
+                let server = manager.get_server(name, &scope, project_path)?;
+                if server.env.is_empty() {
+                    println!("MCP server '{name}' has no env variables.");
+                    return Ok(());
+                }
+
+                let mut keys: Vec<&String> = server.env.keys().collect();
+                keys.sort();
+                for key in keys {
+                    let value = if *show_values {
+                        server.env[key].as_str()
+                    } else {
+                        REDACTED
+                    };
+                    println!("{key}={value}");
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Parse a `KEY=VALUE` environment variable assignment
+fn parse_env_pair(input: &str) -> Result<(String, String), String> {
+    input
+        .split_once('=')
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .ok_or_else(|| format!("'{input}' is not in KEY=VALUE format"))
+}
+
+/// Parse a scope name (global or project)
+fn parse_scope_name(scope: &str) -> Result<ConfigScope> {
+    match scope.to_lowercase().as_str() {
+        "global" => Ok(ConfigScope::Global),
+        "project" => Ok(ConfigScope::Project),
+        _ => anyhow::bail!("Invalid scope '{scope}'. Use 'global' or 'project'."),
+    }
 }
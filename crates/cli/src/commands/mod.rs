@@ -2,8 +2,17 @@
 //!
 //! Individual command implementations
 
+pub mod alias;
 pub mod config;
+pub mod daemon;
+pub mod doctor;
 pub mod history;
+pub mod instructions;
 pub mod mcp;
+pub mod paths;
 pub mod project;
 pub mod search;
+pub mod state;
+pub mod stats;
+pub mod support;
+pub mod usage;
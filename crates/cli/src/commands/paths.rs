@@ -0,0 +1,168 @@
+//! Paths command implementation
+//!
+//! Cleans up project `allowedPaths` that were copied from the global config
+
+use crate::managers::{config_manager, config_manager_in};
+use anyhow::Result;
+use clap::{Parser, Subcommand};
+use claude_config_manager_core::{
+    config::{
+        allowed_paths::{plan_dedupe, DedupePlan, PathClass},
+        merge::MergeOptions,
+    },
+    ConfigScope,
+};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Allowed paths commands
+#[derive(Parser, Debug)]
+pub struct PathsArgs {
+    #[command(subcommand)]
+    command: PathsCommand,
+}
+
+/// Allowed paths commands
+#[derive(Subcommand, Debug)]
+pub enum PathsCommand {
+    /// Remove project allowed paths that duplicate the global list
+    ///
+    /// Only removes entries when the effective allowed paths stay the same.
+    Dedupe {
+        /// Project directory or @alias
+        #[arg(short, long, value_parser = crate::project_arg::parse_project)]
+        project: PathBuf,
+
+        /// Show the classification without changing anything
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Don't ask for confirmation
+        #[arg(short, long)]
+        yes: bool,
+    },
+}
+
+impl PathsArgs {
+    /// Execute the paths command
+    pub fn execute(&self) -> Result<()> {
+        match &self.command {
+            PathsCommand::Dedupe {
+                project,
+                dry_run,
+                yes,
+            } => dedupe(project, *dry_run, *yes),
+        }
+    }
+}
+
+/// Classify a project's allowed paths and apply the cleanup after confirmation
+fn dedupe(project: &Path, dry_run: bool, yes: bool) -> Result<()> {
+    let lookup = config_manager();
+    let config_path = lookup.config_path_for_scope(&ConfigScope::Project, Some(project))?;
+    let backup_dir = config_path
+        .parent()
+        .map(|p| p.join("backups"))
+        .unwrap_or_else(|| PathBuf::from(".backups"));
+    let manager = config_manager_in(&backup_dir);
+
+    let global = manager.get_global_config()?;
+    let mut project_config = manager.read_config(&config_path)?;
+    let plan = plan_dedupe(&global, &project_config, MergeOptions::default());
+
+    print_plan(&plan);
+
+    if plan.is_noop() {
+        println!("\nNothing to remove without changing the effective allowed paths.");
+        return Ok(());
+    }
+    if dry_run {
+        println!("\nDry run: {} not modified.", config_path.display());
+        return Ok(());
+    }
+
+    if !yes {
+        print!(
+            "\nRemove these entries from {}? [y/N] ",
+            config_path.display()
+        );
+        std::io::stdout().flush()?;
+
+        let mut input = String::new();
+        std::io::stdin().read_line(&mut input)?;
+
+        let input = input.trim().to_lowercase();
+        if input != "y" && input != "yes" {
+            println!("Cleanup cancelled.");
+            return Ok(());
+        }
+    }
+
+    plan.apply(&mut project_config);
+    manager.write_config_with_backup(&config_path, &project_config)?;
+    println!(
+        "✓ Removed {} entr{} from {}",
+        plan.removed().len(),
+        if plan.removed().len() == 1 {
+            "y"
+        } else {
+            "ies"
+        },
+        config_path.display()
+    );
+
+    Ok(())
+}
+
+/// Print the classification and the effective paths before and after
+fn print_plan(plan: &DedupePlan) {
+    if plan.entries.is_empty() {
+        println!("The project doesn't set allowedPaths; it uses the global list.");
+    } else {
+        println!("Project allowedPaths:");
+        for entry in &plan.entries {
+            println!("  {:<40} {}", entry.path, entry.class.label());
+        }
+    }
+
+    if !plan.hidden_global.is_empty() && !plan.entries.is_empty() {
+        println!("\nGlobal entries hidden by the project list:");
+        for path in &plan.hidden_global {
+            println!("  {path}");
+        }
+    }
+
+    let kept = plan.kept_global_copies();
+    let project_only = plan
+        .entries
+        .iter()
+        .any(|entry| entry.class == PathClass::ProjectOnly);
+    if !kept.is_empty() && project_only {
+        println!(
+            "\nKept {} global cop{}: arrays replace on merge, so the project-only entries need them.",
+            kept.len(),
+            if kept.len() == 1 { "y" } else { "ies" }
+        );
+    } else if !kept.is_empty() {
+        println!(
+            "\nKept the project list: removing it would grant the project the hidden global entries."
+        );
+    }
+
+    if plan.is_noop() {
+        return;
+    }
+
+    println!("\nTo remove:");
+    for path in plan.removed() {
+        println!("  - {path}");
+    }
+    println!(
+        "\nEffective allowedPaths before: {}",
+        plan.effective_before.join(", ")
+    );
+    println!(
+        "Effective allowedPaths after:  {}",
+        plan.effective_after.join(", ")
+    );
+}
@@ -1,12 +1,30 @@
 //! Project management commands
 //!
-//! Implements `project scan` and `project list` commands for discovering
-//! and managing Claude Code projects.
-
-use anyhow::Result;
+//! Implements `project scan`, `project list`, `project check`,
+//! `project export-all` and `project archive`/`unarchive` commands for
+//! discovering and managing Claude Code projects.
+
+use crate::{
+    bulk,
+    managers::{config_manager, config_manager_in},
+    output::{
+        format_json, format_porcelain, format_table, format_yaml, project_record, ListFormat,
+        OutputFormat, TableOptions,
+    },
+    timings,
+};
+use anyhow::{bail, Context, Result};
 use camino::Utf8PathBuf;
 use clap::{Parser, Subcommand};
-use claude_config_manager_core::{ConfigManager, ProjectScanner};
+use claude_config_manager_core::{
+    bundle::{export_all, STAGING_MANIFEST_NAME},
+    collate, format_timestamp, is_project_archived,
+    paths::{get_registry_path, project_config_path},
+    project::registry::set_archive_marker,
+    validate_config, BundleSource, CancellationToken, ConfigError, ConfigScope, ExportAllOutcome,
+    MultiResult, ProjectInfo, ProjectRegistry, ProjectScanner, ScanStats,
+};
+use std::path::{Path, PathBuf};
 
 /// Project management command arguments
 #[derive(Parser, Debug)]
@@ -31,6 +49,18 @@ pub enum ProjectCommand {
         /// Show detailed information
         #[arg(short, long)]
         verbose: bool,
+
+        /// Only include projects whose config file exists and is readable
+        #[arg(long)]
+        with_config_only: bool,
+
+        /// Print how many directories were visited and skipped
+        #[arg(long)]
+        stats: bool,
+
+        /// Print project paths relative to the scanned directory
+        #[arg(long)]
+        relative: bool,
     },
 
     /// List discovered projects
@@ -46,12 +76,111 @@ pub enum ProjectCommand {
         /// Show detailed information
         #[arg(short, long)]
         verbose: bool,
+
+        /// Print projects as JSON (timestamps in RFC 3339)
+        #[arg(long)]
+        json: bool,
+
+        /// Print the stable, tab-separated porcelain v1 format for scripts
+        #[arg(long, conflicts_with_all = ["json", "verbose"])]
+        porcelain: bool,
+
+        /// Also list archived projects (marked as archived)
+        #[arg(long)]
+        include_archived: bool,
+
+        /// Only include projects whose config file exists and is readable
+        #[arg(long)]
+        with_config_only: bool,
+    },
+
+    /// Read and validate the config of every discovered project
+    Check {
+        /// Directory path to scan (default: current directory)
+        #[arg(short, long)]
+        path: Option<Utf8PathBuf>,
+
+        /// Maximum scan depth (default: unlimited)
+        #[arg(short, long)]
+        depth: Option<usize>,
+
+        /// Abort if any project config cannot be read or is invalid
+        #[arg(long)]
+        strict: bool,
+
+        /// Also check archived projects
+        #[arg(long)]
+        include_archived: bool,
+    },
+
+    /// Export every discovered project config into one zip
+    ///
+    /// Configs are staged in `<OUTPUT>.staging` first; an interrupted export
+    /// can be continued with --resume.
+    ExportAll {
+        /// Destination zip file
+        output: PathBuf,
+
+        /// Directory path to scan (default: current directory)
+        #[arg(short, long)]
+        path: Option<Utf8PathBuf>,
+
+        /// Maximum scan depth (default: unlimited)
+        #[arg(short, long)]
+        depth: Option<usize>,
+
+        /// Also export archived projects
+        #[arg(long)]
+        include_archived: bool,
+
+        /// Continue an interrupted export from its staging directory
+        #[arg(long, value_name = "STAGING_DIR")]
+        resume: Option<PathBuf>,
+    },
+
+    /// Archive a project so it is hidden from listings and bulk commands
+    Archive {
+        /// Project name, root directory or @alias
+        project: String,
+
+        /// Directory to search for the project by name (default: current directory)
+        #[arg(short, long)]
+        path: Option<Utf8PathBuf>,
+    },
+
+    /// Restore an archived project
+    Unarchive {
+        /// Project name, root directory or @alias
+        project: String,
+
+        /// Directory to search for the project by name (default: current directory)
+        #[arg(short, long)]
+        path: Option<Utf8PathBuf>,
     },
 
     /// Show configuration for a project
     Config {
-        /// Project path
-        path: Utf8PathBuf,
+        /// Project path or @alias
+        #[arg(
+            value_parser = crate::project_arg::parse_utf8_project,
+            required_unless_present = "path_option",
+            conflicts_with = "path_option"
+        )]
+        path: Option<Utf8PathBuf>,
+
+        /// Project path or @alias (same as the positional argument)
+        #[arg(
+            id = "path_option",
+            short,
+            long = "path",
+            value_parser = crate::project_arg::parse_utf8_project
+        )]
+        path_option: Option<Utf8PathBuf>,
+
+        /// Output format (default: a summary of servers, instructions,
+        /// paths and skills)
+        #[arg(short, long)]
+        output: Option<OutputFormat>,
     },
 }
 
@@ -63,13 +192,69 @@ impl ProjectCommand {
                 path,
                 depth,
                 verbose,
-            } => self.scan(path.as_deref(), *depth, *verbose),
+                with_config_only,
+                stats,
+                relative,
+            } => self.scan(
+                path.as_deref(),
+                *depth,
+                *verbose,
+                *with_config_only,
+                *stats,
+                *relative,
+            ),
             ProjectCommand::List {
                 path,
                 depth,
                 verbose,
-            } => self.list(path.as_deref(), *depth, *verbose),
-            ProjectCommand::Config { path } => self.show_config(path),
+                json,
+                porcelain,
+                include_archived,
+                with_config_only,
+            } => self.list(
+                path.as_deref(),
+                *depth,
+                *verbose,
+                ListFormat::from_flags(*json, *porcelain),
+                *include_archived,
+                *with_config_only,
+            ),
+            ProjectCommand::Check {
+                path,
+                depth,
+                strict,
+                include_archived,
+            } => self.check(path.as_deref(), *depth, *strict, *include_archived),
+            ProjectCommand::ExportAll {
+                output,
+                path,
+                depth,
+                include_archived,
+                resume,
+            } => self.export_all(
+                output,
+                path.as_deref(),
+                *depth,
+                *include_archived,
+                resume.as_deref(),
+            ),
+            ProjectCommand::Archive { project, path } => {
+                self.set_archived(project, path.as_deref(), true)
+            }
+            ProjectCommand::Unarchive { project, path } => {
+                self.set_archived(project, path.as_deref(), false)
+            }
+            ProjectCommand::Config {
+                path,
+                path_option,
+                output,
+            } => {
+                let path = path
+                    .as_deref()
+                    .or(path_option.as_deref())
+                    .context("A project path is required")?;
+                self.show_config(path, *output)
+            }
         }
     }
 
@@ -79,48 +264,45 @@ impl ProjectCommand {
         path: Option<&camino::Utf8Path>,
         depth: Option<usize>,
         verbose: bool,
+        with_config_only: bool,
+        show_stats: bool,
+        relative: bool,
     ) -> Result<()> {
         let scan_path = if let Some(p) = path {
             p
         } else {
             camino::Utf8Path::new(".")
         };
-        let scanner = ProjectScanner::new(depth, false);
+        let scanner = bulk::project_scanner(depth)?.with_config_only(with_config_only);
 
         println!("Scanning for Claude Code projects in: {scan_path}\n");
 
-        let start = std::time::Instant::now();
-        let projects = scanner.scan_directory(scan_path.as_ref())?;
-        let duration = start.elapsed();
+        // Print each project as the walk finds it, in discovery order
+        let mut walk = scanner.scan_directory_iter(scan_path.as_ref());
+        let mut projects = Vec::new();
+        for project in walk.by_ref() {
+            let project = project?;
+            let shown = if relative {
+                project.to_relative(scan_path.as_ref())
+            } else {
+                project.clone()
+            };
+            print_scanned_project(projects.len() + 1, &shown, verbose);
+            projects.push(project);
+        }
+        let stats = walk.stats();
+        bulk::register_projects(&projects)?;
 
         if projects.is_empty() {
             println!("No projects found.");
-            return Ok(());
+        } else {
+            println!("Found {} project(s)", projects.len());
+            println!("Scan completed in {:?}", stats.elapsed);
         }
-
-        println!("Found {} project(s):\n", projects.len());
-
-        for (index, project) in projects.iter().enumerate() {
-            println!("  [{}] {}", index + 1, project.name);
-
-            if verbose {
-                println!("      Root: {}", project.root.display());
-                println!("      Claude: {}", project.claude_dir.display());
-                println!("      Config: {}", project.config_path.display());
-                println!("      Has Config: {}", project.has_config);
-
-                if let Some(modified) = project.last_modified {
-                    let duration_since = modified.elapsed().unwrap_or_default().as_secs();
-                    println!("      Last Modified: {duration_since} seconds ago");
-                }
-            } else {
-                println!("      {}", project.root.display());
-            }
-            println!();
+        if show_stats {
+            print_scan_stats(&stats);
         }
 
-        println!("Scan completed in {duration:?}");
-
         Ok(())
     }
 
@@ -130,15 +312,34 @@ impl ProjectCommand {
         path: Option<&camino::Utf8Path>,
         depth: Option<usize>,
         verbose: bool,
+        format: ListFormat,
+        include_archived: bool,
+        with_config_only: bool,
     ) -> Result<()> {
         let scan_path = if let Some(p) = path {
             p
         } else {
             camino::Utf8Path::new(".")
         };
-        let scanner = ProjectScanner::new(depth, false);
+        let scanner = bulk::project_scanner(depth)?.with_config_only(with_config_only);
 
-        let projects = scanner.scan_directory(scan_path.as_ref())?;
+        let mut projects = scanner.scan_directory(scan_path.as_ref())?;
+        bulk::register_projects(&projects)?;
+        if !include_archived {
+            projects.retain(|project| !project.archived);
+        }
+
+        match format {
+            ListFormat::Json => {
+                println!("{}", serde_json::to_string_pretty(&projects)?);
+                return Ok(());
+            }
+            ListFormat::Porcelain => {
+                print!("{}", format_porcelain(projects.iter().map(project_record)));
+                return Ok(());
+            }
+            ListFormat::Human => {}
+        }
 
         if projects.is_empty() {
             println!("No projects found.");
@@ -149,21 +350,15 @@ impl ProjectCommand {
         println!("Claude Code Projects ({}):\n", projects.len());
 
         for (index, project) in projects.iter().enumerate() {
-            println!("  [{}]  {}", index + 1, project.name);
+            let marker = if project.archived { "  [archived]" } else { "" };
+            println!("  [{}]  {}{marker}", index + 1, project.name);
 
             if verbose {
                 println!("       Path: {}", project.root.display());
                 println!("       Config: {}", project.config_path.display());
 
-                if let Some(modified) = project.last_modified {
-                    if let Ok(duration) = modified.elapsed() {
-                        let duration_str = if duration.as_secs() < 60 {
-                            format!("{}s ago", duration.as_secs())
-                        } else {
-                            format!("{}m ago", duration.as_secs() / 60)
-                        };
-                        println!("       Modified: {duration_str}");
-                    }
+                if let Some(modified) = &project.last_modified {
+                    println!("       Modified: {}", format_timestamp(modified));
                 }
             }
         }
@@ -173,14 +368,227 @@ impl ProjectCommand {
         Ok(())
     }
 
+    /// Read and validate every discovered project config
+    fn check(
+        &self,
+        path: Option<&camino::Utf8Path>,
+        depth: Option<usize>,
+        strict: bool,
+        include_archived: bool,
+    ) -> Result<()> {
+        let scan_path = path.unwrap_or_else(|| camino::Utf8Path::new("."));
+        let scanner = bulk::project_scanner(depth)?;
+        let backup_dir = claude_config_manager_core::paths::get_backup_dir();
+        let manager = config_manager_in(&backup_dir);
+
+        let projects = scanner.scan_directory(scan_path.as_ref())?;
+        let read = manager.read_configs(bulk::config_paths(&projects, include_archived));
+
+        // Configs that parsed but fail validation count as failures too
+        let mut checked = MultiResult::new();
+        checked.failures = read.failures;
+        for (config_path, config) in read.successes {
+            checked.push(config_path, validate_config(&config));
+        }
+
+        bulk::check_strict(&checked, strict)?;
+
+        if checked.is_empty() {
+            println!("No project configs found.");
+            return Ok(());
+        }
+
+        for (config_path, ()) in &checked.successes {
+            println!("  ok    {}", config_path.display());
+        }
+        for (config_path, _) in &checked.failures {
+            println!("  FAIL  {}", config_path.display());
+        }
+        println!(
+            "\nChecked {} project config(s): {} ok, {} failed",
+            checked.len(),
+            checked.successes.len(),
+            checked.failures.len()
+        );
+
+        bulk::finish(&checked);
+
+        Ok(())
+    }
+
+    /// Export every project config into one zip through a staging directory
+    fn export_all(
+        &self,
+        output: &Path,
+        path: Option<&camino::Utf8Path>,
+        depth: Option<usize>,
+        include_archived: bool,
+        resume: Option<&Path>,
+    ) -> Result<()> {
+        let scan_path = path.unwrap_or_else(|| camino::Utf8Path::new("."));
+        let scanner = bulk::project_scanner(depth)?;
+
+        let mut projects = scanner.scan_directory(scan_path.as_ref())?;
+        if !include_archived {
+            projects.retain(|project| !project.archived);
+        }
+        let sources = BundleSource::from_projects(&projects);
+
+        let staging_dir = match resume {
+            Some(dir) => dir.to_path_buf(),
+            None => {
+                let mut dir = output.as_os_str().to_owned();
+                dir.push(".staging");
+                PathBuf::from(dir)
+            }
+        };
+
+        let total = sources.len();
+        let mut done = 0;
+        let result = export_all(
+            &sources,
+            output,
+            &staging_dir,
+            resume.is_some(),
+            &CancellationToken::new(),
+            |entry| {
+                done += 1;
+                println!("  [{done}/{total}] {}", entry.name);
+            },
+        );
+
+        // Only IO failures leave a partial export that resuming can finish
+        let outcome = match result {
+            Ok(outcome) => outcome,
+            Err(
+                e @ (ConfigError::Filesystem { .. }
+                | ConfigError::WindowsPathLimit { .. }
+                | ConfigError::PermissionDenied { .. }),
+            ) if staging_dir.join(STAGING_MANIFEST_NAME).is_file() => {
+                return Err(anyhow::Error::new(e).context(format!(
+                    "Export did not complete; continue it with --resume {}",
+                    staging_dir.display()
+                )));
+            }
+            Err(e) => return Err(e.into()),
+        };
+
+        match outcome {
+            ExportAllOutcome::Completed {
+                path,
+                entries,
+                reused,
+            } => {
+                if reused > 0 {
+                    println!("\nReused {reused} config(s) from the interrupted export");
+                }
+                println!("Exported {entries} project config(s) to: {}", path.display());
+            }
+            ExportAllOutcome::Cancelled {
+                staging_dir,
+                done,
+                total,
+            } => println!(
+                "\nExport cancelled after {done} of {total} config(s); continue it with --resume {}",
+                staging_dir.display()
+            ),
+        }
+
+        Ok(())
+    }
+
+    /// Archive or unarchive a project
+    ///
+    /// The project is registered if needed and flagged in the registry.
+    /// Unarchiving also clears an archived marker in the project's config.
+    fn set_archived(
+        &self,
+        project: &str,
+        path: Option<&camino::Utf8Path>,
+        archived: bool,
+    ) -> Result<()> {
+        let root = self.resolve_project(project, path)?;
+        let info = ProjectInfo::from_config_path(project_config_path(&root));
+        let mut registry = ProjectRegistry::load(get_registry_path())?;
+        if !registry.contains(&root) && !info.has_config {
+            bail!(
+                "{} is not in the project registry and has no .claude/config.json",
+                root.display()
+            );
+        }
+
+        let was_archived = is_project_archived(&info, Some(&registry));
+        registry.register(&root);
+        registry.set_archived(&root, archived);
+        registry.save()?;
+
+        // A marker would archive the project again if it left the registry
+        if !archived && info.has_config {
+            let manager = config_manager();
+            let mut config = manager.read_config(&info.config_path)?;
+            if set_archive_marker(&mut config, false) {
+                manager.write_config_with_backup(&info.config_path, &config)?;
+            }
+        }
+
+        match (was_archived != archived, archived) {
+            (true, true) => println!("Archived project: {}", root.display()),
+            (true, false) => println!("Unarchived project: {}", root.display()),
+            (false, true) => println!("Project is already archived: {}", root.display()),
+            (false, false) => println!("Project is not archived: {}", root.display()),
+        }
+
+        Ok(())
+    }
+
+    /// Find a project root by alias, directory or name
+    ///
+    /// An `@alias` or existing directory is taken as the root; otherwise the
+    /// name must match exactly one project found under `path`.
+    fn resolve_project(&self, project: &str, path: Option<&camino::Utf8Path>) -> Result<PathBuf> {
+        let candidate = &crate::project_arg::resolve_project(project)?;
+        if candidate.is_dir() {
+            return candidate
+                .canonicalize()
+                .with_context(|| format!("Failed to resolve project path: {project}"));
+        }
+
+        let scan_path = path.unwrap_or_else(|| camino::Utf8Path::new("."));
+        let scanner = ProjectScanner::new(None, false).with_timings(timings::collector());
+        let matches: Vec<_> = scanner
+            .scan_directory(scan_path.as_ref())?
+            .into_iter()
+            .filter(|info| collate::same_name(&info.name, project))
+            .collect();
+
+        match matches.as_slice() {
+            [info] => Ok(info.root.canonicalize().unwrap_or_else(|_| info.root.clone())),
+            [] => bail!("No project named '{project}' found under {scan_path}"),
+            _ => bail!(
+                "{} projects named '{project}' found under {scan_path}; pass the project directory instead",
+                matches.len()
+            ),
+        }
+    }
+
     /// Show configuration for a specific project
-    fn show_config(&self, path: &camino::Utf8Path) -> Result<()> {
+    fn show_config(&self, path: &camino::Utf8Path, output: Option<OutputFormat>) -> Result<()> {
         let backup_dir = claude_config_manager_core::paths::get_backup_dir();
-        let manager = ConfigManager::new(&backup_dir);
+        let manager = config_manager_in(&backup_dir);
 
         // Read project config
-        let config_path = path.join(".claude").join("config.json");
-        let config = manager.read_config(config_path.as_ref())?;
+        let config_path =
+            manager.config_path_for_scope(&ConfigScope::Project, Some(path.as_ref()))?;
+        let config = manager.read_config(&config_path)?;
+
+        match output {
+            Some(OutputFormat::Json) => return format_json(&config, None),
+            Some(OutputFormat::Yaml) => return format_yaml(&config, None),
+            Some(OutputFormat::Table) => {
+                return format_table(&config, None, TableOptions::default())
+            }
+            None => {}
+        }
 
         // Display configuration
         println!("Project Configuration: {path}\n");
@@ -239,3 +647,31 @@ impl ProjectCommand {
         Ok(())
     }
 }
+
+/// Print the statistics of a scan
+/// Print one project found by `project scan`
+fn print_scanned_project(number: usize, project: &ProjectInfo, verbose: bool) {
+    println!("  [{number}] {}", project.name);
+
+    if verbose {
+        println!("      Root: {}", project.root.display());
+        println!("      Claude: {}", project.claude_dir.display());
+        println!("      Config: {}", project.config_path.display());
+        println!("      Has Config: {}", project.has_config);
+
+        if let Some(modified) = &project.last_modified {
+            println!("      Last Modified: {}", format_timestamp(modified));
+        }
+    } else {
+        println!("      {}", project.root.display());
+    }
+    println!();
+}
+
+fn print_scan_stats(stats: &ScanStats) {
+    println!("Scan statistics:");
+    println!("  Directories visited: {}", stats.dirs_visited);
+    println!("  Projects found:      {}", stats.projects_found);
+    println!("  Skipped (ignored):   {}", stats.skipped_ignored);
+    println!("  Elapsed:             {:?}", stats.elapsed);
+}
@@ -2,15 +2,47 @@
 //!
 //! Implements `search` command for finding configuration values
 
+use crate::{
+    bulk,
+    managers::config_manager_in,
+    output::{format_porcelain, query_match_records, search_record},
+};
 use anyhow::Result;
 use clap::Parser;
-use claude_config_manager_core::{types::ConfigScope, ConfigManager, SearchOptions};
+use claude_config_manager_core::{
+    types::ConfigScope, ConfigManager, ConfigSearcher, QueryClause, QueryMatch, SearchOptions,
+    SearchResult,
+};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+/// Which configuration `search` looks in
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Default)]
+pub enum SearchScope {
+    /// The global config only
+    Global,
+    /// The project config only
+    Project,
+    /// The effective (merged) config, labelling each hit with its source
+    #[default]
+    All,
+}
 
 /// Search command arguments
 #[derive(Parser, Debug)]
 pub struct SearchArgs {
     /// Search query
-    query: String,
+    #[arg(
+        required_unless_present = "where_clauses",
+        conflicts_with = "where_clauses"
+    )]
+    query: Option<String>,
+
+    /// Structured query clause; repeat to require several in the same
+    /// server, skill or other entry (e.g. --where value~npx --where key~GITHUB).
+    /// Operators: ~ (contains), = (exact), =~ (regex)
+    #[arg(long = "where", value_name = "CLAUSE")]
+    where_clauses: Vec<QueryClause>,
 
     /// Search in values instead of keys
     #[arg(short, long)]
@@ -24,32 +56,61 @@ pub struct SearchArgs {
     #[arg(short = 'c', long)]
     case_sensitive: bool,
 
+    /// Match the query only as a whole word (`npx` doesn't match `npx-extra`)
+    #[arg(short = 'w', long)]
+    whole_word: bool,
+
     /// Maximum search depth
     #[arg(short = 'd', long)]
     depth: Option<usize>,
 
-    /// Search in global config
-    #[arg(long)]
+    /// Config to search: the effective merged view (all), or one scope
+    #[arg(long, value_enum, default_value_t = SearchScope::All)]
+    scope: SearchScope,
+
+    /// Search in global config only (same as --scope global)
+    #[arg(long, conflicts_with_all = ["scope", "project"])]
     global: bool,
 
-    /// Search in project config
-    #[arg(long)]
+    /// Search in project config only (same as --scope project)
+    #[arg(long, conflicts_with = "scope")]
     project: bool,
 
+    /// Search the configs of every project found under --path
+    #[arg(long)]
+    all_projects: bool,
+
+    /// Directory to scan with --all-projects (default: current directory)
+    #[arg(long, requires = "all_projects")]
+    path: Option<PathBuf>,
+
+    /// With --all-projects, abort if any project config cannot be read
+    #[arg(long, requires = "all_projects")]
+    strict: bool,
+
+    /// With --all-projects, also search archived projects
+    #[arg(long, requires = "all_projects")]
+    include_archived: bool,
+
     /// Show detailed output
     #[arg(long)]
     verbose: bool,
+
+    /// Print the stable, tab-separated porcelain v1 format for scripts
+    #[arg(long, conflicts_with = "verbose")]
+    porcelain: bool,
 }
 
 impl SearchArgs {
     /// Execute the search command
     pub fn execute(&self) -> Result<()> {
         let backup_dir = claude_config_manager_core::paths::get_backup_dir();
-        let manager = ConfigManager::new(&backup_dir);
+        let manager = config_manager_in(&backup_dir);
 
         // Build search options
         let mut options = SearchOptions::new()
             .with_case_sensitive(self.case_sensitive)
+            .with_whole_word(self.whole_word)
             .with_max_depth(self.depth);
 
         if self.value {
@@ -59,38 +120,199 @@ impl SearchArgs {
         }
         // default is keys only
 
-        // Determine scope
-        let scope = if self.global {
-            ConfigScope::Global
+        if !self.where_clauses.is_empty() {
+            let options = self
+                .where_clauses
+                .iter()
+                .cloned()
+                .fold(options, SearchOptions::with_clause);
+            return self.execute_where(&manager, options);
+        }
+
+        let query = self.query.as_deref().unwrap_or_default();
+
+        if self.all_projects {
+            return self.search_all_projects(&manager, options);
+        }
+
+        // Perform search
+        let results = match self.effective_scope() {
+            SearchScope::Global => {
+                manager.search_config_with_options(query, ConfigScope::Global, options)?
+            }
+            SearchScope::Project => {
+                manager.search_config_with_options(query, ConfigScope::Project, options)?
+            }
+            SearchScope::All => manager.search_effective_with_options(query, None, options)?,
+        };
+
+        self.print_results(&results);
+
+        Ok(())
+    }
+
+    /// Scope selected by `--scope`, `--global` or `--project`
+    fn effective_scope(&self) -> SearchScope {
+        if self.global {
+            SearchScope::Global
         } else if self.project {
-            ConfigScope::Project
+            SearchScope::Project
         } else {
-            // Default: try project first, then global
-            ConfigScope::Project
+            self.scope
+        }
+    }
+
+    /// Search every project config under the scan path
+    ///
+    /// Unreadable configs are reported at the end instead of aborting the
+    /// search, unless `--strict` is given.
+    fn search_all_projects(&self, manager: &ConfigManager, options: SearchOptions) -> Result<()> {
+        let scan_path = self.path.clone().unwrap_or_else(|| PathBuf::from("."));
+        let projects = bulk::project_scanner(None)?.scan_directory(&scan_path)?;
+
+        let configs = manager.read_configs(bulk::config_paths(&projects, self.include_archived));
+        bulk::check_strict(&configs, self.strict)?;
+
+        let searcher = ConfigSearcher::with_options(options);
+        let mut results = Vec::new();
+        for (config_path, config) in &configs.successes {
+            results.extend(searcher.search(
+                self.query.as_deref().unwrap_or_default(),
+                config,
+                ConfigScope::Project,
+                config_path.clone(),
+            )?);
+        }
+
+        self.print_results(&results);
+        bulk::finish(&configs);
+
+        Ok(())
+    }
+
+    /// Find entries matching all `--where` clauses
+    fn execute_where(&self, manager: &ConfigManager, options: SearchOptions) -> Result<()> {
+        if self.all_projects {
+            return self.search_all_projects_where(manager, options);
+        }
+
+        let scope = match self.effective_scope() {
+            SearchScope::Global => Some(ConfigScope::Global),
+            SearchScope::Project => Some(ConfigScope::Project),
+            SearchScope::All => None,
         };
+        let mut results = manager.search_all_where(None, options)?;
+        results.retain(|result| scope.map_or(true, |scope| result.source == scope));
 
-        // Perform search
-        let results = manager.search_config_with_options(&self.query, scope, options)?;
+        self.print_matches(&results);
+
+        Ok(())
+    }
+
+    /// Evaluate the `--where` clauses on every project config under the scan path
+    fn search_all_projects_where(
+        &self,
+        manager: &ConfigManager,
+        options: SearchOptions,
+    ) -> Result<()> {
+        let scan_path = self.path.clone().unwrap_or_else(|| PathBuf::from("."));
+        let projects = bulk::project_scanner(None)?.scan_directory(&scan_path)?;
+
+        let configs = manager.read_configs(bulk::config_paths(&projects, self.include_archived));
+        bulk::check_strict(&configs, self.strict)?;
+
+        let searcher = ConfigSearcher::with_options(options);
+        let mut results = Vec::new();
+        for (config_path, config) in &configs.successes {
+            results.extend(searcher.search_where(
+                config,
+                ConfigScope::Project,
+                config_path.clone(),
+            )?);
+        }
+
+        self.print_matches(&results);
+        bulk::finish(&configs);
+
+        Ok(())
+    }
+
+    /// The `--where` clauses as one line, e.g. `value~npx AND key~GITHUB`
+    fn describe_where(&self) -> String {
+        self.where_clauses
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(" AND ")
+    }
+
+    /// Print structured query results
+    fn print_matches(&self, results: &[QueryMatch]) {
+        if self.porcelain {
+            print!(
+                "{}",
+                format_porcelain(results.iter().flat_map(query_match_records))
+            );
+            return;
+        }
 
-        // Display results
+        let query = self.describe_where();
         if results.is_empty() {
-            println!("No matches found for '{}'", self.query);
-            return Ok(());
+            println!("No matches found for {query}");
+            return;
         }
 
-        println!("Found {} result(s) for '{}':\n", results.len(), self.query);
+        println!("Found {} match(es) for {query}:", results.len());
 
-        for (index, result) in results.iter().enumerate() {
-            if self.verbose {
-                println!("  [{}] {}", index + 1, result.format());
-                println!("      Type: {}", result.value_type_label());
-                println!("      Config: {}", result.config_path.display());
-            } else {
-                println!("  [{}] {}", index + 1, result.format());
+        let mut grouped: BTreeMap<ConfigScope, Vec<&QueryMatch>> = BTreeMap::new();
+        for result in results {
+            grouped.entry(result.source).or_default().push(result);
+        }
+
+        let mut index = 0;
+        for (scope, scope_results) in &grouped {
+            println!("\n{} ({}):", scope.display_name(), scope_results.len());
+            for result in scope_results {
+                index += 1;
+                println!("  [{index}] {}", result.root);
+                for hit in &result.matches {
+                    println!("      {}: {} = {}", hit.clause, hit.key_path, hit.text);
+                }
+                if self.verbose {
+                    println!("      Config: {}", result.config_path.display());
+                }
             }
         }
+    }
 
-        Ok(())
+    /// Print search results
+    fn print_results(&self, results: &[SearchResult]) {
+        if self.porcelain {
+            print!("{}", format_porcelain(results.iter().map(search_record)));
+            return;
+        }
+
+        let query = self.query.as_deref().unwrap_or_default();
+        if results.is_empty() {
+            println!("No matches found for '{query}'");
+            return;
+        }
+
+        println!("Found {} result(s) for '{query}':", results.len());
+
+        let grouped = ConfigSearcher::group_by_scope(results.iter().cloned());
+        let mut index = 0;
+        for (scope, scope_results) in &grouped {
+            println!("\n{} ({}):", scope.display_name(), scope_results.len());
+            for result in scope_results {
+                index += 1;
+                println!("  [{index}] {}", result.format());
+                if self.verbose {
+                    println!("      Type: {}", result.value_type_label());
+                    println!("      Config: {}", result.config_path.display());
+                }
+            }
+        }
     }
 }
 
@@ -101,19 +323,28 @@ mod tests {
     #[test]
     fn test_search_args_builder() {
         let args = SearchArgs {
-            query: "test".to_string(),
+            query: Some("test".to_string()),
+            where_clauses: Vec::new(),
             value: false,
             both: false,
             case_sensitive: true,
+            whole_word: false,
             depth: Some(5),
+            scope: SearchScope::All,
             global: true,
             project: false,
+            all_projects: false,
+            path: None,
+            strict: false,
+            include_archived: false,
             verbose: false,
+            porcelain: false,
         };
 
-        assert_eq!(args.query, "test");
+        assert_eq!(args.query.as_deref(), Some("test"));
         assert!(args.case_sensitive);
         assert_eq!(args.depth, Some(5));
         assert!(args.global);
+        assert_eq!(args.effective_scope(), SearchScope::Global);
     }
 }
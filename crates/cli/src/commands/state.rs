@@ -0,0 +1,57 @@
+//! State command implementation
+//!
+//! Manages per-project UI state stored outside of configuration files
+
+use anyhow::Result;
+use clap::{Parser, Subcommand};
+use claude_config_manager_core::{paths::get_state_dir, StateStore};
+use std::path::PathBuf;
+
+/// UI state management commands
+#[derive(Parser, Debug)]
+pub struct StateArgs {
+    #[command(subcommand)]
+    command: StateCommand,
+}
+
+/// UI state management commands
+#[derive(Subcommand, Debug)]
+pub enum StateCommand {
+    /// Clear stored UI state
+    Clear {
+        /// Project path or @alias (clears state for all projects if omitted)
+        #[arg(short, long, value_parser = crate::project_arg::parse_project)]
+        project: Option<PathBuf>,
+    },
+
+    /// Remove UI state for projects that no longer exist
+    Gc,
+}
+
+impl StateArgs {
+    /// Execute the state command
+    pub fn execute(&self) -> Result<()> {
+        let store = StateStore::new(get_state_dir());
+
+        match &self.command {
+            StateCommand::Clear { project } => {
+                if let Some(project) = project {
+                    if store.clear(project)? {
+                        println!("Cleared UI state for {}", project.display());
+                    } else {
+                        println!("No UI state stored for {}", project.display());
+                    }
+                } else {
+                    let removed = store.clear_all()?;
+                    println!("Cleared UI state for {removed} project(s)");
+                }
+            }
+            StateCommand::Gc => {
+                let removed = store.gc()?;
+                println!("Removed {removed} stale state file(s)");
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,89 @@
+//! Stats command implementation
+//!
+//! Summarizes the current configuration and, optionally, local usage counts
+
+use crate::managers::config_manager;
+use anyhow::Result;
+use clap::Parser;
+use claude_config_manager_core::{format_timestamp, paths::get_usage_path, UsageTracker};
+use std::path::PathBuf;
+
+/// Configuration and usage statistics
+#[derive(Parser, Debug)]
+pub struct StatsArgs {
+    /// Project path or @alias (default: auto-detect)
+    #[arg(short, long, value_parser = crate::project_arg::parse_project)]
+    project: Option<PathBuf>,
+
+    /// Show local command usage counts (requires "usageTracking" in ccm settings)
+    #[arg(long)]
+    usage: bool,
+}
+
+impl StatsArgs {
+    /// Execute the stats command
+    pub fn execute(&self) -> Result<()> {
+        if self.usage {
+            self.show_usage()
+        } else {
+            self.show_config_stats()
+        }
+    }
+
+    /// Show counts of configured items
+    fn show_config_stats(&self) -> Result<()> {
+        let manager = config_manager();
+        let config = manager.get_merged_config(self.project.as_deref())?;
+
+        let servers = config.mcp_servers.unwrap_or_default();
+        let enabled = servers.values().filter(|s| s.enabled).count();
+
+        println!("Configuration summary:");
+        println!(
+            "  MCP servers:         {} ({enabled} enabled)",
+            servers.len()
+        );
+        println!(
+            "  Skills:              {}",
+            config.skills.map_or(0, |s| s.len())
+        );
+        println!(
+            "  Allowed paths:       {}",
+            config.allowed_paths.map_or(0, |p| p.len())
+        );
+        println!(
+            "  Custom instructions: {}",
+            config.custom_instructions.map_or(0, |i| i.len())
+        );
+
+        Ok(())
+    }
+
+    /// Show local usage counts
+    fn show_usage(&self) -> Result<()> {
+        let tracker = UsageTracker::new(get_usage_path());
+        let summary = tracker.load()?;
+
+        if summary.commands.is_empty() {
+            println!("No usage recorded.");
+            println!("Enable local tracking by setting \"ccm\": {{\"usageTracking\": true}} in the global config.");
+            return Ok(());
+        }
+
+        let mut commands: Vec<_> = summary.commands.iter().collect();
+        commands.sort_by(|a, b| b.1.count.cmp(&a.1.count).then_with(|| a.0.cmp(b.0)));
+
+        println!("{:<24} {:>6}  LAST USED", "COMMAND", "COUNT");
+        for (name, usage) in commands {
+            println!(
+                "{:<24} {:>6}  {}",
+                name,
+                usage.count,
+                format_timestamp(&usage.last_used)
+            );
+        }
+        println!("\nStored locally in {}", tracker.path().display());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,98 @@
+//! Support bundle command implementation
+//!
+//! Gathers redacted configs and diagnostics into a zip for bug reports
+
+use anyhow::Result;
+use clap::Parser;
+use claude_config_manager_core::{
+    paths::{get_audit_log_path, get_log_path},
+    support, SupportOptions,
+};
+use std::io::Write;
+use std::path::PathBuf;
+
+/// Support bundle generation
+#[derive(Parser, Debug)]
+pub struct SupportBundleArgs {
+    /// Output archive path (default: ccm-support-<timestamp>.zip)
+    #[arg(short, long)]
+    out: Option<PathBuf>,
+
+    /// Also include this project's config (redacted; path or @alias)
+    #[arg(short, long, value_parser = crate::project_arg::parse_project)]
+    project: Option<PathBuf>,
+
+    /// Don't ask for confirmation before including the project config
+    #[arg(short, long)]
+    yes: bool,
+}
+
+impl SupportBundleArgs {
+    /// Execute the support-bundle command
+    pub fn execute(&self) -> Result<()> {
+        let mut options = SupportOptions {
+            journal_path: Some(get_audit_log_path()),
+            log_path: get_log_path(),
+            ..Default::default()
+        };
+
+        if let Some(project) = &self.project {
+            if self.yes || confirm_project(project)? {
+                options.project_path = Some(project.clone());
+            } else {
+                println!("Project config will not be included.");
+            }
+        }
+
+        let bundle = support::collect(&options)?;
+
+        let out = self.out.clone().unwrap_or_else(|| {
+            PathBuf::from(format!(
+                "ccm-support-{}.zip",
+                chrono::Utc::now().format("%Y%m%d-%H%M%S")
+            ))
+        });
+        let written = bundle.write_zip(&out)?;
+
+        println!("Support bundle written to: {}", written.display());
+        println!("\nIncluded:");
+        for entry in &bundle.manifest.files {
+            println!("  {:<22} {}", entry.name, entry.description);
+        }
+
+        if !bundle.manifest.redactions.is_empty() {
+            println!("\nRedacted:");
+            for redaction in &bundle.manifest.redactions {
+                for key_path in &redaction.key_paths {
+                    println!("  {}: {key_path}", redaction.file);
+                }
+            }
+        }
+
+        if !bundle.manifest.omitted.is_empty() {
+            println!("\nNot included:");
+            for omitted in &bundle.manifest.omitted {
+                println!("  {omitted}");
+            }
+        }
+
+        println!("\nPlease review the archive before attaching it to a bug report.");
+
+        Ok(())
+    }
+}
+
+/// Ask whether the project config may be included
+fn confirm_project(project: &std::path::Path) -> Result<bool> {
+    print!(
+        "Include the (redacted) config for {}? [y/N] ",
+        project.display()
+    );
+    std::io::stdout().flush()?;
+
+    let mut input = String::new();
+    std::io::stdin().read_line(&mut input)?;
+
+    let input = input.trim().to_lowercase();
+    Ok(input == "y" || input == "yes")
+}
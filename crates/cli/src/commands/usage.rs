@@ -0,0 +1,65 @@
+//! Usage command implementation
+//!
+//! Manages the local usage statistics file
+
+use anyhow::Result;
+use clap::{Parser, Subcommand};
+use claude_config_manager_core::{paths::get_usage_path, CcmSettings, UsageTracker};
+
+/// Local usage statistics commands
+#[derive(Parser, Debug)]
+pub struct UsageArgs {
+    #[command(subcommand)]
+    command: UsageCommand,
+}
+
+/// Local usage statistics commands
+#[derive(Subcommand, Debug)]
+pub enum UsageCommand {
+    /// Delete all recorded usage statistics
+    Reset,
+}
+
+impl UsageArgs {
+    /// Execute the usage command
+    pub fn execute(&self) -> Result<()> {
+        let tracker = UsageTracker::new(get_usage_path());
+
+        match self.command {
+            UsageCommand::Reset => {
+                if tracker.reset()? {
+                    println!("Usage statistics cleared.");
+                } else {
+                    println!("No usage statistics recorded.");
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Record a command use if local usage tracking is enabled
+///
+/// Best effort: failures are logged and never affect the command itself.
+pub fn record_usage(command: &str) {
+    let global_path = claude_config_manager_core::get_global_config_path();
+
+    // Avoid any further work when there is no global config to enable tracking
+    let Ok(content) = std::fs::read_to_string(&global_path) else {
+        return;
+    };
+    // Tracking is off by default, so a config that never mentions it needs
+    // no parsing at all; otherwise only the ccm section is read
+    if !content.contains("usageTracking") {
+        return;
+    }
+    let Ok(settings) = CcmSettings::from_config_json(&content) else {
+        return;
+    };
+
+    let tracker = UsageTracker::new(get_usage_path());
+    if let Err(e) = tracker.record_if_enabled(&settings, command) {
+        tracing::debug!("Failed to record usage: {e}");
+    }
+}
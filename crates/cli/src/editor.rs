@@ -0,0 +1,109 @@
+//! Launching the user's editor
+//!
+//! The editor comes from `$VISUAL`, then `$EDITOR`. Either may include
+//! arguments (e.g. `code --wait`), quoted as in a shell.
+
+use anyhow::{Context, Result};
+use std::io::{BufRead, Write};
+use std::path::Path;
+use std::process::Command;
+
+/// Editor configured in `$VISUAL` or `$EDITOR`, as program and arguments
+///
+/// # Errors
+/// Returns an error if the variable's quoting is unbalanced
+pub fn configured_editor() -> Result<Option<(String, Vec<String>)>> {
+    for var in ["VISUAL", "EDITOR"] {
+        let Ok(value) = std::env::var(var) else {
+            continue;
+        };
+        if let Some(editor) = parse_editor(&value).with_context(|| format!("Invalid ${var}"))? {
+            return Ok(Some(editor));
+        }
+    }
+    Ok(None)
+}
+
+/// Split an editor command into program and arguments using shell quoting
+fn parse_editor(value: &str) -> Result<Option<(String, Vec<String>)>> {
+    let mut words = shell_words::split(value)?.into_iter();
+    Ok(words.next().map(|program| (program, words.collect())))
+}
+
+/// The platform's default text editor, as program and arguments
+///
+/// `xdg-open` hands the file to the desktop and returns before the editor is
+/// closed, so it's the only one that doesn't wait on its own.
+fn platform_editor() -> ((String, Vec<String>), bool) {
+    if cfg!(target_os = "macos") {
+        (
+            ("open".to_string(), vec!["-W".to_string(), "-t".to_string()]),
+            true,
+        )
+    } else if cfg!(windows) {
+        (("notepad".to_string(), Vec::new()), true)
+    } else {
+        (("xdg-open".to_string(), Vec::new()), false)
+    }
+}
+
+/// Open `path` in the configured editor, or the platform default, and wait
+/// for it to exit
+///
+/// When the platform default returns immediately, waits for the user to press
+/// Enter instead so the file isn't read back while it's still being edited.
+///
+/// # Errors
+/// Returns an error if the editor can't be started or exits unsuccessfully
+pub fn edit_file(path: &Path) -> Result<()> {
+    if let Some(editor) = configured_editor()? {
+        return run_editor(editor, path);
+    }
+
+    let (editor, waits) = platform_editor();
+    run_editor(editor, path)?;
+    if !waits {
+        print!(
+            "Press Enter when you have finished editing {}: ",
+            path.display()
+        );
+        std::io::stdout().flush()?;
+        let mut line = String::new();
+        std::io::stdin().lock().read_line(&mut line)?;
+    }
+    Ok(())
+}
+
+/// Run `editor` (program and arguments) on `path` and wait for it to exit
+///
+/// # Errors
+/// Returns an error if the editor can't be started or exits unsuccessfully
+pub fn run_editor((program, args): (String, Vec<String>), path: &Path) -> Result<()> {
+    let status = Command::new(&program)
+        .args(&args)
+        .arg(path)
+        .status()
+        .with_context(|| format!("Failed to start editor '{program}'"))?;
+
+    if !status.success() {
+        anyhow::bail!("Editor '{program}' exited with {status}");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_editor_honors_quoting() {
+        let (program, args) = parse_editor(r#""/opt/My Editor/bin/edit" --wait 'a b'"#)
+            .unwrap()
+            .unwrap();
+        assert_eq!(program, "/opt/My Editor/bin/edit");
+        assert_eq!(args, vec!["--wait", "a b"]);
+
+        assert!(parse_editor("   ").unwrap().is_none());
+        assert!(parse_editor("vim 'unterminated").is_err());
+    }
+}
@@ -0,0 +1,68 @@
+//! File arguments that accept `-` for standard input or output
+//!
+//! Lets commands take part in pipelines, e.g.
+//! `ccm config export - | jq '.mcpServers'`.
+
+use std::convert::Infallible;
+use std::fmt;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+/// A file path, or `-` for stdin/stdout
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FileArg {
+    /// A file on disk
+    Path(PathBuf),
+    /// Standard input (when reading) or standard output (when writing)
+    Stdio,
+}
+
+impl FromStr for FileArg {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(if s == "-" {
+            FileArg::Stdio
+        } else {
+            FileArg::Path(PathBuf::from(s))
+        })
+    }
+}
+
+impl fmt::Display for FileArg {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FileArg::Path(path) => write!(f, "{}", path.display()),
+            FileArg::Stdio => write!(f, "-"),
+        }
+    }
+}
+
+/// Format of imported or exported configuration
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum FileFormat {
+    /// JSON
+    Json,
+    /// TOML (import only)
+    Toml,
+    /// YAML (import only)
+    Yaml,
+}
+
+impl FileFormat {
+    /// clap value parser for `--format` on exports, which are JSON only
+    pub fn export_parser() -> impl clap::builder::TypedValueParser<Value = Self> {
+        use clap::builder::TypedValueParser;
+        clap::builder::PossibleValuesParser::new(["json"]).map(|_| Self::Json)
+    }
+}
+
+impl From<FileFormat> for claude_config_manager_core::ExportFormat {
+    fn from(format: FileFormat) -> Self {
+        match format {
+            FileFormat::Json => Self::Json,
+            FileFormat::Toml => Self::Toml,
+            FileFormat::Yaml => Self::Yaml,
+        }
+    }
+}
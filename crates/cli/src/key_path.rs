@@ -1,42 +1,141 @@
 //! Key path parsing and manipulation
 //!
-//! Supports dot-notation key paths like "mcpServers.npx.enabled"
+//! Supports dot-notation key paths like "mcpServers.npx.enabled". The
+//! string-array fields (`allowedPaths`, `customInstructions`,
+//! `mcpServers.<name>.args` and `mcpServers.<name>.platforms`) also take a
+//! trailing index: `[N]` names an existing element and `[+]` the position
+//! after the last one.
+//!
+//! Unknown fields and skill parameters are free-form JSON: any nested key
+//! path (`telemetry.level`) can be set, and indexes may appear anywhere in
+//! it (`telemetry.tags[0]`).
 
 use anyhow::Result;
-use claude_config_manager_core::ClaudeConfig;
+use claude_config_manager_core::config::key_path::{self, split_index, ArrayIndex};
+use claude_config_manager_core::{ClaudeConfig, ValueType};
 use serde_json::Value;
 
+/// How [`set_value_by_path`] treats array-valued keys
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SetMode {
+    /// Replace the current value
+    #[default]
+    Replace,
+
+    /// Push the value (or each element of an array value) onto the array
+    Append {
+        /// Skip elements that are already present
+        dedupe: bool,
+    },
+}
+
 /// Parse and set a value using a key path
 ///
+/// Values for known fields are coerced to the field's type with
+/// [`coerce_value`]; values for unknown fields are parsed as JSON, falling
+/// back to a plain string.
+///
+/// An indexed key path sets one element of a string-array field:
+/// `customInstructions[2]` replaces the existing third element and
+/// `customInstructions[+]` appends. Indexes past the end are rejected, so
+/// arrays never get gaps.
+///
 /// # Arguments
 /// * `config` - The configuration to modify
 /// * `key_path` - Dot-separated key path (e.g., "mcpServers.npx.enabled")
 /// * `value` - The value to set (as JSON string)
-pub fn set_value_by_path(config: &mut ClaudeConfig, key_path: &str, value: &str) -> Result<()> {
+/// * `mode` - Replace the value, or append to an array-valued key
+///
+/// # Errors
+/// In append mode, returns an error if the key is not array-valued. For an
+/// indexed key path, returns an error if the field isn't a string array or
+/// the index is out of bounds.
+pub fn set_value_by_path(
+    config: &mut ClaudeConfig,
+    key_path: &str,
+    value: &str,
+    mode: SetMode,
+) -> Result<()> {
+    let (base, index) = split_index(key_path)?;
+    match (index, mode) {
+        (None, _) => {}
+        (Some(ArrayIndex::End), SetMode::Append { dedupe }) => {
+            let keys: Vec<&str> = base.split('.').collect();
+            return append_value_by_path(config, &keys, value, dedupe);
+        }
+        (Some(ArrayIndex::At(_)), SetMode::Append { .. }) => {
+            anyhow::bail!("--append can't be combined with an index; use '{base}[+]' to append")
+        }
+        (Some(_), SetMode::Replace) if is_free_form(base) => {}
+        (Some(index), SetMode::Replace) => return set_element(config, base, index, value),
+    }
+
     let keys: Vec<&str> = key_path.split('.').collect();
 
     if keys.is_empty() {
         anyhow::bail!("Key path cannot be empty");
     }
 
-    // Parse the value as JSON
-    let parsed_value = parse_value(value)?;
+    if let SetMode::Append { dedupe } = mode {
+        return append_value_by_path(config, &keys, value, dedupe);
+    }
 
     // Special handling for known top-level keys
     match keys[0] {
-        "mcpServers" => set_mcp_server_value(config, &keys[1..], parsed_value)?,
-        "allowedPaths" => set_allowed_paths_value(config, &keys[1..], parsed_value)?,
-        "skills" => set_skill_value(config, &keys[1..], parsed_value)?,
-        "customInstructions" => set_custom_instruction_value(config, &keys[1..], parsed_value)?,
+        "mcpServers" => set_mcp_server_value(config, &keys[1..], value)?,
+        "allowedPaths" => set_allowed_paths_value(config, &keys[1..], value)?,
+        "skills" => set_skill_value(config, &keys[1..], value)?,
+        "customInstructions" => set_custom_instruction_value(config, &keys[1..], value)?,
         _ => {
             // Unknown field - add to unknown map
-            set_unknown_value(config, &keys, parsed_value)?;
+            set_unknown_value(config, key_path, parse_value(value)?)?;
         }
     }
 
     Ok(())
 }
 
+/// Coerce a raw command-line value to the expected type
+///
+/// - Boolean: `true`/`false`, `yes`/`no`, `on`/`off` or `1`/`0` (any case)
+/// - Number: any JSON number
+/// - String: the raw text; a quoted JSON string is unquoted
+/// - Array, Object, Null: the matching JSON value
+///
+/// # Errors
+/// Returns an error naming the expected type if the value doesn't fit
+pub fn coerce_value(raw: &str, expected: ValueType) -> Result<Value> {
+    let trimmed = raw.trim();
+
+    match expected {
+        ValueType::Boolean => match trimmed.to_ascii_lowercase().as_str() {
+            "true" | "yes" | "on" | "1" => Ok(Value::Bool(true)),
+            "false" | "no" | "off" | "0" => Ok(Value::Bool(false)),
+            _ => anyhow::bail!("Expected a boolean (true/false, yes/no, on/off, 1/0), got '{raw}'"),
+        },
+        ValueType::Number => match serde_json::from_str::<Value>(trimmed) {
+            Ok(number @ Value::Number(_)) => Ok(number),
+            _ => anyhow::bail!("Expected a number, got '{raw}'"),
+        },
+        ValueType::String => match serde_json::from_str::<Value>(trimmed) {
+            Ok(string @ Value::String(_)) if trimmed.starts_with('"') => Ok(string),
+            _ => Ok(Value::String(raw.to_string())),
+        },
+        ValueType::Array => match serde_json::from_str::<Value>(trimmed) {
+            Ok(array @ Value::Array(_)) => Ok(array),
+            _ => anyhow::bail!("Expected a JSON array, got '{raw}'"),
+        },
+        ValueType::Object => match serde_json::from_str::<Value>(trimmed) {
+            Ok(object @ Value::Object(_)) => Ok(object),
+            _ => anyhow::bail!("Expected a JSON object, got '{raw}'"),
+        },
+        ValueType::Null => match trimmed {
+            "null" => Ok(Value::Null),
+            _ => anyhow::bail!("Expected null, got '{raw}'"),
+        },
+    }
+}
+
 /// Parse a value string as JSON
 fn parse_value(value: &str) -> Result<Value> {
     // Try to parse as JSON first
@@ -48,21 +147,94 @@ fn parse_value(value: &str) -> Result<Value> {
     Ok(Value::String(value.to_string()))
 }
 
+/// Whether a raw value is written as a JSON array
+fn is_array_literal(raw: &str) -> bool {
+    raw.trim_start().starts_with('[')
+}
+
+/// Coerce a raw value to a list of strings
+///
+/// Non-string elements are coerced to strings the same way as scalars.
+fn coerce_string_array(raw: &str, field: &str) -> Result<Vec<String>> {
+    let Value::Array(items) = coerce_value(raw, ValueType::Array)? else {
+        unreachable!("array coercion yields an array");
+    };
+
+    items
+        .into_iter()
+        .map(|item| match item {
+            Value::String(s) => Ok(s),
+            Value::Number(n) => Ok(n.to_string()),
+            Value::Bool(b) => Ok(b.to_string()),
+            other => anyhow::bail!("'{field}' must contain only strings, got {other}"),
+        })
+        .collect()
+}
+
+/// Coerce a raw value to a boolean
+fn coerce_bool(raw: &str, field: &str) -> Result<bool> {
+    coerce_value(raw, ValueType::Boolean)
+        .map(|value| value.as_bool().unwrap_or_default())
+        .map_err(|e| anyhow::anyhow!("'{field}' must be a boolean value: {e}"))
+}
+
+/// Coerce a raw value to a string
+fn coerce_string(raw: &str) -> Result<String> {
+    Ok(coerce_value(raw, ValueType::String)?
+        .as_str()
+        .unwrap_or_default()
+        .to_string())
+}
+
+/// Whether a string is empty or whitespace-only
+fn is_blank(s: &str) -> bool {
+    s.trim().is_empty()
+}
+
+/// Reject empty or whitespace-only elements of fields where they are meaningless
+///
+/// Applies to `allowedPaths` and `customInstructions`; other fields accept
+/// any elements.
+fn check_elements(field: &str, items: &[String]) -> Result<()> {
+    let message = match field {
+        "allowedPaths" => {
+            "allowedPaths entries can't be empty or whitespace-only; use --remove to drop a path"
+        }
+        "customInstructions" => {
+            "Instructions can't be empty or whitespace-only; use 'ccm instructions remove' to drop one"
+        }
+        _ => return Ok(()),
+    };
+
+    if items.iter().any(|item| is_blank(item)) {
+        anyhow::bail!("{message}");
+    }
+    Ok(())
+}
+
 /// Set a value in the mcpServers section
-fn set_mcp_server_value(config: &mut ClaudeConfig, keys: &[&str], value: Value) -> Result<()> {
+fn set_mcp_server_value(config: &mut ClaudeConfig, keys: &[&str], value: &str) -> Result<()> {
     if keys.is_empty() {
         anyhow::bail!("MCP server name is required");
     }
 
     let server_name = keys[0];
+    if is_blank(server_name) {
+        anyhow::bail!(
+            "MCP server name can't be empty or whitespace-only (e.g., 'mcpServers.fetch.command')"
+        );
+    }
 
     // Get or create the mcp_servers map
     let servers = config.mcp_servers.get_or_insert_with(Default::default);
 
-    // Get or create the server
-    let server = servers
-        .entry(server_name.to_string())
-        .or_insert_with(|| claude_config_manager_core::McpServer::new(server_name, "", vec![]));
+    // Get or create the server (without a command until one is set)
+    let server = servers.entry(server_name.to_string()).or_insert_with(|| {
+        claude_config_manager_core::McpServer {
+            command: None,
+            ..claude_config_manager_core::McpServer::new(server_name, "", vec![])
+        }
+    });
 
     // Set the specific field
     if keys.len() == 1 {
@@ -74,39 +246,61 @@ fn set_mcp_server_value(config: &mut ClaudeConfig, keys: &[&str], value: Value)
 
     match field {
         "enabled" => {
-            if let Some(bool_val) = value.as_bool() {
-                server.enabled = bool_val;
-            } else if let Some(string_val) = value.as_str() {
-                server.enabled = string_val.eq_ignore_ascii_case("true")
-                    || string_val.eq_ignore_ascii_case("yes")
-                    || string_val == "1";
-            } else {
-                anyhow::bail!("'enabled' must be a boolean value");
-            }
+            server.enabled = coerce_bool(value, field)?;
+        }
+        "command" if value.trim() == "null" => {
+            server.command = None;
         }
         "command" => {
-            if let Some(string_val) = value.as_str() {
-                server.command = Some(string_val.to_string());
-            } else {
-                anyhow::bail!("'command' must be a string");
+            let command = coerce_string(value)?;
+            if is_blank(&command) {
+                anyhow::bail!(
+                    "MCP server command can't be empty or whitespace-only; use null to clear it"
+                );
             }
+            server.command = Some(command);
         }
         "args" => {
-            match value {
-                Value::Array(arr) => {
-                    server.args = arr
-                        .iter()
-                        .filter_map(|v| v.as_str().map(|s| s.to_string()))
-                        .collect();
-                }
-                Value::String(s) => {
-                    // Split string by spaces
-                    server.args = s.split_whitespace().map(|s| s.to_string()).collect();
-                }
-                _ => {
-                    anyhow::bail!("'args' must be an array or a space-separated string");
-                }
-            }
+            server.args = if is_array_literal(value) {
+                coerce_string_array(value, field)?
+            } else {
+                // Split string by spaces
+                value.split_whitespace().map(|s| s.to_string()).collect()
+            };
+        }
+        "env" => {
+            let [var] = keys[2..] else {
+                anyhow::bail!(
+                    "Set one environment variable at a time (e.g., 'mcpServers.{server_name}.env.API_KEY')"
+                );
+            };
+            server.env.insert(var.to_string(), coerce_string(value)?);
+        }
+        "timeout" if value.trim() == "null" => {
+            server.timeout_ms = None;
+        }
+        "timeout" => {
+            let timeout = coerce_value(value, ValueType::Number)?;
+            server.timeout_ms = Some(timeout.as_u64().ok_or_else(|| {
+                anyhow::anyhow!("'timeout' must be a whole number of milliseconds, got '{value}'")
+            })?);
+        }
+        "platforms" if value.trim() == "null" => {
+            server.platforms = None;
+        }
+        "platforms" => {
+            server.platforms = Some(string_items(value, field)?);
+        }
+        "commandByPlatform" => {
+            let [platform] = keys[2..] else {
+                anyhow::bail!(
+                    "Set one platform's command at a time (e.g., 'mcpServers.{server_name}.commandByPlatform.windows')"
+                );
+            };
+            server
+                .command_by_platform
+                .get_or_insert_with(Default::default)
+                .insert(platform.to_string(), coerce_string(value)?);
         }
         _ => {
             anyhow::bail!("Unknown MCP server field: '{field}'");
@@ -117,32 +311,20 @@ fn set_mcp_server_value(config: &mut ClaudeConfig, keys: &[&str], value: Value)
 }
 
 /// Set a value in the allowedPaths section
-fn set_allowed_paths_value(config: &mut ClaudeConfig, keys: &[&str], value: Value) -> Result<()> {
+fn set_allowed_paths_value(config: &mut ClaudeConfig, keys: &[&str], value: &str) -> Result<()> {
     if !keys.is_empty() {
         anyhow::bail!("Nested paths in allowedPaths are not supported");
     }
 
-    match value {
-        Value::Array(arr) => {
-            config.allowed_paths = Some(
-                arr.iter()
-                    .filter_map(|v| v.as_str().map(|s| s.to_string()))
-                    .collect(),
-            );
-        }
-        Value::String(s) => {
-            config.allowed_paths = Some(vec![s]);
-        }
-        _ => {
-            anyhow::bail!("allowedPaths must be an array or string");
-        }
-    }
+    let paths = string_items(value, "allowedPaths")?;
+    check_elements("allowedPaths", &paths)?;
+    config.allowed_paths = Some(paths);
 
     Ok(())
 }
 
 /// Set a value in the skills section
-fn set_skill_value(config: &mut ClaudeConfig, keys: &[&str], value: Value) -> Result<()> {
+fn set_skill_value(config: &mut ClaudeConfig, keys: &[&str], value: &str) -> Result<()> {
     if keys.is_empty() {
         anyhow::bail!("Skill name is required");
     }
@@ -172,18 +354,16 @@ fn set_skill_value(config: &mut ClaudeConfig, keys: &[&str], value: Value) -> Re
 
     match field {
         "enabled" => {
-            if let Some(bool_val) = value.as_bool() {
-                skill.enabled = bool_val;
-            } else if let Some(string_val) = value.as_str() {
-                skill.enabled = string_val.eq_ignore_ascii_case("true")
-                    || string_val.eq_ignore_ascii_case("yes")
-                    || string_val == "1";
-            } else {
-                anyhow::bail!("'enabled' must be a boolean value");
-            }
+            skill.enabled = coerce_bool(value, field)?;
+        }
+        "parameters" if keys.len() > 2 => {
+            let parameters = skill
+                .parameters
+                .get_or_insert_with(|| Value::Object(Default::default()));
+            set_json_value(parameters, &keys[2..].join("."), parse_value(value)?)?;
         }
         "parameters" => {
-            skill.parameters = Some(value);
+            skill.parameters = Some(parse_value(value)?);
         }
         _ => {
             anyhow::bail!("Unknown skill field: '{field}'");
@@ -197,45 +377,390 @@ fn set_skill_value(config: &mut ClaudeConfig, keys: &[&str], value: Value) -> Re
 fn set_custom_instruction_value(
     config: &mut ClaudeConfig,
     keys: &[&str],
-    value: Value,
+    value: &str,
 ) -> Result<()> {
     if !keys.is_empty() {
         anyhow::bail!("Nested paths in customInstructions are not supported");
     }
 
+    let items = string_items(value, "customInstructions")?;
+    check_elements("customInstructions", &items)?;
+
     let instructions = config.custom_instructions.get_or_insert_with(Vec::new);
+    if is_array_literal(value) {
+        *instructions = items;
+    } else {
+        instructions.extend(items);
+    }
+
+    Ok(())
+}
 
-    match value {
-        Value::Array(arr) => {
-            *instructions = arr
-                .iter()
-                .filter_map(|v| v.as_str().map(|s| s.to_string()))
-                .collect();
+/// Append a value to an array-valued key
+fn append_value_by_path(
+    config: &mut ClaudeConfig,
+    keys: &[&str],
+    value: &str,
+    dedupe: bool,
+) -> Result<()> {
+    let key_path = keys.join(".");
+
+    match keys {
+        ["allowedPaths"] => {
+            let items = string_items(value, "allowedPaths")?;
+            check_elements("allowedPaths", &items)?;
+            extend(
+                config.allowed_paths.get_or_insert_with(Vec::new),
+                items,
+                dedupe,
+            );
         }
-        Value::String(s) => {
-            instructions.push(s);
+        ["customInstructions"] => {
+            let items = string_items(value, "customInstructions")?;
+            check_elements("customInstructions", &items)?;
+            extend(
+                config.custom_instructions.get_or_insert_with(Vec::new),
+                items,
+                dedupe,
+            );
         }
-        _ => {
-            anyhow::bail!("customInstructions must be an array or string");
+        ["mcpServers", server_name, "args"] => {
+            let server = config
+                .mcp_servers
+                .as_mut()
+                .and_then(|servers| servers.get_mut(*server_name))
+                .ok_or_else(|| anyhow::anyhow!("MCP server '{server_name}' not found"))?;
+            extend(&mut server.args, args_items(value)?, dedupe);
+        }
+        [field] if !is_known_field(field) => {
+            let items = match parse_value(value)? {
+                Value::Array(items) => items,
+                item => vec![item],
+            };
+            let current = config
+                .unknown
+                .entry(field.to_string())
+                .or_insert_with(|| Value::Array(Vec::new()));
+            match current {
+                Value::Array(array) => extend(array, items, dedupe),
+                _ => anyhow::bail!("'{key_path}' is not an array; --append only applies to arrays"),
+            }
         }
+        _ => anyhow::bail!("'{key_path}' is not an array field; --append only applies to arrays"),
     }
 
     Ok(())
 }
 
-/// Set a value in the unknown fields map
-fn set_unknown_value(config: &mut ClaudeConfig, keys: &[&str], value: Value) -> Result<()> {
-    if keys.is_empty() {
-        anyhow::bail!("Key path cannot be empty");
+/// Remove matching elements from an array-valued key
+///
+/// The value is coerced like an appended value; an array literal removes
+/// each of its elements.
+///
+/// # Arguments
+/// * `config` - The configuration to modify
+/// * `key_path` - Dot-separated key path (e.g., "allowedPaths")
+/// * `value` - The element(s) to remove
+///
+/// # Returns
+/// Number of elements removed (0 if the value wasn't present)
+///
+/// # Errors
+/// Returns an error if the key is not array-valued
+pub fn remove_value_by_path(
+    config: &mut ClaudeConfig,
+    key_path: &str,
+    value: &str,
+) -> Result<usize> {
+    let keys: Vec<&str> = key_path.split('.').collect();
+
+    let removed = match keys.as_slice() {
+        ["allowedPaths"] => {
+            let items = string_items(value, "allowedPaths")?;
+            config
+                .allowed_paths
+                .as_mut()
+                .map_or(0, |paths| remove_items(paths, &items))
+        }
+        ["customInstructions"] => {
+            let items = string_items(value, "customInstructions")?;
+            config
+                .custom_instructions
+                .as_mut()
+                .map_or(0, |instructions| remove_items(instructions, &items))
+        }
+        ["mcpServers", server_name, "args"] => {
+            let items = args_items(value)?;
+            config
+                .mcp_servers
+                .as_mut()
+                .and_then(|servers| servers.get_mut(*server_name))
+                .map_or(0, |server| remove_items(&mut server.args, &items))
+        }
+        [field] if !is_known_field(field) => {
+            let items = match parse_value(value)? {
+                Value::Array(items) => items,
+                item => vec![item],
+            };
+            match config.unknown.get_mut(*field) {
+                None => 0,
+                Some(Value::Array(array)) => remove_items(array, &items),
+                Some(_) => {
+                    anyhow::bail!("'{key_path}' is not an array; --remove only applies to arrays")
+                }
+            }
+        }
+        _ => anyhow::bail!("'{key_path}' is not an array field; --remove only applies to arrays"),
+    };
+
+    Ok(removed)
+}
+
+/// Replace or append one element of a string-array field
+fn set_element(
+    config: &mut ClaudeConfig,
+    key_path: &str,
+    index: ArrayIndex,
+    value: &str,
+) -> Result<()> {
+    let value = coerce_string(value)?;
+    check_elements(key_path, std::slice::from_ref(&value))?;
+    let array = string_array_mut(config, key_path)?;
+    Ok(key_path::set_at(array, key_path, index, value)?)
+}
+
+/// Insert an element into a string-array field, shifting later elements
+///
+/// # Arguments
+/// * `config` - The configuration to modify
+/// * `key_path` - Key path of the array, without an index (e.g., "customInstructions")
+/// * `index` - Position of the new element; the array's length appends
+/// * `value` - The element to insert
+///
+/// # Errors
+/// Returns an error if the field isn't a string array or `index` is greater
+/// than the array's length
+pub fn insert_value_by_path(
+    config: &mut ClaudeConfig,
+    key_path: &str,
+    index: usize,
+    value: &str,
+) -> Result<()> {
+    if split_index(key_path)?.1.is_some() {
+        anyhow::bail!("'{key_path}' already has an index; pass the array's key path");
     }
 
-    // For unknown fields, we only support top-level setting for now
-    if keys.len() > 1 {
-        anyhow::bail!("Nested paths for unknown fields are not supported");
+    let value = coerce_string(value)?;
+    check_elements(key_path, std::slice::from_ref(&value))?;
+    let array = string_array_mut(config, key_path)?;
+    Ok(key_path::insert_at(array, key_path, index, value)?)
+}
+
+/// Remove one element of a string-array field, shifting later elements
+///
+/// # Arguments
+/// * `config` - The configuration to modify
+/// * `key_path` - Indexed key path (e.g., "allowedPaths[1]")
+///
+/// # Returns
+/// The removed element
+///
+/// # Errors
+/// Returns an error if the key path has no `[N]` index, the field isn't a
+/// string array or the index is out of bounds
+pub fn remove_index_by_path(config: &mut ClaudeConfig, key_path: &str) -> Result<String> {
+    let (base, Some(index)) = split_index(key_path)? else {
+        anyhow::bail!("'{key_path}' has no index; write it as '{key_path}[N]'");
+    };
+
+    let array = string_array_mut(config, base)?;
+    Ok(key_path::remove_at(array, base, index)?)
+}
+
+/// Check that an indexed key path names an existing element
+///
+/// Key paths without an index, and indexes into fields that aren't string
+/// arrays, are left to the caller.
+///
+/// # Errors
+/// Returns an error naming the array's length if the index is out of bounds
+pub fn check_index(config: &ClaudeConfig, key_path: &str) -> Result<()> {
+    let (base, Some(index)) = split_index(key_path)? else {
+        return Ok(());
+    };
+    let len = match base.split('.').collect::<Vec<_>>().as_slice() {
+        ["allowedPaths"] => config.allowed_paths.as_ref().map_or(0, Vec::len),
+        ["customInstructions"] => config.custom_instructions.as_ref().map_or(0, Vec::len),
+        ["mcpServers", server_name, "args"] => config
+            .mcp_servers
+            .as_ref()
+            .and_then(|servers| servers.get(*server_name))
+            .map_or(0, |server| server.args.len()),
+        ["mcpServers", server_name, "platforms"] => config
+            .mcp_servers
+            .as_ref()
+            .and_then(|servers| servers.get(*server_name))
+            .and_then(|server| server.platforms.as_ref())
+            .map_or(0, Vec::len),
+        _ => return Ok(()),
+    };
+
+    Ok(key_path::check_at(len, base, index)?)
+}
+
+/// The string-array field at `key_path`, created empty if unset
+fn string_array_mut<'a>(
+    config: &'a mut ClaudeConfig,
+    key_path: &str,
+) -> Result<&'a mut Vec<String>> {
+    match key_path.split('.').collect::<Vec<_>>().as_slice() {
+        ["allowedPaths"] => Ok(config.allowed_paths.get_or_insert_with(Vec::new)),
+        ["customInstructions"] => Ok(config.custom_instructions.get_or_insert_with(Vec::new)),
+        ["mcpServers", server_name, "args"] => config
+            .mcp_servers
+            .as_mut()
+            .and_then(|servers| servers.get_mut(*server_name))
+            .map(|server| &mut server.args)
+            .ok_or_else(|| anyhow::anyhow!("MCP server '{server_name}' not found")),
+        ["mcpServers", server_name, "platforms"] => config
+            .mcp_servers
+            .as_mut()
+            .and_then(|servers| servers.get_mut(*server_name))
+            .map(|server| server.platforms.get_or_insert_with(Vec::new))
+            .ok_or_else(|| anyhow::anyhow!("MCP server '{server_name}' not found")),
+        _ => anyhow::bail!(
+            "'{key_path}' can't be indexed; indexes apply to allowedPaths, customInstructions, mcpServers.<name>.args and mcpServers.<name>.platforms"
+        ),
+    }
+}
+
+/// Remove every element equal to one of `items`, returning how many were removed
+fn remove_items<T: PartialEq>(array: &mut Vec<T>, items: &[T]) -> usize {
+    let before = array.len();
+    array.retain(|element| !items.contains(element));
+    before - array.len()
+}
+
+/// Whether a key path lies in free-form JSON: an unknown top-level field or
+/// a key inside a skill's parameters
+fn is_free_form(key_path: &str) -> bool {
+    let top = key_path.split(['.', '[']).next().unwrap_or_default();
+    !is_known_field(top)
+        || matches!(
+            key_path.split('.').collect::<Vec<_>>().as_slice(),
+            ["skills", _, "parameters", _, ..]
+        )
+}
+
+/// Whether a top-level key is one of the typed config fields
+fn is_known_field(key: &str) -> bool {
+    matches!(
+        key,
+        "mcpServers" | "allowedPaths" | "skills" | "customInstructions"
+    )
+}
+
+/// Coerce a raw value to the strings to append (one, or each array element)
+fn string_items(raw: &str, field: &str) -> Result<Vec<String>> {
+    if is_array_literal(raw) {
+        coerce_string_array(raw, field)
+    } else {
+        Ok(vec![coerce_string(raw)?])
+    }
+}
+
+/// Parse a value as server args: an array literal, or whitespace-separated
+fn args_items(raw: &str) -> Result<Vec<String>> {
+    if is_array_literal(raw) {
+        coerce_string_array(raw, "args")
+    } else {
+        Ok(raw.split_whitespace().map(|s| s.to_string()).collect())
+    }
+}
+
+/// Push items onto an array, optionally skipping ones already present
+fn extend<T: PartialEq>(array: &mut Vec<T>, items: Vec<T>, dedupe: bool) {
+    for item in items {
+        if !dedupe || !array.contains(&item) {
+            array.push(item);
+        }
+    }
+}
+
+/// Parse a settings file into (key path, raw value) pairs
+///
+/// Two forms are accepted:
+/// - A JSON object mapping key paths to values (`{"mcpServers.npx.enabled": false}`).
+///   String values are used as their text, other values as JSON. Entries
+///   come out sorted by key.
+/// - A `.env`-style list of `key=value` lines, in file order. Blank lines and
+///   lines starting with `#` are ignored; the value is everything after the
+///   first `=`.
+///
+/// The raw values are meant for [`set_value_by_path`].
+///
+/// # Errors
+/// Returns an error for invalid JSON, a JSON document that isn't an object,
+/// or a line without `=` or with an empty key
+pub fn parse_settings(content: &str) -> Result<Vec<(String, String)>> {
+    if content.trim_start().starts_with('{') {
+        let Value::Object(entries) = serde_json::from_str::<Value>(content)
+            .map_err(|e| anyhow::anyhow!("Invalid JSON settings: {e}"))?
+        else {
+            unreachable!("a document starting with '{{' is an object");
+        };
+        return Ok(entries
+            .into_iter()
+            .map(|(key, value)| {
+                let raw = match value {
+                    Value::String(text) => text,
+                    other => other.to_string(),
+                };
+                (key, raw)
+            })
+            .collect());
     }
 
-    config.unknown.insert(keys[0].to_string(), value);
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| {
+            let line = line.trim();
+            !line.is_empty() && !line.starts_with('#')
+        })
+        .map(|(index, line)| {
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| anyhow::anyhow!("Line {}: expected key=value", index + 1))?;
+            let key = key.trim();
+            if key.is_empty() {
+                anyhow::bail!("Line {}: key is empty", index + 1);
+            }
+            Ok((key.to_string(), value.trim().to_string()))
+        })
+        .collect()
+}
 
+/// Set a value in the unknown fields map
+fn set_unknown_value(config: &mut ClaudeConfig, key_path: &str, value: Value) -> Result<()> {
+    let mut root = Value::Object(std::mem::take(&mut config.unknown).into_iter().collect());
+    let result = set_json_value(&mut root, key_path, value);
+    if let Value::Object(fields) = root {
+        config.unknown = fields.into_iter().collect();
+    }
+    result
+}
+
+/// Set a value in free-form JSON
+///
+/// Objects missing along a dot path are created. Indexes name existing
+/// array elements; a trailing `[+]` appends to an array.
+fn set_json_value(root: &mut Value, key_path: &str, value: Value) -> Result<()> {
+    if key_path.contains('[') {
+        key_path::set_element(root, key_path, value)?;
+    } else {
+        key_path::set(root, key_path, value);
+    }
     Ok(())
 }
 
@@ -268,7 +793,13 @@ mod tests {
     #[test]
     fn test_set_mcp_server_enabled() {
         let mut config = ClaudeConfig::new();
-        set_value_by_path(&mut config, "mcpServers.npx.enabled", "true").unwrap();
+        set_value_by_path(
+            &mut config,
+            "mcpServers.npx.enabled",
+            "true",
+            SetMode::Replace,
+        )
+        .unwrap();
 
         assert!(config.mcp_servers.is_some());
         let servers = config.mcp_servers.unwrap();
@@ -280,7 +811,7 @@ mod tests {
     #[test]
     fn test_set_allowed_paths_string() {
         let mut config = ClaudeConfig::new();
-        set_value_by_path(&mut config, "allowedPaths", "~/projects").unwrap();
+        set_value_by_path(&mut config, "allowedPaths", "~/projects", SetMode::Replace).unwrap();
 
         assert!(config.allowed_paths.is_some());
         let paths = config.allowed_paths.unwrap();
@@ -291,7 +822,13 @@ mod tests {
     #[test]
     fn test_set_allowed_paths_array() {
         let mut config = ClaudeConfig::new();
-        set_value_by_path(&mut config, "allowedPaths", "[\"~/projects\", \"~/work\"]").unwrap();
+        set_value_by_path(
+            &mut config,
+            "allowedPaths",
+            "[\"~/projects\", \"~/work\"]",
+            SetMode::Replace,
+        )
+        .unwrap();
 
         assert!(config.allowed_paths.is_some());
         let paths = config.allowed_paths.unwrap();
@@ -301,7 +838,13 @@ mod tests {
     #[test]
     fn test_set_custom_instructions() {
         let mut config = ClaudeConfig::new();
-        set_value_by_path(&mut config, "customInstructions", "Be concise").unwrap();
+        set_value_by_path(
+            &mut config,
+            "customInstructions",
+            "Be concise",
+            SetMode::Replace,
+        )
+        .unwrap();
 
         assert!(config.custom_instructions.is_some());
         let instructions = config.custom_instructions.unwrap();
@@ -312,7 +855,13 @@ mod tests {
     #[test]
     fn test_set_skill_enabled() {
         let mut config = ClaudeConfig::new();
-        set_value_by_path(&mut config, "skills.code-review.enabled", "false").unwrap();
+        set_value_by_path(
+            &mut config,
+            "skills.code-review.enabled",
+            "false",
+            SetMode::Replace,
+        )
+        .unwrap();
 
         assert!(config.skills.is_some());
         let skills = config.skills.unwrap();
@@ -324,7 +873,7 @@ mod tests {
     #[test]
     fn test_set_unknown_field() {
         let mut config = ClaudeConfig::new();
-        set_value_by_path(&mut config, "myField", "myValue").unwrap();
+        set_value_by_path(&mut config, "myField", "myValue", SetMode::Replace).unwrap();
 
         assert!(config.unknown.contains_key("myField"));
         assert_eq!(
@@ -333,10 +882,74 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_set_nested_free_form_values() {
+        let mut config = ClaudeConfig::new();
+        for (key_path, value) in [
+            ("telemetry.level", "2"),
+            ("telemetry.tags", r#"["a", "b"]"#),
+            ("telemetry.tags[1]", "c"),
+            ("telemetry.tags[+]", "d"),
+            ("skills.review.parameters.depth", "3"),
+        ] {
+            set_value_by_path(&mut config, key_path, value, SetMode::Replace).unwrap();
+        }
+
+        assert_eq!(
+            config.unknown["telemetry"],
+            serde_json::json!({"level": 2, "tags": ["a", "c", "d"]})
+        );
+        assert_eq!(
+            config.skills.as_ref().unwrap()["review"].parameters,
+            Some(serde_json::json!({"depth": 3}))
+        );
+        assert!(
+            set_value_by_path(&mut config, "telemetry.tags[5]", "x", SetMode::Replace).is_err()
+        );
+        assert!(set_value_by_path(&mut config, "mcpServers[0]", "x", SetMode::Replace).is_err());
+    }
+
+    #[test]
+    fn test_set_mcp_server_timeout_and_platforms() {
+        let mut config = ClaudeConfig::new();
+        for (key_path, value) in [
+            ("mcpServers.npx.timeout", "30000"),
+            ("mcpServers.npx.platforms", r#"["linux", "macos"]"#),
+            ("mcpServers.npx.platforms[1]", "windows"),
+            ("mcpServers.npx.commandByPlatform.windows", "npx.cmd"),
+        ] {
+            set_value_by_path(&mut config, key_path, value, SetMode::Replace).unwrap();
+        }
+
+        let server = &config.mcp_servers.as_ref().unwrap()["npx"];
+        assert_eq!(server.timeout_ms, Some(30000));
+        assert_eq!(
+            server.platforms.as_deref(),
+            Some(&["linux".to_string(), "windows".to_string()][..])
+        );
+        assert_eq!(
+            server.command_by_platform.as_ref().unwrap()["windows"],
+            "npx.cmd"
+        );
+        assert!(set_value_by_path(
+            &mut config,
+            "mcpServers.npx.timeout",
+            "-1",
+            SetMode::Replace
+        )
+        .is_err());
+    }
+
     #[test]
     fn test_set_mcp_server_command() {
         let mut config = ClaudeConfig::new();
-        set_value_by_path(&mut config, "mcpServers.npx.command", "npx").unwrap();
+        set_value_by_path(
+            &mut config,
+            "mcpServers.npx.command",
+            "npx",
+            SetMode::Replace,
+        )
+        .unwrap();
 
         assert!(config.mcp_servers.is_some());
         let servers = config.mcp_servers.unwrap();
@@ -344,6 +957,105 @@ mod tests {
         assert_eq!(server.command, Some("npx".to_string()));
     }
 
+    #[test]
+    fn test_set_rejects_blank_values() {
+        let mut config = ClaudeConfig::new();
+        let error = |config: &mut ClaudeConfig, key_path: &str, value: &str| {
+            set_value_by_path(config, key_path, value, SetMode::Replace)
+                .unwrap_err()
+                .to_string()
+        };
+
+        assert_eq!(
+            error(&mut config, "mcpServers.fetch.command", ""),
+            "MCP server command can't be empty or whitespace-only; use null to clear it"
+        );
+        assert_eq!(
+            error(&mut config, "mcpServers.fetch.command", "\"  \""),
+            "MCP server command can't be empty or whitespace-only; use null to clear it"
+        );
+        assert!(error(&mut config, "mcpServers. .command", "npx")
+            .starts_with("MCP server name can't be empty or whitespace-only"));
+        assert_eq!(
+            error(&mut config, "allowedPaths", "   "),
+            "allowedPaths entries can't be empty or whitespace-only; use --remove to drop a path"
+        );
+        assert!(error(&mut config, "allowedPaths", r#"["~/a", ""]"#).starts_with("allowedPaths"));
+        assert!(error(&mut config, "allowedPaths[+]", " ").starts_with("allowedPaths"));
+        assert_eq!(
+            error(&mut config, "customInstructions", "\t"),
+            "Instructions can't be empty or whitespace-only; use 'ccm instructions remove' to drop one"
+        );
+        assert!(insert_value_by_path(&mut config, "customInstructions", 0, "").is_err());
+        assert!(set_value_by_path(
+            &mut config,
+            "customInstructions",
+            " ",
+            SetMode::Append { dedupe: false }
+        )
+        .is_err());
+
+        assert!(config.allowed_paths.is_none());
+        assert!(config.custom_instructions.is_none());
+    }
+
+    #[test]
+    fn test_set_mcp_server_command_null_clears_it() {
+        let mut config = ClaudeConfig::new();
+        set_value_by_path(
+            &mut config,
+            "mcpServers.fetch.command",
+            "uvx",
+            SetMode::Replace,
+        )
+        .unwrap();
+        set_value_by_path(
+            &mut config,
+            "mcpServers.fetch.command",
+            "null",
+            SetMode::Replace,
+        )
+        .unwrap();
+        assert_eq!(config.mcp_servers.as_ref().unwrap()["fetch"].command, None);
+
+        // A quoted "null" is the literal command name
+        set_value_by_path(
+            &mut config,
+            "mcpServers.fetch.command",
+            "\"null\"",
+            SetMode::Replace,
+        )
+        .unwrap();
+        assert_eq!(
+            config.mcp_servers.as_ref().unwrap()["fetch"]
+                .command
+                .as_deref(),
+            Some("null")
+        );
+
+        // An empty args value still means no arguments
+        set_value_by_path(&mut config, "mcpServers.fetch.args", "", SetMode::Replace).unwrap();
+        assert!(config.mcp_servers.unwrap()["fetch"].args.is_empty());
+    }
+
+    #[test]
+    fn test_set_mcp_server_env_var() {
+        let mut config = ClaudeConfig::new();
+        set_value_by_path(
+            &mut config,
+            "mcpServers.fetch.env.API_URL",
+            "https://example.com",
+            SetMode::Replace,
+        )
+        .unwrap();
+
+        let server = &config.mcp_servers.as_ref().unwrap()["fetch"];
+        assert_eq!(server.env["API_URL"], "https://example.com");
+        assert!(
+            set_value_by_path(&mut config, "mcpServers.fetch.env", "{}", SetMode::Replace).is_err()
+        );
+    }
+
     #[test]
     fn test_set_mcp_server_args_array() {
         let mut config = ClaudeConfig::new();
@@ -351,6 +1063,7 @@ mod tests {
             &mut config,
             "mcpServers.npx.args",
             "[\"-y\", \"--registry\", \"https://registry.npmjs.org\"]",
+            SetMode::Replace,
         )
         .unwrap();
 
@@ -367,6 +1080,7 @@ mod tests {
             &mut config,
             "mcpServers.npx.args",
             "-y --registry https://registry.npmjs.org",
+            SetMode::Replace,
         )
         .unwrap();
 
@@ -375,4 +1089,331 @@ mod tests {
         let server = servers.get("npx").unwrap();
         assert_eq!(server.args.len(), 3);
     }
+
+    #[test]
+    fn test_coerce_value_boolean() {
+        for raw in ["true", "YES", "on", "1"] {
+            assert_eq!(
+                coerce_value(raw, ValueType::Boolean).unwrap(),
+                Value::Bool(true)
+            );
+        }
+        for raw in ["false", "No", "off", "0"] {
+            assert_eq!(
+                coerce_value(raw, ValueType::Boolean).unwrap(),
+                Value::Bool(false)
+            );
+        }
+        assert!(coerce_value("maybe", ValueType::Boolean).is_err());
+    }
+
+    #[test]
+    fn test_coerce_value_number() {
+        assert_eq!(
+            coerce_value("42", ValueType::Number).unwrap(),
+            Value::from(42)
+        );
+        assert_eq!(
+            coerce_value(" 1.5 ", ValueType::Number).unwrap(),
+            Value::from(1.5)
+        );
+
+        let err = coerce_value("fast", ValueType::Number).unwrap_err();
+        assert_eq!(err.to_string(), "Expected a number, got 'fast'");
+    }
+
+    #[test]
+    fn test_coerce_value_string() {
+        // Numbers, booleans and quoted strings all become plain strings
+        assert_eq!(
+            coerce_value("42", ValueType::String).unwrap(),
+            Value::from("42")
+        );
+        assert_eq!(
+            coerce_value("true", ValueType::String).unwrap(),
+            Value::from("true")
+        );
+        assert_eq!(
+            coerce_value("\"npx\"", ValueType::String).unwrap(),
+            Value::from("npx")
+        );
+        assert_eq!(
+            coerce_value("npx", ValueType::String).unwrap(),
+            Value::from("npx")
+        );
+    }
+
+    #[test]
+    fn test_coerce_value_structured() {
+        assert!(coerce_value("[1, 2]", ValueType::Array).unwrap().is_array());
+        assert!(coerce_value("{\"a\": 1}", ValueType::Object)
+            .unwrap()
+            .is_object());
+        assert_eq!(coerce_value("null", ValueType::Null).unwrap(), Value::Null);
+        assert!(coerce_value("{\"a\": 1}", ValueType::Array).is_err());
+        assert!(coerce_value("[1]", ValueType::Object).is_err());
+    }
+
+    #[test]
+    fn test_set_mcp_server_enabled_rejects_non_boolean() {
+        let mut config = ClaudeConfig::new();
+        let err = set_value_by_path(
+            &mut config,
+            "mcpServers.npx.enabled",
+            "sometimes",
+            SetMode::Replace,
+        )
+        .unwrap_err();
+
+        assert!(err
+            .to_string()
+            .contains("'enabled' must be a boolean value"));
+    }
+
+    #[test]
+    fn test_set_mcp_server_args_rejects_nested_values() {
+        let mut config = ClaudeConfig::new();
+        let result = set_value_by_path(
+            &mut config,
+            "mcpServers.npx.args",
+            "[\"-y\", {\"a\": 1}]",
+            SetMode::Replace,
+        );
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_set_mcp_server_command_numeric_string() {
+        let mut config = ClaudeConfig::new();
+        set_value_by_path(&mut config, "mcpServers.py.command", "3", SetMode::Replace).unwrap();
+
+        let servers = config.mcp_servers.unwrap();
+        assert_eq!(servers["py"].command.as_deref(), Some("3"));
+    }
+
+    #[test]
+    fn test_append_allowed_path_keeps_existing() {
+        let mut config = ClaudeConfig::new().with_allowed_path("~/projects");
+        let append = SetMode::Append { dedupe: false };
+
+        set_value_by_path(&mut config, "allowedPaths", "~/work", append).unwrap();
+
+        assert_eq!(
+            config.allowed_paths.unwrap(),
+            vec!["~/projects".to_string(), "~/work".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_append_dedupes_array_values() {
+        let mut config = ClaudeConfig::new().with_allowed_path("~/projects");
+        set_value_by_path(&mut config, "mcpServers.npx.args", "-y", SetMode::Replace).unwrap();
+        let dedupe = SetMode::Append { dedupe: true };
+
+        set_value_by_path(
+            &mut config,
+            "allowedPaths",
+            "[\"~/projects\", \"~/work\"]",
+            dedupe,
+        )
+        .unwrap();
+        set_value_by_path(&mut config, "mcpServers.npx.args", "-y pkg", dedupe).unwrap();
+        set_value_by_path(&mut config, "extraTags", "[\"a\"]", dedupe).unwrap();
+        set_value_by_path(&mut config, "extraTags", "a", dedupe).unwrap();
+
+        assert_eq!(config.allowed_paths.unwrap(), vec!["~/projects", "~/work"]);
+        assert_eq!(config.mcp_servers.unwrap()["npx"].args, vec!["-y", "pkg"]);
+        assert_eq!(config.unknown["extraTags"], serde_json::json!(["a"]));
+    }
+
+    #[test]
+    fn test_append_rejects_non_array_keys() {
+        let mut config = ClaudeConfig::new();
+        config
+            .unknown
+            .insert("theme".to_string(), Value::from("dark"));
+        let append = SetMode::Append { dedupe: false };
+
+        for key in ["mcpServers.npx.enabled", "skills.review.enabled", "theme"] {
+            let err = set_value_by_path(&mut config, key, "true", append).unwrap_err();
+            assert!(err.to_string().contains("--append only applies to arrays"));
+        }
+    }
+
+    #[test]
+    fn test_remove_allowed_path() {
+        let mut config = ClaudeConfig::new()
+            .with_allowed_path("~/old")
+            .with_allowed_path("~/projects");
+
+        assert_eq!(
+            remove_value_by_path(&mut config, "allowedPaths", "~/old").unwrap(),
+            1
+        );
+        assert_eq!(config.allowed_paths.as_deref().unwrap(), ["~/projects"]);
+    }
+
+    #[test]
+    fn test_remove_args_splits_like_append() {
+        let mut config = ClaudeConfig::new();
+        let append = SetMode::Append { dedupe: false };
+        set_value_by_path(
+            &mut config,
+            "mcpServers.npx.command",
+            "npx",
+            SetMode::Replace,
+        )
+        .unwrap();
+        set_value_by_path(
+            &mut config,
+            "mcpServers.npx.args",
+            "-y pkg --verbose",
+            append,
+        )
+        .unwrap();
+
+        assert_eq!(
+            remove_value_by_path(&mut config, "mcpServers.npx.args", "-y pkg").unwrap(),
+            2
+        );
+        let servers = config.mcp_servers.as_ref().unwrap();
+        assert_eq!(servers["npx"].args, ["--verbose"]);
+    }
+
+    #[test]
+    fn test_remove_missing_value_is_reported() {
+        let mut config = ClaudeConfig::new().with_allowed_path("~/projects");
+        config
+            .unknown
+            .insert("tags".to_string(), serde_json::json!(["a", 1]));
+
+        assert_eq!(
+            remove_value_by_path(&mut config, "allowedPaths", "~/missing").unwrap(),
+            0
+        );
+        assert_eq!(
+            remove_value_by_path(&mut config, "customInstructions", "x").unwrap(),
+            0
+        );
+        assert_eq!(remove_value_by_path(&mut config, "tags", "1").unwrap(), 1);
+        assert_eq!(config.unknown["tags"], serde_json::json!(["a"]));
+        assert!(remove_value_by_path(&mut config, "mcpServers.npx.enabled", "true").is_err());
+    }
+
+    #[test]
+    fn test_indexed_operations_round_trip() {
+        for field in ["customInstructions", "allowedPaths"] {
+            let mut config = ClaudeConfig::new();
+            let at = |i: usize| format!("{field}[{i}]");
+            let append = format!("{field}[+]");
+            let values = |config: &ClaudeConfig| match field {
+                "allowedPaths" => config.allowed_paths.clone().unwrap(),
+                _ => config.custom_instructions.clone().unwrap(),
+            };
+
+            for value in ["b", "d"] {
+                set_value_by_path(&mut config, &append, value, SetMode::Replace).unwrap();
+            }
+            insert_value_by_path(&mut config, field, 0, "a").unwrap();
+            insert_value_by_path(&mut config, field, 2, "c").unwrap();
+            insert_value_by_path(&mut config, field, 4, "e").unwrap();
+            assert_eq!(values(&config), vec!["a", "b", "c", "d", "e"]);
+
+            set_value_by_path(&mut config, &at(1), "B", SetMode::Replace).unwrap();
+            assert_eq!(remove_index_by_path(&mut config, &at(2)).unwrap(), "c");
+            assert_eq!(values(&config), vec!["a", "B", "d", "e"]);
+            check_index(&config, &at(3)).unwrap();
+
+            let err = set_value_by_path(&mut config, &at(7), "x", SetMode::Replace).unwrap_err();
+            assert_eq!(
+                err.to_string(),
+                format!(
+                    "Index 7 is out of bounds for '{field}' (length 4); use '{field}[+]' to append"
+                )
+            );
+            assert!(insert_value_by_path(&mut config, field, 5, "x").is_err());
+            assert!(remove_index_by_path(&mut config, &at(4)).is_err());
+            assert!(check_index(&config, &at(4))
+                .unwrap_err()
+                .to_string()
+                .contains("(length 4)"));
+            assert_eq!(values(&config), vec!["a", "B", "d", "e"]);
+        }
+    }
+
+    #[test]
+    fn test_indexed_set_never_creates_gaps() {
+        let mut config = ClaudeConfig::new();
+
+        let err = set_value_by_path(&mut config, "customInstructions[0]", "a", SetMode::Replace)
+            .unwrap_err();
+        assert!(err.to_string().contains("(length 0)"));
+        assert!(config
+            .custom_instructions
+            .as_ref()
+            .map_or(true, Vec::is_empty));
+
+        assert!(set_value_by_path(&mut config, "theme[0]", "dark", SetMode::Replace).is_err());
+        assert!(config.unknown.is_empty());
+        assert!(set_value_by_path(
+            &mut config,
+            "allowedPaths[0]",
+            "~/a",
+            SetMode::Append { dedupe: false }
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_indexed_mcp_server_args() {
+        let mut config = ClaudeConfig::new();
+        set_value_by_path(
+            &mut config,
+            "mcpServers.npx.args",
+            "-y pkg",
+            SetMode::Replace,
+        )
+        .unwrap();
+
+        set_value_by_path(
+            &mut config,
+            "mcpServers.npx.args[1]",
+            "other pkg",
+            SetMode::Replace,
+        )
+        .unwrap();
+        insert_value_by_path(&mut config, "mcpServers.npx.args", 1, "--quiet").unwrap();
+
+        assert_eq!(
+            config.mcp_servers.as_ref().unwrap()["npx"].args,
+            vec!["-y", "--quiet", "other pkg"]
+        );
+        assert!(remove_index_by_path(&mut config, "mcpServers.missing.args[0]").is_err());
+    }
+
+    #[test]
+    fn test_parse_settings_forms() {
+        let env = "# servers\nmcpServers.npx.enabled = false\n\ncustomInstructions=a=b\n";
+        assert_eq!(
+            parse_settings(env).unwrap(),
+            vec![
+                ("mcpServers.npx.enabled".to_string(), "false".to_string()),
+                ("customInstructions".to_string(), "a=b".to_string()),
+            ]
+        );
+
+        let json = r#"{"allowedPaths": ["~/a"], "mcpServers.npx.command": "npx"}"#;
+        assert_eq!(
+            parse_settings(json).unwrap(),
+            vec![
+                ("allowedPaths".to_string(), r#"["~/a"]"#.to_string()),
+                ("mcpServers.npx.command".to_string(), "npx".to_string()),
+            ]
+        );
+
+        assert!(parse_settings("enabled").is_err());
+        assert!(parse_settings("=true").is_err());
+        assert!(parse_settings("{\"a\": ").is_err());
+    }
 }
@@ -2,17 +2,34 @@
 //!
 //! Command-line interface for managing Claude Code configuration files.
 
-use clap::Parser;
+use clap::{CommandFactory, FromArgMatches, Parser};
+use tracing_subscriber::fmt::writer::MakeWriterExt;
 
+mod backup_retention;
+mod bulk;
 mod commands;
+mod editor;
+mod file_arg;
 mod key_path;
+mod managers;
 mod output;
+mod project_arg;
+mod timings;
 
+use commands::alias::AliasArgs;
 use commands::config::ConfigArgs;
+use commands::daemon::DaemonArgs;
+use commands::doctor::DoctorArgs;
 use commands::history::HistoryArgs;
+use commands::instructions::InstructionsArgs;
 use commands::mcp::McpArgs;
+use commands::paths::PathsArgs;
 use commands::project::ProjectArgs;
 use commands::search::SearchArgs;
+use commands::state::StateArgs;
+use commands::stats::StatsArgs;
+use commands::support::SupportBundleArgs;
+use commands::usage::UsageArgs;
 
 /// Claude Config Manager - Manage Claude Code configurations
 #[derive(Parser, Debug)]
@@ -25,6 +42,10 @@ struct Args {
     #[arg(short, long)]
     verbose: bool,
 
+    /// Keep only the newest N backups of each config this command writes
+    #[arg(long, global = true, value_name = "N")]
+    backup_retention: Option<std::num::NonZeroUsize>,
+
     /// Command to execute
     #[command(subcommand)]
     command: Option<Commands>,
@@ -32,20 +53,39 @@ struct Args {
 
 #[derive(Parser, Debug)]
 enum Commands {
+    /// Project path alias commands (use aliases as @NAME)
+    Alias(AliasArgs),
     /// Configuration management commands
     Config(ConfigArgs),
+    /// Serve config requests over a local socket for editor integrations
+    Daemon(DaemonArgs),
+    /// Check config files and backups for problems
+    Doctor(DoctorArgs),
     /// History and backup management commands
     History(HistoryArgs),
+    /// Custom instructions commands
+    Instructions(InstructionsArgs),
     /// MCP server management commands
     Mcp(McpArgs),
+    /// Allowed paths cleanup commands
+    Paths(PathsArgs),
     /// Project discovery and management commands
     Project(ProjectArgs),
     /// Search configuration values
     Search(SearchArgs),
+    /// Per-project UI state management commands
+    State(StateArgs),
+    /// Configuration and local usage statistics
+    Stats(StatsArgs),
+    /// Generate a redacted support bundle for bug reports
+    SupportBundle(SupportBundleArgs),
+    /// Local usage statistics management commands
+    Usage(UsageArgs),
 }
 
 fn main() -> anyhow::Result<()> {
-    let args = Args::parse();
+    let matches = Args::command().get_matches();
+    let args = Args::from_arg_matches(&matches)?;
 
     // Initialize logging
     let log_level = if args.verbose {
@@ -54,39 +94,108 @@ fn main() -> anyhow::Result<()> {
         tracing::Level::INFO
     };
 
-    tracing_subscriber::fmt().with_max_level(log_level).init();
+    // Logs go to stderr so that stdout only carries command output, and
+    // to the configured log file (if any) for support bundles
+    let log_file = claude_config_manager_core::paths::get_log_path().and_then(|path| {
+        std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .ok()
+    });
+    match log_file {
+        Some(file) => tracing_subscriber::fmt()
+            .with_max_level(log_level)
+            .with_ansi(false)
+            .with_writer(std::io::stderr.and(std::sync::Mutex::new(file)))
+            .init(),
+        None => tracing_subscriber::fmt()
+            .with_max_level(log_level)
+            .with_writer(std::io::stderr)
+            .init(),
+    }
+    timings::init(args.verbose);
+    backup_retention::init(args.backup_retention.map(std::num::NonZeroUsize::get));
 
     tracing::debug!("Claude Config Manager v{}", env!("CARGO_PKG_VERSION"));
 
+    // Record local usage (no-op unless enabled in ccm settings)
+    if let Some((name, sub_matches)) = matches.subcommand() {
+        let command = match sub_matches.subcommand_name() {
+            Some(sub) => format!("{name} {sub}"),
+            None => name.to_string(),
+        };
+        commands::usage::record_usage(&command);
+    }
+
     // Execute command
     match args.command {
+        Some(Commands::Alias(alias_args)) => {
+            alias_args.execute()?;
+        }
         Some(Commands::Config(config_args)) => {
             config_args.execute()?;
         }
+        Some(Commands::Daemon(daemon_args)) => {
+            daemon_args.execute()?;
+        }
+        Some(Commands::Doctor(doctor_args)) => {
+            doctor_args.execute()?;
+        }
         Some(Commands::History(history_args)) => {
             history_args.execute()?;
         }
+        Some(Commands::Instructions(instructions_args)) => {
+            instructions_args.execute()?;
+        }
         Some(Commands::Mcp(mcp_args)) => {
             mcp_args.execute()?;
         }
+        Some(Commands::Paths(paths_args)) => {
+            paths_args.execute()?;
+        }
         Some(Commands::Project(project_args)) => {
             project_args.command.execute()?;
         }
         Some(Commands::Search(search_args)) => {
             search_args.execute()?;
         }
+        Some(Commands::State(state_args)) => {
+            state_args.execute()?;
+        }
+        Some(Commands::Stats(stats_args)) => {
+            stats_args.execute()?;
+        }
+        Some(Commands::SupportBundle(support_args)) => {
+            support_args.execute()?;
+        }
+        Some(Commands::Usage(usage_args)) => {
+            usage_args.execute()?;
+        }
         None => {
             println!("Claude Config Manager v{}", env!("CARGO_PKG_VERSION"));
             println!("\nUsage: ccm <command> [options]");
             println!("\nCommands:");
+            println!("  alias       Project path aliases (@NAME)");
             println!("  config      Configuration management");
+            println!("  daemon      Local socket server for editor integrations");
+            println!("  doctor      Check config files and backups for problems");
             println!("  history     Backup and history management");
+            println!("  instructions  Custom instructions management");
             println!("  mcp         MCP server management");
+            println!("  paths       Allowed paths cleanup");
             println!("  project     Project discovery and management");
             println!("  search      Search configuration values");
+            println!("  state       Per-project UI state management");
+            println!("  stats       Configuration and usage statistics");
+            println!("  support-bundle  Generate a redacted bundle for bug reports");
+            println!("  usage       Local usage statistics management");
             println!("\nRun 'ccm help <command>' for more information.");
         }
     }
 
+    // Timing footer (verbose mode only)
+    timings::print_footer();
+
     Ok(())
 }
@@ -0,0 +1,33 @@
+//! Managers shared by all commands
+//!
+//! Commands build their managers through these helpers, so the process-wide
+//! `--verbose` timings and `--backup-retention` apply to every one of them.
+
+use crate::{backup_retention, timings};
+use claude_config_manager_core::{paths::get_backup_dir, BackupManager, ConfigManager, McpManager};
+use std::path::PathBuf;
+
+/// Config manager keeping backups in the global backup directory
+pub fn config_manager() -> ConfigManager {
+    config_manager_in(get_backup_dir())
+}
+
+/// Config manager keeping backups in `backup_dir`
+pub fn config_manager_in(backup_dir: impl Into<PathBuf>) -> ConfigManager {
+    ConfigManager::new(backup_dir)
+        .with_timings(timings::collector())
+        .with_backup_retention(backup_retention::get())
+}
+
+/// MCP manager for `backup_dir`, logging changes if the operation log is enabled
+pub fn mcp_manager(backup_dir: impl Into<PathBuf>) -> McpManager {
+    McpManager::new(backup_dir)
+        .with_timings(timings::collector())
+        .with_backup_retention(backup_retention::get())
+        .with_configured_audit_log()
+}
+
+/// Backup manager for `backup_dir`, keeping `--backup-retention` backups
+pub fn backup_manager(backup_dir: impl Into<PathBuf>) -> BackupManager {
+    BackupManager::new(backup_dir, backup_retention::get())
+}
@@ -3,7 +3,7 @@
 //! Formats configuration as JSON
 
 use anyhow::Result;
-use claude_config_manager_core::ClaudeConfig;
+use claude_config_manager_core::{config::key_path, ClaudeConfig};
 use serde_json::Value;
 
 /// Format configuration as JSON
@@ -17,7 +17,9 @@ pub fn format_json(config: &ClaudeConfig, key: Option<&str>) -> Result<()> {
 
     // Filter by key if specified
     let output = if let Some(key_path) = key {
-        get_nested_value(&json_value, key_path).unwrap_or(Value::Null)
+        key_path::get_element(&json_value, key_path)
+            .cloned()
+            .unwrap_or(Value::Null)
     } else {
         json_value
     };
@@ -27,36 +29,9 @@ pub fn format_json(config: &ClaudeConfig, key: Option<&str>) -> Result<()> {
     Ok(())
 }
 
-/// Get a nested value from JSON using dot notation
-///
-/// # Arguments
-/// * `json` - The JSON value to search
-/// * `key_path` - Dot-separated key path (e.g., "mcpServers.npx.enabled")
-fn get_nested_value(json: &Value, key_path: &str) -> Option<Value> {
-    let keys: Vec<&str> = key_path.split('.').collect();
-    let mut current = json;
-
-    for key in keys {
-        match current {
-            Value::Object(map) => {
-                current = map.get(key)?;
-            }
-            Value::Array(arr) => {
-                // Try to parse as index
-                let index = key.parse::<usize>().ok()?;
-                current = arr.get(index)?;
-            }
-            _ => return None,
-        }
-    }
-
-    Some(current.clone())
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
-    use serde_json::json;
 
     #[test]
     fn test_format_json_full_config() {
@@ -65,59 +40,4 @@ mod tests {
         // Should not panic
         format_json(&config, None).unwrap();
     }
-
-    #[test]
-    fn test_get_nested_value_simple() {
-        let json = json!({
-            "mcpServers": {
-                "npx": {
-                    "enabled": true
-                }
-            }
-        });
-
-        let result = get_nested_value(&json, "mcpServers");
-        assert!(result.is_some());
-        assert!(result.unwrap().is_object());
-    }
-
-    #[test]
-    fn test_get_nested_value_deep() {
-        let json = json!({
-            "mcpServers": {
-                "npx": {
-                    "enabled": true
-                }
-            }
-        });
-
-        let result = get_nested_value(&json, "mcpServers.npx.enabled");
-        assert!(result.is_some());
-        assert_eq!(result.unwrap(), json!(true));
-    }
-
-    #[test]
-    fn test_get_nested_value_missing_key() {
-        let json = json!({
-            "mcpServers": {
-                "npx": {
-                    "enabled": true
-                }
-            }
-        });
-
-        let result = get_nested_value(&json, "mcpServers.nonexistent.enabled");
-        assert!(result.is_none());
-    }
-
-    #[test]
-    fn test_get_nested_value_from_array() {
-        let json = json!({
-            "allowedPaths": ["~/projects", "~/work"]
-        });
-
-        let result = get_nested_value(&json, "allowedPaths.0");
-        assert!(result.is_some());
-        assert_eq!(result.unwrap(), json!("~/projects"));
-    }
 }
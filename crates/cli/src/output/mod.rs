@@ -3,7 +3,49 @@
 //! Functions for formatting configuration output
 
 mod json;
+mod porcelain;
 mod table;
+mod yaml;
 
 pub use json::format_json;
-pub use table::format_table;
+pub use porcelain::{
+    backup_record, format_porcelain, mcp_server_record, project_record, query_match_records,
+    search_record,
+};
+pub use table::{format_table, TableOptions};
+pub use yaml::{format_yaml, to_yaml_string};
+
+/// Output format for configuration display
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum OutputFormat {
+    /// Human-readable table format
+    Table,
+    /// Machine-readable JSON format
+    Json,
+    /// Machine-readable YAML format
+    Yaml,
+}
+
+/// How list commands print their results
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ListFormat {
+    /// Human-readable output, free to change between versions
+    Human,
+    /// Machine-readable JSON
+    Json,
+    /// Stable tab-separated output (see [`format_porcelain`])
+    Porcelain,
+}
+
+impl ListFormat {
+    /// Format selected by the `--json` and `--porcelain` flags
+    pub fn from_flags(json: bool, porcelain: bool) -> Self {
+        if porcelain {
+            Self::Porcelain
+        } else if json {
+            Self::Json
+        } else {
+            Self::Human
+        }
+    }
+}
@@ -0,0 +1,291 @@
+//! Porcelain output formatter
+//!
+//! Stable, script-friendly output for `--porcelain`. Unlike the human
+//! format, the porcelain format of a version never changes: adding,
+//! removing or reordering fields requires a new version in the header.
+//!
+//! Output starts with the header line `#ccm-porcelain v1`, followed by one
+//! record per line. Fields are separated by a single tab. In field values,
+//! backslash, tab, newline and carriage return are written as `\\`, `\t`,
+//! `\n` and `\r`. Missing values are empty fields, booleans are `true` or
+//! `false` and timestamps are RFC 3339 in UTC.
+//!
+//! Fields of each record, in order:
+//!
+//! * `mcp list`: name, enabled, command, platforms (comma-separated), then
+//!   one field per argument
+//! * `history list`: index, created, size in bytes, tag, path
+//! * `project list`: name, archived, last modified, root, config path
+//! * `search`: scope, value type, key path, value
+//! * `search --where`: scope, entry, clause, key path, matched text
+
+use claude_config_manager_core::{
+    format_timestamp, paths::Platform, BackupInfo, McpServer, ProjectInfo, QueryMatch, SearchResult,
+};
+use std::path::Path;
+
+/// First line of porcelain output
+pub const PORCELAIN_HEADER: &str = "#ccm-porcelain v1";
+
+/// Render `records` as porcelain output, header included
+///
+/// Each record is a list of unescaped field values.
+pub fn format_porcelain(records: impl IntoIterator<Item = Vec<String>>) -> String {
+    let mut output = format!("{PORCELAIN_HEADER}\n");
+    for record in records {
+        let fields: Vec<String> = record.iter().map(|field| escape(field)).collect();
+        output.push_str(&fields.join("\t"));
+        output.push('\n');
+    }
+    output
+}
+
+/// Escape the characters that would break a field or record apart
+fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for ch in value.chars() {
+        match ch {
+            '\\' => escaped.push_str("\\\\"),
+            '\t' => escaped.push_str("\\t"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            _ => escaped.push(ch),
+        }
+    }
+    escaped
+}
+
+fn path_field(path: &Path) -> String {
+    path.to_string_lossy().into_owned()
+}
+
+/// Record of an MCP server (`platform` selects the command shown)
+pub fn mcp_server_record(
+    name: &str,
+    server: &McpServer,
+    platform: Option<Platform>,
+) -> Vec<String> {
+    let command = match platform {
+        Some(platform) => server.command_for(platform),
+        None => server.command.as_deref(),
+    };
+    let mut record = vec![
+        name.to_string(),
+        server.enabled.to_string(),
+        command.unwrap_or_default().to_string(),
+        server.platforms.as_deref().unwrap_or_default().join(","),
+    ];
+    record.extend(server.args.iter().cloned());
+    record
+}
+
+/// Record of the backup at `index` in `history list`
+pub fn backup_record(index: usize, backup: &BackupInfo) -> Vec<String> {
+    vec![
+        index.to_string(),
+        format_timestamp(&backup.created_at),
+        backup.size.to_string(),
+        backup.tag.clone().unwrap_or_default(),
+        backup.path.clone(),
+    ]
+}
+
+/// Record of a discovered project
+pub fn project_record(project: &ProjectInfo) -> Vec<String> {
+    vec![
+        project.name.clone(),
+        project.archived.to_string(),
+        project
+            .last_modified
+            .as_ref()
+            .map(format_timestamp)
+            .unwrap_or_default(),
+        path_field(&project.root),
+        path_field(&project.config_path),
+    ]
+}
+
+/// Record of a search hit
+pub fn search_record(result: &SearchResult) -> Vec<String> {
+    vec![
+        result.source.display_name().to_string(),
+        result.value_type_label().to_string(),
+        result.key_path.clone(),
+        result.value.clone(),
+    ]
+}
+
+/// Records of a structured query match, one per matching clause
+pub fn query_match_records(result: &QueryMatch) -> impl Iterator<Item = Vec<String>> + '_ {
+    result.matches.iter().map(|hit| {
+        vec![
+            result.source.display_name().to_string(),
+            result.root.clone(),
+            hit.clause.to_string(),
+            hit.key_path.clone(),
+            hit.text.clone(),
+        ]
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    //! Golden-file tests of the porcelain format
+    //!
+    //! These tests guard a format scripts rely on. If one fails, the change
+    //! broke porcelain v1: revert it, or introduce v2 in the header instead
+    //! of editing the golden file.
+
+    use super::*;
+    use chrono::{TimeZone, Utc};
+    use claude_config_manager_core::{
+        types::ConfigScope, ClauseMatch, QueryClause, SearchResult, ValueType,
+    };
+    use std::path::PathBuf;
+
+    fn assert_golden(actual: &str, golden: &str, name: &str) {
+        assert!(
+            actual == golden,
+            "porcelain v1 output of `{name}` changed; the format is frozen \
+             (bump the header version instead of editing the golden file)\n\
+             --- expected ---\n{golden}--- actual ---\n{actual}"
+        );
+    }
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(escape("a\tb\nc\rd\\e"), "a\\tb\\nc\\rd\\\\e");
+        assert_eq!(escape("plain"), "plain");
+    }
+
+    #[test]
+    fn test_empty_output_is_header_only() {
+        assert_eq!(format_porcelain(Vec::new()), "#ccm-porcelain v1\n");
+    }
+
+    #[test]
+    fn test_mcp_list_golden() {
+        let github = McpServer::new("github", "npx", vec!["-y".into(), "@mcp/github".into()])
+            .with_env("GITHUB_TOKEN", "secret")
+            .with_platforms(&[Platform::Linux, Platform::MacOs]);
+        let mut fetch = McpServer::new("fetch", "uvx", vec!["tab\there".into()]);
+        fetch.enabled = false;
+        let mut bare = McpServer::new("bare", "", Vec::new());
+        bare.command = None;
+
+        let output = format_porcelain([
+            mcp_server_record("bare", &bare, None),
+            mcp_server_record("fetch", &fetch, None),
+            mcp_server_record("github", &github, None),
+        ]);
+        assert_golden(
+            &output,
+            include_str!("../../tests/golden/porcelain/mcp_list.txt"),
+            "mcp list",
+        );
+    }
+
+    #[test]
+    fn test_history_list_golden() {
+        let created_at = Utc.with_ymd_and_hms(2024, 3, 1, 12, 30, 0).unwrap();
+        let backups = [
+            BackupInfo {
+                path: "/backups/config_20240301_123000.000000.json".into(),
+                original_path: "/home/user/.claude.json".into(),
+                created_at,
+                size: 512,
+                tag: None,
+            },
+            BackupInfo {
+                path: "/backups/config_20240301_123000.000000_auto-risk-import.json".into(),
+                original_path: "/home/user/.claude.json".into(),
+                created_at,
+                size: 2048,
+                tag: Some("auto-risk-import".into()),
+            },
+        ];
+
+        let output = format_porcelain(
+            backups
+                .iter()
+                .enumerate()
+                .map(|(index, backup)| backup_record(index, backup)),
+        );
+        assert_golden(
+            &output,
+            include_str!("../../tests/golden/porcelain/history_list.txt"),
+            "history list",
+        );
+    }
+
+    #[test]
+    fn test_project_list_golden() {
+        let project = |name: &str, archived: bool, modified: bool| {
+            let root = PathBuf::from("/work").join(name);
+            ProjectInfo {
+                claude_dir: root.join(".claude"),
+                config_path: root.join(".claude").join("config.json"),
+                root,
+                has_config: true,
+                name: name.to_string(),
+                last_modified: modified.then(|| Utc.with_ymd_and_hms(2024, 3, 1, 8, 0, 5).unwrap()),
+                archived,
+            }
+        };
+
+        let output = format_porcelain([
+            project_record(&project("api", false, true)),
+            project_record(&project("old site", true, false)),
+        ]);
+        assert_golden(
+            &output,
+            include_str!("../../tests/golden/porcelain/project_list.txt"),
+            "project list",
+        );
+    }
+
+    #[test]
+    fn test_search_golden() {
+        let config_path = PathBuf::from("/home/user/.claude.json");
+        let results = [
+            SearchResult::new(
+                "mcpServers.github.command".into(),
+                "npx".into(),
+                ConfigScope::Global,
+                config_path.clone(),
+                ValueType::String,
+            ),
+            SearchResult::new(
+                "customInstructions[0]".into(),
+                "Line one\nLine two".into(),
+                ConfigScope::Project,
+                config_path.clone(),
+                ValueType::String,
+            ),
+        ];
+        let query_match = QueryMatch {
+            root: "mcpServers.github".into(),
+            source: ConfigScope::Global,
+            config_path,
+            matches: vec![ClauseMatch {
+                clause: "value~npx".parse::<QueryClause>().unwrap(),
+                key_path: "mcpServers.github.command".into(),
+                text: "npx".into(),
+            }],
+        };
+
+        let output = format_porcelain(results.iter().map(search_record));
+        assert_golden(
+            &output,
+            include_str!("../../tests/golden/porcelain/search.txt"),
+            "search",
+        );
+
+        let output = format_porcelain(query_match_records(&query_match));
+        assert_golden(
+            &output,
+            include_str!("../../tests/golden/porcelain/search_where.txt"),
+            "search --where",
+        );
+    }
+}
@@ -1,172 +1,442 @@
 //! Table output formatter
 //!
-//! Formats configuration as human-readable tables
+//! Formats configuration as one row per leaf value, keyed by its full key
+//! path, so every printed path can be passed to `ccm config get` or
+//! `ccm config set` as is.
 
 use anyhow::Result;
+use claude_config_manager_core::config::key_path::{self, Walk};
 use claude_config_manager_core::ClaudeConfig;
 use serde_json::Value;
 
+/// Arrays with more items than this collapse into one row unless `--full`
+const ARRAY_ITEM_LIMIT: usize = 10;
+
+/// Levels below a section that are expanded; deeper values are summarized
+const MAX_DEPTH: usize = 8;
+
+/// Rows printed before the rest are left out
+const MAX_ROWS: usize = 500;
+
+/// Characters of a value shown before it is cut short
+const MAX_VALUE_WIDTH: usize = 60;
+
+/// How much of a large config the table shows
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TableOptions {
+    /// Expand long arrays and deep values and lift the row limit
+    pub full: bool,
+}
+
+/// One printed leaf (or summarized subtree)
+struct Row {
+    key_path: String,
+    value: String,
+    kind: &'static str,
+}
+
+/// Rows grouped under a section header
+struct Section {
+    title: String,
+    rows: Vec<Row>,
+}
+
+/// What the limits left out, for the note after the table
+#[derive(Default)]
+struct Truncation {
+    omitted_rows: usize,
+    summarized_depth: bool,
+}
+
 /// Format configuration as a human-readable table
 ///
 /// # Arguments
 /// * `config` - The configuration to format
 /// * `key` - Optional key to filter output (e.g., "mcpServers.npx.enabled")
-pub fn format_table(config: &ClaudeConfig, key: Option<&str>) -> Result<()> {
-    let json_value = serde_json::to_value(config)?;
+/// * `options` - Whether to lift the size limits
+pub fn format_table(config: &ClaudeConfig, key: Option<&str>, options: TableOptions) -> Result<()> {
+    print!("{}", render_table(config, key, options)?);
+    Ok(())
+}
 
-    if let Some(key_path) = key {
-        // Show specific key
-        let value = get_nested_value(&json_value, key_path).unwrap_or(Value::Null);
+/// Render the table printed by [`format_table`]
+///
+/// Each top-level key (or the requested key) becomes a section with one
+/// `key path  value  type` row per leaf.
+pub fn render_table(
+    config: &ClaudeConfig,
+    key: Option<&str>,
+    options: TableOptions,
+) -> Result<String> {
+    let mut json_value = serde_json::to_value(config)?;
+    drop_entry_names(&mut json_value);
+    let mut truncation = Truncation::default();
+    let mut row_budget = if options.full { usize::MAX } else { MAX_ROWS };
 
-        println!("{key_path}:");
-        print_value(&value, 1);
+    let mut out = String::new();
+    let sections = if let Some(key_path) = key {
+        let value = key_path::get_element(&json_value, key_path)
+            .cloned()
+            .unwrap_or(Value::Null);
+        vec![section(
+            key_path,
+            &value,
+            options,
+            &mut row_budget,
+            &mut truncation,
+        )]
     } else {
-        // Show all configuration
-        println!("Claude Code Configuration:");
-        println!();
-
-        // Display each section
-        if let Some(servers) = &config.mcp_servers {
-            println!("MCP Servers:");
-            for (name, server) in servers {
-                println!("  {name}:");
-                println!("    Enabled: {}", server.enabled);
-                if let Some(command) = &server.command {
-                    println!("    Command: {command}");
-                }
-                if !server.args.is_empty() {
-                    println!("    Args: {}", server.args.join(" "));
-                }
-            }
-            println!();
+        out.push_str("Claude Code Configuration:\n\n");
+        json_value
+            .as_object()
+            .into_iter()
+            .flatten()
+            .map(|(key, value)| section(key, value, options, &mut row_budget, &mut truncation))
+            .collect()
+    };
+
+    if sections.is_empty() {
+        out.push_str("No configuration values set.\n");
+        return Ok(out);
+    }
+
+    let rows = sections.iter().flat_map(|section| &section.rows);
+    let path_width = rows.clone().map(|row| width(&row.key_path)).max();
+    let value_width = rows.map(|row| width(&row.value)).max();
+    let (path_width, value_width) = (path_width.unwrap_or(0), value_width.unwrap_or(0));
+
+    let mut sections = sections.iter().filter(|s| !s.rows.is_empty()).peekable();
+    while let Some(section) = sections.next() {
+        out.push_str(&format!("{}\n", section.title));
+        for row in &section.rows {
+            out.push_str(&format!(
+                "  {:<path_width$}  {:<value_width$}  {}\n",
+                row.key_path, row.value, row.kind
+            ));
+        }
+        if sections.peek().is_some() {
+            out.push('\n');
         }
+    }
 
-        if let Some(paths) = &config.allowed_paths {
-            println!("Allowed Paths:");
-            for path in paths {
-                println!("  - {path}");
+    if truncation.omitted_rows > 0 {
+        out.push_str(&format!(
+            "\n… {} more row{} not shown; use --full or --output json to see everything\n",
+            truncation.omitted_rows,
+            if truncation.omitted_rows == 1 {
+                ""
+            } else {
+                "s"
             }
-            println!();
-        }
+        ));
+    }
+    if truncation.summarized_depth {
+        out.push_str(&format!(
+            "\nValues nested more than {MAX_DEPTH} levels deep are summarized; use --full to expand them\n"
+        ));
+    }
 
-        if let Some(skills) = &config.skills {
-            println!("Skills:");
-            for (name, skill) in skills {
-                println!("  {name}:");
-                println!("    Enabled: {}", skill.enabled);
-                if let Some(params) = &skill.parameters {
-                    println!("    Parameters: {params}");
-                }
+    Ok(out)
+}
+
+/// Remove the `name` of each MCP server and skill
+///
+/// Names repeat the entry's key and can't be set on their own, so they
+/// would print rows `config set` rejects.
+fn drop_entry_names(config: &mut Value) {
+    for section in ["mcpServers", "skills"] {
+        let entries = config.get_mut(section).and_then(Value::as_object_mut);
+        for entry in entries.into_iter().flat_map(|entries| entries.values_mut()) {
+            if let Some(entry) = entry.as_object_mut() {
+                entry.remove("name");
             }
-            println!();
         }
+    }
+}
 
-        if let Some(instructions) = &config.custom_instructions {
-            println!("Custom Instructions:");
-            for (i, instruction) in instructions.iter().enumerate() {
-                println!("  {}. {}", i + 1, instruction);
-            }
-            println!();
+/// Rows for `value` at `key_path`, walked with the shared key-path helper
+fn section(
+    key_path: &str,
+    value: &Value,
+    options: TableOptions,
+    row_budget: &mut usize,
+    truncation: &mut Truncation,
+) -> Section {
+    let mut rows = Vec::new();
+    let mut push = |row: Row, truncation: &mut Truncation| {
+        if *row_budget == 0 {
+            truncation.omitted_rows += 1;
+        } else {
+            *row_budget -= 1;
+            rows.push(row);
         }
+    };
 
-        // Show unknown fields
-        if !config.unknown.is_empty() {
-            println!("Other Configuration:");
-            for (key, value) in &config.unknown {
-                println!("  {key}:");
-                print_value(value, 2);
+    if let Some(row) = collapsed(key_path, value, options) {
+        push(row, truncation);
+    } else if key_path::is_leaf(value) {
+        push(leaf_row(key_path, value, options), truncation);
+    } else {
+        key_path::walk(value, key_path, &mut |path, child, depth| {
+            if let Some(row) = collapsed(path, child, options) {
+                push(row, truncation);
+                Walk::Skip
+            } else if key_path::is_leaf(child) {
+                push(leaf_row(path, child, options), truncation);
+                Walk::Skip
+            } else if depth >= MAX_DEPTH && !options.full {
+                truncation.summarized_depth = true;
+                push(summary_row(path, child), truncation);
+                Walk::Skip
+            } else {
+                Walk::Descend
             }
-        }
+        });
     }
 
-    Ok(())
+    Section {
+        title: key_path.to_string(),
+        rows,
+    }
 }
 
-/// Print a JSON value with indentation
-fn print_value(value: &Value, indent: usize) {
-    let indent_str = "  ".repeat(indent);
-
+/// A single row standing in for an array too long to list
+fn collapsed(key_path: &str, value: &Value, options: TableOptions) -> Option<Row> {
     match value {
-        Value::Null => println!("{indent_str}null"),
-        Value::Bool(b) => println!("{indent_str}{b}"),
-        Value::Number(n) => println!("{indent_str}{n}"),
-        Value::String(s) => println!("{indent_str}{s}"),
-        Value::Array(arr) => {
-            for item in arr {
-                print_value(item, indent);
-            }
-        }
-        Value::Object(obj) => {
-            for (key, val) in obj {
-                println!("{indent_str}{key}:");
-                print_value(val, indent + 1);
-            }
+        Value::Array(items) if items.len() > ARRAY_ITEM_LIMIT && !options.full => {
+            Some(summary_row(key_path, value))
         }
+        _ => None,
     }
 }
 
-/// Get a nested value from JSON using dot notation
-fn get_nested_value(json: &Value, key_path: &str) -> Option<Value> {
-    let keys: Vec<&str> = key_path.split('.').collect();
-    let mut current = json;
+/// A row counting the children of an object or array instead of listing them
+fn summary_row(key_path: &str, value: &Value) -> Row {
+    let (count, noun) = match value {
+        Value::Array(items) => (items.len(), "item"),
+        Value::Object(map) => (map.len(), "key"),
+        _ => (0, "item"),
+    };
+    Row {
+        key_path: key_path.to_string(),
+        value: format!(
+            "… ({count} {noun}{}, use --full)",
+            if count == 1 { "" } else { "s" }
+        ),
+        kind: kind(value),
+    }
+}
 
-    for key in keys {
-        match current {
-            Value::Object(map) => {
-                current = map.get(key)?;
-            }
-            Value::Array(arr) => {
-                let index = key.parse::<usize>().ok()?;
-                current = arr.get(index)?;
-            }
-            _ => return None,
-        }
+/// A row showing a leaf value
+fn leaf_row(key_path: &str, value: &Value, options: TableOptions) -> Row {
+    Row {
+        key_path: key_path.to_string(),
+        value: display_value(value, options),
+        kind: kind(value),
+    }
+}
+
+/// A leaf value as shown in the value column
+fn display_value(value: &Value, options: TableOptions) -> String {
+    let text = match value {
+        Value::String(s) if s.is_empty() => "\"\"".to_string(),
+        Value::String(s) => s
+            .replace('\n', "\\n")
+            .replace('\r', "\\r")
+            .replace('\t', "\\t"),
+        Value::Array(_) => "[]".to_string(),
+        Value::Object(_) => "{}".to_string(),
+        other => other.to_string(),
+    };
+
+    if options.full || width(&text) <= MAX_VALUE_WIDTH {
+        text
+    } else {
+        let cut: String = text.chars().take(MAX_VALUE_WIDTH - 1).collect();
+        format!("{cut}…")
+    }
+}
+
+/// JSON type name shown in the type column
+fn kind(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
     }
+}
 
-    Some(current.clone())
+/// Display width of a cell, counted in characters
+fn width(text: &str) -> usize {
+    text.chars().count()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::key_path::{set_value_by_path, SetMode};
     use serde_json::json;
 
+    /// A config with nested env vars, a long array and an unknown section
+    fn sample_config() -> ClaudeConfig {
+        serde_json::from_value(json!({
+            "mcpServers": {
+                "fetch": {
+                    "command": "uvx",
+                    "args": ["mcp-server-fetch"],
+                    "env": {"API_URL": "https://example.com/api"},
+                    "enabled": true,
+                    "timeout": 30000,
+                    "platforms": ["linux"],
+                    "commandByPlatform": {"linux": "uvx"}
+                }
+            },
+            "skills": {"review": {"enabled": true, "parameters": {"depth": 2}}},
+            "allowedPaths": (0..12).map(|i| format!("~/p{i}")).collect::<Vec<_>>(),
+            "customInstructions": ["Be concise"],
+            "telemetry": {"level": 2, "tags": ["ci"], "sinks": []}
+        }))
+        .unwrap()
+    }
+
+    /// Key path and value columns of the printed rows
+    fn printed_rows(table: &str) -> Vec<(String, String)> {
+        table
+            .lines()
+            .filter_map(|line| line.strip_prefix("  "))
+            .map(|row| {
+                let (path, rest) = row.split_once(' ').unwrap();
+                let (value, _kind) = rest.trim().rsplit_once("  ").unwrap();
+                (path.to_string(), value.trim().to_string())
+            })
+            .collect()
+    }
+
     #[test]
-    fn test_format_table_full_config() {
-        let config = ClaudeConfig::new().with_custom_instruction("Be concise");
+    fn test_render_table_layout() {
+        let table = render_table(&sample_config(), None, TableOptions::default()).unwrap();
+
+        assert_eq!(
+            table,
+            "\
+Claude Code Configuration:
 
-        // Should not panic
-        format_table(&config, None).unwrap();
+allowedPaths
+  allowedPaths                              … (12 items, use --full)  array
+
+customInstructions
+  customInstructions[0]                     Be concise                string
+
+mcpServers
+  mcpServers.fetch.args[0]                  mcp-server-fetch          string
+  mcpServers.fetch.command                  uvx                       string
+  mcpServers.fetch.commandByPlatform.linux  uvx                       string
+  mcpServers.fetch.enabled                  true                      boolean
+  mcpServers.fetch.env.API_URL              https://example.com/api   string
+  mcpServers.fetch.platforms[0]             linux                     string
+  mcpServers.fetch.timeout                  30000                     number
+
+skills
+  skills.review.enabled                     true                      boolean
+  skills.review.parameters.depth            2                         number
+
+telemetry
+  telemetry.level                           2                         number
+  telemetry.sinks                           []                        array
+  telemetry.tags[0]                         ci                        string
+"
+        );
     }
 
     #[test]
-    fn test_format_table_with_key() {
-        let config = ClaudeConfig::new().with_custom_instruction("Be concise");
+    fn test_render_table_paths_round_trip() {
+        let config = sample_config();
+        let mut json_value = serde_json::to_value(&config).unwrap();
+        drop_entry_names(&mut json_value);
 
-        // Should not panic even with unknown key
-        format_table(&config, Some("customInstructions")).unwrap();
+        for options in [TableOptions::default(), TableOptions { full: true }] {
+            let table = render_table(&config, None, options).unwrap();
+            for (path, value) in printed_rows(&table) {
+                let resolved = key_path::get_element(&json_value, &path)
+                    .unwrap_or_else(|| panic!("{path} doesn't resolve"));
+                if !value.starts_with('…') {
+                    assert_eq!(value, display_value(resolved, options), "{path}");
+                }
+
+                // Setting the printed value at the printed path changes nothing
+                let mut updated = config.clone();
+                set_value_by_path(&mut updated, &path, &resolved.to_string(), SetMode::Replace)
+                    .unwrap_or_else(|e| panic!("config set {path}: {e}"));
+                assert_eq!(updated, config, "{path}");
+            }
+        }
+
+        let full = render_table(&config, None, TableOptions { full: true }).unwrap();
+        let printed: Vec<String> = printed_rows(&full).into_iter().map(|(p, _)| p).collect();
+        let leaves: Vec<String> = key_path::leaves(&json_value, "")
+            .into_iter()
+            .map(|(p, _)| p)
+            .collect();
+        assert_eq!(printed, leaves);
     }
 
     #[test]
-    fn test_print_value_string() {
-        let value = Value::String("test".to_string());
-        // Should not panic
-        print_value(&value, 0);
+    fn test_render_table_with_key() {
+        let table = render_table(
+            &sample_config(),
+            Some("mcpServers.fetch.env"),
+            TableOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            table,
+            "mcpServers.fetch.env\n  mcpServers.fetch.env.API_URL  https://example.com/api  string\n"
+        );
+
+        let table = render_table(
+            &sample_config(),
+            Some("mcpServers.fetch.enabled"),
+            TableOptions::default(),
+        )
+        .unwrap();
+        assert!(table.contains("  mcpServers.fetch.enabled  true  boolean"));
     }
 
     #[test]
-    fn test_print_value_object() {
-        let value = json!({
-            "key": "value"
-        });
-        // Should not panic
-        print_value(&value, 0);
+    fn test_render_table_limits() {
+        let mut deep = json!("bottom");
+        for level in (0..12).rev() {
+            deep = json!({ format!("l{level}"): deep });
+        }
+        let wide: serde_json::Map<String, Value> = (0..MAX_ROWS + 5)
+            .map(|i| (format!("k{i:04}"), json!(i)))
+            .collect();
+        let config: ClaudeConfig = serde_json::from_value(json!({
+            "deep": deep,
+            "wide": wide,
+            "long": "x".repeat(100)
+        }))
+        .unwrap();
+
+        let table = render_table(&config, None, TableOptions::default()).unwrap();
+        assert!(table.contains("  deep.l0.l1.l2.l3.l4.l5.l6.l7"));
+        assert!(table.contains("… (1 key, use --full)"));
+        assert!(table.contains("nested more than 8 levels deep"));
+        assert!(table.contains(&format!("{}…", "x".repeat(MAX_VALUE_WIDTH - 1))));
+        assert!(table.contains("… 7 more rows not shown"));
+
+        let full = render_table(&config, None, TableOptions { full: true }).unwrap();
+        assert!(full.contains("deep.l0.l1.l2.l3.l4.l5.l6.l7.l8.l9.l10.l11"));
+        assert!(full.contains(&"x".repeat(100)));
+        assert!(!full.contains("not shown"));
     }
 
     #[test]
-    fn test_print_value_array() {
-        let value = json!(["item1", "item2"]);
-        // Should not panic
-        print_value(&value, 0);
+    fn test_render_table_empty() {
+        let table = render_table(&ClaudeConfig::new(), None, TableOptions::default()).unwrap();
+        assert!(table.ends_with("No configuration values set.\n"));
     }
 }
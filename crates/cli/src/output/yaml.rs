@@ -0,0 +1,49 @@
+//! YAML output formatter
+//!
+//! Formats configuration as YAML
+
+use anyhow::Result;
+use claude_config_manager_core::{config::key_path, ClaudeConfig};
+use serde_json::Value;
+
+/// Format configuration as YAML
+///
+/// # Arguments
+/// * `config` - The configuration to format
+/// * `key` - Optional key to filter output (e.g., "mcpServers.npx.enabled")
+pub fn format_yaml(config: &ClaudeConfig, key: Option<&str>) -> Result<()> {
+    let json_value = serde_json::to_value(config)?;
+
+    let output = if let Some(key_path) = key {
+        key_path::get_element(&json_value, key_path)
+            .cloned()
+            .unwrap_or(Value::Null)
+    } else {
+        json_value
+    };
+
+    print!("{}", to_yaml_string(&output)?);
+    Ok(())
+}
+
+/// Render a JSON value as a YAML document
+pub fn to_yaml_string(value: &Value) -> Result<String> {
+    Ok(serde_yaml::to_string(value)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_to_yaml_string_round_trips() {
+        let value = json!({"mcpServers": {"npx": {"command": "npx", "args": ["-y"]}}});
+
+        let yaml = to_yaml_string(&value).unwrap();
+
+        assert!(yaml.contains("mcpServers:"));
+        let parsed: Value = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(parsed, value);
+    }
+}
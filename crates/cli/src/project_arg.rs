@@ -0,0 +1,66 @@
+//! Project arguments that accept `@alias`
+//!
+//! Every option and argument that takes a project path parses through
+//! [`parse_project`] (or [`parse_utf8_project`]), so aliases defined with
+//! `ccm alias add` work in all commands, e.g. `ccm mcp list --project @api`.
+
+use crate::managers::config_manager;
+use camino::Utf8PathBuf;
+use claude_config_manager_core::{settings::ALIAS_PREFIX, CcmSettings};
+use std::path::PathBuf;
+
+/// Read ccm's settings from the global config
+pub fn load_settings() -> anyhow::Result<CcmSettings> {
+    Ok(config_manager().get_global_config()?.ccm_settings())
+}
+
+/// Resolve a project argument: `@name` through the alias table, anything else as a path
+///
+/// Warns on stderr if the alias target no longer exists.
+///
+/// # Errors
+/// Returns an error listing the defined aliases if the alias is unknown
+pub fn resolve_project(input: &str) -> anyhow::Result<PathBuf> {
+    if !input.starts_with(ALIAS_PREFIX) {
+        return Ok(PathBuf::from(input));
+    }
+
+    let settings = load_settings()?;
+    match settings.resolve_alias(input) {
+        Some(target) => {
+            if !target.is_dir() {
+                eprintln!(
+                    "Warning: alias {input} points at {}, which no longer exists",
+                    target.display()
+                );
+            }
+            Ok(target)
+        }
+        None if settings.aliases.is_empty() => anyhow::bail!(
+            "Unknown project alias '{input}'; no aliases are defined (add one with `ccm alias add <NAME> <PATH>`)"
+        ),
+        None => {
+            let defined: Vec<String> = settings
+                .aliases
+                .keys()
+                .map(|name| format!("{ALIAS_PREFIX}{name}"))
+                .collect();
+            anyhow::bail!(
+                "Unknown project alias '{input}'; defined aliases: {}",
+                defined.join(", ")
+            )
+        }
+    }
+}
+
+/// clap value parser for project paths
+pub fn parse_project(input: &str) -> Result<PathBuf, String> {
+    resolve_project(input).map_err(|e| e.to_string())
+}
+
+/// clap value parser for project paths stored as UTF-8 paths
+pub fn parse_utf8_project(input: &str) -> Result<Utf8PathBuf, String> {
+    let path = parse_project(input)?;
+    Utf8PathBuf::from_path_buf(path)
+        .map_err(|path| format!("Project path is not valid UTF-8: {}", path.display()))
+}
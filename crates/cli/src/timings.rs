@@ -0,0 +1,33 @@
+//! Process-wide operation timings
+//!
+//! In `--verbose` mode every manager records its operation timings into one
+//! shared collector, printed as a footer once the command finishes.
+
+use claude_config_manager_core::Timings;
+use std::sync::OnceLock;
+
+/// Collector shared by all commands in this process
+static TIMINGS: OnceLock<Timings> = OnceLock::new();
+
+/// Enable or disable timing collection for this process
+pub fn init(enabled: bool) {
+    let timings = if enabled {
+        Timings::new()
+    } else {
+        Timings::disabled()
+    };
+    let _ = TIMINGS.set(timings);
+}
+
+/// Get the shared collector (disabled if [`init`] was not called)
+pub fn collector() -> Timings {
+    TIMINGS.get().cloned().unwrap_or_default()
+}
+
+/// Print the timing footer, if anything was recorded
+pub fn print_footer() {
+    let summary = collector().summary();
+    if !summary.is_empty() {
+        eprintln!("Timings: {summary}");
+    }
+}
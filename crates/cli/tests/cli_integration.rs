@@ -2,15 +2,22 @@
 //!
 //! Tests the CLI commands end-to-end using assert_cmd.
 
+// `Command::cargo_bin` is deprecated in newer assert_cmd releases but still works here.
+#![allow(deprecated)]
+
 use assert_cmd::assert::OutputAssertExt;
 use assert_cmd::cargo::CommandCargoExt;
 use predicates::prelude::*;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 use tempfile::TempDir;
 
-/// Helper struct to set up and tear down test environment
+/// Sandbox for one test: `CCM_HOME` points at a private temp directory
+///
+/// The global config, backups, registry, state and logs of every `ccm`
+/// started through [`TestEnv::ccm`] live there, so tests can exercise the
+/// global scope and run in parallel without touching the real config.
 struct TestEnv {
     temp_dir: TempDir,
     config_path: PathBuf,
@@ -27,19 +34,49 @@ impl TestEnv {
         }
     }
 
-    /// Create a config file with test data
+    /// Create a global config file with test data
     fn create_test_config(&self) {
         let test_config = r#"{
-            "customInstructions": "Test instructions",
+            "customInstructions": ["Test instructions"],
             "mcpServers": {
                 "test-server": {
                     "command": "npx",
-                    "args": ["-y"]
+                    "args": ["-y"],
+                    "enabled": true
                 }
             }
         }"#;
         fs::write(&self.config_path, test_config).unwrap();
     }
+
+    /// `ccm` with `CCM_HOME` set to the sandbox
+    fn ccm(&self) -> Command {
+        let mut cmd = Command::cargo_bin("ccm").unwrap();
+        cmd.current_dir(self.temp_dir.path())
+            .env("CCM_HOME", self.temp_dir.path());
+        cmd
+    }
+
+    /// The global config as JSON
+    fn read_config(&self) -> serde_json::Value {
+        serde_json::from_str(&fs::read_to_string(&self.config_path).unwrap()).unwrap()
+    }
+
+    /// Number of backups of the global config
+    fn backup_count(&self) -> usize {
+        backup_files(&self.temp_dir.path().join("backups"))
+    }
+}
+
+/// Number of backup files in `dir`, leaving out its origin records
+fn backup_files(dir: &Path) -> usize {
+    fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter(|entry| entry.as_ref().is_ok_and(|e| e.path().is_file()))
+                .count()
+        })
+        .unwrap_or(0)
 }
 
 #[cfg(test)]
@@ -94,26 +131,33 @@ mod cli_tests {
         assert!(env.temp_dir.path().exists());
     }
 
-    // Note: Tests that modify the actual global config require special handling
-    // to avoid interfering with the user's actual configuration.
-    // Future tests should use custom config paths via environment variables or CLI flags.
-
     #[test]
-    fn test_config_get_help() {
-        Command::cargo_bin("ccm")
-            .unwrap()
-            .args(["config", "get", "--help"])
+    fn test_config_get_global_value() {
+        let env = TestEnv::new();
+        env.create_test_config();
+
+        env.ccm()
+            .args(["config", "get", "customInstructions"])
             .assert()
-            .success();
+            .success()
+            .stdout(predicate::str::contains("Test instructions"));
     }
 
     #[test]
-    fn test_config_set_help() {
-        Command::cargo_bin("ccm")
-            .unwrap()
-            .args(["config", "set", "--help"])
+    fn test_config_set_global_value() {
+        let env = TestEnv::new();
+        env.create_test_config();
+
+        env.ccm()
+            .args(["config", "set", "allowedPaths", "~/a"])
             .assert()
-            .success();
+            .success()
+            .stdout(predicate::str::contains("updated successfully"));
+
+        let config = env.read_config();
+        assert_eq!(config["allowedPaths"], serde_json::json!(["~/a"]));
+        assert_eq!(config["mcpServers"]["test-server"]["command"], "npx");
+        assert_eq!(env.backup_count(), 1);
     }
 
     #[test]
@@ -126,21 +170,48 @@ mod cli_tests {
     }
 
     #[test]
-    fn test_mcp_list_help() {
-        Command::cargo_bin("ccm")
-            .unwrap()
-            .args(["mcp", "list", "--help"])
+    fn test_mcp_list_global() {
+        let env = TestEnv::new();
+        env.create_test_config();
+
+        env.ccm()
+            .args(["mcp", "list"])
             .assert()
-            .success();
+            .success()
+            .stdout(predicate::str::contains("MCP Servers (1)"))
+            .stdout(predicate::str::contains("test-server"));
     }
 
     #[test]
-    fn test_mcp_add_help() {
-        Command::cargo_bin("ccm")
-            .unwrap()
-            .args(["mcp", "add", "--help"])
-            .assert()
-            .success();
+    fn test_mcp_add_disable_and_enable_global() {
+        let env = TestEnv::new();
+        env.create_test_config();
+
+        let mcp = |args: &[&str]| {
+            env.ccm().arg("mcp").args(args).assert().success();
+        };
+
+        mcp(&[
+            "add",
+            "fetch",
+            "--command",
+            "uvx",
+            "--args",
+            "mcp-server-fetch",
+        ]);
+        assert_eq!(env.read_config()["mcpServers"]["fetch"]["command"], "uvx");
+
+        mcp(&["disable", "fetch"]);
+        assert_eq!(env.read_config()["mcpServers"]["fetch"]["enabled"], false);
+
+        mcp(&["enable", "fetch"]);
+        let config = env.read_config();
+        assert_eq!(config["mcpServers"]["fetch"]["enabled"], true);
+        assert_eq!(config["mcpServers"]["test-server"]["enabled"], true);
+
+        // Backups go to the sandbox, not the working directory
+        assert_eq!(env.backup_count(), 3);
+        assert!(!env.temp_dir.path().join(".backups").exists());
     }
 
     #[test]
@@ -189,6 +260,7 @@ mod cli_tests {
 
         Command::cargo_bin("ccm")
             .unwrap()
+            .env("CCM_HOME", temp_dir.path().join("ccm"))
             .args([
                 "project",
                 "scan",
@@ -200,6 +272,33 @@ mod cli_tests {
             .stdout(predicate::str::contains("No projects found"));
     }
 
+    #[test]
+    fn test_project_scan_stats() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::create_dir_all(temp_dir.path().join("node_modules").join("pkg")).unwrap();
+        fs::create_dir_all(temp_dir.path().join("app").join(".claude")).unwrap();
+        fs::write(
+            temp_dir
+                .path()
+                .join("app")
+                .join(".claude")
+                .join("config.json"),
+            "{}",
+        )
+        .unwrap();
+
+        Command::cargo_bin("ccm")
+            .unwrap()
+            .env("CCM_HOME", temp_dir.path().join("ccm"))
+            .args(["project", "scan", "--stats", "--path"])
+            .arg(temp_dir.path())
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Directories visited: 2"))
+            .stdout(predicate::str::contains("Projects found:      1"))
+            .stdout(predicate::str::contains("Skipped (ignored):   1"));
+    }
+
     #[test]
     fn test_project_scan_finds_projects() {
         let temp_dir = TempDir::new().unwrap();
@@ -212,6 +311,7 @@ mod cli_tests {
 
         Command::cargo_bin("ccm")
             .unwrap()
+            .env("CCM_HOME", temp_dir.path().join("ccm"))
             .args([
                 "project",
                 "scan",
@@ -236,6 +336,7 @@ mod cli_tests {
 
         Command::cargo_bin("ccm")
             .unwrap()
+            .env("CCM_HOME", temp_dir.path().join("ccm"))
             .args([
                 "project",
                 "scan",
@@ -251,12 +352,93 @@ mod cli_tests {
             .stdout(predicate::str::contains("Has Config:"));
     }
 
+    #[test]
+    fn test_project_scan_relative() {
+        let temp_dir = TempDir::new().unwrap();
+        let claude_dir = temp_dir.path().join("relative-project").join(".claude");
+        fs::create_dir_all(&claude_dir).unwrap();
+        fs::write(claude_dir.join("config.json"), "{}").unwrap();
+
+        let output = Command::cargo_bin("ccm")
+            .unwrap()
+            .env("CCM_HOME", temp_dir.path().join("ccm"))
+            .args(["project", "scan", "--relative", "--verbose", "--path"])
+            .arg(temp_dir.path())
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        let config_path = PathBuf::from("relative-project")
+            .join(".claude")
+            .join("config.json");
+        assert!(stdout.contains(&format!("Config: {}\n", config_path.display())));
+        assert!(!stdout.contains(&format!("Root: {}", temp_dir.path().display())));
+    }
+
+    #[test]
+    fn test_project_config_output_json() {
+        let temp_dir = TempDir::new().unwrap();
+        let project_dir = temp_dir.path().join("json-project");
+        let claude_dir = project_dir.join(".claude");
+        fs::create_dir_all(&claude_dir).unwrap();
+        fs::write(
+            claude_dir.join("config.json"),
+            r#"{"customInstructions": ["Use tabs"],
+                "mcpServers": {"fs": {"command": "npx", "enabled": true}}}"#,
+        )
+        .unwrap();
+
+        let output = Command::cargo_bin("ccm")
+            .unwrap()
+            .env("CCM_HOME", temp_dir.path().join("ccm"))
+            .args(["project", "config", "--output", "json", "--path"])
+            .arg(&project_dir)
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+
+        let config: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        assert_eq!(config["customInstructions"][0], "Use tabs");
+        assert_eq!(config["mcpServers"]["fs"]["command"], "npx");
+    }
+
+    #[test]
+    fn test_project_config_output_yaml() {
+        let temp_dir = TempDir::new().unwrap();
+        let project_dir = temp_dir.path().join("yaml-project");
+        let claude_dir = project_dir.join(".claude");
+        fs::create_dir_all(&claude_dir).unwrap();
+        fs::write(
+            claude_dir.join("config.json"),
+            r#"{"customInstructions": ["Use tabs"],
+                "mcpServers": {"fs": {"command": "npx", "enabled": true}}}"#,
+        )
+        .unwrap();
+
+        let output = Command::cargo_bin("ccm")
+            .unwrap()
+            .env("CCM_HOME", temp_dir.path().join("ccm"))
+            .args(["project", "config", "--output", "yaml", "--path"])
+            .arg(&project_dir)
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        assert!(stdout.contains("mcpServers:"));
+        let config: serde_json::Value = serde_yaml::from_str(&stdout).unwrap();
+        assert_eq!(config["customInstructions"][0], "Use tabs");
+        assert_eq!(config["mcpServers"]["fs"]["command"], "npx");
+    }
+
     #[test]
     fn test_project_list_no_projects() {
         let temp_dir = TempDir::new().unwrap();
 
         Command::cargo_bin("ccm")
             .unwrap()
+            .env("CCM_HOME", temp_dir.path().join("ccm"))
             .args([
                 "project",
                 "list",
@@ -283,6 +465,7 @@ mod cli_tests {
         // Scan with depth 1 should not find the deep project
         Command::cargo_bin("ccm")
             .unwrap()
+            .env("CCM_HOME", temp_dir.path().join("ccm"))
             .args([
                 "project",
                 "scan",
@@ -297,13 +480,28 @@ mod cli_tests {
     }
 
     #[test]
-    fn test_history_list_help() {
-        Command::cargo_bin("ccm")
-            .unwrap()
-            .args(["history", "list", "--help"])
+    fn test_history_list_global() {
+        let env = TestEnv::new();
+        env.create_test_config();
+
+        env.ccm()
+            .args(["history", "list"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("No backups found"));
+
+        for value in ["~/a", "~/b"] {
+            env.ccm()
+                .args(["config", "set", "allowedPaths", value])
+                .assert()
+                .success();
+        }
+
+        env.ccm()
+            .args(["history", "list"])
             .assert()
             .success()
-            .stdout(predicate::str::contains("List available"));
+            .stdout(predicate::str::contains("Backups (2 available"));
     }
 
     #[test]
@@ -322,6 +520,7 @@ mod cli_tests {
 
         Command::cargo_bin("ccm")
             .unwrap()
+            .env("CCM_HOME", temp_dir.path().join("ccm"))
             .args([
                 "history",
                 "list",
@@ -333,6 +532,36 @@ mod cli_tests {
             .stdout(predicate::str::contains("No backups found"));
     }
 
+    #[test]
+    fn test_history_prune_dry_run_and_apply() {
+        let temp_dir = TempDir::new().unwrap();
+        let home = temp_dir.path().join("home");
+        let backup_dir = home.join(".config").join("claude").join("backups");
+        fs::create_dir_all(&backup_dir).unwrap();
+        for i in 0..3 {
+            fs::write(backup_dir.join(format!("config_2025010{i}.json")), "{}").unwrap();
+        }
+
+        let prune = |extra: &[&str]| {
+            let mut cmd = Command::cargo_bin("ccm").unwrap();
+            cmd.env("HOME", &home)
+                .env("CCM_HOME", home.join(".config").join("claude"))
+                .args(["history", "prune", "--keep", "1"])
+                .args(extra);
+            cmd.assert().success()
+        };
+
+        let output = prune(&["--dry-run"]).get_output().stdout.clone();
+        let output = String::from_utf8(output).unwrap();
+        assert_eq!(output.matches("KEEP").count(), 1);
+        assert_eq!(output.matches("DELETE").count(), 2);
+        assert!(output.contains("beyond retention count"));
+        assert_eq!(backup_files(&backup_dir), 3);
+
+        prune(&["--yes"]).stdout(predicate::str::contains("Removed 2 backup(s)"));
+        assert_eq!(backup_files(&backup_dir), 1);
+    }
+
     #[test]
     fn test_search_help() {
         Command::cargo_bin("ccm")
@@ -363,6 +592,3109 @@ mod cli_tests {
             .stdout(predicate::str::contains("Import configuration"));
     }
 
+    #[test]
+    fn test_config_get_resolve_paths() {
+        let temp_dir = TempDir::new().unwrap();
+        let home = temp_dir.path().join("home");
+        let project_dir = temp_dir.path().join("project");
+        let claude_dir = project_dir.join(".claude");
+        fs::create_dir_all(&home).unwrap();
+        fs::create_dir_all(&claude_dir).unwrap();
+        fs::write(
+            claude_dir.join("config.json"),
+            r#"{"allowedPaths": ["~/projects"]}"#,
+        )
+        .unwrap();
+
+        let run = |resolve: bool| {
+            let mut cmd = Command::cargo_bin("ccm").unwrap();
+            cmd.env("HOME", &home)
+                .env("CCM_HOME", home.join(".config").join("claude"))
+                .args(["config", "--project", project_dir.to_str().unwrap()])
+                .args(["--output", "json", "get", "allowedPaths"]);
+            if resolve {
+                cmd.arg("--resolve-paths");
+            }
+            cmd.assert().success()
+        };
+
+        run(false).stdout(predicate::str::contains("~/projects"));
+        run(true)
+            .stdout(predicate::str::contains(
+                home.join("projects").to_str().unwrap(),
+            ))
+            .stdout(predicate::str::contains("~/projects").not());
+
+        // The stored config is not modified
+        let stored = fs::read_to_string(claude_dir.join("config.json")).unwrap();
+        assert!(stored.contains("~/projects"));
+    }
+
+    #[test]
+    fn test_config_get_large_config_hint() {
+        let temp_dir = TempDir::new().unwrap();
+        let home = temp_dir.path().join("home");
+        let project_dir = temp_dir.path().join("project");
+        let claude_dir = project_dir.join(".claude");
+        fs::create_dir_all(&home).unwrap();
+        fs::create_dir_all(&claude_dir).unwrap();
+
+        let instructions: Vec<String> = (0..9 * 1024).map(|_| "a".repeat(1024)).collect();
+        let config = serde_json::json!({ "customInstructions": instructions });
+        fs::write(claude_dir.join("config.json"), config.to_string()).unwrap();
+
+        Command::cargo_bin("ccm")
+            .unwrap()
+            .env("HOME", &home)
+            .env("CCM_HOME", home.join(".config").join("claude"))
+            .args(["config", "--project", project_dir.to_str().unwrap()])
+            .args(["--output", "json", "get", "allowedPaths"])
+            .assert()
+            .success()
+            .stderr(predicate::str::contains("Hint: config files over 8 MB"));
+    }
+
+    #[test]
+    fn test_config_archive_and_unarchive() {
+        let temp_dir = TempDir::new().unwrap();
+        let home = temp_dir.path().join("home");
+        let claude_dir = home.join(".config").join("claude");
+        let backup_dir = claude_dir.join("backups");
+        fs::create_dir_all(&backup_dir).unwrap();
+
+        let files = [
+            (
+                claude_dir.join("config.json"),
+                r#"{"allowedPaths": ["~/a"]}"#,
+            ),
+            (backup_dir.join("config_20250101.json"), r#"{"old": true}"#),
+            (backup_dir.join("config_20250102.json"), "{}"),
+        ];
+        for (path, content) in &files {
+            fs::write(path, content).unwrap();
+        }
+        let archive = temp_dir.path().join("backup.tar.gz");
+
+        let ccm = |args: &[&str]| {
+            let mut cmd = Command::cargo_bin("ccm").unwrap();
+            cmd.env("HOME", &home)
+                .env("CCM_HOME", home.join(".config").join("claude"))
+                .arg("config")
+                .args(args);
+            cmd.assert()
+        };
+
+        ccm(&["archive", "--out", archive.to_str().unwrap()])
+            .success()
+            .stdout(predicate::str::contains("Archived 3 file(s)"));
+
+        fs::remove_dir_all(&claude_dir).unwrap();
+
+        ccm(&["unarchive", archive.to_str().unwrap()])
+            .success()
+            .stdout(predicate::str::contains("Restored 3 file(s)"));
+        for (path, content) in &files {
+            assert_eq!(fs::read_to_string(path).unwrap(), *content);
+        }
+
+        // Refuses to overwrite the restored config without --force
+        ccm(&["unarchive", archive.to_str().unwrap()]).failure();
+        ccm(&["unarchive", "--force", archive.to_str().unwrap()]).success();
+    }
+
+    #[test]
+    fn test_project_and_history_json_use_rfc3339() {
+        let temp_dir = TempDir::new().unwrap();
+        let home = temp_dir.path().join("home");
+        let project = temp_dir.path().join("projects").join("web");
+        fs::create_dir_all(project.join(".claude")).unwrap();
+        fs::write(project.join(".claude").join("config.json"), "{}").unwrap();
+
+        let ccm = |args: &[&str]| {
+            let mut cmd = Command::cargo_bin("ccm").unwrap();
+            cmd.env("HOME", &home)
+                .env("CCM_HOME", home.join(".config").join("claude"))
+                .args(args);
+            let output = cmd.output().unwrap();
+            assert!(output.status.success());
+            serde_json::from_slice::<serde_json::Value>(&output.stdout).unwrap()
+        };
+        let assert_rfc3339 = |value: &serde_json::Value| {
+            let text = value.as_str().expect("timestamp is a string");
+            assert!(
+                chrono::DateTime::parse_from_rfc3339(text).is_ok(),
+                "not RFC 3339: {text}"
+            );
+        };
+
+        let projects_dir = temp_dir.path().join("projects");
+        let projects = ccm(&[
+            "project",
+            "list",
+            "--json",
+            "--path",
+            projects_dir.to_str().unwrap(),
+        ]);
+        assert_eq!(projects.as_array().unwrap().len(), 1);
+        assert_rfc3339(&projects[0]["last_modified"]);
+
+        // Two writes produce one backup of the global config
+        ccm_run(&home, &["config", "set", "allowedPaths", "~/a"]);
+        ccm_run(&home, &["config", "set", "allowedPaths", "~/b"]);
+        let backups = ccm(&["history", "list", "--json"]);
+        assert_eq!(backups.as_array().unwrap().len(), 1);
+        assert_rfc3339(&backups[0]["created_at"]);
+    }
+
+    fn ccm_run(home: &std::path::Path, args: &[&str]) {
+        Command::cargo_bin("ccm")
+            .unwrap()
+            .env("HOME", home)
+            .env("CCM_HOME", home.join(".config").join("claude"))
+            .args(args)
+            .assert()
+            .success();
+    }
+
+    #[test]
+    fn test_config_diff_exit_code() {
+        let temp_dir = TempDir::new().unwrap();
+        let home = temp_dir.path().join("home");
+        let global_dir = home.join(".config").join("claude");
+        let project = temp_dir.path().join("project");
+        let project_config = project.join(".claude").join("config.json");
+        fs::create_dir_all(&global_dir).unwrap();
+        fs::create_dir_all(project_config.parent().unwrap()).unwrap();
+        fs::write(
+            global_dir.join("config.json"),
+            r#"{"allowedPaths": ["~/a"]}"#,
+        )
+        .unwrap();
+        fs::write(&project_config, r#"{"allowedPaths": ["~/a"]}"#).unwrap();
+
+        let ccm = |args: &[&str]| {
+            let mut cmd = Command::cargo_bin("ccm").unwrap();
+            cmd.env("HOME", &home)
+                .env("CCM_HOME", home.join(".config").join("claude"))
+                .arg("config")
+                .arg("diff")
+                .arg(&project)
+                .args(args);
+            cmd.assert()
+        };
+
+        // No drift
+        ccm(&["--exit-code"]).code(0);
+
+        // Drift: exit 1 with --exit-code, 0 without
+        fs::write(&project_config, r#"{"allowedPaths": ["~/b"]}"#).unwrap();
+        ccm(&["--exit-code"])
+            .code(1)
+            .stdout(predicate::str::contains("allowedPaths"));
+        ccm(&[]).code(0);
+    }
+
+    #[test]
+    fn test_verbose_prints_timing_footer() {
+        let temp_dir = TempDir::new().unwrap();
+        let home = temp_dir.path().join("home");
+
+        let ccm = |args: &[&str]| {
+            let mut cmd = Command::cargo_bin("ccm").unwrap();
+            cmd.env("HOME", &home)
+                .env("CCM_HOME", home.join(".config").join("claude"))
+                .args(args);
+            cmd.assert()
+        };
+
+        ccm(&["config", "set", "allowedPaths", "~/a"])
+            .success()
+            .stderr(predicate::str::contains("Timings:").not());
+        ccm(&["--verbose", "config", "set", "allowedPaths", "~/b"])
+            .success()
+            .stderr(
+                predicate::str::is_match(
+                    "Timings: read \\d+ms, backup \\d+ms, validate \\d+ms, write \\d+ms",
+                )
+                .unwrap(),
+            );
+    }
+
+    #[test]
+    fn test_config_migrate_embedded() {
+        let temp_dir = TempDir::new().unwrap();
+        let home = temp_dir.path().join("home");
+        let project = temp_dir.path().join("project");
+        fs::create_dir_all(&project).unwrap();
+        let manifest = project.join("package.json");
+        fs::write(
+            &manifest,
+            r#"{
+  "name": "web",
+  "claude": { "allowedPaths": ["./src"] },
+  "private": true
+}"#,
+        )
+        .unwrap();
+
+        let ccm = |args: &[&str]| {
+            let mut cmd = Command::cargo_bin("ccm").unwrap();
+            cmd.env("HOME", &home)
+                .env("CCM_HOME", home.join(".config").join("claude"))
+                .arg("config")
+                .arg("--project")
+                .arg(&project)
+                .args(args);
+            cmd.assert()
+        };
+
+        // The embedded config is read, but can't be written
+        ccm(&["get", "allowedPaths"])
+            .success()
+            .stdout(predicate::str::contains("./src"));
+        ccm(&["set", "allowedPaths", r#"["./lib"]"#])
+            .failure()
+            .stderr(predicate::str::contains("migrate-embedded"));
+
+        ccm(&["migrate-embedded", "--remove"])
+            .success()
+            .stdout(predicate::str::contains("Migrated"));
+        assert!(project.join(".claude").join("config.json").exists());
+        assert_eq!(
+            fs::read_to_string(&manifest).unwrap(),
+            "{\n  \"name\": \"web\",\n  \"private\": true\n}"
+        );
+
+        // Now writable
+        ccm(&["set", "allowedPaths", r#"["./lib"]"#]).success();
+    }
+
+    #[test]
+    fn test_config_normalize_dry_run_and_apply() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("config.json");
+        let original = r#"{
+  // hand-edited
+  "mcpServers": {
+    "fetch": { "enabled": true, "command": "uvx", "args": "mcp-server-fetch" },
+  },
+  "skills": {},
+  "allowedPaths": ["/srv", "/srv"]
+}"#;
+        fs::write(&config_path, original).unwrap();
+
+        let normalize = |extra: &[&str]| {
+            let mut cmd = Command::cargo_bin("ccm").unwrap();
+            cmd.env("CCM_HOME", temp_dir.path().join("ccm"))
+                .args(["config", "normalize", "--file"])
+                .arg(&config_path)
+                .args(extra);
+            cmd.assert()
+        };
+
+        normalize(&["--dry-run", "--skip", "empty-maps"])
+            .success()
+            .stdout(predicate::str::contains(
+                "converted args string to array for server 'fetch'",
+            ))
+            .stdout(predicate::str::contains(
+                "removed duplicate allowed path '/srv'",
+            ))
+            .stdout(predicate::str::contains("removed empty skills map").not());
+        assert_eq!(fs::read_to_string(&config_path).unwrap(), original);
+
+        normalize(&["--skip", "bogus"]).failure();
+
+        normalize(&[])
+            .success()
+            .stdout(predicate::str::contains("removed empty skills map"));
+        let normalized: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&config_path).unwrap()).unwrap();
+        assert_eq!(
+            normalized["mcpServers"]["fetch"]["args"],
+            serde_json::json!(["mcp-server-fetch"])
+        );
+        assert!(normalized.get("skills").is_none());
+
+        normalize(&[])
+            .success()
+            .stdout(predicate::str::contains("already normalized"));
+    }
+
+    #[test]
+    fn test_config_normalize_reformats_only() {
+        let env = TestEnv::new();
+        let original =
+            "{\"allowedPaths\":[\"/srv\"],\n\n    \"customInstructions\": [\"Be brief\"]}";
+        fs::write(&env.config_path, original).unwrap();
+
+        env.ccm()
+            .args(["config", "normalize", "--file"])
+            .arg(&env.config_path)
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("reformatted to canonical style"));
+        let written = fs::read_to_string(&env.config_path).unwrap();
+        assert_ne!(written, original);
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&written).unwrap(),
+            serde_json::from_str::<serde_json::Value>(original).unwrap()
+        );
+
+        env.ccm()
+            .args(["config", "normalize", "--file"])
+            .arg(&env.config_path)
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("already normalized"));
+    }
+
+    #[test]
+    fn test_config_fix_dry_run_and_apply() {
+        let temp_dir = TempDir::new().unwrap();
+        let claude_dir = temp_dir.path().join(".claude");
+        fs::create_dir_all(&claude_dir).unwrap();
+        let config_path = claude_dir.join("config.json");
+        let original = r#"{"allowedPaths": ["~/a ", "~/a"], "customInstructions": []}"#;
+        fs::write(&config_path, original).unwrap();
+
+        let fix = |extra: &[&str]| {
+            let mut cmd = Command::cargo_bin("ccm").unwrap();
+            cmd.env("CCM_HOME", temp_dir.path().join("ccm"))
+                .args(["config", "--project", temp_dir.path().to_str().unwrap()])
+                .arg("fix")
+                .args(extra);
+            cmd.assert().success()
+        };
+
+        fix(&["--dry-run"]).stdout(predicate::str::contains("Would fix 2 issue(s)"));
+        assert_eq!(fs::read_to_string(&config_path).unwrap(), original);
+
+        fix(&[]).stdout(predicate::str::contains("Fixed 2 issue(s)"));
+        let fixed: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&config_path).unwrap()).unwrap();
+        assert_eq!(fixed["allowedPaths"], serde_json::json!(["~/a"]));
+        assert_eq!(fixed["customInstructions"], serde_json::json!([]));
+
+        // An explicit empty array is not an issue
+        fs::write(&config_path, r#"{"allowedPaths": []}"#).unwrap();
+        fix(&[]).stdout(predicate::str::contains("No fixable issues found"));
+        let fixed: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&config_path).unwrap()).unwrap();
+        assert_eq!(fixed["allowedPaths"], serde_json::json!([]));
+    }
+
+    #[test]
+    fn test_usage_tracking_opt_in() {
+        let temp_dir = TempDir::new().unwrap();
+        let home = temp_dir.path().join("home");
+        let config_dir = home.join(".config").join("claude");
+        fs::create_dir_all(&config_dir).unwrap();
+        let usage_path = config_dir.join("usage.json");
+
+        let ccm = |args: &[&str]| {
+            let mut cmd = Command::cargo_bin("ccm").unwrap();
+            cmd.env("HOME", &home)
+                .env("CCM_HOME", home.join(".config").join("claude"))
+                .args(args);
+            cmd.assert().success()
+        };
+
+        // Disabled by default: nothing is written
+        fs::write(config_dir.join("config.json"), "{}").unwrap();
+        ccm(&["stats"]);
+        assert!(!usage_path.exists());
+
+        // Enabled: each invocation is counted
+        fs::write(
+            config_dir.join("config.json"),
+            r#"{"ccm": {"usageTracking": true}}"#,
+        )
+        .unwrap();
+        ccm(&["stats"]);
+        ccm(&["stats"]);
+        ccm(&["stats", "--usage"]).stdout(predicate::str::is_match(r"stats\s+3").unwrap());
+
+        ccm(&["usage", "reset"]).stdout(predicate::str::contains("cleared"));
+        assert!(!usage_path.exists());
+    }
+
+    #[test]
+    fn test_support_bundle_written() {
+        let temp_dir = TempDir::new().unwrap();
+        let home = temp_dir.path().join("home");
+        let config_dir = home.join(".config").join("claude");
+        fs::create_dir_all(&config_dir).unwrap();
+        fs::write(
+            config_dir.join("config.json"),
+            r#"{"mcpServers": {"gh": {"command": "npx", "env": {"GITHUB_TOKEN": "not-a-real-token"}}}}"#,
+        )
+        .unwrap();
+        let out = temp_dir.path().join("bundle.zip");
+
+        Command::cargo_bin("ccm")
+            .unwrap()
+            .env("HOME", &home)
+            .env("CCM_HOME", home.join(".config").join("claude"))
+            .args(["support-bundle", "--out", out.to_str().unwrap()])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Support bundle written"))
+            .stdout(predicate::str::contains("mcpServers.gh.env.GITHUB_TOKEN"))
+            .stdout(predicate::str::contains("not-a-real-token").not())
+            .stdout(predicate::str::contains("journal.jsonl").not())
+            .stdout(predicate::str::contains("log.txt").not());
+
+        assert!(out.exists());
+    }
+
+    #[test]
+    fn test_support_bundle_includes_journal_and_log() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_dir = temp_dir.path().join("home").join(".config").join("claude");
+        fs::create_dir_all(&config_dir).unwrap();
+        fs::write(config_dir.join("config.json"), "{}").unwrap();
+        fs::write(
+            config_dir.join("ccm-ops.log"),
+            r#"{"op": "add", "server": "fetch"}"#.to_string() + "\n",
+        )
+        .unwrap();
+        let log_file = temp_dir.path().join("ccm.log");
+        let out = temp_dir.path().join("bundle.zip");
+
+        Command::cargo_bin("ccm")
+            .unwrap()
+            .env("HOME", temp_dir.path().join("home"))
+            .env("CCM_HOME", &config_dir)
+            .env("CCM_LOG_FILE", &log_file)
+            .args([
+                "--verbose",
+                "support-bundle",
+                "--out",
+                out.to_str().unwrap(),
+            ])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("journal.jsonl"))
+            .stdout(predicate::str::contains("log.txt"));
+
+        assert!(fs::read_to_string(&log_file)
+            .unwrap()
+            .contains("Claude Config Manager v"));
+    }
+
+    #[test]
+    fn test_state_clear_help() {
+        Command::cargo_bin("ccm")
+            .unwrap()
+            .args(["state", "clear", "--help"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Clear stored UI state"));
+    }
+
+    #[test]
+    fn test_bulk_commands_continue_past_corrupt_project() {
+        let temp_dir = TempDir::new().unwrap();
+        let home = temp_dir.path().join("home");
+        let workspace = temp_dir.path().join("workspace");
+        for name in ["alpha", "beta", "gamma"] {
+            let claude_dir = workspace.join(name).join(".claude");
+            fs::create_dir_all(&claude_dir).unwrap();
+            fs::write(
+                claude_dir.join("config.json"),
+                format!(r#"{{"customInstructions": ["{name} marker"]}}"#),
+            )
+            .unwrap();
+        }
+
+        let ccm = |args: &[&str]| {
+            let mut cmd = Command::cargo_bin("ccm").unwrap();
+            cmd.env("HOME", &home)
+                .env("CCM_HOME", home.join(".config").join("claude"))
+                .current_dir(&workspace)
+                .args(args);
+            cmd.assert()
+        };
+
+        // Clean run
+        ccm(&["project", "check"])
+            .code(0)
+            .stdout(predicate::str::contains("3 ok, 0 failed"));
+
+        // One corrupt project: the other two are still processed
+        fs::write(
+            workspace.join("beta").join(".claude").join("config.json"),
+            "{ not json",
+        )
+        .unwrap();
+        ccm(&["project", "check"])
+            .code(3)
+            .stdout(predicate::str::contains("2 ok, 1 failed"))
+            .stderr(predicate::str::contains("beta"));
+        ccm(&["search", "--value", "marker", "--all-projects"])
+            .code(3)
+            .stdout(predicate::str::contains("alpha marker"))
+            .stdout(predicate::str::contains("gamma marker"))
+            .stderr(predicate::str::contains("1 of 3 file(s)"))
+            .stderr(predicate::str::contains("beta"));
+
+        // --strict aborts before producing results
+        ccm(&["project", "check", "--strict"])
+            .code(1)
+            .stdout(predicate::str::contains("ok").not())
+            .stderr(predicate::str::contains("--strict"));
+        ccm(&["search", "--value", "marker", "--all-projects", "--strict"])
+            .code(1)
+            .stdout(predicate::str::contains("alpha marker").not());
+    }
+
+    #[test]
+    fn test_config_wait_for_key_and_timeout() {
+        let temp_dir = TempDir::new().unwrap();
+        let home = temp_dir.path().join("home");
+        let global_dir = home.join(".config").join("claude");
+        let config_path = global_dir.join("config.json");
+
+        let ccm = |args: &[&str]| {
+            let mut cmd = Command::cargo_bin("ccm").unwrap();
+            cmd.env("HOME", &home)
+                .env("CCM_HOME", home.join(".config").join("claude"))
+                .args(["config", "wait"])
+                .args(args);
+            cmd.assert()
+        };
+
+        // Config doesn't exist yet: times out with a distinct exit code
+        ccm(&["--timeout", "300ms", "--interval", "50ms"])
+            .code(124)
+            .stderr(predicate::str::contains("Timed out"));
+
+        // Another process enables the server after a delay
+        let writer_path = config_path.clone();
+        let writer = std::thread::spawn(move || {
+            std::thread::sleep(std::time::Duration::from_millis(300));
+            fs::create_dir_all(writer_path.parent().unwrap()).unwrap();
+            fs::write(
+                &writer_path,
+                r#"{"mcpServers": {"npx": {"command": "npx", "enabled": fa"#,
+            )
+            .unwrap();
+            std::thread::sleep(std::time::Duration::from_millis(100));
+            fs::write(
+                &writer_path,
+                r#"{"mcpServers": {"npx": {"command": "npx", "enabled": true}}}"#,
+            )
+            .unwrap();
+        });
+
+        ccm(&[
+            "--key",
+            "mcpServers.npx.enabled",
+            "--until",
+            "true",
+            "--timeout",
+            "10s",
+            "--interval",
+            "50ms",
+        ])
+        .code(0)
+        .stdout(predicate::str::contains("mcpServers.npx.enabled = true"));
+        writer.join().unwrap();
+
+        // Already satisfied: returns immediately
+        ccm(&[
+            "--key",
+            "mcpServers.npx.enabled",
+            "--until",
+            "true",
+            "--timeout",
+            "1s",
+        ])
+        .code(0);
+
+        // Any change to the key's value; none happens, so this times out
+        ccm(&["--key", "mcpServers.npx.enabled", "--timeout", "200ms"]).code(124);
+    }
+
+    #[test]
+    fn test_project_archive_hides_projects() {
+        let temp_dir = TempDir::new().unwrap();
+        let home = temp_dir.path().join("home");
+        let workspace = temp_dir.path().join("workspace");
+        for name in ["alpha", "beta", "gamma", "delta"] {
+            let claude_dir = workspace.join(name).join(".claude");
+            fs::create_dir_all(&claude_dir).unwrap();
+            fs::write(
+                claude_dir.join("config.json"),
+                format!(r#"{{"customInstructions": ["{name} marker"]}}"#),
+            )
+            .unwrap();
+        }
+
+        // delta was archived with a marker in its config
+        let delta_config = workspace.join("delta").join(".claude").join("config.json");
+        fs::write(
+            &delta_config,
+            r#"{"customInstructions": ["delta marker"], "ccm": {"archived": true}}"#,
+        )
+        .unwrap();
+
+        // gamma is registered already
+        let registry_dir = home.join(".config").join("claude");
+        fs::create_dir_all(&registry_dir).unwrap();
+        let gamma_root = workspace.join("gamma").canonicalize().unwrap();
+        fs::write(
+            registry_dir.join("projects.json"),
+            serde_json::json!({ gamma_root.to_str().unwrap(): {} }).to_string(),
+        )
+        .unwrap();
+
+        let ccm = |args: &[&str]| {
+            let mut cmd = Command::cargo_bin("ccm").unwrap();
+            cmd.env("HOME", &home)
+                .env("CCM_HOME", home.join(".config").join("claude"))
+                .current_dir(&workspace)
+                .args(args);
+            cmd.assert()
+        };
+
+        let registry = || -> serde_json::Value {
+            serde_json::from_str(&fs::read_to_string(registry_dir.join("projects.json")).unwrap())
+                .unwrap()
+        };
+        let archived = |name: &str| {
+            let root = workspace.join(name).canonicalize().unwrap();
+            registry()[root.to_str().unwrap()]["archived"].as_bool()
+        };
+
+        // Archiving registers the project and flags it in the registry,
+        // leaving the config untouched
+        ccm(&["project", "archive", "beta"])
+            .success()
+            .stdout(predicate::str::contains("Archived project"));
+        ccm(&["project", "archive", "gamma"]).success();
+        assert_eq!(archived("beta"), Some(true));
+        assert_eq!(archived("gamma"), Some(true));
+        for name in ["beta", "gamma"] {
+            let config =
+                fs::read_to_string(workspace.join(name).join(".claude").join("config.json"))
+                    .unwrap();
+            assert!(!config.contains("archived"));
+        }
+
+        // Listing hides archived projects unless asked, and registers the
+        // rest; delta's marker carries over into its entry
+        ccm(&["project", "list"])
+            .success()
+            .stdout(predicate::str::contains("alpha"))
+            .stdout(predicate::str::contains("beta").not())
+            .stdout(predicate::str::contains("gamma").not())
+            .stdout(predicate::str::contains("delta").not());
+        assert_eq!(archived("alpha"), Some(false));
+        assert_eq!(archived("delta"), Some(true));
+        ccm(&["project", "list", "--include-archived"])
+            .success()
+            .stdout(predicate::str::contains("beta  [archived]"))
+            .stdout(predicate::str::contains("gamma  [archived]"))
+            .stdout(predicate::str::contains("delta  [archived]"));
+
+        // Bulk commands skip them too
+        ccm(&["project", "check"])
+            .success()
+            .stdout(predicate::str::contains("1 ok, 0 failed"));
+        ccm(&["search", "--value", "marker", "--all-projects"])
+            .success()
+            .stdout(predicate::str::contains("alpha marker"))
+            .stdout(predicate::str::contains("beta marker").not());
+        ccm(&["project", "check", "--include-archived"])
+            .success()
+            .stdout(predicate::str::contains("4 ok, 0 failed"));
+
+        // Unarchive clears the flag, and delta's marker with it
+        for name in ["beta", "gamma", "delta"] {
+            ccm(&["project", "unarchive", name])
+                .success()
+                .stdout(predicate::str::contains("Unarchived project"));
+        }
+        assert!(!fs::read_to_string(&delta_config)
+            .unwrap()
+            .contains("archived"));
+        ccm(&["project", "list"])
+            .success()
+            .stdout(predicate::str::contains("beta"))
+            .stdout(predicate::str::contains("gamma"))
+            .stdout(predicate::str::contains("delta"))
+            .stdout(predicate::str::contains("[archived]").not());
+        ccm(&["project", "unarchive", "alpha"])
+            .success()
+            .stdout(predicate::str::contains("not archived"));
+    }
+
+    #[test]
+    fn test_config_set_append_keeps_existing_entries() {
+        let temp_dir = TempDir::new().unwrap();
+        let home = temp_dir.path().join("home");
+        let config_path = home.join(".config").join("claude").join("config.json");
+
+        ccm_run(&home, &["config", "set", "allowedPaths", "~/projects"]);
+        ccm_run(
+            &home,
+            &["config", "set", "--append", "allowedPaths", "~/work"],
+        );
+        ccm_run(
+            &home,
+            &[
+                "config",
+                "set",
+                "--append",
+                "--dedupe",
+                "allowedPaths",
+                "~/work",
+            ],
+        );
+
+        let config: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&config_path).unwrap()).unwrap();
+        assert_eq!(
+            config["allowedPaths"],
+            serde_json::json!(["~/projects", "~/work"])
+        );
+
+        // Scalar fields cannot be appended to
+        Command::cargo_bin("ccm")
+            .unwrap()
+            .env("HOME", &home)
+            .env("CCM_HOME", home.join(".config").join("claude"))
+            .args([
+                "config",
+                "set",
+                "--append",
+                "mcpServers.npx.enabled",
+                "true",
+            ])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("only applies to arrays"));
+    }
+
+    #[test]
+    fn test_history_snapshot_and_restore_dir() {
+        let temp_dir = TempDir::new().unwrap();
+        let home = temp_dir.path().join("home");
+        let project = temp_dir.path().join("project");
+        let claude_dir = project.join(".claude");
+        fs::create_dir_all(claude_dir.join("prompts")).unwrap();
+        fs::write(claude_dir.join("config.json"), "{}").unwrap();
+        fs::write(claude_dir.join("prompts").join("review.md"), "Review").unwrap();
+        let project_arg = project.to_str().unwrap();
+
+        ccm_run(
+            &home,
+            &["history", "snapshot-dir", "--project", project_arg],
+        );
+        fs::write(claude_dir.join("prompts").join("review.md"), "Changed").unwrap();
+
+        Command::cargo_bin("ccm")
+            .unwrap()
+            .env("HOME", &home)
+            .env("CCM_HOME", home.join(".config").join("claude"))
+            .args([
+                "history",
+                "restore-dir",
+                "0",
+                "--project",
+                project_arg,
+                "--yes",
+            ])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Safety snapshot"))
+            .stdout(predicate::str::contains("Restored 2 file(s)"));
+        assert_eq!(
+            fs::read_to_string(claude_dir.join("prompts").join("review.md")).unwrap(),
+            "Review"
+        );
+    }
+
+    #[test]
+    fn test_config_set_remove_array_element() {
+        let temp_dir = TempDir::new().unwrap();
+        let home = temp_dir.path().join("home");
+        let config_path = home.join(".config").join("claude").join("config.json");
+        let ccm = |args: &[&str]| {
+            let mut cmd = Command::cargo_bin("ccm").unwrap();
+            cmd.env("HOME", &home)
+                .env("CCM_HOME", home.join(".config").join("claude"))
+                .args(args);
+            cmd.assert()
+        };
+
+        ccm(&[
+            "config",
+            "set",
+            "allowedPaths",
+            r#"["~/old", "~/projects"]"#,
+        ])
+        .success();
+        ccm(&["config", "set", "allowedPaths", "--remove", "~/old"])
+            .success()
+            .stdout(predicate::str::contains("Removed 1 element(s)"));
+
+        let config: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&config_path).unwrap()).unwrap();
+        assert_eq!(config["allowedPaths"], serde_json::json!(["~/projects"]));
+
+        // Missing values fail unless --ignore-missing is given
+        ccm(&["config", "set", "allowedPaths", "--remove", "~/old"])
+            .failure()
+            .stderr(predicate::str::contains("'~/old' is not in 'allowedPaths'"));
+        ccm(&[
+            "config",
+            "set",
+            "allowedPaths",
+            "--remove",
+            "~/old",
+            "--ignore-missing",
+        ])
+        .success()
+        .stdout(predicate::str::contains("nothing to remove"));
+    }
+
+    #[test]
+    fn test_mcp_operation_log_opt_in() {
+        let temp_dir = TempDir::new().unwrap();
+        let home = temp_dir.path().join("home");
+        let config_dir = home.join(".config").join("claude");
+        fs::create_dir_all(&config_dir).unwrap();
+        let log_path = config_dir.join("ccm-ops.log");
+
+        let ccm = |args: &[&str]| {
+            Command::cargo_bin("ccm")
+                .unwrap()
+                .current_dir(temp_dir.path())
+                .env("HOME", &home)
+                .env("CCM_HOME", home.join(".config").join("claude"))
+                .args(args)
+                .assert()
+                .success();
+        };
+
+        // Disabled by default
+        fs::write(config_dir.join("config.json"), "{}").unwrap();
+        ccm(&["mcp", "add", "fetch", "--command", "uvx"]);
+        ccm(&["mcp", "remove", "fetch"]);
+        assert!(!log_path.exists());
+
+        fs::write(
+            config_dir.join("config.json"),
+            r#"{"ccm": {"auditLog": true}}"#,
+        )
+        .unwrap();
+        ccm(&["mcp", "add", "fetch", "--command", "uvx"]);
+        ccm(&["mcp", "disable", "fetch"]);
+        ccm(&["mcp", "remove", "fetch"]);
+
+        let ops: Vec<String> = fs::read_to_string(&log_path)
+            .unwrap()
+            .lines()
+            .map(|line| {
+                let entry: serde_json::Value = serde_json::from_str(line).unwrap();
+                assert_eq!(entry["server"], "fetch");
+                entry["op"].as_str().unwrap().to_string()
+            })
+            .collect();
+        assert_eq!(ops, ["add", "disable", "remove"]);
+    }
+
+    #[test]
+    fn test_mcp_move_global_to_project() {
+        let temp_dir = TempDir::new().unwrap();
+        let home = temp_dir.path().join("home");
+        let config_dir = home.join(".config").join("claude");
+        let project = temp_dir.path().join("app");
+        fs::create_dir_all(&config_dir).unwrap();
+        fs::create_dir_all(&project).unwrap();
+        fs::write(
+            config_dir.join("config.json"),
+            r#"{"mcpServers": {"fetch": {"command": "uvx", "enabled": true}}}"#,
+        )
+        .unwrap();
+
+        let project_arg = project.to_str().unwrap();
+        Command::cargo_bin("ccm")
+            .unwrap()
+            .current_dir(temp_dir.path())
+            .env("HOME", &home)
+            .env("CCM_HOME", home.join(".config").join("claude"))
+            .args(["mcp", "--project", project_arg, "move", "fetch"])
+            .args(["--from", "global", "--to", "project"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("moved from global to project"));
+
+        let global: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(config_dir.join("config.json")).unwrap())
+                .unwrap();
+        assert!(global.get("mcpServers").is_none());
+        let local: serde_json::Value = serde_json::from_str(
+            &fs::read_to_string(project.join(".claude").join("config.json")).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(local["mcpServers"]["fetch"]["command"], "uvx");
+    }
+
+    #[test]
+    fn test_history_show_prints_backup() {
+        let temp_dir = TempDir::new().unwrap();
+        let home = temp_dir.path().join("home");
+
+        // Two writes produce one backup holding the first value
+        ccm_run(&home, &["config", "set", "allowedPaths", "~/first"]);
+        ccm_run(&home, &["config", "set", "allowedPaths", "~/second"]);
+
+        let ccm = |args: &[&str]| {
+            let mut cmd = Command::cargo_bin("ccm").unwrap();
+            cmd.env("HOME", &home)
+                .env("CCM_HOME", home.join(".config").join("claude"))
+                .args(args);
+            cmd.output().unwrap()
+        };
+        let backups: serde_json::Value =
+            serde_json::from_slice(&ccm(&["history", "list", "--json"]).stdout).unwrap();
+        let backup = backups[0]["path"].as_str().unwrap();
+
+        let output = ccm(&["history", "show", backup, "--output", "json"]);
+        assert!(output.status.success());
+        let shown: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        assert_eq!(shown, serde_json::json!({"allowedPaths": ["~/first"]}));
+
+        let missing = home.join("missing.json");
+        assert!(!ccm(&["history", "show", missing.to_str().unwrap()])
+            .status
+            .success());
+    }
+
+    #[test]
+    fn test_mcp_usage_flags_unreferenced_servers() {
+        let temp_dir = TempDir::new().unwrap();
+        let home = temp_dir.path().join("home");
+        let config_dir = home.join(".config").join("claude");
+        fs::create_dir_all(&config_dir).unwrap();
+        fs::write(
+            config_dir.join("config.json"),
+            r#"{
+                "mcpServers": {
+                    "fetch": {"command": "uvx", "enabled": true},
+                    "postgres": {"command": "npx", "enabled": true}
+                },
+                "customInstructions": ["Use fetch for web pages"]
+            }"#,
+        )
+        .unwrap();
+
+        Command::cargo_bin("ccm")
+            .unwrap()
+            .current_dir(temp_dir.path())
+            .env("HOME", &home)
+            .env("CCM_HOME", home.join(".config").join("claude"))
+            .args(["mcp", "usage"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains(
+                "customInstructions[0]: Use fetch for web pages",
+            ))
+            .stdout(predicate::str::contains("postgres: not referenced"))
+            .stdout(predicate::str::contains("Cleanup candidates: postgres"));
+    }
+
+    #[test]
+    fn test_history_restore_only_selected_keys() {
+        let temp_dir = TempDir::new().unwrap();
+        let home = temp_dir.path().join("home");
+        let config_dir = home.join(".config").join("claude");
+        fs::create_dir_all(&config_dir).unwrap();
+        let backup = temp_dir.path().join("config_20250101_000000.json");
+        fs::write(
+            &backup,
+            r#"{
+                "mcpServers": {"fetch": {"command": "uvx", "enabled": true}},
+                "customInstructions": ["Be concise"]
+            }"#,
+        )
+        .unwrap();
+        let config_path = config_dir.join("config.json");
+        let current = r#"{
+                "mcpServers": {
+                    "fetch": {"command": "broken", "enabled": false},
+                    "sqlite": {"command": "uvx", "enabled": true}
+                },
+                "customInstructions": ["Be thorough"]
+            }"#;
+        fs::write(&config_path, current).unwrap();
+        let backup = backup.to_str().unwrap();
+        let ccm = |args: &[&str]| {
+            let mut cmd = Command::cargo_bin("ccm").unwrap();
+            cmd.env("HOME", &home)
+                .env("CCM_HOME", home.join(".config").join("claude"))
+                .args(args);
+            cmd.output().unwrap()
+        };
+
+        let output = ccm(&[
+            "history",
+            "restore",
+            backup,
+            "--only",
+            "mcpServers.fetch,mcpServers.sqlite",
+            "--dry-run",
+        ]);
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(output.status.success(), "{stdout}");
+        assert!(stdout.contains("~ mcpServers"));
+        assert!(stdout
+            .contains("Not in backup (kept; use --allow-remove to remove): mcpServers.sqlite"));
+        assert_eq!(fs::read_to_string(&config_path).unwrap(), current);
+
+        let output = ccm(&[
+            "history",
+            "restore",
+            backup,
+            "--only",
+            "mcpServers.fetch,mcpServers.sqlite",
+            "--allow-remove",
+            "--yes",
+        ]);
+        assert!(output.status.success());
+        let config: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&config_path).unwrap()).unwrap();
+        let servers = config["mcpServers"].as_object().unwrap();
+        assert_eq!(servers.len(), 1);
+        assert_eq!(servers["fetch"]["command"], "uvx");
+        assert_eq!(servers["fetch"]["enabled"], true);
+        assert_eq!(
+            config["customInstructions"],
+            serde_json::json!(["Be thorough"])
+        );
+    }
+
+    /// Write an executable shell script that stands in for the user's editor
+    #[cfg(unix)]
+    fn editor_script(dir: &std::path::Path, body: &str) -> PathBuf {
+        use std::os::unix::fs::PermissionsExt;
+
+        let script = dir.join("editor.sh");
+        fs::write(&script, format!("#!/bin/sh\n{body}\n")).unwrap();
+        fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+        script
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_config_edit_accepts_valid_changes() {
+        let temp_dir = TempDir::new().unwrap();
+        let home = temp_dir.path().join("home");
+        let config_path = home.join(".config").join("claude").join("config.json");
+        let editor = editor_script(
+            temp_dir.path(),
+            r#"printf '{"allowedPaths": ["~/edited"]}' > "$1""#,
+        );
+
+        // The missing config is created first, after confirmation
+        assert_cmd::Command::cargo_bin("ccm")
+            .unwrap()
+            .env("HOME", &home)
+            .env("CCM_HOME", home.join(".config").join("claude"))
+            .env_remove("VISUAL")
+            .env("EDITOR", &editor)
+            .args(["config", "edit"])
+            .write_stdin("y\n")
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Created"))
+            .stdout(predicate::str::contains("✓ Saved"));
+
+        assert_eq!(
+            fs::read_to_string(&config_path).unwrap(),
+            r#"{"allowedPaths": ["~/edited"]}"#
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_config_edit_offers_restore_for_invalid_json() {
+        let temp_dir = TempDir::new().unwrap();
+        let home = temp_dir.path().join("home");
+        let config_dir = home.join(".config").join("claude");
+        fs::create_dir_all(&config_dir).unwrap();
+        let config_path = config_dir.join("config.json");
+        let original = r#"{"allowedPaths": ["~/projects"]}"#;
+        fs::write(&config_path, original).unwrap();
+        let editor = editor_script(temp_dir.path(), r#"printf '{"allowedPaths": [' > "$1""#);
+        let edit = |answer: &str| {
+            assert_cmd::Command::cargo_bin("ccm")
+                .unwrap()
+                .env("HOME", &home)
+                .env("CCM_HOME", home.join(".config").join("claude"))
+                .env_remove("VISUAL")
+                .env("EDITOR", &editor)
+                .args(["config", "edit"])
+                .write_stdin(answer)
+                .assert()
+        };
+
+        edit("y\n")
+            .success()
+            .stdout(predicate::str::contains("The edited config is invalid"))
+            .stdout(predicate::str::contains(
+                "Restore the version from before the edit?",
+            ))
+            .stdout(predicate::str::contains("✓ Restored"));
+        assert_eq!(fs::read_to_string(&config_path).unwrap(), original);
+
+        // Declining leaves the broken file and points at the backup
+        edit("n\n")
+            .failure()
+            .stderr(predicate::str::contains("was left invalid"));
+        assert_eq!(
+            fs::read_to_string(&config_path).unwrap(),
+            r#"{"allowedPaths": ["#
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_config_open_applies_editor_changes() {
+        let temp_dir = TempDir::new().unwrap();
+        let home = temp_dir.path().join("home");
+        let config_dir = home.join(".config").join("claude");
+        fs::create_dir_all(&config_dir).unwrap();
+        let config_path = config_dir.join("config.json");
+        fs::write(&config_path, r#"{"allowedPaths": ["~/projects"]}"#).unwrap();
+        let editor = editor_script(
+            temp_dir.path(),
+            r#"sed -i 's/^{/{"mcpServers": {"known": {"command": "uvx", "enabled": true}}, /' "$1""#,
+        );
+        let open = |editor: Option<&std::path::Path>| {
+            let mut cmd = Command::cargo_bin("ccm").unwrap();
+            cmd.env("HOME", &home)
+                .env("CCM_HOME", home.join(".config").join("claude"))
+                .env_remove("VISUAL")
+                .env_remove("EDITOR")
+                .args(["config", "open"]);
+            if let Some(editor) = editor {
+                cmd.env("EDITOR", editor);
+            }
+            cmd.assert()
+        };
+
+        open(None)
+            .failure()
+            .stderr(predicate::str::contains("set $EDITOR or $VISUAL"));
+
+        open(Some(&editor))
+            .success()
+            .stdout(predicate::str::contains("is valid"));
+        let config: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&config_path).unwrap()).unwrap();
+        assert_eq!(config["mcpServers"]["known"]["command"], "uvx");
+        assert_eq!(config["allowedPaths"], serde_json::json!(["~/projects"]));
+
+        // An edit that breaks validation is reported
+        let editor = editor_script(temp_dir.path(), r#"printf '{"allowedPaths": [""]}' > "$1""#);
+        open(Some(&editor))
+            .failure()
+            .stderr(predicate::str::contains("fails validation"));
+    }
+
+    #[test]
+    fn test_config_shadows_classifies_projects() {
+        let temp_dir = TempDir::new().unwrap();
+        let home = temp_dir.path().join("home");
+        let config_dir = home.join(".config").join("claude");
+        fs::create_dir_all(&config_dir).unwrap();
+        fs::write(
+            config_dir.join("config.json"),
+            r#"{"mcpServers": {"fetch": {"command": "uvx", "enabled": true}}}"#,
+        )
+        .unwrap();
+
+        let workspace = temp_dir.path().join("workspace");
+        let project = |name: &str, content: &str| {
+            let claude_dir = workspace.join(name).join(".claude");
+            fs::create_dir_all(&claude_dir).unwrap();
+            fs::write(claude_dir.join("config.json"), content).unwrap();
+        };
+        project(
+            "overriding",
+            r#"{"mcpServers": {"fetch": {"command": "npx", "enabled": true}}}"#,
+        );
+        project(
+            "matching",
+            r#"{"mcpServers": {"fetch": {"command": "uvx", "enabled": false}}}"#,
+        );
+        project("inheriting", r#"{"allowedPaths": ["~/work"]}"#);
+        project("broken", r#"{"mcpServers": "#);
+
+        let ccm = |output: &str| {
+            let mut cmd = Command::cargo_bin("ccm").unwrap();
+            cmd.env("HOME", &home)
+                .env("CCM_HOME", home.join(".config").join("claude"))
+                .args([
+                    "config",
+                    "--output",
+                    output,
+                    "shadows",
+                    "mcpServers.fetch.command",
+                ])
+                .arg("--path")
+                .arg(&workspace);
+            cmd.output().unwrap()
+        };
+
+        let output = ccm("table");
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert_eq!(output.status.code(), Some(3), "{stdout}");
+        assert!(stdout.contains(r#"Global mcpServers.fetch.command = "uvx""#));
+        assert!(stdout.contains(r#"overriding = "npx""#));
+        assert!(stdout.contains("matches") && stdout.contains("matching"));
+        assert!(stdout.contains("inherits") && stdout.contains("inheriting"));
+        assert!(String::from_utf8_lossy(&output.stderr).contains("broken"));
+
+        let output = ccm("json");
+        let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        assert_eq!(report["global"], "uvx");
+        let states: std::collections::HashMap<String, serde_json::Value> = report["projects"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|project| {
+                let path = project["config_path"].as_str().unwrap();
+                let name = path.split('/').rev().nth(2).unwrap().to_string();
+                (name, project.clone())
+            })
+            .collect();
+        assert_eq!(states["overriding"]["state"], "overrides");
+        assert_eq!(states["overriding"]["value"], "npx");
+        assert_eq!(states["matching"]["state"], "matches");
+        assert_eq!(states["inheriting"]["state"], "inherits");
+        assert_eq!(report["failures"].as_array().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_config_shadows_all_uses_scanned_projects() {
+        let temp_dir = TempDir::new().unwrap();
+        let home = temp_dir.path().join("home");
+        let config_dir = home.join(".config").join("claude");
+        fs::create_dir_all(&config_dir).unwrap();
+        fs::write(
+            config_dir.join("config.json"),
+            r#"{"mcpServers": {"fetch": {"command": "uvx", "enabled": true}}}"#,
+        )
+        .unwrap();
+
+        let workspace = temp_dir.path().join("workspace");
+        for (name, command) in [("api", "npx"), ("web", "bunx"), ("old", "deno")] {
+            let claude_dir = workspace.join(name).join(".claude");
+            fs::create_dir_all(&claude_dir).unwrap();
+            fs::write(
+                claude_dir.join("config.json"),
+                format!(
+                    r#"{{"mcpServers": {{"fetch": {{"command": "{command}", "enabled": true}}}}}}"#
+                ),
+            )
+            .unwrap();
+        }
+
+        let ccm = |args: &[&str]| {
+            let mut cmd = Command::cargo_bin("ccm").unwrap();
+            cmd.env("HOME", &home)
+                .env("CCM_HOME", &config_dir)
+                .current_dir(temp_dir.path())
+                .args(args);
+            cmd.assert()
+        };
+        let shadows = || ccm(&["config", "shadows", "--all", "mcpServers.fetch.command"]);
+
+        // Nothing is registered before a scan
+        shadows()
+            .success()
+            .stdout(predicate::str::contains("npx").not());
+
+        ccm(&["project", "scan", "--path", workspace.to_str().unwrap()]).success();
+        shadows()
+            .success()
+            .stdout(predicate::str::contains(r#"= "npx""#))
+            .stdout(predicate::str::contains(r#"= "bunx""#))
+            .stdout(predicate::str::contains(r#"= "deno""#));
+
+        // Archived projects are left out
+        ccm(&[
+            "project",
+            "archive",
+            workspace.join("old").to_str().unwrap(),
+        ])
+        .success();
+        shadows()
+            .success()
+            .stdout(predicate::str::contains(r#"= "npx""#))
+            .stdout(predicate::str::contains(r#"= "deno""#).not());
+    }
+
+    /// Run `ccm config apply <file>` against a fresh global config
+    fn apply_settings(
+        temp_dir: &TempDir,
+        settings_name: &str,
+        settings: &str,
+    ) -> (assert_cmd::assert::Assert, std::path::PathBuf) {
+        let home = temp_dir.path().join("home");
+        let config_dir = home.join(".config").join("claude");
+        fs::create_dir_all(&config_dir).unwrap();
+        let config_path = config_dir.join("config.json");
+        fs::write(
+            &config_path,
+            r#"{"mcpServers": {"npx": {"command": "npx", "enabled": true}}}"#,
+        )
+        .unwrap();
+        let settings_path = temp_dir.path().join(settings_name);
+        fs::write(&settings_path, settings).unwrap();
+
+        let assert = Command::cargo_bin("ccm")
+            .unwrap()
+            .env("HOME", &home)
+            .env("CCM_HOME", home.join(".config").join("claude"))
+            .args(["config", "apply"])
+            .arg(&settings_path)
+            .assert();
+        (assert, config_path)
+    }
+
+    #[test]
+    fn test_config_apply_key_value_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let (assert, config_path) = apply_settings(
+            &temp_dir,
+            "settings.env",
+            "# batch\nmcpServers.npx.enabled=false\nmcpServers.npx.args=-y pkg\nallowedPaths=[\"~/a\", \"~/b\"]\n",
+        );
+        assert
+            .success()
+            .stdout(predicate::str::contains("Applied 3 setting(s)"));
+
+        let config: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&config_path).unwrap()).unwrap();
+        assert_eq!(config["mcpServers"]["npx"]["enabled"], false);
+        assert_eq!(
+            config["mcpServers"]["npx"]["args"],
+            serde_json::json!(["-y", "pkg"])
+        );
+        assert_eq!(config["allowedPaths"], serde_json::json!(["~/a", "~/b"]));
+
+        // One write, so one backup
+        let backups = backup_files(&config_path.parent().unwrap().join("backups"));
+        assert_eq!(backups, 1);
+    }
+
+    #[test]
+    fn test_config_apply_json_document() {
+        let temp_dir = TempDir::new().unwrap();
+        let (assert, config_path) = apply_settings(
+            &temp_dir,
+            "settings.json",
+            r#"{"mcpServers.fetch.command": "uvx", "mcpServers.fetch.enabled": true, "theme": "dark"}"#,
+        );
+        assert.success();
+
+        let config: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&config_path).unwrap()).unwrap();
+        assert_eq!(config["mcpServers"]["fetch"]["command"], "uvx");
+        assert_eq!(config["mcpServers"]["fetch"]["enabled"], true);
+        assert_eq!(config["mcpServers"]["npx"]["command"], "npx");
+        assert_eq!(config["theme"], "dark");
+    }
+
+    #[test]
+    fn test_config_apply_invalid_entry_aborts_batch() {
+        let temp_dir = TempDir::new().unwrap();
+        let original = r#"{"mcpServers": {"npx": {"command": "npx", "enabled": true}}}"#;
+
+        let (assert, config_path) = apply_settings(
+            &temp_dir,
+            "settings.env",
+            "allowedPaths=~/a\nmcpServers.npx.enabled=maybe\nmcpServers.npx.colour=red\n",
+        );
+        assert
+            .failure()
+            .stderr(predicate::str::contains(
+                "No settings were applied; 2 invalid",
+            ))
+            .stderr(predicate::str::contains("mcpServers.npx.enabled"))
+            .stderr(predicate::str::contains("mcpServers.npx.colour"));
+        assert_eq!(fs::read_to_string(&config_path).unwrap(), original);
+
+        // A batch that only fails validation as a whole is rejected too
+        let (assert, config_path) =
+            apply_settings(&temp_dir, "settings.json", r#"{"allowedPaths": [""]}"#);
+        assert
+            .failure()
+            .stderr(predicate::str::contains("No settings were applied"));
+        assert_eq!(fs::read_to_string(&config_path).unwrap(), original);
+    }
+
+    #[test]
+    fn test_config_init_from_project_sections() {
+        let temp_dir = TempDir::new().unwrap();
+        let home = temp_dir.path().join("home");
+        let source = temp_dir.path().join("source");
+        fs::create_dir_all(source.join(".claude")).unwrap();
+        fs::write(
+            source.join(".claude").join("config.json"),
+            r#"{"mcpServers": {"fetch": {"command": "uvx", "enabled": true}}, "customInstructions": ["Be terse"]}"#,
+        )
+        .unwrap();
+        let target = temp_dir.path().join("target");
+        fs::create_dir_all(&target).unwrap();
+
+        let init = |extra: &[&str]| {
+            let mut cmd = Command::cargo_bin("ccm").unwrap();
+            cmd.env("HOME", &home)
+                .env("CCM_HOME", home.join(".config").join("claude"))
+                .arg("config")
+                .arg("--project")
+                .arg(&target)
+                .args(["init", "--only", "mcpServers", "--from-project"])
+                .arg(&source)
+                .args(extra)
+                .assert()
+        };
+
+        init(&[])
+            .success()
+            .stdout(predicate::str::contains("mcpServers: 1"));
+        let config_path = target.join(".claude").join("config.json");
+        let config: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&config_path).unwrap()).unwrap();
+        assert_eq!(config["mcpServers"]["fetch"]["command"], "uvx");
+        assert!(config.get("customInstructions").is_none());
+
+        init(&[])
+            .failure()
+            .stderr(predicate::str::contains("already exists"))
+            .stderr(predicate::str::contains("--force"));
+        init(&["--force"]).success();
+    }
+
+    #[test]
+    fn test_config_save_profile_then_init_from_it() {
+        let temp_dir = TempDir::new().unwrap();
+        let home = temp_dir.path().join("home");
+        let config_dir = home.join(".config").join("claude");
+        fs::create_dir_all(&config_dir).unwrap();
+        fs::write(
+            config_dir.join("config.json"),
+            r#"{"mcpServers": {"fetch": {"command": "uvx", "enabled": true}}, "customInstructions": ["Be terse"]}"#,
+        )
+        .unwrap();
+        let target = temp_dir.path().join("target");
+        fs::create_dir_all(&target).unwrap();
+
+        let ccm = |args: &[&str]| {
+            let mut cmd = Command::cargo_bin("ccm").unwrap();
+            cmd.env("HOME", &home)
+                .env("CCM_HOME", &config_dir)
+                .args(args)
+                .assert()
+        };
+
+        ccm(&["config", "save-profile", "servers", "--only", "mcpServers"])
+            .success()
+            .stdout(predicate::str::contains("Saved profile 'servers'"));
+        assert!(config_dir.join("profiles").join("servers.json").is_file());
+        ccm(&["config", "save-profile", "servers"])
+            .failure()
+            .stderr(predicate::str::contains("--force"));
+
+        ccm(&[
+            "config",
+            "--project",
+            target.to_str().unwrap(),
+            "init",
+            "--from-profile",
+            "servers",
+        ])
+        .success()
+        .stdout(predicate::str::contains("mcpServers: 1"));
+        let config: serde_json::Value = serde_json::from_str(
+            &fs::read_to_string(target.join(".claude").join("config.json")).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(config["mcpServers"]["fetch"]["command"], "uvx");
+        assert!(config.get("customInstructions").is_none());
+    }
+
+    #[test]
+    fn test_search_defaults_to_effective_config() {
+        let temp_dir = TempDir::new().unwrap();
+        let home = temp_dir.path().join("home");
+        let config_dir = home.join(".config").join("claude");
+        fs::create_dir_all(&config_dir).unwrap();
+        fs::write(
+            config_dir.join("config.json"),
+            r#"{"mcpServers": {"fetch": {"command": "uvx", "enabled": true}}}"#,
+        )
+        .unwrap();
+        let project = temp_dir.path().join("project");
+        fs::create_dir_all(project.join(".claude")).unwrap();
+        fs::write(
+            project.join(".claude").join("config.json"),
+            r#"{"mcpServers": {"localonly": {"command": "node", "enabled": true}}}"#,
+        )
+        .unwrap();
+
+        let search = |args: &[&str]| {
+            Command::cargo_bin("ccm")
+                .unwrap()
+                .env("HOME", &home)
+                .env("CCM_HOME", home.join(".config").join("claude"))
+                .current_dir(&project)
+                .arg("search")
+                .args(args)
+                .assert()
+                .success()
+        };
+
+        search(&["localonly"]).stdout(predicate::str::contains("PROJECT: mcpServers.localonly"));
+        search(&["fetch"]).stdout(predicate::str::contains("GLOBAL: mcpServers.fetch"));
+        search(&["localonly", "--scope", "global"])
+            .stdout(predicate::str::contains("No matches found"));
+    }
+
+    #[test]
+    fn test_config_validate_schema() {
+        let temp_dir = TempDir::new().unwrap();
+        let home = temp_dir.path().join("home");
+        let config_dir = home.join(".config").join("claude");
+        fs::create_dir_all(&config_dir).unwrap();
+        let config_path = config_dir.join("config.json");
+        fs::write(
+            &config_path,
+            r#"{"mcpServers": {"fetch": {"command": "uvx", "enabled": true, "type": "pipe"}}}"#,
+        )
+        .unwrap();
+
+        let validate = |args: &[&str]| {
+            Command::cargo_bin("ccm")
+                .unwrap()
+                .env("HOME", &home)
+                .env("CCM_HOME", home.join(".config").join("claude"))
+                .args(["config", "validate"])
+                .args(args)
+                .assert()
+        };
+
+        // The built-in rules accept the unknown server type
+        validate(&[])
+            .success()
+            .stdout(predicate::str::contains("is valid"));
+        validate(&["--schema"])
+            .failure()
+            .stdout(predicate::str::contains(
+                r#"/mcpServers/fetch/type: expected one of "stdio", "sse", "http", found "pipe""#,
+            ))
+            .stderr(predicate::str::contains("1 violation(s)"));
+        validate(&["--schema", "--schema-version", "99"])
+            .failure()
+            .stderr(predicate::str::contains(
+                "No bundled schema with version 99",
+            ));
+
+        fs::write(
+            &config_path,
+            r#"{"mcpServers": {"fetch": {"command": "uvx", "enabled": true, "type": "stdio"}}}"#,
+        )
+        .unwrap();
+        validate(&["--schema"])
+            .success()
+            .stdout(predicate::str::contains("Matches schema version 1"));
+    }
+
+    #[test]
+    fn test_project_export_all() {
+        let temp_dir = TempDir::new().unwrap();
+        let home = temp_dir.path().join("home");
+        let projects = temp_dir.path().join("projects");
+        for name in ["alpha", "beta"] {
+            let claude_dir = projects.join(name).join(".claude");
+            fs::create_dir_all(&claude_dir).unwrap();
+            fs::write(
+                claude_dir.join("config.json"),
+                r#"{"allowedPaths": ["~/x"]}"#,
+            )
+            .unwrap();
+        }
+        let output = temp_dir.path().join("all.zip");
+
+        let export = |extra: &[&str]| {
+            let mut cmd = Command::cargo_bin("ccm").unwrap();
+            cmd.env("HOME", &home)
+                .env("CCM_HOME", home.join(".config").join("claude"))
+                .args(["project", "export-all", output.to_str().unwrap(), "--path"])
+                .arg(&projects)
+                .args(extra);
+            cmd.assert()
+        };
+
+        export(&[])
+            .success()
+            .stdout(predicate::str::contains("[1/2] projects/alpha.json"))
+            .stdout(predicate::str::contains("Exported 2 project config(s)"));
+        assert!(output.exists());
+        assert!(!temp_dir.path().join("all.zip.staging").exists());
+
+        // Resuming needs a staging directory created by ccm, and there's
+        // nothing to resume after that failure
+        let missing = temp_dir.path().join("missing-staging");
+        export(&["--resume", missing.to_str().unwrap()])
+            .failure()
+            .stderr(predicate::str::contains("is not a ccm staging directory"))
+            .stderr(predicate::str::contains("continue it with --resume").not());
+
+        // An existing directory with other files isn't used for staging
+        let staging = temp_dir.path().join("all.zip.staging");
+        fs::create_dir_all(&staging).unwrap();
+        fs::write(staging.join("notes.txt"), "keep me").unwrap();
+        export(&[])
+            .failure()
+            .stderr(predicate::str::contains("is not empty"));
+        assert!(staging.join("notes.txt").is_file());
+    }
+
+    #[test]
+    fn test_config_set_skips_unchanged_value() {
+        let temp_dir = TempDir::new().unwrap();
+        let home = temp_dir.path().join("home");
+        let config_dir = home.join(".config").join("claude");
+        fs::create_dir_all(&config_dir).unwrap();
+        fs::write(config_dir.join("config.json"), "{}").unwrap();
+
+        let ccm = |args: &[&str]| {
+            let mut cmd = Command::cargo_bin("ccm").unwrap();
+            cmd.env("HOME", &home)
+                .env("CCM_HOME", home.join(".config").join("claude"))
+                .args(args);
+            cmd.assert().success()
+        };
+        let backups = || backup_files(&config_dir.join("backups"));
+
+        ccm(&["config", "set", "allowedPaths", "~/a"])
+            .stdout(predicate::str::contains("updated successfully"));
+        for _ in 0..3 {
+            ccm(&["config", "set", "allowedPaths", "~/a"])
+                .stdout(predicate::str::contains("unchanged"));
+        }
+        assert_eq!(backups(), 1);
+
+        ccm(&["config", "set", "allowedPaths", "~/a", "--force-write"])
+            .stdout(predicate::str::contains("updated successfully"));
+        ccm(&["config", "set", "allowedPaths", "~/b"])
+            .stdout(predicate::str::contains("updated successfully"));
+        assert_eq!(backups(), 3);
+    }
+
+    #[test]
+    fn test_mcp_enable_and_add_if_missing_skip_unchanged() {
+        let temp_dir = TempDir::new().unwrap();
+        let home = temp_dir.path().join("home");
+        let config_dir = home.join(".config").join("claude");
+        fs::create_dir_all(&config_dir).unwrap();
+        let config_path = config_dir.join("config.json");
+        fs::write(
+            &config_path,
+            r#"{"mcpServers": {"fetch": {"command": "uvx", "enabled": true}}}"#,
+        )
+        .unwrap();
+
+        let ccm = |args: &[&str]| {
+            let mut cmd = Command::cargo_bin("ccm").unwrap();
+            cmd.current_dir(temp_dir.path())
+                .env("HOME", &home)
+                .env("CCM_HOME", home.join(".config").join("claude"))
+                .args(args);
+            cmd.assert()
+        };
+        let original = fs::read_to_string(&config_path).unwrap();
+
+        ccm(&["mcp", "enable", "fetch"])
+            .success()
+            .stdout(predicate::str::contains("already enabled; unchanged"));
+        ccm(&["mcp", "add", "fetch", "--command", "uvx", "--if-missing"])
+            .success()
+            .stdout(predicate::str::contains("already configured; unchanged"));
+        ccm(&["mcp", "add", "fetch", "--command", "npx", "--if-missing"])
+            .success()
+            .stdout(predicate::str::contains("different definition; unchanged"));
+        assert_eq!(fs::read_to_string(&config_path).unwrap(), original);
+        assert!(!temp_dir.path().join(".backups").exists());
+
+        // Without --if-missing a different definition is still an error
+        ccm(&["mcp", "add", "fetch", "--command", "npx"])
+            .failure()
+            .stderr(predicate::str::contains("already exists"));
+
+        ccm(&["mcp", "add", "github", "--command", "npx", "--if-missing"])
+            .success()
+            .stdout(predicate::str::contains("added successfully"));
+    }
+
+    #[test]
+    fn test_project_list_with_config_only() {
+        let temp_dir = TempDir::new().unwrap();
+        let home = temp_dir.path().join("home");
+
+        let complete = temp_dir.path().join("complete").join(".claude");
+        fs::create_dir_all(&complete).unwrap();
+        fs::write(complete.join("config.json"), "{}").unwrap();
+        // A .claude directory whose config path is not a readable file
+        let broken = temp_dir.path().join("broken").join(".claude");
+        fs::create_dir_all(broken.join("config.json")).unwrap();
+
+        let list = |extra: &[&str]| {
+            let mut cmd = Command::cargo_bin("ccm").unwrap();
+            cmd.env("HOME", &home)
+                .env("CCM_HOME", home.join(".config").join("claude"))
+                .args(["project", "list", "--path"])
+                .arg(temp_dir.path())
+                .args(extra);
+            cmd.assert().success()
+        };
+
+        list(&[])
+            .stdout(predicate::str::contains("complete"))
+            .stdout(predicate::str::contains("broken"));
+        list(&["--with-config-only"])
+            .stdout(predicate::str::contains("Claude Code Projects (1)"))
+            .stdout(predicate::str::contains("complete"))
+            .stdout(predicate::str::contains("broken").not());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_daemon_status_and_stop() {
+        let temp_dir = TempDir::new().unwrap();
+        let home = temp_dir.path().join("home");
+        let socket = temp_dir.path().join("daemon").join("ccm.sock");
+
+        let ccm = |args: &[&str]| {
+            let mut cmd = Command::cargo_bin("ccm").unwrap();
+            cmd.env("HOME", &home)
+                .env("CCM_HOME", home.join(".config").join("claude"))
+                .args(args)
+                .arg("--socket")
+                .arg(&socket);
+            cmd
+        };
+
+        ccm(&["daemon", "status"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("No daemon running"));
+
+        let mut daemon = ccm(&["daemon", "--idle-timeout", "30"]).spawn().unwrap();
+        for _ in 0..100 {
+            if socket.exists() {
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(50));
+        }
+
+        ccm(&["daemon", "status"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Daemon running"))
+            .stdout(predicate::str::contains("protocol version: 1"));
+        ccm(&["daemon", "stop"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("is stopping"));
+
+        assert!(daemon.wait().unwrap().success());
+        assert!(!socket.exists());
+    }
+
+    #[test]
+    fn test_paths_dedupe_removes_copy_of_global_list() {
+        let temp_dir = TempDir::new().unwrap();
+        let home = temp_dir.path().join("home");
+        let global = home.join(".config").join("claude").join("config.json");
+        fs::create_dir_all(global.parent().unwrap()).unwrap();
+        fs::write(&global, r#"{"allowedPaths": ["/srv/a", "/srv/b"]}"#).unwrap();
+
+        let project = temp_dir.path().join("project");
+        let project_config = project.join(".claude").join("config.json");
+        fs::create_dir_all(project_config.parent().unwrap()).unwrap();
+        fs::write(
+            &project_config,
+            r#"{"allowedPaths": ["/srv/b", "/srv/a", "/srv/a/"]}"#,
+        )
+        .unwrap();
+
+        let dedupe = |extra: &[&str]| {
+            let mut cmd = Command::cargo_bin("ccm").unwrap();
+            cmd.env("HOME", &home)
+                .env("CCM_HOME", home.join(".config").join("claude"))
+                .args(["paths", "dedupe", "--project"])
+                .arg(&project)
+                .args(extra);
+            cmd.assert().success()
+        };
+
+        dedupe(&["--dry-run"])
+            .stdout(predicate::str::contains("duplicate of global"))
+            .stdout(predicate::str::contains("repeated in project"))
+            .stdout(predicate::str::contains(
+                "Effective allowedPaths after:  /srv/a, /srv/b",
+            ));
+        assert!(fs::read_to_string(&project_config)
+            .unwrap()
+            .contains("/srv/a/"));
+
+        dedupe(&["--yes"]).stdout(predicate::str::contains("Removed 3 entries"));
+        assert!(!fs::read_to_string(&project_config)
+            .unwrap()
+            .contains("allowedPaths"));
+
+        dedupe(&[]).stdout(predicate::str::contains("uses the global list"));
+    }
+
+    #[test]
+    fn test_config_export_and_import_through_pipes() {
+        let temp_dir = TempDir::new().unwrap();
+        let home = temp_dir.path().join("home");
+        let global = home.join(".config").join("claude").join("config.json");
+        fs::create_dir_all(global.parent().unwrap()).unwrap();
+        fs::write(
+            &global,
+            r#"{"mcpServers": {"fetch": {"command": "uvx", "args": ["mcp-fetch"], "enabled": true}}}"#,
+        )
+        .unwrap();
+
+        let ccm = |args: &[&str]| {
+            let mut cmd = assert_cmd::Command::cargo_bin("ccm").unwrap();
+            cmd.env("HOME", &home)
+                .env("CCM_HOME", home.join(".config").join("claude"))
+                .args(args);
+            cmd
+        };
+
+        let output = ccm(&["config", "export", "-"])
+            .assert()
+            .success()
+            .stderr(predicate::str::contains("Content hash:"))
+            .get_output()
+            .clone();
+        let exported: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        assert_eq!(exported["mcpServers"]["fetch"]["command"], "uvx");
+
+        let mut piped = exported.clone();
+        piped["allowedPaths"] = serde_json::json!(["~/piped"]);
+        ccm(&["config", "import", "-"])
+            .write_stdin(piped.to_string())
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("imported from standard input"));
+        let written: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&global).unwrap()).unwrap();
+        assert_eq!(written, piped);
+        // The regular backup plus the import's risk snapshot
+        assert_eq!(backup_files(&global.parent().unwrap().join("backups")), 2);
+
+        ccm(&["config", "import", "-"])
+            .write_stdin("{ not json")
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains(
+                "Invalid JSON in configuration file: <stdin>",
+            ))
+            .stderr(predicate::str::contains("Error at line 1, column 3"));
+
+        // Only JSON can be exported; clap rejects the other formats
+        ccm(&["config", "export", "-", "--format", "toml"])
+            .assert()
+            .code(2)
+            .stderr(predicate::str::contains("invalid value 'toml'"));
+
+        // --auto-name and --force combine
+        let exports = temp_dir.path().join("exports");
+        fs::create_dir_all(&exports).unwrap();
+        for _ in 0..2 {
+            ccm(&["config", "export", "--auto-name", "--force"])
+                .arg(&exports)
+                .assert()
+                .success();
+        }
+
+        // An explicit --format wins over the extension; errors name the file
+        let yaml = temp_dir.path().join("config.txt");
+        fs::write(&yaml, "allowedPaths:\n  - ~/yaml\n  - [\n").unwrap();
+        ccm(&["config", "import", "--format", "yaml"])
+            .arg(&yaml)
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains(yaml.display().to_string()));
+        fs::write(&yaml, "allowedPaths:\n  - ~/yaml\n").unwrap();
+        ccm(&["config", "import", "--format", "yaml"])
+            .arg(&yaml)
+            .assert()
+            .success();
+        assert!(fs::read_to_string(&global).unwrap().contains("~/yaml"));
+    }
+
+    #[test]
+    fn test_mcp_export_and_import() {
+        let temp_dir = TempDir::new().unwrap();
+        let home = temp_dir.path().join("home");
+        let global = home.join(".config").join("claude").join("config.json");
+        fs::create_dir_all(global.parent().unwrap()).unwrap();
+        fs::write(
+            &global,
+            r#"{"customInstructions": ["private"], "mcpServers": {"fetch": {"command": "uvx", "args": ["mcp-fetch"], "env": {"API_KEY": "s3cret"}, "enabled": true}}}"#,
+        )
+        .unwrap();
+        let project = temp_dir.path().join("project");
+        fs::create_dir_all(project.join(".claude")).unwrap();
+        let export = temp_dir.path().join("servers.json");
+
+        let mcp = |args: &[&str]| {
+            let mut cmd = Command::cargo_bin("ccm").unwrap();
+            cmd.env("HOME", &home)
+                .env("CCM_HOME", home.join(".config").join("claude"))
+                .current_dir(temp_dir.path())
+                .arg("mcp")
+                .args(args);
+            cmd.assert()
+        };
+
+        mcp(&["export", export.to_str().unwrap()])
+            .success()
+            .stdout(predicate::str::contains("Exported 1 MCP server(s)"));
+        let exported = fs::read_to_string(&export).unwrap();
+        assert!(exported.contains("mcp-fetch"));
+        assert!(!exported.contains("private"));
+        assert!(!exported.contains("s3cret"));
+        assert!(exported.contains("<redacted>"));
+
+        let with_secrets = temp_dir.path().join("with-secrets.json");
+        mcp(&[
+            "export",
+            with_secrets.to_str().unwrap(),
+            "--include-secrets",
+        ])
+        .success();
+        assert!(fs::read_to_string(&with_secrets)
+            .unwrap()
+            .contains("s3cret"));
+
+        let project_args = ["--scope", "project", "--project", project.to_str().unwrap()];
+        // A redacted export has no secrets to add a new server with
+        mcp(&[&project_args[..], &["import", export.to_str().unwrap()]].concat())
+            .failure()
+            .stderr(predicate::str::contains("redacted value"));
+        mcp(&[
+            &project_args[..],
+            &["import", with_secrets.to_str().unwrap()],
+        ]
+        .concat())
+        .success()
+        .stdout(predicate::str::contains("Added: fetch"));
+        mcp(&[&project_args[..], &["list"]].concat())
+            .success()
+            .stdout(predicate::str::contains("fetch"));
+        mcp(&[&project_args[..], &["import", export.to_str().unwrap()]].concat())
+            .success()
+            .stdout(predicate::str::contains("Unchanged: fetch"));
+        let config = fs::read_to_string(project.join(".claude").join("config.json")).unwrap();
+        assert!(config.contains("s3cret"));
+    }
+
+    #[test]
+    fn test_parallel_sandboxes_do_not_interfere() {
+        let handles: Vec<_> = (0..4)
+            .map(|i| {
+                std::thread::spawn(move || {
+                    let env = TestEnv::new();
+                    env.create_test_config();
+                    let path = format!("~/sandbox-{i}");
+                    for name in ["a", "b", "c"] {
+                        env.ccm()
+                            .args(["mcp", "add", &format!("{name}-{i}"), "--command", "uvx"])
+                            .assert()
+                            .success();
+                    }
+                    env.ccm()
+                        .args(["config", "set", "allowedPaths", &path])
+                        .assert()
+                        .success();
+
+                    let config = env.read_config();
+                    let servers = config["mcpServers"].as_object().unwrap();
+                    let mut names: Vec<_> = servers.keys().cloned().collect();
+                    names.sort();
+                    assert_eq!(
+                        names,
+                        [
+                            format!("a-{i}"),
+                            format!("b-{i}"),
+                            format!("c-{i}"),
+                            "test-server".to_string()
+                        ]
+                    );
+                    assert_eq!(config["allowedPaths"], serde_json::json!([path]));
+                    assert_eq!(env.backup_count(), 4);
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            handle.join().unwrap();
+        }
+    }
+
+    #[test]
+    fn test_config_set_dedupe_instructions() {
+        let env = TestEnv::new();
+        fs::write(
+            &env.config_path,
+            r#"{"customInstructions": ["Be brief", "Be brief"]}"#,
+        )
+        .unwrap();
+
+        env.ccm()
+            .args([
+                "config",
+                "set",
+                "customInstructions",
+                "Use tabs",
+                "--append",
+            ])
+            .assert()
+            .success();
+        assert_eq!(
+            env.read_config()["customInstructions"],
+            serde_json::json!(["Be brief", "Be brief", "Use tabs"])
+        );
+
+        env.ccm()
+            .args([
+                "config",
+                "set",
+                "customInstructions",
+                "Be brief",
+                "--append",
+            ])
+            .arg("--dedupe-instructions")
+            .assert()
+            .success();
+        assert_eq!(
+            env.read_config()["customInstructions"],
+            serde_json::json!(["Be brief", "Use tabs"])
+        );
+    }
+
+    #[test]
+    fn test_merged_views_note_replaced_instructions() {
+        let env = TestEnv::new();
+        fs::write(
+            &env.config_path,
+            r#"{"customInstructions": ["Be brief", "Use tabs", "Cite sources"]}"#,
+        )
+        .unwrap();
+        let project = env.temp_dir.path().join("project");
+        fs::create_dir_all(project.join(".claude")).unwrap();
+        fs::write(
+            project.join(".claude").join("config.json"),
+            r#"{"customInstructions": ["Write tests"]}"#,
+        )
+        .unwrap();
+
+        env.ccm()
+            .args(["config", "--project", project.to_str().unwrap(), "get"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Write tests"))
+            .stderr(predicate::str::contains(
+                "Note: customInstructions: the project's 1 instruction(s) replace 3 global instructions (3 dropped).",
+            ));
+
+        env.ccm()
+            .args(["config", "--project", project.to_str().unwrap()])
+            .args(["validate", "--merged"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Merged configuration is valid"))
+            .stdout(predicate::str::contains("Warning: customInstructions"))
+            .stdout(predicate::str::contains("--append"));
+
+        // Without a project list nothing is discarded
+        fs::write(project.join(".claude").join("config.json"), "{}").unwrap();
+        env.ccm()
+            .args(["config", "--project", project.to_str().unwrap(), "get"])
+            .assert()
+            .success()
+            .stderr(predicate::str::contains("Note:").not());
+    }
+
+    #[test]
+    fn test_project_alias_resolves_in_commands() {
+        let env = TestEnv::new();
+        env.create_test_config();
+        let project = env.temp_dir.path().join("api");
+        fs::create_dir_all(project.join(".claude")).unwrap();
+        fs::write(
+            project.join(".claude").join("config.json"),
+            r#"{"mcpServers": {"api-db": {"command": "db", "enabled": true}}}"#,
+        )
+        .unwrap();
+
+        env.ccm()
+            .args(["alias", "add", "api", "api"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("@api ->"));
+        env.ccm()
+            .args(["alias", "list"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("@api"))
+            .stdout(predicate::str::contains("(missing)").not());
+
+        env.ccm()
+            .args(["config", "--project", "@api", "get"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("api-db"));
+        env.ccm()
+            .args(["mcp", "--project", "@api", "--scope", "project", "list"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("api-db"));
+
+        env.ccm()
+            .args(["config", "--project", "@nah", "get"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains(
+                "Unknown project alias '@nah'; defined aliases: @api",
+            ));
+
+        env.ccm()
+            .args(["alias", "remove", "api"])
+            .assert()
+            .success();
+        env.ccm()
+            .args(["alias", "remove", "api"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("No alias named '@api'"));
+    }
+
+    #[test]
+    fn test_stale_project_alias_warns() {
+        let env = TestEnv::new();
+        env.create_test_config();
+        let project = env.temp_dir.path().join("gone");
+        fs::create_dir_all(&project).unwrap();
+
+        env.ccm()
+            .args(["alias", "add", "gone", project.to_str().unwrap()])
+            .assert()
+            .success();
+        fs::remove_dir_all(&project).unwrap();
+
+        env.ccm()
+            .args(["alias", "list"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("(missing)"));
+        env.ccm()
+            .args(["config", "--project", "@gone", "get"])
+            .assert()
+            .stderr(predicate::str::contains("which no longer exists"));
+    }
+
+    #[test]
+    fn test_config_canonicalize_on_write() {
+        let env = TestEnv::new();
+        env.create_test_config();
+        let project = env.temp_dir.path().join("project");
+        let config_path = project.join(".claude").join("config.json");
+        fs::create_dir_all(project.join(".claude")).unwrap();
+        fs::write(
+            &config_path,
+            r#"{"allowedPaths": ["~/b", "~/a"], "mcpServers": {"s": {"command": "x", "args": ["-z", "-a"], "enabled": true}}}"#,
+        )
+        .unwrap();
+        let project_arg = project.to_str().unwrap();
+
+        env.ccm()
+            .args([
+                "config",
+                "--project",
+                project_arg,
+                "canonicalize",
+                "--enable",
+            ])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Canonicalized"));
+        let written = fs::read_to_string(&config_path).unwrap();
+        assert!(written.ends_with("}\n"));
+        assert!(written.find("~/a").unwrap() < written.find("~/b").unwrap());
+        assert!(written.find("-z").unwrap() < written.find("-a").unwrap());
+
+        // Later writes keep the canonical form
+        env.ccm()
+            .args(["config", "--project", project_arg])
+            .args(["set", "allowedPaths", r#"["~/c", "~/a"]"#])
+            .assert()
+            .success();
+        let written = fs::read_to_string(&config_path).unwrap();
+        assert!(written.ends_with("}\n"));
+        assert!(written.find("~/a").unwrap() < written.find("~/c").unwrap());
+
+        env.ccm()
+            .args(["config", "--project", project_arg, "canonicalize"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("already canonical"));
+
+        env.ccm()
+            .args([
+                "config",
+                "--project",
+                project_arg,
+                "canonicalize",
+                "--disable",
+            ])
+            .assert()
+            .success();
+        let written = fs::read_to_string(&config_path).unwrap();
+        assert!(!written.ends_with('\n'));
+        assert!(!written.contains("canonicalOnWrite"));
+    }
+
+    #[test]
+    fn test_config_get_only_overrides() {
+        let env = TestEnv::new();
+        fs::write(
+            &env.config_path,
+            r#"{"allowedPaths": ["~/code", "~/notes"], "customInstructions": ["Be brief"],
+                "mcpServers": {"git": {"command": "npx", "env": {"DEBUG": "1"}, "enabled": true}}}"#,
+        )
+        .unwrap();
+        let project = env.temp_dir.path().join("project");
+        fs::create_dir_all(project.join(".claude")).unwrap();
+        fs::write(
+            project.join(".claude").join("config.json"),
+            r#"{"allowedPaths": ["~/code", "~/secret"],
+                "mcpServers": {"git": {"command": "npx", "enabled": true}}}"#,
+        )
+        .unwrap();
+
+        env.ccm()
+            .args(["config", "--project", project.to_str().unwrap()])
+            .args(["get", "--only-overrides"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains(
+                r#"allowedPaths: ["~/code","~/secret"]"#,
+            ))
+            // Dropping a field replaces the whole server
+            .stdout(predicate::str::contains("mcpServers.git: {"))
+            .stdout(predicate::str::contains("customInstructions").not());
+    }
+
+    #[test]
+    fn test_doctor_self_check() {
+        let env = TestEnv::new();
+
+        env.ccm()
+            .arg("doctor")
+            .assert()
+            .failure()
+            .stdout(predicate::str::contains(
+                "✓ config directory: CCM_HOME overrides",
+            ))
+            .stdout(predicate::str::contains("✗ global config:"))
+            .stdout(predicate::str::contains("does not exist"))
+            .stdout(predicate::str::contains("ccm doctor --fix"));
+        assert!(!env.config_path.exists());
+
+        env.ccm()
+            .args(["doctor", "--fix"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Created empty global config"))
+            .stdout(predicate::str::contains("✓ global config:"))
+            .stdout(predicate::str::contains("✓ backup directory:"));
+        assert!(env.config_path.exists());
+
+        fs::write(&env.config_path, "{ invalid").unwrap();
+        env.ccm()
+            .arg("doctor")
+            .assert()
+            .failure()
+            .stdout(predicate::str::contains("✗ global config: invalid JSON"));
+
+        env.ccm()
+            .arg("doctor")
+            .env("CCM_HOME", "relative/dir")
+            .assert()
+            .failure()
+            .stdout(predicate::str::contains("CCM_HOME is a relative path"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_doctor_fixes_world_readable_backups() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let env = TestEnv::new();
+        env.create_test_config();
+        let backup_dir = env.temp_dir.path().join("backups");
+        fs::create_dir_all(&backup_dir).unwrap();
+        let backup = backup_dir.join("config_20250120_123456.000000.json");
+        fs::write(
+            &backup,
+            r#"{"mcpServers": {"gh": {"command": "gh-mcp", "enabled": true, "env": {"GITHUB_TOKEN": "ghp_0123456789abcdef"}}}}"#,
+        )
+        .unwrap();
+        fs::set_permissions(&backup, fs::Permissions::from_mode(0o644)).unwrap();
+
+        env.ccm()
+            .arg("doctor")
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("world-readable"));
+
+        env.ccm()
+            .args(["doctor", "--fix"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("private to the owner"));
+
+        let mode = fs::metadata(&backup).unwrap().permissions().mode() & 0o777;
+        assert_eq!(mode, 0o600);
+    }
+
+    #[test]
+    fn test_search_where_clauses() {
+        let env = TestEnv::new();
+        fs::write(
+            &env.config_path,
+            r#"{"mcpServers": {
+                "github": {"command": "npx", "enabled": true, "env": {"GITHUB_TOKEN": "x"}},
+                "fetch": {"command": "npx", "enabled": true}
+            }}"#,
+        )
+        .unwrap();
+
+        env.ccm()
+            .current_dir(env.temp_dir.path())
+            .args(["search", "--scope", "global"])
+            .args(["--where", "value=npx", "--where", "key~TOKEN"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Found 1 match(es)"))
+            .stdout(predicate::str::contains("[1] mcpServers.github"))
+            .stdout(predicate::str::contains(
+                "key~TOKEN: mcpServers.github.env.GITHUB_TOKEN",
+            ))
+            .stdout(predicate::str::contains("mcpServers.fetch").not());
+
+        env.ccm()
+            .args(["search", "--where", "command~npx"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("Invalid query clause"));
+    }
+
+    #[test]
+    fn test_backup_retention_flag_prunes_backups() {
+        let env = TestEnv::new();
+        env.create_test_config();
+
+        for i in 0..4 {
+            env.ccm()
+                .args(["--backup-retention", "2", "config", "set", "allowedPaths"])
+                .arg(format!("~/p{i}"))
+                .assert()
+                .success();
+        }
+        assert_eq!(env.backup_count(), 2);
+        assert_eq!(
+            env.read_config()["allowedPaths"],
+            serde_json::json!(["~/p3"])
+        );
+
+        // Without the flag, the default retention (10) applies
+        env.ccm()
+            .args(["config", "set", "allowedPaths", "~/p4"])
+            .assert()
+            .success();
+        assert_eq!(env.backup_count(), 3);
+
+        // history commands honor the flag too
+        env.ccm()
+            .args(["--backup-retention", "1", "history", "prune", "--yes"])
+            .assert()
+            .success();
+        assert_eq!(env.backup_count(), 1);
+
+        env.ccm()
+            .args(["--backup-retention", "0", "config", "get"])
+            .assert()
+            .failure();
+    }
+
+    #[test]
+    fn test_instructions_indexed_operations() {
+        let env = TestEnv::new();
+        env.create_test_config();
+
+        for args in [
+            vec!["instructions", "insert", "0", "First"],
+            vec!["instructions", "add", "Last"],
+            vec!["instructions", "replace", "1", "Middle"],
+        ] {
+            env.ccm().args(&args).assert().success();
+        }
+        assert_eq!(
+            env.read_config()["customInstructions"],
+            serde_json::json!(["First", "Middle", "Last"])
+        );
+
+        env.ccm()
+            .args(["instructions", "insert", "7", "Nope"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains(
+                "Index 7 is out of bounds for 'customInstructions' (length 3)",
+            ));
+        env.ccm()
+            .args(["config", "get", "customInstructions[7]"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("(length 3)"));
+        env.ccm()
+            .args(["config", "get", "customInstructions[1]"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Middle"));
+
+        env.ccm()
+            .args(["config", "set", "customInstructions[1]", "--remove"])
+            .assert()
+            .success();
+        env.ccm()
+            .args(["config", "set", "allowedPaths[+]", "~/a"])
+            .assert()
+            .success();
+        env.ccm()
+            .args(["config", "set", "allowedPaths[0]", "~/b"])
+            .assert()
+            .success();
+        env.ccm()
+            .args(["instructions", "remove", "0"])
+            .assert()
+            .success();
+
+        let config = env.read_config();
+        assert_eq!(config["customInstructions"], serde_json::json!(["Last"]));
+        assert_eq!(config["allowedPaths"], serde_json::json!(["~/b"]));
+        env.ccm()
+            .args(["instructions", "list"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("0  Last"));
+    }
+
+    #[test]
+    fn test_mcp_disable_all_and_revert() {
+        let env = TestEnv::new();
+        fs::write(
+            &env.config_path,
+            r#"{"mcpServers": {
+                "fetch": {"command": "npx", "enabled": true},
+                "git": {"command": "uvx", "enabled": true},
+                "old": {"command": "npx", "enabled": false}
+            }}"#,
+        )
+        .unwrap();
+        let flags = |env: &TestEnv| {
+            let config = env.read_config();
+            ["fetch", "git", "old"].map(|name| config["mcpServers"][name]["enabled"].clone())
+        };
+
+        env.ccm()
+            .args(["mcp", "disable-all", "--except", "fetch"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Disabled 1 MCP server(s)"))
+            .stdout(predicate::str::contains("ccm mcp revert-enabled"));
+        assert_eq!(
+            flags(&env),
+            [true, false, false].map(serde_json::Value::from)
+        );
+
+        env.ccm()
+            .args(["mcp", "remove", "fetch"])
+            .assert()
+            .success();
+        env.ccm()
+            .args(["mcp", "revert-enabled"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("git: enabled"))
+            .stdout(predicate::str::contains("'fetch' was removed"));
+        let config = env.read_config();
+        assert_eq!(config["mcpServers"]["git"]["enabled"], true);
+        assert_eq!(config["mcpServers"]["old"]["enabled"], false);
+    }
+
+    #[test]
+    fn test_mcp_list_platform() {
+        let env = TestEnv::new();
+        fs::write(
+            &env.config_path,
+            r#"{"mcpServers": {
+                "finder": {"command": "npx", "enabled": true, "platforms": ["macos"]},
+                "fs": {"command": "npx", "enabled": true,
+                       "commandByPlatform": {"linux": "/usr/bin/mcp-fs"}}
+            }}"#,
+        )
+        .unwrap();
+
+        env.ccm()
+            .args(["mcp", "list"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("finder [macos]:"))
+            .stdout(predicate::str::contains(
+                "Command on linux: /usr/bin/mcp-fs",
+            ));
+
+        env.ccm()
+            .args(["mcp", "list", "--platform", "linux"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("MCP Servers for linux (1)"))
+            .stdout(predicate::str::contains("finder").not())
+            .stdout(predicate::str::contains("Command: /usr/bin/mcp-fs"));
+
+        env.ccm()
+            .args(["mcp", "list", "--platform", "beos"])
+            .assert()
+            .failure();
+
+        fs::write(
+            &env.config_path,
+            r#"{"mcpServers": {"fs": {"command": "npx", "enabled": true, "platforms": ["mac"]}}}"#,
+        )
+        .unwrap();
+        env.ccm()
+            .args(["config", "validate"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains(
+                "Warning: mcpServers.fs.platforms: unknown platform 'mac'",
+            ));
+    }
+
+    #[test]
+    fn test_config_extends_base() {
+        let env = TestEnv::new();
+        let base = env.temp_dir.path().join("team.json");
+        fs::write(
+            &base,
+            r#"{"customInstructions": ["team"], "allowedPaths": ["~/team"]}"#,
+        )
+        .unwrap();
+        fs::write(
+            &env.config_path,
+            r#"{"ccm": {"extends": "team.json"}, "allowedPaths": ["~/mine"]}"#,
+        )
+        .unwrap();
+
+        env.ccm()
+            .args(["config", "which"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains(format!(
+                "  base     {}",
+                base.display()
+            )))
+            .stdout(predicate::str::contains(format!(
+                "  global   {}",
+                env.config_path.display()
+            )));
+
+        env.ccm()
+            .args(["config", "get", "customInstructions"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("team"));
+
+        // A missing base leaves the user's own settings in effect
+        fs::remove_file(&base).unwrap();
+        env.ccm()
+            .args(["config", "get", "allowedPaths"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("~/mine"))
+            .stderr(predicate::str::contains("'team.json' was not loaded"));
+        env.ccm()
+            .args(["config", "which"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Warning: base 'team.json'"));
+    }
+
+    #[test]
+    fn test_config_get_table_rows() {
+        let env = TestEnv::new();
+        env.create_test_config();
+        let paths: Vec<String> = (0..12).map(|i| format!("~/p{i}")).collect();
+        env.ccm()
+            .args([
+                "config",
+                "set",
+                "allowedPaths",
+                &serde_json::to_string(&paths).unwrap(),
+            ])
+            .assert()
+            .success();
+        env.ccm()
+            .args([
+                "config",
+                "set",
+                "mcpServers.test-server.env.API_URL",
+                "https://example.com",
+            ])
+            .assert()
+            .success();
+
+        let output = env.ccm().args(["config", "get"]).output().unwrap();
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.contains("mcpServers.test-server.env.API_URL"));
+        assert!(stdout.contains("… (12 items, use --full)"));
+        assert!(!stdout.contains("~/p11"));
+
+        let row = stdout
+            .lines()
+            .find(|line| line.contains("customInstructions[0]"))
+            .unwrap();
+        let key = row.split_whitespace().next().unwrap();
+        env.ccm()
+            .args(["config", "--output", "json", "get", key])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("\"Test instructions\""));
+
+        env.ccm()
+            .args(["config", "get", "allowedPaths", "--full"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("allowedPaths[11]"))
+            .stdout(predicate::str::contains("~/p11"));
+    }
+
+    #[test]
+    fn test_config_diff_against_base_file() {
+        let env = TestEnv::new();
+        env.create_test_config();
+        let template = env.temp_dir.path().join("template.json");
+        fs::write(
+            &template,
+            r#"{
+                "customInstructions": ["Team style"],
+                "allowedPaths": ["~/team"],
+                "mcpServers": {"test-server": {"command": "uvx", "enabled": true}}
+            }"#,
+        )
+        .unwrap();
+        let project = env.temp_dir.path().join("project");
+        let project_config = project.join(".claude").join("config.json");
+        fs::create_dir_all(project_config.parent().unwrap()).unwrap();
+        fs::write(
+            &project_config,
+            r#"{
+                "customInstructions": ["Project style"],
+                "mcpServers": {"test-server": {"command": "uvx", "enabled": false}},
+                "allowedPaths": ["~/team"]
+            }"#,
+        )
+        .unwrap();
+
+        let template_arg = template.to_str().unwrap();
+        env.ccm()
+            .args(["config", "diff"])
+            .arg(&project)
+            .args(["--base", template_arg, "--exit-code"])
+            .assert()
+            .code(1)
+            .stdout(predicate::str::contains(
+                "Modifications (different from base):",
+            ))
+            .stdout(predicate::str::contains("~ customInstructions[0]"))
+            .stdout(predicate::str::contains("~ mcpServers"))
+            .stdout(predicate::str::contains("allowedPaths").not());
+
+        // Without a project config, --merged compares the global config
+        env.ccm()
+            .args(["config", "diff"])
+            .arg(env.temp_dir.path())
+            .args(["--base", template_arg, "--merged"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("- allowedPaths"))
+            .stdout(predicate::str::contains("~ customInstructions[0]"));
+
+        env.ccm()
+            .args(["config", "diff"])
+            .arg(env.temp_dir.path())
+            .args(["--base", template_arg])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("No project configuration found"));
+    }
+
+    #[test]
+    fn test_risk_snapshot_before_import() {
+        let env = TestEnv::new();
+        env.create_test_config();
+        let import = env.temp_dir.path().join("import.json");
+        fs::write(&import, r#"{"customInstructions": ["Imported"]}"#).unwrap();
+
+        env.ccm()
+            .args(["config", "import"])
+            .arg(&import)
+            .assert()
+            .success();
+
+        // Aggressive retention prunes the plain backups but not the snapshot
+        for path in ["~/a", "~/b", "~/c"] {
+            env.ccm()
+                .args([
+                    "--backup-retention",
+                    "1",
+                    "config",
+                    "set",
+                    "allowedPaths",
+                    path,
+                ])
+                .assert()
+                .success();
+        }
+        env.ccm()
+            .args(["history", "list"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("(auto-risk-import)"))
+            .stdout(predicate::str::contains("Backups (2 available"));
+
+        env.ccm()
+            .args(["history", "restore", "--last-risk", "--yes"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("_auto-risk-import.json"));
+        assert_eq!(
+            env.read_config()["customInstructions"],
+            serde_json::json!(["Test instructions"])
+        );
+
+        // The restore itself left a snapshot of what it replaced
+        env.ccm()
+            .args(["history", "list"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("(auto-risk-restore)"));
+    }
+
+    #[test]
+    fn test_last_risk_restore_ignores_other_files_snapshots() {
+        let env = TestEnv::new();
+        env.create_test_config();
+        let project = env.temp_dir.path().join("project");
+        fs::create_dir_all(project.join(".claude")).unwrap();
+        let project_config = project.join(".claude").join("config.json");
+        fs::write(
+            &project_config,
+            r#"{"mcpServers": {"local": {"command": "uvx", "enabled": true}}}"#,
+        )
+        .unwrap();
+
+        env.ccm().args(["mcp", "disable-all"]).assert().success();
+        env.ccm()
+            .args(["mcp", "--scope", "project", "--project"])
+            .arg(&project)
+            .arg("disable-all")
+            .assert()
+            .success();
+
+        // The newest risk snapshot is the project's, but only the global
+        // config's own snapshot may be restored over it
+        env.ccm()
+            .args(["history", "restore", "--last-risk", "--yes"])
+            .assert()
+            .success();
+        assert_eq!(
+            env.read_config()["mcpServers"]["test-server"]["enabled"],
+            serde_json::json!(true)
+        );
+        assert!(env.read_config()["mcpServers"].get("local").is_none());
+        let project_json: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&project_config).unwrap()).unwrap();
+        assert_eq!(
+            project_json["mcpServers"]["local"]["enabled"],
+            serde_json::json!(false)
+        );
+    }
+
+    #[test]
+    fn test_history_prune_honors_risk_snapshot_days() {
+        let env = TestEnv::new();
+        fs::write(
+            &env.config_path,
+            r#"{
+                "ccm": {"riskSnapshotDays": 0},
+                "mcpServers": {"fetch": {"command": "uvx", "enabled": true}}
+            }"#,
+        )
+        .unwrap();
+        env.ccm().args(["mcp", "disable-all"]).assert().success();
+        env.ccm()
+            .args(["config", "set", "allowedPaths", "~/a"])
+            .assert()
+            .success();
+
+        let output = env
+            .ccm()
+            .args(["history", "prune", "--keep", "1", "--dry-run"])
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+        let output = String::from_utf8(output).unwrap();
+        let snapshot = output
+            .lines()
+            .find(|line| line.contains("auto-risk-"))
+            .unwrap();
+        assert!(snapshot.contains("DELETE"), "{output}");
+    }
+
+    #[test]
+    fn test_revert_enabled_with_relative_and_absolute_project_path() {
+        let env = TestEnv::new();
+        let project = env.temp_dir.path().join("project");
+        fs::create_dir_all(project.join(".claude")).unwrap();
+        let project_config = project.join(".claude").join("config.json");
+        fs::write(
+            &project_config,
+            r#"{"mcpServers": {"local": {"command": "uvx", "enabled": true}}}"#,
+        )
+        .unwrap();
+        let local_enabled = || {
+            let config: serde_json::Value =
+                serde_json::from_str(&fs::read_to_string(&project_config).unwrap()).unwrap();
+            config["mcpServers"]["local"]["enabled"].clone()
+        };
+
+        // `ccm` runs in the sandbox, so `project` is relative to it
+        env.ccm()
+            .args([
+                "mcp",
+                "--scope",
+                "project",
+                "--project",
+                "project",
+                "disable-all",
+            ])
+            .assert()
+            .success();
+        assert_eq!(local_enabled(), serde_json::json!(false));
+
+        env.ccm()
+            .args(["mcp", "--scope", "project", "--project"])
+            .arg(&project)
+            .arg("revert-enabled")
+            .assert()
+            .success();
+        assert_eq!(local_enabled(), serde_json::json!(true));
+    }
+
+    #[test]
+    fn test_blank_values_rejected_by_set_and_warned_by_validate() {
+        let env = TestEnv::new();
+        env.create_test_config();
+
+        env.ccm()
+            .args(["config", "set", "mcpServers.test-server.command", ""])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains(
+                "MCP server command can't be empty or whitespace-only; use null to clear it",
+            ));
+        env.ccm()
+            .args(["config", "set", "customInstructions[0]", "  "])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("Instructions can't be empty"));
+        assert_eq!(
+            env.read_config()["mcpServers"]["test-server"]["command"],
+            "npx"
+        );
+
+        fs::write(
+            &env.config_path,
+            r#"{"customInstructions": [" "], "mcpServers": {"fs": {"command": "", "enabled": true}}}"#,
+        )
+        .unwrap();
+        env.ccm()
+            .args(["config", "validate"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains(
+                "Warning: customInstructions[0]: instruction is empty or whitespace-only",
+            ))
+            .stdout(predicate::str::contains(
+                "Warning: mcpServers.fs.command: command is empty or whitespace-only",
+            ));
+    }
+
+    #[test]
+    fn test_porcelain_output() {
+        let env = TestEnv::new();
+        env.create_test_config();
+
+        env.ccm()
+            .args(["mcp", "list", "--porcelain"])
+            .assert()
+            .success()
+            .stdout("#ccm-porcelain v1\ntest-server\ttrue\tnpx\t\t-y\n");
+
+        env.ccm()
+            .args(["search", "Test instr", "--value", "--global", "--porcelain"])
+            .assert()
+            .success()
+            .stdout(
+                "#ccm-porcelain v1\nglobal\tstring\tcustomInstructions[0]\tTest instructions\n",
+            );
+
+        env.ccm()
+            .args(["mcp", "list", "--porcelain", "--verbose"])
+            .assert()
+            .failure();
+    }
+
+    #[test]
+    fn test_mcp_env_set_unset_and_list() {
+        let env = TestEnv::new();
+        env.create_test_config();
+        let mcp_env = |args: &[&str]| {
+            let mut cmd = env.ccm();
+            cmd.args(["mcp", "env"]).args(args);
+            cmd
+        };
+
+        // Several pairs are written with one backup
+        mcp_env(&[
+            "set",
+            "test-server",
+            "API_TOKEN=ghp_abcdefghijklmnop",
+            "API_URL=https://x",
+        ])
+        .assert()
+        .success();
+        assert_eq!(env.backup_count(), 1);
+        let config = env.read_config();
+        assert_eq!(
+            config["mcpServers"]["test-server"]["env"]["API_URL"],
+            "https://x"
+        );
+
+        // Overwriting notes the old value, redacted whatever it looks like
+        mcp_env(&["set", "test-server", "API_TOKEN=other"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains(
+                "replaced existing value of API_TOKEN (was <redacted>)",
+            ))
+            .stdout(predicate::str::contains("ghp_").not());
+        mcp_env(&["set", "test-server", "API_URL=https://y"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains(
+                "replaced existing value of API_URL (was <redacted>)",
+            ));
+
+        mcp_env(&["list", "test-server"])
+            .assert()
+            .success()
+            .stdout("API_TOKEN=<redacted>\nAPI_URL=<redacted>\n");
+        mcp_env(&["list", "test-server", "--show-values"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("API_TOKEN=other"));
+
+        mcp_env(&["unset", "test-server", "API_URL"])
+            .assert()
+            .success();
+        mcp_env(&["unset", "test-server", "API_URL"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains(
+                "MCP server 'test-server' has no env variable 'API_URL'",
+            ));
+        mcp_env(&["set", "test-server", "NOT A PAIR"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("KEY=VALUE"));
+
+        // A server defined only in the project config is edited there
+        let project = env.temp_dir.path().join("app");
+        let claude_dir = project.join(".claude");
+        fs::create_dir_all(&claude_dir).unwrap();
+        fs::write(
+            claude_dir.join("config.json"),
+            r#"{"mcpServers": {"local": {"command": "node", "enabled": true}}}"#,
+        )
+        .unwrap();
+        let project_arg = project.to_str().unwrap();
+        env.ccm()
+            .args([
+                "mcp",
+                "--project",
+                project_arg,
+                "env",
+                "set",
+                "local",
+                "DEBUG=1",
+            ])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("only in the project config"));
+        env.ccm()
+            .args(["mcp", "--project", project_arg, "--scope", "project"])
+            .args(["env", "set", "local", "DEBUG=1"])
+            .assert()
+            .success();
+        let local: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(claude_dir.join("config.json")).unwrap())
+                .unwrap();
+        assert_eq!(local["mcpServers"]["local"]["env"]["DEBUG"], "1");
+    }
+
     // Additional integration tests will be added as CLI features evolve
     // These tests verify the basic CLI structure and command availability
 }
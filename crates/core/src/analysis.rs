@@ -0,0 +1,365 @@
+//! Cross-references between MCP servers and the rest of a config
+//!
+//! Servers tend to stay configured long after anything needs them. This
+//! module looks for mentions of each server name in custom instructions and
+//! in string values of skill parameters, so that servers nothing refers to
+//! can be reviewed.
+//!
+//! This is a heuristic: Claude can use a server without any instruction
+//! naming it, so an unreferenced server is a candidate for cleanup, not
+//! proof that it is unused.
+//!
+//! It also finds projects that shadow a global key, i.e. that set the same
+//! key path themselves and so ignore changes to the global value.
+
+use crate::{
+    collate,
+    config::{key_path, ClaudeConfig},
+    error::Result,
+    search::{ConfigSearcher, SearchOptions},
+    types::ConfigScope,
+};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::ops::Range;
+use std::path::PathBuf;
+
+/// Characters of context shown on each side of a mention
+const SNIPPET_CONTEXT: usize = 30;
+
+/// A place in the config that mentions a server
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reference {
+    /// Key path of the mentioning value (e.g., "customInstructions[0]")
+    pub key_path: String,
+
+    /// Text around the mention
+    pub snippet: String,
+}
+
+/// Find where each configured MCP server is mentioned
+///
+/// Looks in `customInstructions` and in string values of skill parameters.
+/// A mention is the server name as a whole word, ignoring case.
+///
+/// # Returns
+/// An entry for every configured server; servers that are never mentioned
+/// map to an empty list
+pub fn server_references(config: &ClaudeConfig) -> HashMap<String, Vec<Reference>> {
+    let Some(servers) = &config.mcp_servers else {
+        return HashMap::new();
+    };
+
+    // Only the parts of the config that can mention a server
+    let haystack = ClaudeConfig {
+        custom_instructions: config.custom_instructions.clone(),
+        skills: config.skills.clone(),
+        ..ClaudeConfig::default()
+    };
+    let searcher =
+        ConfigSearcher::with_options(SearchOptions::new().with_keys(false).with_values(true));
+
+    servers
+        .keys()
+        .map(|name| {
+            let references = searcher
+                .search(name, &haystack, ConfigScope::Global, PathBuf::new())
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|result| is_reference_path(&result.key_path))
+                .filter_map(|result| {
+                    let found = find_word(&result.value, name)?;
+                    Some(Reference {
+                        snippet: snippet(&result.value, found.start, found.len()),
+                        key_path: result.key_path,
+                    })
+                })
+                .collect();
+            (name.clone(), references)
+        })
+        .collect()
+}
+
+/// Names of configured servers that nothing mentions, sorted
+pub fn unreferenced_servers(references: &HashMap<String, Vec<Reference>>) -> Vec<&str> {
+    let mut names: Vec<&str> = references
+        .iter()
+        .filter(|(_, refs)| refs.is_empty())
+        .map(|(name, _)| name.as_str())
+        .collect();
+    collate::sort(&mut names);
+    names
+}
+
+/// How a project relates to a global key
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "state", rename_all = "lowercase")]
+pub enum ShadowState {
+    /// The project sets a different value, so the global one is ignored
+    Overrides {
+        /// The project's value
+        value: Value,
+    },
+
+    /// The project sets the key explicitly, to the global value
+    Matches,
+
+    /// The project doesn't set the key and uses the global value
+    Inherits,
+}
+
+/// Whether one project shadows a global key
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ShadowReport {
+    /// Project config file
+    pub config_path: PathBuf,
+
+    /// How the project relates to the key
+    #[serde(flatten)]
+    pub state: ShadowState,
+}
+
+/// Find which projects override a global key
+///
+/// # Arguments
+/// * `global` - The global config
+/// * `projects` - Project configs with their file paths (e.g. the successes
+///   of [`ConfigManager::read_configs`](crate::ConfigManager::read_configs))
+/// * `key_path` - Dotted key path (e.g. "mcpServers.fetch.command")
+///
+/// # Returns
+/// One report per project, in the given order
+pub fn find_shadows(
+    global: &ClaudeConfig,
+    projects: &[(PathBuf, ClaudeConfig)],
+    key_path: &str,
+) -> Result<Vec<ShadowReport>> {
+    let global_json = serde_json::to_value(global)?;
+    let global_value = key_path::get(&global_json, key_path);
+
+    projects
+        .iter()
+        .map(|(config_path, config)| {
+            let project_json = serde_json::to_value(config)?;
+            let state = match key_path::get(&project_json, key_path) {
+                None => ShadowState::Inherits,
+                Some(value) if Some(value) == global_value => ShadowState::Matches,
+                Some(value) => ShadowState::Overrides {
+                    value: value.clone(),
+                },
+            };
+            Ok(ShadowReport {
+                config_path: config_path.clone(),
+                state,
+            })
+        })
+        .collect()
+}
+
+/// Whether a search hit lies in an instruction or a skill parameter
+fn is_reference_path(key_path: &str) -> bool {
+    key_path.starts_with("customInstructions[")
+        || (key_path.starts_with("skills.") && key_path.contains(".parameters"))
+}
+
+/// Byte range of the first whole-word, case-insensitive occurrence of `word`
+///
+/// Matching is done char by char on `text` itself, so the range always
+/// falls on char boundaries even where lowercasing changes byte lengths.
+fn find_word(text: &str, word: &str) -> Option<Range<usize>> {
+    if word.is_empty() {
+        return None;
+    }
+
+    let is_word_char = |c: char| c.is_alphanumeric() || c == '_' || c == '-';
+    let same = |a: char, b: char| a == b || a.to_lowercase().eq(b.to_lowercase());
+
+    text.char_indices()
+        .filter_map(|(start, _)| {
+            let mut rest = text[start..].char_indices();
+            let mut len = 0;
+            for expected in word.chars() {
+                let (offset, c) = rest.next()?;
+                if !same(c, expected) {
+                    return None;
+                }
+                len = offset + c.len_utf8();
+            }
+            Some(start..start + len)
+        })
+        .find(|range| {
+            let before = text[..range.start].chars().next_back();
+            let after = text[range.end..].chars().next();
+            !before.is_some_and(is_word_char) && !after.is_some_and(is_word_char)
+        })
+}
+
+/// Single-line excerpt of `text` around the match at `start..start + len`
+fn snippet(text: &str, start: usize, len: usize) -> String {
+    let from = floor_char_boundary(text, start.saturating_sub(SNIPPET_CONTEXT));
+    let to = ceil_char_boundary(text, start + len + SNIPPET_CONTEXT);
+
+    let mut excerpt = text[from..to]
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+    if from > 0 {
+        excerpt.insert_str(0, "...");
+    }
+    if to < text.len() {
+        excerpt.push_str("...");
+    }
+    excerpt
+}
+
+fn floor_char_boundary(text: &str, mut index: usize) -> usize {
+    while !text.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
+fn ceil_char_boundary(text: &str, mut index: usize) -> usize {
+    index = index.min(text.len());
+    while !text.is_char_boundary(index) {
+        index += 1;
+    }
+    index
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::McpServer;
+
+    fn config() -> ClaudeConfig {
+        serde_json::from_value(serde_json::json!({
+            "mcpServers": {
+                "fetch": {"command": "uvx", "enabled": true},
+                "github": {"command": "npx", "enabled": true},
+                "postgres": {"command": "npx", "enabled": false}
+            },
+            "customInstructions": [
+                "Always use the fetch server to download documentation before answering."
+            ],
+            "skills": {
+                "review": {
+                    "enabled": true,
+                    "parameters": {"sources": ["GitHub pull requests"], "depth": 2}
+                }
+            }
+        }))
+        .unwrap()
+    }
+
+    // TDD Test 1: Mentions in instructions and skill parameters are found
+    #[test]
+    fn test_server_references_classification() {
+        let references = server_references(&config());
+
+        assert_eq!(references.len(), 3);
+        assert_eq!(
+            references["fetch"],
+            vec![Reference {
+                key_path: "customInstructions[0]".to_string(),
+                snippet: "Always use the fetch server to download documentat...".to_string(),
+            }]
+        );
+        assert_eq!(references["github"].len(), 1);
+        assert_eq!(
+            references["github"][0].key_path,
+            "skills.review.parameters.sources[0]"
+        );
+        assert_eq!(references["github"][0].snippet, "GitHub pull requests");
+        assert_eq!(unreferenced_servers(&references), vec!["postgres"]);
+    }
+
+    // TDD Test 2: Only whole words count, and server definitions are ignored
+    #[test]
+    fn test_partial_words_and_definitions_do_not_count() {
+        let config = ClaudeConfig::new()
+            .with_mcp_server("git", McpServer::new("git", "git-mcp", vec![]))
+            .with_custom_instruction("Prefer github-style markdown and digital tools");
+
+        let references = server_references(&config);
+
+        assert!(references["git"].is_empty());
+        assert_eq!(unreferenced_servers(&references), vec!["git"]);
+    }
+
+    // TDD Test 3: Long values are cut to a snippet around the mention
+    #[test]
+    fn test_snippet_is_trimmed() {
+        let text = format!("{} uses fetch {}", "a".repeat(50), "b".repeat(50));
+        let found = find_word(&text, "FETCH").unwrap();
+        let excerpt = snippet(&text, found.start, found.len());
+
+        assert!(excerpt.starts_with("..."));
+        assert!(excerpt.ends_with("..."));
+        assert!(excerpt.contains(" uses fetch "));
+    }
+
+    // TDD Test 3b: Non-ASCII text is matched on char boundaries
+    #[test]
+    fn test_find_word_non_ascii() {
+        // 'İ' lowercases to two chars of a different byte length
+        let text = "İstanbul team: use Fetch, not fetchér";
+        let found = find_word(text, "fetch").unwrap();
+        assert_eq!(&text[found.clone()], "Fetch");
+        assert!(snippet(text, found.start, found.len()).contains("use Fetch"));
+
+        assert_eq!(find_word("ÄPI — äpi", "äpi"), Some(0.."ÄPI".len()));
+        assert_eq!(find_word("fetchér", "fetch"), None);
+        assert_eq!(find_word("日本語 fetch", "fetch"), Some(10..15));
+    }
+
+    // TDD Test 4: Projects are classified as overriding, matching or inheriting
+    #[test]
+    fn test_find_shadows_classification() {
+        let global = config();
+        let project = |command: Option<&str>| {
+            let mut config = ClaudeConfig::new().with_allowed_path("~/work");
+            if let Some(command) = command {
+                config = config.with_mcp_server("fetch", McpServer::new("fetch", command, vec![]));
+            }
+            config
+        };
+        let projects = vec![
+            (
+                PathBuf::from("/a/.claude/config.json"),
+                project(Some("npx")),
+            ),
+            (
+                PathBuf::from("/b/.claude/config.json"),
+                project(Some("uvx")),
+            ),
+            (PathBuf::from("/c/.claude/config.json"), project(None)),
+        ];
+
+        let reports = find_shadows(&global, &projects, "mcpServers.fetch.command").unwrap();
+
+        assert_eq!(
+            reports.iter().map(|r| &r.state).collect::<Vec<_>>(),
+            [
+                &ShadowState::Overrides {
+                    value: serde_json::json!("npx")
+                },
+                &ShadowState::Matches,
+                &ShadowState::Inherits,
+            ]
+        );
+        assert_eq!(
+            reports[0].config_path,
+            PathBuf::from("/a/.claude/config.json")
+        );
+        assert_eq!(
+            serde_json::to_value(&reports[0]).unwrap(),
+            serde_json::json!({
+                "config_path": "/a/.claude/config.json",
+                "state": "overrides",
+                "value": "npx"
+            })
+        );
+    }
+}
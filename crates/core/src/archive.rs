@@ -0,0 +1,272 @@
+//! Config archives for migrating between machines
+//!
+//! This module packs the global config file and the backup directory into a
+//! single `.tar.gz` archive, and restores such an archive to the resolved
+//! locations on the target machine.
+
+use crate::backup::ORIGINS_DIR;
+use crate::config::manager::atomic_write;
+use crate::error::{ConfigError, Result};
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use std::ffi::OsStr;
+use std::fs;
+use std::io::Read;
+use std::path::{Component, Path, PathBuf};
+
+/// Archive entry name of the global config file
+const CONFIG_ENTRY: &str = "config.json";
+
+/// Archive directory holding the backups
+const BACKUPS_ENTRY: &str = "backups";
+
+/// Create a gzipped tar archive of the global config and its backups
+///
+/// Missing inputs are skipped, so an archive can be made on a machine that
+/// has a config but no backups yet (or vice versa).
+///
+/// # Arguments
+/// * `out` - Destination archive path
+/// * `config_path` - Path to the global config file
+/// * `backup_dir` - Backup directory
+///
+/// # Returns
+/// Number of files written to the archive
+///
+/// # Errors
+/// Returns an error if an input cannot be read or the archive cannot be written
+pub fn create_archive(out: &Path, config_path: &Path, backup_dir: &Path) -> Result<usize> {
+    if let Some(parent) = out.parent() {
+        if !parent.as_os_str().is_empty() && !parent.exists() {
+            fs::create_dir_all(parent)
+                .map_err(|e| ConfigError::filesystem("create archive directory", parent, e))?;
+        }
+    }
+
+    let file =
+        fs::File::create(out).map_err(|e| ConfigError::filesystem("create archive", out, e))?;
+    let mut builder = tar::Builder::new(GzEncoder::new(file, Compression::default()));
+    let write_error = |e| ConfigError::filesystem("write archive", out, e);
+    let mut count = 0;
+
+    if config_path.is_file() {
+        builder
+            .append_path_with_name(config_path, CONFIG_ENTRY)
+            .map_err(write_error)?;
+        count += 1;
+    }
+
+    if backup_dir.is_dir() {
+        for path in files_in(backup_dir)? {
+            let relative = path.strip_prefix(backup_dir).unwrap_or(&path);
+            builder
+                .append_path_with_name(&path, Path::new(BACKUPS_ENTRY).join(relative))
+                .map_err(write_error)?;
+            count += 1;
+        }
+    }
+
+    builder
+        .into_inner()
+        .and_then(|encoder| encoder.finish())
+        .map_err(write_error)?;
+
+    tracing::info!("Wrote config archive to: {}", out.display());
+
+    Ok(count)
+}
+
+/// Restore a config archive
+///
+/// The whole archive is read and checked before anything is written, and
+/// each file is replaced atomically, so a bad archive leaves the existing
+/// config and backups untouched.
+///
+/// # Arguments
+/// * `archive` - Archive created by [`create_archive`]
+/// * `config_path` - Where to restore the global config file
+/// * `backup_dir` - Where to restore the backups
+///
+/// # Returns
+/// Number of files restored
+///
+/// # Errors
+/// Returns an error if the archive cannot be read, contains unexpected
+/// entries, or a file cannot be written
+pub fn extract_archive(archive: &Path, config_path: &Path, backup_dir: &Path) -> Result<usize> {
+    let file =
+        fs::File::open(archive).map_err(|e| ConfigError::filesystem("open archive", archive, e))?;
+    let mut tar = tar::Archive::new(GzDecoder::new(file));
+    let read_error = |e| ConfigError::filesystem("read archive", archive, e);
+    let mut files = Vec::new();
+
+    for entry in tar.entries().map_err(read_error)? {
+        let mut entry = entry.map_err(read_error)?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+
+        let entry_path = entry.path().map_err(read_error)?.into_owned();
+        let target = target_path(&entry_path, config_path, backup_dir).ok_or_else(|| {
+            ConfigError::validation_failed(
+                "ConfigArchive",
+                format!("Unexpected archive entry: {}", entry_path.display()),
+                "Only archives created by 'ccm config archive' can be restored",
+            )
+        })?;
+
+        let mut content = Vec::new();
+        entry.read_to_end(&mut content).map_err(read_error)?;
+        files.push((target, content));
+    }
+
+    for (target, content) in &files {
+        atomic_write(target, content)?;
+    }
+
+    tracing::info!("Restored config archive from: {}", archive.display());
+
+    Ok(files.len())
+}
+
+/// Map an archive entry to its restore location
+///
+/// Returns None for entries outside the expected layout, including any
+/// path that could escape the backup directory.
+fn target_path(entry: &Path, config_path: &Path, backup_dir: &Path) -> Option<PathBuf> {
+    if entry == Path::new(CONFIG_ENTRY) {
+        return Some(config_path.to_path_buf());
+    }
+
+    let relative = entry.strip_prefix(BACKUPS_ENTRY).ok()?;
+    let safe = relative.components().count() > 0
+        && relative
+            .components()
+            .all(|c| matches!(c, Component::Normal(_)));
+
+    safe.then(|| backup_dir.join(relative))
+}
+
+/// All files below `dir`, recursively, in a stable order
+///
+/// Backup origin records are left out: they name paths on this machine, so
+/// restored backups belong to the config next to the backup directory.
+fn files_in(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+
+    while let Some(current) = pending.pop() {
+        for entry in fs::read_dir(&current)
+            .map_err(|e| ConfigError::filesystem("read backup directory", &current, e))?
+        {
+            let path = entry
+                .map_err(|e| ConfigError::filesystem("read backup entry", &current, e))?
+                .path();
+            if path.file_name() == Some(OsStr::new(ORIGINS_DIR)) {
+                continue;
+            } else if path.is_dir() {
+                pending.push(path);
+            } else if path.is_file() {
+                files.push(path);
+            }
+        }
+    }
+
+    files.sort();
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    // TDD Test 1: Archive round-trips the config and nested backups
+    #[test]
+    fn test_archive_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("claude").join("config.json");
+        let backup_dir = temp_dir.path().join("claude").join("backups");
+        let project_backups = backup_dir.join("home").join("proj").join(".claude");
+        fs::create_dir_all(&project_backups).unwrap();
+        fs::write(&config_path, r#"{"mcpServers": {}}"#).unwrap();
+        fs::write(backup_dir.join("config_1.json"), "{}").unwrap();
+        fs::write(project_backups.join("config_2.json"), "[]").unwrap();
+
+        let out = temp_dir.path().join("out").join("backup.tar.gz");
+        assert_eq!(create_archive(&out, &config_path, &backup_dir).unwrap(), 3);
+
+        let restored = temp_dir.path().join("restored");
+        let restored_config = restored.join("config.json");
+        let restored_backups = restored.join("backups");
+        assert_eq!(
+            extract_archive(&out, &restored_config, &restored_backups).unwrap(),
+            3
+        );
+
+        assert_eq!(
+            fs::read(&restored_config).unwrap(),
+            fs::read(&config_path).unwrap()
+        );
+        assert_eq!(
+            fs::read(
+                restored_backups
+                    .join("home")
+                    .join("proj")
+                    .join(".claude")
+                    .join("config_2.json")
+            )
+            .unwrap(),
+            b"[]"
+        );
+    }
+
+    // TDD Test 2: Entries outside the expected layout are rejected
+    #[test]
+    fn test_target_path_rejects_unexpected_entries() {
+        let config = Path::new("/cfg/config.json");
+        let backups = Path::new("/cfg/backups");
+
+        assert_eq!(
+            target_path(Path::new("config.json"), config, backups),
+            Some(config.to_path_buf())
+        );
+        assert_eq!(
+            target_path(Path::new("backups/a/b.json"), config, backups),
+            Some(backups.join("a/b.json"))
+        );
+        assert!(target_path(Path::new("backups/../evil"), config, backups).is_none());
+        assert!(target_path(Path::new("other.json"), config, backups).is_none());
+        assert!(target_path(Path::new("backups"), config, backups).is_none());
+    }
+
+    // TDD Test 3: A bad archive leaves the existing files untouched
+    #[test]
+    fn test_extract_archive_is_all_or_nothing() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("config.json");
+        let backup_dir = temp_dir.path().join("backups");
+        fs::write(&config_path, r#"{"mcpServers": {}}"#).unwrap();
+
+        let out = temp_dir.path().join("bad.tar.gz");
+        let mut builder = tar::Builder::new(GzEncoder::new(
+            fs::File::create(&out).unwrap(),
+            Compression::default(),
+        ));
+        for (name, content) in [(CONFIG_ENTRY, "{}"), ("other.json", "{}")] {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_cksum();
+            builder
+                .append_data(&mut header, name, content.as_bytes())
+                .unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap();
+
+        assert!(extract_archive(&out, &config_path, &backup_dir).is_err());
+        assert_eq!(
+            fs::read_to_string(&config_path).unwrap(),
+            r#"{"mcpServers": {}}"#
+        );
+        assert!(!backup_dir.exists());
+    }
+}
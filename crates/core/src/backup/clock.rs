@@ -0,0 +1,51 @@
+//! Time source for backup timestamps
+//!
+//! Backup names embed their creation time. Taking that time from a [`Clock`]
+//! lets tests create distinct, ordered backups without sleeping.
+
+use chrono::{DateTime, Duration, Utc};
+use std::sync::Mutex;
+
+/// Source of the current time
+pub trait Clock: Send + Sync {
+    /// Get the current time
+    fn now(&self) -> DateTime<Utc>;
+}
+
+/// Clock reading the system time
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// Clock that only moves when told to (for tests)
+#[derive(Debug)]
+pub struct FixedClock {
+    /// Time returned by `now`
+    current: Mutex<DateTime<Utc>>,
+}
+
+impl FixedClock {
+    /// Create a clock stopped at `time`
+    pub fn new(time: DateTime<Utc>) -> Self {
+        Self {
+            current: Mutex::new(time),
+        }
+    }
+
+    /// Move the clock forward
+    pub fn advance(&self, duration: Duration) {
+        let mut current = self.current.lock().unwrap_or_else(|e| e.into_inner());
+        *current += duration;
+    }
+}
+
+impl Clock for FixedClock {
+    fn now(&self) -> DateTime<Utc> {
+        *self.current.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
@@ -1,19 +1,89 @@
 //! Backup system for configuration files
 //!
 //! This module provides functionality to create, list, and manage backups
-//! of configuration files to ensure data safety.
+//! of configuration files to ensure data safety, and snapshots of whole
+//! project directories.
+//!
+//! Each backup records the file it was taken from in the `.origins`
+//! subfolder of the backup directory. Files with the same name, such as the
+//! global `config.json` and every project's `.claude/config.json`, can
+//! therefore share a backup directory without listing, pruning or restoring
+//! each other's backups.
+//!
+//! Backups may carry a tag in their name. Operations that are easy to regret
+//! (see [`RiskyOperation`]) take an extra backup tagged `auto-risk-<operation>`
+//! that retention keeps for [`DEFAULT_RISK_WINDOW_DAYS`] days, however few
+//! backups the retention count allows.
+
+mod clock;
+mod retention;
+mod snapshot;
+
+pub use clock::{Clock, FixedClock, SystemClock};
+pub use retention::{
+    execute_plan, plan_cleanup, Decision, DeleteReason, KeepReason, RetentionPolicy,
+};
+pub use snapshot::{SnapshotOptions, SnapshotRestore, DEFAULT_SNAPSHOT_MAX_SIZE};
 
 use crate::{
+    config::{manager::read_config_file, ClaudeConfig},
     error::{ConfigError, Result},
+    paths::{canonical_path, file_name_for, filesystem_error, to_extended_length, Platform},
     types::BackupInfo,
 };
 use chrono::{DateTime, Utc};
+use flate2::read::GzDecoder;
 use std::fs;
+use std::io::Read;
 use std::path::{Path, PathBuf};
 
 /// Default number of backups to retain
 const DEFAULT_RETENTION_COUNT: usize = 10;
 
+/// Timestamp format embedded in backup file names
+const TIMESTAMP_FORMAT: &str = "%Y%m%d_%H%M%S%.6f";
+
+/// Length of a formatted backup timestamp (e.g. `20250120_123456.789012`)
+const TIMESTAMP_LEN: usize = 22;
+
+/// Subfolder of the backup directory recording the origin of each backup
+///
+/// Holds one file per backup, named like the backup and containing the
+/// canonical path of the file it was taken from.
+pub(crate) const ORIGINS_DIR: &str = ".origins";
+
+/// Tag prefix of snapshots taken before risky operations
+pub const RISK_TAG_PREFIX: &str = "auto-risk-";
+
+/// Days risk snapshots are kept regardless of the retention count
+pub const DEFAULT_RISK_WINDOW_DAYS: i64 = 90;
+
+/// Operation that gets a risk snapshot of the config before it runs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RiskyOperation {
+    /// Replacing a config with an imported one
+    Import,
+    /// Restoring a backup over a config
+    Restore,
+    /// Disabling every MCP server
+    DisableAll,
+    /// A single write changing many key paths at once
+    LargeChange,
+}
+
+impl RiskyOperation {
+    /// Backup tag of the operation's snapshots (e.g. `auto-risk-import`)
+    pub fn tag(&self) -> String {
+        let operation = match self {
+            RiskyOperation::Import => "import",
+            RiskyOperation::Restore => "restore",
+            RiskyOperation::DisableAll => "disable-all",
+            RiskyOperation::LargeChange => "large-change",
+        };
+        format!("{RISK_TAG_PREFIX}{operation}")
+    }
+}
+
 /// Backup manager for configuration files
 ///
 /// Manages backup creation, listing, and cleanup with retention policies.
@@ -23,6 +93,8 @@ pub struct BackupManager {
     backup_dir: PathBuf,
     /// Number of backups to retain
     retention_count: usize,
+    /// How long risk snapshots are kept regardless of the count
+    risk_window: chrono::Duration,
 }
 
 impl BackupManager {
@@ -35,9 +107,16 @@ impl BackupManager {
         Self {
             backup_dir: backup_dir.into(),
             retention_count: retention_count.unwrap_or(DEFAULT_RETENTION_COUNT),
+            risk_window: chrono::Duration::days(DEFAULT_RISK_WINDOW_DAYS),
         }
     }
 
+    /// Keep risk snapshots for `window` instead of the default 90 days
+    pub fn with_risk_window(mut self, window: chrono::Duration) -> Self {
+        self.risk_window = window;
+        self
+    }
+
     /// Create a backup of the specified file
     ///
     /// # Arguments
@@ -52,20 +131,96 @@ impl BackupManager {
     /// - Backup directory cannot be created
     /// - File cannot be copied
     pub fn create_backup(&self, file_path: &Path) -> Result<PathBuf> {
+        self.create_backup_with_clock(file_path, &SystemClock)
+    }
+
+    /// Create a backup of the specified file, timestamped by `clock`
+    ///
+    /// Same as [`create_backup`](Self::create_backup), but the time embedded
+    /// in the backup name comes from the given clock.
+    ///
+    /// # Arguments
+    /// * `file_path` - Path to the file to backup
+    /// * `clock` - Time source for the backup timestamp
+    ///
+    /// # Returns
+    /// Path to the created backup file
+    pub fn create_backup_with_clock(&self, file_path: &Path, clock: &dyn Clock) -> Result<PathBuf> {
+        self.create_named_backup(file_path, clock, None)
+    }
+
+    /// Create a backup whose name carries `tag`
+    ///
+    /// Tagged backups are named `<file_stem>_<timestamp>_<tag>.<ext>` and
+    /// listed with their [`tag`](BackupInfo::tag).
+    ///
+    /// # Errors
+    /// Returns an error if the tag isn't made of letters, digits and `-`
+    /// (or is only digits), and the errors of
+    /// [`create_backup`](Self::create_backup)
+    pub fn create_tagged_backup(&self, file_path: &Path, tag: &str) -> Result<PathBuf> {
+        let valid = !tag.is_empty()
+            && tag.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+            && !tag.chars().all(|c| c.is_ascii_digit());
+        if !valid {
+            return Err(ConfigError::validation_failed(
+                "BackupTag",
+                format!("Invalid backup tag: '{tag}'"),
+                "Use letters, digits and '-' (e.g. 'before-upgrade')",
+            ));
+        }
+        self.create_named_backup(file_path, &SystemClock, Some(tag))
+    }
+
+    /// Snapshot a config before a risky operation
+    ///
+    /// The backup is tagged with [`RiskyOperation::tag`], so the default
+    /// retention policy keeps it for the risk window.
+    pub fn create_risk_snapshot(
+        &self,
+        file_path: &Path,
+        operation: RiskyOperation,
+    ) -> Result<PathBuf> {
+        let path = self.create_tagged_backup(file_path, &operation.tag())?;
+        tracing::info!(
+            "Saved {} snapshot of {}: {}",
+            operation.tag(),
+            file_path.display(),
+            path.display()
+        );
+        Ok(path)
+    }
+
+    /// Newest risk snapshot of a file, if any
+    pub fn latest_risk_snapshot(&self, original_file: &Path) -> Result<Option<BackupInfo>> {
+        Ok(self
+            .list_backups(original_file)?
+            .into_iter()
+            .find(BackupInfo::is_risk_snapshot))
+    }
+
+    /// Copy `file_path` to a new backup named after it, the time and `tag`
+    fn create_named_backup(
+        &self,
+        file_path: &Path,
+        clock: &dyn Clock,
+        tag: Option<&str>,
+    ) -> Result<PathBuf> {
         // Verify source file exists
         if !file_path.exists() {
             return Err(ConfigError::not_found(file_path));
         }
 
-        // Create backup directory if it doesn't exist
+        // Create backup directory if it doesn't exist; backups may hold
+        // secrets, so keep it private to the owner
         if !self.backup_dir.exists() {
-            fs::create_dir_all(&self.backup_dir).map_err(|e| {
-                ConfigError::filesystem("create backup directory", &self.backup_dir, e)
+            create_private_dir(&self.backup_dir).map_err(|e| {
+                filesystem_error("create backup directory", &self.backup_dir, &[], e)
             })?;
         }
 
         // Generate backup filename with timestamp (microsecond precision to avoid collisions)
-        let timestamp = Utc::now().format("%Y%m%d_%H%M%S%.6f");
+        let timestamp = clock.now().format(TIMESTAMP_FORMAT);
         let file_stem = file_path
             .file_stem()
             .and_then(|s| s.to_str())
@@ -76,7 +231,12 @@ impl BackupManager {
             .unwrap_or("json");
 
         // Add sequential number if backup already exists with same timestamp
-        let mut backup_name = format!("{file_stem}_{timestamp}.{extension}");
+        let platform = Platform::current();
+        let tag = tag.map(|tag| format!("_{tag}")).unwrap_or_default();
+        let mut backup_name = file_name_for(
+            platform,
+            &format!("{file_stem}_{timestamp}{tag}.{extension}"),
+        );
         let mut counter = 0;
         let backup_path = loop {
             let path = self.backup_dir.join(&backup_name);
@@ -84,12 +244,22 @@ impl BackupManager {
                 break path;
             }
             counter += 1;
-            backup_name = format!("{file_stem}_{timestamp}_{counter}.{extension}");
+            backup_name = file_name_for(
+                platform,
+                &format!("{file_stem}_{timestamp}_{counter}{tag}.{extension}"),
+            );
         };
 
         // Copy file to backup location
-        fs::copy(file_path, &backup_path)
-            .map_err(|e| ConfigError::filesystem("copy file to backup", file_path, e))?;
+        fs::copy(
+            to_extended_length(file_path),
+            to_extended_length(&backup_path),
+        )
+        .map_err(|e| filesystem_error("copy file to backup", file_path, &[&backup_path], e))?;
+        copy_permissions(file_path, &backup_path).map_err(|e| {
+            filesystem_error("copy permissions to backup", &backup_path, &[file_path], e)
+        })?;
+        self.record_origin(&backup_path, file_path)?;
 
         tracing::debug!(
             "Created backup: {} -> {}",
@@ -100,8 +270,130 @@ impl BackupManager {
         Ok(backup_path)
     }
 
+    /// Record that `backup_path` was taken from `original`
+    fn record_origin(&self, backup_path: &Path, original: &Path) -> Result<()> {
+        let Some(record) = origin_record(backup_path) else {
+            return Ok(());
+        };
+        let origins = self.backup_dir.join(ORIGINS_DIR);
+        if !origins.exists() {
+            create_private_dir(&origins).map_err(|e| {
+                filesystem_error("create backup origins directory", &origins, &[], e)
+            })?;
+        }
+
+        fs::write(
+            &record,
+            canonical_path(original).to_string_lossy().as_bytes(),
+        )
+        .map_err(|e| filesystem_error("record backup origin", &record, &[backup_path], e))
+    }
+
+    /// Record the backups of `from` as taken from `to`
+    ///
+    /// Used when a file moves: its backups stay where they are but list and
+    /// restore as backups of the new path. Backups of other files, and
+    /// backups made before origins were recorded, are left alone.
+    ///
+    /// # Returns
+    /// Number of backups re-keyed
+    ///
+    /// # Errors
+    /// Returns an error if an origin record cannot be read or rewritten
+    pub fn rekey_backups(&self, from: &Path, to: &Path) -> Result<usize> {
+        let origins = self.backup_dir.join(ORIGINS_DIR);
+        if !origins.is_dir() {
+            return Ok(0);
+        }
+        let from = canonical_path(from);
+        let to = canonical_path(to);
+
+        let mut count = 0;
+        for entry in fs::read_dir(&origins)
+            .map_err(|e| filesystem_error("read backup origins", &origins, &[], e))?
+        {
+            let record = entry
+                .map_err(|e| filesystem_error("read backup origins", &origins, &[], e))?
+                .path();
+            let origin = fs::read_to_string(&record)
+                .map_err(|e| filesystem_error("read backup origin", &record, &[], e))?;
+            if Path::new(origin.trim_end()) == from {
+                fs::write(&record, to.to_string_lossy().as_bytes())
+                    .map_err(|e| filesystem_error("record backup origin", &record, &[], e))?;
+                count += 1;
+            }
+        }
+        Ok(count)
+    }
+
+    /// Move every backup in this manager's directory into `dir`
+    ///
+    /// Origin records move with their backups. Files are renamed, or copied
+    /// and removed across filesystems, and the emptied directory is removed.
+    ///
+    /// # Returns
+    /// Number of backups moved
+    ///
+    /// # Errors
+    /// Returns an error if a backup already exists in `dir` under the same
+    /// name, or if a file cannot be moved
+    pub fn move_backups(&self, dir: &Path) -> Result<usize> {
+        if !self.backup_dir.is_dir() {
+            return Ok(0);
+        }
+
+        let mut count = 0;
+        for entry in fs::read_dir(&self.backup_dir)
+            .map_err(|e| ConfigError::filesystem("read backup directory", &self.backup_dir, e))?
+        {
+            let path = entry
+                .map_err(|e| ConfigError::filesystem("read backup entry", &self.backup_dir, e))?
+                .path();
+            let Some(name) = path.file_name().filter(|_| path.is_file()) else {
+                continue;
+            };
+
+            let target = dir.join(name);
+            if target.exists() {
+                return Err(ConfigError::validation_failed(
+                    "BackupMove",
+                    format!("{} already exists", target.display()),
+                    "Move or remove the existing backup first",
+                ));
+            }
+            if !dir.exists() {
+                create_private_dir(dir)
+                    .map_err(|e| filesystem_error("create backup directory", dir, &[], e))?;
+            }
+            move_file(&path, &target)?;
+
+            if let (Some(record), Some(moved)) = (origin_record(&path), origin_record(&target)) {
+                if record.is_file() {
+                    let origins = dir.join(ORIGINS_DIR);
+                    if !origins.exists() {
+                        create_private_dir(&origins).map_err(|e| {
+                            filesystem_error("create backup origins directory", &origins, &[], e)
+                        })?;
+                    }
+                    move_file(&record, &moved)?;
+                }
+            }
+            count += 1;
+        }
+
+        // Only succeed if nothing else is left
+        let _ = fs::remove_dir(self.backup_dir.join(ORIGINS_DIR));
+        let _ = fs::remove_dir(&self.backup_dir);
+        Ok(count)
+    }
+
     /// List all available backups for a specific file
     ///
+    /// Backups recorded as taken from another file with the same name are
+    /// left out. Backups made before origins were recorded belong to the
+    /// file of their name next to the backup directory, where restores put
+    /// them.
+    ///
     /// # Arguments
     /// * `original_file` - Path to the original file
     ///
@@ -116,6 +408,11 @@ impl BackupManager {
             .file_stem()
             .and_then(|s| s.to_str())
             .unwrap_or("config");
+        let target = canonical_path(original_file);
+        let owns_unrecorded = original_file.file_name().is_some_and(|name| {
+            let sibling = self.backup_dir.parent().unwrap_or(&self.backup_dir);
+            canonical_path(&sibling.join(name)) == target
+        });
 
         let mut backups = Vec::new();
 
@@ -130,16 +427,33 @@ impl BackupManager {
             // Check if filename matches pattern: <file_stem>_<timestamp>.<ext>
             if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
                 if name.starts_with(&format!("{file_stem}_")) {
+                    let origin = backup_origin(&path);
+                    let owned = match &origin {
+                        Some(origin) => *origin == target,
+                        None => owns_unrecorded,
+                    };
+                    if !owned {
+                        continue;
+                    }
+
                     if let Ok(metadata) = entry.metadata() {
-                        if let Ok(modified) = metadata.modified() {
-                            let created_at: DateTime<Utc> = modified.into();
+                        // Prefer the timestamp in the name; fall back to mtime
+                        let created_at = parse_backup_timestamp(name, file_stem)
+                            .or_else(|| metadata.modified().ok().map(DateTime::<Utc>::from));
+
+                        if let Some(created_at) = created_at {
                             let size = metadata.len();
 
                             backups.push(BackupInfo {
                                 path: path.to_string_lossy().to_string(),
-                                original_path: original_file.to_string_lossy().to_string(),
+                                original_path: origin
+                                    .as_deref()
+                                    .unwrap_or(original_file)
+                                    .to_string_lossy()
+                                    .to_string(),
                                 created_at,
                                 size,
+                                tag: parse_backup_tag(name, file_stem),
                             });
                         }
                     }
@@ -147,8 +461,20 @@ impl BackupManager {
             }
         }
 
-        // Sort by creation time, newest first
-        backups.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        // Sort by creation time, newest first (ties broken by the collision
+        // counter, compared as a number so `_10` sorts after `_9`)
+        let counter = |backup: &BackupInfo| {
+            Path::new(&backup.path)
+                .file_name()
+                .and_then(|name| name.to_str())
+                .map_or(0, |name| parse_backup_counter(name, file_stem))
+        };
+        backups.sort_by(|a, b| {
+            b.created_at
+                .cmp(&a.created_at)
+                .then_with(|| counter(b).cmp(&counter(a)))
+                .then_with(|| b.path.cmp(&a.path))
+        });
 
         Ok(backups)
     }
@@ -163,26 +489,43 @@ impl BackupManager {
     /// # Returns
     /// Number of backups removed
     pub fn cleanup_old_backups(&self, original_file: &Path) -> Result<usize> {
-        let mut backups = self.list_backups(original_file)?;
-
-        // Keep only the most recent N backups
-        if backups.len() <= self.retention_count {
-            return Ok(0);
-        }
+        let plan = self.cleanup_plan(original_file, &self.retention_policy())?;
+        execute_plan(&plan)
+    }
 
-        let backups_to_remove = backups.split_off(self.retention_count);
-        let mut removed_count = 0;
+    /// Plan which backups of a file a cleanup would keep or delete
+    ///
+    /// Nothing is deleted; pass the plan to [`execute_plan`] to apply it.
+    ///
+    /// # Arguments
+    /// * `original_file` - Path to the original file
+    /// * `policy` - Retention rules to apply
+    ///
+    /// # Returns
+    /// Every backup of the file with its decision, newest first
+    pub fn cleanup_plan(
+        &self,
+        original_file: &Path,
+        policy: &RetentionPolicy,
+    ) -> Result<Vec<(BackupInfo, Decision)>> {
+        let backups = self.list_backups(original_file)?;
+        Ok(plan_cleanup(&backups, policy))
+    }
 
-        for backup in backups_to_remove {
-            fs::remove_file(&backup.path).map_err(|e| {
-                ConfigError::filesystem("remove old backup", Path::new(&backup.path), e)
-            })?;
+    /// Get the default retention policy (keep the newest N backups, plus
+    /// risk snapshots inside the risk window)
+    pub fn retention_policy(&self) -> RetentionPolicy {
+        RetentionPolicy::new(self.retention_count).with_risk_window(self.risk_window)
+    }
 
-            tracing::debug!("Removed old backup: {}", backup.path);
-            removed_count += 1;
+    /// Get the retention policy for backups of `config`, whose
+    /// `riskSnapshotDays` setting overrides the risk window
+    pub fn retention_policy_for(&self, config: &ClaudeConfig) -> RetentionPolicy {
+        let policy = self.retention_policy();
+        match config.ccm_settings().risk_snapshot_days {
+            Some(days) => policy.with_risk_window(chrono::Duration::days(days.into())),
+            None => policy,
         }
-
-        Ok(removed_count)
     }
 
     /// Get the backup directory path
@@ -195,8 +538,24 @@ impl BackupManager {
         self.retention_count
     }
 
+    /// Retain `count` backups from now on
+    pub fn set_retention_count(&mut self, count: usize) {
+        self.retention_count = count;
+    }
+
+    /// Store backups in `dir` from now on
+    ///
+    /// Backups already in the previous directory are left where they are.
+    pub fn set_backup_dir(&mut self, dir: impl Into<PathBuf>) {
+        self.backup_dir = dir.into();
+    }
+
     /// Restore a backup to the original file location
     ///
+    /// The backup is copied over the file it was taken from. Backups made
+    /// before origins were recorded are restored to the file of their name
+    /// next to the backup directory.
+    ///
     /// # Arguments
     /// * `backup_path` - Path to the backup file to restore
     ///
@@ -214,64 +573,256 @@ impl BackupManager {
             return Err(ConfigError::not_found(backup_path));
         }
 
-        // Extract original file path from backup name
-        // Backup format: <file_stem>_<timestamp>.<ext>
-        let file_name = backup_path
-            .file_name()
-            .and_then(|n| n.to_str())
-            .ok_or_else(|| {
-                ConfigError::validation_failed(
-                    "BackupRestore",
-                    format!("Invalid backup file name: {:?}", backup_path.file_name()),
-                    "Ensure the backup file follows the naming pattern: <filename>_<timestamp>.<ext>",
-                )
-            })?;
+        let original_file = match backup_origin(backup_path) {
+            Some(origin) => origin,
+            None => self.unrecorded_origin(backup_path)?,
+        };
 
-        // Parse the backup filename to get the original file stem
-        // Format: config_20250120_123456.789.json
-        if let Some(stem_with_timestamp) = backup_path.file_stem().and_then(|s| s.to_str()) {
-            if let Some(original_stem) = stem_with_timestamp.split('_').next() {
-                let extension = backup_path
-                    .extension()
-                    .and_then(|s| s.to_str())
-                    .unwrap_or("json");
-
-                // Build the original file path (in parent directory of backups)
-                let original_file = self
-                    .backup_dir
-                    .parent()
-                    .unwrap_or(&self.backup_dir)
-                    .join(format!("{original_stem}.{extension}"));
-
-                // Ensure parent directory exists
-                if let Some(parent) = original_file.parent() {
-                    if !parent.exists() {
-                        fs::create_dir_all(parent).map_err(|e| {
-                            ConfigError::filesystem("create parent directory", parent, e)
-                        })?;
-                    }
-                }
+        // Ensure parent directory exists
+        if let Some(parent) = original_file.parent() {
+            if !parent.exists() {
+                fs::create_dir_all(to_extended_length(parent))
+                    .map_err(|e| filesystem_error("create parent directory", parent, &[], e))?;
+            }
+        }
+
+        // Never loosen permissions the target had before the restore
+        let target_permissions = fs::metadata(&original_file).ok().map(|m| m.permissions());
+
+        // Copy backup to original location
+        fs::copy(
+            to_extended_length(backup_path),
+            to_extended_length(&original_file),
+        )
+        .map_err(|e| filesystem_error("restore backup", &original_file, &[backup_path], e))?;
+        restore_permissions(backup_path, &original_file, target_permissions).map_err(|e| {
+            filesystem_error("restore permissions", &original_file, &[backup_path], e)
+        })?;
+
+        tracing::info!(
+            "Restored backup: {} -> {}",
+            backup_path.display(),
+            original_file.display()
+        );
 
-                // Copy backup to original location
-                fs::copy(backup_path, &original_file)
-                    .map_err(|e| ConfigError::filesystem("restore backup", &original_file, e))?;
+        Ok(original_file)
+    }
 
-                tracing::info!(
-                    "Restored backup: {} -> {}",
-                    backup_path.display(),
-                    original_file.display()
-                );
+    /// Original file of a backup made before origins were recorded
+    ///
+    /// Backup format: `<file_stem>_<timestamp>.<ext>`, restored to
+    /// `<file_stem>.<ext>` in the parent of the backup directory.
+    fn unrecorded_origin(&self, backup_path: &Path) -> Result<PathBuf> {
+        let invalid_name = || {
+            ConfigError::validation_failed(
+                "BackupRestore",
+                format!(
+                    "Could not determine original file path from backup name: {}",
+                    backup_path.display()
+                ),
+                "Ensure the backup file follows the naming pattern: <filename>_<timestamp>.<ext>",
+            )
+        };
 
-                return Ok(original_file);
-            }
-        }
+        let stem_with_timestamp = backup_path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .ok_or_else(invalid_name)?;
+        let original_stem = stem_with_timestamp
+            .split('_')
+            .next()
+            .ok_or_else(invalid_name)?;
+        let extension = backup_path
+            .extension()
+            .and_then(|s| s.to_str())
+            .unwrap_or("json");
+
+        Ok(self
+            .backup_dir
+            .parent()
+            .unwrap_or(&self.backup_dir)
+            .join(format!("{original_stem}.{extension}")))
+    }
+}
+
+/// Origin record of a backup (`None` if the path has no file name)
+fn origin_record(backup_path: &Path) -> Option<PathBuf> {
+    let dir = backup_path.parent()?;
+    Some(dir.join(ORIGINS_DIR).join(backup_path.file_name()?))
+}
 
-        Err(ConfigError::validation_failed(
-            "BackupRestore",
-            format!("Could not determine original file path from backup name: {file_name}"),
-            "Ensure the backup file follows the naming pattern: <filename>_<timestamp>.<ext>",
-        ))
+/// Recorded original file of a backup, if any
+pub(crate) fn backup_origin(backup_path: &Path) -> Option<PathBuf> {
+    let record = fs::read_to_string(origin_record(backup_path)?).ok()?;
+    Some(PathBuf::from(record.trim_end()))
+}
+
+/// Rename `from` to `to`, or copy and remove it across filesystems
+fn move_file(from: &Path, to: &Path) -> Result<()> {
+    if fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+    fs::copy(from, to).map_err(|e| filesystem_error("copy backup", to, &[from], e))?;
+    fs::remove_file(from).map_err(|e| filesystem_error("remove moved backup", from, &[], e))
+}
+
+/// Whether `dir` looks like a ccm backup directory
+///
+/// That is, it holds origin records or at least one backup of a
+/// `config.json` (`config_<timestamp>...`).
+pub(crate) fn is_backup_dir(dir: &Path) -> bool {
+    if dir.join(ORIGINS_DIR).is_dir() {
+        return true;
+    }
+    fs::read_dir(dir).is_ok_and(|entries| {
+        entries.flatten().any(|entry| {
+            entry
+                .file_name()
+                .to_str()
+                .is_some_and(|name| parse_backup_timestamp(name, "config").is_some())
+        })
+    })
+}
+
+/// Remove the origin record of a deleted backup
+pub(crate) fn forget_origin(backup_path: &Path) {
+    if let Some(record) = origin_record(backup_path) {
+        let _ = fs::remove_file(record);
+    }
+}
+
+/// Read and parse a backup without restoring it
+///
+/// Backups ending in `.gz` are decompressed first.
+///
+/// # Arguments
+/// * `backup_path` - Path to the backup file
+///
+/// # Errors
+/// Returns an error if the backup doesn't exist, can't be decompressed or
+/// isn't a valid configuration
+pub fn read_backup(backup_path: &Path) -> Result<ClaudeConfig> {
+    if !backup_path.exists() {
+        return Err(ConfigError::not_found(backup_path));
+    }
+
+    if backup_path.extension().and_then(|e| e.to_str()) != Some("gz") {
+        return read_config_file(backup_path);
+    }
+
+    let file = fs::File::open(to_extended_length(backup_path))
+        .map_err(|e| filesystem_error("open backup", backup_path, &[], e))?;
+    let mut content = String::new();
+    GzDecoder::new(file)
+        .read_to_string(&mut content)
+        .map_err(|e| filesystem_error("decompress backup", backup_path, &[], e))?;
+
+    serde_json::from_str(&content)
+        .map_err(|e| ConfigError::invalid_json(backup_path, e.line(), e.column(), e.to_string()))
+}
+
+/// Create a directory (and its parents) readable only by the owner
+#[cfg(unix)]
+fn create_private_dir(path: &Path) -> std::io::Result<()> {
+    use std::os::unix::fs::DirBuilderExt;
+
+    fs::DirBuilder::new()
+        .recursive(true)
+        .mode(0o700)
+        .create(to_extended_length(path))
+}
+
+/// Create a directory (and its parents)
+#[cfg(not(unix))]
+fn create_private_dir(path: &Path) -> std::io::Result<()> {
+    fs::create_dir_all(to_extended_length(path))
+}
+
+/// Give `to` the permission bits of `from`
+///
+/// `fs::copy` already does this on most platforms; doing it explicitly
+/// keeps backups of private configs private regardless. Atomic writes use
+/// it to give the temp file the mode of the file it replaces.
+#[cfg(unix)]
+pub(crate) fn copy_permissions(from: &Path, to: &Path) -> std::io::Result<()> {
+    fs::set_permissions(to, fs::metadata(from)?.permissions())
+}
+
+#[cfg(not(unix))]
+pub(crate) fn copy_permissions(_from: &Path, _to: &Path) -> std::io::Result<()> {
+    Ok(())
+}
+
+/// Set the permissions of a restored file
+///
+/// Uses the backup's permission bits, narrowed to those the target had
+/// before the restore (if it existed), so restoring never loosens access.
+#[cfg(unix)]
+fn restore_permissions(
+    backup_path: &Path,
+    restored: &Path,
+    previous: Option<fs::Permissions>,
+) -> std::io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let mut mode = fs::metadata(backup_path)?.permissions().mode() & 0o7777;
+    if let Some(previous) = previous {
+        mode &= previous.mode();
     }
+    fs::set_permissions(restored, fs::Permissions::from_mode(mode))
+}
+
+#[cfg(not(unix))]
+fn restore_permissions(
+    _backup_path: &Path,
+    _restored: &Path,
+    _previous: Option<fs::Permissions>,
+) -> std::io::Result<()> {
+    Ok(())
+}
+
+/// Parse the creation time from a backup file name
+///
+/// Backup names look like `<file_stem>_<timestamp>[_<counter>][_<tag>].<ext>`.
+fn parse_backup_timestamp(name: &str, file_stem: &str) -> Option<DateTime<Utc>> {
+    let timestamp = name
+        .strip_prefix(file_stem)?
+        .strip_prefix('_')?
+        .get(..TIMESTAMP_LEN)?;
+
+    chrono::NaiveDateTime::parse_from_str(timestamp, TIMESTAMP_FORMAT)
+        .ok()
+        .map(|naive| naive.and_utc())
+}
+
+/// Parse the tag from a backup file name, if it has one
+fn parse_backup_tag(name: &str, file_stem: &str) -> Option<String> {
+    backup_name_suffix(name, file_stem)?
+        .split('_')
+        .find(|part| !part.is_empty() && !part.chars().all(|c| c.is_ascii_digit()))
+        .map(str::to_string)
+}
+
+/// Parse the collision counter from a backup file name (0 if it has none)
+fn parse_backup_counter(name: &str, file_stem: &str) -> u64 {
+    backup_name_suffix(name, file_stem)
+        .and_then(|suffix| {
+            suffix
+                .split('_')
+                .find(|part| !part.is_empty())?
+                .parse()
+                .ok()
+        })
+        .unwrap_or(0)
+}
+
+/// The `[_<counter>][_<tag>]` part of a backup file name
+fn backup_name_suffix<'a>(name: &'a str, file_stem: &str) -> Option<&'a str> {
+    let rest = name
+        .strip_prefix(file_stem)?
+        .strip_prefix('_')?
+        .get(TIMESTAMP_LEN..)?;
+    rest.split('.').next()
 }
 
 #[cfg(test)]
@@ -346,10 +897,15 @@ mod tests {
         let mut file = File::create(&test_file).unwrap();
         file.write_all(b"{\"test\": \"data\"}").unwrap();
 
-        // Create multiple backups with longer delay to ensure different timestamps
-        manager.create_backup(&test_file).unwrap();
-        std::thread::sleep(std::time::Duration::from_millis(100));
-        manager.create_backup(&test_file).unwrap();
+        // Create multiple backups at distinct times
+        let clock = FixedClock::new(Utc::now());
+        let older = manager
+            .create_backup_with_clock(&test_file, &clock)
+            .unwrap();
+        clock.advance(chrono::Duration::seconds(1));
+        let newer = manager
+            .create_backup_with_clock(&test_file, &clock)
+            .unwrap();
 
         // List backups
         let backups = manager.list_backups(&test_file).unwrap();
@@ -357,14 +913,9 @@ mod tests {
         assert_eq!(backups.len(), 2);
 
         // Verify sorted by creation time (newest first)
-        // Note: Some file systems have limited timestamp precision,
-        // so we just verify we have 2 backups and the list is sorted
-        assert!(backups.len() == 2);
-
-        // Verify that if timestamps differ, the order is correct
-        if backups[0].created_at != backups[1].created_at {
-            assert!(backups[0].created_at > backups[1].created_at);
-        }
+        assert!(backups[0].created_at > backups[1].created_at);
+        assert_eq!(PathBuf::from(&backups[0].path), newer);
+        assert_eq!(PathBuf::from(&backups[1].path), older);
     }
 
     // TDD Test 5: Cleanup old backups removes excess backups
@@ -380,18 +931,26 @@ mod tests {
         file.write_all(b"{\"test\": \"data\"}").unwrap();
 
         // Create 5 backups
+        let clock = FixedClock::new(Utc::now());
+        let mut created = Vec::new();
         for _ in 0..5 {
-            manager.create_backup(&test_file).unwrap();
-            std::thread::sleep(std::time::Duration::from_millis(50));
+            created.push(
+                manager
+                    .create_backup_with_clock(&test_file, &clock)
+                    .unwrap(),
+            );
+            clock.advance(chrono::Duration::seconds(1));
         }
 
         // Cleanup should remove 3 oldest backups
         let removed = manager.cleanup_old_backups(&test_file).unwrap();
         assert_eq!(removed, 3);
 
-        // Verify only 2 backups remain
+        // Verify only the 2 newest backups remain
         let backups = manager.list_backups(&test_file).unwrap();
         assert_eq!(backups.len(), 2);
+        assert_eq!(PathBuf::from(&backups[0].path), created[4]);
+        assert_eq!(PathBuf::from(&backups[1].path), created[3]);
     }
 
     // TDD Test 6: Cleanup doesn't remove backups under retention limit
@@ -520,7 +1079,6 @@ mod tests {
         let mut file = File::create(&test_file).unwrap();
         file.write_all(content1).unwrap();
         let backup1 = manager.create_backup(&test_file).unwrap();
-        std::thread::sleep(std::time::Duration::from_millis(100));
 
         // Create second backup
         let content2 = b"{\"version\": 2}";
@@ -538,4 +1096,234 @@ mod tests {
         let restored_content = fs::read_to_string(&restored_path).unwrap();
         assert_eq!(restored_content, String::from_utf8_lossy(content2));
     }
+
+    // TDD Test 12: Fixed clock gives distinct, ordered names within one instant
+    #[test]
+    fn test_fixed_clock_backups_are_ordered() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = BackupManager::new(temp_dir.path().join("backups"), None);
+        let test_file = temp_dir.path().join("config.json");
+        fs::write(&test_file, "{}").unwrap();
+
+        let start = "2025-01-20T12:34:56Z".parse::<DateTime<Utc>>().unwrap();
+        let clock = FixedClock::new(start);
+
+        let first = manager
+            .create_backup_with_clock(&test_file, &clock)
+            .unwrap();
+        let same_instant = manager
+            .create_backup_with_clock(&test_file, &clock)
+            .unwrap();
+        clock.advance(chrono::Duration::minutes(5));
+        let later = manager
+            .create_backup_with_clock(&test_file, &clock)
+            .unwrap();
+
+        assert_eq!(
+            first.file_name().unwrap(),
+            "config_20250120_123456.000000.json"
+        );
+        assert_eq!(
+            same_instant.file_name().unwrap(),
+            "config_20250120_123456.000000_1.json"
+        );
+
+        let backups = manager.list_backups(&test_file).unwrap();
+        let paths: Vec<_> = backups.iter().map(|b| PathBuf::from(&b.path)).collect();
+        assert_eq!(paths, vec![later, same_instant, first]);
+        assert_eq!(backups[0].created_at, start + chrono::Duration::minutes(5));
+    }
+
+    // TDD Test 13: Read plain and gzip-compressed backups
+    #[test]
+    fn test_read_backup_plain_and_gz() {
+        let temp_dir = TempDir::new().unwrap();
+        let plain = temp_dir.path().join("config_20250120_123456.000000.json");
+        fs::write(&plain, r#"{"allowedPaths": ["~/a"]}"#).unwrap();
+
+        let compressed = temp_dir
+            .path()
+            .join("config_20250120_123457.000000.json.gz");
+        let mut encoder = flate2::write::GzEncoder::new(
+            File::create(&compressed).unwrap(),
+            flate2::Compression::default(),
+        );
+        encoder.write_all(br#"{"allowedPaths": ["~/b"]}"#).unwrap();
+        encoder.finish().unwrap();
+
+        assert_eq!(
+            read_backup(&plain).unwrap().allowed_paths,
+            Some(vec!["~/a".to_string()])
+        );
+        assert_eq!(
+            read_backup(&compressed).unwrap().allowed_paths,
+            Some(vec!["~/b".to_string()])
+        );
+
+        let broken = temp_dir.path().join("broken.json");
+        fs::write(&broken, "{").unwrap();
+        assert!(matches!(
+            read_backup(&broken),
+            Err(ConfigError::InvalidFormat { .. })
+        ));
+        assert!(matches!(
+            read_backup(&temp_dir.path().join("missing.json")),
+            Err(ConfigError::NotFound { .. })
+        ));
+    }
+
+    // TDD Test 14: Backups keep the source's permissions in a private directory
+    #[cfg(unix)]
+    #[test]
+    fn test_backup_and_restore_preserve_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let mode = |path: &Path| fs::metadata(path).unwrap().permissions().mode() & 0o777;
+
+        let temp_dir = TempDir::new().unwrap();
+        let backup_dir = temp_dir.path().join("backups");
+        let manager = BackupManager::new(&backup_dir, None);
+
+        let test_file = temp_dir.path().join("config.json");
+        fs::write(&test_file, r#"{"mcpServers": {}}"#).unwrap();
+        fs::set_permissions(&test_file, fs::Permissions::from_mode(0o600)).unwrap();
+
+        let backup_path = manager.create_backup(&test_file).unwrap();
+        assert_eq!(mode(&backup_dir), 0o700);
+        assert_eq!(mode(&backup_path), 0o600);
+
+        // A loosened target doesn't loosen the restored file beyond the backup
+        fs::set_permissions(&test_file, fs::Permissions::from_mode(0o644)).unwrap();
+        let restored = manager.restore_backup(&backup_path).unwrap();
+        assert_eq!(mode(&restored), 0o600);
+
+        // A loosened backup doesn't loosen a private target
+        fs::set_permissions(&test_file, fs::Permissions::from_mode(0o600)).unwrap();
+        fs::set_permissions(&backup_path, fs::Permissions::from_mode(0o644)).unwrap();
+        let restored = manager.restore_backup(&backup_path).unwrap();
+        assert_eq!(mode(&restored), 0o600);
+    }
+
+    // TDD Test 15: Risk snapshots are tagged and survive count-based cleanup
+    #[test]
+    fn test_risk_snapshot_tagging_and_retention() {
+        let temp_dir = TempDir::new().unwrap();
+        let backup_dir = temp_dir.path().join("backups");
+        let manager = BackupManager::new(&backup_dir, Some(1));
+
+        let test_file = temp_dir.path().join("config.json");
+        fs::write(&test_file, "{}").unwrap();
+
+        let snapshot = manager
+            .create_risk_snapshot(&test_file, RiskyOperation::Import)
+            .unwrap();
+        assert!(snapshot
+            .to_string_lossy()
+            .ends_with("_auto-risk-import.json"));
+        for _ in 0..3 {
+            manager.create_backup(&test_file).unwrap();
+        }
+
+        assert_eq!(manager.cleanup_old_backups(&test_file).unwrap(), 2);
+        let backups = manager.list_backups(&test_file).unwrap();
+        assert_eq!(backups.len(), 2);
+        assert_eq!(backups[0].tag, None);
+        assert_eq!(backups[1].tag.as_deref(), Some("auto-risk-import"));
+
+        let latest = manager.latest_risk_snapshot(&test_file).unwrap().unwrap();
+        assert_eq!(Path::new(&latest.path), snapshot);
+
+        // Restoring a tagged backup finds the original file
+        assert_eq!(manager.restore_backup(&snapshot).unwrap(), test_file);
+
+        assert!(manager.create_tagged_backup(&test_file, "bad tag").is_err());
+        assert!(manager.create_tagged_backup(&test_file, "42").is_err());
+    }
+
+    // TDD Test 16: Files sharing a backup directory keep their own backups
+    #[test]
+    fn test_shared_backup_dir_keeps_backups_per_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let backup_dir = temp_dir.path().join("backups");
+        let manager = BackupManager::new(&backup_dir, Some(1));
+
+        let global = temp_dir.path().join("config.json");
+        let project = temp_dir
+            .path()
+            .join("project")
+            .join(".claude")
+            .join("config.json");
+        fs::create_dir_all(project.parent().unwrap()).unwrap();
+        fs::write(&global, r#"{"scope": "global"}"#).unwrap();
+        fs::write(&project, r#"{"scope": "project"}"#).unwrap();
+
+        let clock = FixedClock::new(Utc::now());
+        let global_backup = manager.create_backup_with_clock(&global, &clock).unwrap();
+        for _ in 0..3 {
+            clock.advance(chrono::Duration::seconds(1));
+            manager.create_backup_with_clock(&project, &clock).unwrap();
+        }
+
+        // Pruning the project's backups leaves the global one alone
+        assert_eq!(manager.cleanup_old_backups(&project).unwrap(), 2);
+        assert!(global_backup.exists());
+        assert_eq!(manager.list_backups(&project).unwrap().len(), 1);
+        let global_backups = manager.list_backups(&global).unwrap();
+        assert_eq!(global_backups.len(), 1);
+        assert_eq!(PathBuf::from(&global_backups[0].path), global_backup);
+
+        // Pruned backups take their origin records with them
+        assert_eq!(
+            fs::read_dir(backup_dir.join(ORIGINS_DIR)).unwrap().count(),
+            2
+        );
+
+        // Restores go back to the file each backup was taken from
+        let project_backup = manager.list_backups(&project).unwrap()[0].path.clone();
+        fs::write(&project, "{}").unwrap();
+        assert_eq!(
+            manager.restore_backup(Path::new(&project_backup)).unwrap(),
+            canonical_path(&project)
+        );
+        assert_eq!(
+            fs::read_to_string(&project).unwrap(),
+            r#"{"scope": "project"}"#
+        );
+        assert_eq!(
+            fs::read_to_string(&global).unwrap(),
+            r#"{"scope": "global"}"#
+        );
+
+        // Backups without a record still belong to the config next to them
+        fs::remove_dir_all(backup_dir.join(ORIGINS_DIR)).unwrap();
+        assert_eq!(manager.list_backups(&global).unwrap().len(), 2);
+        assert!(manager.list_backups(&project).unwrap().is_empty());
+    }
+
+    // TDD Test 17: Collision counters of 10 or more sort numerically
+    #[test]
+    fn test_collision_counters_sort_numerically() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = BackupManager::new(temp_dir.path().join("backups"), None);
+        let test_file = temp_dir.path().join("config.json");
+        fs::write(&test_file, "{}").unwrap();
+
+        let clock = FixedClock::new("2025-01-20T12:34:56Z".parse().unwrap());
+        let created: Vec<_> = (0..12)
+            .map(|_| {
+                manager
+                    .create_backup_with_clock(&test_file, &clock)
+                    .unwrap()
+            })
+            .collect();
+        assert_eq!(
+            created[11].file_name().unwrap(),
+            "config_20250120_123456.000000_11.json"
+        );
+
+        let backups = manager.list_backups(&test_file).unwrap();
+        let paths: Vec<_> = backups.iter().map(|b| PathBuf::from(&b.path)).collect();
+        let newest_first: Vec<_> = created.into_iter().rev().collect();
+        assert_eq!(paths, newest_first);
+    }
 }
@@ -0,0 +1,376 @@
+//! Backup retention planning
+//!
+//! Retention decisions are computed by a pure planning step, so a dry-run
+//! preview and the actual cleanup always agree on what gets deleted.
+
+use crate::{
+    error::{ConfigError, Result},
+    types::BackupInfo,
+};
+use chrono::{DateTime, Duration, Utc};
+use std::collections::HashSet;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+/// Rules deciding which backups of a file are kept
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetentionPolicy {
+    /// Maximum number of (unprotected) backups to keep
+    pub keep_count: usize,
+
+    /// Delete backups older than this
+    pub max_age: Option<Duration>,
+
+    /// Maximum total size in bytes of the kept (unprotected) backups
+    pub max_total_size: Option<u64>,
+
+    /// Backup paths that are never deleted (e.g. tagged backups)
+    pub protected: HashSet<String>,
+
+    /// Keep risk snapshots younger than this regardless of the other rules
+    pub risk_window: Option<Duration>,
+}
+
+impl RetentionPolicy {
+    /// Create a policy keeping the newest `keep_count` backups
+    pub fn new(keep_count: usize) -> Self {
+        Self {
+            keep_count,
+            max_age: None,
+            max_total_size: None,
+            protected: HashSet::new(),
+            risk_window: None,
+        }
+    }
+
+    /// Delete backups older than `max_age`
+    pub fn with_max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = Some(max_age);
+        self
+    }
+
+    /// Delete backups older than `days` days
+    ///
+    /// # Errors
+    /// Returns a validation error if `days` is negative or too large to
+    /// represent as a duration
+    pub fn with_max_age_days(self, days: i64) -> Result<Self> {
+        match Duration::try_days(days) {
+            Some(max_age) if days >= 0 => Ok(self.with_max_age(max_age)),
+            _ => Err(ConfigError::validation_failed(
+                "max_age_days",
+                format!("invalid maximum backup age of {days} days"),
+                "Use a number of days of 0 or more",
+            )),
+        }
+    }
+
+    /// Limit the total size of kept backups
+    pub fn with_max_total_size(mut self, max_total_size: u64) -> Self {
+        self.max_total_size = Some(max_total_size);
+        self
+    }
+
+    /// Keep risk snapshots for `window` (see [`BackupInfo::is_risk_snapshot`])
+    ///
+    /// Like protected backups, risk snapshots inside the window don't count
+    /// towards the retention count or size budget. Older ones are treated
+    /// like any other backup.
+    pub fn with_risk_window(mut self, window: Duration) -> Self {
+        self.risk_window = Some(window);
+        self
+    }
+
+    /// Never delete the backup at `path`
+    pub fn protect(mut self, path: impl Into<String>) -> Self {
+        self.protected.insert(path.into());
+        self
+    }
+}
+
+/// Why a backup is kept
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeepReason {
+    /// Backup satisfies every retention rule
+    WithinPolicy,
+    /// Backup is protected from deletion
+    Protected,
+    /// Backup is a risk snapshot inside the risk window
+    RiskSnapshot,
+}
+
+/// Why a backup is deleted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeleteReason {
+    /// Newer backups already fill the retention count
+    BeyondCount,
+    /// Backup is older than the maximum age
+    OlderThanMaxAge,
+    /// Keeping the backup would exceed the size budget
+    OverSizeBudget,
+}
+
+/// Retention decision for a single backup
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Decision {
+    /// Keep the backup
+    Keep(KeepReason),
+    /// Delete the backup
+    Delete(DeleteReason),
+}
+
+impl Decision {
+    /// Whether the backup will be deleted
+    pub fn is_delete(&self) -> bool {
+        matches!(self, Decision::Delete(_))
+    }
+}
+
+impl fmt::Display for KeepReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            KeepReason::WithinPolicy => "within policy",
+            KeepReason::Protected => "protected",
+            KeepReason::RiskSnapshot => "recent risk snapshot",
+        })
+    }
+}
+
+impl fmt::Display for DeleteReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            DeleteReason::BeyondCount => "beyond retention count",
+            DeleteReason::OlderThanMaxAge => "older than max age",
+            DeleteReason::OverSizeBudget => "over size budget",
+        })
+    }
+}
+
+impl fmt::Display for Decision {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Decision::Keep(reason) => write!(f, "KEEP ({reason})"),
+            Decision::Delete(reason) => write!(f, "DELETE ({reason})"),
+        }
+    }
+}
+
+/// Decide which backups to keep and which to delete
+///
+/// Backups are considered newest first. Protected backups (and risk
+/// snapshots inside the policy's risk window) are always kept and don't count
+/// towards the retention count or size budget.
+///
+/// # Arguments
+/// * `backups` - Backups of a single original file
+/// * `policy` - Retention rules to apply
+///
+/// # Returns
+/// Every backup with its decision, newest first
+pub fn plan_cleanup(
+    backups: &[BackupInfo],
+    policy: &RetentionPolicy,
+) -> Vec<(BackupInfo, Decision)> {
+    plan_cleanup_at(backups, policy, Utc::now())
+}
+
+/// [`plan_cleanup`] with an explicit current time
+fn plan_cleanup_at(
+    backups: &[BackupInfo],
+    policy: &RetentionPolicy,
+    now: DateTime<Utc>,
+) -> Vec<(BackupInfo, Decision)> {
+    let mut sorted = backups.to_vec();
+    sorted.sort_by_key(|b| std::cmp::Reverse(b.created_at));
+
+    let mut kept_count = 0;
+    let mut kept_size = 0;
+
+    sorted
+        .into_iter()
+        .map(|backup| {
+            let decision = if policy.protected.contains(&backup.path) {
+                Decision::Keep(KeepReason::Protected)
+            } else if backup.is_risk_snapshot()
+                && policy
+                    .risk_window
+                    .is_some_and(|window| now - backup.created_at <= window)
+            {
+                Decision::Keep(KeepReason::RiskSnapshot)
+            } else if kept_count >= policy.keep_count {
+                Decision::Delete(DeleteReason::BeyondCount)
+            } else if policy
+                .max_age
+                .is_some_and(|max_age| now - backup.created_at > max_age)
+            {
+                Decision::Delete(DeleteReason::OlderThanMaxAge)
+            } else if policy
+                .max_total_size
+                .is_some_and(|budget| kept_size + backup.size > budget)
+            {
+                Decision::Delete(DeleteReason::OverSizeBudget)
+            } else {
+                kept_count += 1;
+                kept_size += backup.size;
+                Decision::Keep(KeepReason::WithinPolicy)
+            };
+
+            (backup, decision)
+        })
+        .collect()
+}
+
+/// Delete the backups a plan marks for deletion
+///
+/// # Arguments
+/// * `plan` - Plan produced by [`plan_cleanup`]
+///
+/// # Returns
+/// Number of backups removed
+///
+/// # Errors
+/// Returns an error if a backup cannot be removed
+pub fn execute_plan(plan: &[(BackupInfo, Decision)]) -> Result<usize> {
+    let mut removed_count = 0;
+
+    for (backup, _) in plan.iter().filter(|(_, decision)| decision.is_delete()) {
+        fs::remove_file(&backup.path).map_err(|e| {
+            ConfigError::filesystem("remove old backup", Path::new(&backup.path), e)
+        })?;
+        super::forget_origin(Path::new(&backup.path));
+
+        tracing::debug!("Removed old backup: {}", backup.path);
+        removed_count += 1;
+    }
+
+    Ok(removed_count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn backup(dir: &Path, name: &str, age_days: i64, size: u64, now: DateTime<Utc>) -> BackupInfo {
+        let path = dir.join(name);
+        fs::write(&path, vec![b'x'; size as usize]).unwrap();
+
+        BackupInfo {
+            path: path.to_string_lossy().to_string(),
+            original_path: dir.join("config.json").to_string_lossy().to_string(),
+            created_at: now - Duration::days(age_days),
+            size,
+            tag: None,
+        }
+    }
+
+    // TDD Test 1: Every decision reason is produced
+    #[test]
+    fn test_plan_cleanup_decision_reasons() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path();
+        let now = Utc::now();
+
+        let backups = vec![
+            backup(dir, "config_1.json", 0, 10, now),
+            backup(dir, "config_2.json", 1, 100, now),
+            backup(dir, "config_3.json", 2, 10, now),
+            backup(dir, "config_4.json", 40, 10, now),
+            backup(dir, "config_5.json", 50, 10, now),
+            backup(dir, "config_6.json", 60, 10, now),
+        ];
+        let policy = RetentionPolicy::new(2)
+            .with_max_age(Duration::days(30))
+            .with_max_total_size(50)
+            .protect(backups[4].path.clone());
+
+        let plan = plan_cleanup_at(&backups, &policy, now);
+        let decisions: Vec<_> = plan.iter().map(|(_, d)| *d).collect();
+
+        assert_eq!(
+            decisions,
+            vec![
+                Decision::Keep(KeepReason::WithinPolicy),
+                Decision::Delete(DeleteReason::OverSizeBudget),
+                Decision::Keep(KeepReason::WithinPolicy),
+                Decision::Delete(DeleteReason::BeyondCount),
+                Decision::Keep(KeepReason::Protected),
+                Decision::Delete(DeleteReason::BeyondCount),
+            ]
+        );
+
+        let policy = RetentionPolicy::new(10).with_max_age(Duration::days(30));
+        let plan = plan_cleanup_at(&backups, &policy, now);
+        assert_eq!(plan[3].1, Decision::Delete(DeleteReason::OlderThanMaxAge));
+    }
+
+    // TDD Test 2: Executing a plan removes exactly the DELETE-marked files
+    #[test]
+    fn test_execute_plan_removes_deleted_backups() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path();
+        let now = Utc::now();
+
+        let backups: Vec<_> = (0..5)
+            .map(|i| backup(dir, &format!("config_{i}.json"), i * 10, 10, now))
+            .collect();
+        let policy = RetentionPolicy::new(2)
+            .with_max_age(Duration::days(25))
+            .protect(backups[4].path.clone());
+
+        let plan = plan_cleanup_at(&backups, &policy, now);
+        let removed = execute_plan(&plan).unwrap();
+
+        assert_eq!(removed, plan.iter().filter(|(_, d)| d.is_delete()).count());
+        for (backup, decision) in &plan {
+            assert_eq!(Path::new(&backup.path).exists(), !decision.is_delete());
+        }
+    }
+
+    // TDD Test 3: Risk snapshots outlive the count inside their window
+    #[test]
+    fn test_plan_cleanup_keeps_recent_risk_snapshots() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path();
+        let now = Utc::now();
+
+        let mut backups: Vec<_> = (0..4)
+            .map(|i| backup(dir, &format!("config_{i}.json"), i * 30, 10, now))
+            .collect();
+        for risky in &mut backups[2..] {
+            risky.tag = Some("auto-risk-import".to_string());
+        }
+        let policy = RetentionPolicy::new(1).with_risk_window(Duration::days(75));
+
+        let decisions: Vec<_> = plan_cleanup_at(&backups, &policy, now)
+            .into_iter()
+            .map(|(_, decision)| decision)
+            .collect();
+        assert_eq!(
+            decisions,
+            vec![
+                Decision::Keep(KeepReason::WithinPolicy),
+                Decision::Delete(DeleteReason::BeyondCount),
+                Decision::Keep(KeepReason::RiskSnapshot),
+                Decision::Delete(DeleteReason::BeyondCount),
+            ]
+        );
+
+        let plan = plan_cleanup_at(&backups, &RetentionPolicy::new(1), now);
+        assert_eq!(plan[2].1, Decision::Delete(DeleteReason::BeyondCount));
+    }
+
+    // TDD Test 4: The maximum age in days must not be negative
+    #[test]
+    fn test_with_max_age_days_validates() {
+        let policy = RetentionPolicy::new(1).with_max_age_days(30).unwrap();
+        assert_eq!(policy.max_age, Some(Duration::days(30)));
+        assert!(RetentionPolicy::new(1).with_max_age_days(0).is_ok());
+
+        for days in [-1, i64::MIN, i64::MAX] {
+            let err = RetentionPolicy::new(1).with_max_age_days(days).unwrap_err();
+            assert!(matches!(err, ConfigError::ValidationFailed { .. }));
+        }
+    }
+}
@@ -0,0 +1,448 @@
+//! Directory snapshots
+//!
+//! A project's `.claude` directory holds more than `config.json` (agent
+//! files, `settings.local.json`, prompts), and restoring the config alone can
+//! leave it inconsistent. Snapshots archive the whole directory as a
+//! `.tar.gz` with paths relative to the directory, so they can also be
+//! inspected or extracted with plain `tar`.
+//!
+//! Snapshots are stored in the `snapshots` subfolder of the backup directory
+//! and named `<dir name>_<timestamp>.tar.gz` (leading dots dropped).
+
+use super::{parse_backup_timestamp, BackupManager, TIMESTAMP_FORMAT};
+use crate::{
+    backup::{Clock, SystemClock},
+    error::{ConfigError, Result},
+    types::BackupInfo,
+};
+use chrono::{DateTime, Utc};
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+/// Subfolder of the backup directory holding snapshots
+const SNAPSHOT_DIR: &str = "snapshots";
+
+/// Snapshot file extension
+const SNAPSHOT_EXTENSION: &str = ".tar.gz";
+
+/// Default size limit for the files in one snapshot (50 MiB)
+pub const DEFAULT_SNAPSHOT_MAX_SIZE: u64 = 50 * 1024 * 1024;
+
+/// Directory names that are never archived, at any depth
+const DEFAULT_EXCLUDES: &[&str] = &["node_modules", ".git", "target", ".venv", "__pycache__"];
+
+/// What a snapshot may contain
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnapshotOptions {
+    /// Refuse to archive more than this many bytes of file content
+    pub max_size: u64,
+
+    /// Directory names skipped at any depth
+    pub exclude: Vec<String>,
+}
+
+impl Default for SnapshotOptions {
+    fn default() -> Self {
+        Self {
+            max_size: DEFAULT_SNAPSHOT_MAX_SIZE,
+            exclude: DEFAULT_EXCLUDES.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+}
+
+impl SnapshotOptions {
+    /// Create options with the default size limit and exclusions
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the size limit
+    pub fn with_max_size(mut self, max_size: u64) -> Self {
+        self.max_size = max_size;
+        self
+    }
+
+    /// Also skip directories with this name
+    pub fn exclude(mut self, name: impl Into<String>) -> Self {
+        self.exclude.push(name.into());
+        self
+    }
+}
+
+/// Outcome of restoring a snapshot
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnapshotRestore {
+    /// Number of files written
+    pub files: usize,
+
+    /// Snapshot of the directory taken before restoring (None if it didn't exist)
+    pub safety_snapshot: Option<PathBuf>,
+}
+
+impl BackupManager {
+    /// Archive a directory with the default [`SnapshotOptions`]
+    ///
+    /// # Arguments
+    /// * `dir` - Directory to snapshot (e.g. a project's `.claude` directory)
+    ///
+    /// # Returns
+    /// Path to the created `.tar.gz` snapshot
+    pub fn create_dir_backup(&self, dir: &Path) -> Result<PathBuf> {
+        self.create_dir_backup_with_options(dir, &SnapshotOptions::default())
+    }
+
+    /// Archive a directory
+    ///
+    /// The directory's own `backups` subfolder, the snapshot directory and
+    /// any excluded directory names are skipped. Only regular files are
+    /// archived.
+    ///
+    /// # Arguments
+    /// * `dir` - Directory to snapshot
+    /// * `options` - Size limit and exclusions
+    ///
+    /// # Returns
+    /// Path to the created `.tar.gz` snapshot
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - The directory doesn't exist
+    /// - The files to archive exceed `options.max_size`
+    /// - A file cannot be read or the snapshot cannot be written
+    pub fn create_dir_backup_with_options(
+        &self,
+        dir: &Path,
+        options: &SnapshotOptions,
+    ) -> Result<PathBuf> {
+        self.create_dir_backup_with_clock(dir, options, &SystemClock)
+    }
+
+    fn create_dir_backup_with_clock(
+        &self,
+        dir: &Path,
+        options: &SnapshotOptions,
+        clock: &dyn Clock,
+    ) -> Result<PathBuf> {
+        if !dir.is_dir() {
+            return Err(ConfigError::not_found(dir));
+        }
+
+        let snapshot_dir = self.snapshot_dir();
+        let skipped = [dir.join("backups"), snapshot_dir.clone()];
+        let files = snapshot_files(dir, options, &skipped)?;
+
+        let total: u64 = files.iter().map(|(_, size)| size).sum();
+        if total > options.max_size {
+            return Err(ConfigError::validation_failed(
+                "DirectorySnapshot",
+                format!(
+                    "{} holds {total} bytes of files, over the snapshot limit of {} bytes",
+                    dir.display(),
+                    options.max_size
+                ),
+                "Exclude large subdirectories or raise the size limit",
+            ));
+        }
+
+        fs::create_dir_all(&snapshot_dir)
+            .map_err(|e| ConfigError::filesystem("create snapshot directory", &snapshot_dir, e))?;
+
+        let tag = snapshot_tag(dir);
+        let timestamp = clock.now().format(TIMESTAMP_FORMAT);
+        let mut name = format!("{tag}_{timestamp}{SNAPSHOT_EXTENSION}");
+        let mut counter = 0;
+        let snapshot_path = loop {
+            let path = snapshot_dir.join(&name);
+            if !path.exists() {
+                break path;
+            }
+            counter += 1;
+            name = format!("{tag}_{timestamp}_{counter}{SNAPSHOT_EXTENSION}");
+        };
+
+        let file = fs::File::create(&snapshot_path)
+            .map_err(|e| ConfigError::filesystem("create snapshot", &snapshot_path, e))?;
+        let mut builder = tar::Builder::new(GzEncoder::new(file, Compression::default()));
+        let write_error = |e| ConfigError::filesystem("write snapshot", &snapshot_path, e);
+
+        for (path, _) in &files {
+            let relative = path.strip_prefix(dir).unwrap_or(path);
+            builder
+                .append_path_with_name(path, relative)
+                .map_err(write_error)?;
+        }
+        builder
+            .into_inner()
+            .and_then(|encoder| encoder.finish())
+            .map_err(write_error)?;
+
+        tracing::debug!(
+            "Created snapshot of {} ({} files): {}",
+            dir.display(),
+            files.len(),
+            snapshot_path.display()
+        );
+
+        Ok(snapshot_path)
+    }
+
+    /// List snapshots of a directory
+    ///
+    /// # Returns
+    /// Snapshot information, sorted by creation time (newest first)
+    pub fn list_dir_backups(&self, dir: &Path) -> Result<Vec<BackupInfo>> {
+        let snapshot_dir = self.snapshot_dir();
+        if !snapshot_dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let tag = snapshot_tag(dir);
+        let mut snapshots = Vec::new();
+
+        for entry in fs::read_dir(&snapshot_dir)
+            .map_err(|e| ConfigError::filesystem("read snapshot directory", &snapshot_dir, e))?
+        {
+            let entry = entry
+                .map_err(|e| ConfigError::filesystem("read snapshot entry", &snapshot_dir, e))?;
+            let path = entry.path();
+            let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+                continue;
+            };
+            if !name.starts_with(&format!("{tag}_")) || !name.ends_with(SNAPSHOT_EXTENSION) {
+                continue;
+            }
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+
+            let created_at = parse_backup_timestamp(name, &tag)
+                .or_else(|| metadata.modified().ok().map(DateTime::<Utc>::from));
+            if let Some(created_at) = created_at {
+                snapshots.push(BackupInfo {
+                    path: path.to_string_lossy().to_string(),
+                    original_path: dir.to_string_lossy().to_string(),
+                    created_at,
+                    size: metadata.len(),
+                    tag: None,
+                });
+            }
+        }
+
+        snapshots.sort_by(|a, b| {
+            b.created_at
+                .cmp(&a.created_at)
+                .then_with(|| b.path.cmp(&a.path))
+        });
+
+        Ok(snapshots)
+    }
+
+    /// Restore a snapshot over a directory
+    ///
+    /// The current directory is snapshotted first, so the restore itself can
+    /// be undone. Files in the snapshot overwrite their counterparts; files
+    /// that aren't in the snapshot are left alone.
+    ///
+    /// # Arguments
+    /// * `archive` - Snapshot created by [`create_dir_backup`](Self::create_dir_backup)
+    /// * `target` - Directory to restore into (created if needed)
+    ///
+    /// # Errors
+    /// Returns an error if the snapshot cannot be read, contains entries
+    /// outside the directory, or the safety snapshot or a file cannot be
+    /// written. Nothing is extracted unless the whole snapshot is valid.
+    pub fn restore_dir_backup(&self, archive: &Path, target: &Path) -> Result<SnapshotRestore> {
+        if !archive.is_file() {
+            return Err(ConfigError::not_found(archive));
+        }
+
+        // Check every entry before touching the directory
+        check_snapshot_entries(archive)?;
+
+        // The safety snapshot has no size limit: it must never block a restore
+        let safety_snapshot = if target.is_dir() {
+            Some(self.create_dir_backup_with_options(
+                target,
+                &SnapshotOptions::default().with_max_size(u64::MAX),
+            )?)
+        } else {
+            None
+        };
+
+        let file = fs::File::open(archive)
+            .map_err(|e| ConfigError::filesystem("open snapshot", archive, e))?;
+        let mut tar = tar::Archive::new(GzDecoder::new(file));
+        let read_error = |e| ConfigError::filesystem("read snapshot", archive, e);
+        let mut files = 0;
+
+        for entry in tar.entries().map_err(read_error)? {
+            let mut entry = entry.map_err(read_error)?;
+            if !entry.header().entry_type().is_file() {
+                continue;
+            }
+            let relative = entry.path().map_err(read_error)?.into_owned();
+            let destination = target.join(&relative);
+            if let Some(parent) = destination.parent() {
+                fs::create_dir_all(parent)
+                    .map_err(|e| ConfigError::filesystem("create directory", parent, e))?;
+            }
+            entry
+                .unpack(&destination)
+                .map_err(|e| ConfigError::filesystem("restore snapshot file", &destination, e))?;
+            files += 1;
+        }
+
+        tracing::info!(
+            "Restored snapshot {} into {}",
+            archive.display(),
+            target.display()
+        );
+
+        Ok(SnapshotRestore {
+            files,
+            safety_snapshot,
+        })
+    }
+
+    /// Directory holding snapshots
+    fn snapshot_dir(&self) -> PathBuf {
+        self.backup_dir.join(SNAPSHOT_DIR)
+    }
+}
+
+/// Name prefix of a directory's snapshots (`.claude` -> `claude`)
+fn snapshot_tag(dir: &Path) -> String {
+    let name = dir
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("dir")
+        .trim_start_matches('.');
+    if name.is_empty() {
+        "dir".to_string()
+    } else {
+        name.to_string()
+    }
+}
+
+/// Regular files to archive below `dir`, with their sizes, in a stable order
+fn snapshot_files(
+    dir: &Path,
+    options: &SnapshotOptions,
+    skipped: &[PathBuf],
+) -> Result<Vec<(PathBuf, u64)>> {
+    let skipped: Vec<PathBuf> = skipped
+        .iter()
+        .map(|path| path.canonicalize().unwrap_or_else(|_| path.clone()))
+        .collect();
+    let mut files = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+
+    while let Some(current) = pending.pop() {
+        for entry in fs::read_dir(&current)
+            .map_err(|e| ConfigError::filesystem("read directory", &current, e))?
+        {
+            let entry =
+                entry.map_err(|e| ConfigError::filesystem("read directory entry", &current, e))?;
+            let path = entry.path();
+            let file_type = entry
+                .file_type()
+                .map_err(|e| ConfigError::filesystem("read file type", &path, e))?;
+
+            if file_type.is_dir() {
+                let name = entry.file_name();
+                let excluded = options.exclude.iter().any(|e| name == e.as_str());
+                let canonical = path.canonicalize().unwrap_or_else(|_| path.clone());
+                if excluded || skipped.contains(&canonical) {
+                    tracing::debug!("Skipped {} in snapshot", path.display());
+                    continue;
+                }
+                pending.push(path);
+            } else if file_type.is_file() {
+                let size = entry
+                    .metadata()
+                    .map_err(|e| ConfigError::filesystem("read file metadata", &path, e))?
+                    .len();
+                files.push((path, size));
+            }
+        }
+    }
+
+    files.sort();
+    Ok(files)
+}
+
+/// Validate a snapshot's entries without extracting anything
+fn check_snapshot_entries(archive: &Path) -> Result<()> {
+    let file = fs::File::open(archive)
+        .map_err(|e| ConfigError::filesystem("open snapshot", archive, e))?;
+    let mut tar = tar::Archive::new(GzDecoder::new(file));
+    let read_error = |e| ConfigError::filesystem("read snapshot", archive, e);
+
+    for entry in tar.entries().map_err(read_error)? {
+        let entry = entry.map_err(read_error)?;
+        let path = entry.path().map_err(read_error)?;
+        let safe = path.components().count() > 0
+            && path.components().all(|c| matches!(c, Component::Normal(_)));
+        if !safe {
+            return Err(ConfigError::validation_failed(
+                "DirectorySnapshot",
+                format!("Unexpected snapshot entry: {}", path.display()),
+                "Only snapshots created by 'ccm history snapshot-dir' can be restored",
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backup::FixedClock;
+    use tempfile::TempDir;
+
+    // TDD Test 1: Exclusions, the backups subfolder and the size limit
+    #[test]
+    fn test_snapshot_skips_excluded_dirs_and_enforces_limit() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path().join(".claude");
+        fs::create_dir_all(dir.join("node_modules").join("pkg")).unwrap();
+        fs::create_dir_all(dir.join("backups")).unwrap();
+        fs::write(dir.join("config.json"), "{}").unwrap();
+        fs::write(dir.join("node_modules").join("pkg").join("big.js"), "x").unwrap();
+        fs::write(dir.join("backups").join("config_1.json"), "{}").unwrap();
+
+        // Snapshots stored inside the directory itself are skipped too
+        let manager = BackupManager::new(&dir, None);
+        let clock = FixedClock::new(
+            DateTime::parse_from_rfc3339("2025-01-20T14:30:45Z")
+                .unwrap()
+                .with_timezone(&Utc),
+        );
+        let options = SnapshotOptions::new();
+        manager
+            .create_dir_backup_with_clock(&dir, &options, &clock)
+            .unwrap();
+        let snapshot = manager
+            .create_dir_backup_with_clock(&dir, &options, &clock)
+            .unwrap();
+        assert!(snapshot
+            .to_string_lossy()
+            .ends_with("claude_20250120_143045.000000_1.tar.gz"));
+
+        let file = fs::File::open(&snapshot).unwrap();
+        let names: Vec<String> = tar::Archive::new(GzDecoder::new(file))
+            .entries()
+            .unwrap()
+            .map(|e| e.unwrap().path().unwrap().to_string_lossy().to_string())
+            .collect();
+        assert_eq!(names, vec!["config.json"]);
+        assert_eq!(manager.list_dir_backups(&dir).unwrap().len(), 2);
+
+        let err = manager
+            .create_dir_backup_with_options(&dir, &options.with_max_size(1))
+            .unwrap_err();
+        assert!(err.to_string().contains("snapshot limit"));
+    }
+}
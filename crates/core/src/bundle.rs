@@ -0,0 +1,689 @@
+//! Resumable export of many project configs into one zip
+//!
+//! Each config is first copied into a staging directory, and a progress
+//! manifest there is rewritten after every entry. An interrupted run
+//! (cancelled, or failed on a full disk) leaves the staging directory behind;
+//! resuming it skips entries whose source is unchanged. The zip is only
+//! assembled, under a temporary name and then renamed, once every entry is
+//! staged, so a bundle at the output path is always complete.
+//!
+//! A fresh export only stages into a missing or empty directory and marks it
+//! with [`STAGING_MARKER_NAME`]; only marked directories are resumed or
+//! removed afterwards, so a mistyped path never deletes unrelated files.
+//!
+//! Entries are ordered by project root and the archive uses fixed
+//! timestamps, so the same sources always produce the same bytes.
+
+use crate::{
+    config::manager::atomic_write,
+    error::{ConfigError, Result},
+    hash::content_hash,
+    paths::project_config_path,
+    project::ProjectInfo,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+/// Name of the progress manifest in the staging directory
+pub const STAGING_MANIFEST_NAME: &str = "progress.json";
+
+/// Name of the file marking a staging directory as created by ccm
+pub const STAGING_MARKER_NAME: &str = ".ccm-staging";
+
+/// Name of the index stored in the final bundle
+const INDEX_NAME: &str = "bundle.json";
+
+/// Directory holding the project entries, kept apart from the manifest and index
+const PROJECTS_DIR: &str = "projects";
+
+/// Shared flag for stopping a long-running operation from another thread
+///
+/// Clones share the same flag.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    /// Create a token that is not cancelled
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ask the operation to stop at the next safe point
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    /// Whether [`cancel`](Self::cancel) has been called
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+/// A config file to include in a bundle
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BundleSource {
+    /// Entry name in the bundle (unique, e.g. `projects/my-app.json`)
+    pub name: String,
+
+    /// Config file to copy
+    pub path: PathBuf,
+}
+
+impl BundleSource {
+    /// Sources for the project configs, in deterministic order
+    ///
+    /// Projects without a `.claude/config.json` (including those whose config
+    /// is embedded in a manifest) are skipped. Entries are named after the
+    /// project under `projects/`, with a numeric suffix when the name is
+    /// already taken.
+    pub fn from_projects<'a>(projects: impl IntoIterator<Item = &'a ProjectInfo>) -> Vec<Self> {
+        let mut projects: Vec<&ProjectInfo> = projects
+            .into_iter()
+            .filter(|project| {
+                project.has_config && project.config_path == project_config_path(&project.root)
+            })
+            .collect();
+        projects.sort_by(|a, b| a.root.cmp(&b.root));
+
+        let mut taken: HashSet<String> = HashSet::new();
+        projects
+            .into_iter()
+            .map(|project| {
+                let stem = entry_stem(&project.name);
+                let mut name = stem.clone();
+                let mut suffix = 1;
+                while !taken.insert(name.clone()) {
+                    suffix += 1;
+                    name = format!("{stem}-{suffix}");
+                }
+                let name = format!("{PROJECTS_DIR}/{name}.json");
+                Self {
+                    name,
+                    path: project.config_path.clone(),
+                }
+            })
+            .collect()
+    }
+}
+
+/// Progress of one staged entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EntryStatus {
+    /// Not copied yet
+    Pending,
+
+    /// Copied into the staging directory
+    Done,
+}
+
+/// One entry of the staging manifest
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StagedEntry {
+    /// Entry name in the bundle
+    pub name: String,
+
+    /// Config file the entry was copied from
+    pub source: PathBuf,
+
+    /// Content hash of the source when it was copied
+    pub source_hash: Option<String>,
+
+    /// Progress of the entry
+    pub status: EntryStatus,
+}
+
+/// Progress manifest kept in the staging directory
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StagingManifest {
+    /// Entries in bundle order
+    pub entries: Vec<StagedEntry>,
+}
+
+impl StagingManifest {
+    /// Read the manifest of a staging directory
+    ///
+    /// # Errors
+    /// Returns an error if the manifest is missing or malformed
+    pub fn load(staging_dir: &Path) -> Result<Self> {
+        let path = staging_dir.join(STAGING_MANIFEST_NAME);
+        if !path.exists() {
+            return Err(ConfigError::not_found(&path));
+        }
+        let content = fs::read_to_string(&path)
+            .map_err(|e| ConfigError::filesystem("read staging manifest", &path, e))?;
+        serde_json::from_str(&content)
+            .map_err(|e| ConfigError::invalid_json(&path, e.line(), e.column(), e.to_string()))
+    }
+
+    /// Write the manifest to a staging directory (atomically)
+    ///
+    /// # Errors
+    /// Returns an error if the manifest cannot be written
+    pub fn save(&self, staging_dir: &Path) -> Result<()> {
+        let content = serde_json::to_string_pretty(self)?;
+        atomic_write(&staging_dir.join(STAGING_MANIFEST_NAME), &content)
+    }
+
+    /// Number of entries that are done
+    pub fn done(&self) -> usize {
+        self.entries
+            .iter()
+            .filter(|entry| entry.status == EntryStatus::Done)
+            .count()
+    }
+}
+
+/// Result of [`export_all`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExportAllOutcome {
+    /// Every entry was staged and the bundle was written
+    Completed {
+        /// Path of the bundle
+        path: PathBuf,
+
+        /// Number of entries in the bundle
+        entries: usize,
+
+        /// Entries reused from an earlier, interrupted run
+        reused: usize,
+    },
+
+    /// The token was cancelled; the staging directory can be resumed
+    Cancelled {
+        /// Staging directory holding the partial export
+        staging_dir: PathBuf,
+
+        /// Entries staged so far
+        done: usize,
+
+        /// Total number of entries
+        total: usize,
+    },
+}
+
+/// Export config files into a single zip through a staging directory
+///
+/// # Arguments
+/// * `sources` - Config files to include (see [`BundleSource::from_projects`])
+/// * `out` - Destination zip path
+/// * `staging_dir` - Directory for the partial export
+/// * `resume` - Continue the partial export in `staging_dir`
+/// * `token` - Checked before each entry; cancelling leaves a resumable state
+/// * `on_entry` - Called after each entry is staged or reused
+///
+/// # Errors
+/// Returns an error if `staging_dir` already holds a partial export or other
+/// files and `resume` is false, if there is nothing to resume or `staging_dir`
+/// wasn't created by ccm, or if a file cannot be read or written. Entries
+/// staged before the error are kept for resuming.
+pub fn export_all(
+    sources: &[BundleSource],
+    out: &Path,
+    staging_dir: &Path,
+    resume: bool,
+    token: &CancellationToken,
+    mut on_entry: impl FnMut(&StagedEntry),
+) -> Result<ExportAllOutcome> {
+    let previous = if resume {
+        if !is_staging_dir(staging_dir) {
+            return Err(ConfigError::validation_failed(
+                "StagingNotMarked",
+                format!("{} is not a ccm staging directory", staging_dir.display()),
+                "Pass the staging directory an interrupted export reported",
+            ));
+        }
+        StagingManifest::load(staging_dir)?
+    } else {
+        if staging_dir.join(STAGING_MANIFEST_NAME).exists() {
+            return Err(ConfigError::validation_failed(
+                "StagingExists",
+                format!("{} already holds a partial export", staging_dir.display()),
+                "Resume it, or remove the directory to start over",
+            ));
+        }
+        create_staging_dir(staging_dir)?;
+        StagingManifest::default()
+    };
+
+    let previous: HashMap<&str, &StagedEntry> = previous
+        .entries
+        .iter()
+        .map(|entry| (entry.name.as_str(), entry))
+        .collect();
+
+    // The manifest always lists the current sources, so entries for projects
+    // that disappeared since the interrupted run are dropped
+    let mut manifest = StagingManifest {
+        entries: sources
+            .iter()
+            .map(|source| StagedEntry {
+                name: source.name.clone(),
+                source: source.path.clone(),
+                source_hash: None,
+                status: EntryStatus::Pending,
+            })
+            .collect(),
+    };
+    manifest.save(staging_dir)?;
+
+    let mut reused = 0;
+    for (index, source) in sources.iter().enumerate() {
+        if token.is_cancelled() {
+            tracing::info!(
+                "Export cancelled after {} of {} entries",
+                manifest.done(),
+                sources.len()
+            );
+            return Ok(ExportAllOutcome::Cancelled {
+                staging_dir: staging_dir.to_path_buf(),
+                done: manifest.done(),
+                total: sources.len(),
+            });
+        }
+
+        let content = fs::read(&source.path)
+            .map_err(|e| ConfigError::filesystem("read config for export", &source.path, e))?;
+        let hash = content_hash(&content);
+        let staged_path = staging_dir.join(&source.name);
+
+        let unchanged = previous.get(source.name.as_str()).is_some_and(|entry| {
+            entry.status == EntryStatus::Done
+                && entry.source == source.path
+                && entry.source_hash.as_deref() == Some(hash.as_str())
+                && staged_path.is_file()
+        });
+        if unchanged {
+            reused += 1;
+        } else {
+            if let Some(parent) = staged_path.parent() {
+                fs::create_dir_all(parent)
+                    .map_err(|e| ConfigError::filesystem("create staging directory", parent, e))?;
+            }
+            fs::write(&staged_path, &content)
+                .map_err(|e| ConfigError::filesystem("stage config", &staged_path, e))?;
+        }
+
+        let entry = &mut manifest.entries[index];
+        entry.source_hash = Some(hash);
+        entry.status = EntryStatus::Done;
+        manifest.save(staging_dir)?;
+        on_entry(&manifest.entries[index]);
+    }
+
+    write_bundle(&manifest, staging_dir, out)?;
+    if is_staging_dir(staging_dir) {
+        fs::remove_dir_all(staging_dir)
+            .map_err(|e| ConfigError::filesystem("remove staging directory", staging_dir, e))?;
+    }
+
+    tracing::info!(
+        "Wrote bundle of {} configs to: {}",
+        sources.len(),
+        out.display()
+    );
+
+    Ok(ExportAllOutcome::Completed {
+        path: out.to_path_buf(),
+        entries: sources.len(),
+        reused,
+    })
+}
+
+/// Whether `dir` carries the marker of a ccm staging directory
+fn is_staging_dir(dir: &Path) -> bool {
+    dir.join(STAGING_MARKER_NAME).is_file()
+}
+
+/// Create and mark a staging directory
+///
+/// An existing directory is only used when it's empty, so files that aren't
+/// ccm's never end up in a directory that is removed afterwards.
+fn create_staging_dir(dir: &Path) -> Result<()> {
+    if dir.exists() {
+        let mut entries = fs::read_dir(dir)
+            .map_err(|e| ConfigError::filesystem("read staging directory", dir, e))?;
+        if entries.next().is_some() {
+            return Err(ConfigError::validation_failed(
+                "StagingNotEmpty",
+                format!("{} exists and is not empty", dir.display()),
+                "Choose another output path, or remove the directory",
+            ));
+        }
+    }
+    fs::create_dir_all(dir)
+        .map_err(|e| ConfigError::filesystem("create staging directory", dir, e))?;
+    let marker = dir.join(STAGING_MARKER_NAME);
+    fs::write(&marker, "")
+        .map_err(|e| ConfigError::filesystem("mark staging directory", &marker, e))
+}
+
+/// Assemble the staged entries into `out`
+///
+/// The archive is written next to `out` and renamed into place, so `out`
+/// never holds a partial bundle.
+fn write_bundle(manifest: &StagingManifest, staging_dir: &Path, out: &Path) -> Result<()> {
+    if let Some(parent) = out.parent() {
+        if !parent.as_os_str().is_empty() && !parent.exists() {
+            fs::create_dir_all(parent)
+                .map_err(|e| ConfigError::filesystem("create bundle directory", parent, e))?;
+        }
+    }
+
+    let mut partial = out.as_os_str().to_owned();
+    partial.push(".partial");
+    let partial = PathBuf::from(partial);
+
+    let file = fs::File::create(&partial)
+        .map_err(|e| ConfigError::filesystem("create bundle", &partial, e))?;
+    let mut zip = zip::ZipWriter::new(file);
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated)
+        .last_modified_time(zip::DateTime::default());
+
+    // Index of the entries, without the staging progress
+    let index: Vec<_> = manifest
+        .entries
+        .iter()
+        .map(|entry| {
+            serde_json::json!({
+                "name": entry.name,
+                "source": entry.source,
+                "sourceHash": entry.source_hash,
+            })
+        })
+        .collect();
+
+    zip.start_file(INDEX_NAME, options).map_err(zip_error)?;
+    zip.write_all(&serde_json::to_vec_pretty(&index)?)
+        .map_err(|e| ConfigError::filesystem("write bundle", &partial, e))?;
+
+    for entry in &manifest.entries {
+        let staged_path = staging_dir.join(&entry.name);
+        let content = fs::read(&staged_path)
+            .map_err(|e| ConfigError::filesystem("read staged config", &staged_path, e))?;
+        zip.start_file(entry.name.as_str(), options)
+            .map_err(zip_error)?;
+        zip.write_all(&content)
+            .map_err(|e| ConfigError::filesystem("write bundle", &partial, e))?;
+    }
+
+    zip.finish().map_err(zip_error)?;
+    fs::rename(&partial, out).map_err(|e| ConfigError::filesystem("rename bundle", out, e))
+}
+
+/// File-name-safe form of a project name
+fn entry_stem(name: &str) -> String {
+    let stem: String = name
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || matches!(c, '-' | '_' | '.') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    match stem.trim_start_matches('.') {
+        "" => "project".to_string(),
+        trimmed => trimmed.to_string(),
+    }
+}
+
+fn zip_error(e: zip::result::ZipError) -> ConfigError {
+    ConfigError::Generic(format!("Failed to write bundle: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn sources(temp_dir: &TempDir, count: usize) -> Vec<BundleSource> {
+        (0..count)
+            .map(|i| {
+                let path = temp_dir.path().join(format!("project{i}.json"));
+                fs::write(&path, format!(r#"{{"allowedPaths": ["~/p{i}"]}}"#)).unwrap();
+                BundleSource {
+                    name: format!("project{i}.json"),
+                    path,
+                }
+            })
+            .collect()
+    }
+
+    // TDD Test 1: An interrupted and resumed export matches an uninterrupted one
+    #[test]
+    fn test_cancel_and_resume_matches_full_run() {
+        let temp_dir = TempDir::new().unwrap();
+        let sources = sources(&temp_dir, 5);
+
+        let full = temp_dir.path().join("full.zip");
+        let outcome = export_all(
+            &sources,
+            &full,
+            &temp_dir.path().join("full-staging"),
+            false,
+            &CancellationToken::new(),
+            |_| {},
+        )
+        .unwrap();
+        assert!(matches!(
+            outcome,
+            ExportAllOutcome::Completed {
+                entries: 5,
+                reused: 0,
+                ..
+            }
+        ));
+
+        let out = temp_dir.path().join("resumed.zip");
+        let staging = temp_dir.path().join("staging");
+        let token = CancellationToken::new();
+        let mut staged = 0;
+        let outcome = export_all(&sources, &out, &staging, false, &token, |_| {
+            staged += 1;
+            if staged == 2 {
+                token.cancel();
+            }
+        })
+        .unwrap();
+        assert_eq!(
+            outcome,
+            ExportAllOutcome::Cancelled {
+                staging_dir: staging.clone(),
+                done: 2,
+                total: 5
+            }
+        );
+        assert!(!out.exists());
+        assert_eq!(StagingManifest::load(&staging).unwrap().done(), 2);
+
+        // Starting over on top of a partial export is refused
+        assert!(export_all(
+            &sources,
+            &out,
+            &staging,
+            false,
+            &CancellationToken::new(),
+            |_| {}
+        )
+        .is_err());
+
+        let outcome = export_all(
+            &sources,
+            &out,
+            &staging,
+            true,
+            &CancellationToken::new(),
+            |_| {},
+        )
+        .unwrap();
+        assert!(matches!(
+            outcome,
+            ExportAllOutcome::Completed {
+                entries: 5,
+                reused: 2,
+                ..
+            }
+        ));
+        assert!(!staging.exists());
+        assert_eq!(fs::read(&out).unwrap(), fs::read(&full).unwrap());
+    }
+
+    // TDD Test 2: Sources changed since the interruption are copied again
+    #[test]
+    fn test_resume_restages_changed_sources() {
+        let temp_dir = TempDir::new().unwrap();
+        let sources = sources(&temp_dir, 3);
+        let out = temp_dir.path().join("bundle.zip");
+        let staging = temp_dir.path().join("staging");
+
+        let token = CancellationToken::new();
+        export_all(&sources, &out, &staging, false, &token, |_| token.cancel()).unwrap();
+        fs::write(&sources[0].path, r#"{"allowedPaths": ["~/changed"]}"#).unwrap();
+
+        let outcome = export_all(
+            &sources,
+            &out,
+            &staging,
+            true,
+            &CancellationToken::new(),
+            |_| {},
+        )
+        .unwrap();
+        assert!(matches!(
+            outcome,
+            ExportAllOutcome::Completed { reused: 0, .. }
+        ));
+
+        let mut archive = zip::ZipArchive::new(fs::File::open(&out).unwrap()).unwrap();
+        let mut content = String::new();
+        std::io::Read::read_to_string(&mut archive.by_name("project0.json").unwrap(), &mut content)
+            .unwrap();
+        assert!(content.contains("~/changed"));
+        assert_eq!(archive.len(), 4);
+    }
+
+    // TDD Test 3: Entry names are ordered by project root and made unique
+    #[test]
+    fn test_sources_from_projects() {
+        let project = |root: &str, name: &str, has_config: bool| {
+            let mut info =
+                ProjectInfo::from_config_path(Path::new(root).join(".claude").join("config.json"));
+            info.name = name.to_string();
+            info.has_config = has_config;
+            info
+        };
+        let projects = [
+            project("/b/app", "app", true),
+            project("/a/app", "app", true),
+            project("/c/my app", "my app", true),
+            project("/d/empty", "empty", false),
+        ];
+
+        let names: Vec<String> = BundleSource::from_projects(&projects)
+            .into_iter()
+            .map(|source| source.name)
+            .collect();
+
+        assert_eq!(
+            names,
+            [
+                "projects/app.json",
+                "projects/app-2.json",
+                "projects/my_app.json"
+            ]
+        );
+    }
+
+    // TDD Test 4: Directories ccm didn't create are neither staged into nor removed
+    #[test]
+    fn test_unmarked_staging_dir_is_left_alone() {
+        let temp_dir = TempDir::new().unwrap();
+        let sources = sources(&temp_dir, 2);
+        let out = temp_dir.path().join("bundle.zip");
+        let staging = temp_dir.path().join("documents");
+        fs::create_dir_all(&staging).unwrap();
+        fs::write(staging.join("notes.txt"), "keep me").unwrap();
+
+        let token = CancellationToken::new();
+        for resume in [false, true] {
+            let err = export_all(&sources, &out, &staging, resume, &token, |_| {}).unwrap_err();
+            assert!(matches!(err, ConfigError::ValidationFailed { .. }));
+        }
+        assert!(staging.join("notes.txt").is_file());
+        assert!(!out.exists());
+
+        // An empty existing directory is marked and removed once done
+        fs::remove_file(staging.join("notes.txt")).unwrap();
+        export_all(&sources, &out, &staging, false, &token, |_| {}).unwrap();
+        assert!(out.is_file());
+        assert!(!staging.exists());
+    }
+
+    // TDD Test 5: Project names can't clash with the manifest, index or suffixed names
+    #[test]
+    fn test_entry_names_do_not_collide() {
+        let temp_dir = TempDir::new().unwrap();
+        let projects: Vec<ProjectInfo> = [
+            ("a-app", "app"),
+            ("b-app", "app"),
+            ("c-app-2", "app-2"),
+            ("d-bundle", "bundle"),
+            ("e-progress", "progress"),
+        ]
+        .into_iter()
+        .map(|(dir, name)| {
+            let root = temp_dir.path().join(dir);
+            let config_path = project_config_path(&root);
+            fs::create_dir_all(config_path.parent().unwrap()).unwrap();
+            fs::write(&config_path, format!(r#"{{"allowedPaths": ["~/{name}"]}}"#)).unwrap();
+            let mut info = ProjectInfo::from_config_path(config_path);
+            info.name = name.to_string();
+            info.has_config = true;
+            info
+        })
+        .collect();
+
+        let sources = BundleSource::from_projects(&projects);
+        let names: Vec<&str> = sources.iter().map(|source| source.name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "projects/app.json",
+                "projects/app-2.json",
+                "projects/app-2-2.json",
+                "projects/bundle.json",
+                "projects/progress.json",
+            ]
+        );
+
+        let out = temp_dir.path().join("out.zip");
+        let staging = temp_dir.path().join("staging");
+        let token = CancellationToken::new();
+        export_all(&sources, &out, &staging, false, &token, |_| {}).unwrap();
+
+        let mut archive = zip::ZipArchive::new(fs::File::open(&out).unwrap()).unwrap();
+        assert_eq!(archive.len(), 6);
+        for (source, project) in sources.iter().zip(&projects) {
+            let mut content = String::new();
+            std::io::Read::read_to_string(
+                &mut archive.by_name(&source.name).unwrap(),
+                &mut content,
+            )
+            .unwrap();
+            assert!(content.contains(&format!("~/{}", project.name)));
+        }
+        assert!(archive.by_name(INDEX_NAME).is_ok());
+        assert!(archive.by_name(STAGING_MANIFEST_NAME).is_err());
+    }
+}
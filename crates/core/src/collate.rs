@@ -0,0 +1,110 @@
+//! Locale-independent comparison of names
+//!
+//! Server and project names are compared and sorted in several places
+//! (listings, duplicate detection, looking a project up by name). Plain
+//! string ordering puts `Zebra` before `apple`, and ad-hoc lowercasing
+//! differs between code paths, so the same names could sort or match
+//! differently depending on where they were shown.
+//!
+//! All name comparisons go through this module instead. A name's collation
+//! key is its NFKC normalization, lowercased:
+//!
+//! - `Résumé`, `résumé` and `Re\u{301}sume\u{301}` (decomposed accents) are the
+//!   same name; full-width `Ｆｅｔｃｈ` equals `fetch`
+//! - names are ordered by the Unicode code points of their keys, so ASCII
+//!   sorts before accented Latin, which sorts before CJK, on every platform
+//!   and regardless of the user's locale
+//! - names with equal keys are ordered by their original code points, so
+//!   sorting is total and repeatable
+//!
+//! This is deliberately simpler than full Unicode collation (UCA): it is
+//! stable and dependency-light, not linguistically ideal for any one locale.
+
+use icu_normalizer::ComposingNormalizerBorrowed;
+use std::cmp::Ordering;
+
+/// Collation key of a name: NFKC-normalized and lowercased
+pub fn key(name: &str) -> String {
+    ComposingNormalizerBorrowed::new_nfkc()
+        .normalize(name)
+        .to_lowercase()
+}
+
+/// Compare two names by collation key, then by their original text
+pub fn compare(a: &str, b: &str) -> Ordering {
+    key(a).cmp(&key(b)).then_with(|| a.cmp(b))
+}
+
+/// Whether two names are the same name (equal collation keys)
+pub fn same_name(a: &str, b: &str) -> bool {
+    a == b || key(a) == key(b)
+}
+
+/// Sort names in collation order
+pub fn sort<T: AsRef<str>>(names: &mut [T]) {
+    names.sort_by_cached_key(|name| (key(name.as_ref()), name.as_ref().to_string()));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXTURE: &[&str] = &[
+        "项目",
+        "Zebra",
+        "résumé",
+        "apple",
+        "Résumé",
+        "プロジェクト",
+        "Apple",
+        "Ｆｅｔｃｈ",
+        "éclair",
+        "beta",
+        "Re\u{301}sume\u{301}",
+        "fetch",
+    ];
+
+    // TDD Test 1: Case, accents and width variants are the same name
+    #[test]
+    fn test_same_name() {
+        assert!(same_name("Résumé", "résumé"));
+        assert!(same_name("résumé", "Re\u{301}sume\u{301}"));
+        assert!(same_name("Ｆｅｔｃｈ", "fetch"));
+        assert!(same_name("项目", "项目"));
+        assert!(!same_name("resume", "résumé"));
+        assert!(!same_name("项目", "プロジェクト"));
+    }
+
+    // TDD Test 2: Ordering is fixed and repeatable for mixed scripts
+    #[test]
+    fn test_sort_fixture_is_stable() {
+        let mut names = FIXTURE.to_vec();
+        sort(&mut names);
+
+        assert_eq!(
+            names,
+            [
+                "Apple",
+                "apple",
+                "beta",
+                "fetch",
+                "Ｆｅｔｃｈ",
+                "Re\u{301}sume\u{301}",
+                "Résumé",
+                "résumé",
+                "Zebra",
+                "éclair",
+                "プロジェクト",
+                "项目",
+            ]
+        );
+
+        // Any input order gives the same result
+        for rotation in 1..FIXTURE.len() {
+            let mut rotated = FIXTURE.to_vec();
+            rotated.rotate_left(rotation);
+            rotated.sort_by(|a, b| compare(a, b));
+            assert_eq!(rotated, names);
+        }
+    }
+}
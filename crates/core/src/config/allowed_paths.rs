@@ -0,0 +1,328 @@
+//! Untangling project `allowedPaths` copied from the global config
+//!
+//! Arrays replace on merge, so a project that sets `allowedPaths` hides the
+//! global list entirely. Users work around this by copying the global list
+//! into each project, and the copies drift over time. [`plan_dedupe`]
+//! classifies each project entry against the global list and proposes a
+//! change only when the effective `allowedPaths` stay exactly the same:
+//!
+//! - entries repeating an earlier project entry are dropped;
+//! - a project list matching the global list is removed, so the project
+//!   inherits the global one again.
+//!
+//! Global copies next to project-only entries have to stay: without them
+//! the project would lose those paths, since there is no append merge.
+
+use crate::{
+    config::{
+        merge::{merge_configs_with_options, MergeOptions},
+        ClaudeConfig,
+    },
+    paths::{expand_env, expand_tilde},
+};
+use serde::Serialize;
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+
+/// How a project's allowed path relates to the global list
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PathClass {
+    /// Not in the global list
+    ProjectOnly,
+    /// Resolves to a global entry
+    DuplicateOfGlobal,
+    /// Resolves to an earlier entry of the project list
+    Repeated,
+}
+
+impl PathClass {
+    /// Short description for listings
+    pub fn label(self) -> &'static str {
+        match self {
+            PathClass::ProjectOnly => "project only",
+            PathClass::DuplicateOfGlobal => "duplicate of global",
+            PathClass::Repeated => "repeated in project",
+        }
+    }
+}
+
+/// A project allowed path and its classification
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ClassifiedPath {
+    /// The entry as written in the project config
+    pub path: String,
+
+    /// How it relates to the global list
+    pub class: PathClass,
+}
+
+/// Proposed change to a project's `allowedPaths`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case", tag = "action", content = "paths")]
+pub enum DedupeAction {
+    /// Nothing can be removed without changing the effective paths
+    Keep,
+    /// Replace the project list with these entries
+    Replace(Vec<String>),
+    /// Remove the project list so the global list applies
+    Remove,
+}
+
+/// Classification of a project's `allowedPaths` and the proposed cleanup
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DedupePlan {
+    /// Every project entry, in order
+    pub entries: Vec<ClassifiedPath>,
+
+    /// Global entries the project list lacks (hidden from the project)
+    pub hidden_global: Vec<String>,
+
+    /// Proposed change
+    pub action: DedupeAction,
+
+    /// Effective allowed paths now
+    pub effective_before: Vec<String>,
+
+    /// Effective allowed paths after the change
+    pub effective_after: Vec<String>,
+}
+
+impl DedupePlan {
+    /// Whether the plan changes nothing
+    pub fn is_noop(&self) -> bool {
+        self.action == DedupeAction::Keep
+    }
+
+    /// Entries the plan drops from the project config
+    pub fn removed(&self) -> Vec<&str> {
+        match &self.action {
+            DedupeAction::Keep => Vec::new(),
+            DedupeAction::Remove => self.entries.iter().map(|e| e.path.as_str()).collect(),
+            DedupeAction::Replace(_) => self
+                .entries
+                .iter()
+                .filter(|e| e.class == PathClass::Repeated)
+                .map(|e| e.path.as_str())
+                .collect(),
+        }
+    }
+
+    /// Global copies that stay because project-only entries need them
+    pub fn kept_global_copies(&self) -> Vec<&str> {
+        if self.action == DedupeAction::Remove {
+            return Vec::new();
+        }
+        self.entries
+            .iter()
+            .filter(|e| e.class == PathClass::DuplicateOfGlobal)
+            .map(|e| e.path.as_str())
+            .collect()
+    }
+
+    /// Apply the plan to a project config
+    pub fn apply(&self, project: &mut ClaudeConfig) {
+        match &self.action {
+            DedupeAction::Keep => {}
+            DedupeAction::Replace(paths) => project.allowed_paths = Some(paths.clone()),
+            DedupeAction::Remove => project.allowed_paths = None,
+        }
+    }
+}
+
+/// Location an allowed path refers to, after expanding variables and `~`
+///
+/// Two entries are duplicates when they resolve to the same location.
+pub fn resolve_allowed_path(path: &str) -> PathBuf {
+    expand_tilde(&PathBuf::from(expand_env(path)))
+}
+
+/// Classify a project's allowed paths against the global list
+///
+/// # Arguments
+/// * `global` - Global configuration
+/// * `project` - Project configuration
+/// * `options` - Merge options used to compute the effective paths
+///
+/// # Returns
+/// The classification and a change that keeps the effective paths as they
+/// are (possibly [`DedupeAction::Keep`])
+pub fn plan_dedupe(
+    global: &ClaudeConfig,
+    project: &ClaudeConfig,
+    options: MergeOptions,
+) -> DedupePlan {
+    let global_paths = global.allowed_paths.clone().unwrap_or_default();
+    let global_set: BTreeSet<PathBuf> = global_paths
+        .iter()
+        .map(|p| resolve_allowed_path(p))
+        .collect();
+
+    let mut seen = BTreeSet::new();
+    let entries: Vec<ClassifiedPath> = project
+        .allowed_paths
+        .iter()
+        .flatten()
+        .map(|path| {
+            let resolved = resolve_allowed_path(path);
+            let class = if !seen.insert(resolved.clone()) {
+                PathClass::Repeated
+            } else if global_set.contains(&resolved) {
+                PathClass::DuplicateOfGlobal
+            } else {
+                PathClass::ProjectOnly
+            };
+            ClassifiedPath {
+                path: path.clone(),
+                class,
+            }
+        })
+        .collect();
+
+    let hidden_global: Vec<String> = global_paths
+        .iter()
+        .filter(|p| !seen.contains(&resolve_allowed_path(p)))
+        .cloned()
+        .collect();
+
+    let effective = |project: &ClaudeConfig| {
+        merge_configs_with_options(global, project, options)
+            .allowed_paths
+            .unwrap_or_default()
+    };
+    let effective_before = effective(project);
+
+    let any_project_only = entries.iter().any(|e| e.class == PathClass::ProjectOnly);
+    let any_repeated = entries.iter().any(|e| e.class == PathClass::Repeated);
+    let action = if project.allowed_paths.is_some() && !any_project_only && hidden_global.is_empty()
+    {
+        DedupeAction::Remove
+    } else if any_repeated {
+        DedupeAction::Replace(
+            entries
+                .iter()
+                .filter(|e| e.class != PathClass::Repeated)
+                .map(|e| e.path.clone())
+                .collect(),
+        )
+    } else {
+        DedupeAction::Keep
+    };
+
+    let mut plan = DedupePlan {
+        entries,
+        hidden_global,
+        action,
+        effective_before,
+        effective_after: Vec::new(),
+    };
+    let mut after = project.clone();
+    plan.apply(&mut after);
+    plan.effective_after = effective(&after);
+
+    // Never propose a change that alters what the project may access
+    if resolved_set(&plan.effective_after) != resolved_set(&plan.effective_before) {
+        plan.action = DedupeAction::Keep;
+        plan.effective_after = plan.effective_before.clone();
+    }
+
+    plan
+}
+
+/// Locations of a list of allowed paths
+fn resolved_set(paths: &[String]) -> BTreeSet<PathBuf> {
+    paths.iter().map(|p| resolve_allowed_path(p)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(paths: &[&str]) -> ClaudeConfig {
+        ClaudeConfig {
+            allowed_paths: Some(paths.iter().map(|p| p.to_string()).collect()),
+            ..ClaudeConfig::default()
+        }
+    }
+
+    fn classes(plan: &DedupePlan) -> Vec<PathClass> {
+        plan.entries.iter().map(|e| e.class).collect()
+    }
+
+    // TDD Test 1: A project list that only repeats the global list is removed
+    #[test]
+    fn test_normalized_duplicates_of_global_are_removed() {
+        let home = dirs::home_dir().unwrap();
+        let global = config(&["~/code", "/srv/shared"]);
+        let project = config(&[&format!("{}/code", home.display()), "/srv/shared", "~/code"]);
+
+        let plan = plan_dedupe(&global, &project, MergeOptions::default());
+
+        assert_eq!(
+            classes(&plan),
+            [
+                PathClass::DuplicateOfGlobal,
+                PathClass::DuplicateOfGlobal,
+                PathClass::Repeated
+            ]
+        );
+        assert_eq!(plan.action, DedupeAction::Remove);
+        assert_eq!(plan.removed().len(), 3);
+        assert_eq!(plan.effective_after, ["~/code", "/srv/shared"]);
+
+        let mut after = project.clone();
+        plan.apply(&mut after);
+        assert_eq!(after.allowed_paths, None);
+    }
+
+    // TDD Test 2: Global copies stay next to project-only entries
+    #[test]
+    fn test_project_unique_entries_keep_global_copies() {
+        let global = config(&["~/code"]);
+        let project = config(&["~/code", "~/secret", "~/secret/"]);
+
+        let plan = plan_dedupe(&global, &project, MergeOptions::default());
+
+        assert_eq!(
+            classes(&plan),
+            [
+                PathClass::DuplicateOfGlobal,
+                PathClass::ProjectOnly,
+                PathClass::Repeated
+            ]
+        );
+        assert_eq!(
+            plan.action,
+            DedupeAction::Replace(vec!["~/code".to_string(), "~/secret".to_string()])
+        );
+        assert_eq!(plan.removed(), ["~/secret/"]);
+        assert_eq!(plan.kept_global_copies(), ["~/code"]);
+        assert_eq!(plan.effective_after, ["~/code", "~/secret"]);
+
+        let unique = plan_dedupe(
+            &global,
+            &config(&["~/code", "~/secret"]),
+            MergeOptions::new(),
+        );
+        assert!(unique.is_noop());
+    }
+
+    // TDD Test 3: A stale copy missing global entries is left alone
+    #[test]
+    fn test_stale_copy_of_global_is_kept() {
+        let global = config(&["~/code", "~/notes"]);
+        let project = config(&["~/code"]);
+
+        let plan = plan_dedupe(&global, &project, MergeOptions::default());
+
+        assert_eq!(classes(&plan), [PathClass::DuplicateOfGlobal]);
+        assert_eq!(plan.hidden_global, ["~/notes"]);
+        // Removing the list would grant the project ~/notes
+        assert!(plan.is_noop());
+        assert_eq!(plan.effective_after, plan.effective_before);
+
+        let inherited = plan_dedupe(&global, &ClaudeConfig::new(), MergeOptions::default());
+        assert!(inherited.is_noop());
+        assert!(inherited.entries.is_empty());
+    }
+}
@@ -0,0 +1,445 @@
+//! Differences between two configurations
+//!
+//! Compares two configs as JSON values and reports what was added, removed
+//! or modified, keyed by dotted key path. Arrays are compared element by
+//! element, so changing one entry of a long list reports that entry at
+//! `key[index]` rather than the whole array:
+//!
+//! - Order-independent arrays (see [`is_order_independent`]) are matched by
+//!   value: elements only on the left are `Removed`, elements only on the
+//!   right are `Added`, and reordering is not a difference.
+//! - Other arrays are aligned on their longest common subsequence, so
+//!   inserting or removing an entry reports only that entry. Entries replaced
+//!   in place are `Modified`.
+
+use crate::{
+    config::{
+        merge::ENTRY_REPLACE_FIELDS,
+        normalize::{array_order, ArrayOrder},
+    },
+    types::{ConfigDiff, ConfigScope, SourceMap},
+};
+use serde_json::Value;
+
+/// Compare two configs given as JSON values
+///
+/// # Arguments
+/// * `left` - The base config (e.g. global)
+/// * `right` - The config compared against it (e.g. project)
+///
+/// # Returns
+/// The differences, and which side each compared key path comes from:
+/// keys that differ or only exist on the right are attributed to
+/// [`ConfigScope::Project`], the others to [`ConfigScope::Global`]
+pub fn diff_values(left: &Value, right: &Value) -> (Vec<ConfigDiff>, SourceMap) {
+    let mut diffs = Vec::new();
+    let mut source_map = SourceMap::new();
+
+    compare_values(left, right, "", &mut diffs, &mut source_map);
+    find_additions(left, right, "", &mut diffs, &mut source_map);
+
+    (diffs, source_map)
+}
+
+/// Whether the array at `key_path` is compared by value, ignoring order
+///
+/// Arrays marked unordered in [`ARRAY_ORDER`](crate::config::normalize::ARRAY_ORDER).
+pub fn is_order_independent(key_path: &str) -> bool {
+    array_order(key_path) == ArrayOrder::Unordered
+}
+
+/// Element-level differences between two arrays at `key_path`
+///
+/// `Removed` diffs use the element's index in `old`, `Added` and `Modified`
+/// diffs its index in `new`.
+pub fn diff_arrays(key_path: &str, old: &[Value], new: &[Value]) -> Vec<ConfigDiff> {
+    let element = |index: usize| format!("{key_path}[{index}]");
+    let mut diffs = Vec::new();
+
+    if is_order_independent(key_path) {
+        // Match equal elements pairwise, so duplicates are counted
+        let mut unmatched: Vec<Option<&Value>> = new.iter().map(Some).collect();
+        for (index, value) in old.iter().enumerate() {
+            match unmatched.iter_mut().find(|slot| *slot == &Some(value)) {
+                Some(slot) => *slot = None,
+                None => diffs.push(ConfigDiff::Removed {
+                    key_path: element(index),
+                    value: value.clone(),
+                }),
+            }
+        }
+        for (index, value) in unmatched.into_iter().enumerate() {
+            if let Some(value) = value {
+                diffs.push(ConfigDiff::Added {
+                    key_path: element(index),
+                    value: value.clone(),
+                });
+            }
+        }
+        return diffs;
+    }
+
+    // lcs[i][j]: length of the longest common subsequence of old[i..], new[j..]
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    // Walk the alignment, collecting each run of unmatched elements
+    let (mut i, mut j) = (0, 0);
+    let (mut removed, mut added) = (Vec::new(), Vec::new());
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            push_run(key_path, old, new, &mut removed, &mut added, &mut diffs);
+            i += 1;
+            j += 1;
+        } else if j < new.len() && (i == old.len() || lcs[i][j + 1] >= lcs[i + 1][j]) {
+            added.push(j);
+            j += 1;
+        } else {
+            removed.push(i);
+            i += 1;
+        }
+    }
+    push_run(key_path, old, new, &mut removed, &mut added, &mut diffs);
+    diffs
+}
+
+/// Report a run of unmatched elements between two matched ones
+///
+/// Removed and added elements are paired up as `Modified`; the rest are
+/// `Removed` or `Added`. Empties both runs.
+fn push_run(
+    key_path: &str,
+    old: &[Value],
+    new: &[Value],
+    removed: &mut Vec<usize>,
+    added: &mut Vec<usize>,
+    diffs: &mut Vec<ConfigDiff>,
+) {
+    let element = |index: usize| format!("{key_path}[{index}]");
+    let paired = removed.len().min(added.len());
+
+    for (&old_index, &new_index) in removed.iter().zip(added.iter()) {
+        diffs.push(ConfigDiff::Modified {
+            key_path: element(new_index),
+            old_value: old[old_index].clone(),
+            new_value: new[new_index].clone(),
+        });
+    }
+    for &index in &removed[paired..] {
+        diffs.push(ConfigDiff::Removed {
+            key_path: element(index),
+            value: old[index].clone(),
+        });
+    }
+    for &index in &added[paired..] {
+        diffs.push(ConfigDiff::Added {
+            key_path: element(index),
+            value: new[index].clone(),
+        });
+    }
+    removed.clear();
+    added.clear();
+}
+
+/// Key paths a project sets differently from the base config, outermost only
+///
+/// Keys missing on the project side are inherited, so plain removals don't
+/// count. Element diffs such as `allowedPaths[1]` (removals included)
+/// collapse to their array, since arrays replace on merge. MCP servers and
+/// skills replace on merge too, so any difference in one, including a field
+/// the project leaves out, lists the whole entry (e.g. `mcpServers.fetch`).
+/// Other objects merge key by key, so a modified object is narrowed down to
+/// the keys that differ, and paths below an already listed path are dropped.
+///
+/// # Arguments
+/// * `diffs` - Differences from [`diff_values`] (base on the left)
+pub fn overridden_key_paths(diffs: &[ConfigDiff]) -> Vec<String> {
+    fn collect(diffs: &[ConfigDiff], prefix: &str, paths: &mut Vec<String>) {
+        for diff in diffs {
+            let key_path = diff.key_path();
+            match (key_path.split_once('['), diff) {
+                (Some((array, _)), _) => paths.push(child_path(prefix, array)),
+                (
+                    None,
+                    ConfigDiff::Modified {
+                        old_value: old @ Value::Object(_),
+                        new_value: new @ Value::Object(_),
+                        ..
+                    },
+                ) if !ENTRY_REPLACE_FIELDS.contains(&prefix) => {
+                    let (nested, _) = diff_values(old, new);
+                    collect(&nested, &child_path(prefix, key_path), paths);
+                }
+                (None, ConfigDiff::Removed { .. }) => {}
+                (None, _) => paths.push(child_path(prefix, key_path)),
+            }
+        }
+    }
+
+    let mut paths = Vec::new();
+    collect(diffs, "", &mut paths);
+    paths.sort_unstable();
+    paths.dedup();
+
+    // Sorted, so a path comes right after its ancestors
+    let mut outermost: Vec<String> = Vec::new();
+    for path in paths {
+        let nested = outermost.iter().any(|parent| {
+            path.strip_prefix(parent.as_str())
+                .is_some_and(|rest| rest.starts_with('.'))
+        });
+        if !nested {
+            outermost.push(path);
+        }
+    }
+    outermost
+}
+
+/// Join a key onto a key path
+fn child_path(key_path: &str, key: &str) -> String {
+    if key_path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{key_path}.{key}")
+    }
+}
+
+/// Compare keys present on the left with the right side
+fn compare_values(
+    left: &Value,
+    right: &Value,
+    key_path: &str,
+    diffs: &mut Vec<ConfigDiff>,
+    source_map: &mut SourceMap,
+) {
+    match (left, right) {
+        (Value::Object(left_map), Value::Object(right_map)) => {
+            for (key, left_value) in left_map {
+                let new_key_path = child_path(key_path, key);
+
+                match right_map.get(key) {
+                    Some(right_value) if left_value == right_value => {
+                        source_map.insert(new_key_path, ConfigScope::Global);
+                    }
+                    Some(right_value) if left_value.is_array() && right_value.is_array() => {
+                        compare_values(left_value, right_value, &new_key_path, diffs, source_map);
+                        // Arrays are replaced wholesale when merging
+                        source_map.insert(new_key_path, ConfigScope::Project);
+                    }
+                    Some(right_value) => {
+                        diffs.push(ConfigDiff::Modified {
+                            key_path: new_key_path.clone(),
+                            old_value: left_value.clone(),
+                            new_value: right_value.clone(),
+                        });
+                        source_map.insert(new_key_path, ConfigScope::Project);
+                    }
+                    None => {
+                        diffs.push(ConfigDiff::Removed {
+                            key_path: new_key_path.clone(),
+                            value: left_value.clone(),
+                        });
+                        source_map.insert(new_key_path, ConfigScope::Global);
+                    }
+                }
+            }
+        }
+        (Value::Array(left_arr), Value::Array(right_arr)) => {
+            diffs.extend(diff_arrays(key_path, left_arr, right_arr));
+        }
+        _ => {
+            if left != right {
+                diffs.push(ConfigDiff::Modified {
+                    key_path: key_path.to_string(),
+                    old_value: left.clone(),
+                    new_value: right.clone(),
+                });
+            }
+        }
+    }
+}
+
+/// Find keys that only exist on the right side (additions)
+fn find_additions(
+    left: &Value,
+    right: &Value,
+    key_path: &str,
+    diffs: &mut Vec<ConfigDiff>,
+    source_map: &mut SourceMap,
+) {
+    if let (Value::Object(left_map), Value::Object(right_map)) = (left, right) {
+        for (key, right_value) in right_map {
+            if left_map.contains_key(key) {
+                continue;
+            }
+
+            let new_key_path = child_path(key_path, key);
+            diffs.push(ConfigDiff::Added {
+                key_path: new_key_path.clone(),
+                value: right_value.clone(),
+            });
+            source_map.insert(new_key_path.clone(), ConfigScope::Project);
+
+            // Record the nested keys of added objects as well
+            if let Value::Object(_) = right_value {
+                let empty = Value::Object(Default::default());
+                find_additions(&empty, right_value, &new_key_path, diffs, source_map);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    // TDD Test 1: Adding one path to a list reports only that element
+    #[test]
+    fn test_added_allowed_path_is_single_element_diff() {
+        let paths = ["~/a", "~/b", "~/c", "~/d", "~/e"];
+        let left = json!({"allowedPaths": paths});
+        let right = json!({"allowedPaths": ["~/a", "~/b", "~/new", "~/c", "~/d", "~/e"]});
+
+        let (diffs, source_map) = diff_values(&left, &right);
+
+        assert_eq!(
+            diffs,
+            vec![ConfigDiff::Added {
+                key_path: "allowedPaths[2]".to_string(),
+                value: json!("~/new"),
+            }]
+        );
+        assert_eq!(source_map.get("allowedPaths"), Some(&ConfigScope::Project));
+    }
+
+    // TDD Test 2: Order-independent arrays ignore reordering and count duplicates
+    #[test]
+    fn test_order_independent_arrays_match_by_value() {
+        let left = json!({"allowedPaths": ["~/a", "~/b", "~/a"]});
+        let right = json!({"allowedPaths": ["~/b", "~/a", "~/c"]});
+
+        let (diffs, _) = diff_values(&left, &right);
+
+        assert_eq!(
+            diffs,
+            vec![
+                ConfigDiff::Removed {
+                    key_path: "allowedPaths[2]".to_string(),
+                    value: json!("~/a"),
+                },
+                ConfigDiff::Added {
+                    key_path: "allowedPaths[2]".to_string(),
+                    value: json!("~/c"),
+                },
+            ]
+        );
+    }
+
+    // TDD Test 3: Ordered arrays pair up elements replaced in place
+    #[test]
+    fn test_ordered_arrays_compare_by_position() {
+        let left = json!({"customInstructions": ["one", "two", "three"]});
+        let right = json!({"customInstructions": ["one", "TWO"]});
+
+        let (diffs, _) = diff_values(&left, &right);
+
+        assert_eq!(
+            diffs,
+            vec![
+                ConfigDiff::Modified {
+                    key_path: "customInstructions[1]".to_string(),
+                    old_value: json!("two"),
+                    new_value: json!("TWO"),
+                },
+                ConfigDiff::Removed {
+                    key_path: "customInstructions[2]".to_string(),
+                    value: json!("three"),
+                },
+            ]
+        );
+    }
+
+    // TDD Test 4: Overrides collapse to outermost paths and skip inherited keys
+    #[test]
+    fn test_overridden_key_paths() {
+        let left = json!({
+            "allowedPaths": ["~/a", "~/b"],
+            "customInstructions": ["Be brief"],
+            "mcpServers": {
+                "fetch": {"command": "uvx", "args": ["fetch"]},
+                "git": {"command": "npx", "env": {"DEBUG": "1"}},
+                "old": {"command": "old"}
+            },
+            "skills": {"review": {"enabled": true, "parameters": {"depth": 2}}},
+            "telemetry": {"level": "info", "endpoint": "https://x"}
+        });
+        let right = json!({
+            "allowedPaths": ["~/a"],
+            "mcpServers": {
+                "fetch": {"command": "uvx", "args": ["fetch", "--verbose"]},
+                "git": {"command": "npx"},
+                "db": {"command": "db", "args": []}
+            },
+            "skills": {"review": {"enabled": true, "parameters": {"depth": 3}}},
+            "telemetry": {"level": "debug"}
+        });
+
+        let (diffs, _) = diff_values(&left, &right);
+
+        // Servers and skills are overridden whole, even by a missing field;
+        // a missing server or other object key is inherited
+        assert_eq!(
+            overridden_key_paths(&diffs),
+            [
+                "allowedPaths",
+                "mcpServers.db",
+                "mcpServers.fetch",
+                "mcpServers.git",
+                "skills.review",
+                "telemetry.level"
+            ]
+        );
+    }
+
+    // TDD Test 5: Inserting into an ordered array reports only the new element
+    #[test]
+    fn test_ordered_array_insertion_is_single_element_diff() {
+        let left = json!({"customInstructions": ["one", "two", "three"]});
+        let right = json!({"customInstructions": ["zero", "one", "two", "three"]});
+
+        let (diffs, _) = diff_values(&left, &right);
+
+        assert_eq!(
+            diffs,
+            vec![ConfigDiff::Added {
+                key_path: "customInstructions[0]".to_string(),
+                value: json!("zero"),
+            }]
+        );
+
+        let old = [json!("--port"), json!("80"), json!("--verbose")];
+        let new = [json!("--verbose")];
+        assert_eq!(
+            diff_arrays("mcpServers.fetch.args", &old, &new),
+            vec![
+                ConfigDiff::Removed {
+                    key_path: "mcpServers.fetch.args[0]".to_string(),
+                    value: json!("--port"),
+                },
+                ConfigDiff::Removed {
+                    key_path: "mcpServers.fetch.args[1]".to_string(),
+                    value: json!("80"),
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,151 @@
+//! Hand-editing a config file with a safety net
+//!
+//! An [`EditSession`] wraps an edit made outside ccm (e.g. in the user's
+//! editor): it backs the file up before the edit, then re-reads and
+//! validates it afterwards. If the edited file is broken, the pre-edit
+//! backup can be put back. Launching the editor is up to the caller.
+
+use crate::{
+    config::{
+        manager::{atomic_write, read_config_file, ConfigManager},
+        validation::validate_config,
+        ClaudeConfig,
+    },
+    error::{ConfigError, Result},
+};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A config file being edited by hand
+#[derive(Debug, Clone)]
+pub struct EditSession {
+    /// Config file being edited
+    path: PathBuf,
+    /// Copy of the file taken before the edit
+    backup: PathBuf,
+    /// Whether the session created the file
+    created: bool,
+}
+
+impl EditSession {
+    /// Prepare `path` for editing
+    ///
+    /// Creates the file as an empty config if it doesn't exist, then backs
+    /// it up with the manager's backup manager.
+    ///
+    /// # Errors
+    /// Returns an error if the file can't be created or backed up
+    pub fn begin(manager: &ConfigManager, path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let created = manager.ensure_config(&path)?;
+
+        let backup = manager.backup_manager().create_backup(&path)?;
+        tracing::debug!("Backed up {} before editing", path.display());
+
+        Ok(Self {
+            path,
+            backup,
+            created,
+        })
+    }
+
+    /// Config file being edited
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Backup holding the pre-edit content
+    pub fn backup(&self) -> &Path {
+        &self.backup
+    }
+
+    /// Whether the file didn't exist before the session
+    pub fn was_created(&self) -> bool {
+        self.created
+    }
+
+    /// Whether the file differs from its pre-edit content
+    pub fn is_changed(&self) -> Result<bool> {
+        let before = fs::read(&self.backup)
+            .map_err(|e| ConfigError::filesystem("read backup", &self.backup, e))?;
+        let after = fs::read(&self.path)
+            .map_err(|e| ConfigError::filesystem("read config file", &self.path, e))?;
+        Ok(before != after)
+    }
+
+    /// Re-read and validate the edited file
+    ///
+    /// # Errors
+    /// Returns the parse or validation error if the edit broke the config
+    pub fn finish(&self) -> Result<ClaudeConfig> {
+        let config = read_config_file(&self.path)?;
+        validate_config(&config)?;
+        Ok(config)
+    }
+
+    /// Put the pre-edit content back
+    pub fn restore(&self) -> Result<()> {
+        let content = fs::read_to_string(&self.backup)
+            .map_err(|e| ConfigError::filesystem("read backup", &self.backup, e))?;
+        atomic_write(&self.path, &content)?;
+        tracing::debug!(
+            "Restored {} from {}",
+            self.path.display(),
+            self.backup.display()
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    // TDD Test 1: A valid edit is accepted
+    #[test]
+    fn test_valid_edit_is_accepted() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = ConfigManager::new(temp_dir.path().join("backups"));
+        let path = temp_dir.path().join("config.json");
+        fs::write(&path, r#"{"allowedPaths": ["~/old"]}"#).unwrap();
+
+        let session = EditSession::begin(&manager, &path).unwrap();
+        assert!(!session.was_created());
+        assert!(!session.is_changed().unwrap());
+
+        fs::write(&path, r#"{"allowedPaths": ["~/new"]}"#).unwrap();
+
+        assert!(session.is_changed().unwrap());
+        assert_eq!(session.finish().unwrap().allowed_paths.unwrap(), ["~/new"]);
+        assert_eq!(
+            fs::read_to_string(session.backup()).unwrap(),
+            r#"{"allowedPaths": ["~/old"]}"#
+        );
+    }
+
+    // TDD Test 2: A broken edit is reported and can be rolled back
+    #[test]
+    fn test_invalid_edit_can_be_restored() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = ConfigManager::new(temp_dir.path().join("backups"));
+        let path = temp_dir.path().join("claude").join("config.json");
+
+        let session = EditSession::begin(&manager, &path).unwrap();
+        assert!(session.was_created());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "{}");
+
+        fs::write(&path, r#"{"allowedPaths": ["#).unwrap();
+        assert!(matches!(
+            session.finish(),
+            Err(ConfigError::InvalidFormat { .. })
+        ));
+
+        fs::write(&path, r#"{"allowedPaths": [""]}"#).unwrap();
+        assert!(session.finish().is_err());
+
+        session.restore().unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "{}");
+        assert!(session.finish().is_ok());
+    }
+}
@@ -0,0 +1,519 @@
+//! Configs embedded in project manifests
+//!
+//! Some projects keep their Claude settings inside an existing manifest
+//! instead of `.claude/config.json`:
+//! - `package.json`: a top-level `"claude"` key
+//! - `pyproject.toml`: a `[tool.claude]` table
+//!
+//! Embedded configs are read-only. They have lower precedence than a real
+//! `.claude/config.json` and can be migrated into one.
+
+use crate::{
+    config::{
+        json_hints,
+        manager::atomic_write,
+        normalize::{canonical_json, restore_names},
+        ClaudeConfig,
+    },
+    error::{ConfigError, Result},
+    import_export, validate_config,
+};
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Top-level key holding the config in package.json
+const PACKAGE_JSON_KEY: &str = "claude";
+
+/// Manifest format holding an embedded config
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ManifestKind {
+    /// `package.json` with a `"claude"` key
+    PackageJson,
+    /// `pyproject.toml` with a `[tool.claude]` table
+    PyProject,
+}
+
+impl ManifestKind {
+    /// Manifest file name
+    pub fn file_name(self) -> &'static str {
+        match self {
+            ManifestKind::PackageJson => "package.json",
+            ManifestKind::PyProject => "pyproject.toml",
+        }
+    }
+
+    /// Description of the section holding the config
+    pub fn section(self) -> &'static str {
+        match self {
+            ManifestKind::PackageJson => "\"claude\" key",
+            ManifestKind::PyProject => "[tool.claude] table",
+        }
+    }
+}
+
+/// A config embedded in a project manifest
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EmbeddedConfig {
+    /// Path to the manifest file
+    pub path: PathBuf,
+
+    /// Manifest format
+    pub kind: ManifestKind,
+}
+
+impl EmbeddedConfig {
+    /// The embedded config a manifest path refers to, if it names a manifest
+    ///
+    /// Only the file name is checked; the manifest isn't read.
+    pub fn from_manifest_path(path: &Path) -> Option<Self> {
+        let name = path.file_name()?;
+        [ManifestKind::PackageJson, ManifestKind::PyProject]
+            .into_iter()
+            .find(|kind| name == kind.file_name())
+            .map(|kind| EmbeddedConfig {
+                path: path.to_path_buf(),
+                kind,
+            })
+    }
+
+    /// Human-readable location, e.g. `package.json "claude" key`
+    pub fn describe(&self) -> String {
+        format!("{} {}", self.path.display(), self.kind.section())
+    }
+}
+
+/// Find a config embedded in a manifest in `dir`
+///
+/// `package.json` is checked before `pyproject.toml`. Manifests that can't
+/// be parsed are ignored.
+pub fn find_embedded_config(dir: &Path) -> Option<EmbeddedConfig> {
+    [ManifestKind::PackageJson, ManifestKind::PyProject]
+        .into_iter()
+        .map(|kind| EmbeddedConfig {
+            path: dir.join(kind.file_name()),
+            kind,
+        })
+        .find(|embedded| {
+            embedded.path.is_file() && matches!(extract_section(embedded), Ok(Some(_)))
+        })
+}
+
+/// Read an embedded config
+///
+/// # Errors
+/// Returns an error if the manifest cannot be read or parsed, has no
+/// config section, or the section is not a valid configuration
+pub fn read_embedded_config(embedded: &EmbeddedConfig) -> Result<ClaudeConfig> {
+    let section = extract_section(embedded)?.ok_or_else(|| {
+        ConfigError::validation_failed(
+            "EmbeddedConfig",
+            format!(
+                "{} has no {}",
+                embedded.path.display(),
+                embedded.kind.section()
+            ),
+            "Add the section or use .claude/config.json instead",
+        )
+    })?;
+
+    let mut config: ClaudeConfig = serde_json::from_value(section).map_err(|e| {
+        ConfigError::validation_failed(
+            "EmbeddedConfig",
+            format!("Invalid config in {}: {e}", embedded.describe()),
+            "Fix the embedded section so it matches the config.json format",
+        )
+    })?;
+
+    restore_names(&mut config);
+
+    tracing::debug!(
+        "Loaded embedded configuration from: {}",
+        embedded.describe()
+    );
+
+    Ok(config)
+}
+
+/// Create `.claude/config.json` from a project's embedded config
+///
+/// # Arguments
+/// * `project_dir` - Project root containing the manifest
+/// * `remove` - Also remove the section from the manifest
+///
+/// # Returns
+/// The manifest the config was taken from and the created config path
+///
+/// # Errors
+/// Returns an error if the project has no embedded config, already has a
+/// `.claude/config.json`, or a file cannot be read or written
+pub fn migrate_embedded(project_dir: &Path, remove: bool) -> Result<(EmbeddedConfig, PathBuf)> {
+    let embedded = find_embedded_config(project_dir).ok_or_else(|| {
+        ConfigError::validation_failed(
+            "EmbeddedConfig",
+            format!("No embedded config found in {}", project_dir.display()),
+            "Embedded configs live in package.json (\"claude\" key) or pyproject.toml ([tool.claude])",
+        )
+    })?;
+
+    let config_path = crate::paths::project_config_path(project_dir);
+    if config_path.exists() {
+        return Err(ConfigError::validation_failed(
+            "EmbeddedConfig",
+            format!("{} already exists", config_path.display()),
+            "Merge the embedded section into it by hand, or remove it first",
+        ));
+    }
+
+    let config = read_embedded_config(&embedded)?;
+    validate_config(&config)?;
+    atomic_write(&config_path, &canonical_json(&config)?)?;
+
+    if remove {
+        remove_embedded_section(&embedded)?;
+    }
+
+    tracing::info!(
+        "Migrated embedded config {} -> {}",
+        embedded.describe(),
+        config_path.display()
+    );
+
+    Ok((embedded, config_path))
+}
+
+/// Remove the config section from a manifest
+///
+/// The rest of the manifest (key order, formatting, comments) is kept.
+///
+/// # Errors
+/// Returns an error if the manifest cannot be read, parsed or written
+pub fn remove_embedded_section(embedded: &EmbeddedConfig) -> Result<()> {
+    let content = read_manifest(embedded)?;
+
+    let updated = match embedded.kind {
+        ManifestKind::PackageJson => remove_json_key(&content, PACKAGE_JSON_KEY),
+        ManifestKind::PyProject => {
+            let mut doc = parse_toml(embedded, &content)?;
+            let removed = doc
+                .get_mut("tool")
+                .and_then(|tool| tool.as_table_like_mut())
+                .and_then(|tool| tool.remove("claude"));
+            removed.map(|_| doc.to_string())
+        }
+    };
+
+    match updated {
+        Some(updated) => atomic_write(&embedded.path, &updated),
+        None => Ok(()),
+    }
+}
+
+/// Extract the config section of a manifest as JSON
+fn extract_section(embedded: &EmbeddedConfig) -> Result<Option<Value>> {
+    let content = read_manifest(embedded)?;
+
+    match embedded.kind {
+        ManifestKind::PackageJson => {
+            let mut manifest: Value = serde_json::from_str(&content).map_err(|e| {
+                let error =
+                    ConfigError::invalid_json(&embedded.path, e.line(), e.column(), e.to_string());
+                match json_hints::suggest_fix(content.as_bytes(), e.line(), e.column()) {
+                    Some(hint) => error.with_suggestion(hint),
+                    None => error,
+                }
+            })?;
+            Ok(manifest
+                .as_object_mut()
+                .and_then(|root| root.remove(PACKAGE_JSON_KEY)))
+        }
+        ManifestKind::PyProject => {
+            let doc = parse_toml(embedded, &content)?;
+            Ok(doc
+                .get("tool")
+                .and_then(|tool| tool.get("claude"))
+                .and_then(toml_item_to_json))
+        }
+    }
+}
+
+/// Read a manifest file
+fn read_manifest(embedded: &EmbeddedConfig) -> Result<String> {
+    fs::read_to_string(&embedded.path)
+        .map_err(|e| ConfigError::filesystem("read manifest", &embedded.path, e))
+}
+
+/// Parse a TOML manifest
+fn parse_toml(embedded: &EmbeddedConfig, content: &str) -> Result<toml_edit::DocumentMut> {
+    content
+        .parse()
+        .map_err(|e| import_export::toml_error(&embedded.path, content, &e))
+}
+
+/// Convert a TOML item into JSON
+pub(crate) fn toml_item_to_json(item: &toml_edit::Item) -> Option<Value> {
+    match item {
+        toml_edit::Item::None => None,
+        toml_edit::Item::Value(value) => Some(toml_value_to_json(value)),
+        toml_edit::Item::Table(table) => Some(Value::Object(
+            table
+                .iter()
+                .filter_map(|(key, item)| Some((key.to_string(), toml_item_to_json(item)?)))
+                .collect(),
+        )),
+        toml_edit::Item::ArrayOfTables(tables) => Some(Value::Array(
+            tables
+                .iter()
+                .filter_map(|table| toml_item_to_json(&toml_edit::Item::Table(table.clone())))
+                .collect(),
+        )),
+    }
+}
+
+/// Convert a TOML value into JSON
+fn toml_value_to_json(value: &toml_edit::Value) -> Value {
+    match value {
+        toml_edit::Value::String(s) => Value::String(s.value().clone()),
+        toml_edit::Value::Integer(i) => Value::from(*i.value()),
+        toml_edit::Value::Float(f) => Value::from(*f.value()),
+        toml_edit::Value::Boolean(b) => Value::Bool(*b.value()),
+        toml_edit::Value::Datetime(d) => Value::String(d.value().to_string()),
+        toml_edit::Value::Array(array) => {
+            Value::Array(array.iter().map(toml_value_to_json).collect())
+        }
+        toml_edit::Value::InlineTable(table) => Value::Object(
+            table
+                .iter()
+                .map(|(key, value)| (key.to_string(), toml_value_to_json(value)))
+                .collect(),
+        ),
+    }
+}
+
+/// Remove a top-level key from a JSON object, keeping the rest of the text
+///
+/// Returns None if the key is not present.
+fn remove_json_key(text: &str, key: &str) -> Option<String> {
+    let bytes = text.as_bytes();
+    let mut depth = 0;
+    let mut i = 0;
+    // Index of the last structural '{' or ',' seen at depth 1
+    let mut last_separator = None;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'"' => {
+                let start = i;
+                i = string_end(bytes, i)?;
+                let is_key = depth == 1 && next_non_ws(bytes, i) == Some(b':');
+                if is_key && serde_json::from_str::<String>(&text[start..i]).ok()? == key {
+                    let value_end = value_end(bytes, next_non_ws_index(bytes, i)? + 1)?;
+                    let separator = last_separator?;
+
+                    return Some(if bytes[value_end] == b',' {
+                        // Not the last member: drop the key and its trailing comma
+                        format!("{}{}", &text[..separator + 1], &text[value_end + 1..])
+                    } else if bytes[separator] == b',' {
+                        // Last member: drop the comma before it
+                        format!("{}{}", &text[..separator], &text[value_end..])
+                    } else {
+                        // Only member
+                        format!("{}{}", &text[..separator + 1], &text[value_end..])
+                    });
+                }
+                continue;
+            }
+            b'{' | b'[' => {
+                depth += 1;
+                if depth == 1 {
+                    last_separator = Some(i);
+                }
+            }
+            b'}' | b']' => depth -= 1,
+            b',' if depth == 1 => last_separator = Some(i),
+            _ => {}
+        }
+        i += 1;
+    }
+
+    None
+}
+
+/// Index just past the string starting at `start`
+fn string_end(bytes: &[u8], start: usize) -> Option<usize> {
+    let mut i = start + 1;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 2,
+            b'"' => return Some(i + 1),
+            _ => i += 1,
+        }
+    }
+    None
+}
+
+/// Index of the ',' or '}' ending the member value starting at `start`
+fn value_end(bytes: &[u8], start: usize) -> Option<usize> {
+    let mut depth = 0;
+    let mut i = start;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'"' => {
+                i = string_end(bytes, i)?;
+                continue;
+            }
+            b'{' | b'[' => depth += 1,
+            b'}' | b']' if depth > 0 => depth -= 1,
+            b',' | b'}' if depth == 0 => return Some(i),
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+/// Index of the next non-whitespace byte at or after `start`
+fn next_non_ws_index(bytes: &[u8], start: usize) -> Option<usize> {
+    (start..bytes.len()).find(|&i| !bytes[i].is_ascii_whitespace())
+}
+
+/// The next non-whitespace byte at or after `start`
+fn next_non_ws(bytes: &[u8], start: usize) -> Option<u8> {
+    next_non_ws_index(bytes, start).map(|i| bytes[i])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const PACKAGE_JSON: &str = r#"{
+  "name": "web",
+  "claude": {
+    "mcpServers": { "fetch": { "enabled": true, "command": "uvx", "args": ["mcp-server-fetch"] } },
+    "allowedPaths": ["./src"]
+  },
+  "version": "1.0.0"
+}"#;
+
+    const PYPROJECT: &str = r#"[project]
+name = "svc"
+
+# Claude settings
+[tool.claude]
+allowedPaths = ["./src"]
+
+[tool.claude.mcpServers.fetch]
+enabled = true
+command = "uvx"
+args = ["mcp-server-fetch"]
+
+[tool.ruff]
+line-length = 100
+"#;
+
+    // TDD Test 1: Config is read from package.json
+    #[test]
+    fn test_read_package_json_config() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("package.json"), PACKAGE_JSON).unwrap();
+
+        let embedded = find_embedded_config(temp_dir.path()).unwrap();
+        let config = read_embedded_config(&embedded).unwrap();
+
+        assert_eq!(embedded.kind, ManifestKind::PackageJson);
+        assert_eq!(config.mcp_servers.unwrap()["fetch"].name, "fetch");
+        assert_eq!(config.allowed_paths, Some(vec!["./src".to_string()]));
+    }
+
+    // TDD Test 2: Config is read from pyproject.toml
+    #[test]
+    fn test_read_pyproject_config() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("pyproject.toml"), PYPROJECT).unwrap();
+
+        let embedded = find_embedded_config(temp_dir.path()).unwrap();
+        let config = read_embedded_config(&embedded).unwrap();
+
+        assert_eq!(embedded.kind, ManifestKind::PyProject);
+        let servers = config.mcp_servers.unwrap();
+        assert_eq!(servers["fetch"].command.as_deref(), Some("uvx"));
+        assert_eq!(servers["fetch"].args, vec!["mcp-server-fetch"]);
+        assert_eq!(config.allowed_paths, Some(vec!["./src".to_string()]));
+    }
+
+    // TDD Test 3: Manifests without a config section are ignored
+    #[test]
+    fn test_manifest_without_section_is_ignored() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("package.json"), r#"{"name": "x"}"#).unwrap();
+        fs::write(temp_dir.path().join("pyproject.toml"), "[tool.ruff]\n").unwrap();
+
+        assert!(find_embedded_config(temp_dir.path()).is_none());
+    }
+
+    // TDD Test 4: Removing the section keeps the rest of the manifest
+    #[test]
+    fn test_remove_embedded_section() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("package.json"), PACKAGE_JSON).unwrap();
+        fs::write(temp_dir.path().join("pyproject.toml"), PYPROJECT).unwrap();
+
+        for kind in [ManifestKind::PackageJson, ManifestKind::PyProject] {
+            let embedded = EmbeddedConfig {
+                path: temp_dir.path().join(kind.file_name()),
+                kind,
+            };
+            remove_embedded_section(&embedded).unwrap();
+        }
+
+        let package = fs::read_to_string(temp_dir.path().join("package.json")).unwrap();
+        assert_eq!(
+            package,
+            "{\n  \"name\": \"web\",\n  \"version\": \"1.0.0\"\n}"
+        );
+        let pyproject = fs::read_to_string(temp_dir.path().join("pyproject.toml")).unwrap();
+        assert!(!pyproject.contains("claude"));
+        assert!(pyproject.contains("[tool.ruff]\nline-length = 100"));
+        assert!(find_embedded_config(temp_dir.path()).is_none());
+    }
+
+    // TDD Test 5: JSON key removal handles first, last and only members
+    #[test]
+    fn test_remove_json_key_positions() {
+        assert_eq!(
+            remove_json_key(r#"{"claude": {"a": [1, "}"]}, "b": 2}"#, "claude").unwrap(),
+            r#"{ "b": 2}"#
+        );
+        assert_eq!(
+            remove_json_key(r#"{"a": 1, "claude": "x"}"#, "claude").unwrap(),
+            r#"{"a": 1}"#
+        );
+        assert_eq!(
+            remove_json_key(r#"{"claude": {}}"#, "claude").unwrap(),
+            "{}"
+        );
+        assert!(remove_json_key(r#"{"a": {"claude": 1}}"#, "claude").is_none());
+    }
+
+    // TDD Test 6: Migration creates .claude/config.json and optionally removes the section
+    #[test]
+    fn test_migrate_embedded() {
+        let temp_dir = TempDir::new().unwrap();
+        let manifest = temp_dir.path().join("pyproject.toml");
+        fs::write(&manifest, PYPROJECT).unwrap();
+
+        let (embedded, config_path) = migrate_embedded(temp_dir.path(), true).unwrap();
+
+        assert_eq!(embedded.kind, ManifestKind::PyProject);
+        let content = fs::read_to_string(&config_path).unwrap();
+        let config: ClaudeConfig = serde_json::from_str(&content).unwrap();
+        assert!(config.mcp_servers.unwrap().contains_key("fetch"));
+        assert!(!fs::read_to_string(&manifest)
+            .unwrap()
+            .contains("tool.claude"));
+
+        // Nothing left to migrate
+        assert!(migrate_embedded(temp_dir.path(), false).is_err());
+    }
+}
@@ -0,0 +1,462 @@
+//! Config inheritance from a declared base
+//!
+//! The global config can name a base in its ccm section, such as a team
+//! baseline kept in a shared checkout:
+//!
+//! ```json
+//! { "ccm": { "extends": "~/team/claude-base.json" } }
+//! ```
+//!
+//! The merged views resolve the base first, so the effective order is
+//! base < global < project. A base may extend another base, up to
+//! [`MAX_EXTENDS_DEPTH`] bases in all. Relative paths are resolved against
+//! the directory of the file that declares them.
+//!
+//! A base that is missing or invalid doesn't stop the config from loading,
+//! since the user's own settings are intact: the chain ends there and
+//! [`BaseChain::warnings`] says why. A cycle or a chain deeper than the
+//! limit is an error, as the declarations themselves are wrong.
+//!
+//! A base can also be an `http://` or `https://` URL. Fetched bases are
+//! cached for [`REMOTE_CACHE_TTL`]; fetching needs the `http` feature, and
+//! a stale cached copy is used when a fetch fails.
+
+use crate::{
+    config::{
+        manager::{atomic_write, read_config_file},
+        merge::{merge_configs_with_options, MergeOptions},
+        validation::validate_config,
+        ClaudeConfig,
+    },
+    error::{ConfigError, Result},
+    hash::content_hash,
+    paths::{expand_env, expand_tilde},
+    settings::SETTINGS_KEY,
+};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Key of the base declaration in the ccm settings section
+pub const EXTENDS_KEY: &str = "extends";
+
+/// Most bases a chain may contain
+pub const MAX_EXTENDS_DEPTH: usize = 3;
+
+/// How long a fetched remote base is used before fetching it again
+pub const REMOTE_CACHE_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// One base in an extends chain
+#[derive(Debug, Clone, PartialEq)]
+pub struct BaseLink {
+    /// The `extends` value as written
+    pub declared: String,
+
+    /// The expanded path or URL it names
+    pub source: String,
+
+    /// File the base was read from (the cached copy for URLs)
+    pub path: PathBuf,
+
+    /// The base configuration, without its own `extends` declaration
+    pub config: ClaudeConfig,
+}
+
+impl BaseLink {
+    /// Whether the base was fetched from a URL
+    pub fn is_remote(&self) -> bool {
+        is_url(&self.source)
+    }
+}
+
+/// The bases a config inherits from
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BaseChain {
+    /// Bases, nearest first: the declared base, then the base it extends
+    pub links: Vec<BaseLink>,
+
+    /// Why the chain ended early (missing or invalid bases)
+    pub warnings: Vec<String>,
+}
+
+impl BaseChain {
+    /// Whether the config inherits nothing
+    pub fn is_empty(&self) -> bool {
+        self.links.is_empty()
+    }
+
+    /// Fold the bases into one config, the farthest base lowest
+    ///
+    /// # Returns
+    /// `None` if the chain has no bases
+    pub fn merged(&self, options: MergeOptions) -> Option<ClaudeConfig> {
+        let mut links = self.links.iter().rev();
+        let first = links.next()?.config.clone();
+        Some(links.fold(first, |merged, link| {
+            merge_configs_with_options(&merged, &link.config, options)
+        }))
+    }
+
+    /// Merge `config` over its bases
+    ///
+    /// Returns `config` unchanged if the chain has no bases.
+    pub fn apply(&self, config: &ClaudeConfig, options: MergeOptions) -> ClaudeConfig {
+        match self.merged(options) {
+            Some(base) => merge_configs_with_options(&base, config, options),
+            None => config.clone(),
+        }
+    }
+}
+
+/// The base a config declares in `ccm.extends`, if any
+pub fn declared_base(config: &ClaudeConfig) -> Option<String> {
+    config
+        .ccm_settings()
+        .extends
+        .filter(|base| !base.is_empty())
+}
+
+/// Resolves the bases a config extends
+#[derive(Debug, Clone, Default)]
+pub struct BaseResolver {
+    /// Where fetched remote bases are cached (`None` disables remote bases)
+    cache_dir: Option<PathBuf>,
+}
+
+impl BaseResolver {
+    /// Create a resolver for local bases only
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cache fetched remote bases in `cache_dir`
+    pub fn with_cache_dir(mut self, cache_dir: impl Into<PathBuf>) -> Self {
+        self.cache_dir = Some(cache_dir.into());
+        self
+    }
+
+    /// Resolve the chain of bases `config` extends
+    ///
+    /// # Arguments
+    /// * `config` - Config declaring the first base
+    /// * `config_path` - File `config` was read from; relative bases and
+    ///   cycles are resolved against it
+    ///
+    /// # Returns
+    /// The bases, nearest first, with a warning for a base that couldn't be
+    /// loaded (the chain ends before it)
+    ///
+    /// # Errors
+    /// Returns an error if a base extends a file already in the chain, or if
+    /// the chain is longer than [`MAX_EXTENDS_DEPTH`]
+    pub fn resolve(&self, config: &ClaudeConfig, config_path: &Path) -> Result<BaseChain> {
+        let mut chain = BaseChain::default();
+        let mut visited = vec![location_key(&config_path.to_string_lossy())];
+        let mut next = declared_base(config);
+        let mut declaring_dir = config_path.parent().map(Path::to_path_buf);
+
+        while let Some(declared) = next {
+            let source = expand_source(&declared, declaring_dir.as_deref());
+            let key = location_key(&source);
+            if visited.contains(&key) {
+                let mut cycle = visited.clone();
+                cycle.push(key);
+                return Err(ConfigError::validation_failed(
+                    "Extends",
+                    format!("'ccm.extends' forms a cycle: {}", cycle.join(" -> ")),
+                    "Remove 'extends' from one of the files in the cycle",
+                ));
+            }
+            if chain.links.len() == MAX_EXTENDS_DEPTH {
+                return Err(ConfigError::validation_failed(
+                    "Extends",
+                    format!(
+                        "'ccm.extends' chain is longer than {MAX_EXTENDS_DEPTH} bases (at '{declared}')"
+                    ),
+                    "Flatten the chain by merging some of the bases",
+                ));
+            }
+            visited.push(key);
+
+            let (path, mut base) = match self.load(&source) {
+                Ok(loaded) => loaded,
+                Err(e) => {
+                    chain
+                        .warnings
+                        .push(format!("base '{declared}' was not loaded: {e}"));
+                    break;
+                }
+            };
+
+            next = declared_base(&base);
+            declaring_dir = if is_url(&source) {
+                None
+            } else {
+                path.parent().map(Path::to_path_buf)
+            };
+            remove_extends(&mut base);
+            chain.links.push(BaseLink {
+                declared,
+                source,
+                path,
+                config: base,
+            });
+        }
+
+        Ok(chain)
+    }
+
+    /// Load and validate one base
+    ///
+    /// # Returns
+    /// The file the base was read from, and the base
+    fn load(&self, source: &str) -> Result<(PathBuf, ClaudeConfig)> {
+        if !is_url(source) {
+            let path = PathBuf::from(source);
+            let config = read_config_file(&path)?;
+            validate_config(&config)?;
+            return Ok((path, config));
+        }
+
+        let cache_dir = self.cache_dir.as_ref().ok_or_else(|| {
+            ConfigError::Generic("remote bases need a cache directory".to_string())
+        })?;
+        let path = cache_dir.join(format!("{}.json", content_hash(source.as_bytes())));
+
+        if !is_fresh(&path) {
+            match fetch(source) {
+                Ok(content) => {
+                    // Check the content before replacing a good cached copy
+                    parse_base(&content, source)?;
+                    atomic_write(&path, &content)?;
+                }
+                Err(e) if path.exists() => {
+                    tracing::warn!("Using the cached copy of base '{source}': {e}");
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        let content = fs::read_to_string(&path)
+            .map_err(|e| ConfigError::filesystem("read cached base", &path, e))?;
+        Ok((path, parse_base(&content, source)?))
+    }
+}
+
+/// Parse and validate the text of a remote base
+fn parse_base(content: &str, source: &str) -> Result<ClaudeConfig> {
+    let config: ClaudeConfig = serde_json::from_str(content)
+        .map_err(|e| ConfigError::Generic(format!("invalid JSON from {source}: {e}")))?;
+    validate_config(&config)?;
+    Ok(config)
+}
+
+/// Fetch a remote base
+#[cfg(feature = "http")]
+fn fetch(url: &str) -> Result<String> {
+    ureq::get(url)
+        .timeout(Duration::from_secs(10))
+        .call()
+        .map_err(|e| ConfigError::Generic(format!("cannot fetch {url}: {e}")))?
+        .into_string()
+        .map_err(|e| ConfigError::Generic(format!("cannot read {url}: {e}")))
+}
+
+/// Fetch a remote base
+#[cfg(not(feature = "http"))]
+fn fetch(url: &str) -> Result<String> {
+    Err(ConfigError::Generic(format!(
+        "cannot fetch {url}: remote bases need ccm built with the 'http' feature"
+    )))
+}
+
+/// Whether a cached copy exists and is younger than [`REMOTE_CACHE_TTL`]
+fn is_fresh(path: &Path) -> bool {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .and_then(|modified| modified.elapsed().ok())
+        .is_some_and(|age| age < REMOTE_CACHE_TTL)
+}
+
+/// Whether a base names a URL rather than a file
+fn is_url(source: &str) -> bool {
+    source.starts_with("http://") || source.starts_with("https://")
+}
+
+/// Expand `~` and environment variables, resolving relative paths against `dir`
+fn expand_source(declared: &str, dir: Option<&Path>) -> String {
+    if is_url(declared) {
+        return declared.to_string();
+    }
+
+    let path = expand_tilde(Path::new(&expand_env(declared)));
+    match dir {
+        Some(dir) if path.is_relative() => dir.join(path),
+        _ => path,
+    }
+    .to_string_lossy()
+    .to_string()
+}
+
+/// Identity of a base for cycle detection (canonical path where possible)
+fn location_key(source: &str) -> String {
+    if is_url(source) {
+        return source.to_string();
+    }
+    fs::canonicalize(source)
+        .map(|path| path.to_string_lossy().to_string())
+        .unwrap_or_else(|_| source.to_string())
+}
+
+/// Drop a base's own `extends`, so it doesn't show up in merged views
+fn remove_extends(config: &mut ClaudeConfig) {
+    if let Some(serde_json::Value::Object(section)) = config.unknown.get_mut(SETTINGS_KEY) {
+        section.remove(EXTENDS_KEY);
+        if section.is_empty() {
+            config.unknown.remove(SETTINGS_KEY);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write(path: &Path, json: serde_json::Value) {
+        fs::write(path, json.to_string()).unwrap();
+    }
+
+    // TDD Test 1: A base lies below the config, chained bases below it
+    #[test]
+    fn test_chained_bases_merge_order() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path();
+        write(
+            &dir.join("org.json"),
+            serde_json::json!({"customInstructions": ["org"], "allowedPaths": ["~/org"]}),
+        );
+        write(
+            &dir.join("team.json"),
+            serde_json::json!({"ccm": {"extends": "org.json"}, "customInstructions": ["team"]}),
+        );
+        let global: ClaudeConfig = serde_json::from_value(serde_json::json!({
+            "ccm": {"extends": "./team.json"},
+            "allowedPaths": ["~/mine"]
+        }))
+        .unwrap();
+
+        let chain = BaseResolver::new()
+            .resolve(&global, &dir.join("config.json"))
+            .unwrap();
+        assert!(chain.warnings.is_empty());
+        let declared: Vec<&str> = chain.links.iter().map(|l| l.declared.as_str()).collect();
+        assert_eq!(declared, ["./team.json", "org.json"]);
+
+        let merged = chain.apply(&global, MergeOptions::default());
+        assert_eq!(merged.custom_instructions.as_deref().unwrap(), ["team"]);
+        assert_eq!(merged.allowed_paths.as_deref().unwrap(), ["~/mine"]);
+        // Bases don't leak their own declarations
+        assert_eq!(declared_base(&merged).as_deref(), Some("./team.json"));
+        assert!(chain.links[0].config.unknown.is_empty());
+    }
+
+    // TDD Test 2: Cycles and overlong chains are errors
+    #[test]
+    fn test_cycle_and_depth_errors() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path();
+        write(
+            &dir.join("a.json"),
+            serde_json::json!({"ccm": {"extends": "b.json"}}),
+        );
+        write(
+            &dir.join("b.json"),
+            serde_json::json!({"ccm": {"extends": "a.json"}}),
+        );
+        let config_path = dir.join("config.json");
+
+        let global: ClaudeConfig =
+            serde_json::from_value(serde_json::json!({"ccm": {"extends": "a.json"}})).unwrap();
+        let err = BaseResolver::new()
+            .resolve(&global, &config_path)
+            .unwrap_err();
+        assert!(err.to_string().contains("forms a cycle"));
+
+        // A base extending the declaring file is a cycle too
+        write(
+            &dir.join("b.json"),
+            serde_json::json!({"ccm": {"extends": "config.json"}}),
+        );
+        write(&config_path, serde_json::json!({}));
+        assert!(BaseResolver::new().resolve(&global, &config_path).is_err());
+
+        for (name, next) in [("a", "b"), ("b", "c"), ("c", "d")] {
+            write(
+                &dir.join(format!("{name}.json")),
+                serde_json::json!({"ccm": {"extends": format!("{next}.json")}}),
+            );
+        }
+        write(&dir.join("d.json"), serde_json::json!({}));
+        let err = BaseResolver::new()
+            .resolve(&global, &config_path)
+            .unwrap_err();
+        assert!(err.to_string().contains("longer than 3 bases"));
+    }
+
+    // TDD Test 3: Missing or invalid bases end the chain with a warning
+    #[test]
+    fn test_missing_and_invalid_bases_warn() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path();
+        let config_path = dir.join("config.json");
+
+        let global: ClaudeConfig =
+            serde_json::from_value(serde_json::json!({"ccm": {"extends": "missing.json"}}))
+                .unwrap();
+        let chain = BaseResolver::new().resolve(&global, &config_path).unwrap();
+        assert!(chain.is_empty());
+        assert_eq!(chain.warnings.len(), 1);
+        assert!(chain.warnings[0].starts_with("base 'missing.json' was not loaded"));
+
+        write(
+            &dir.join("team.json"),
+            serde_json::json!({"ccm": {"extends": "bad.json"}}),
+        );
+        fs::write(dir.join("bad.json"), "{ invalid").unwrap();
+        let global: ClaudeConfig =
+            serde_json::from_value(serde_json::json!({"ccm": {"extends": "team.json"}})).unwrap();
+        let chain = BaseResolver::new().resolve(&global, &config_path).unwrap();
+        assert_eq!(chain.links.len(), 1);
+        assert!(chain.warnings[0].contains("'bad.json'"));
+    }
+
+    // TDD Test 4: Fresh cached remote bases are used without fetching
+    #[test]
+    fn test_remote_base_from_cache() {
+        let temp_dir = TempDir::new().unwrap();
+        let cache_dir = temp_dir.path().join("bases");
+        let url = "https://example.com/claude-base.json";
+        fs::create_dir_all(&cache_dir).unwrap();
+        let cached = cache_dir.join(format!("{}.json", content_hash(url.as_bytes())));
+        write(&cached, serde_json::json!({"customInstructions": ["team"]}));
+
+        let global: ClaudeConfig =
+            serde_json::from_value(serde_json::json!({"ccm": {"extends": url}})).unwrap();
+        let chain = BaseResolver::new()
+            .with_cache_dir(&cache_dir)
+            .resolve(&global, &temp_dir.path().join("config.json"))
+            .unwrap();
+        assert!(chain.warnings.is_empty());
+        assert!(chain.links[0].is_remote());
+        assert_eq!(chain.links[0].path, cached);
+
+        // Without a cache directory, remote bases are skipped with a warning
+        let chain = BaseResolver::new()
+            .resolve(&global, &temp_dir.path().join("config.json"))
+            .unwrap();
+        assert!(chain.is_empty());
+        assert_eq!(chain.warnings.len(), 1);
+    }
+}
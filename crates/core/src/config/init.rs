@@ -0,0 +1,387 @@
+//! Creating a project config from a template
+//!
+//! A new `.claude/config.json` can start empty, or copy selected key paths
+//! (e.g. `mcpServers` or `mcpServers.fetch`, see [`key_path`]) from another
+//! project or from a profile. A profile is a config file saved as
+//! `profiles/<name>.json` next to the global config, by [`save_profile`].
+
+use crate::{
+    config::{key_path, manager::ConfigManager, ClaudeConfig},
+    error::{ConfigError, Result},
+    paths::{expand_env, expand_tilde},
+    types::ConfigScope,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Where a new project config takes its content from
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "value", rename_all = "lowercase")]
+pub enum ConfigSource {
+    /// Another project's config (the project root)
+    Project(PathBuf),
+
+    /// A saved profile, by name
+    Profile(String),
+
+    /// Nothing: the new config is empty
+    Empty,
+}
+
+/// Result of [`create_project_config`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct CreatedConfig {
+    /// The config file that was written
+    pub path: PathBuf,
+
+    /// The config that was written
+    pub config: ClaudeConfig,
+
+    /// Number of entries in each section of the new config
+    pub section_counts: BTreeMap<String, usize>,
+
+    /// Problems worth reviewing that didn't stop the config being created
+    pub warnings: Vec<String>,
+}
+
+/// Path of the profile called `name`
+///
+/// # Errors
+/// Returns an error if `name` is empty, starts with a dot or contains a path
+/// separator
+pub fn profile_path(manager: &ConfigManager, name: &str) -> Result<PathBuf> {
+    if name.is_empty() || name.contains(['/', '\\']) || name.starts_with('.') {
+        return Err(ConfigError::validation_failed(
+            "ProfileName",
+            format!("'{name}' is not a valid profile name"),
+            "Use a plain name such as 'work', without slashes",
+        ));
+    }
+    let global = manager.config_path_for_scope(&ConfigScope::Global, None)?;
+    let dir = global.parent().unwrap_or(Path::new("."));
+    Ok(dir.join("profiles").join(format!("{name}.json")))
+}
+
+/// Copy of `config` with only the given key paths
+///
+/// An empty `key_paths` list keeps everything.
+///
+/// # Returns
+/// The extracted config and the requested key paths that `config` doesn't
+/// have
+pub fn extract_key_paths(
+    config: &ClaudeConfig,
+    key_paths: &[String],
+) -> Result<(ClaudeConfig, Vec<String>)> {
+    if key_paths.is_empty() {
+        return Ok((config.clone(), Vec::new()));
+    }
+
+    let all = serde_json::to_value(config)?;
+    let mut kept = Value::Object(serde_json::Map::new());
+    let mut missing = Vec::new();
+    for path in key_paths {
+        match key_path::get(&all, path) {
+            Some(value) => key_path::set(&mut kept, path, value.clone()),
+            None => missing.push(path.clone()),
+        }
+    }
+
+    Ok((serde_json::from_value(kept)?, missing))
+}
+
+/// Save `config` as the profile called `name`
+///
+/// Only the given key paths are saved (empty: all). The result is validated
+/// before it's written.
+///
+/// # Returns
+/// The profile file and the requested key paths `config` doesn't have
+///
+/// # Errors
+/// Returns an error if the name is invalid, if the profile exists and
+/// `overwrite` is false, or if the result fails validation
+pub fn save_profile(
+    manager: &ConfigManager,
+    name: &str,
+    config: &ClaudeConfig,
+    key_paths: &[String],
+    overwrite: bool,
+) -> Result<(PathBuf, Vec<String>)> {
+    let path = profile_path(manager, name)?;
+    if path.exists() && !overwrite {
+        return Err(ConfigError::validation_failed(
+            "ProfileExists",
+            format!("Profile '{name}' already exists"),
+            "Choose another name, or overwrite the existing profile explicitly",
+        ));
+    }
+
+    let (profile, missing) = extract_key_paths(config, key_paths)?;
+    manager.write_config_with_backup(&path, &profile)?;
+    tracing::debug!("Saved profile '{name}': {}", path.display());
+
+    Ok((path, missing))
+}
+
+/// Number of entries in each section of `config`
+///
+/// Objects and arrays count their entries; any other value counts as one.
+pub fn section_counts(config: &ClaudeConfig) -> Result<BTreeMap<String, usize>> {
+    let Value::Object(sections) = serde_json::to_value(config)? else {
+        unreachable!("a config serializes to an object");
+    };
+    Ok(sections
+        .into_iter()
+        .map(|(name, value)| {
+            let count = match value {
+                Value::Object(map) => map.len(),
+                Value::Array(items) => items.len(),
+                _ => 1,
+            };
+            (name, count)
+        })
+        .collect())
+}
+
+/// Create `<project>/.claude/config.json` from `source`
+///
+/// # Arguments
+/// * `manager` - Manager used to read the source and write the new config
+/// * `project` - Root of the project to create the config in
+/// * `source` - Where the content comes from
+/// * `sections` - Key paths to copy (empty: all), e.g. `mcpServers`
+/// * `overwrite` - Replace an existing config (it is backed up first)
+///
+/// # Errors
+/// Returns an error if the config exists and `overwrite` is false, if the
+/// source can't be read, or if the result fails validation
+pub fn create_project_config(
+    manager: &ConfigManager,
+    project: &Path,
+    source: &ConfigSource,
+    sections: &[String],
+    overwrite: bool,
+) -> Result<CreatedConfig> {
+    let path = manager.config_path_for_scope(&ConfigScope::Project, Some(project))?;
+    if path.exists() && !overwrite {
+        return Err(ConfigError::validation_failed(
+            "ConfigExists",
+            format!("{} already exists", path.display()),
+            "Choose another project, or overwrite the existing config explicitly",
+        ));
+    }
+
+    let template = match source {
+        ConfigSource::Project(source_project) => manager
+            .get_project_config(Some(source_project))?
+            .ok_or_else(|| {
+                ConfigError::not_found(crate::paths::project_config_path(source_project))
+            })?,
+        ConfigSource::Profile(name) => manager.read_config(&profile_path(manager, name)?)?,
+        ConfigSource::Empty => ClaudeConfig::new(),
+    };
+
+    let (config, missing) = extract_key_paths(&template, sections)?;
+
+    let mut warnings: Vec<String> = missing
+        .iter()
+        .map(|section| format!("The source has no '{section}' section"))
+        .collect();
+    warnings.extend(
+        config
+            .allowed_paths
+            .iter()
+            .flatten()
+            .filter(|allowed| !expand_tilde(Path::new(&expand_env(allowed))).exists())
+            .map(|allowed| format!("Allowed path '{allowed}' does not exist on this machine")),
+    );
+
+    manager.write_config_with_backup(&path, &config)?;
+    tracing::debug!("Created project config: {}", path.display());
+
+    Ok(CreatedConfig {
+        section_counts: section_counts(&config)?,
+        path,
+        config,
+        warnings,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn setup() -> (TempDir, ConfigManager) {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = ConfigManager::with_custom_global_config(
+            temp_dir.path().join("backups"),
+            temp_dir.path().join("claude").join("config.json"),
+        );
+        (temp_dir, manager)
+    }
+
+    fn source_config() -> &'static str {
+        r#"{
+            "mcpServers": {
+                "fetch": {"command": "uvx", "enabled": true},
+                "github": {"command": "npx", "enabled": true}
+            },
+            "allowedPaths": ["/definitely/not/here"],
+            "customInstructions": ["Be concise"]
+        }"#
+    }
+
+    // TDD Test 1: Selected sections are copied from another project
+    #[test]
+    fn test_create_from_project_copies_selected_sections() {
+        let (temp_dir, manager) = setup();
+        let source = temp_dir.path().join("source");
+        fs::create_dir_all(source.join(".claude")).unwrap();
+        fs::write(source.join(".claude").join("config.json"), source_config()).unwrap();
+        let target = temp_dir.path().join("target");
+
+        let created = create_project_config(
+            &manager,
+            &target,
+            &ConfigSource::Project(source),
+            &["mcpServers".to_string(), "skills".to_string()],
+            false,
+        )
+        .unwrap();
+
+        assert_eq!(created.path, target.join(".claude").join("config.json"));
+        assert_eq!(
+            created.section_counts,
+            BTreeMap::from([("mcpServers".to_string(), 2)])
+        );
+        assert_eq!(created.warnings, ["The source has no 'skills' section"]);
+        assert!(created.config.allowed_paths.is_none());
+        assert_eq!(manager.read_config(&created.path).unwrap(), created.config);
+    }
+
+    // TDD Test 2: A profile is copied whole, with warnings for missing paths
+    #[test]
+    fn test_create_from_profile() {
+        let (temp_dir, manager) = setup();
+        let profile = profile_path(&manager, "work").unwrap();
+        assert_eq!(
+            profile,
+            temp_dir
+                .path()
+                .join("claude")
+                .join("profiles")
+                .join("work.json")
+        );
+        fs::create_dir_all(profile.parent().unwrap()).unwrap();
+        fs::write(&profile, source_config()).unwrap();
+
+        let created = create_project_config(
+            &manager,
+            temp_dir.path(),
+            &ConfigSource::Profile("work".to_string()),
+            &[],
+            false,
+        )
+        .unwrap();
+
+        assert_eq!(
+            created.section_counts,
+            BTreeMap::from([
+                ("allowedPaths".to_string(), 1),
+                ("customInstructions".to_string(), 1),
+                ("mcpServers".to_string(), 2),
+            ])
+        );
+        assert_eq!(created.warnings.len(), 1);
+        assert!(created.warnings[0].contains("/definitely/not/here"));
+
+        assert!(profile_path(&manager, "../work").is_err());
+        assert!(matches!(
+            create_project_config(
+                &manager,
+                &temp_dir.path().join("other"),
+                &ConfigSource::Profile("missing".to_string()),
+                &[],
+                false,
+            ),
+            Err(ConfigError::NotFound { .. })
+        ));
+    }
+
+    // TDD Test 3: An empty config is created, and existing configs are kept
+    #[test]
+    fn test_create_empty_refuses_overwrite() {
+        let (temp_dir, manager) = setup();
+        let project = temp_dir.path().join("project");
+
+        let created =
+            create_project_config(&manager, &project, &ConfigSource::Empty, &[], false).unwrap();
+        assert_eq!(created.config, ClaudeConfig::new());
+        assert!(created.section_counts.is_empty());
+        assert_eq!(fs::read_to_string(&created.path).unwrap().trim(), "{}");
+
+        fs::write(&created.path, r#"{"allowedPaths": ["~/keep"]}"#).unwrap();
+        let err = create_project_config(&manager, &project, &ConfigSource::Empty, &[], false)
+            .unwrap_err();
+        assert!(err.to_string().contains("already exists"));
+        assert!(fs::read_to_string(&created.path)
+            .unwrap()
+            .contains("~/keep"));
+
+        create_project_config(&manager, &project, &ConfigSource::Empty, &[], true).unwrap();
+        assert!(!fs::read_to_string(&created.path)
+            .unwrap()
+            .contains("~/keep"));
+    }
+
+    // TDD Test 4: Profiles are saved from a config, and key paths apply to every source
+    #[test]
+    fn test_save_profile_and_key_paths() {
+        let (temp_dir, manager) = setup();
+        let source: ClaudeConfig = serde_json::from_str(source_config()).unwrap();
+
+        let (path, missing) = save_profile(
+            &manager,
+            "fetch-only",
+            &source,
+            &["mcpServers.fetch".to_string(), "skills".to_string()],
+            false,
+        )
+        .unwrap();
+        assert_eq!(path, profile_path(&manager, "fetch-only").unwrap());
+        assert_eq!(missing, ["skills"]);
+        assert!(save_profile(&manager, "fetch-only", &source, &[], false)
+            .unwrap_err()
+            .to_string()
+            .contains("already exists"));
+
+        let created = create_project_config(
+            &manager,
+            &temp_dir.path().join("from-profile"),
+            &ConfigSource::Profile("fetch-only".to_string()),
+            &[],
+            false,
+        )
+        .unwrap();
+        let servers = created.config.mcp_servers.unwrap();
+        assert_eq!(servers.keys().collect::<Vec<_>>(), ["fetch"]);
+        assert!(created.config.custom_instructions.is_none());
+
+        // An empty source has none of the requested key paths
+        let created = create_project_config(
+            &manager,
+            &temp_dir.path().join("empty"),
+            &ConfigSource::Empty,
+            &["mcpServers".to_string()],
+            false,
+        )
+        .unwrap();
+        assert_eq!(created.config, ClaudeConfig::new());
+        assert_eq!(created.warnings, ["The source has no 'mcpServers' section"]);
+    }
+}
@@ -0,0 +1,119 @@
+//! Targeted suggestions for common JSON mistakes
+//!
+//! serde_json reports where parsing failed, but not why a hand-edited file
+//! is wrong. This module looks at the text around the error location for
+//! the two most common mistakes, trailing commas and single-quoted strings,
+//! and turns them into a concrete suggestion.
+
+use std::io::{BufRead, BufReader, Read};
+
+/// Suggest a fix for a JSON parse error
+///
+/// Only the lines up to the error line are read, so this also works on
+/// large configs read from a stream.
+///
+/// # Arguments
+/// * `source` - The JSON text that failed to parse
+/// * `line` - 1-based error line as reported by serde_json
+/// * `column` - 1-based error column (in bytes)
+///
+/// # Returns
+/// A suggestion, or None if the error does not look like a known mistake
+pub(crate) fn suggest_fix(source: impl Read, line: usize, column: usize) -> Option<String> {
+    if line == 0 {
+        return None;
+    }
+
+    // Last non-blank line before the error line, for commas that end a line
+    let mut previous: Option<(usize, String)> = None;
+    let mut error_line = None;
+
+    for (index, text) in BufReader::new(source).lines().enumerate() {
+        let text = text.ok()?;
+        let number = index + 1;
+        if number == line {
+            error_line = Some(text);
+            break;
+        }
+        if !text.trim().is_empty() {
+            previous = Some((number, text));
+        }
+    }
+
+    let error_line = error_line?;
+    let bytes = error_line.as_bytes();
+    let offset = column.saturating_sub(1).min(bytes.len());
+    let at = bytes.get(offset).copied();
+    let before = String::from_utf8_lossy(&bytes[..offset]);
+    let before = before.trim_end();
+
+    if at == Some(b'\'') || before.ends_with('\'') {
+        return Some(format!(
+            "Use double quotes instead of single quotes on line {line}; JSON keys and strings must be double-quoted"
+        ));
+    }
+
+    if matches!(at, Some(b'}' | b']')) {
+        let comma_line = if before.ends_with(',') {
+            Some(line)
+        } else if before.is_empty() {
+            previous
+                .filter(|(_, text)| text.trim_end().ends_with(','))
+                .map(|(number, _)| number)
+        } else {
+            None
+        };
+
+        if let Some(comma_line) = comma_line {
+            return Some(format!(
+                "Remove the trailing comma after line {comma_line}; JSON does not allow a comma before '}}' or ']'"
+            ));
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn suggest(source: &str) -> Option<String> {
+        let err = serde_json::from_str::<serde_json::Value>(source).unwrap_err();
+        suggest_fix(source.as_bytes(), err.line(), err.column())
+    }
+
+    // TDD Test 1: Trailing comma before a closing brace on the next line
+    #[test]
+    fn test_suggests_trailing_comma_on_previous_line() {
+        let source = "{\n  \"allowedPaths\": [\"~/a\"],\n}\n";
+        assert_eq!(
+            suggest(source).unwrap(),
+            "Remove the trailing comma after line 2; JSON does not allow a comma before '}' or ']'"
+        );
+
+        let source = "{\"allowedPaths\": [\"~/a\", ]}";
+        assert!(suggest(source)
+            .unwrap()
+            .contains("trailing comma after line 1"));
+    }
+
+    // TDD Test 2: Single-quoted keys and values
+    #[test]
+    fn test_suggests_double_quotes() {
+        let key = "{\n  'allowedPaths': []\n}";
+        assert!(suggest(key).unwrap().contains("double quotes"));
+        assert!(suggest(key).unwrap().contains("line 2"));
+
+        let value = "{\"allowedPaths\": ['~/a']}";
+        assert!(suggest(value).unwrap().contains("double quotes"));
+    }
+
+    // TDD Test 3: Other errors get no tailored suggestion
+    #[test]
+    fn test_no_suggestion_for_other_errors() {
+        assert!(suggest("{\"allowedPaths\": [1 2]}").is_none());
+        assert!(suggest("{\"a\": tru}").is_none());
+        assert!(suggest_fix("{}".as_bytes(), 0, 0).is_none());
+    }
+}
@@ -0,0 +1,478 @@
+//! Dotted key paths into a config's JSON form
+//!
+//! A key path names a section or a nested key, e.g. `customInstructions` or
+//! `mcpServers.fetch`. Each dot-separated part is an object key; arrays are
+//! addressed as a whole, or by element as `key[index]` where noted. A
+//! trailing `[N]` or `[+]` ([`split_index`]) names an element to replace,
+//! insert or remove, or the position after the last one.
+
+use crate::error::{ConfigError, Result};
+use serde_json::{Map, Value};
+
+/// Value at `key_path`, if present
+pub fn get<'a>(root: &'a Value, key_path: &str) -> Option<&'a Value> {
+    key_path
+        .split('.')
+        .try_fold(root, |value, key| value.as_object()?.get(key))
+}
+
+/// Value at a key path that may address array elements as `key[index]`
+///
+/// This is the form used in search results and diffs (e.g.
+/// `customInstructions[0]`). A numeric part after a dot also indexes an
+/// array, so `customInstructions.0` names the same element.
+pub fn get_element<'a>(root: &'a Value, key_path: &str) -> Option<&'a Value> {
+    segments(key_path).try_fold(root, |value, segment| match (value, segment?) {
+        (Value::Object(map), Segment::Key(key)) => map.get(key),
+        (Value::Array(items), Segment::Key(key)) => items.get(key.parse::<usize>().ok()?),
+        (Value::Array(items), Segment::Index(index)) => items.get(index),
+        _ => None,
+    })
+}
+
+/// Mutable value at a key path in the form read by [`get_element`]
+pub fn get_element_mut<'a>(root: &'a mut Value, key_path: &str) -> Option<&'a mut Value> {
+    segments(key_path).try_fold(root, |value, segment| match (value, segment?) {
+        (Value::Object(map), Segment::Key(key)) => map.get_mut(key),
+        (Value::Array(items), Segment::Key(key)) => items.get_mut(key.parse::<usize>().ok()?),
+        (Value::Array(items), Segment::Index(index)) => items.get_mut(index),
+        _ => None,
+    })
+}
+
+/// Array position named by a trailing `[N]` or `[+]` on a key path
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArrayIndex {
+    /// An existing element
+    At(usize),
+
+    /// The position after the last element (`[+]`)
+    End,
+}
+
+/// Split a trailing `[N]` or `[+]` off a key path
+///
+/// `customInstructions[7]` gives `("customInstructions", Some(ArrayIndex::At(7)))`;
+/// a path without brackets is returned unchanged with `None`.
+///
+/// # Errors
+/// Returns an error if the brackets hold neither a number nor `+`
+pub fn split_index(key_path: &str) -> Result<(&str, Option<ArrayIndex>)> {
+    let Some(inner) = key_path.strip_suffix(']') else {
+        return Ok((key_path, None));
+    };
+    let Some((base, index)) = inner.rsplit_once('[') else {
+        return Err(ConfigError::Generic(format!(
+            "'{key_path}' has a ']' without a matching '['"
+        )));
+    };
+
+    let index = match index {
+        "+" => ArrayIndex::End,
+        number => ArrayIndex::At(number.parse().map_err(|_| {
+            ConfigError::Generic(format!(
+                "Invalid index '{number}' in '{key_path}'; use a number or '+'"
+            ))
+        })?),
+    };
+    Ok((base, Some(index)))
+}
+
+/// Set the array element named by an indexed key path
+///
+/// `key[N]` replaces an existing element and `key[+]` appends to an existing
+/// array. Indexes past the end are rejected, so arrays never get gaps.
+///
+/// # Errors
+/// Returns an error if the key path has no index, the array doesn't exist or
+/// the element doesn't
+pub fn set_element(root: &mut Value, key_path: &str, new_value: Value) -> Result<()> {
+    match split_index(key_path)? {
+        (_, None) => Err(ConfigError::Generic(format!(
+            "'{key_path}' has no index; write it as '{key_path}[N]'"
+        ))),
+        (base, Some(ArrayIndex::End)) => match get_element_mut(root, base) {
+            Some(Value::Array(items)) => {
+                items.push(new_value);
+                Ok(())
+            }
+            _ => Err(ConfigError::Generic(format!(
+                "'{base}' is not an array; '[+]' only applies to arrays"
+            ))),
+        },
+        (_, Some(ArrayIndex::At(_))) => match get_element_mut(root, key_path) {
+            Some(element) => {
+                *element = new_value;
+                Ok(())
+            }
+            None => Err(ConfigError::Generic(format!(
+                "'{key_path}' doesn't exist; indexes must name an existing array element"
+            ))),
+        },
+    }
+}
+
+/// Replace the element of `items` at `index`, or append for [`ArrayIndex::End`]
+///
+/// `key_path` names the array in error messages.
+///
+/// # Errors
+/// Returns an error if `index` is out of bounds
+pub fn set_at<T>(items: &mut Vec<T>, key_path: &str, index: ArrayIndex, value: T) -> Result<()> {
+    match index {
+        ArrayIndex::End => items.push(value),
+        ArrayIndex::At(i) => {
+            let len = items.len();
+            let element = items.get_mut(i).ok_or_else(|| {
+                ConfigError::Generic(format!(
+                    "{}; use '{key_path}[+]' to append",
+                    out_of_bounds(key_path, i, len)
+                ))
+            })?;
+            *element = value;
+        }
+    }
+    Ok(())
+}
+
+/// Insert `value` into `items` at `index`, shifting later elements
+///
+/// # Errors
+/// Returns an error if `index` is greater than the array's length
+pub fn insert_at<T>(items: &mut Vec<T>, key_path: &str, index: usize, value: T) -> Result<()> {
+    if index > items.len() {
+        return Err(ConfigError::Generic(format!(
+            "{}; insert positions are 0 to {}",
+            out_of_bounds(key_path, index, items.len()),
+            items.len()
+        )));
+    }
+    items.insert(index, value);
+    Ok(())
+}
+
+/// Remove the element of `items` at `index`, shifting later elements
+///
+/// # Errors
+/// Returns an error if `index` is [`ArrayIndex::End`] or out of bounds
+pub fn remove_at<T>(items: &mut Vec<T>, key_path: &str, index: ArrayIndex) -> Result<T> {
+    check_at(items.len(), key_path, index)?;
+    match index {
+        ArrayIndex::At(i) => Ok(items.remove(i)),
+        ArrayIndex::End => unreachable!("check_at rejects [+]"),
+    }
+}
+
+/// Check that `index` names an existing element of an array of length `len`
+///
+/// # Errors
+/// Returns an error naming the array's length if the index is out of
+/// bounds, or if it is [`ArrayIndex::End`]
+pub fn check_at(len: usize, key_path: &str, index: ArrayIndex) -> Result<()> {
+    match index {
+        ArrayIndex::At(i) if i < len => Ok(()),
+        ArrayIndex::At(i) => Err(ConfigError::Generic(out_of_bounds(key_path, i, len))),
+        ArrayIndex::End => Err(ConfigError::Generic(format!(
+            "'{key_path}[+]' doesn't name an element"
+        ))),
+    }
+}
+
+/// Whether [`walk`] enters an object or array
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Walk {
+    /// Visit the value's children
+    Descend,
+    /// Skip the value's children
+    Skip,
+}
+
+/// Visit every value below `value`, depth first in key order
+///
+/// `key_path` is the path of `value` itself (empty for the config root). The
+/// visitor gets each value's path in the `key[index]` form read by
+/// [`get_element`] and its depth below `value` (1 for direct children), and
+/// decides whether the walk enters it.
+pub fn walk<'a>(
+    value: &'a Value,
+    key_path: &str,
+    visit: &mut impl FnMut(&str, &'a Value, usize) -> Walk,
+) {
+    walk_children(value, key_path, 1, visit);
+}
+
+/// Key path and value of every leaf below `value`
+///
+/// Leaves are scalars and empty objects or arrays, so each path can be passed
+/// to [`get_element`] (or `ccm config set`) on its own.
+pub fn leaves<'a>(value: &'a Value, key_path: &str) -> Vec<(String, &'a Value)> {
+    let mut leaves = Vec::new();
+    walk(value, key_path, &mut |path, value, _| {
+        if is_leaf(value) {
+            leaves.push((path.to_string(), value));
+        }
+        Walk::Descend
+    });
+    leaves
+}
+
+/// Whether a value has no children to walk into
+pub fn is_leaf(value: &Value) -> bool {
+    match value {
+        Value::Object(map) => map.is_empty(),
+        Value::Array(items) => items.is_empty(),
+        _ => true,
+    }
+}
+
+/// Set the value at `key_path`, creating (or replacing non-object) parents
+pub fn set(root: &mut Value, key_path: &str, new_value: Value) {
+    let mut keys: Vec<&str> = key_path.split('.').collect();
+    let last = keys.pop().unwrap_or_default();
+
+    let mut current = root;
+    for key in keys {
+        current = as_object(current)
+            .entry(key)
+            .or_insert_with(|| Value::Object(Map::new()));
+    }
+    as_object(current).insert(last.to_string(), new_value);
+}
+
+/// Remove the value at `key_path`, returning it if it was present
+pub fn remove(root: &mut Value, key_path: &str) -> Option<Value> {
+    let (parent, last) = match key_path.rsplit_once('.') {
+        Some((parent, last)) => (get_mut(root, parent)?, last),
+        None => (root, key_path),
+    };
+    parent.as_object_mut()?.remove(last)
+}
+
+/// Mutable value at `key_path`, if present
+fn get_mut<'a>(root: &'a mut Value, key_path: &str) -> Option<&'a mut Value> {
+    key_path
+        .split('.')
+        .try_fold(root, |value, key| value.as_object_mut()?.get_mut(key))
+}
+
+/// One dot-separated key or bracketed index of a key path
+enum Segment<'a> {
+    Key(&'a str),
+    Index(usize),
+}
+
+/// The keys and indexes of a key path, `None` for a malformed index
+fn segments(key_path: &str) -> impl Iterator<Item = Option<Segment<'_>>> {
+    key_path.split('.').flat_map(|part| {
+        let (key, indices) = part.split_once('[').unwrap_or((part, ""));
+        let indices = indices
+            .split('[')
+            .filter(|index| !index.is_empty())
+            .map(|index| Some(Segment::Index(index.strip_suffix(']')?.parse().ok()?)));
+        std::iter::once(Some(Segment::Key(key))).chain(indices)
+    })
+}
+
+/// Message for an index past the end of an array
+fn out_of_bounds(key_path: &str, index: usize, len: usize) -> String {
+    format!("Index {index} is out of bounds for '{key_path}' (length {len})")
+}
+
+/// Visit the children of `value` at `depth`, then theirs if asked to
+fn walk_children<'a>(
+    value: &'a Value,
+    key_path: &str,
+    depth: usize,
+    visit: &mut impl FnMut(&str, &'a Value, usize) -> Walk,
+) {
+    let children: Vec<(String, &Value)> = match value {
+        Value::Object(map) => map
+            .iter()
+            .map(|(key, child)| {
+                let path = if key_path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{key_path}.{key}")
+                };
+                (path, child)
+            })
+            .collect(),
+        Value::Array(items) => items
+            .iter()
+            .enumerate()
+            .map(|(index, child)| (format!("{key_path}[{index}]"), child))
+            .collect(),
+        _ => return,
+    };
+
+    for (path, child) in children {
+        if visit(&path, child, depth) == Walk::Descend {
+            walk_children(child, &path, depth + 1, visit);
+        }
+    }
+}
+
+/// The object in `value`, replacing a non-object with an empty one
+fn as_object(value: &mut Value) -> &mut Map<String, Value> {
+    if !value.is_object() {
+        *value = Value::Object(Map::new());
+    }
+    value
+        .as_object_mut()
+        .expect("value was just made an object")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    // TDD Test 1: Get, set and remove nested keys
+    #[test]
+    fn test_get_set_remove() {
+        let mut value = json!({"mcpServers": {"fetch": {"command": "uvx"}}});
+
+        assert_eq!(get(&value, "mcpServers.fetch.command"), Some(&json!("uvx")));
+        assert_eq!(get(&value, "mcpServers.github"), None);
+
+        set(&mut value, "mcpServers.github", json!({"command": "npx"}));
+        set(&mut value, "skills.review.enabled", json!(true));
+        assert_eq!(
+            get(&value, "mcpServers.github.command"),
+            Some(&json!("npx"))
+        );
+        assert_eq!(value["skills"], json!({"review": {"enabled": true}}));
+
+        assert_eq!(
+            remove(&mut value, "mcpServers.fetch"),
+            Some(json!({"command": "uvx"}))
+        );
+        assert_eq!(remove(&mut value, "mcpServers.fetch"), None);
+        assert_eq!(remove(&mut value, "missing.key"), None);
+        assert_eq!(
+            remove(&mut value, "skills"),
+            Some(json!({"review": {"enabled": true}}))
+        );
+        assert_eq!(value, json!({"mcpServers": {"github": {"command": "npx"}}}));
+    }
+
+    // TDD Test 2: Array elements are addressed by index
+    #[test]
+    fn test_get_element() {
+        let value = json!({"customInstructions": ["a", "b"], "grid": {"rows": [[1, 2]]}});
+
+        assert_eq!(
+            get_element(&value, "customInstructions[1]"),
+            Some(&json!("b"))
+        );
+        assert_eq!(get_element(&value, "customInstructions[2]"), None);
+        assert_eq!(get_element(&value, "grid.rows[0][1]"), Some(&json!(2)));
+        assert_eq!(get_element(&value, "grid.rows"), Some(&json!([[1, 2]])));
+        assert_eq!(get_element(&value, "grid.rows[x]"), None);
+        assert_eq!(
+            get_element(&value, "customInstructions.0"),
+            Some(&json!("a"))
+        );
+
+        let mut value = value;
+        *get_element_mut(&mut value, "grid.rows[0][1]").unwrap() = json!(3);
+        assert_eq!(value["grid"]["rows"], json!([[1, 3]]));
+        assert_eq!(get_element_mut(&mut value, "grid.rows[1]"), None);
+    }
+
+    // TDD Test 3: Walking yields paths get_element resolves
+    #[test]
+    fn test_walk_and_leaves() {
+        let value = json!({
+            "mcpServers": {"fetch": {"args": ["-y", "fetch"], "env": {}}},
+            "customInstructions": ["a"],
+            "verbose": true
+        });
+
+        let paths: Vec<String> = leaves(&value, "")
+            .into_iter()
+            .map(|(path, leaf)| {
+                assert_eq!(get_element(&value, &path), Some(leaf));
+                path
+            })
+            .collect();
+        assert_eq!(
+            paths,
+            [
+                "customInstructions[0]",
+                "mcpServers.fetch.args[0]",
+                "mcpServers.fetch.args[1]",
+                "mcpServers.fetch.env",
+                "verbose",
+            ]
+        );
+
+        let fetch = &value["mcpServers"]["fetch"];
+        assert_eq!(
+            leaves(fetch, "mcpServers.fetch")[0].0,
+            "mcpServers.fetch.args[0]"
+        );
+
+        let mut visited = Vec::new();
+        walk(&value, "", &mut |path, _, depth| {
+            visited.push((path.to_string(), depth));
+            if path == "mcpServers" {
+                Walk::Skip
+            } else {
+                Walk::Descend
+            }
+        });
+        assert_eq!(
+            visited,
+            [
+                ("customInstructions".to_string(), 1),
+                ("customInstructions[0]".to_string(), 2),
+                ("mcpServers".to_string(), 1),
+                ("verbose".to_string(), 1),
+            ]
+        );
+    }
+
+    // TDD Test 4: Indexed operations never leave gaps
+    #[test]
+    fn test_indexed_operations() {
+        assert_eq!(
+            split_index("customInstructions[7]").unwrap(),
+            ("customInstructions", Some(ArrayIndex::At(7)))
+        );
+        assert_eq!(
+            split_index("mcpServers.npx.args[+]").unwrap(),
+            ("mcpServers.npx.args", Some(ArrayIndex::End))
+        );
+        assert_eq!(split_index("allowedPaths").unwrap(), ("allowedPaths", None));
+        assert!(split_index("allowedPaths[x]").is_err());
+        assert!(split_index("allowedPaths]").is_err());
+
+        let mut items = vec!["a", "b"];
+        set_at(&mut items, "items", ArrayIndex::At(1), "B").unwrap();
+        set_at(&mut items, "items", ArrayIndex::End, "c").unwrap();
+        insert_at(&mut items, "items", 0, "first").unwrap();
+        assert_eq!(items, ["first", "a", "B", "c"]);
+        assert_eq!(
+            remove_at(&mut items, "items", ArrayIndex::At(0)).unwrap(),
+            "first"
+        );
+
+        let error = set_at(&mut items, "items", ArrayIndex::At(3), "d").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Index 3 is out of bounds for 'items' (length 3); use 'items[+]' to append"
+        );
+        assert!(insert_at(&mut items, "items", 4, "d").is_err());
+        assert!(remove_at(&mut items, "items", ArrayIndex::End).is_err());
+        assert!(check_at(3, "items", ArrayIndex::At(2)).is_ok());
+
+        let mut value = json!({"telemetry": {"tags": ["a"]}});
+        set_element(&mut value, "telemetry.tags[0]", json!("b")).unwrap();
+        set_element(&mut value, "telemetry.tags[+]", json!("c")).unwrap();
+        assert_eq!(value["telemetry"]["tags"], json!(["b", "c"]));
+        assert!(set_element(&mut value, "telemetry.tags[2]", json!("d")).is_err());
+        assert!(set_element(&mut value, "telemetry[+]", json!("d")).is_err());
+        assert!(set_element(&mut value, "telemetry.tags", json!("d")).is_err());
+    }
+}
@@ -0,0 +1,185 @@
+//! Size limits for configuration files
+//!
+//! Config files are normally a few kilobytes, but scripts occasionally
+//! write huge blobs (e.g. base64 data in `customInstructions`). This module
+//! rejects files above a hard limit before they are loaded, identifies
+//! "large" files that should be parsed from a stream, and builds parse error
+//! excerpts without holding the whole file in memory.
+
+use crate::error::{ConfigError, Result};
+use std::fs;
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
+
+/// Hard limit for config files; larger files are rejected before parsing
+pub const MAX_CONFIG_FILE_SIZE: u64 = 64 * 1024 * 1024;
+
+/// Files above this size are parsed from a stream instead of a string
+pub const LARGE_CONFIG_FILE_SIZE: u64 = 8 * 1024 * 1024;
+
+/// Bytes of context shown on each side of a parse error
+const EXCERPT_CONTEXT: usize = 40;
+
+/// Check a config file against the size limit
+///
+/// # Arguments
+/// * `path` - Path to the config file
+///
+/// # Returns
+/// The file size in bytes
+///
+/// # Errors
+/// Returns an error if:
+/// - The file metadata cannot be read
+/// - The file is larger than [`MAX_CONFIG_FILE_SIZE`]
+pub fn check_config_size(path: &Path) -> Result<u64> {
+    let size = fs::metadata(path)
+        .map_err(|e| ConfigError::filesystem("read config metadata", path, e))?
+        .len();
+
+    if size > MAX_CONFIG_FILE_SIZE {
+        return Err(ConfigError::validation_failed(
+            "ConfigSizeLimit",
+            format!(
+                "{} is {} bytes (limit: {} bytes)",
+                path.display(),
+                size,
+                MAX_CONFIG_FILE_SIZE
+            ),
+            "Remove large embedded data (e.g. base64 blobs in customInstructions) or move it into separate files",
+        ));
+    }
+
+    Ok(size)
+}
+
+/// Check whether a file size counts as "large"
+pub fn is_large_config(size: u64) -> bool {
+    size > LARGE_CONFIG_FILE_SIZE
+}
+
+/// Build an excerpt of the source around a parse error
+///
+/// The source is streamed, so only the bytes around the error location are
+/// kept in memory, even when the offending line is megabytes long.
+///
+/// # Arguments
+/// * `source` - Reader over the config file content
+/// * `line` - 1-based error line
+/// * `column` - 1-based error column (in bytes)
+///
+/// # Returns
+/// The excerpt followed by a caret line pointing at the error, or None if
+/// the location lies outside the source
+pub fn error_excerpt(source: impl Read, line: usize, column: usize) -> Option<String> {
+    if line == 0 {
+        return None;
+    }
+
+    let start = column.saturating_sub(EXCERPT_CONTEXT).max(1);
+    let end = column + EXCERPT_CONTEXT;
+
+    let mut reader = BufReader::new(source);
+    let mut current_line = 1;
+    let mut current_column = 0;
+    let mut snippet = Vec::new();
+    let mut truncated_end = false;
+
+    'read: loop {
+        let chunk = reader.fill_buf().ok()?;
+        if chunk.is_empty() {
+            break;
+        }
+        let len = chunk.len();
+
+        for &byte in chunk {
+            if current_line < line {
+                if byte == b'\n' {
+                    current_line += 1;
+                }
+                continue;
+            }
+
+            if byte == b'\n' {
+                break 'read;
+            }
+
+            current_column += 1;
+            if current_column > end {
+                truncated_end = true;
+                break 'read;
+            }
+            if current_column >= start {
+                snippet.push(byte);
+            }
+        }
+
+        reader.consume(len);
+    }
+
+    if current_line < line {
+        return None;
+    }
+
+    let caret_bytes = column.saturating_sub(start).min(snippet.len());
+    let prefix = if start > 1 { "..." } else { "" };
+    let suffix = if truncated_end { "..." } else { "" };
+    let padding = prefix.len()
+        + String::from_utf8_lossy(&snippet[..caret_bytes])
+            .chars()
+            .count();
+
+    Some(format!(
+        "{prefix}{}{suffix}\n{}^",
+        String::from_utf8_lossy(&snippet).replace('\t', " "),
+        " ".repeat(padding)
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    // TDD Test 1: Files over the hard limit are rejected
+    #[test]
+    fn test_check_config_size_rejects_oversized_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("config.json");
+        let file = fs::File::create(&path).unwrap();
+        file.set_len(MAX_CONFIG_FILE_SIZE + 1).unwrap();
+
+        let result = check_config_size(&path);
+
+        assert!(result.unwrap_err().to_string().contains("ConfigSizeLimit"));
+    }
+
+    // TDD Test 2: Excerpt points at the error column
+    #[test]
+    fn test_error_excerpt_points_at_column() {
+        let source = "{\n  \"a\": 1,\n  \"b\": x\n}";
+
+        let excerpt = error_excerpt(source.as_bytes(), 3, 8).unwrap();
+
+        assert_eq!(excerpt, "  \"b\": x\n       ^");
+    }
+
+    // TDD Test 3: Excerpt of a long line only keeps the surrounding bytes
+    #[test]
+    fn test_error_excerpt_truncates_long_line() {
+        let source = format!("{}!{}", "a".repeat(1000), "b".repeat(1000));
+
+        let excerpt = error_excerpt(source.as_bytes(), 1, 1001).unwrap();
+        let (text, caret) = excerpt.split_once('\n').unwrap();
+
+        assert!(text.starts_with("...") && text.ends_with("..."));
+        assert!(text.len() < 100);
+        assert_eq!(text.find('!').unwrap(), caret.len() - 1);
+    }
+
+    // TDD Test 4: Location past the end yields no excerpt
+    #[test]
+    fn test_error_excerpt_out_of_range() {
+        assert!(error_excerpt("{}".as_bytes(), 5, 1).is_none());
+    }
+}
@@ -4,18 +4,39 @@
 //! configuration files with automatic backup and atomic writes.
 
 use crate::{
-    backup::BackupManager,
-    config::validation::validate_config,
+    backup::{copy_permissions, execute_plan, read_backup, BackupManager, RiskyOperation},
+    config::{
+        diff, embedded,
+        inherit::{declared_base, BaseChain, BaseResolver},
+        json_hints, key_path, limits,
+        merge::{
+            merge_all_with_sources, merge_configs_with_options, merge_notes, MergeNote,
+            MergeOptions,
+        },
+        normalize,
+        validation::validate_config,
+    },
     error::{ConfigError, Result},
-    paths::{find_project_config, get_global_config_path},
+    hash::content_hash,
+    mcp::origin::{ChangeCache, MergedServer},
+    multi::MultiResult,
+    paths::{
+        find_project_config, find_project_config_files, get_global_config_path,
+        project_config_files_in, project_config_path, Platform,
+    },
+    timings::Timings,
     types::{ConfigDiff, ConfigScope, SourceMap},
-    ConfigSearcher, SearchOptions, SearchResult,
+    ConfigSearcher, QueryMatch, SearchOptions, SearchResult,
 };
+use serde::{de::DeserializeOwned, Deserialize};
 use serde_json::Value;
 use std::fs::{self, File};
-use std::io::Write;
+use std::io::{BufReader, Write};
 use std::path::{Path, PathBuf};
 
+/// Writes changing more key paths than this get a risk snapshot by default
+pub const LARGE_CHANGE_KEY_PATHS: usize = 20;
+
 /// Configuration file manager
 ///
 /// Handles reading and writing configuration files with safety features:
@@ -27,6 +48,16 @@ use std::path::{Path, PathBuf};
 pub struct ConfigManager {
     /// Backup manager for this configuration
     backup_manager: BackupManager,
+    /// Optional custom global config path (for testing)
+    custom_global_config: Option<PathBuf>,
+    /// Timing collector for the operations below (disabled by default)
+    timings: Timings,
+    /// How configs are combined by the merged views
+    merge_options: MergeOptions,
+    /// Last-change scans of [`list_servers_merged`](Self::list_servers_merged)
+    change_cache: ChangeCache,
+    /// Platform the merged views resolve MCP servers for (`None` keeps all)
+    platform: Option<Platform>,
 }
 
 impl ConfigManager {
@@ -37,6 +68,128 @@ impl ConfigManager {
     pub fn new(backup_dir: impl Into<PathBuf>) -> Self {
         Self {
             backup_manager: BackupManager::new(backup_dir, None),
+            custom_global_config: None,
+            timings: Timings::disabled(),
+            merge_options: MergeOptions::default(),
+            change_cache: ChangeCache::default(),
+            platform: Some(Platform::current()),
+        }
+    }
+
+    /// Record operation timings in `timings`
+    ///
+    /// # Arguments
+    /// * `timings` - Collector to record read, validate, backup, write and merge times in
+    pub fn with_timings(mut self, timings: Timings) -> Self {
+        self.timings = timings;
+        self
+    }
+
+    /// Get the timing collector
+    pub fn timings(&self) -> &Timings {
+        &self.timings
+    }
+
+    /// Keep only the newest `retention` backups of each written file
+    ///
+    /// Every write that makes a backup prunes the written file's backups
+    /// down to the retention count once it succeeds. `None` keeps the
+    /// backup manager's default count.
+    pub fn with_backup_retention(mut self, retention: Option<usize>) -> Self {
+        if let Some(count) = retention {
+            self.set_retention(count);
+        }
+        self
+    }
+
+    /// Keep only the newest `count` backups of each file written from now on
+    ///
+    /// Same as [`with_backup_retention`](Self::with_backup_retention), for a
+    /// manager that is already in use.
+    pub fn set_retention(&mut self, count: usize) {
+        self.backup_manager.set_retention_count(count);
+    }
+
+    /// Store the backups of later writes in `dir`
+    ///
+    /// Existing backups are not moved, so history lookups through this
+    /// manager no longer see them.
+    pub fn set_backup_dir(&mut self, dir: impl Into<PathBuf>) {
+        self.backup_manager.set_backup_dir(dir);
+    }
+
+    /// Combine configs in merged views (e.g. [`get_merged_config`](Self::get_merged_config)) with `options`
+    pub fn with_merge_options(mut self, options: MergeOptions) -> Self {
+        self.merge_options = options;
+        self
+    }
+
+    /// Resolve MCP servers in merged views for `platform`
+    ///
+    /// Merged views drop servers whose `platforms` list excludes the platform
+    /// and use its `commandByPlatform` entry as the command. Defaults to the
+    /// current platform; `None` returns servers exactly as configured.
+    pub fn with_platform(mut self, platform: Option<Platform>) -> Self {
+        self.platform = platform;
+        self
+    }
+
+    /// Create a new ConfigManager with a custom global config path (for testing)
+    ///
+    /// # Arguments
+    /// * `backup_dir` - Directory to store backups
+    /// * `custom_global_config` - Custom path for global config
+    #[cfg(test)]
+    pub fn with_custom_global_config(
+        backup_dir: impl Into<PathBuf>,
+        custom_global_config: impl Into<PathBuf>,
+    ) -> Self {
+        Self {
+            backup_manager: BackupManager::new(backup_dir, None),
+            custom_global_config: Some(custom_global_config.into()),
+            timings: Timings::disabled(),
+            merge_options: MergeOptions::default(),
+            change_cache: ChangeCache::default(),
+            platform: Some(Platform::current()),
+        }
+    }
+
+    /// Get the global config path (custom path if set, otherwise default)
+    fn global_config_path(&self) -> PathBuf {
+        self.custom_global_config
+            .clone()
+            .unwrap_or_else(get_global_config_path)
+    }
+
+    /// Path of the config file for a scope
+    ///
+    /// # Arguments
+    /// * `scope` - Global or project scope
+    /// * `project_path` - Project root (required for [`ConfigScope::Project`])
+    ///
+    /// # Returns
+    /// The global config path (honoring a custom global config), or
+    /// `<project>/.claude/config.json`
+    ///
+    /// # Errors
+    /// Returns an error for project scope without a project path, and for
+    /// [`ConfigScope::Base`]
+    pub fn config_path_for_scope(
+        &self,
+        scope: &ConfigScope,
+        project_path: Option<&Path>,
+    ) -> Result<PathBuf> {
+        match scope {
+            ConfigScope::Global => Ok(self.global_config_path()),
+            ConfigScope::Project => {
+                let path = project_path.ok_or_else(|| {
+                    ConfigError::Generic("Project path required for Project scope".to_string())
+                })?;
+                Ok(project_config_path(path))
+            }
+            ConfigScope::Base => Err(ConfigError::Generic(
+                "Base configs are read-only; edit the file 'ccm.extends' names".to_string(),
+            )),
         }
     }
 
@@ -54,36 +207,118 @@ impl ConfigManager {
     /// - File cannot be read
     /// - JSON is invalid
     pub fn read_config(&self, path: &Path) -> Result<crate::ClaudeConfig> {
-        // Check if file exists
-        if !path.exists() {
-            return Err(ConfigError::not_found(path));
-        }
+        self.timings.time("read", || read_config_file(path))
+    }
 
-        // Read file content
-        let content = fs::read_to_string(path)
-            .map_err(|e| ConfigError::filesystem("read config file", path, e))?;
+    /// Read a configuration file as both a typed config and an untyped value
+    ///
+    /// For callers that validate the config but also need its exact
+    /// structure: the file is read and parsed once, and the config is built
+    /// from the parsed value.
+    ///
+    /// # Arguments
+    /// * `path` - Path to the configuration file
+    ///
+    /// # Returns
+    /// The parsed configuration and the JSON value it was built from
+    ///
+    /// # Errors
+    /// Same as [`read_config`](Self::read_config)
+    pub fn read_config_dual(&self, path: &Path) -> Result<(crate::ClaudeConfig, Value)> {
+        self.timings.time("read", || {
+            let value: Value = parse_config_file(path)?;
+            match crate::ClaudeConfig::deserialize(&value) {
+                Ok(config) => Ok((config, value)),
+                // Valid JSON that doesn't fit the config: parse the text
+                // again for an error with a location
+                Err(e) => Err(read_config_file(path)
+                    .err()
+                    .unwrap_or_else(|| ConfigError::Generic(e.to_string()))),
+            }
+        })
+    }
 
-        // Parse JSON
-        let config: crate::ClaudeConfig = serde_json::from_str(&content).map_err(|e| {
-            // Try to extract line and column from error message
-            let error_str = e.to_string();
-            let (line, column) = parse_json_error_location(&error_str);
+    /// Read several configuration files, continuing past failures
+    ///
+    /// Used by bulk operations so that one corrupt config does not abort
+    /// work across every other project.
+    ///
+    /// # Arguments
+    /// * `paths` - Configuration files to read; project manifests
+    ///   (`package.json`, `pyproject.toml`) are read for their embedded config
+    ///
+    /// # Returns
+    /// Parsed configurations and the errors for files that could not be read
+    pub fn read_configs<I, P>(&self, paths: I) -> MultiResult<crate::ClaudeConfig>
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<Path>,
+    {
+        let mut result = MultiResult::new();
+        for path in paths {
+            let path = path.as_ref();
+            result.push(path, self.read_project_file(path));
+        }
+        result
+    }
 
-            ConfigError::invalid_json(path, line, column, error_str)
-        })?;
+    /// Read a config file, or the config embedded in a project manifest
+    fn read_project_file(&self, path: &Path) -> Result<crate::ClaudeConfig> {
+        match embedded::EmbeddedConfig::from_manifest_path(path) {
+            Some(embedded) => embedded::read_embedded_config(&embedded),
+            None => self.read_config(path),
+        }
+    }
 
-        tracing::debug!("Loaded configuration from: {}", path.display());
+    /// Fingerprint of a configuration file's bytes
+    ///
+    /// Cheap way to tell whether a file changed since it was last read, e.g.
+    /// for conditional reloads or to refuse a write when someone else edited
+    /// the file in between. Any byte change, including formatting, changes
+    /// the fingerprint.
+    ///
+    /// # Arguments
+    /// * `path` - Path to the configuration file
+    ///
+    /// # Returns
+    /// The file's [`content_hash`] (16 lowercase hex digits)
+    ///
+    /// # Errors
+    /// Returns an error if the file doesn't exist or cannot be read
+    pub fn config_fingerprint(&self, path: &Path) -> Result<String> {
+        match fs::read(path) {
+            Ok(bytes) => Ok(content_hash(&bytes)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Err(ConfigError::not_found(path)),
+            Err(e) => Err(ConfigError::filesystem("read config file", path, e)),
+        }
+    }
 
-        Ok(config)
+    /// Fingerprint of the configuration file of a scope
+    ///
+    /// # Arguments
+    /// * `scope` - Global or project scope
+    /// * `project_path` - Project root (required for [`ConfigScope::Project`])
+    ///
+    /// # Errors
+    /// Returns an error for project scope without a project path, or if the
+    /// file doesn't exist or cannot be read
+    pub fn config_fingerprint_for_scope(
+        &self,
+        scope: &ConfigScope,
+        project_path: Option<&Path>,
+    ) -> Result<String> {
+        self.config_fingerprint(&self.config_path_for_scope(scope, project_path)?)
     }
 
     /// Write configuration with automatic backup
     ///
-    /// This method:
+    /// If the file already holds an equal configuration nothing is written
+    /// and no backup is made. Otherwise this method:
     /// 1. Creates a backup of the existing file (if it exists)
     /// 2. Validates the new configuration
     /// 3. Writes to a temporary file
     /// 4. Atomically renames temp file to target
+    /// 5. Reads the file back and checks it matches what was written
     ///
     /// # Arguments
     /// * `path` - Path to write the configuration
@@ -94,69 +329,170 @@ impl ConfigManager {
     /// - Backup creation fails (operation aborted to protect data)
     /// - Validation fails
     /// - Write operation fails
+    /// - The written file doesn't read back as `config` (the previous
+    ///   content is restored first)
     pub fn write_config_with_backup(
         &self,
         path: &Path,
         config: &crate::ClaudeConfig,
     ) -> Result<()> {
-        // Step 1: Create backup if file exists
-        if path.exists() {
-            tracing::debug!("Creating backup before writing: {}", path.display());
-            self.backup_manager.create_backup(path)?;
-        }
-
-        // Step 2: Validate configuration
-        validate_config(config)?;
-
-        // Step 3: Serialize configuration
-        let json = serde_json::to_string_pretty(config)
-            .map_err(|e| ConfigError::Generic(format!("Failed to serialize config: {e}")))?;
-
-        // Step 4: Atomic write using temp file
-        self.atomic_write(path, &json)?;
-
-        tracing::debug!("Wrote configuration to: {}", path.display());
+        self.write_config_with_options(path, config, WriteOptions::default())
+            .map(|_| ())
+    }
 
-        Ok(())
+    /// Write configuration with backup, unless the file changed meanwhile
+    ///
+    /// Optimistic concurrency for editors: read the config and its
+    /// [`config_fingerprint`](Self::config_fingerprint), let the user edit,
+    /// then save with the fingerprint from the read. If anyone else wrote
+    /// the file in between, nothing is written.
+    ///
+    /// # Arguments
+    /// * `path` - Path to write the configuration
+    /// * `config` - Configuration to write
+    /// * `expected_fingerprint` - Fingerprint of the file when it was read
+    ///
+    /// # Errors
+    /// Returns [`ConfigError::Conflict`] if the file's fingerprint differs
+    /// from `expected_fingerprint`, and the errors of
+    /// [`write_config_with_backup`](Self::write_config_with_backup)
+    pub fn write_config_if_unchanged(
+        &self,
+        path: &Path,
+        config: &crate::ClaudeConfig,
+        expected_fingerprint: &str,
+    ) -> Result<()> {
+        let actual = self.config_fingerprint(path)?;
+        if actual != expected_fingerprint {
+            return Err(ConfigError::conflict(path, expected_fingerprint, actual));
+        }
+        self.write_config_with_backup(path, config)
     }
 
-    /// Internal atomic write implementation
+    /// Write configuration with automatic backup and custom options
     ///
-    /// Uses write-then-rename pattern to ensure atomicity:
-    /// 1. Write to temp file in same directory
-    /// 2. Rename temp file to target (atomic on most filesystems)
-    fn atomic_write(&self, target: &Path, content: &str) -> Result<()> {
-        // Ensure parent directory exists
-        if let Some(parent) = target.parent() {
-            if !parent.exists() {
-                fs::create_dir_all(parent)
-                    .map_err(|e| ConfigError::filesystem("create config directory", parent, e))?;
+    /// Same as [`write_config_with_backup`](Self::write_config_with_backup),
+    /// with read-back verification, the unchanged check, instruction
+    /// deduplication and strict canonical form controlled by `options`.
+    /// Configs that set `"ccm": {"canonicalOnWrite": true}` are always
+    /// written in strict canonical form.
+    ///
+    /// # Returns
+    /// `true` if the file was written, `false` if it already held `config`
+    pub fn write_config_with_options(
+        &self,
+        path: &Path,
+        config: &crate::ClaudeConfig,
+        options: WriteOptions,
+    ) -> Result<bool> {
+        let deduped = options.dedupe_instructions.then(|| {
+            let mut deduped = config.clone();
+            if let Some(instructions) = deduped.custom_instructions.as_mut() {
+                let removed = normalize::dedupe_instructions(instructions);
+                tracing::debug!("Removed {removed} duplicate custom instruction(s)");
             }
+            deduped
+        });
+        let config = deduped.as_ref().unwrap_or(config);
+        let strict = options.canonical || config.ccm_settings().canonical_on_write;
+        let sorted = strict
+            .then(|| normalize::sort_unordered(config))
+            .transpose()?;
+        let config = sorted.as_ref().unwrap_or(config);
+
+        // Embedded configs are read-only; don't shadow them with a new file
+        if !path.exists() {
+            refuse_embedded_shadow(path)?;
+        } else if !options.force && holds_config(path, config) {
+            tracing::debug!("Configuration unchanged, not writing: {}", path.display());
+            return Ok(false);
         }
 
-        // Create temp file path
-        let temp_path = target.with_extension("tmp");
+        // Step 1: Create backup if file exists, plus a risk snapshot for
+        // flagged or large changes
+        let backup = if path.exists() {
+            tracing::debug!("Creating backup before writing: {}", path.display());
+            let risk = options
+                .risk
+                .or_else(|| large_change(path, config, options.large_change_threshold));
+            Some(self.timings.time("backup", || {
+                if let Some(operation) = risk {
+                    self.backup_manager.create_risk_snapshot(path, operation)?;
+                }
+                self.backup_manager.create_backup(path)
+            })?)
+        } else {
+            None
+        };
 
-        // Write to temp file
-        {
-            let mut file = File::create(&temp_path)
-                .map_err(|e| ConfigError::filesystem("create temp file", &temp_path, e))?;
+        // Step 2: Validate configuration
+        self.timings.time("validate", || validate_config(config))?;
+
+        self.timings.time("write", || {
+            // Step 3: Serialize configuration (sorted keys, so output is deterministic)
+            let json = if strict {
+                normalize::canonical_json_strict(config)?
+            } else {
+                normalize::canonical_json(config)?
+            };
+            #[cfg(test)]
+            let json = corrupt_for_test(json);
+
+            // Step 4: Atomic write using temp file
+            self.atomic_write(path, &json)?;
+
+            // Step 5: Make sure the file reads back as what we meant to write
+            if options.verify {
+                verify_written(path, config, backup.as_deref())
+            } else {
+                Ok(())
+            }
+        })?;
 
-            file.write_all(content.as_bytes())
-                .map_err(|e| ConfigError::filesystem("write to temp file", &temp_path, e))?;
+        tracing::debug!("Wrote configuration to: {}", path.display());
 
-            file.flush()
-                .map_err(|e| ConfigError::filesystem("flush temp file", &temp_path, e))?;
+        // The write already succeeded, so a failed prune is only worth a warning
+        if backup.is_some() {
+            let policy = self.backup_manager.retention_policy_for(config);
+            let pruned = self
+                .backup_manager
+                .cleanup_plan(path, &policy)
+                .and_then(|plan| execute_plan(&plan));
+            match pruned {
+                Ok(0) => {}
+                Ok(removed) => tracing::debug!(
+                    "Pruned {removed} old backup(s) of {} (keeping {})",
+                    path.display(),
+                    self.backup_manager.retention_count()
+                ),
+                Err(e) => tracing::warn!("Could not prune old backups: {e}"),
+            }
         }
 
-        // Atomic rename (temp -> target)
-        fs::rename(&temp_path, target).map_err(|e| {
-            // Clean up temp file on failure
-            let _ = fs::remove_file(&temp_path);
-            ConfigError::filesystem("atomic rename (temp to config)", target, e)
-        })?;
+        Ok(true)
+    }
 
-        Ok(())
+    /// Mark a config as changed without touching its content
+    ///
+    /// Sets the file's modification time to now, for tools that watch
+    /// mtimes. No backup is made, since the content stays the same.
+    ///
+    /// # Arguments
+    /// * `path` - Config file to touch
+    ///
+    /// # Errors
+    /// Returns an error if the file doesn't exist or its mtime can't be set
+    pub fn touch_config(&self, path: &Path) -> Result<()> {
+        if !path.is_file() {
+            return Err(ConfigError::not_found(path));
+        }
+        filetime::set_file_mtime(path, filetime::FileTime::now())
+            .map_err(|e| ConfigError::filesystem("update modification time", path, e))
+    }
+
+    /// Internal atomic write implementation
+    fn atomic_write(&self, target: &Path, content: &str) -> Result<()> {
+        atomic_write(target, content)
     }
 
     /// Get reference to backup manager
@@ -176,7 +512,7 @@ impl ConfigManager {
     /// - File exists but cannot be read
     /// - JSON is invalid
     pub fn get_global_config(&self) -> Result<crate::ClaudeConfig> {
-        let global_path = get_global_config_path();
+        let global_path = self.global_config_path();
 
         if !global_path.exists() {
             tracing::debug!("Global config not found, returning empty config");
@@ -186,9 +522,80 @@ impl ConfigManager {
         self.read_config(&global_path)
     }
 
+    /// Get the global configuration merged over the bases it extends
+    ///
+    /// See [`inherit`](crate::config::inherit). Without `ccm.extends` this
+    /// is the same as [`get_global_config`](Self::get_global_config).
+    ///
+    /// # Returns
+    /// The effective global config, and the bases it inherits from
+    ///
+    /// # Errors
+    /// Returns an error if the global config can't be read, or if its bases
+    /// form a cycle or too long a chain. Missing or invalid bases are only
+    /// warnings in the returned chain.
+    pub fn get_inherited_global_config(&self) -> Result<(crate::ClaudeConfig, BaseChain)> {
+        let global = self.get_global_config()?;
+        let chain = self.base_chain(&global)?;
+        Ok((chain.apply(&global, self.merge_options), chain))
+    }
+
+    /// Resolve the bases a global config extends
+    ///
+    /// Remote bases are cached in the `bases` folder next to the global
+    /// config. Warnings about bases that weren't loaded are also logged.
+    ///
+    /// # Errors
+    /// Returns an error if the bases form a cycle or too long a chain
+    pub fn base_chain(&self, global: &crate::ClaudeConfig) -> Result<BaseChain> {
+        if declared_base(global).is_none() {
+            return Ok(BaseChain::default());
+        }
+
+        let global_path = self.global_config_path();
+        let cache_dir = global_path
+            .parent()
+            .map(|dir| dir.join("bases"))
+            .unwrap_or_else(|| PathBuf::from("bases"));
+        let chain = BaseResolver::new()
+            .with_cache_dir(cache_dir)
+            .resolve(global, &global_path)?;
+        for warning in &chain.warnings {
+            tracing::warn!("{warning}");
+        }
+        Ok(chain)
+    }
+
+    /// Create the global config as an empty config if it doesn't exist
+    ///
+    /// # Returns
+    /// Path to the global config
+    ///
+    /// # Errors
+    /// Returns an error if the file can't be created
+    pub fn ensure_global_config(&self) -> Result<PathBuf> {
+        let global_path = self.global_config_path();
+        self.ensure_config(&global_path)?;
+        Ok(global_path)
+    }
+
+    /// Create `path` as an empty config if it doesn't exist
+    ///
+    /// # Returns
+    /// Whether the file was created
+    pub fn ensure_config(&self, path: &Path) -> Result<bool> {
+        if path.exists() {
+            return Ok(false);
+        }
+        self.write_config_with_backup(path, &crate::ClaudeConfig::new())?;
+        tracing::debug!("Created empty config: {}", path.display());
+        Ok(true)
+    }
+
     /// Get project configuration
     ///
-    /// Finds and reads the project-specific configuration.
+    /// Finds and reads the project-specific configuration. A config embedded in
+    /// `package.json` or `pyproject.toml` is merged below `.claude/config.json`.
     ///
     /// # Arguments
     /// * `project_path` - Path to the project directory (if None, searches upward from current dir)
@@ -204,26 +611,32 @@ impl ConfigManager {
         &self,
         project_path: Option<&Path>,
     ) -> Result<Option<crate::ClaudeConfig>> {
-        let config_path = if let Some(path) = project_path {
-            path.join(".claude").join("config.json")
-        } else {
+        let config_files = match project_path {
+            Some(path) => project_config_files_in(path),
             // Search upward from current directory
-            match find_project_config(None) {
-                Some(path) => path,
-                None => return Ok(None),
-            }
+            None => find_project_config_files(None),
         };
 
-        if !config_path.exists() {
-            return Ok(None);
+        // Files are ordered lowest precedence first, so a real
+        // .claude/config.json overrides an embedded manifest section
+        let mut merged: Option<crate::ClaudeConfig> = None;
+        for config_path in config_files {
+            let config = self.read_project_file(&config_path)?;
+
+            merged = Some(match merged {
+                Some(base) => merge_configs_with_options(&base, &config, self.merge_options),
+                None => config,
+            });
         }
 
-        self.read_config(&config_path).map(Some)
+        Ok(merged)
     }
 
     /// Get merged configuration
     ///
-    /// Merges global and project configurations, with project values taking precedence.
+    /// Merges global and project configurations, with project values taking
+    /// precedence. The global config is first merged over the bases it
+    /// extends (see [`get_inherited_global_config`](Self::get_inherited_global_config)).
     ///
     /// # Arguments
     /// * `project_path` - Path to the project directory (if None, searches upward from current dir)
@@ -235,23 +648,117 @@ impl ConfigManager {
     /// Returns an error if:
     /// - Either config file exists but cannot be read
     /// - JSON is invalid
+    /// - The global config's bases form a cycle or too long a chain
     pub fn get_merged_config(&self, project_path: Option<&Path>) -> Result<crate::ClaudeConfig> {
-        // Read global config (always present, may be empty)
-        let global_config = self.get_global_config()?;
+        self.get_merged_config_with_notes(project_path)
+            .map(|(config, _)| config)
+    }
+
+    /// Get merged configuration, with notes on what the merge discarded
+    ///
+    /// Same as [`get_merged_config`](Self::get_merged_config), plus a
+    /// [`MergeNote`] for each base that wasn't loaded (key path
+    /// `ccm.extends`) and for each global list the project replaces (see
+    /// [`merge_notes`]).
+    ///
+    /// # Errors
+    /// Same as [`get_merged_config`](Self::get_merged_config)
+    pub fn get_merged_config_with_notes(
+        &self,
+        project_path: Option<&Path>,
+    ) -> Result<(crate::ClaudeConfig, Vec<MergeNote>)> {
+        // Read global config (always present, may be empty) over its bases
+        let (global_config, chain) = self.get_inherited_global_config()?;
 
         // Try to read project config
         let project_config = self.get_project_config(project_path)?;
 
-        match project_config {
+        let (mut merged, mut notes) = match project_config {
             Some(proj) => {
                 // Merge: project config overrides global config
-                Ok(crate::config::merge::merge_configs(&global_config, &proj))
+                let merged = self.timings.time("merge", || {
+                    merge_configs_with_options(&global_config, &proj, self.merge_options)
+                });
+                let notes = merge_notes(&global_config, &proj);
+                (merged, notes)
             }
             None => {
                 // No project config, return global only
-                Ok(global_config)
+                (global_config, Vec::new())
             }
+        };
+
+        notes.splice(
+            0..0,
+            chain.warnings.iter().map(|warning| MergeNote {
+                key_path: "ccm.extends".to_string(),
+                message: warning.clone(),
+                suggestion: "Fix the base or remove 'ccm.extends'; the global and project settings still apply".to_string(),
+            }),
+        );
+
+        if let Some(platform) = self.platform {
+            merged.resolve_platform(platform);
         }
+        Ok((merged, notes))
+    }
+
+    /// Get the effective definition of an MCP server
+    ///
+    /// Resolves the server across scopes (project over global over base) and
+    /// reports which scope provided the winning definition.
+    ///
+    /// # Arguments
+    /// * `name` - Server name
+    /// * `project_path` - Path to the project directory (if None, searches upward from current dir)
+    ///
+    /// # Returns
+    /// The effective server and the scope that defines it
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - Either config file exists but cannot be read
+    /// - The server is not defined in any scope
+    pub fn get_effective_server(
+        &self,
+        name: &str,
+        project_path: Option<&Path>,
+    ) -> Result<(crate::McpServer, ConfigScope)> {
+        let server = self
+            .get_merged_config(project_path)?
+            .mcp_servers
+            .and_then(|mut servers| servers.remove(name))
+            .ok_or_else(|| {
+                ConfigError::mcp_server_error(
+                    name,
+                    "resolve",
+                    "Server is not defined in the global or project configuration",
+                )
+            })?;
+
+        let (_, source_map) = self.diff_configs(project_path)?;
+        let scope = match source_map.get(&format!("mcpServers.{name}")) {
+            Some(scope) => *scope,
+            // When both scopes define servers, the source map only records the
+            // whole map, so check which scope defines this server
+            None => {
+                let defines = |config: Option<crate::ClaudeConfig>| {
+                    config
+                        .and_then(|config| config.mcp_servers)
+                        .is_some_and(|servers| servers.contains_key(name))
+                };
+
+                if defines(self.get_project_config(project_path)?) {
+                    ConfigScope::Project
+                } else if defines(Some(self.get_global_config()?)) {
+                    ConfigScope::Global
+                } else {
+                    ConfigScope::Base
+                }
+            }
+        };
+
+        Ok((server, scope))
     }
 
     /// Update global configuration
@@ -262,7 +769,7 @@ impl ConfigManager {
     /// # Errors
     /// Returns an error if write fails
     pub fn update_global_config(&self, config: &crate::ClaudeConfig) -> Result<()> {
-        let global_path = get_global_config_path();
+        let global_path = self.global_config_path();
         self.write_config_with_backup(&global_path, config)
     }
 
@@ -279,177 +786,341 @@ impl ConfigManager {
         project_path: &Path,
         config: &crate::ClaudeConfig,
     ) -> Result<()> {
-        let config_path = project_path.join(".claude").join("config.json");
+        let config_path = project_config_path(project_path);
         self.write_config_with_backup(&config_path, config)
     }
 
-    /// Compute differences between global and project configurations
+    /// Move a project's config to a new project root
     ///
-    /// # Arguments
-    /// * `project_path` - Path to the project directory (if None, searches upward)
+    /// Used when a repository is relocated. The config is renamed into place
+    /// (or copied and removed when the roots are on different filesystems),
+    /// and the old `.claude` directory is removed if nothing else is left in
+    /// it. The config's history moves with it: backups in the old
+    /// `.claude/backups` directory move to the new one, and backups in this
+    /// manager's backup directory are re-keyed to the new path.
     ///
-    /// # Returns
-    /// List of differences and source map
+    /// # Arguments
+    /// * `old_root` - Current project root
+    /// * `new_root` - New project root (created if needed)
     ///
     /// # Errors
-    /// Returns an error if configs cannot be read
-    pub fn diff_configs(
-        &self,
-        project_path: Option<&Path>,
-    ) -> Result<(Vec<ConfigDiff>, SourceMap)> {
-        let global_config = self.get_global_config()?;
-        let project_config = self.get_project_config(project_path)?;
+    /// Returns an error if:
+    /// - The old project has no `.claude/config.json`
+    /// - The new project already has a config
+    /// - The file cannot be moved
+    pub fn relocate_project(&self, old_root: &Path, new_root: &Path) -> Result<()> {
+        let source_dir = old_root.join(".claude");
+        let source = project_config_path(old_root);
+        let target = project_config_path(new_root);
+
+        if !source.is_file() {
+            return Err(ConfigError::not_found(&source));
+        }
+        if target.exists() {
+            return Err(ConfigError::validation_failed(
+                "ProjectRelocation",
+                format!("{} already has a config", new_root.display()),
+                "Remove or merge the existing config before relocating",
+            ));
+        }
+        refuse_embedded_shadow(&target)?;
 
-        let global_json = serde_json::to_value(&global_config)?;
-        let project_json = serde_json::to_value(&project_config)?;
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| ConfigError::filesystem("create config directory", parent, e))?;
+        }
+
+        if fs::rename(&source, &target).is_err() {
+            // Renames fail across filesystems; fall back to copy and remove
+            let content = fs::read_to_string(&source)
+                .map_err(|e| ConfigError::filesystem("read config file", &source, e))?;
+            atomic_write(&target, &content)?;
+            fs::remove_file(&source)
+                .map_err(|e| ConfigError::filesystem("remove old config", &source, e))?;
+        }
 
-        let mut diffs = Vec::new();
-        let mut source_map = SourceMap::new();
+        self.backup_manager.rekey_backups(&source, &target)?;
+        let target_backups = new_root.join(".claude").join("backups");
+        let moved =
+            BackupManager::new(source_dir.join("backups"), None).move_backups(&target_backups)?;
+        if moved > 0 {
+            BackupManager::new(&target_backups, None).rekey_backups(&source, &target)?;
+        }
 
-        // Compare all keys
-        self.compare_values(
-            &global_json,
-            &project_json,
-            "",
-            &mut diffs,
-            &mut source_map,
-            ConfigScope::Global,
-        );
+        // Only succeeds if the directory is now empty
+        let _ = fs::remove_dir(&source_dir);
 
-        // Find additions (keys only in project)
-        self.find_additions(
-            &global_json,
-            &project_json,
-            "",
-            &mut diffs,
-            &mut source_map,
-            ConfigScope::Project,
+        tracing::info!(
+            "Relocated project config: {} -> {}",
+            source.display(),
+            target.display()
         );
 
-        Ok((diffs, source_map))
+        Ok(())
     }
 
-    /// Compare values between two configs
-    fn compare_values(
-        &self,
-        global: &serde_json::Value,
-        project: &serde_json::Value,
-        key_path: &str,
-        diffs: &mut Vec<ConfigDiff>,
-        source_map: &mut SourceMap,
-        global_scope: ConfigScope,
-    ) {
-        match (global, project) {
-            (Value::Object(global_map), Value::Object(project_map)) => {
-                // Process all keys in global
-                for (key, global_value) in global_map {
-                    let new_key_path = if key_path.is_empty() {
-                        key.clone()
-                    } else {
-                        format!("{key_path}.{key}")
-                    };
-
-                    if let Some(project_value) = project_map.get(key) {
-                        // Key exists in both - check if values differ
-                        if global_value != project_value {
-                            diffs.push(ConfigDiff::Modified {
-                                key_path: new_key_path.clone(),
-                                old_value: global_value.clone(),
-                                new_value: project_value.clone(),
-                            });
-                            source_map.insert(new_key_path.clone(), ConfigScope::Project);
-                        } else {
-                            // Values are the same - from global
-                            source_map.insert(new_key_path, global_scope);
-                        }
-                    } else {
-                        // Key only in global - removed in project
-                        diffs.push(ConfigDiff::Removed {
-                            key_path: new_key_path.clone(),
-                            value: global_value.clone(),
-                        });
-                        source_map.insert(new_key_path, ConfigScope::Global);
-                    }
-                }
-            }
-            (Value::Array(global_arr), Value::Array(project_arr)) => {
-                // Arrays use replace strategy - no deep comparison needed
-                if global_arr != project_arr {
-                    let new_key_path = if key_path.is_empty() {
-                        key_path.to_string()
-                    } else {
-                        key_path.to_string()
-                    };
-
-                    diffs.push(ConfigDiff::Modified {
-                        key_path: new_key_path.clone(),
-                        old_value: Value::Array(global_arr.clone()),
-                        new_value: Value::Array(project_arr.clone()),
-                    });
-                    source_map.insert(new_key_path, ConfigScope::Project);
-                }
-            }
-            _ => {
-                // Different types - treat as modification
-                if global != project {
-                    diffs.push(ConfigDiff::Modified {
-                        key_path: key_path.to_string(),
-                        old_value: global.clone(),
-                        new_value: project.clone(),
-                    });
-                    source_map.insert(key_path.to_string(), ConfigScope::Project);
-                }
-            }
+    /// List the effective MCP servers with the file that defines each
+    ///
+    /// Each server reports its origin scope and file, and when it last
+    /// changed according to the newest backups of that file (global
+    /// backups in this manager's backup directory, project backups in the
+    /// `backups` directory next to the project config). Scans are cached
+    /// while a file's servers and backups stay the same.
+    ///
+    /// # Arguments
+    /// * `project_path` - Path to the project directory (if None, searches upward)
+    ///
+    /// # Returns
+    /// The servers, sorted by name
+    ///
+    /// # Errors
+    /// Returns an error if either config file exists but cannot be read
+    pub fn list_servers_merged(&self, project_path: Option<&Path>) -> Result<Vec<MergedServer>> {
+        let global_path = self.global_config_path();
+        let global = self.get_global_config()?;
+        let chain = self.base_chain(&global)?;
+        let project = self.get_project_config(project_path)?;
+        let mut merged = merge_configs_with_options(
+            &chain.apply(&global, self.merge_options),
+            project.as_ref().unwrap_or(&crate::ClaudeConfig::new()),
+            self.merge_options,
+        );
+        if let Some(platform) = self.platform {
+            merged.resolve_platform(platform);
         }
-    }
 
-    /// Find keys that only exist in project (additions)
-    fn find_additions(
-        &self,
-        global: &serde_json::Value,
-        project: &serde_json::Value,
-        key_path: &str,
-        diffs: &mut Vec<ConfigDiff>,
-        source_map: &mut SourceMap,
-        project_scope: ConfigScope,
-    ) {
-        if let (Value::Object(global_map), Value::Object(project_map)) = (global, project) {
-            for (key, project_value) in project_map {
-                let new_key_path = if key_path.is_empty() {
-                    key.clone()
-                } else {
-                    format!("{key_path}.{key}")
+        let global_changes = self.change_cache.last_changed(
+            &global_path,
+            &global,
+            &self.backup_manager.list_backups(&global_path)?,
+        );
+        let project_file = project_config_file(project_path);
+        let project_origin = match (&project, project_file) {
+            (Some(project), Some(path)) => {
+                let backups = path
+                    .parent()
+                    .map(|dir| BackupManager::new(dir.join("backups"), None))
+                    .map(|manager| manager.list_backups(&path))
+                    .transpose()?
+                    .unwrap_or_default();
+                let changes = self.change_cache.last_changed(&path, project, &backups);
+                Some((project, path, changes))
+            }
+            _ => None,
+        };
+
+        let mut servers: Vec<MergedServer> = merged
+            .mcp_servers
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(name, mut server)| {
+                server.name = name.clone();
+                let defines = |config: &crate::ClaudeConfig| {
+                    config
+                        .mcp_servers
+                        .as_ref()
+                        .is_some_and(|servers| servers.contains_key(&name))
+                };
+                let (origin, origin_path, changes) = match &project_origin {
+                    Some((project, path, changes)) if defines(project) => {
+                        (ConfigScope::Project, path.clone(), Some(changes))
+                    }
+                    _ if defines(&global) || chain.is_empty() => (
+                        ConfigScope::Global,
+                        global_path.clone(),
+                        Some(&global_changes),
+                    ),
+                    // Bases have no backups to date changes from
+                    _ => {
+                        let base = chain.links.iter().find(|link| defines(&link.config));
+                        let path =
+                            base.map_or_else(|| global_path.clone(), |link| link.path.clone());
+                        (ConfigScope::Base, path, None)
+                    }
                 };
+                MergedServer {
+                    last_changed: changes.and_then(|changes| changes.get(&name).copied().flatten()),
+                    name,
+                    server,
+                    origin,
+                    origin_path,
+                }
+            })
+            .collect();
+        servers.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Ok(servers)
+    }
+
+    /// Compute differences between global and project configurations
+    ///
+    /// The global side includes the bases it extends. Key paths whose global
+    /// value is inherited from a base are attributed to [`ConfigScope::Base`].
+    ///
+    /// # Arguments
+    /// * `project_path` - Path to the project directory (if None, searches upward)
+    ///
+    /// # Returns
+    /// List of differences and source map
+    ///
+    /// # Errors
+    /// Returns an error if configs cannot be read
+    pub fn diff_configs(
+        &self,
+        project_path: Option<&Path>,
+    ) -> Result<(Vec<ConfigDiff>, SourceMap)> {
+        let global_config = self.get_global_config()?;
+        let chain = self.base_chain(&global_config)?;
+        let project_config = self.get_project_config(project_path)?;
+
+        let global_json = serde_json::to_value(chain.apply(&global_config, self.merge_options))?;
+        let project_json = serde_json::to_value(&project_config)?;
 
-                if !global_map.contains_key(key) {
-                    // Key only in project - addition
-                    diffs.push(ConfigDiff::Added {
-                        key_path: new_key_path.clone(),
-                        value: project_value.clone(),
-                    });
-                    source_map.insert(new_key_path.clone(), project_scope);
-
-                    // Recurse into nested objects for additions
-                    if let Value::Object(nested_project) = project_value {
-                        let empty_object = Value::Object(Default::default());
-                        let global_nested_ref = global_map.get(key).unwrap_or(&empty_object);
-
-                        if let Value::Object(nested_global) = global_nested_ref {
-                            let global_value = Value::Object(nested_global.clone());
-                            let project_value = Value::Object(nested_project.clone());
-                            self.find_additions(
-                                &global_value,
-                                &project_value,
-                                &new_key_path,
-                                diffs,
-                                source_map,
-                                project_scope,
-                            );
-                        }
+        let (diffs, mut source_map) = diff::diff_values(&global_json, &project_json);
+        if let Some(base) = chain.merged(self.merge_options) {
+            let (_, inherited) = merge_all_with_sources(&[
+                (ConfigScope::Base, &base),
+                (ConfigScope::Global, &global_config),
+            ]);
+            for (key_path, scope) in source_map.sources.iter_mut() {
+                if *scope == ConfigScope::Global
+                    && inherited.lookup(key_path) == Some(&ConfigScope::Base)
+                {
+                    *scope = ConfigScope::Base;
+                }
+            }
+        }
+
+        Ok((diffs, source_map))
+    }
+
+    /// Compute differences between two config files
+    ///
+    /// `base_path` is the old side (attributed to [`ConfigScope::Global`] in
+    /// the source map) and `config_path` the new side
+    /// ([`ConfigScope::Project`]), as in [`diff_configs`](Self::diff_configs).
+    ///
+    /// # Errors
+    /// Returns an error if either file cannot be read or parsed
+    pub fn diff_files(
+        &self,
+        base_path: &Path,
+        config_path: &Path,
+    ) -> Result<(Vec<ConfigDiff>, SourceMap)> {
+        let config = self.read_config(config_path)?;
+        self.diff_against_file(base_path, &config)
+    }
+
+    /// Compute differences between a config file and an in-memory config
+    ///
+    /// Like [`diff_files`](Self::diff_files), for configs that were resolved
+    /// or merged rather than read from one file.
+    ///
+    /// # Errors
+    /// Returns an error if the base file cannot be read or parsed
+    pub fn diff_against_file(
+        &self,
+        base_path: &Path,
+        config: &crate::ClaudeConfig,
+    ) -> Result<(Vec<ConfigDiff>, SourceMap)> {
+        let base = self.read_config(base_path)?;
+        Ok(diff::diff_values(
+            &serde_json::to_value(&base)?,
+            &serde_json::to_value(config)?,
+        ))
+    }
+
+    /// Restore selected keys from a backup onto the current config
+    ///
+    /// Shorthand for [`restore_keys_with_options`](Self::restore_keys_with_options)
+    /// with default options: keys missing from the backup are left alone.
+    ///
+    /// # Returns
+    /// The changes made to the target config
+    pub fn restore_keys(
+        &self,
+        backup_path: &Path,
+        target_path: &Path,
+        keys: &[String],
+    ) -> Result<Vec<ConfigDiff>> {
+        let restore = self.restore_keys_with_options(
+            backup_path,
+            target_path,
+            keys,
+            RestoreKeysOptions::default(),
+        )?;
+        for key in &restore.missing {
+            tracing::warn!("'{key}' is not in backup {}", backup_path.display());
+        }
+        Ok(restore.diffs)
+    }
+
+    /// Restore selected keys from a backup onto the current config
+    ///
+    /// Each key path (e.g. `mcpServers.fetch` or `customInstructions`) is
+    /// copied from the backup over the same path in the target config.
+    /// Everything else in the target is left as it is. The result is
+    /// validated and written with a backup of the current file.
+    ///
+    /// # Arguments
+    /// * `backup_path` - Backup to take the values from (may be compressed)
+    /// * `target_path` - Config file to update (treated as empty if missing)
+    /// * `keys` - Dotted key paths to restore
+    /// * `options` - Whether to remove keys missing from the backup, and
+    ///   whether to write at all
+    ///
+    /// # Returns
+    /// The changes to the target config and the keys the backup doesn't have
+    ///
+    /// # Errors
+    /// Returns an error if either config can't be read, or if the result is
+    /// not a valid config
+    pub fn restore_keys_with_options(
+        &self,
+        backup_path: &Path,
+        target_path: &Path,
+        keys: &[String],
+        options: RestoreKeysOptions,
+    ) -> Result<KeyRestore> {
+        let backup_json = serde_json::to_value(read_backup(backup_path)?)?;
+        let current = if target_path.exists() {
+            self.read_config(target_path)?
+        } else {
+            crate::ClaudeConfig::new()
+        };
+        let current_json = serde_json::to_value(&current)?;
+
+        let mut restored_json = current_json.clone();
+        let mut missing = Vec::new();
+        for key in keys {
+            match key_path::get(&backup_json, key) {
+                Some(value) => key_path::set(&mut restored_json, key, value.clone()),
+                None => {
+                    if options.allow_remove {
+                        key_path::remove(&mut restored_json, key);
                     }
+                    missing.push(key.clone());
                 }
             }
         }
+
+        let restored: crate::ClaudeConfig =
+            serde_json::from_value(restored_json.clone()).map_err(|e| {
+                ConfigError::validation_failed(
+                    "restore",
+                    format!("restored keys don't form a valid config: {e}"),
+                    "Restore whole sections (e.g. mcpServers.<name>) rather than single fields",
+                )
+            })?;
+        let (diffs, _) = diff::diff_values(&current_json, &restored_json);
+
+        if options.dry_run || diffs.is_empty() {
+            validate_config(&restored)?;
+        } else {
+            self.write_config_with_backup(target_path, &restored)?;
+        }
+
+        Ok(KeyRestore { diffs, missing })
     }
 
     /// Search configuration for matching keys and/or values
@@ -494,7 +1165,7 @@ impl ConfigManager {
         // Search based on scope
         match scope {
             ConfigScope::Global => {
-                let global_path = get_global_config_path();
+                let global_path = self.global_config_path();
                 if global_path.exists() {
                     if let Ok(config) = self.read_config(&global_path) {
                         let searcher = ConfigSearcher::with_options(options.clone());
@@ -515,11 +1186,159 @@ impl ConfigManager {
                     }
                 }
             }
+            ConfigScope::Base => {
+                // Search each base the global config extends, nearest first
+                let chain = self.base_chain(&self.get_global_config()?)?;
+                let searcher = ConfigSearcher::with_options(options.clone());
+                for link in chain.links {
+                    let results =
+                        searcher.search(query, &link.config, ConfigScope::Base, link.path)?;
+                    all_results.extend(results);
+                }
+            }
         }
 
         Ok(all_results)
     }
 
+    /// Search the global config and, if there is one, the project config
+    ///
+    /// Each file is searched on its own, so a global value the project
+    /// overrides is found too. Every hit carries the scope and path of the
+    /// file it was found in.
+    ///
+    /// # Arguments
+    /// * `query` - Search query string
+    /// * `project_path` - Project directory (if None, searches upward from the current dir)
+    /// * `options` - Search options
+    ///
+    /// # Returns
+    /// Global hits first, then project hits
+    ///
+    /// # Errors
+    /// Returns an error if either config exists but cannot be read
+    pub fn search_all(
+        &self,
+        query: &str,
+        project_path: Option<&Path>,
+        options: SearchOptions,
+    ) -> Result<Vec<SearchResult>> {
+        let searcher = ConfigSearcher::with_options(options);
+        let mut results = searcher.search(
+            query,
+            &self.get_global_config()?,
+            ConfigScope::Global,
+            self.global_config_path(),
+        )?;
+
+        if let Some((project, project_config_path)) = self.project_config_and_path(project_path)? {
+            results.extend(searcher.search(
+                query,
+                &project,
+                ConfigScope::Project,
+                project_config_path,
+            )?);
+        }
+
+        Ok(results)
+    }
+
+    /// Find subtrees of the global and project configs matching a query
+    ///
+    /// Like [`search_all`](Self::search_all), but evaluates the structured
+    /// query in `options.clauses` (see [`ConfigSearcher::search_where`]).
+    ///
+    /// # Arguments
+    /// * `project_path` - Project directory (if None, searches upward from the current dir)
+    /// * `options` - Search options with at least one clause
+    ///
+    /// # Returns
+    /// Global matches first, then project matches
+    ///
+    /// # Errors
+    /// Returns an error if either config exists but cannot be read, or the
+    /// query is invalid
+    pub fn search_all_where(
+        &self,
+        project_path: Option<&Path>,
+        options: SearchOptions,
+    ) -> Result<Vec<QueryMatch>> {
+        let searcher = ConfigSearcher::with_options(options);
+        let mut results = searcher.search_where(
+            &self.get_global_config()?,
+            ConfigScope::Global,
+            self.global_config_path(),
+        )?;
+
+        if let Some((project, project_config_path)) = self.project_config_and_path(project_path)? {
+            results.extend(searcher.search_where(
+                &project,
+                ConfigScope::Project,
+                project_config_path,
+            )?);
+        }
+
+        Ok(results)
+    }
+
+    /// Search the effective (merged) configuration
+    ///
+    /// Each hit is attributed to the scope its value comes from: project if
+    /// the project config sets that key path, global otherwise.
+    ///
+    /// # Arguments
+    /// * `query` - Search query string
+    /// * `project_path` - Project directory (if None, searches upward from the current dir)
+    /// * `options` - Search options
+    ///
+    /// # Errors
+    /// Returns an error if either config exists but cannot be read
+    pub fn search_effective_with_options(
+        &self,
+        query: &str,
+        project_path: Option<&Path>,
+        options: SearchOptions,
+    ) -> Result<Vec<SearchResult>> {
+        let merged = self.get_merged_config(project_path)?;
+        let project = self.project_config_and_path(project_path)?;
+
+        let searcher = ConfigSearcher::with_options(options);
+        let mut results = searcher.search(
+            query,
+            &merged,
+            ConfigScope::Global,
+            self.global_config_path(),
+        )?;
+        if let Some((project, project_config_path)) = project {
+            let project_json = serde_json::to_value(project)?;
+            for result in &mut results {
+                if key_path::get_element(&project_json, &result.key_path).is_some() {
+                    result.source = ConfigScope::Project;
+                    result.config_path = project_config_path.clone();
+                }
+            }
+        }
+
+        Ok(results)
+    }
+
+    /// The project config for `project_path` and the path of its
+    /// highest-precedence file
+    ///
+    /// `None` if no project config is found, so searches skip the project
+    /// scope rather than attributing hits to an empty path.
+    fn project_config_and_path(
+        &self,
+        project_path: Option<&Path>,
+    ) -> Result<Option<(crate::ClaudeConfig, PathBuf)>> {
+        let Some(config_path) = project_config_file(project_path) else {
+            return Ok(None);
+        };
+        Ok(self
+            .get_project_config(project_path)?
+            .map(|config| (config, config_path)))
+    }
+
     /// Export configuration to a file
     ///
     /// # Arguments
@@ -557,16 +1376,34 @@ impl ConfigManager {
     /// * `options` - Export options
     ///
     /// # Returns
-    /// Path to the exported file
+    /// The exported file and its content hash
     pub fn export_config_with_options(
         &self,
         config: &crate::ClaudeConfig,
         path: &Path,
         options: crate::ImportExportOptions,
-    ) -> Result<PathBuf> {
+    ) -> Result<crate::ExportOutcome> {
         crate::ConfigImporter::export_config(config, path, &options)
     }
 
+    /// Export configuration to a writer (e.g. stdout)
+    ///
+    /// # Arguments
+    /// * `config` - Configuration to export
+    /// * `writer` - Destination
+    /// * `options` - Export options (`overwrite` and `auto_name` don't apply)
+    ///
+    /// # Returns
+    /// Content hash of the serialized configuration
+    pub fn export_config_to_writer<W: Write>(
+        &self,
+        config: &crate::ClaudeConfig,
+        writer: &mut W,
+        options: crate::ImportExportOptions,
+    ) -> Result<String> {
+        crate::ConfigImporter::export_to_writer(config, writer, &options)
+    }
+
     /// Import configuration with custom options
     ///
     /// # Arguments
@@ -582,119 +1419,541 @@ impl ConfigManager {
     ) -> Result<crate::ClaudeConfig> {
         crate::ConfigImporter::import_config(path, &options)
     }
-}
 
-/// Parse JSON error location from error message
-///
-/// Extracts line and column numbers from serde_json error messages.
-/// Returns (0, 0) if location cannot be determined.
-fn parse_json_error_location(error_msg: &str) -> (usize, usize) {
-    // Typical serde_json error format: "key error at line X, column Y"
-    if let Some(line_pos) = error_msg.find("line ") {
-        if let Some(colon_pos) = error_msg[line_pos + 5..].find(',') {
-            if let Ok(line) = error_msg[line_pos + 5..line_pos + colon_pos].parse::<usize>() {
-                if let Some(col_pos) = error_msg.find("column ") {
-                    if let Some(end) = error_msg[col_pos + 7..].find(',') {
-                        if let Ok(column) =
-                            error_msg[col_pos + 7..col_pos + 7 + end].parse::<usize>()
-                        {
-                            return (line, column);
-                        }
-                    }
-                }
-            }
-        }
+    /// Import configuration from a file in `options.format`, ignoring its
+    /// extension
+    ///
+    /// # Arguments
+    /// * `path` - Source file path
+    /// * `options` - Import options
+    ///
+    /// # Returns
+    /// Imported configuration
+    pub fn import_config_as(
+        &self,
+        path: &Path,
+        options: crate::ImportExportOptions,
+    ) -> Result<crate::ClaudeConfig> {
+        crate::ConfigImporter::import_config_as(path, &options)
+    }
+
+    /// Import configuration in `options.format` from a reader (e.g. stdin)
+    ///
+    /// # Arguments
+    /// * `reader` - Source, read to the end
+    /// * `options` - Import options
+    ///
+    /// # Returns
+    /// Imported configuration
+    pub fn import_config_from_reader<R: std::io::Read>(
+        &self,
+        reader: &mut R,
+        options: crate::ImportExportOptions,
+    ) -> Result<crate::ClaudeConfig> {
+        crate::ConfigImporter::import_from_reader(reader, &options)
     }
-    (0, 0)
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use tempfile::TempDir;
+/// Options for [`ConfigManager::write_config_with_options`]
+#[derive(Debug, Clone, Copy)]
+pub struct WriteOptions {
+    /// Read the file back after writing and compare it with the config
+    pub verify: bool,
 
-    // TDD Test 1: Read valid config
-    #[test]
-    fn test_read_valid_config() {
-        let temp_dir = TempDir::new().unwrap();
-        let config_path = temp_dir.path().join("config.json");
-        let backup_dir = temp_dir.path().join("backups");
+    /// Write (and back up) even if the file already holds the config
+    pub force: bool,
 
-        // Create valid config file
-        let config_content = r#"{
-            "mcpServers": {
-                "npx": {
-                    "enabled": true,
-                    "command": "npx",
-                    "args": []
-                }
-            }
-        }"#;
-        fs::write(&config_path, config_content).unwrap();
+    /// Remove exact-duplicate `customInstructions` entries before writing
+    pub dedupe_instructions: bool,
 
-        let manager = ConfigManager::new(&backup_dir);
-        let config = manager.read_config(&config_path).unwrap();
+    /// Write in strict canonical form, even if the config doesn't opt in
+    pub canonical: bool,
 
-        assert!(config.mcp_servers.is_some());
-        assert_eq!(config.mcp_servers.unwrap().len(), 1);
-    }
+    /// Take a risk snapshot tagged with this operation before writing
+    pub risk: Option<RiskyOperation>,
 
-    // TDD Test 2: Read nonexistent file returns proper error
-    #[test]
-    fn test_read_nonexistent_file() {
-        let temp_dir = TempDir::new().unwrap();
-        let config_path = temp_dir.path().join("nonexistent.json");
-        let backup_dir = temp_dir.path().join("backups");
+    /// Take a [`RiskyOperation::LargeChange`] snapshot when a write changes
+    /// more key paths than this (`None` turns the check off)
+    pub large_change_threshold: Option<usize>,
+}
 
-        let manager = ConfigManager::new(&backup_dir);
-        let result = manager.read_config(&config_path);
+impl Default for WriteOptions {
+    fn default() -> Self {
+        Self {
+            verify: true,
+            force: false,
+            dedupe_instructions: false,
+            canonical: false,
+            risk: None,
+            large_change_threshold: Some(LARGE_CHANGE_KEY_PATHS),
+        }
+    }
+}
 
-        assert!(result.is_err());
-        let err = result.unwrap_err();
-        assert!(err.to_string().contains("not found"));
+impl WriteOptions {
+    /// Create default options (verification on, unchanged files skipped)
+    pub fn new() -> Self {
+        Self::default()
     }
 
-    // TDD Test 3: Read invalid JSON returns proper error
-    #[test]
-    fn test_read_invalid_json() {
-        let temp_dir = TempDir::new().unwrap();
-        let config_path = temp_dir.path().join("config.json");
-        let backup_dir = temp_dir.path().join("backups");
+    /// Turn read-back verification on or off
+    pub fn with_verify(mut self, verify: bool) -> Self {
+        self.verify = verify;
+        self
+    }
 
-        // Create invalid JSON
-        fs::write(&config_path, b"{invalid json}").unwrap();
+    /// Write even if the file already holds the config
+    pub fn with_force(mut self, force: bool) -> Self {
+        self.force = force;
+        self
+    }
 
-        let manager = ConfigManager::new(&backup_dir);
-        let result = manager.read_config(&config_path);
+    /// Remove repeated `customInstructions` entries (first occurrence wins)
+    pub fn with_dedupe_instructions(mut self, dedupe: bool) -> Self {
+        self.dedupe_instructions = dedupe;
+        self
+    }
 
-        assert!(result.is_err());
-        let err = result.unwrap_err();
-        let message = err.to_string();
-        assert!(message.contains("Invalid JSON"));
-        assert!(message.contains("line 1"));
+    /// Write in strict canonical form (see [`normalize::canonical_json_strict`])
+    pub fn with_canonical(mut self, canonical: bool) -> Self {
+        self.canonical = canonical;
+        self
     }
 
-    // TDD Test 4: Write config creates backup
-    #[test]
-    fn test_write_creates_backup() {
-        let temp_dir = TempDir::new().unwrap();
-        let config_path = temp_dir.path().join("config.json");
-        let backup_dir = temp_dir.path().join("backups");
+    /// Snapshot the file before writing, tagged as `operation`
+    pub fn with_risk(mut self, operation: RiskyOperation) -> Self {
+        self.risk = Some(operation);
+        self
+    }
 
-        // Create initial config
-        fs::write(&config_path, b"{}").unwrap();
+    /// Change (or with `None`, turn off) the large-change threshold
+    pub fn with_large_change_threshold(mut self, threshold: Option<usize>) -> Self {
+        self.large_change_threshold = threshold;
+        self
+    }
+}
 
-        let manager = ConfigManager::new(&backup_dir);
+/// Options for [`ConfigManager::restore_keys_with_options`]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RestoreKeysOptions {
+    /// Remove keys that are missing from the backup, so those paths match it
+    pub allow_remove: bool,
 
-        // Write new config
-        let config = crate::ClaudeConfig::new();
-        manager
-            .write_config_with_backup(&config_path, &config)
-            .unwrap();
+    /// Compute the changes without writing anything
+    pub dry_run: bool,
+}
 
-        // Verify backup was created
-        let backups = manager.backup_manager().list_backups(&config_path).unwrap();
-        assert_eq!(backups.len(), 1);
+impl RestoreKeysOptions {
+    /// Create default options
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Remove keys that are missing from the backup
+    pub fn with_allow_remove(mut self, allow_remove: bool) -> Self {
+        self.allow_remove = allow_remove;
+        self
+    }
+
+    /// Only compute the changes
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+}
+
+/// Outcome of restoring keys from a backup
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyRestore {
+    /// Changes to the target config (made, or to be made in a dry run)
+    pub diffs: Vec<ConfigDiff>,
+
+    /// Requested keys the backup doesn't contain
+    pub missing: Vec<String>,
+}
+
+/// Read and parse a configuration file
+///
+/// [`ConfigManager::read_config`] without a manager, and without recording a
+/// timing.
+pub(crate) fn read_config_file(path: &Path) -> Result<crate::ClaudeConfig> {
+    parse_config_file(path)
+}
+
+/// Read a configuration file and parse it as `T`
+///
+/// Parse errors report the line and column, with an excerpt and a hint for
+/// common mistakes.
+fn parse_config_file<T: DeserializeOwned>(path: &Path) -> Result<T> {
+    // Check if file exists
+    if !path.exists() {
+        return Err(ConfigError::not_found(path));
+    }
+
+    // Reject oversized files before reading anything
+    let size = limits::check_config_size(path)?;
+
+    let config = if limits::is_large_config(size) {
+        // Parse large files from a stream to avoid holding the text and
+        // the parsed config in memory at the same time
+        let file =
+            File::open(path).map_err(|e| ConfigError::filesystem("read config file", path, e))?;
+        serde_json::from_reader(BufReader::new(file)).map_err(|e| {
+            let excerpt = File::open(path)
+                .ok()
+                .and_then(|file| limits::error_excerpt(file, e.line(), e.column()));
+            let hint = File::open(path)
+                .ok()
+                .and_then(|file| json_hints::suggest_fix(file, e.line(), e.column()));
+            with_hint(
+                ConfigError::invalid_json(
+                    path,
+                    e.line(),
+                    e.column(),
+                    with_excerpt(e.to_string(), excerpt),
+                ),
+                hint,
+            )
+        })?
+    } else {
+        // Read file content
+        let content = fs::read_to_string(path)
+            .map_err(|e| ConfigError::filesystem("read config file", path, e))?;
+
+        // Parse JSON
+        serde_json::from_str(&content).map_err(|e| {
+            let error_str = e.to_string();
+            let (line, column) = (e.line(), e.column());
+            let excerpt = limits::error_excerpt(content.as_bytes(), line, column);
+            let hint = json_hints::suggest_fix(content.as_bytes(), line, column);
+
+            with_hint(
+                ConfigError::invalid_json(path, line, column, with_excerpt(error_str, excerpt)),
+                hint,
+            )
+        })?
+    };
+
+    tracing::debug!("Loaded configuration from: {}", path.display());
+
+    Ok(config)
+}
+
+/// [`RiskyOperation::LargeChange`] if writing `config` over the file at
+/// `path` changes more key paths than `threshold`
+///
+/// A file that can't be read isn't counted as a large change.
+fn large_change(
+    path: &Path,
+    config: &crate::ClaudeConfig,
+    threshold: Option<usize>,
+) -> Option<RiskyOperation> {
+    let threshold = threshold?;
+    let current = serde_json::to_value(read_config_file(path).ok()?).ok()?;
+    let (diffs, _) = diff::diff_values(&current, &serde_json::to_value(config).ok()?);
+    (diffs.len() > threshold).then_some(RiskyOperation::LargeChange)
+}
+
+/// Whether the file at `path` parses to a config equal to `config`
+///
+/// Compared as JSON values, so formatting and key order don't matter. A file
+/// that can't be read counts as different.
+fn holds_config(path: &Path, config: &crate::ClaudeConfig) -> bool {
+    let Ok(current) = read_config_file(path) else {
+        return false;
+    };
+    match (serde_json::to_value(&current), serde_json::to_value(config)) {
+        (Ok(current), Ok(new)) => current == new,
+        _ => false,
+    }
+}
+
+/// Write a file atomically
+///
+/// Uses write-then-rename pattern to ensure atomicity:
+/// 1. Write to temp file in same directory
+/// 2. Rename temp file to target (atomic on most filesystems)
+///
+/// An existing target keeps its permissions, so a private config stays
+/// private after the rename.
+pub(crate) fn atomic_write(target: &Path, content: impl AsRef<[u8]>) -> Result<()> {
+    // Ensure parent directory exists
+    if let Some(parent) = target.parent() {
+        if !parent.exists() {
+            fs::create_dir_all(parent)
+                .map_err(|e| ConfigError::filesystem("create config directory", parent, e))?;
+        }
+    }
+
+    // Create temp file path
+    let temp_path = target.with_extension("tmp");
+
+    // Write to temp file
+    {
+        let mut file = File::create(&temp_path)
+            .map_err(|e| ConfigError::filesystem("create temp file", &temp_path, e))?;
+
+        if target.exists() {
+            copy_permissions(target, &temp_path)
+                .map_err(|e| ConfigError::filesystem("copy permissions", &temp_path, e))?;
+        }
+
+        file.write_all(content.as_ref())
+            .map_err(|e| ConfigError::filesystem("write to temp file", &temp_path, e))?;
+
+        file.flush()
+            .map_err(|e| ConfigError::filesystem("flush temp file", &temp_path, e))?;
+    }
+
+    // Atomic rename (temp -> target)
+    fs::rename(&temp_path, target).map_err(|e| {
+        // Clean up temp file on failure
+        let _ = fs::remove_file(&temp_path);
+        ConfigError::filesystem("atomic rename (temp to config)", target, e)
+    })?;
+
+    Ok(())
+}
+
+/// Check that a just-written config reads back as `expected`
+///
+/// On a mismatch the previous content is restored from `backup` (or the
+/// file removed, if it didn't exist before) and the differences are
+/// reported.
+fn verify_written(
+    path: &Path,
+    expected: &crate::ClaudeConfig,
+    backup: Option<&Path>,
+) -> Result<()> {
+    // Round-trip the expected side too, so fields that aren't read from the
+    // file (e.g. server names) compare equal
+    let expected_json = serde_json::to_value(serde_json::from_value::<crate::ClaudeConfig>(
+        serde_json::to_value(expected)?,
+    )?)?;
+    let differences = match fs::read_to_string(path)
+        .map_err(|e| e.to_string())
+        .and_then(|content| {
+            serde_json::from_str::<crate::ClaudeConfig>(&content).map_err(|e| e.to_string())
+        })
+        .and_then(|written| serde_json::to_value(written).map_err(|e| e.to_string()))
+    {
+        Ok(written_json) if written_json == expected_json => return Ok(()),
+        Ok(written_json) => describe_diffs(&diff::diff_values(&expected_json, &written_json).0),
+        Err(e) => format!("  the written file could not be read back: {e}"),
+    };
+
+    let restored = match backup {
+        Some(backup) => fs::read_to_string(backup)
+            .map_err(|e| ConfigError::filesystem("read backup", backup, e))
+            .and_then(|content| atomic_write(path, &content)),
+        None => fs::remove_file(path)
+            .map_err(|e| ConfigError::filesystem("remove written config", path, e)),
+    };
+    let recovery = match (restored, backup) {
+        (Ok(()), Some(backup)) => {
+            format!(
+                "The previous content was restored from {}.",
+                backup.display()
+            )
+        }
+        (Ok(()), None) => "The file did not exist before and was removed.".to_string(),
+        (Err(e), Some(backup)) => format!(
+            "Restoring the previous content failed ({e}); restore it from {}.",
+            backup.display()
+        ),
+        (Err(e), None) => format!("Removing the written file failed ({e})."),
+    };
+
+    Err(ConfigError::WriteVerificationFailed {
+        path: path.to_path_buf(),
+        differences,
+        recovery,
+    })
+}
+
+/// One line per difference, as `+`/`-`/`~ key: value`
+fn describe_diffs(diffs: &[ConfigDiff]) -> String {
+    diffs
+        .iter()
+        .map(|diff| match diff {
+            ConfigDiff::Added { key_path, value } => format!("  + {key_path}: {value}"),
+            ConfigDiff::Removed { key_path, value } => format!("  - {key_path}: {value}"),
+            ConfigDiff::Modified {
+                key_path,
+                old_value,
+                new_value,
+            } => format!("  ~ {key_path}: expected {old_value}, read back {new_value}"),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Content rewriter used by the write test hook
+#[cfg(test)]
+type CorruptFn = fn(String) -> String;
+
+#[cfg(test)]
+thread_local! {
+    /// Test hook: rewrites serialized content before it is written
+    static CORRUPT_WRITES: std::cell::Cell<Option<CorruptFn>> =
+        const { std::cell::Cell::new(None) };
+}
+
+/// Apply the test hook, if one is set on this thread
+#[cfg(test)]
+fn corrupt_for_test(json: String) -> String {
+    match CORRUPT_WRITES.with(|hook| hook.get()) {
+        Some(corrupt) => corrupt(json),
+        None => json,
+    }
+}
+
+/// Refuse to create a project config next to an embedded one
+///
+/// Creating `.claude/config.json` would silently take over from the config
+/// embedded in the project's manifest, so users are asked to migrate instead.
+pub(crate) fn refuse_embedded_shadow(path: &Path) -> Result<()> {
+    let Some(project_dir) = path
+        .parent()
+        .filter(|dir| dir.ends_with(".claude") && path.ends_with("config.json"))
+        .and_then(Path::parent)
+    else {
+        return Ok(());
+    };
+
+    match embedded::find_embedded_config(project_dir) {
+        Some(embedded) => Err(ConfigError::validation_failed(
+            "EmbeddedConfig",
+            format!(
+                "Project config is embedded in {}, which ccm cannot modify",
+                embedded.describe()
+            ),
+            "Run 'ccm config migrate-embedded' to move it into .claude/config.json first",
+        )),
+        None => Ok(()),
+    }
+}
+
+/// The project config file [`ConfigManager::get_project_config`] reads last,
+/// which takes precedence, if any
+fn project_config_file(project_path: Option<&Path>) -> Option<PathBuf> {
+    match project_path {
+        Some(path) => project_config_files_in(path).pop(),
+        None => find_project_config_files(None).pop(),
+    }
+}
+
+/// Add a targeted hint to the generic suggestion of a parse error
+pub(crate) fn with_hint(error: ConfigError, hint: Option<String>) -> ConfigError {
+    match hint {
+        Some(hint) => error.with_suggestion(hint),
+        None => error,
+    }
+}
+
+/// Append a source excerpt (if any) to a parse error message
+pub(crate) fn with_excerpt(message: String, excerpt: Option<String>) -> String {
+    match excerpt {
+        Some(excerpt) => format!("{message}\n\n{excerpt}"),
+        None => message,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    /// Number of backup files in `dir`, leaving out its origin records
+    fn backup_files(dir: &Path) -> usize {
+        fs::read_dir(dir)
+            .unwrap()
+            .filter(|entry| entry.as_ref().is_ok_and(|e| e.path().is_file()))
+            .count()
+    }
+
+    // TDD Test 1: Read valid config
+    #[test]
+    fn test_read_valid_config() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("config.json");
+        let backup_dir = temp_dir.path().join("backups");
+
+        // Create valid config file
+        let config_content = r#"{
+            "mcpServers": {
+                "npx": {
+                    "enabled": true,
+                    "command": "npx",
+                    "args": []
+                }
+            }
+        }"#;
+        fs::write(&config_path, config_content).unwrap();
+
+        let manager = ConfigManager::new(&backup_dir);
+        let config = manager.read_config(&config_path).unwrap();
+
+        assert!(config.mcp_servers.is_some());
+        assert_eq!(config.mcp_servers.unwrap().len(), 1);
+    }
+
+    // TDD Test 2: Read nonexistent file returns proper error
+    #[test]
+    fn test_read_nonexistent_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("nonexistent.json");
+        let backup_dir = temp_dir.path().join("backups");
+
+        let manager = ConfigManager::new(&backup_dir);
+        let result = manager.read_config(&config_path);
+
+        assert!(result.is_err());
+        let err = result.unwrap_err();
+        assert!(err.to_string().contains("not found"));
+    }
+
+    // TDD Test 3: Read invalid JSON returns proper error
+    #[test]
+    fn test_read_invalid_json() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("config.json");
+        let backup_dir = temp_dir.path().join("backups");
+
+        // Create invalid JSON
+        fs::write(&config_path, b"{invalid json}").unwrap();
+
+        let manager = ConfigManager::new(&backup_dir);
+        let result = manager.read_config(&config_path);
+
+        assert!(result.is_err());
+        let err = result.unwrap_err();
+        let message = err.to_string();
+        assert!(message.contains("Invalid JSON"));
+        assert!(message.contains("line 1"));
+    }
+
+    // TDD Test 4: Write config creates backup
+    #[test]
+    fn test_write_creates_backup() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("config.json");
+        let backup_dir = temp_dir.path().join("backups");
+
+        // Create initial config
+        fs::write(&config_path, br#"{"allowedPaths": ["~/old"]}"#).unwrap();
+
+        let manager = ConfigManager::new(&backup_dir);
+
+        // Write new config
+        let config = crate::ClaudeConfig::new();
+        manager
+            .write_config_with_backup(&config_path, &config)
+            .unwrap();
+
+        // Verify backup was created
+        let backups = manager.backup_manager().list_backups(&config_path).unwrap();
+        assert_eq!(backups.len(), 1);
     }
 
     // TDD Test 5: Write validates config
@@ -1008,4 +2267,1307 @@ mod tests {
         assert!(servers.contains_key("npx"));
         assert!(servers.contains_key("uvx"));
     }
+
+    // TDD Test 17: Effective server comes from project when it overrides global
+    #[test]
+    fn test_get_effective_server_project_override() {
+        let temp_dir = TempDir::new().unwrap();
+        let global_path = temp_dir.path().join("global.json");
+        let project_dir = temp_dir.path().join("project");
+        let manager =
+            ConfigManager::with_custom_global_config(temp_dir.path().join("backups"), &global_path);
+
+        let global_config = crate::ClaudeConfig::new()
+            .with_mcp_server(
+                "npx",
+                crate::McpServer::new("npx", "npx", vec!["-y".to_string()]),
+            )
+            .with_mcp_server("uvx", crate::McpServer::new("uvx", "uvx", vec![]));
+        let project_config = crate::ClaudeConfig::new().with_mcp_server(
+            "npx",
+            crate::McpServer::new("npx", "npx", vec!["--project".to_string()]),
+        );
+
+        manager
+            .write_config_with_backup(&global_path, &global_config)
+            .unwrap();
+        manager
+            .update_project_config(&project_dir, &project_config)
+            .unwrap();
+
+        let (server, scope) = manager
+            .get_effective_server("npx", Some(&project_dir))
+            .unwrap();
+        assert_eq!(server.args, vec!["--project".to_string()]);
+        assert_eq!(scope, ConfigScope::Project);
+
+        let (server, scope) = manager
+            .get_effective_server("uvx", Some(&project_dir))
+            .unwrap();
+        assert_eq!(server.command.as_deref(), Some("uvx"));
+        assert_eq!(scope, ConfigScope::Global);
+
+        assert!(manager
+            .get_effective_server("missing", Some(&project_dir))
+            .is_err());
+    }
+
+    // TDD Test 18: Embedded manifest config is merged below .claude/config.json
+    #[test]
+    fn test_get_project_config_embedded_precedence() {
+        let temp_dir = TempDir::new().unwrap();
+        let project_dir = temp_dir.path().join("project");
+        fs::create_dir_all(&project_dir).unwrap();
+        fs::write(
+            project_dir.join("package.json"),
+            r#"{"name": "web", "claude": {"allowedPaths": ["./embedded"], "customInstructions": ["from manifest"]}}"#,
+        )
+        .unwrap();
+        let manager = ConfigManager::new(temp_dir.path().join("backups"));
+
+        // Embedded only
+        let config = manager
+            .get_project_config(Some(&project_dir))
+            .unwrap()
+            .unwrap();
+        assert_eq!(config.allowed_paths, Some(vec!["./embedded".to_string()]));
+
+        // A real config file overrides the embedded section
+        fs::create_dir_all(project_dir.join(".claude")).unwrap();
+        fs::write(
+            project_dir.join(".claude").join("config.json"),
+            r#"{"allowedPaths": ["./real"]}"#,
+        )
+        .unwrap();
+        let config = manager
+            .get_project_config(Some(&project_dir))
+            .unwrap()
+            .unwrap();
+        assert_eq!(config.allowed_paths, Some(vec!["./real".to_string()]));
+        assert_eq!(
+            config.custom_instructions,
+            Some(vec!["from manifest".to_string()])
+        );
+    }
+
+    // TDD Test 19: Writing a project config next to an embedded one is refused
+    #[test]
+    fn test_write_refuses_to_shadow_embedded_config() {
+        let temp_dir = TempDir::new().unwrap();
+        let project_dir = temp_dir.path().join("project");
+        fs::create_dir_all(&project_dir).unwrap();
+        fs::write(
+            project_dir.join("pyproject.toml"),
+            "[tool.claude]\nallowedPaths = [\"./src\"]\n",
+        )
+        .unwrap();
+        let manager = ConfigManager::new(temp_dir.path().join("backups"));
+
+        let result = manager.update_project_config(&project_dir, &crate::ClaudeConfig::new());
+
+        let message = result.unwrap_err().to_string();
+        assert!(message.contains("migrate-embedded"));
+        assert!(!project_dir.join(".claude").join("config.json").exists());
+    }
+
+    // TDD Test 20: Write with backup records backup, validate and write timings
+    #[test]
+    fn test_write_with_backup_records_timings() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("config.json");
+        fs::write(&config_path, r#"{"allowedPaths": ["~/old"]}"#).unwrap();
+        let timings = crate::Timings::new();
+        let manager =
+            ConfigManager::new(temp_dir.path().join("backups")).with_timings(timings.clone());
+
+        manager
+            .write_config_with_backup(&config_path, &crate::ClaudeConfig::new())
+            .unwrap();
+        manager.read_config(&config_path).unwrap();
+
+        let names: Vec<_> = timings.entries().iter().map(|e| e.name).collect();
+        assert_eq!(names, vec!["backup", "validate", "write", "read"]);
+
+        // Disabled collectors (the default) record nothing
+        let manager = ConfigManager::new(temp_dir.path().join("backups"));
+        manager
+            .write_config_with_backup(&config_path, &crate::ClaudeConfig::new())
+            .unwrap();
+        assert!(!manager.timings().is_enabled());
+        assert!(manager.timings().entries().is_empty());
+    }
+
+    // TDD Test 21: Reading several configs keeps going past a corrupt one
+    #[test]
+    fn test_read_configs_collects_failures() {
+        let temp_dir = TempDir::new().unwrap();
+        let good_a = temp_dir.path().join("a.json");
+        let bad = temp_dir.path().join("b.json");
+        let good_c = temp_dir.path().join("c.json");
+        fs::write(&good_a, r#"{"customInstructions": ["a"]}"#).unwrap();
+        fs::write(&bad, "{ not json").unwrap();
+        fs::write(&good_c, "{}").unwrap();
+        let manager = ConfigManager::new(temp_dir.path().join("backups"));
+
+        let result = manager.read_configs([&good_a, &bad, &good_c]);
+
+        assert_eq!(result.successes.len(), 2);
+        assert_eq!(result.successes[0].0, good_a);
+        assert_eq!(result.successes[1].0, good_c);
+        assert_eq!(result.failures.len(), 1);
+        assert_eq!(result.failures[0].0, bad);
+        assert!(matches!(
+            result.failures[0].1,
+            ConfigError::InvalidFormat { .. }
+        ));
+    }
+
+    // TDD Test 22: Parse errors suggest fixes for common JSON mistakes
+    #[test]
+    fn test_read_invalid_json_suggests_fixes() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("config.json");
+        let manager = ConfigManager::new(temp_dir.path().join("backups"));
+
+        fs::write(
+            &config_path,
+            "{\n  \"allowedPaths\": [\"~/projects\"],\n  \"customInstructions\": [],\n}\n",
+        )
+        .unwrap();
+        let message = manager.read_config(&config_path).unwrap_err().to_string();
+        assert!(message.contains("Error at line 4, column 1"));
+        assert!(message.contains("Suggestion: Check JSON syntax"));
+        assert!(message.contains("\nRemove the trailing comma after line 3"));
+
+        fs::write(&config_path, "{\n  'allowedPaths': ['~/projects']\n}\n").unwrap();
+        let message = manager.read_config(&config_path).unwrap_err().to_string();
+        assert!(message.contains("Error at line 2"));
+        assert!(message.contains("\nUse double quotes instead of single quotes"));
+    }
+
+    // TDD Test 23: Relocating a project moves its config content
+    #[test]
+    fn test_relocate_project_preserves_config() {
+        let temp_dir = TempDir::new().unwrap();
+        let old_root = temp_dir.path().join("old").join("repo");
+        let new_root = temp_dir.path().join("new").join("nested").join("repo");
+        let old_config = old_root.join(".claude").join("config.json");
+        fs::create_dir_all(old_config.parent().unwrap()).unwrap();
+        let content = r#"{"customInstructions": ["moved with the repo"]}"#;
+        fs::write(&old_config, content).unwrap();
+        let manager = ConfigManager::new(temp_dir.path().join("backups"));
+
+        manager.relocate_project(&old_root, &new_root).unwrap();
+
+        let new_config = new_root.join(".claude").join("config.json");
+        assert_eq!(fs::read_to_string(&new_config).unwrap(), content);
+        assert!(!old_config.exists());
+        assert!(!old_root.join(".claude").exists());
+
+        // Missing source config and an existing target are errors
+        let result = manager.relocate_project(&old_root, &new_root);
+        assert!(matches!(result, Err(ConfigError::NotFound { .. })));
+
+        fs::create_dir_all(old_config.parent().unwrap()).unwrap();
+        fs::write(&old_config, "{}").unwrap();
+        let result = manager.relocate_project(&old_root, &new_root);
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("already has a config"));
+        assert_eq!(fs::read_to_string(&new_config).unwrap(), content);
+    }
+
+    fn write_restore_fixture(dir: &Path) -> (PathBuf, PathBuf) {
+        let backup = dir.join("backup.json");
+        let current = dir.join("config.json");
+        fs::write(
+            &backup,
+            r#"{
+                "mcpServers": {
+                    "fetch": {"command": "uvx", "args": ["mcp-server-fetch"], "enabled": true},
+                    "github": {"command": "npx", "enabled": true},
+                    "postgres": {"command": "npx", "enabled": true}
+                },
+                "allowedPaths": ["~/projects"],
+                "customInstructions": ["Be concise"]
+            }"#,
+        )
+        .unwrap();
+        // Three sections changed since the backup was taken
+        fs::write(
+            &current,
+            r#"{
+                "mcpServers": {
+                    "fetch": {"command": "broken", "enabled": false},
+                    "github": {"command": "npx", "enabled": false},
+                    "sqlite": {"command": "uvx", "enabled": true}
+                },
+                "allowedPaths": ["~/projects", "~/work"],
+                "customInstructions": ["Be thorough"]
+            }"#,
+        )
+        .unwrap();
+        (backup, current)
+    }
+
+    // TDD Test 24: Restoring one key leaves other current changes alone
+    #[test]
+    fn test_restore_keys_restores_only_selected_server() {
+        let temp_dir = TempDir::new().unwrap();
+        let (backup, current) = write_restore_fixture(temp_dir.path());
+        let manager = ConfigManager::new(temp_dir.path().join("backups"));
+
+        let diffs = manager
+            .restore_keys(&backup, &current, &["mcpServers.fetch".to_string()])
+            .unwrap();
+
+        assert!(!diffs.is_empty());
+        assert!(diffs.iter().all(|diff| match diff {
+            ConfigDiff::Added { key_path, .. }
+            | ConfigDiff::Removed { key_path, .. }
+            | ConfigDiff::Modified { key_path, .. } => key_path.starts_with("mcpServers"),
+        }));
+
+        let config = manager.read_config(&current).unwrap();
+        let servers = config.mcp_servers.unwrap();
+        assert_eq!(servers["fetch"].command.as_deref(), Some("uvx"));
+        assert!(servers["fetch"].enabled);
+        assert!(!servers["github"].enabled);
+        assert!(servers.contains_key("sqlite"));
+        assert!(!servers.contains_key("postgres"));
+        assert_eq!(config.allowed_paths.unwrap(), ["~/projects", "~/work"]);
+        assert_eq!(config.custom_instructions.unwrap(), ["Be thorough"]);
+        assert_eq!(
+            manager
+                .backup_manager()
+                .list_backups(&current)
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+
+    // TDD Test 25: Missing keys are reported, and removed only when allowed
+    #[test]
+    fn test_restore_keys_missing_keys_and_allow_remove() {
+        let temp_dir = TempDir::new().unwrap();
+        let (backup, current) = write_restore_fixture(temp_dir.path());
+        let manager = ConfigManager::new(temp_dir.path().join("backups"));
+        let keys = vec![
+            "customInstructions".to_string(),
+            "mcpServers.sqlite".to_string(),
+        ];
+
+        let dry_run = manager
+            .restore_keys_with_options(
+                &backup,
+                &current,
+                &keys,
+                RestoreKeysOptions::new().with_dry_run(true),
+            )
+            .unwrap();
+        assert_eq!(dry_run.missing, ["mcpServers.sqlite"]);
+        assert_eq!(dry_run.diffs.len(), 1);
+        assert!(fs::read_to_string(&current)
+            .unwrap()
+            .contains("Be thorough"));
+
+        let kept = manager
+            .restore_keys_with_options(&backup, &current, &keys, RestoreKeysOptions::new())
+            .unwrap();
+        assert_eq!(kept, dry_run);
+        let config = manager.read_config(&current).unwrap();
+        assert_eq!(config.custom_instructions.unwrap(), ["Be concise"]);
+        assert!(config.mcp_servers.unwrap().contains_key("sqlite"));
+
+        let removed = manager
+            .restore_keys_with_options(
+                &backup,
+                &current,
+                &keys,
+                RestoreKeysOptions::new().with_allow_remove(true),
+            )
+            .unwrap();
+        assert_eq!(removed.missing, ["mcpServers.sqlite"]);
+        assert!(matches!(
+            removed.diffs.as_slice(),
+            [ConfigDiff::Modified { key_path, .. }] if key_path == "mcpServers"
+        ));
+        let servers = manager.read_config(&current).unwrap().mcp_servers.unwrap();
+        assert!(!servers.contains_key("sqlite"));
+        assert!(servers.contains_key("github"));
+    }
+
+    // TDD Test 26: Ensuring the global config creates it only once
+    #[test]
+    fn test_ensure_global_config_creates_empty_config() {
+        let temp_dir = TempDir::new().unwrap();
+        let global_path = temp_dir.path().join("claude").join("config.json");
+        let manager =
+            ConfigManager::with_custom_global_config(temp_dir.path().join("backups"), &global_path);
+
+        assert_eq!(manager.ensure_global_config().unwrap(), global_path);
+        assert_eq!(fs::read_to_string(&global_path).unwrap(), "{}");
+
+        fs::write(&global_path, r#"{"allowedPaths": ["~/projects"]}"#).unwrap();
+        assert!(!manager.ensure_config(&global_path).unwrap());
+        assert!(fs::read_to_string(&global_path)
+            .unwrap()
+            .contains("~/projects"));
+    }
+
+    // TDD Test 27: Config paths are resolved per scope
+    #[test]
+    fn test_config_path_for_scope() {
+        let temp_dir = TempDir::new().unwrap();
+        let global_path = temp_dir.path().join("global.json");
+        let manager =
+            ConfigManager::with_custom_global_config(temp_dir.path().join("backups"), &global_path);
+        let project = temp_dir.path().join("project");
+
+        assert_eq!(
+            manager
+                .config_path_for_scope(&ConfigScope::Global, None)
+                .unwrap(),
+            global_path
+        );
+        // The project path is ignored for the global scope
+        assert_eq!(
+            manager
+                .config_path_for_scope(&ConfigScope::Global, Some(&project))
+                .unwrap(),
+            global_path
+        );
+        assert_eq!(
+            manager
+                .config_path_for_scope(&ConfigScope::Project, Some(&project))
+                .unwrap(),
+            project.join(".claude").join("config.json")
+        );
+
+        let err = manager
+            .config_path_for_scope(&ConfigScope::Project, None)
+            .unwrap_err();
+        assert!(err.to_string().contains("Project path required"));
+    }
+
+    // TDD Test 28: A write that doesn't read back is rolled back
+    #[test]
+    fn test_write_verification_rolls_back_mismatch() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = ConfigManager::new(temp_dir.path().join("backups"));
+        let config_path = temp_dir.path().join("config.json");
+        let original = r#"{"allowedPaths": ["~/old"]}"#;
+        fs::write(&config_path, original).unwrap();
+        let config = crate::ClaudeConfig::new().with_allowed_path("~/new");
+
+        CORRUPT_WRITES.with(|hook| hook.set(Some(|json| json.replace("~/new", "~/bad"))));
+        let result = manager.write_config_with_backup(&config_path, &config);
+        let unverified = manager.write_config_with_options(
+            &temp_dir.path().join("unverified.json"),
+            &config,
+            WriteOptions::new().with_verify(false),
+        );
+        CORRUPT_WRITES.with(|hook| hook.set(None));
+
+        let err = result.unwrap_err();
+        assert!(matches!(err, ConfigError::WriteVerificationFailed { .. }));
+        let message = err.to_string();
+        assert!(message.contains(r#"- allowedPaths[0]: "~/new""#));
+        assert!(message.contains(r#"+ allowedPaths[0]: "~/bad""#));
+        assert!(message.contains("previous content was restored"));
+        assert!(message.contains("file a bug"));
+        assert_eq!(fs::read_to_string(&config_path).unwrap(), original);
+
+        // Without verification the corrupted content stays
+        unverified.unwrap();
+        assert!(fs::read_to_string(temp_dir.path().join("unverified.json"))
+            .unwrap()
+            .contains("~/bad"));
+    }
+
+    // TDD Test 29: An unparseable new file is removed
+    #[test]
+    fn test_write_verification_removes_unreadable_new_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = ConfigManager::new(temp_dir.path().join("backups"));
+        let config_path = temp_dir.path().join("config.json");
+
+        CORRUPT_WRITES.with(|hook| hook.set(Some(|json| json[1..].to_string())));
+        let result = manager.write_config_with_backup(&config_path, &crate::ClaudeConfig::new());
+        CORRUPT_WRITES.with(|hook| hook.set(None));
+
+        let message = result.unwrap_err().to_string();
+        assert!(message.contains("could not be read back"));
+        assert!(message.contains("did not exist before and was removed"));
+        assert!(!config_path.exists());
+    }
+
+    // TDD Test 30: Effective search attributes hits to the scope that sets them
+    #[test]
+    fn test_search_effective_attributes_sources() {
+        let temp_dir = TempDir::new().unwrap();
+        let global_path = temp_dir.path().join("global").join("config.json");
+        let manager =
+            ConfigManager::with_custom_global_config(temp_dir.path().join("backups"), &global_path);
+        fs::create_dir_all(global_path.parent().unwrap()).unwrap();
+        fs::write(
+            &global_path,
+            r#"{"mcpServers": {"fetch": {"command": "uvx", "enabled": true}}, "allowedPaths": ["~/global"]}"#,
+        )
+        .unwrap();
+        let project = temp_dir.path().join("project");
+        fs::create_dir_all(project.join(".claude")).unwrap();
+        fs::write(
+            project.join(".claude").join("config.json"),
+            r#"{"mcpServers": {"fetch-local": {"command": "node", "enabled": true}}, "allowedPaths": ["~/project"]}"#,
+        )
+        .unwrap();
+
+        let options = SearchOptions::new().with_keys(true).with_values(true);
+        let results = manager
+            .search_effective_with_options("fetch", Some(&project), options.clone())
+            .unwrap();
+        let source_of = |key: &str| {
+            results
+                .iter()
+                .find(|result| result.key_path == key)
+                .map(|result| result.source)
+        };
+        assert_eq!(source_of("mcpServers.fetch"), Some(ConfigScope::Global));
+        assert_eq!(
+            source_of("mcpServers.fetch-local"),
+            Some(ConfigScope::Project)
+        );
+
+        let results = manager
+            .search_effective_with_options("~/", Some(&project), options)
+            .unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].key_path, "allowedPaths[0]");
+        assert_eq!(results[0].source, ConfigScope::Project);
+        assert_eq!(
+            results[0].config_path,
+            project.join(".claude").join("config.json")
+        );
+    }
+
+    // TDD Test 31: Merge options apply to the merged view
+    #[test]
+    fn test_merged_config_uses_merge_options() {
+        let temp_dir = TempDir::new().unwrap();
+        let global_path = temp_dir.path().join("global").join("config.json");
+        fs::create_dir_all(global_path.parent().unwrap()).unwrap();
+        fs::write(
+            &global_path,
+            r#"{"mcpServers": {"github": {"command": "npx", "args": ["-y"], "enabled": true}}}"#,
+        )
+        .unwrap();
+        let project = temp_dir.path().join("project");
+        fs::create_dir_all(project.join(".claude")).unwrap();
+        fs::write(
+            project.join(".claude").join("config.json"),
+            r#"{"mcpServers": {"github": {"enabled": false}}}"#,
+        )
+        .unwrap();
+
+        let manager =
+            ConfigManager::with_custom_global_config(temp_dir.path().join("backups"), &global_path)
+                .with_merge_options(MergeOptions::new().with_deep_merge_servers(true));
+        let merged = manager.get_merged_config(Some(&project)).unwrap();
+
+        let github = &merged.mcp_servers.unwrap()["github"];
+        assert_eq!(github.command.as_deref(), Some("npx"));
+        assert!(!github.enabled);
+    }
+
+    // TDD Test 32: Writing an equal config is skipped unless forced
+    #[test]
+    fn test_write_skips_unchanged_config() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("config.json");
+        // Same content as the config below, but formatted differently
+        fs::write(&config_path, r#"{ "allowedPaths" : [ "~/a" ] }"#).unwrap();
+        let manager = ConfigManager::new(temp_dir.path().join("backups"));
+        let config = crate::ClaudeConfig::new().with_allowed_path("~/a");
+
+        let written = manager
+            .write_config_with_options(&config_path, &config, WriteOptions::new())
+            .unwrap();
+        assert!(!written);
+        assert!(fs::read_to_string(&config_path)
+            .unwrap()
+            .contains(r#""allowedPaths" : "#));
+        assert!(manager
+            .backup_manager()
+            .list_backups(&config_path)
+            .unwrap()
+            .is_empty());
+
+        let written = manager
+            .write_config_with_options(&config_path, &config, WriteOptions::new().with_force(true))
+            .unwrap();
+        assert!(written);
+        assert_eq!(
+            manager
+                .backup_manager()
+                .list_backups(&config_path)
+                .unwrap()
+                .len(),
+            1
+        );
+
+        let changed = config.with_allowed_path("~/b");
+        assert!(manager
+            .write_config_with_options(&config_path, &changed, WriteOptions::new())
+            .unwrap());
+        assert_eq!(manager.read_config(&config_path).unwrap(), changed);
+    }
+
+    // TDD Test 33: The fingerprint follows the file's bytes
+    #[test]
+    fn test_config_fingerprint() {
+        let temp_dir = TempDir::new().unwrap();
+        let global = temp_dir.path().join("config.json");
+        let manager =
+            ConfigManager::with_custom_global_config(temp_dir.path().join("backups"), &global);
+
+        assert!(matches!(
+            manager.config_fingerprint(&global),
+            Err(ConfigError::NotFound { .. })
+        ));
+
+        fs::write(&global, r#"{"allowedPaths": ["~/a"]}"#).unwrap();
+        let first = manager.config_fingerprint(&global).unwrap();
+        assert_eq!(first.len(), 16);
+        assert_eq!(manager.config_fingerprint(&global).unwrap(), first);
+        assert_eq!(
+            manager
+                .config_fingerprint_for_scope(&ConfigScope::Global, None)
+                .unwrap(),
+            first
+        );
+
+        fs::write(&global, r#"{"allowedPaths": ["~/b"]}"#).unwrap();
+        assert_ne!(manager.config_fingerprint(&global).unwrap(), first);
+    }
+
+    // TDD Test 34: Conditional writes refuse to overwrite external changes
+    #[test]
+    fn test_write_config_if_unchanged() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("config.json");
+        fs::write(&config_path, r#"{"allowedPaths": ["~/a"]}"#).unwrap();
+        let manager = ConfigManager::new(temp_dir.path().join("backups"));
+
+        let read = manager.config_fingerprint(&config_path).unwrap();
+        let edited = crate::ClaudeConfig::new().with_allowed_path("~/b");
+        manager
+            .write_config_if_unchanged(&config_path, &edited, &read)
+            .unwrap();
+        assert_eq!(manager.read_config(&config_path).unwrap(), edited);
+
+        // Someone else writes after our read
+        let stale = manager.config_fingerprint(&config_path).unwrap();
+        let external = r#"{"allowedPaths": ["~/external"]}"#;
+        fs::write(&config_path, external).unwrap();
+
+        let ours = crate::ClaudeConfig::new().with_allowed_path("~/ours");
+        let err = manager
+            .write_config_if_unchanged(&config_path, &ours, &stale)
+            .unwrap_err();
+        match &err {
+            ConfigError::Conflict {
+                expected, actual, ..
+            } => {
+                assert_eq!(expected, &stale);
+                assert_eq!(actual, &manager.config_fingerprint(&config_path).unwrap());
+            }
+            other => panic!("expected a conflict, got {other:?}"),
+        }
+        assert!(err.to_string().contains("changed since it was read"));
+        assert_eq!(fs::read_to_string(&config_path).unwrap(), external);
+        assert_eq!(
+            manager
+                .backup_manager()
+                .list_backups(&config_path)
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+
+    // TDD Test 35: Duplicate instructions are only removed when asked to
+    #[test]
+    fn test_write_dedupes_instructions_when_enabled() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("config.json");
+        let manager = ConfigManager::new(temp_dir.path().join("backups"));
+        let config = crate::ClaudeConfig {
+            custom_instructions: Some(
+                [
+                    "Be brief",
+                    "Use tabs",
+                    "Be brief",
+                    "Use tabs",
+                    "Cite sources",
+                ]
+                .map(String::from)
+                .to_vec(),
+            ),
+            ..crate::ClaudeConfig::new()
+        };
+
+        manager
+            .write_config_with_options(&config_path, &config, WriteOptions::new())
+            .unwrap();
+        assert_eq!(
+            manager
+                .read_config(&config_path)
+                .unwrap()
+                .custom_instructions,
+            config.custom_instructions
+        );
+
+        let written = manager
+            .write_config_with_options(
+                &config_path,
+                &config,
+                WriteOptions::new().with_dedupe_instructions(true),
+            )
+            .unwrap();
+        assert!(written);
+        assert_eq!(
+            manager
+                .read_config(&config_path)
+                .unwrap()
+                .custom_instructions,
+            Some(vec![
+                "Be brief".to_string(),
+                "Use tabs".to_string(),
+                "Cite sources".to_string()
+            ])
+        );
+    }
+
+    // TDD Test 36: Searching all scopes tags hits with the file they come from
+    #[test]
+    fn test_search_all_tags_sources() {
+        let temp_dir = TempDir::new().unwrap();
+        let global_path = temp_dir.path().join("global").join("config.json");
+        let manager =
+            ConfigManager::with_custom_global_config(temp_dir.path().join("backups"), &global_path);
+        fs::create_dir_all(global_path.parent().unwrap()).unwrap();
+        fs::write(
+            &global_path,
+            r#"{"mcpServers": {"fetch": {"command": "uvx", "enabled": true}}}"#,
+        )
+        .unwrap();
+        let project = temp_dir.path().join("project");
+        let project_config_path = project.join(".claude").join("config.json");
+        fs::create_dir_all(project.join(".claude")).unwrap();
+        fs::write(
+            &project_config_path,
+            r#"{"mcpServers": {"fetch": {"command": "node", "enabled": true}, "fetch-local": {"command": "node", "enabled": true}}}"#,
+        )
+        .unwrap();
+
+        let results = manager
+            .search_all("fetch", Some(&project), SearchOptions::new())
+            .unwrap();
+        let hits: Vec<(&str, ConfigScope, &Path)> = results
+            .iter()
+            .map(|r| (r.key_path.as_str(), r.source, r.config_path.as_path()))
+            .collect();
+        assert_eq!(
+            hits,
+            [
+                (
+                    "mcpServers.fetch",
+                    ConfigScope::Global,
+                    global_path.as_path()
+                ),
+                (
+                    "mcpServers.fetch",
+                    ConfigScope::Project,
+                    project_config_path.as_path()
+                ),
+                (
+                    "mcpServers.fetch-local",
+                    ConfigScope::Project,
+                    project_config_path.as_path()
+                ),
+            ]
+        );
+
+        // Without a project config only the global hits remain
+        let elsewhere = temp_dir.path().join("elsewhere");
+        fs::create_dir_all(&elsewhere).unwrap();
+        let results = manager
+            .search_all("fetch", Some(&elsewhere), SearchOptions::new())
+            .unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].source, ConfigScope::Global);
+
+        // A project configured only in its manifest is attributed to the manifest
+        let embedded = temp_dir.path().join("embedded");
+        fs::create_dir_all(&embedded).unwrap();
+        fs::write(
+            embedded.join("pyproject.toml"),
+            "[tool.claude.mcpServers.fetch]\ncommand = \"node\"\nenabled = true\n",
+        )
+        .unwrap();
+        for results in [
+            manager
+                .search_all("fetch", Some(&embedded), SearchOptions::new())
+                .unwrap(),
+            manager
+                .search_effective_with_options("fetch", Some(&embedded), SearchOptions::new())
+                .unwrap(),
+        ] {
+            let project_hit = results
+                .iter()
+                .find(|r| r.source == ConfigScope::Project)
+                .unwrap();
+            assert_eq!(project_hit.config_path, embedded.join("pyproject.toml"));
+        }
+    }
+
+    // TDD Test 37: canonicalOnWrite makes writes byte-identical across orderings
+    #[test]
+    fn test_canonical_on_write_is_deterministic() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = ConfigManager::new(temp_dir.path().join("backups"));
+        let parse = |json: &str| serde_json::from_str::<crate::ClaudeConfig>(json).unwrap();
+        let first = parse(
+            r#"{"ccm": {"canonicalOnWrite": true}, "allowedPaths": ["~/b", "~/a"], "mcpServers": {"s": {"command": "x", "args": ["--z", "--a"], "enabled": true}}}"#,
+        );
+        let second = parse(
+            r#"{"mcpServers": {"s": {"args": ["--z", "--a"], "enabled": true, "command": "x"}}, "allowedPaths": ["~/a", "~/b"], "ccm": {"canonicalOnWrite": true}}"#,
+        );
+
+        let first_path = temp_dir.path().join("first.json");
+        let second_path = temp_dir.path().join("second.json");
+        manager
+            .write_config_with_backup(&first_path, &first)
+            .unwrap();
+        manager
+            .write_config_with_backup(&second_path, &second)
+            .unwrap();
+
+        let written = fs::read_to_string(&first_path).unwrap();
+        assert_eq!(written, fs::read_to_string(&second_path).unwrap());
+        assert!(written.ends_with("}\n"));
+        let value: serde_json::Value = serde_json::from_str(&written).unwrap();
+        assert_eq!(value["allowedPaths"], serde_json::json!(["~/a", "~/b"]));
+        assert_eq!(
+            value["mcpServers"]["s"]["args"],
+            serde_json::json!(["--z", "--a"])
+        );
+
+        // Turning the setting off restores the default formatting
+        let mut plain = manager.read_config(&first_path).unwrap();
+        let mut settings = plain.ccm_settings();
+        settings.canonical_on_write = false;
+        plain.set_ccm_settings(&settings);
+        plain.allowed_paths = Some(vec!["~/b".to_string(), "~/a".to_string()]);
+        manager
+            .write_config_with_backup(&first_path, &plain)
+            .unwrap();
+
+        let written = fs::read_to_string(&first_path).unwrap();
+        assert!(!written.ends_with('\n'));
+        assert_eq!(written, normalize::canonical_json(&plain).unwrap());
+    }
+
+    // TDD Test 38: Merged server listing reports origin and last change
+    #[test]
+    fn test_list_servers_merged_reports_last_change() {
+        let temp_dir = TempDir::new().unwrap();
+        let global_path = temp_dir.path().join("config.json");
+        let manager =
+            ConfigManager::with_custom_global_config(temp_dir.path().join("backups"), &global_path);
+        let version = |a: &str, b: &str| {
+            crate::ClaudeConfig::new()
+                .with_mcp_server("a", crate::McpServer::new("a", a, vec![]))
+                .with_mcp_server("b", crate::McpServer::new("b", b, vec![]))
+                .with_mcp_server("c", crate::McpServer::new("c", "same", vec![]))
+        };
+
+        manager
+            .write_config_with_backup(&global_path, &version("a1", "b1"))
+            .unwrap();
+        manager
+            .write_config_with_backup(&global_path, &version("a2", "b1"))
+            .unwrap();
+        manager
+            .write_config_with_backup(&global_path, &version("a2", "b2"))
+            .unwrap();
+
+        // Newest first: [before "b2", before "a2"]
+        let backups = manager.backup_manager().list_backups(&global_path).unwrap();
+        assert_eq!(backups.len(), 2);
+
+        let servers = manager.list_servers_merged(None).unwrap();
+        let last_changed: Vec<(&str, Option<chrono::DateTime<chrono::Utc>>)> = servers
+            .iter()
+            .map(|s| (s.name.as_str(), s.last_changed))
+            .collect();
+        assert_eq!(
+            last_changed,
+            [
+                ("a", Some(backups[1].created_at)),
+                ("b", Some(backups[0].created_at)),
+                ("c", None),
+            ]
+        );
+        assert!(servers
+            .iter()
+            .all(|s| s.origin == ConfigScope::Global && s.origin_path == global_path));
+
+        // A project server overrides the global one and has no history yet
+        let project = temp_dir.path().join("project");
+        fs::create_dir_all(project.join(".claude")).unwrap();
+        fs::write(
+            project.join(".claude").join("config.json"),
+            r#"{"mcpServers": {"a": {"command": "local", "enabled": true}}}"#,
+        )
+        .unwrap();
+        let servers = manager.list_servers_merged(Some(&project)).unwrap();
+        assert_eq!(servers[0].origin, ConfigScope::Project);
+        assert_eq!(servers[0].server.command.as_deref(), Some("local"));
+        assert_eq!(servers[0].last_changed, None);
+        assert_eq!(servers[1].origin, ConfigScope::Global);
+    }
+
+    // TDD Test 39: Touching a config advances its mtime and keeps the content
+    #[test]
+    fn test_touch_config_advances_mtime() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = ConfigManager::new(temp_dir.path().join("backups"));
+        let path = temp_dir.path().join("config.json");
+        fs::write(&path, r#"{"allowedPaths": ["~/code"]}"#).unwrap();
+        let past = filetime::FileTime::from_unix_time(1_000_000_000, 0);
+        filetime::set_file_mtime(&path, past).unwrap();
+
+        manager.touch_config(&path).unwrap();
+
+        let modified =
+            filetime::FileTime::from_last_modification_time(&fs::metadata(&path).unwrap());
+        assert!(modified > past);
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            r#"{"allowedPaths": ["~/code"]}"#
+        );
+        assert!(!temp_dir.path().join("backups").exists());
+
+        let missing = manager.touch_config(&temp_dir.path().join("missing.json"));
+        assert!(matches!(missing, Err(ConfigError::NotFound { .. })));
+    }
+
+    // TDD Test 40: A backup retention prunes old backups after each write
+    #[test]
+    fn test_backup_retention_prunes_after_write() {
+        let temp_dir = TempDir::new().unwrap();
+        let backup_dir = temp_dir.path().join("backups");
+        let path = temp_dir.path().join("config.json");
+        fs::write(&path, "{}").unwrap();
+
+        let manager = ConfigManager::new(&backup_dir).with_backup_retention(Some(2));
+        for i in 0..4 {
+            let config = crate::ClaudeConfig::new().with_allowed_path(format!("~/p{i}"));
+            manager.write_config_with_backup(&path, &config).unwrap();
+        }
+        assert_eq!(backup_files(&backup_dir), 2);
+    }
+
+    // TDD Test 41: Without a retention, backups settle at the default count
+    #[test]
+    fn test_default_retention_prunes_after_write() {
+        let temp_dir = TempDir::new().unwrap();
+        let backup_dir = temp_dir.path().join("backups");
+        let path = temp_dir.path().join("config.json");
+        fs::write(&path, "{}").unwrap();
+
+        let manager = ConfigManager::new(&backup_dir);
+        let limit = manager.backup_manager().retention_count();
+        for i in 0..limit + 3 {
+            let config = crate::ClaudeConfig::new().with_allowed_path(format!("~/p{i}"));
+            manager.write_config_with_backup(&path, &config).unwrap();
+        }
+        assert_eq!(backup_files(&backup_dir), limit);
+    }
+
+    // TDD Test 42: Merged views resolve servers for the configured platform
+    #[test]
+    fn test_merged_views_resolve_platform() {
+        let temp_dir = TempDir::new().unwrap();
+        let global_path = temp_dir.path().join("global.json");
+        let project_dir = temp_dir.path().join("project");
+        let backup_dir = temp_dir.path().join("backups");
+
+        let global_config = crate::ClaudeConfig::new()
+            .with_mcp_server(
+                "finder",
+                crate::McpServer::new("finder", "npx", vec![]).with_platforms(&[Platform::MacOs]),
+            )
+            .with_mcp_server(
+                "fs",
+                crate::McpServer::new("fs", "npx", vec![])
+                    .with_platform_command(Platform::Linux, "/usr/bin/mcp-fs"),
+            );
+        let manager = ConfigManager::with_custom_global_config(&backup_dir, &global_path)
+            .with_platform(Some(Platform::Linux));
+        manager
+            .write_config_with_backup(&global_path, &global_config)
+            .unwrap();
+
+        let merged = manager.get_merged_config(Some(&project_dir)).unwrap();
+        let servers = merged.mcp_servers.unwrap();
+        assert!(!servers.contains_key("finder"));
+        assert_eq!(servers["fs"].command.as_deref(), Some("/usr/bin/mcp-fs"));
+
+        let listed = manager.list_servers_merged(Some(&project_dir)).unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].server.command.as_deref(), Some("/usr/bin/mcp-fs"));
+
+        // Without a platform, servers stay as configured
+        let manager =
+            ConfigManager::with_custom_global_config(&backup_dir, &global_path).with_platform(None);
+        let merged = manager.get_merged_config(Some(&project_dir)).unwrap();
+        assert_eq!(merged, manager.read_config(&global_path).unwrap());
+    }
+
+    // TDD Test 43: A declared base lies below the global and project configs
+    #[test]
+    fn test_merged_config_with_base() {
+        let temp_dir = TempDir::new().unwrap();
+        let global_path = temp_dir.path().join("global.json");
+        let project_dir = temp_dir.path().join("project");
+        let manager =
+            ConfigManager::with_custom_global_config(temp_dir.path().join("backups"), &global_path);
+
+        fs::write(
+            temp_dir.path().join("team.json"),
+            r#"{
+                "customInstructions": ["team"],
+                "allowedPaths": ["~/team"],
+                "mcpServers": {"lint": {"command": "team-lint", "enabled": true}}
+            }"#,
+        )
+        .unwrap();
+        fs::write(
+            &global_path,
+            r#"{"ccm": {"extends": "team.json"}, "allowedPaths": ["~/mine"]}"#,
+        )
+        .unwrap();
+        manager
+            .update_project_config(
+                &project_dir,
+                &crate::ClaudeConfig::new().with_custom_instruction("project"),
+            )
+            .unwrap();
+
+        let (merged, notes) = manager
+            .get_merged_config_with_notes(Some(&project_dir))
+            .unwrap();
+        assert_eq!(merged.allowed_paths.unwrap(), ["~/mine"]);
+        assert_eq!(merged.custom_instructions.unwrap(), ["project"]);
+        assert!(merged.mcp_servers.unwrap().contains_key("lint"));
+        assert!(notes.iter().all(|note| note.key_path != "ccm.extends"));
+
+        // A missing base only produces a note
+        fs::remove_file(temp_dir.path().join("team.json")).unwrap();
+        let (merged, notes) = manager
+            .get_merged_config_with_notes(Some(&project_dir))
+            .unwrap();
+        assert_eq!(merged.allowed_paths.unwrap(), ["~/mine"]);
+        assert!(merged.mcp_servers.is_none());
+        assert_eq!(notes[0].key_path, "ccm.extends");
+        assert!(notes[0].message.contains("'team.json' was not loaded"));
+    }
+
+    // TDD Test 44: Values inherited from a base are attributed to it
+    #[test]
+    fn test_base_source_labeling() {
+        let temp_dir = TempDir::new().unwrap();
+        let global_path = temp_dir.path().join("global.json");
+        let project_dir = temp_dir.path().join("project");
+        let base_path = temp_dir.path().join("team.json");
+        let manager =
+            ConfigManager::with_custom_global_config(temp_dir.path().join("backups"), &global_path);
+
+        fs::write(
+            &base_path,
+            r#"{
+                "customInstructions": ["team"],
+                "mcpServers": {"lint": {"command": "team-lint", "enabled": true}}
+            }"#,
+        )
+        .unwrap();
+        fs::write(
+            &global_path,
+            r#"{"ccm": {"extends": "team.json"}, "allowedPaths": ["~/mine"]}"#,
+        )
+        .unwrap();
+        manager
+            .update_project_config(&project_dir, &crate::ClaudeConfig::new())
+            .unwrap();
+
+        let (_, sources) = manager.diff_configs(Some(&project_dir)).unwrap();
+        assert!(sources.is_base("customInstructions"));
+        assert_eq!(
+            sources.lookup("mcpServers.lint.command"),
+            Some(&ConfigScope::Base)
+        );
+        assert!(sources.is_global("allowedPaths"));
+
+        let (server, scope) = manager
+            .get_effective_server("lint", Some(&project_dir))
+            .unwrap();
+        assert_eq!(server.command.as_deref(), Some("team-lint"));
+        assert_eq!(scope, ConfigScope::Base);
+
+        let listed = manager.list_servers_merged(Some(&project_dir)).unwrap();
+        assert_eq!(listed[0].origin, ConfigScope::Base);
+        assert_eq!(listed[0].origin_path, base_path);
+        assert_eq!(listed[0].last_changed, None);
+    }
+
+    // TDD Test 45: Diff two config files
+    #[test]
+    fn test_diff_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let base_path = temp_dir.path().join("template.json");
+        let config_path = temp_dir.path().join("config.json");
+        let manager = ConfigManager::new(temp_dir.path().join("backups"));
+
+        fs::write(
+            &base_path,
+            r#"{"customInstructions": ["team"], "allowedPaths": ["~/work"]}"#,
+        )
+        .unwrap();
+        fs::write(
+            &config_path,
+            r#"{"customInstructions": ["mine"], "mcpServers": {"fetch": {"command": "uvx", "enabled": true}}}"#,
+        )
+        .unwrap();
+
+        let (diffs, _) = manager.diff_files(&base_path, &config_path).unwrap();
+        let paths: Vec<(&str, &str)> = diffs
+            .iter()
+            .map(|diff| match diff {
+                ConfigDiff::Added { key_path, .. } => ("added", key_path.as_str()),
+                ConfigDiff::Removed { key_path, .. } => ("removed", key_path.as_str()),
+                ConfigDiff::Modified { key_path, .. } => ("modified", key_path.as_str()),
+            })
+            .collect();
+        assert!(paths.contains(&("removed", "allowedPaths")));
+        assert!(paths.contains(&("modified", "customInstructions[0]")));
+        assert!(paths.contains(&("added", "mcpServers")));
+
+        assert!(manager
+            .diff_files(&temp_dir.path().join("missing.json"), &config_path)
+            .is_err());
+    }
+
+    // TDD Test 46: Flagged and large writes take a risk snapshot first
+    #[test]
+    fn test_write_risk_snapshots() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("config.json");
+        let manager = ConfigManager::new(temp_dir.path().join("backups"));
+        let backups = manager.backup_manager();
+        let risk_tags = || -> Vec<String> {
+            backups
+                .list_backups(&config_path)
+                .unwrap()
+                .into_iter()
+                .filter_map(|backup| backup.tag)
+                .collect()
+        };
+
+        let small = crate::ClaudeConfig::new().with_allowed_path("~/a");
+        manager
+            .write_config_with_backup(&config_path, &small)
+            .unwrap();
+        manager
+            .write_config_with_backup(&config_path, &small.clone().with_allowed_path("~/b"))
+            .unwrap();
+        assert!(risk_tags().is_empty());
+
+        let mut large = crate::ClaudeConfig::new();
+        for i in 0..=LARGE_CHANGE_KEY_PATHS {
+            large
+                .unknown
+                .insert(format!("key{i}"), serde_json::json!(i));
+        }
+        manager
+            .write_config_with_options(
+                &config_path,
+                &large,
+                WriteOptions::default().with_large_change_threshold(None),
+            )
+            .unwrap();
+        assert!(risk_tags().is_empty());
+        manager
+            .write_config_with_backup(&config_path, &small)
+            .unwrap();
+        assert_eq!(risk_tags(), ["auto-risk-large-change"]);
+
+        manager
+            .write_config_with_options(
+                &config_path,
+                &crate::ClaudeConfig::new(),
+                WriteOptions::default().with_risk(RiskyOperation::Import),
+            )
+            .unwrap();
+        assert_eq!(risk_tags(), ["auto-risk-import", "auto-risk-large-change"]);
+    }
+
+    // TDD Test 47: Backup options can be changed after construction
+    #[test]
+    fn test_backup_options_change_after_construction() {
+        let temp_dir = TempDir::new().unwrap();
+        let backup_dir = temp_dir.path().join("backups");
+        let path = temp_dir.path().join("config.json");
+        fs::write(&path, "{}").unwrap();
+
+        let mut manager = ConfigManager::new(&backup_dir).with_backup_retention(Some(3));
+        let write = |manager: &ConfigManager, i: usize| {
+            let config = crate::ClaudeConfig::new().with_allowed_path(format!("~/p{i}"));
+            manager.write_config_with_backup(&path, &config).unwrap();
+        };
+        for i in 0..3 {
+            write(&manager, i);
+        }
+        assert_eq!(backup_files(&backup_dir), 3);
+
+        manager.set_retention(1);
+        write(&manager, 3);
+        assert_eq!(manager.backup_manager().retention_count(), 1);
+        assert_eq!(backup_files(&backup_dir), 1);
+
+        let other_dir = temp_dir.path().join("other-backups");
+        manager.set_backup_dir(&other_dir);
+        write(&manager, 4);
+        assert_eq!(manager.backup_manager().backup_dir(), other_dir);
+        assert_eq!(backup_files(&other_dir), 1);
+        assert_eq!(backup_files(&backup_dir), 1);
+    }
+
+    // TDD Test 48: A dual read returns matching typed and untyped configs
+    #[test]
+    fn test_read_config_dual() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("config.json");
+        fs::write(
+            &config_path,
+            r#"{
+                "customInstructions": ["Be concise"],
+                "mcpServers": {"fetch": {"command": "uvx", "args": ["mcp-fetch"], "enabled": true}},
+                "theme": {"name": "dark"}
+            }"#,
+        )
+        .unwrap();
+        let timings = Timings::new();
+        let manager =
+            ConfigManager::new(temp_dir.path().join("backups")).with_timings(timings.clone());
+
+        let (config, value) = manager.read_config_dual(&config_path).unwrap();
+
+        let reads = timings
+            .entries()
+            .iter()
+            .filter(|e| e.name == "read")
+            .count();
+        assert_eq!(reads, 1);
+        assert_eq!(config, manager.read_config(&config_path).unwrap());
+        assert_eq!(
+            serde_json::to_value(&config).unwrap()["theme"],
+            value["theme"]
+        );
+        assert_eq!(
+            value["mcpServers"]["fetch"]["command"],
+            config.mcp_servers.as_ref().unwrap()["fetch"]
+                .command
+                .as_deref()
+                .unwrap()
+        );
+        assert!(value["mcpServers"]["fetch"].get("name").is_none());
+
+        // Type errors still carry a location
+        fs::write(&config_path, r#"{"allowedPaths": "~/code"}"#).unwrap();
+        assert!(matches!(
+            manager.read_config_dual(&config_path),
+            Err(ConfigError::InvalidFormat { line: 1, .. })
+        ));
+    }
+
+    // TDD Test 49: Relocating a project keeps its backup history
+    #[test]
+    fn test_relocate_project_moves_backups() {
+        let temp_dir = TempDir::new().unwrap();
+        let old_root = temp_dir.path().join("old");
+        let new_root = temp_dir.path().join("new");
+        let old_config = project_config_path(&old_root);
+        let new_config = project_config_path(&new_root);
+        fs::create_dir_all(old_config.parent().unwrap()).unwrap();
+        fs::write(&old_config, "{}").unwrap();
+
+        let write = |manager: &ConfigManager, path: &str| {
+            let config = crate::ClaudeConfig::new().with_allowed_path(path);
+            manager
+                .write_config_with_backup(&old_config, &config)
+                .unwrap();
+        };
+
+        // Backups in the shared directory and next to the project config
+        let shared = ConfigManager::new(temp_dir.path().join("backups"));
+        write(&shared, "~/a");
+        write(&shared, "~/b");
+        let local = ConfigManager::new(old_root.join(".claude").join("backups"));
+        write(&local, "~/c");
+
+        shared.relocate_project(&old_root, &new_root).unwrap();
+
+        let backups = shared.backup_manager().list_backups(&new_config).unwrap();
+        assert_eq!(backups.len(), 2);
+        assert!(backups
+            .iter()
+            .all(|b| Path::new(&b.original_path) == crate::paths::canonical_path(&new_config)));
+        assert!(shared
+            .backup_manager()
+            .list_backups(&old_config)
+            .unwrap()
+            .is_empty());
+
+        let moved = BackupManager::new(new_root.join(".claude").join("backups"), None);
+        assert_eq!(moved.list_backups(&new_config).unwrap().len(), 1);
+        assert!(!old_root.join(".claude").exists());
+    }
+
+    // TDD Test 50: Writes keep the permissions of the config they replace
+    #[cfg(unix)]
+    #[test]
+    fn test_write_preserves_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = TempDir::new().unwrap();
+        let manager = ConfigManager::new(temp_dir.path().join("backups"));
+        let config_path = temp_dir.path().join("config.json");
+        fs::write(&config_path, "{}").unwrap();
+        fs::set_permissions(&config_path, fs::Permissions::from_mode(0o600)).unwrap();
+
+        manager
+            .write_config_with_backup(
+                &config_path,
+                &crate::ClaudeConfig::new().with_allowed_path("~/a"),
+            )
+            .unwrap();
+
+        let mode = fs::metadata(&config_path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
 }
@@ -5,9 +5,37 @@
 //! - Objects (nested structures): Deep merge
 //! - Arrays: Replace (higher scope wins)
 //! - Primitives: Replace (higher scope wins)
+//!
+//! MCP servers and skills defined in both configs are replaced as a whole,
+//! unless [`MergeOptions::deep_merge_servers`] asks for servers to be merged
+//! field by field.
+
+use crate::{
+    types::{ConfigScope, SourceMap},
+    ClaudeConfig,
+};
+use serde_json::Value;
+
+/// How [`merge_configs_with_options`] combines configurations
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MergeOptions {
+    /// Merge a server defined in both configs field by field (see
+    /// [`ClaudeConfig::merge_server`]) instead of replacing it whole
+    pub deep_merge_servers: bool,
+}
 
-use crate::ClaudeConfig;
-use std::collections::HashMap;
+impl MergeOptions {
+    /// Create default options (servers are replaced whole)
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Merge same-named servers field by field
+    pub fn with_deep_merge_servers(mut self, deep_merge_servers: bool) -> Self {
+        self.deep_merge_servers = deep_merge_servers;
+        self
+    }
+}
 
 /// Merge two configurations
 ///
@@ -45,40 +73,260 @@ use std::collections::HashMap;
 /// assert_eq!(merged.allowed_paths.unwrap().len(), 1);
 /// ```
 pub fn merge_configs(base_config: &ClaudeConfig, override_config: &ClaudeConfig) -> ClaudeConfig {
-    let mut merged = base_config.clone();
+    merge_configs_with_options(base_config, override_config, MergeOptions::default())
+}
 
-    // Merge MCP servers (deep merge)
-    if let Some(override_servers) = &override_config.mcp_servers {
-        let merged_servers = merged.mcp_servers.get_or_insert_with(HashMap::new);
-        for (name, server) in override_servers {
-            merged_servers.insert(name.clone(), server.clone());
+/// Merge two configurations with custom options
+///
+/// Same as [`merge_configs`], except that servers defined in both configs
+/// are merged field by field when `options.deep_merge_servers` is set.
+pub fn merge_configs_with_options(
+    base_config: &ClaudeConfig,
+    override_config: &ClaudeConfig,
+    options: MergeOptions,
+) -> ClaudeConfig {
+    // Servers in both configs that are merged after deserializing
+    let deep_merged: Vec<(&String, &crate::McpServer)> = match (
+        options.deep_merge_servers,
+        &base_config.mcp_servers,
+        &override_config.mcp_servers,
+    ) {
+        (true, Some(base), Some(overrides)) => overrides
+            .iter()
+            .filter(|(name, _)| base.contains_key(*name))
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    // Serialize once per merge; ClaudeConfig always has a JSON representation
+    let mut merged = serde_json::to_value(base_config).expect("ClaudeConfig serializes to JSON");
+    let overlay = serde_json::to_value(override_config).expect("ClaudeConfig serializes to JSON");
+
+    if let (Value::Object(merged_map), Value::Object(overlay_map)) = (&mut merged, &overlay) {
+        for (key, value) in overlay_map {
+            match (merged_map.get_mut(key), value) {
+                // MCP servers and skills: each entry is replaced as a whole
+                (Some(Value::Object(entries)), Value::Object(overrides))
+                    if ENTRY_REPLACE_FIELDS.contains(&key.as_str()) =>
+                {
+                    for (name, entry) in overrides {
+                        let deferred = key == "mcpServers"
+                            && deep_merged.iter().any(|(deep, _)| *deep == name);
+                        if !deferred {
+                            entries.insert(name.clone(), entry.clone());
+                        }
+                    }
+                }
+                (Some(existing), _) => deep_merge(existing, value),
+                (None, _) => {
+                    merged_map.insert(key.clone(), value.clone());
+                }
+            }
         }
     }
 
-    // Merge allowed paths (replace)
-    if override_config.allowed_paths.is_some() {
-        merged.allowed_paths = override_config.allowed_paths.clone();
+    let mut result: ClaudeConfig =
+        serde_json::from_value(merged).expect("merged values deserialize into ClaudeConfig");
+    for (name, server) in deep_merged {
+        result.merge_server(name, server);
+    }
+
+    // Names are not deserialized (the map key is the name), so restore them
+    // from whichever config supplied each entry
+    if let Some(servers) = result.mcp_servers.as_mut() {
+        for (key, server) in servers.iter_mut() {
+            if let Some(source) = [override_config, base_config]
+                .iter()
+                .find_map(|c| c.mcp_servers.as_ref()?.get(key))
+            {
+                server.name = source.name.clone();
+            }
+        }
+    }
+    if let Some(skills) = result.skills.as_mut() {
+        for (key, skill) in skills.iter_mut() {
+            if let Some(source) = [override_config, base_config]
+                .iter()
+                .find_map(|c| c.skills.as_ref()?.get(key))
+            {
+                skill.name = source.name.clone();
+            }
+        }
+    }
+
+    result
+}
+
+/// Merge any number of scoped configurations, recording where each value came from
+///
+/// Configs are folded in order with [`merge_configs`], so later configs
+/// take precedence. As each one is folded in, every key path it sets is
+/// attributed to its scope in the returned [`SourceMap`]:
+/// - Object keys are attributed one by one, as they are deep merged
+/// - Arrays and primitives are attributed as a whole, and so are MCP servers
+///   and skills (e.g. `mcpServers.fetch` and everything below it), since
+///   the merge replaces them whole
+///
+/// # Arguments
+/// * `configs` - Configurations with their scopes, lowest priority first
+///
+/// # Returns
+/// The merged configuration, and the scope that last set each key path
+/// (an empty config and map if `configs` is empty)
+pub fn merge_all_with_sources(
+    configs: &[(ConfigScope, &ClaudeConfig)],
+) -> (ClaudeConfig, SourceMap) {
+    let mut sources = SourceMap::new();
+    let mut merged: Option<ClaudeConfig> = None;
+
+    for (scope, config) in configs {
+        let value = serde_json::to_value(config).expect("ClaudeConfig serializes to JSON");
+        if let Value::Object(fields) = &value {
+            for (key, field) in fields {
+                match field {
+                    Value::Object(entries) if ENTRY_REPLACE_FIELDS.contains(&key.as_str()) => {
+                        sources.insert(key.as_str(), *scope);
+                        for (name, entry) in entries {
+                            let entry_path = format!("{key}.{name}");
+                            clear_below(&mut sources, &entry_path);
+                            record_all(&mut sources, entry, &entry_path, *scope);
+                        }
+                    }
+                    _ => record_merged(&mut sources, field, key, *scope),
+                }
+            }
+        }
+
+        merged = Some(match merged {
+            Some(base) => merge_configs(&base, config),
+            None => (*config).clone(),
+        });
+    }
+
+    (merged.unwrap_or_default(), sources)
+}
+
+/// Attribute a deep-merged value: object keys one by one, anything else whole
+fn record_merged(sources: &mut SourceMap, value: &Value, key_path: &str, scope: ConfigScope) {
+    sources.insert(key_path, scope);
+    match value {
+        Value::Object(map) => {
+            for (key, child) in map {
+                record_merged(sources, child, &format!("{key_path}.{key}"), scope);
+            }
+        }
+        // Replaced whole, so nothing below it survives from earlier configs
+        _ => clear_below(sources, key_path),
     }
+}
 
-    // Merge skills (deep merge)
-    if let Some(override_skills) = &override_config.skills {
-        let merged_skills = merged.skills.get_or_insert_with(HashMap::new);
-        for (name, skill) in override_skills {
-            merged_skills.insert(name.clone(), skill.clone());
+/// Attribute a value and every object key below it
+fn record_all(sources: &mut SourceMap, value: &Value, key_path: &str, scope: ConfigScope) {
+    sources.insert(key_path, scope);
+    if let Value::Object(map) = value {
+        for (key, child) in map {
+            record_all(sources, child, &format!("{key_path}.{key}"), scope);
         }
     }
+}
+
+/// Forget the attributions of key paths nested below `key_path`
+fn clear_below(sources: &mut SourceMap, key_path: &str) {
+    let prefix = format!("{key_path}.");
+    sources.sources.retain(|path, _| !path.starts_with(&prefix));
+}
+
+/// Advisory about configuration a merge silently discards
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct MergeNote {
+    /// Key path of the affected value (e.g. `customInstructions`)
+    pub key_path: String,
 
-    // Merge custom instructions (replace)
-    if override_config.custom_instructions.is_some() {
-        merged.custom_instructions = override_config.custom_instructions.clone();
+    /// What the merge did
+    pub message: String,
+
+    /// How to keep the discarded values
+    pub suggestion: String,
+}
+
+impl std::fmt::Display for MergeNote {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {} {}", self.key_path, self.message, self.suggestion)
     }
+}
 
-    // Merge unknown fields (deep merge)
-    for (key, value) in &override_config.unknown {
-        merged.unknown.insert(key.clone(), value.clone());
+/// Find values the project config discards when merged over the global one
+///
+/// Arrays replace on merge, so a project that sets one custom instruction
+/// drops every global instruction without a trace. This reports such
+/// replacements so merged views can point them out.
+///
+/// # Arguments
+/// * `base_config` - Base configuration (lower priority)
+/// * `override_config` - Override configuration (higher priority)
+///
+/// # Returns
+/// One note per discarded list (empty if nothing is lost)
+pub fn merge_notes(base_config: &ClaudeConfig, override_config: &ClaudeConfig) -> Vec<MergeNote> {
+    let mut notes = Vec::new();
+
+    let base = base_config
+        .custom_instructions
+        .as_deref()
+        .unwrap_or_default();
+    let dropped = |project: &[String]| base.iter().filter(|i| !project.contains(i)).count();
+    // Nothing is lost if the project repeats every global instruction
+    if let Some(project) = override_config
+        .custom_instructions
+        .as_deref()
+        .filter(|project| dropped(project) > 0)
+    {
+        let count = base.len();
+        let plural = if count == 1 { "" } else { "s" };
+        let message = if project.is_empty() {
+            format!("the project's empty list clears {count} global instruction{plural}.")
+        } else {
+            format!(
+                "the project's {} instruction(s) replace {count} global instruction{plural} ({} dropped).",
+                project.len(),
+                dropped(project)
+            )
+        };
+        notes.push(MergeNote {
+            key_path: "customInstructions".to_string(),
+            message,
+            suggestion: "Arrays replace on merge; add the global instructions the project \
+                         still needs with `ccm config --project <dir> set customInstructions \
+                         <text> --append`, or remove the project list to inherit them."
+                .to_string(),
+        });
     }
 
-    merged
+    notes
+}
+
+/// Top-level fields whose entries are replaced whole instead of deep merged
+pub(crate) const ENTRY_REPLACE_FIELDS: &[&str] = &["mcpServers", "skills"];
+
+/// Deep merge `overlay` into `base`
+///
+/// - Objects: Merged key by key (recursive)
+/// - Arrays: Replaced by the overlay
+/// - Primitives: Replaced by the overlay
+pub fn deep_merge(base: &mut Value, overlay: &Value) {
+    match (base, overlay) {
+        (Value::Object(base_map), Value::Object(overlay_map)) => {
+            for (key, value) in overlay_map {
+                match base_map.get_mut(key) {
+                    Some(existing) => deep_merge(existing, value),
+                    None => {
+                        base_map.insert(key.clone(), value.clone());
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay.clone(),
+    }
 }
 
 #[cfg(test)]
@@ -300,4 +548,202 @@ mod tests {
         assert_eq!(instructions.len(), 1); // Only override instruction
         assert_eq!(instructions[0], "Override");
     }
+
+    // TDD Test 11: Nested unknown objects merge key by key
+    #[test]
+    fn test_nested_unknown_objects_deep_merge() {
+        let mut base = ClaudeConfig::new();
+        base.unknown.insert(
+            "permissions".to_string(),
+            serde_json::json!({"allow": ["Read"], "deny": ["Bash"], "mode": {"default": "ask"}}),
+        );
+
+        let mut override_config = ClaudeConfig::new();
+        override_config.unknown.insert(
+            "permissions".to_string(),
+            serde_json::json!({"allow": ["Write"], "mode": {"plan": true}}),
+        );
+
+        let merged = merge_configs(&base, &override_config);
+
+        assert_eq!(
+            merged.unknown["permissions"],
+            serde_json::json!({
+                "allow": ["Write"],
+                "deny": ["Bash"],
+                "mode": {"default": "ask", "plan": true}
+            })
+        );
+    }
+
+    // TDD Test 12: Arrays inside unknown objects follow the replace rule
+    #[test]
+    fn test_arrays_in_unknown_objects_replace() {
+        let mut base = ClaudeConfig::new();
+        base.unknown.insert(
+            "permissions".to_string(),
+            serde_json::json!({"allow": ["Read", "Grep"]}),
+        );
+
+        let mut override_config = ClaudeConfig::new();
+        override_config
+            .unknown
+            .insert("permissions".to_string(), serde_json::json!({"allow": []}));
+
+        let merged = merge_configs(&base, &override_config);
+
+        assert_eq!(
+            merged.unknown["permissions"],
+            serde_json::json!({"allow": []})
+        );
+    }
+
+    // TDD Test 13: Server and skill names survive the merge
+    #[test]
+    fn test_merge_preserves_entry_names() {
+        let base = ClaudeConfig::new().with_mcp_server("npx", McpServer::new("npx", "npx", vec![]));
+        let override_config =
+            ClaudeConfig::new().with_mcp_server("uvx", McpServer::new("uvx", "uvx", vec![]));
+
+        let merged = merge_configs(&base, &override_config);
+        let servers = merged.mcp_servers.unwrap();
+
+        assert_eq!(servers["npx"].name, "npx");
+        assert_eq!(servers["uvx"].name, "uvx");
+    }
+
+    // TDD Test 14: Server timeouts survive the merge
+    #[test]
+    fn test_merge_preserves_server_timeout() {
+        let base = ClaudeConfig::new().with_mcp_server(
+            "npx",
+            McpServer::new("npx", "npx", vec![]).with_timeout_ms(2000),
+        );
+
+        let merged = merge_configs(&base, &ClaudeConfig::new());
+
+        assert_eq!(merged.mcp_servers.unwrap()["npx"].timeout_ms, Some(2000));
+    }
+
+    // TDD Test 15: Deep-merged servers keep unset fields from the base
+    #[test]
+    fn test_deep_merge_servers_per_field() {
+        let global: ClaudeConfig = serde_json::from_str(
+            r#"{"mcpServers": {"github": {"command": "npx", "args": ["-y", "server-github"], "enabled": true, "env": {"A": "1"}}}}"#,
+        )
+        .unwrap();
+        let project: ClaudeConfig = serde_json::from_str(
+            r#"{"mcpServers": {"github": {"enabled": false, "env": {"B": "2"}}, "local": {"command": "node", "enabled": true}}}"#,
+        )
+        .unwrap();
+
+        let merged = merge_configs_with_options(
+            &global,
+            &project,
+            MergeOptions::new().with_deep_merge_servers(true),
+        );
+        let servers = merged.mcp_servers.unwrap();
+        let github = &servers["github"];
+        assert_eq!(github.command.as_deref(), Some("npx"));
+        assert_eq!(github.args, ["-y", "server-github"]);
+        assert!(!github.enabled);
+        assert_eq!(github.env.len(), 2);
+        assert_eq!(servers["local"].command.as_deref(), Some("node"));
+
+        // By default the project's server replaces the global one
+        let replaced = merge_configs(&global, &project).mcp_servers.unwrap();
+        assert_eq!(replaced["github"].command, None);
+        assert!(replaced["github"].args.is_empty());
+    }
+
+    // TDD Test 16: Replacing global instructions produces a note
+    #[test]
+    fn test_merge_notes_for_replaced_instructions() {
+        let instructions = |items: &[&str]| ClaudeConfig {
+            custom_instructions: Some(items.iter().map(|i| i.to_string()).collect()),
+            ..ClaudeConfig::new()
+        };
+        let global = instructions(&["Be brief", "Use tabs", "Cite sources"]);
+
+        // Nothing to lose
+        assert!(merge_notes(&ClaudeConfig::new(), &instructions(&["Be brief"])).is_empty());
+        assert!(merge_notes(&instructions(&[]), &instructions(&["Be brief"])).is_empty());
+        assert!(merge_notes(&global, &ClaudeConfig::new()).is_empty());
+        assert!(merge_notes(
+            &global,
+            &instructions(&["Cite sources", "Be brief", "Use tabs", "Write tests"])
+        )
+        .is_empty());
+
+        // An empty project list clears the global one
+        let notes = merge_notes(&global, &instructions(&[]));
+        assert_eq!(notes.len(), 1);
+        assert_eq!(notes[0].key_path, "customInstructions");
+        assert!(notes[0].message.contains("clears 3 global instructions"));
+
+        // A normal replacement counts the dropped entries
+        let notes = merge_notes(&global, &instructions(&["Be brief", "Write tests"]));
+        assert_eq!(notes.len(), 1);
+        assert!(notes[0]
+            .message
+            .contains("2 instruction(s) replace 3 global instructions (2 dropped)"));
+        assert!(notes[0].suggestion.contains("--append"));
+    }
+
+    // TDD Test 17: Merging N scoped configs attributes each key to its last setter
+    #[test]
+    fn test_merge_all_with_sources() {
+        let global = ClaudeConfig::new()
+            .with_mcp_server("fetch", McpServer::new("fetch", "npx", vec![]))
+            .with_mcp_server("git", McpServer::new("git", "uvx", vec![]))
+            .with_allowed_path("~/global")
+            .with_custom_instruction("Be brief");
+        let mut project = ClaudeConfig::new()
+            .with_mcp_server("git", McpServer::new("git", "git-mcp", vec![]))
+            .with_allowed_path("~/project");
+        project.unknown.insert(
+            "editor".to_string(),
+            serde_json::json!({"theme": "dark", "font": {"size": 12}}),
+        );
+        // A policy layer applied on top of the project
+        let mut policy = ClaudeConfig::new().with_custom_instruction("Never push");
+        policy
+            .unknown
+            .insert("editor".to_string(), serde_json::json!({"font": 14}));
+
+        let (merged, sources) = merge_all_with_sources(&[
+            (ConfigScope::Global, &global),
+            (ConfigScope::Project, &project),
+            (ConfigScope::Global, &policy),
+        ]);
+
+        let mut expected = merge_configs(&global, &project);
+        expected = merge_configs(&expected, &policy);
+        assert_eq!(merged, expected);
+
+        assert_eq!(sources.get("mcpServers.fetch"), Some(&ConfigScope::Global));
+        assert_eq!(
+            sources.get("mcpServers.fetch.command"),
+            Some(&ConfigScope::Global)
+        );
+        assert_eq!(sources.get("mcpServers.git"), Some(&ConfigScope::Project));
+        assert_eq!(
+            sources.get("mcpServers.git.command"),
+            Some(&ConfigScope::Project)
+        );
+        assert_eq!(sources.get("allowedPaths"), Some(&ConfigScope::Project));
+        assert_eq!(
+            sources.get("customInstructions"),
+            Some(&ConfigScope::Global)
+        );
+        assert_eq!(sources.get("editor"), Some(&ConfigScope::Global));
+        assert_eq!(sources.get("editor.theme"), Some(&ConfigScope::Project));
+        assert_eq!(sources.get("editor.font"), Some(&ConfigScope::Global));
+        // The policy's number replaced the project's object
+        assert_eq!(sources.get("editor.font.size"), None);
+
+        let (empty, sources) = merge_all_with_sources(&[]);
+        assert_eq!(empty, ClaudeConfig::new());
+        assert!(sources.sources.is_empty());
+    }
 }
@@ -3,10 +3,23 @@
 //! This module defines the structure of Claude Code configuration files
 //! following the specification in contracts/claude-config-spec.md.
 
+pub mod allowed_paths;
+pub mod diff;
+pub mod edit;
+pub mod embedded;
+pub mod inherit;
+pub mod init;
+pub(crate) mod json_hints;
+pub mod key_path;
+pub mod limits;
 pub mod manager;
 pub mod merge;
+pub mod normalize;
+pub mod schema;
+pub mod transaction;
 pub mod validation;
 
+use crate::paths::Platform;
 use crate::types::{McpServer, Skill};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -19,8 +32,14 @@ use std::collections::HashMap;
 pub struct ClaudeConfig {
     /// MCP (Model Context Protocol) server configurations
     ///
-    /// Maps server names to their configurations.
-    #[serde(rename = "mcpServers", skip_serializing_if = "Option::is_none")]
+    /// Maps server names to their configurations. An array of objects with
+    /// a `name` field is accepted too and converted to the map.
+    #[serde(
+        rename = "mcpServers",
+        default,
+        deserialize_with = "normalize::deserialize_servers",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub mcp_servers: Option<HashMap<String, McpServer>>,
 
     /// Filesystem paths that Claude Code is allowed to access
@@ -85,6 +104,60 @@ impl ClaudeConfig {
             .push(instruction.into());
         self
     }
+
+    /// Merge `server` into the server called `name`, field by field
+    ///
+    /// If there is no such server, `server` is added as is. Otherwise
+    /// `server`'s fields win where it sets them: `enabled` always, `command`
+    /// and `timeout` when present, `args` when non-empty, and `env` per
+    /// variable. An empty `args` therefore can't clear the existing arguments.
+    pub fn merge_server(&mut self, name: &str, server: &McpServer) {
+        let servers = self.mcp_servers.get_or_insert_with(HashMap::new);
+        let Some(existing) = servers.get_mut(name) else {
+            servers.insert(name.to_string(), server.clone());
+            return;
+        };
+
+        existing.enabled = server.enabled;
+        if server.command.is_some() {
+            existing.command.clone_from(&server.command);
+        }
+        if !server.args.is_empty() {
+            existing.args.clone_from(&server.args);
+        }
+        existing.env.extend(server.env.clone());
+        if server.timeout_ms.is_some() {
+            existing.timeout_ms = server.timeout_ms;
+        }
+        if server.platforms.is_some() {
+            existing.platforms.clone_from(&server.platforms);
+        }
+        if let Some(commands) = &server.command_by_platform {
+            existing
+                .command_by_platform
+                .get_or_insert_with(Default::default)
+                .extend(commands.clone());
+        }
+    }
+
+    /// Resolve the per-platform server settings for `platform`
+    ///
+    /// Servers whose `platforms` don't include `platform` are dropped, and
+    /// each remaining server's `command` becomes its `commandByPlatform`
+    /// entry for `platform`, if it has one. The per-platform fields are
+    /// kept, so the result still shows where a command came from.
+    pub fn resolve_platform(&mut self, platform: Platform) {
+        let Some(servers) = self.mcp_servers.as_mut() else {
+            return;
+        };
+
+        servers.retain(|_, server| server.runs_on(platform));
+        for server in servers.values_mut() {
+            if let Some(command) = server.command_for(platform) {
+                server.command = Some(command.to_string());
+            }
+        }
+    }
 }
 
 #[cfg(test)]
@@ -220,4 +293,83 @@ mod tests {
         assert!(config.allowed_paths.is_some());
         assert_eq!(config.allowed_paths.as_ref().unwrap().len(), 1);
     }
+
+    // TDD Test 9: Servers are filtered for an injected platform
+    #[test]
+    fn test_resolve_platform_filters_servers() {
+        let config = ClaudeConfig::new()
+            .with_mcp_server("everywhere", McpServer::new("everywhere", "npx", vec![]))
+            .with_mcp_server(
+                "mac-only",
+                McpServer::new("mac-only", "npx", vec![]).with_platforms(&[Platform::MacOs]),
+            )
+            .with_mcp_server(
+                "unix",
+                McpServer::new("unix", "npx", vec![])
+                    .with_platforms(&[Platform::MacOs, Platform::Linux]),
+            );
+
+        let names = |platform: Platform| {
+            let mut resolved = config.clone();
+            resolved.resolve_platform(platform);
+            let mut names: Vec<String> = resolved.mcp_servers.unwrap().into_keys().collect();
+            names.sort();
+            names
+        };
+
+        assert_eq!(names(Platform::MacOs), ["everywhere", "mac-only", "unix"]);
+        assert_eq!(names(Platform::Linux), ["everywhere", "unix"]);
+        assert_eq!(names(Platform::Windows), ["everywhere"]);
+    }
+
+    // TDD Test 10: commandByPlatform replaces the command, falling back to the base one
+    #[test]
+    fn test_resolve_platform_command() {
+        let server = McpServer::new("filesystem", "npx", vec![])
+            .with_platform_command(Platform::MacOs, "/opt/homebrew/bin/mcp-fs")
+            .with_platform_command(Platform::Linux, "/usr/bin/mcp-fs");
+        let config = ClaudeConfig::new().with_mcp_server("filesystem", server);
+
+        let command = |platform: Platform| {
+            let mut resolved = config.clone();
+            resolved.resolve_platform(platform);
+            resolved.mcp_servers.unwrap()["filesystem"].command.clone()
+        };
+
+        assert_eq!(command(Platform::Linux).as_deref(), Some("/usr/bin/mcp-fs"));
+        assert_eq!(
+            command(Platform::MacOs).as_deref(),
+            Some("/opt/homebrew/bin/mcp-fs")
+        );
+        assert_eq!(command(Platform::Windows).as_deref(), Some("npx"));
+    }
+
+    // TDD Test 11: Per-platform fields survive a round trip
+    #[test]
+    fn test_platform_fields_round_trip() {
+        let json = r#"{"mcpServers": {"filesystem": {
+            "enabled": true,
+            "command": "npx",
+            "platforms": ["macos", "linux"],
+            "commandByPlatform": {"macos": "mcp-fs-mac", "linux": "mcp-fs"}
+        }}}"#;
+
+        let config: ClaudeConfig = serde_json::from_str(json).unwrap();
+        let server = &config.mcp_servers.as_ref().unwrap()["filesystem"];
+        assert_eq!(server.platforms.as_deref().unwrap(), ["macos", "linux"]);
+        assert_eq!(server.command_for(Platform::MacOs), Some("mcp-fs-mac"));
+
+        let value = serde_json::to_value(&config).unwrap();
+        let written = &value["mcpServers"]["filesystem"];
+        assert_eq!(written["platforms"], serde_json::json!(["macos", "linux"]));
+        assert_eq!(
+            written["commandByPlatform"],
+            serde_json::json!({"macos": "mcp-fs-mac", "linux": "mcp-fs"})
+        );
+
+        // Unset fields stay out of the file
+        let plain = serde_json::to_value(McpServer::new("plain", "npx", vec![])).unwrap();
+        assert!(plain.get("platforms").is_none());
+        assert!(plain.get("commandByPlatform").is_none());
+    }
 }
@@ -0,0 +1,836 @@
+//! Configuration normalization
+//!
+//! This module rewrites hand-edited configuration files into ccm's canonical
+//! form. Files are read leniently (comments, trailing commas, string-form
+//! args), then a registry of [`NormalizationRule`]s cleans up the raw JSON
+//! before it is deserialized.
+
+use crate::{
+    config::{allowed_paths::resolve_allowed_path, ClaudeConfig},
+    error::{ConfigError, Result},
+    types::McpServer,
+};
+use serde::{
+    de::{
+        self, value::MapAccessDeserializer, value::SeqAccessDeserializer, MapAccess, SeqAccess,
+        Visitor,
+    },
+    Deserialize, Deserializer,
+};
+use serde_json::{Map, Value};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::hash::Hash;
+
+/// Trait for configuration normalization rules
+///
+/// Each rule rewrites one aspect of the raw configuration and describes
+/// every change it made. Rules work on the raw JSON so they can fix values
+/// that would not deserialize (e.g. `args` given as a string).
+pub trait NormalizationRule: Send + Sync {
+    /// Normalize the configuration in place
+    ///
+    /// Returns a description of each change made (empty if nothing changed).
+    fn normalize(&self, config: &mut Value) -> Vec<String>;
+
+    /// Get the name of this normalization rule (used with `--skip`)
+    fn name(&self) -> &'static str;
+}
+
+/// Convert string-form server `args` into arrays
+///
+/// `"args": "-y @modelcontextprotocol/server-fetch"` becomes
+/// `"args": ["-y", "@modelcontextprotocol/server-fetch"]`. Single and double
+/// quotes group words.
+#[derive(Debug, Clone, Default)]
+pub struct ArgsArrayRule;
+
+impl NormalizationRule for ArgsArrayRule {
+    fn normalize(&self, config: &mut Value) -> Vec<String> {
+        let mut changes = Vec::new();
+
+        let Some(servers) = config.get_mut("mcpServers").and_then(Value::as_object_mut) else {
+            return changes;
+        };
+
+        for (name, server) in servers.iter_mut() {
+            let Some(args) = server.get_mut("args") else {
+                continue;
+            };
+            if let Value::String(line) = args {
+                *args = split_args(line).into_iter().map(Value::String).collect();
+                changes.push(format!(
+                    "converted args string to array for server '{name}'"
+                ));
+            }
+        }
+
+        changes
+    }
+
+    fn name(&self) -> &'static str {
+        "args-array"
+    }
+}
+
+/// Convert an array-form `mcpServers` into the canonical map
+///
+/// Some tools write `"mcpServers": [{"name": "fetch", "command": "npx"}]`.
+/// Configs in that form load anyway (see [`deserialize_servers`]); this
+/// rule reports the conversion so the file can be rewritten.
+#[derive(Debug, Clone, Default)]
+pub struct ServersMapRule;
+
+impl NormalizationRule for ServersMapRule {
+    fn normalize(&self, config: &mut Value) -> Vec<String> {
+        let Some(Value::Array(items)) = config.get("mcpServers") else {
+            return Vec::new();
+        };
+
+        // Leave invalid arrays alone; deserializing reports the problem
+        let Ok(servers) = servers_array_to_map(items.clone()) else {
+            return Vec::new();
+        };
+        let count = servers.len();
+        config["mcpServers"] = Value::Object(servers);
+
+        vec![format!(
+            "converted mcpServers from an array of {count} named server(s) to a map"
+        )]
+    }
+
+    fn name(&self) -> &'static str {
+        "servers-map"
+    }
+}
+
+/// Key an array of named server objects by their `name` field
+///
+/// # Errors
+/// Returns a message naming the offending entry if one isn't an object
+/// with a string `name`, or if two entries share a name
+pub fn servers_array_to_map(items: Vec<Value>) -> std::result::Result<Map<String, Value>, String> {
+    let mut servers = Map::new();
+
+    for (index, item) in items.into_iter().enumerate() {
+        let Value::Object(mut server) = item else {
+            return Err(format!("mcpServers[{index}] is not an object"));
+        };
+        let Some(Value::String(name)) = server.remove("name") else {
+            return Err(format!(
+                "mcpServers[{index}] has no \"name\" field; array entries need a name"
+            ));
+        };
+        if servers.contains_key(&name) {
+            return Err(format!(
+                "duplicate MCP server name '{name}' in mcpServers (entry {index})"
+            ));
+        }
+        servers.insert(name, Value::Object(server));
+    }
+
+    Ok(servers)
+}
+
+/// Deserialize `mcpServers` from the canonical map or an array of named objects
+///
+/// The array form is converted into the map, which is what ccm writes back.
+/// Map entries are deserialized in place, so their errors keep the exact
+/// position in the file.
+pub(crate) fn deserialize_servers<'de, D>(
+    deserializer: D,
+) -> std::result::Result<Option<HashMap<String, McpServer>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    struct ServersVisitor;
+
+    impl<'de> Visitor<'de> for ServersVisitor {
+        type Value = Option<HashMap<String, McpServer>>;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("a map of MCP servers or an array of named server objects")
+        }
+
+        fn visit_none<E: de::Error>(self) -> std::result::Result<Self::Value, E> {
+            Ok(None)
+        }
+
+        fn visit_unit<E: de::Error>(self) -> std::result::Result<Self::Value, E> {
+            Ok(None)
+        }
+
+        fn visit_some<D: Deserializer<'de>>(
+            self,
+            deserializer: D,
+        ) -> std::result::Result<Self::Value, D::Error> {
+            deserializer.deserialize_any(self)
+        }
+
+        fn visit_map<A: MapAccess<'de>>(
+            self,
+            map: A,
+        ) -> std::result::Result<Self::Value, A::Error> {
+            HashMap::deserialize(MapAccessDeserializer::new(map)).map(Some)
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(
+            self,
+            seq: A,
+        ) -> std::result::Result<Self::Value, A::Error> {
+            let items = Vec::<Value>::deserialize(SeqAccessDeserializer::new(seq))?;
+            let servers = servers_array_to_map(items).map_err(de::Error::custom)?;
+            serde_json::from_value(Value::Object(servers))
+                .map(Some)
+                .map_err(de::Error::custom)
+        }
+    }
+
+    deserializer.deserialize_option(ServersVisitor)
+}
+
+/// Remove allowed paths that resolve to the same location
+///
+/// Paths are compared after expanding environment variables and `~`, so
+/// `~/code` and `$HOME/code` are duplicates. The first spelling is kept.
+#[derive(Debug, Clone, Default)]
+pub struct DedupePathsRule;
+
+impl NormalizationRule for DedupePathsRule {
+    fn normalize(&self, config: &mut Value) -> Vec<String> {
+        let mut changes = Vec::new();
+
+        let Some(paths) = config.get_mut("allowedPaths").and_then(Value::as_array_mut) else {
+            return changes;
+        };
+
+        retain_first(
+            paths,
+            |path| path.as_str().map(resolve_allowed_path),
+            |path| {
+                changes.push(format!(
+                    "removed duplicate allowed path '{}'",
+                    path.as_str().unwrap_or_default()
+                ))
+            },
+        );
+
+        changes
+    }
+
+    fn name(&self) -> &'static str {
+        "dedupe-paths"
+    }
+}
+
+/// Remove exact-duplicate instructions, keeping the first occurrence
+///
+/// # Returns
+/// Number of entries removed
+pub fn dedupe_instructions(instructions: &mut Vec<String>) -> usize {
+    let before = instructions.len();
+    retain_first(
+        instructions,
+        |instruction| Some(instruction.clone()),
+        |_| {},
+    );
+    before - instructions.len()
+}
+
+/// Remove items whose key was already seen, keeping the first occurrence
+///
+/// Items without a key are always kept; `on_removed` is called for each
+/// item that is dropped.
+pub(crate) fn retain_first<T, K: Eq + Hash>(
+    items: &mut Vec<T>,
+    mut key: impl FnMut(&T) -> Option<K>,
+    mut on_removed: impl FnMut(&T),
+) {
+    let mut seen = HashSet::new();
+    items.retain(|item| {
+        let first = key(item).map_or(true, |key| seen.insert(key));
+        if !first {
+            on_removed(item);
+        }
+        first
+    });
+}
+
+/// Drop empty `mcpServers` and `skills` maps
+#[derive(Debug, Clone, Default)]
+pub struct EmptyMapsRule;
+
+impl NormalizationRule for EmptyMapsRule {
+    fn normalize(&self, config: &mut Value) -> Vec<String> {
+        let mut changes = Vec::new();
+
+        let Some(root) = config.as_object_mut() else {
+            return changes;
+        };
+
+        for key in ["mcpServers", "skills"] {
+            if root
+                .get(key)
+                .is_some_and(|v| v.as_object().is_some_and(|m| m.is_empty()))
+            {
+                root.remove(key);
+                changes.push(format!("removed empty {key} map"));
+            }
+        }
+
+        changes
+    }
+
+    fn name(&self) -> &'static str {
+        "empty-maps"
+    }
+}
+
+/// All normalization rules, in the order they run
+pub fn default_rules() -> Vec<Box<dyn NormalizationRule>> {
+    vec![
+        Box::<ServersMapRule>::default(),
+        Box::<ArgsArrayRule>::default(),
+        Box::<DedupePathsRule>::default(),
+        Box::<EmptyMapsRule>::default(),
+    ]
+}
+
+/// A change made while normalizing
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NormalizationChange {
+    /// Name of the rule that made the change
+    pub rule: &'static str,
+
+    /// Human-readable description
+    pub description: String,
+}
+
+/// Result of normalizing a configuration file
+#[derive(Debug, Clone)]
+pub struct Normalized {
+    /// Normalized configuration
+    pub config: ClaudeConfig,
+
+    /// Changes made by the rules, in order
+    pub changes: Vec<NormalizationChange>,
+
+    /// The configuration rendered in canonical form
+    pub canonical: String,
+}
+
+/// Applies normalization rules to configuration text
+pub struct Normalizer {
+    /// Rules to run
+    rules: Vec<Box<dyn NormalizationRule>>,
+}
+
+impl Normalizer {
+    /// Create a normalizer running all default rules
+    pub fn new() -> Self {
+        Self {
+            rules: default_rules(),
+        }
+    }
+
+    /// Disable a rule by name
+    ///
+    /// # Errors
+    /// Returns an error if no rule has that name
+    pub fn skip(mut self, name: &str) -> Result<Self> {
+        if !self.rules.iter().any(|rule| rule.name() == name) {
+            return Err(ConfigError::validation_failed(
+                "NormalizationRule",
+                format!("Unknown normalization rule: '{name}'"),
+                format!("Use one of: {}", Self::new().rule_names().join(", ")),
+            ));
+        }
+
+        self.rules.retain(|rule| rule.name() != name);
+        Ok(self)
+    }
+
+    /// Names of the rules that will run
+    pub fn rule_names(&self) -> Vec<&'static str> {
+        self.rules.iter().map(|rule| rule.name()).collect()
+    }
+
+    /// Normalize configuration text
+    ///
+    /// # Arguments
+    /// * `text` - Configuration file content (JSON or JSONC)
+    ///
+    /// # Errors
+    /// Returns an error if the text is not valid JSONC, or the normalized
+    /// JSON does not form a configuration
+    pub fn normalize_str(&self, text: &str) -> Result<Normalized> {
+        let mut value: Value = serde_json::from_str(&strip_jsonc(text))?;
+        let mut changes = Vec::new();
+
+        for rule in &self.rules {
+            changes.extend(rule.normalize(&mut value).into_iter().map(|description| {
+                NormalizationChange {
+                    rule: rule.name(),
+                    description,
+                }
+            }));
+        }
+
+        let mut config: ClaudeConfig = serde_json::from_value(value)?;
+        restore_names(&mut config);
+
+        let canonical = if config.ccm_settings().canonical_on_write {
+            canonical_json_strict(&config)?
+        } else {
+            canonical_json(&config)?
+        };
+
+        Ok(Normalized {
+            config,
+            changes,
+            canonical,
+        })
+    }
+}
+
+impl Default for Normalizer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Render a configuration in canonical form
+///
+/// Keys are sorted at every level and the output is pretty-printed with
+/// two-space indentation, so the same configuration always produces the
+/// same text.
+pub fn canonical_json(config: &ClaudeConfig) -> Result<String> {
+    let value = serde_json::to_value(config)
+        .map_err(|e| ConfigError::Generic(format!("Failed to serialize config: {e}")))?;
+    serde_json::to_string_pretty(&value)
+        .map_err(|e| ConfigError::Generic(format!("Failed to serialize config: {e}")))
+}
+
+/// Whether the order of an array's entries matters
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArrayOrder {
+    /// Entries are read in sequence; never reorder them
+    Ordered,
+    /// Entries form a set; strict canonical form sorts them
+    Unordered,
+}
+
+/// Order sensitivity of the arrays ccm knows, by key path
+///
+/// `*` matches any single key (e.g. a server name). Arrays not listed are
+/// treated as [`ArrayOrder::Ordered`]. The diff compares unordered arrays by
+/// value, too.
+pub const ARRAY_ORDER: &[(&str, ArrayOrder)] = &[
+    ("allowedPaths", ArrayOrder::Unordered),
+    ("customInstructions", ArrayOrder::Ordered),
+    ("mcpServers.*.args", ArrayOrder::Ordered),
+];
+
+/// Look up the order sensitivity of the array at `key_path`
+pub fn array_order(key_path: &str) -> ArrayOrder {
+    let segments: Vec<&str> = key_path.split('.').collect();
+    ARRAY_ORDER
+        .iter()
+        .find(|(pattern, _)| {
+            let pattern: Vec<&str> = pattern.split('.').collect();
+            pattern.len() == segments.len()
+                && pattern
+                    .iter()
+                    .zip(&segments)
+                    .all(|(p, s)| *p == "*" || p == s)
+        })
+        .map_or(ArrayOrder::Ordered, |(_, order)| *order)
+}
+
+/// Sort the string arrays [`ARRAY_ORDER`] marks as unordered
+///
+/// # Returns
+/// Key paths of the arrays whose order changed
+pub fn sort_unordered_arrays(value: &mut Value) -> Vec<String> {
+    fn walk(value: &mut Value, path: &mut Vec<String>, sorted: &mut Vec<String>) {
+        match value {
+            Value::Object(map) => {
+                for (key, child) in map.iter_mut() {
+                    path.push(key.clone());
+                    walk(child, path, sorted);
+                    path.pop();
+                }
+            }
+            Value::Array(items) => {
+                let key_path = path.join(".");
+                if array_order(&key_path) == ArrayOrder::Unordered
+                    && items.iter().all(Value::is_string)
+                {
+                    let before = items.clone();
+                    items.sort_by(|a, b| a.as_str().cmp(&b.as_str()));
+                    if *items != before {
+                        sorted.push(key_path);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut sorted = Vec::new();
+    walk(value, &mut Vec::new(), &mut sorted);
+    sorted
+}
+
+/// Copy of a configuration with its unordered arrays sorted
+///
+/// # Errors
+/// Returns an error if the configuration can't be converted to JSON
+pub fn sort_unordered(config: &ClaudeConfig) -> Result<ClaudeConfig> {
+    let mut value = serde_json::to_value(config)?;
+    sort_unordered_arrays(&mut value);
+    let mut sorted: ClaudeConfig = serde_json::from_value(value)?;
+    restore_names(&mut sorted);
+    Ok(sorted)
+}
+
+/// Render a configuration in strict canonical form, for files tracked in git
+///
+/// Like [`canonical_json`], with the arrays [`ARRAY_ORDER`] marks as
+/// unordered sorted and a trailing newline, so the same logical
+/// configuration always produces byte-identical files. Used for configs
+/// that set `"ccm": {"canonicalOnWrite": true}`.
+pub fn canonical_json_strict(config: &ClaudeConfig) -> Result<String> {
+    let mut json = canonical_json(&sort_unordered(config)?)?;
+    json.push('\n');
+    Ok(json)
+}
+
+/// Names are not deserialized (the map key is the name), so restore them
+pub(crate) fn restore_names(config: &mut ClaudeConfig) {
+    for (name, server) in config.mcp_servers.iter_mut().flatten() {
+        server.name = name.clone();
+    }
+    for (name, skill) in config.skills.iter_mut().flatten() {
+        skill.name = name.clone();
+    }
+}
+
+/// Strip comments and trailing commas from JSONC text
+///
+/// Handles `//` line comments, `/* */` block comments and commas directly
+/// before `}` or `]`. String contents are left untouched.
+pub fn strip_jsonc(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    let mut in_string = false;
+
+    while let Some(c) = chars.next() {
+        if in_string {
+            out.push(c);
+            match c {
+                '\\' => out.extend(chars.next()),
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+
+        match c {
+            '"' => {
+                in_string = true;
+                out.push(c);
+            }
+            '/' if chars.peek() == Some(&'/') => {
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        out.push('\n');
+                        break;
+                    }
+                }
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut prev = '\0';
+                for c in chars.by_ref() {
+                    if prev == '*' && c == '/' {
+                        break;
+                    }
+                    if c == '\n' {
+                        out.push('\n');
+                    }
+                    prev = c;
+                }
+            }
+            '}' | ']' => {
+                // Drop a trailing comma (and keep the whitespace after it)
+                let trimmed = out.trim_end();
+                if trimmed.ends_with(',') {
+                    let comma = trimmed.len() - 1;
+                    out.remove(comma);
+                }
+                out.push(c);
+            }
+            _ => out.push(c),
+        }
+    }
+
+    out
+}
+
+/// Split a command line into words, honouring single and double quotes
+fn split_args(line: &str) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut in_word = false;
+    let mut quote = None;
+
+    for c in line.chars() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => current.push(c),
+            None if c == '"' || c == '\'' => {
+                quote = Some(c);
+                in_word = true;
+            }
+            None if c.is_whitespace() => {
+                if in_word {
+                    args.push(std::mem::take(&mut current));
+                    in_word = false;
+                }
+            }
+            None => {
+                current.push(c);
+                in_word = true;
+            }
+        }
+    }
+
+    if in_word {
+        args.push(current);
+    }
+
+    args
+}
+
+/// Quote a word so that [`split_args`] (and a POSIX shell) reads it back as-is
+///
+/// Words without whitespace or quotes are returned unchanged.
+pub(crate) fn quote_arg(arg: &str) -> String {
+    let plain = !arg.is_empty()
+        && !arg
+            .chars()
+            .any(|c| c.is_whitespace() || c == '"' || c == '\'');
+    if plain {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', r#"'"'"'"#))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    // TDD Test 1: String args are split into arrays
+    #[test]
+    fn test_args_array_rule() {
+        let mut config = json!({
+            "mcpServers": {
+                "fetch": { "command": "uvx", "args": "mcp-server-fetch --ua 'ccm bot'" },
+                "npx": { "command": "npx", "args": ["-y"] }
+            }
+        });
+
+        let changes = ArgsArrayRule.normalize(&mut config);
+
+        assert_eq!(
+            changes,
+            vec!["converted args string to array for server 'fetch'"]
+        );
+        assert_eq!(
+            config["mcpServers"]["fetch"]["args"],
+            json!(["mcp-server-fetch", "--ua", "ccm bot"])
+        );
+        assert_eq!(config["mcpServers"]["npx"]["args"], json!(["-y"]));
+    }
+
+    // TDD Test 1b: Quoted words split back into the original args
+    #[test]
+    fn test_quote_arg_round_trips() {
+        let args = ["-y", "ccm bot", "it's", r#"say "hi""#, ""];
+        let line = args.map(quote_arg).join(" ");
+
+        assert_eq!(quote_arg("-y"), "-y");
+        assert_eq!(quote_arg("ccm bot"), "'ccm bot'");
+        assert_eq!(split_args(&line), args);
+    }
+
+    // TDD Test 1c: Strict canonical form is byte-identical across input orderings
+    #[test]
+    fn test_canonical_json_strict_is_order_independent() {
+        let first: ClaudeConfig = serde_json::from_str(
+            r#"{"allowedPaths": ["~/b", "~/a"], "mcpServers": {"s": {"command": "x", "args": ["-y", "--b", "--a"], "enabled": true}}}"#,
+        )
+        .unwrap();
+        let second: ClaudeConfig = serde_json::from_str(
+            r#"{"mcpServers": {"s": {"enabled": true, "args": ["-y", "--b", "--a"], "command": "x"}}, "allowedPaths": ["~/a", "~/b"]}"#,
+        )
+        .unwrap();
+
+        let json = canonical_json_strict(&first).unwrap();
+        assert_eq!(json, canonical_json_strict(&second).unwrap());
+        assert!(json.ends_with("}\n"));
+        assert!(!json.contains('\r'));
+
+        let value: Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["allowedPaths"], json!(["~/a", "~/b"]));
+        // args are order-sensitive
+        assert_eq!(
+            value["mcpServers"]["s"]["args"],
+            json!(["-y", "--b", "--a"])
+        );
+
+        assert_eq!(array_order("mcpServers.fetch.args"), ArrayOrder::Ordered);
+        assert_eq!(array_order("allowedPaths"), ArrayOrder::Unordered);
+        assert_eq!(array_order("somethingNew"), ArrayOrder::Ordered);
+    }
+
+    // TDD Test 1d: Array-form servers are converted to a map, or rejected clearly
+    #[test]
+    fn test_servers_map_rule() {
+        let normalized = Normalizer::new()
+            .normalize_str(r#"{"mcpServers": [{"name": "fetch", "command": "uvx", "args": "mcp-fetch --x", "enabled": true}]}"#)
+            .unwrap();
+        assert_eq!(normalized.changes[0].rule, "servers-map");
+        assert_eq!(normalized.changes[1].rule, "args-array");
+        let servers = normalized.config.mcp_servers.unwrap();
+        assert_eq!(servers["fetch"].args, ["mcp-fetch", "--x"]);
+        assert!(normalized.canonical.contains(r#""fetch": {"#));
+
+        let missing = serde_json::from_str::<ClaudeConfig>(
+            r#"{"mcpServers": [{"name": "a", "enabled": true}, {"command": "b", "enabled": true}]}"#,
+        )
+        .unwrap_err();
+        assert!(missing
+            .to_string()
+            .contains("mcpServers[1] has no \"name\" field"));
+
+        // The map form still reports errors at their position
+        let invalid =
+            serde_json::from_str::<ClaudeConfig>("{\"mcpServers\": {\"a\": {\"enabled\": 1}}}")
+                .unwrap_err();
+        assert_eq!(invalid.column(), 34);
+    }
+
+    // TDD Test 2: Allowed paths are deduplicated after expansion
+    #[test]
+    fn test_dedupe_paths_rule() {
+        let home = dirs::home_dir().unwrap();
+        let mut config = json!({
+            "allowedPaths": [
+                "~/code",
+                home.join("code").to_string_lossy(),
+                "/tmp",
+                "/tmp"
+            ]
+        });
+
+        let changes = DedupePathsRule.normalize(&mut config);
+
+        assert_eq!(changes.len(), 2);
+        assert_eq!(config["allowedPaths"], json!(["~/code", "/tmp"]));
+    }
+
+    // TDD Test 2b: Duplicate instructions are only removed on request
+    #[test]
+    fn test_instructions_kept_by_default() {
+        let input = r#"{"customInstructions": ["Use tabs", "Be brief", "Use tabs"]}"#;
+
+        let normalized = Normalizer::new().normalize_str(input).unwrap();
+        assert_eq!(
+            normalized.config.custom_instructions.as_deref().unwrap(),
+            ["Use tabs", "Be brief", "Use tabs"]
+        );
+
+        let mut instructions = normalized.config.custom_instructions.unwrap();
+        assert_eq!(dedupe_instructions(&mut instructions), 1);
+        assert_eq!(instructions, ["Use tabs", "Be brief"]);
+    }
+
+    // TDD Test 3: Empty server and skill maps are dropped
+    #[test]
+    fn test_empty_maps_rule() {
+        let mut config = json!({ "mcpServers": {}, "skills": {}, "allowedPaths": [] });
+
+        let changes = EmptyMapsRule.normalize(&mut config);
+
+        assert_eq!(changes.len(), 2);
+        assert_eq!(config, json!({ "allowedPaths": [] }));
+    }
+
+    // TDD Test 4: JSONC comments and trailing commas are stripped
+    #[test]
+    fn test_strip_jsonc() {
+        let text = r#"{
+            // line comment
+            "a": "http://x/*y*/", /* block */
+            "b": [1, 2,],
+        }"#;
+
+        let value: Value = serde_json::from_str(&strip_jsonc(text)).unwrap();
+
+        assert_eq!(value, json!({ "a": "http://x/*y*/", "b": [1, 2] }));
+    }
+
+    // TDD Test 5: End-to-end fixture needing several rules at once
+    #[test]
+    fn test_normalize_fixture() {
+        let text = r#"{
+            // Hand-edited
+            "skills": {},
+            "mcpServers": {
+                "fetch": { "enabled": true, "command": "uvx", "args": "mcp-server-fetch" },
+            },
+            "allowedPaths": ["/srv", "/srv"],
+        }"#;
+
+        let normalized = Normalizer::new().normalize_str(text).unwrap();
+        let rules: Vec<_> = normalized.changes.iter().map(|c| c.rule).collect();
+
+        assert_eq!(rules, vec!["args-array", "dedupe-paths", "empty-maps"]);
+        assert_eq!(
+            normalized.config.mcp_servers.as_ref().unwrap()["fetch"].args,
+            vec!["mcp-server-fetch"]
+        );
+        assert_eq!(normalized.config.allowed_paths, Some(vec!["/srv".into()]));
+        assert!(normalized.config.skills.is_none());
+        assert!(
+            normalized.canonical.find("allowedPaths") < normalized.canonical.find("mcpServers")
+        );
+
+        // Canonical output is stable
+        let again = Normalizer::new()
+            .normalize_str(&normalized.canonical)
+            .unwrap();
+        assert!(again.changes.is_empty());
+        assert_eq!(again.canonical, normalized.canonical);
+    }
+
+    // TDD Test 6: Skipped rules don't run; unknown rules are rejected
+    #[test]
+    fn test_normalizer_skip() {
+        let text = r#"{"mcpServers": {}, "allowedPaths": ["/a", "/a"]}"#;
+
+        let normalized = Normalizer::new()
+            .skip("empty-maps")
+            .unwrap()
+            .normalize_str(text)
+            .unwrap();
+
+        assert_eq!(normalized.changes.len(), 1);
+        assert!(normalized.config.mcp_servers.is_some());
+        assert!(Normalizer::new().skip("bogus").is_err());
+    }
+}
@@ -0,0 +1,250 @@
+//! Optional JSON Schema check of a whole config file
+//!
+//! [`validate_config`](crate::validate_config) only sees what
+//! [`ClaudeConfig`](crate::ClaudeConfig) models; anything else ends up in
+//! lenient unknown maps. The schemas bundled under `schemas/` approximate the
+//! format Claude Code accepts, and checking the raw JSON document against
+//! one catches mistakes such as `args` given as a number.
+//!
+//! Schemas are versioned assets: a new version is a new
+//! `schemas/claude-config-v<N>.json` file and an entry in [`BUNDLED_SCHEMAS`].
+
+use crate::error::{ConfigError, Result};
+use jsonschema::error::{TypeKind, ValidationErrorKind};
+use serde::Serialize;
+use serde_json::Value;
+use std::fmt;
+
+/// Bundled schemas, by version (oldest first)
+const BUNDLED_SCHEMAS: &[(u32, &str)] = &[(1, include_str!("../../schemas/claude-config-v1.json"))];
+
+/// Schema version used when none is requested
+pub const LATEST_SCHEMA_VERSION: u32 = 1;
+
+/// A place where a config document doesn't match the schema
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SchemaViolation {
+    /// JSON Pointer to the offending value (empty for the whole document)
+    pub pointer: String,
+
+    /// What is wrong, e.g. "expected array, found number"
+    pub message: String,
+
+    /// Expected type or values, when the schema names them
+    pub expected: Option<String>,
+}
+
+impl fmt::Display for SchemaViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let location = if self.pointer.is_empty() {
+            "(document)"
+        } else {
+            &self.pointer
+        };
+        write!(f, "{location}: {}", self.message)
+    }
+}
+
+/// Versions of the bundled schema
+pub fn schema_versions() -> Vec<u32> {
+    BUNDLED_SCHEMAS
+        .iter()
+        .map(|(version, _)| *version)
+        .collect()
+}
+
+/// Check a raw config document against the latest bundled schema
+///
+/// # Returns
+/// Every violation found; empty if the document matches
+pub fn validate_against_schema(raw: &Value) -> Vec<SchemaViolation> {
+    validate_against_schema_version(raw, LATEST_SCHEMA_VERSION)
+        .expect("the latest schema version is bundled and valid")
+}
+
+/// Check a raw config document against a specific bundled schema version
+///
+/// # Errors
+/// Returns an error if no schema with that version is bundled
+pub fn validate_against_schema_version(raw: &Value, version: u32) -> Result<Vec<SchemaViolation>> {
+    let source = BUNDLED_SCHEMAS
+        .iter()
+        .find(|(bundled, _)| *bundled == version)
+        .map(|(_, source)| *source)
+        .ok_or_else(|| {
+            let available: Vec<String> = schema_versions().iter().map(u32::to_string).collect();
+            ConfigError::validation_failed(
+                "SchemaVersion",
+                format!("No bundled schema with version {version}"),
+                format!("Use one of: {}", available.join(", ")),
+            )
+        })?;
+
+    let schema: Value = serde_json::from_str(source)?;
+    let validator = jsonschema::validator_for(&schema).map_err(|e| {
+        ConfigError::Generic(format!("Bundled schema version {version} is invalid: {e}"))
+    })?;
+
+    Ok(validator
+        .iter_errors(raw)
+        .map(|error| {
+            let (message, expected) = describe(&error.kind, &error.instance);
+            SchemaViolation {
+                pointer: error.instance_path.to_string(),
+                message: message.unwrap_or_else(|| error.to_string()),
+                expected,
+            }
+        })
+        .collect())
+}
+
+/// Readable message and expectation for the common kinds of violation
+fn describe(kind: &ValidationErrorKind, instance: &Value) -> (Option<String>, Option<String>) {
+    match kind {
+        ValidationErrorKind::Type { kind } => {
+            let expected = match kind {
+                TypeKind::Single(json_type) => json_type.to_string(),
+                TypeKind::Multiple(types) => types
+                    .iter()
+                    .map(|json_type| json_type.to_string())
+                    .collect::<Vec<_>>()
+                    .join(" or "),
+            };
+            (
+                Some(format!(
+                    "expected {expected}, found {}",
+                    type_name(instance)
+                )),
+                Some(expected),
+            )
+        }
+        ValidationErrorKind::Enum { options } => {
+            let expected = match options {
+                Value::Array(values) => values
+                    .iter()
+                    .map(Value::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                other => other.to_string(),
+            };
+            (
+                Some(format!("expected one of {expected}, found {instance}")),
+                Some(expected),
+            )
+        }
+        ValidationErrorKind::Required { property } => {
+            (Some(format!("missing required property {property}")), None)
+        }
+        _ => (None, None),
+    }
+}
+
+/// JSON type name of a value
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    // TDD Test 1: A well-formed config has no violations
+    #[test]
+    fn test_valid_config_passes() {
+        let raw = json!({
+            "mcpServers": {
+                "fetch": {"command": "uvx", "args": ["mcp-fetch"], "enabled": true, "type": "stdio"}
+            },
+            "allowedPaths": ["~/projects"],
+            "skills": {"review": {"enabled": true, "parameters": {"depth": 2}}},
+            "customInstructions": ["Be concise"],
+            "futureFeature": {"anything": 1}
+        });
+
+        assert_eq!(validate_against_schema(&raw), []);
+    }
+
+    // TDD Test 2: A wrongly typed nested field is reported with its location
+    #[test]
+    fn test_type_violation() {
+        let raw = json!({"mcpServers": {"fetch": {"command": "uvx", "args": 3, "enabled": true}}});
+
+        let violations = validate_against_schema(&raw);
+
+        assert_eq!(
+            violations,
+            [SchemaViolation {
+                pointer: "/mcpServers/fetch/args".to_string(),
+                message: "expected array, found number".to_string(),
+                expected: Some("array".to_string()),
+            }]
+        );
+        assert_eq!(
+            violations[0].to_string(),
+            "/mcpServers/fetch/args: expected array, found number"
+        );
+    }
+
+    // TDD Test 3: A value outside an enum lists the accepted values
+    #[test]
+    fn test_enum_violation() {
+        let raw = json!({"mcpServers": {"fetch": {"enabled": true, "type": "websocket"}}});
+
+        let violations = validate_against_schema(&raw);
+
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].pointer, "/mcpServers/fetch/type");
+        assert_eq!(
+            violations[0].message,
+            r#"expected one of "stdio", "sse", "http", found "websocket""#
+        );
+    }
+
+    // TDD Test 4: A missing required property is reported on its parent
+    #[test]
+    fn test_required_violation() {
+        let raw = json!({"skills": {"review": {"parameters": {}}}});
+
+        let violations = validate_against_schema(&raw);
+
+        assert_eq!(violations.len(), 1);
+        assert_eq!(
+            violations[0].to_string(),
+            r#"/skills/review: missing required property "enabled""#
+        );
+        assert_eq!(violations[0].expected, None);
+    }
+
+    // TDD Test 6: `command` may be a string or an array of strings
+    #[test]
+    fn test_command_array_form() {
+        let raw =
+            json!({"mcpServers": {"fetch": {"command": ["uvx", "mcp-fetch"], "enabled": true}}});
+        assert_eq!(validate_against_schema(&raw), []);
+
+        let raw = json!({"mcpServers": {"fetch": {"command": ["uvx", 1], "enabled": true}}});
+        let violations = validate_against_schema(&raw);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].pointer, "/mcpServers/fetch/command");
+    }
+
+    // TDD Test 5: Unknown schema versions are rejected
+    #[test]
+    fn test_unknown_schema_version() {
+        assert_eq!(schema_versions(), [LATEST_SCHEMA_VERSION]);
+
+        let err = validate_against_schema_version(&json!({}), 99).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("No bundled schema with version 99"));
+        assert!(err.to_string().contains("Use one of: 1"));
+    }
+}
@@ -0,0 +1,379 @@
+//! All-or-nothing writes across several configuration files
+//!
+//! Some operations change two configs at once, e.g. moving an MCP server from
+//! the global config into a project. Writing them one after the other can
+//! leave the first file changed when the second write fails. A
+//! [`Transaction`] stages the new contents of every file, then:
+//!
+//! 1. validates and serializes all of them,
+//! 2. backs up every existing target,
+//! 3. writes all temp files,
+//! 4. renames the temp files over their targets.
+//!
+//! If a rename fails, targets that were already replaced are restored from
+//! the backups just taken (or removed, if they didn't exist before), and the
+//! error lists the state each file ended in.
+
+use crate::{
+    backup::copy_permissions,
+    config::{
+        manager::{refuse_embedded_shadow, ConfigManager},
+        normalize::{canonical_json, canonical_json_strict},
+        validation::validate_config,
+        ClaudeConfig,
+    },
+    error::{ConfigError, Result},
+};
+use std::fmt;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::PathBuf;
+
+/// State a file was left in by a failed transaction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FileState {
+    /// The file was never touched
+    Unchanged,
+
+    /// The file was replaced and then restored to its previous content
+    /// (or removed again, if it didn't exist before)
+    RolledBack,
+
+    /// The file was replaced but could not be restored
+    RollbackFailed {
+        /// Backup holding the previous content, if the file existed
+        backup: Option<PathBuf>,
+    },
+}
+
+impl fmt::Display for FileState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FileState::Unchanged => write!(f, "unchanged"),
+            FileState::RolledBack => write!(f, "rolled back to its previous content"),
+            FileState::RollbackFailed {
+                backup: Some(backup),
+            } => write!(f, "NOT rolled back; restore it from {}", backup.display()),
+            FileState::RollbackFailed { backup: None } => {
+                write!(
+                    f,
+                    "NOT rolled back; it did not exist before and can be deleted"
+                )
+            }
+        }
+    }
+}
+
+/// Staged writes to several config files
+///
+/// # Example
+/// ```no_run
+/// use claude_config_manager_core::{ClaudeConfig, ConfigManager, Transaction};
+///
+/// let manager = ConfigManager::new("/tmp/backups");
+/// Transaction::new()
+///     .stage("/home/me/.config/claude/config.json", ClaudeConfig::new())
+///     .stage("/work/app/.claude/config.json", ClaudeConfig::new())
+///     .commit(&manager)?;
+/// # Ok::<(), claude_config_manager_core::ConfigError>(())
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Transaction {
+    /// Target files and their new content, in staging order
+    staged: Vec<(PathBuf, ClaudeConfig)>,
+}
+
+/// A staged file while the transaction is being committed
+struct Target {
+    /// Config file to replace
+    path: PathBuf,
+    /// Temp file holding the new content
+    temp: PathBuf,
+    /// Serialized new content
+    json: String,
+    /// Backup of the previous content (None if the file didn't exist)
+    backup: Option<PathBuf>,
+}
+
+impl Transaction {
+    /// Create an empty transaction
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stage new content for `path`
+    ///
+    /// Staging the same path again replaces the earlier content.
+    pub fn stage(mut self, path: impl Into<PathBuf>, config: ClaudeConfig) -> Self {
+        let path = path.into();
+        match self.staged.iter_mut().find(|(staged, _)| *staged == path) {
+            Some(entry) => entry.1 = config,
+            None => self.staged.push((path, config)),
+        }
+        self
+    }
+
+    /// Number of staged files
+    pub fn len(&self) -> usize {
+        self.staged.len()
+    }
+
+    /// Whether nothing is staged
+    pub fn is_empty(&self) -> bool {
+        self.staged.is_empty()
+    }
+
+    /// Write all staged files, or none of them
+    ///
+    /// # Arguments
+    /// * `manager` - Config manager whose backup manager backs up the targets
+    ///
+    /// # Errors
+    /// Returns an error without touching any file if a config is invalid, a
+    /// backup fails or a temp file can't be written. If replacing a file
+    /// fails, the files already replaced are rolled back and a
+    /// [`ConfigError::TransactionFailed`] reports the state of every file.
+    pub fn commit(self, manager: &ConfigManager) -> Result<()> {
+        // Validate and serialize everything before touching the filesystem
+        let mut targets = Vec::with_capacity(self.staged.len());
+        for (path, config) in &self.staged {
+            if !path.exists() {
+                refuse_embedded_shadow(path)?;
+            }
+            validate_config(config)?;
+            targets.push(Target {
+                temp: path.with_extension("tmp"),
+                path: path.clone(),
+                json: if config.ccm_settings().canonical_on_write {
+                    canonical_json_strict(config)?
+                } else {
+                    canonical_json(config)?
+                },
+                backup: None,
+            });
+        }
+
+        for target in &mut targets {
+            if target.path.is_file() {
+                target.backup = Some(manager.backup_manager().create_backup(&target.path)?);
+            }
+        }
+
+        for (index, target) in targets.iter().enumerate() {
+            if let Err(e) = write_temp(target) {
+                remove_temps(&targets[..=index]);
+                return Err(e);
+            }
+        }
+
+        for (index, target) in targets.iter().enumerate() {
+            if let Err(e) = fs::rename(&target.temp, &target.path) {
+                remove_temps(&targets[index..]);
+                let message = format!("could not replace {}: {e}", target.path.display());
+                return Err(rollback(&targets, index, message));
+            }
+        }
+
+        tracing::debug!("Committed transaction on {} file(s)", targets.len());
+        Ok(())
+    }
+}
+
+/// Write a target's new content to its temp file, with the target's permissions
+fn write_temp(target: &Target) -> Result<()> {
+    if let Some(parent) = target.path.parent() {
+        if !parent.as_os_str().is_empty() && !parent.exists() {
+            fs::create_dir_all(parent)
+                .map_err(|e| ConfigError::filesystem("create config directory", parent, e))?;
+        }
+    }
+
+    let mut file = File::create(&target.temp)
+        .map_err(|e| ConfigError::filesystem("create temp file", &target.temp, e))?;
+    if target.path.exists() {
+        copy_permissions(&target.path, &target.temp)
+            .map_err(|e| ConfigError::filesystem("copy permissions", &target.temp, e))?;
+    }
+    file.write_all(target.json.as_bytes())
+        .map_err(|e| ConfigError::filesystem("write to temp file", &target.temp, e))
+}
+
+/// Remove leftover temp files (best effort)
+fn remove_temps(targets: &[Target]) {
+    for target in targets {
+        let _ = fs::remove_file(&target.temp);
+    }
+}
+
+/// Undo the first `renamed` targets after a failed rename
+fn rollback(targets: &[Target], renamed: usize, message: String) -> ConfigError {
+    let files = targets
+        .iter()
+        .enumerate()
+        .map(|(index, target)| {
+            let state = if index < renamed {
+                restore(target)
+            } else {
+                FileState::Unchanged
+            };
+            (target.path.clone(), state)
+        })
+        .collect();
+
+    ConfigError::TransactionFailed { message, files }
+}
+
+/// Restore one replaced target from its backup
+fn restore(target: &Target) -> FileState {
+    let restored = match &target.backup {
+        Some(backup) => fs::read(backup).and_then(|content| {
+            let temp = target.temp.as_path();
+            fs::write(temp, content)?;
+            fs::rename(temp, &target.path)
+        }),
+        None => fs::remove_file(&target.path),
+    };
+
+    match restored {
+        Ok(()) => {
+            tracing::debug!("Rolled back {}", target.path.display());
+            FileState::RolledBack
+        }
+        Err(e) => {
+            tracing::warn!("Could not roll back {}: {e}", target.path.display());
+            FileState::RollbackFailed {
+                backup: target.backup.clone(),
+            }
+        }
+    }
+}
+
+/// Describe the state of each file, one per line
+pub(crate) fn describe_states(files: &[(PathBuf, FileState)]) -> String {
+    files
+        .iter()
+        .map(|(path, state)| format!("  {}: {state}", path.display()))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn config_with_path(path: &str) -> ClaudeConfig {
+        ClaudeConfig::new().with_allowed_path(path)
+    }
+
+    // TDD Test 1: All staged files are written and backed up
+    #[test]
+    fn test_commit_writes_all_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = ConfigManager::new(temp_dir.path().join("backups"));
+        let first = temp_dir.path().join("a").join("config.json");
+        let second = temp_dir.path().join("b").join("config.json");
+        fs::create_dir_all(first.parent().unwrap()).unwrap();
+        fs::write(&first, r#"{"allowedPaths": ["~/old"]}"#).unwrap();
+
+        Transaction::new()
+            .stage(&first, config_with_path("~/a"))
+            .stage(&second, config_with_path("~/b"))
+            .commit(&manager)
+            .unwrap();
+
+        assert!(fs::read_to_string(&first).unwrap().contains("~/a"));
+        assert!(fs::read_to_string(&second).unwrap().contains("~/b"));
+        assert_eq!(
+            manager.backup_manager().list_backups(&first).unwrap().len(),
+            1
+        );
+        assert!(!first.with_extension("tmp").exists());
+    }
+
+    // TDD Test 2: A failed second rename rolls the first file back
+    #[test]
+    fn test_failed_rename_rolls_back_first_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = ConfigManager::new(temp_dir.path().join("backups"));
+        let first = temp_dir.path().join("a").join("config.json");
+        let created = temp_dir.path().join("b").join("config.json");
+        let blocked = temp_dir.path().join("c").join("config.json");
+        fs::create_dir_all(first.parent().unwrap()).unwrap();
+        let original = r#"{"allowedPaths": ["~/old"]}"#;
+        fs::write(&first, original).unwrap();
+
+        // A non-empty directory in place of the last config makes its rename
+        // fail even for root, after the temp file was written
+        fs::create_dir_all(blocked.join("occupied")).unwrap();
+
+        let err = Transaction::new()
+            .stage(&first, config_with_path("~/a"))
+            .stage(&created, config_with_path("~/b"))
+            .stage(&blocked, config_with_path("~/c"))
+            .commit(&manager)
+            .unwrap_err();
+
+        assert_eq!(fs::read_to_string(&first).unwrap(), original);
+        assert!(!created.exists());
+        assert!(blocked.join("occupied").is_dir());
+        assert!(!first.with_extension("tmp").exists());
+        assert!(!blocked.with_extension("tmp").exists());
+
+        match &err {
+            ConfigError::TransactionFailed { files, .. } => assert_eq!(
+                files.iter().map(|(_, state)| state).collect::<Vec<_>>(),
+                [
+                    &FileState::RolledBack,
+                    &FileState::RolledBack,
+                    &FileState::Unchanged
+                ]
+            ),
+            other => panic!("expected TransactionFailed, got {other:?}"),
+        }
+        assert!(err
+            .to_string()
+            .contains("rolled back to its previous content"));
+    }
+
+    // TDD Test 3: Invalid configs abort before any file is touched
+    #[test]
+    fn test_invalid_config_aborts_without_changes() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = ConfigManager::new(temp_dir.path().join("backups"));
+        let first = temp_dir.path().join("config.json");
+        let second = temp_dir.path().join("other.json");
+        fs::write(&first, "{}").unwrap();
+
+        let result = Transaction::new()
+            .stage(&first, config_with_path("~/a"))
+            .stage(&second, config_with_path(""))
+            .commit(&manager);
+
+        assert!(result.is_err());
+        assert_eq!(fs::read_to_string(&first).unwrap(), "{}");
+        assert!(!second.exists());
+        assert!(!temp_dir.path().join("backups").exists());
+    }
+
+    // TDD Test 4: Replaced files keep their permissions
+    #[cfg(unix)]
+    #[test]
+    fn test_commit_preserves_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = TempDir::new().unwrap();
+        let manager = ConfigManager::new(temp_dir.path().join("backups"));
+        let path = temp_dir.path().join("config.json");
+        fs::write(&path, "{}").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o600)).unwrap();
+
+        Transaction::new()
+            .stage(&path, config_with_path("~/a"))
+            .commit(&manager)
+            .unwrap();
+
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+}
@@ -6,7 +6,12 @@
 use crate::{
     config::ClaudeConfig,
     error::{ConfigError, Result},
+    paths::{expand_env, expand_tilde, Platform},
 };
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 /// Trait for configuration validation rules
 ///
@@ -29,6 +34,7 @@ pub trait ValidationRule: Send + Sync {
 /// - Server names are unique
 /// - All servers have required fields (enabled)
 /// - Server names are not empty
+/// - Timeouts are positive when present
 #[derive(Debug, Clone, Default)]
 pub struct McpServersRule;
 
@@ -43,7 +49,7 @@ impl ValidationRule for McpServersRule {
         };
 
         // Check each server
-        for name in servers.keys() {
+        for (name, server) in servers {
             // Name should not be empty
             if name.is_empty() {
                 return Err(ConfigError::validation_failed(
@@ -53,6 +59,15 @@ impl ValidationRule for McpServersRule {
                 ));
             }
 
+            // Timeout must be positive when present
+            if server.timeout_ms == Some(0) {
+                return Err(ConfigError::validation_failed(
+                    "McpServersRule",
+                    format!("Server '{name}' has a timeout of 0"),
+                    "Set a positive timeout in milliseconds or remove the field",
+                ));
+            }
+
             // Enabled field must be present (it's required, serde ensures this)
             // Additional validation can be added here
         }
@@ -65,6 +80,29 @@ impl ValidationRule for McpServersRule {
     }
 }
 
+/// How to fix an invalid environment variable name
+const ENV_KEY_SUGGESTION: &str = "Use a non-empty name without '=', whitespace or NUL characters";
+
+/// Check that `key` can be used as an MCP server environment variable name
+///
+/// Names can't be empty or contain `=`, whitespace or NUL characters,
+/// which can't be passed to a server process reliably. New names are
+/// checked when set; names already in a config only produce
+/// [`env_key_warnings`].
+///
+/// # Errors
+/// Returns a validation error naming the invalid key
+pub fn validate_env_key(key: &str) -> Result<()> {
+    if key.is_empty() || key.contains(|c: char| c == '=' || c == '\0' || c.is_whitespace()) {
+        return Err(ConfigError::validation_failed(
+            "McpServersRule",
+            format!("Invalid env variable name '{key}'"),
+            ENV_KEY_SUGGESTION,
+        ));
+    }
+    Ok(())
+}
+
 /// Validate allowed paths configuration
 ///
 /// Ensures:
@@ -112,14 +150,175 @@ impl ValidationRule for AllowedPathsRule {
     }
 }
 
+/// Require allowed paths to exist
+///
+/// Not part of the default rules, since a config may list paths that only
+/// exist on some machines. Paths are checked after expanding `~` and
+/// environment variables. Glob patterns such as `~/projects/**` can't be
+/// checked literally, so the directory before their first wildcard component
+/// must exist instead. Empty or malformed paths are left to
+/// [`AllowedPathsRule`].
+#[derive(Debug, Clone, Default)]
+pub struct AllowedPathsExistRule;
+
+impl ValidationRule for AllowedPathsExistRule {
+    fn validate(&self, config: &ClaudeConfig) -> Result<()> {
+        config
+            .allowed_paths
+            .iter()
+            .flatten()
+            .filter(|path| !path.is_empty() && !path.contains('\0'))
+            .try_for_each(|path| check_path_exists(path))
+    }
+
+    fn name(&self) -> &'static str {
+        "AllowedPathsExistRule"
+    }
+}
+
+/// Check that an allowed path, or the base directory of a glob, exists
+fn check_path_exists(path: &str) -> Result<()> {
+    let expanded = expand_tilde(Path::new(&expand_env(path)));
+
+    if !is_glob(path) {
+        if expanded.exists() {
+            return Ok(());
+        }
+        return Err(ConfigError::validation_failed(
+            "AllowedPathsExistRule",
+            format!("Path '{path}' does not exist"),
+            "Create the directory or remove it from allowedPaths",
+        ));
+    }
+
+    let base = glob_base(&expanded);
+    if base.as_os_str().is_empty() || base.is_dir() {
+        return Ok(());
+    }
+    Err(ConfigError::validation_failed(
+        "AllowedPathsExistRule",
+        format!(
+            "Glob '{path}' starts in '{}', which does not exist",
+            base.display()
+        ),
+        "Fix the directory before the first wildcard or remove the pattern from allowedPaths",
+    ))
+}
+
+/// Whether a path contains glob wildcards (`*`, `?` or `[`)
+fn is_glob(path: &str) -> bool {
+    path.contains(['*', '?', '['])
+}
+
+/// Leading components of `path` before the first one with a wildcard
+fn glob_base(path: &Path) -> PathBuf {
+    path.components()
+        .take_while(|component| !is_glob(&component.as_os_str().to_string_lossy()))
+        .collect()
+}
+
+/// JSON Schemas for skill parameters, keyed by skill name
+///
+/// Skills carry free-form `parameters`; registering a schema for a skill
+/// lets [`SkillsRule`] catch typos and wrong types in them.
+#[derive(Debug, Clone, Default)]
+pub struct SkillSchemaRegistry {
+    /// Compiled schemas by skill name
+    schemas: HashMap<String, Arc<jsonschema::Validator>>,
+}
+
+impl SkillSchemaRegistry {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register the parameter schema for a skill
+    ///
+    /// Replaces any schema previously registered for the same skill.
+    ///
+    /// # Arguments
+    /// * `skill` - Skill name
+    /// * `schema` - JSON Schema the skill's `parameters` must satisfy
+    ///
+    /// # Errors
+    /// Returns an error if the schema itself is invalid
+    pub fn register(&mut self, skill: impl Into<String>, schema: &Value) -> Result<()> {
+        let skill = skill.into();
+        let validator = jsonschema::validator_for(schema).map_err(|e| {
+            ConfigError::validation_failed(
+                "SkillsRule",
+                format!("Invalid parameter schema for skill '{skill}': {e}"),
+                "Fix the schema so it is valid JSON Schema",
+            )
+        })?;
+
+        self.schemas.insert(skill, Arc::new(validator));
+        Ok(())
+    }
+
+    /// Whether a schema is registered for a skill
+    pub fn contains(&self, skill: &str) -> bool {
+        self.schemas.contains_key(skill)
+    }
+
+    /// Validate a skill's parameters against its registered schema
+    ///
+    /// Missing parameters are checked as an empty object, so required
+    /// fields are still reported. Skills without a schema always pass.
+    fn validate(&self, skill: &str, parameters: Option<&Value>) -> Result<()> {
+        let Some(validator) = self.schemas.get(skill) else {
+            return Ok(());
+        };
+
+        let empty = Value::Object(Default::default());
+        let parameters = parameters.unwrap_or(&empty);
+        let violations: Vec<String> = validator
+            .iter_errors(parameters)
+            .map(|error| {
+                let location = error.instance_path.to_string();
+                if location.is_empty() {
+                    error.to_string()
+                } else {
+                    format!("{location}: {error}")
+                }
+            })
+            .collect();
+
+        if violations.is_empty() {
+            return Ok(());
+        }
+
+        Err(ConfigError::validation_failed(
+            "SkillsRule",
+            format!(
+                "Parameters of skill '{skill}' do not match its schema: {}",
+                violations.join("; ")
+            ),
+            "Fix the skill's parameters to match its registered schema",
+        ))
+    }
+}
+
 /// Validate skills configuration
 ///
 /// Ensures:
 /// - Skill names are unique (ensured by HashMap)
 /// - All skills have required fields (enabled)
 /// - Skill names are not empty
+/// - Parameters match the skill's registered schema, if any
 #[derive(Debug, Clone, Default)]
-pub struct SkillsRule;
+pub struct SkillsRule {
+    /// Parameter schemas to check skills against
+    schemas: SkillSchemaRegistry,
+}
+
+impl SkillsRule {
+    /// Create a rule that also checks parameters against `schemas`
+    pub fn with_schemas(schemas: SkillSchemaRegistry) -> Self {
+        Self { schemas }
+    }
+}
 
 impl ValidationRule for SkillsRule {
     fn validate(&self, config: &ClaudeConfig) -> Result<()> {
@@ -132,7 +331,7 @@ impl ValidationRule for SkillsRule {
         };
 
         // Check each skill
-        for name in skills.keys() {
+        for (name, skill) in skills {
             // Name should not be empty
             if name.is_empty() {
                 return Err(ConfigError::validation_failed(
@@ -143,7 +342,7 @@ impl ValidationRule for SkillsRule {
             }
 
             // Enabled field must be present (it's required, serde ensures this)
-            // Additional validation can be added here
+            self.schemas.validate(name, skill.parameters.as_ref())?;
         }
 
         Ok(())
@@ -158,10 +357,21 @@ impl ValidationRule for SkillsRule {
 ///
 /// Runs all validation rules and returns the first error encountered
 pub fn validate_config(config: &ClaudeConfig) -> Result<()> {
+    validate_config_with_skill_schemas(config, &SkillSchemaRegistry::default())
+}
+
+/// Validate the configuration, checking skill parameters against `schemas`
+///
+/// Same as [`validate_config`], but skills with a registered schema must
+/// also have conforming parameters.
+pub fn validate_config_with_skill_schemas(
+    config: &ClaudeConfig,
+    schemas: &SkillSchemaRegistry,
+) -> Result<()> {
     let rules: Vec<Box<dyn ValidationRule>> = vec![
         Box::<McpServersRule>::default(),
         Box::<AllowedPathsRule>::default(),
-        Box::<SkillsRule>::default(),
+        Box::new(SkillsRule::with_schemas(schemas.clone())),
     ];
 
     for rule in rules {
@@ -171,6 +381,179 @@ pub fn validate_config(config: &ClaudeConfig) -> Result<()> {
     Ok(())
 }
 
+/// Warn about unknown platform names in MCP server definitions
+///
+/// `platforms` and `commandByPlatform` entries that don't name a known
+/// platform never match, so a typo like `mac` silently hides a server or
+/// its command. These are warnings rather than errors because configs
+/// written for a newer platform list should still load.
+///
+/// # Returns
+/// One message per unknown name, ordered by server name
+pub fn platform_warnings(config: &ClaudeConfig) -> Vec<String> {
+    let Some(servers) = config.mcp_servers.as_ref() else {
+        return Vec::new();
+    };
+    let mut names: Vec<_> = servers.keys().collect();
+    names.sort();
+
+    let expected = "expected macos, linux or windows";
+    let mut warnings = Vec::new();
+    for name in names {
+        let server = &servers[name];
+        for platform in server.platforms.iter().flatten() {
+            if Platform::from_key(platform).is_none() {
+                warnings.push(format!(
+                    "mcpServers.{name}.platforms: unknown platform '{platform}' ({expected})"
+                ));
+            }
+        }
+        for platform in server.command_by_platform.iter().flat_map(|m| m.keys()) {
+            if Platform::from_key(platform).is_none() {
+                warnings.push(format!(
+                    "mcpServers.{name}.commandByPlatform: unknown platform '{platform}' ({expected})"
+                ));
+            }
+        }
+    }
+    warnings
+}
+
+/// Warn about MCP server env variable names [`validate_env_key`] rejects
+///
+/// These are warnings rather than errors so configs written by hand or by
+/// other tools still load.
+///
+/// # Returns
+/// One message per invalid name, ordered by server and variable name
+pub fn env_key_warnings(config: &ClaudeConfig) -> Vec<String> {
+    let Some(servers) = config.mcp_servers.as_ref() else {
+        return Vec::new();
+    };
+    let mut names: Vec<_> = servers.keys().collect();
+    names.sort();
+
+    let mut warnings = Vec::new();
+    for name in names {
+        let mut keys: Vec<_> = servers[name].env.keys().collect();
+        keys.sort();
+        for key in keys {
+            if validate_env_key(key).is_err() {
+                warnings.push(format!(
+                    "mcpServers.{name}.env: invalid variable name '{key}' (empty, or contains '=', whitespace or NUL)"
+                ));
+            }
+        }
+    }
+    warnings
+}
+
+/// Warn about blank values that are allowed but almost certainly mistakes
+///
+/// Reports empty or whitespace-only instructions and MCP server commands,
+/// and whitespace-only allowed paths (empty paths fail [`AllowedPathsRule`]).
+/// These are warnings rather than errors so existing configs containing
+/// them still load.
+///
+/// # Returns
+/// One message per blank value, in config order (servers by name)
+pub fn blank_value_warnings(config: &ClaudeConfig) -> Vec<String> {
+    let is_blank = |s: &str| s.trim().is_empty();
+    let mut warnings = Vec::new();
+
+    for (idx, instruction) in config.custom_instructions.iter().flatten().enumerate() {
+        if is_blank(instruction) {
+            warnings.push(format!(
+                "customInstructions[{idx}]: instruction is empty or whitespace-only"
+            ));
+        }
+    }
+
+    for (idx, path) in config.allowed_paths.iter().flatten().enumerate() {
+        if !path.is_empty() && is_blank(path) {
+            warnings.push(format!("allowedPaths[{idx}]: path is whitespace-only"));
+        }
+    }
+
+    if let Some(servers) = config.mcp_servers.as_ref() {
+        let mut names: Vec<_> = servers.keys().collect();
+        names.sort();
+        for name in names {
+            if servers[name].command.as_deref().is_some_and(is_blank) {
+                warnings.push(format!(
+                    "mcpServers.{name}.command: command is empty or whitespace-only"
+                ));
+            }
+        }
+    }
+
+    warnings
+}
+
+impl ClaudeConfig {
+    /// Apply safe, mechanical corrections to the configuration
+    ///
+    /// Fixes applied:
+    /// - Trims surrounding whitespace from allowed paths and server commands
+    /// - Removes duplicate allowed paths (keeping the first occurrence)
+    /// - Drops empty `mcpServers` and `skills` maps
+    ///
+    /// Empty `allowedPaths` and `customInstructions` arrays are kept: an
+    /// explicit `[]` can clear what another scope sets.
+    ///
+    /// Ambiguous problems such as empty server or skill names are left
+    /// untouched so they still fail [`validate_config`].
+    ///
+    /// # Returns
+    /// A description of each change made (empty if nothing changed)
+    pub fn validate_and_fix(&mut self) -> Vec<String> {
+        let mut changes = Vec::new();
+
+        if let Some(paths) = self.allowed_paths.as_mut() {
+            for (idx, path) in paths.iter_mut().enumerate() {
+                let trimmed = path.trim();
+                if trimmed != path && !trimmed.is_empty() {
+                    changes.push(format!("allowedPaths[{idx}]: trimmed whitespace"));
+                    *path = trimmed.to_string();
+                }
+            }
+
+            super::normalize::retain_first(
+                paths,
+                |path| Some(path.clone()),
+                |path| changes.push(format!("allowedPaths: removed duplicate '{path}'")),
+            );
+        }
+
+        if let Some(servers) = self.mcp_servers.as_mut() {
+            let mut names: Vec<_> = servers.keys().cloned().collect();
+            names.sort();
+
+            for name in names {
+                let command = servers.get_mut(&name).and_then(|s| s.command.as_mut());
+                if let Some(command) = command {
+                    let trimmed = command.trim();
+                    if trimmed != command && !trimmed.is_empty() {
+                        changes.push(format!("mcpServers.{name}.command: trimmed whitespace"));
+                        *command = trimmed.to_string();
+                    }
+                }
+            }
+        }
+
+        if self.mcp_servers.as_ref().is_some_and(|s| s.is_empty()) {
+            self.mcp_servers = None;
+            changes.push("mcpServers: removed empty collection".to_string());
+        }
+        if self.skills.as_ref().is_some_and(|s| s.is_empty()) {
+            self.skills = None;
+            changes.push("skills: removed empty collection".to_string());
+        }
+
+        changes
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -300,4 +683,230 @@ mod tests {
         assert!(err.contains("AllowedPathsRule"));
         assert!(err.contains("Suggestion:"));
     }
+
+    // TDD Test 11: validate_and_fix dedups and trims allowed paths
+    #[test]
+    fn test_validate_and_fix_dedups_and_trims_paths() {
+        let mut config = ClaudeConfig::new()
+            .with_allowed_path("~/projects  ")
+            .with_allowed_path("~/work")
+            .with_allowed_path("~/projects");
+
+        let changes = config.validate_and_fix();
+
+        assert_eq!(
+            config.allowed_paths,
+            Some(vec!["~/projects".to_string(), "~/work".to_string()])
+        );
+        assert_eq!(changes.len(), 2);
+        assert!(validate_config(&config).is_ok());
+
+        // Running again finds nothing to fix
+        assert!(config.validate_and_fix().is_empty());
+    }
+
+    // TDD Test 12: validate_and_fix drops empty maps but keeps empty arrays
+    #[test]
+    fn test_validate_and_fix_drops_empty_maps() {
+        let mut config = ClaudeConfig {
+            mcp_servers: Some(std::collections::HashMap::new()),
+            allowed_paths: Some(Vec::new()),
+            custom_instructions: Some(Vec::new()),
+            ..Default::default()
+        };
+
+        let changes = config.validate_and_fix();
+
+        assert!(config.mcp_servers.is_none());
+        assert_eq!(config.allowed_paths, Some(Vec::new()));
+        assert_eq!(config.custom_instructions, Some(Vec::new()));
+        assert_eq!(changes.len(), 1);
+    }
+
+    // TDD Test 13: validate_and_fix leaves empty server names as errors
+    #[test]
+    fn test_validate_and_fix_keeps_empty_server_name() {
+        let mut config = ClaudeConfig::new().with_mcp_server("", McpServer::new("", "npx", vec![]));
+
+        config.validate_and_fix();
+
+        assert!(config.mcp_servers.as_ref().unwrap().contains_key(""));
+        assert!(validate_config(&config).is_err());
+    }
+
+    // TDD Test 14: Zero server timeout fails validation
+    #[test]
+    fn test_zero_server_timeout_is_invalid() {
+        let config = ClaudeConfig::new().with_mcp_server(
+            "npx",
+            McpServer::new("npx", "npx", vec![]).with_timeout_ms(0),
+        );
+
+        let result = validate_config(&config);
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("timeout"));
+
+        let config = ClaudeConfig::new().with_mcp_server(
+            "npx",
+            McpServer::new("npx", "npx", vec![]).with_timeout_ms(5000),
+        );
+        assert!(validate_config(&config).is_ok());
+    }
+
+    // TDD Test 15: Skill parameters are checked against registered schemas
+    #[test]
+    fn test_skill_parameters_validated_against_schema() {
+        let mut schemas = SkillSchemaRegistry::new();
+        schemas
+            .register(
+                "code-review",
+                &serde_json::json!({
+                    "type": "object",
+                    "properties": {"strict": {"type": "boolean"}},
+                    "required": ["strict"]
+                }),
+            )
+            .unwrap();
+        let skill = |parameters| Skill {
+            name: "code-review".to_string(),
+            enabled: true,
+            parameters,
+        };
+
+        // Non-conforming parameters name the skill and the violation
+        let config = ClaudeConfig::new().with_skill(
+            "code-review",
+            skill(Some(serde_json::json!({"strict": "yes"}))),
+        );
+        let message = validate_config_with_skill_schemas(&config, &schemas)
+            .unwrap_err()
+            .to_string();
+        assert!(message.contains("SkillsRule"));
+        assert!(message.contains("'code-review'"));
+        assert!(message.contains("/strict"));
+        assert!(message.contains("boolean"));
+
+        // Missing required field
+        let config = ClaudeConfig::new().with_skill("code-review", skill(None));
+        let message = validate_config_with_skill_schemas(&config, &schemas)
+            .unwrap_err()
+            .to_string();
+        assert!(message.contains("strict"));
+
+        // Conforming parameters pass; without a schema anything goes
+        let config = ClaudeConfig::new().with_skill(
+            "code-review",
+            skill(Some(serde_json::json!({"strict": true}))),
+        );
+        assert!(validate_config_with_skill_schemas(&config, &schemas).is_ok());
+
+        let config = ClaudeConfig::new()
+            .with_skill("other", skill(Some(serde_json::json!({"strict": "yes"}))));
+        assert!(validate_config_with_skill_schemas(&config, &schemas).is_ok());
+        assert!(validate_config(&config).is_ok());
+    }
+
+    // TDD Test 16: Invalid schemas are rejected at registration
+    #[test]
+    fn test_invalid_skill_schema_rejected() {
+        let mut schemas = SkillSchemaRegistry::new();
+        let result = schemas.register("code-review", &serde_json::json!({"type": 42}));
+
+        assert!(result.is_err());
+        assert!(!schemas.contains("code-review"));
+    }
+
+    // TDD Test 17: The existence rule checks the base directory of glob paths
+    #[test]
+    fn test_allowed_paths_exist_with_globs() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let projects = temp_dir.path().join("projects");
+        std::fs::create_dir_all(&projects).unwrap();
+        let strict = AllowedPathsExistRule;
+        let check = |path: String| strict.validate(&ClaudeConfig::new().with_allowed_path(path));
+
+        assert!(check(projects.display().to_string()).is_ok());
+        assert!(check(format!("{}/**", projects.display())).is_ok());
+        assert!(check(format!("{}/*/src/[a-z]?.rs", projects.display())).is_ok());
+
+        let missing = temp_dir.path().join("missing");
+        let err = check(format!("{}/**", missing.display())).unwrap_err();
+        assert!(err.to_string().contains("does not exist"));
+        assert!(check(missing.display().to_string()).is_err());
+
+        // The default rule doesn't touch the filesystem
+        assert!(AllowedPathsRule
+            .validate(&ClaudeConfig::new().with_allowed_path(missing.display().to_string()))
+            .is_ok());
+    }
+
+    // TDD Test 18: Unknown platform names produce warnings, not errors
+    #[test]
+    fn test_platform_warnings() {
+        let json = r#"{"mcpServers": {
+            "zeta": {"enabled": true, "command": "npx", "platforms": ["linux", "mac"]},
+            "alpha": {"enabled": true, "command": "npx",
+                      "commandByPlatform": {"Windows": "mcp.exe", "win32": "mcp.exe"}}
+        }}"#;
+        let config: ClaudeConfig = serde_json::from_str(json).unwrap();
+
+        assert!(validate_config(&config).is_ok());
+        assert_eq!(
+            platform_warnings(&config),
+            [
+                "mcpServers.alpha.commandByPlatform: unknown platform 'win32' (expected macos, linux or windows)",
+                "mcpServers.zeta.platforms: unknown platform 'mac' (expected macos, linux or windows)",
+            ]
+        );
+        assert!(platform_warnings(&ClaudeConfig::new()).is_empty());
+    }
+
+    // TDD Test 19: Blank values in existing configs are warnings, not errors
+    #[test]
+    fn test_blank_value_warnings() {
+        let config: ClaudeConfig = serde_json::from_str(
+            r#"{
+                "customInstructions": ["Be concise", "", "   "],
+                "allowedPaths": ["~/code", " \t"],
+                "mcpServers": {
+                    "fetch": {"command": "", "args": [], "enabled": true},
+                    "npx": {"command": "npx", "args": [], "enabled": true}
+                }
+            }"#,
+        )
+        .unwrap();
+
+        assert!(validate_config(&config).is_ok());
+        assert_eq!(
+            blank_value_warnings(&config),
+            vec![
+                "customInstructions[1]: instruction is empty or whitespace-only",
+                "customInstructions[2]: instruction is empty or whitespace-only",
+                "allowedPaths[1]: path is whitespace-only",
+                "mcpServers.fetch.command: command is empty or whitespace-only",
+            ]
+        );
+        assert!(blank_value_warnings(&ClaudeConfig::new()).is_empty());
+    }
+
+    // TDD Test 20: Invalid env variable names are rejected by the check and
+    // only warned about in existing configs
+    #[test]
+    fn test_env_keys_are_validated() {
+        assert!(validate_env_key("GITHUB_TOKEN").is_ok());
+        assert!(validate_env_key("").is_err());
+        assert!(validate_env_key("A=B").is_err());
+        assert!(validate_env_key("MY KEY").is_err());
+
+        let config: ClaudeConfig = serde_json::from_str(
+            r#"{"mcpServers": {"fetch": {"command": "uvx", "enabled": true,
+                "env": {"BAD=KEY": "1"}}}}"#,
+        )
+        .unwrap();
+        assert!(validate_config(&config).is_ok());
+        assert_eq!(
+            env_key_warnings(&config),
+            ["mcpServers.fetch.env: invalid variable name 'BAD=KEY' (empty, or contains '=', whitespace or NUL)"]
+        );
+    }
 }
@@ -0,0 +1,148 @@
+//! Request framing shared by the daemon transports
+//!
+//! Both transports carry the same stream of newline-terminated JSON-RPC
+//! objects; only how a connection is opened differs (see `socket` and
+//! `pipe`).
+
+use super::{Handler, RpcError, Session, PROTOCOL_VERSION};
+use crate::error::{ConfigError, Result};
+use serde_json::{json, Value};
+use std::io::{self, BufRead, BufReader, ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Instant;
+
+#[cfg(windows)]
+use super::pipe::connect;
+#[cfg(unix)]
+use super::socket::connect;
+
+/// A connected byte stream to or from the daemon
+#[cfg(unix)]
+pub(super) type Stream = std::os::unix::net::UnixStream;
+
+/// A connected byte stream to or from the daemon
+#[cfg(windows)]
+pub(super) type Stream = std::fs::File;
+
+/// Longest request line accepted; longer requests close the connection
+const MAX_REQUEST_BYTES: usize = 1024 * 1024;
+
+/// Answer requests on one connection until it closes or the daemon stops
+///
+/// Reads that time out (see [`super::POLL_INTERVAL`]) only check for
+/// shutdown; partial lines are kept until the rest arrives.
+pub(super) fn serve_connection(
+    handler: &Handler,
+    stream: Stream,
+    last_request: &Mutex<Instant>,
+) -> io::Result<()> {
+    let mut writer = stream.try_clone()?;
+    let mut reader = BufReader::new(stream);
+    let mut session = Session::new();
+    let mut line = Vec::new();
+
+    while !handler.token.is_cancelled() {
+        // Partial lines stay in `line` across read timeouts
+        match reader.read_until(b'\n', &mut line) {
+            Ok(0) => break,
+            Ok(_) if line.ends_with(b"\n") => {
+                if let Ok(mut last) = last_request.lock() {
+                    *last = Instant::now();
+                }
+                let text = String::from_utf8_lossy(&line);
+                if !text.trim().is_empty() {
+                    if let Some(response) = handler.handle_line(&mut session, text.trim()) {
+                        writer.write_all(response.as_bytes())?;
+                        writer.write_all(b"\n")?;
+                        writer.flush()?;
+                    }
+                }
+                line.clear();
+            }
+            Ok(_) => break,
+            Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {}
+            Err(e) if e.kind() == ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+
+        if line.len() > MAX_REQUEST_BYTES {
+            return Err(io::Error::new(
+                ErrorKind::InvalidData,
+                "request exceeds the size limit",
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// A connection to a running daemon
+#[derive(Debug)]
+pub struct Client {
+    reader: BufReader<Stream>,
+    writer: Stream,
+    next_id: u64,
+}
+
+impl Client {
+    /// Connect to a daemon and perform the handshake
+    ///
+    /// # Errors
+    /// Returns an error if nothing listens on the socket or pipe, or the
+    /// daemon speaks another protocol version
+    pub fn connect(socket_path: &Path) -> Result<Self> {
+        let stream = connect(socket_path)
+            .map_err(|e| ConfigError::filesystem("connect to daemon", socket_path, e))?;
+        let writer = stream
+            .try_clone()
+            .map_err(|e| ConfigError::filesystem("connect to daemon", socket_path, e))?;
+        let mut client = Self {
+            reader: BufReader::new(stream),
+            writer,
+            next_id: 1,
+        };
+        client.call("handshake", json!({"protocolVersion": PROTOCOL_VERSION}))?;
+        Ok(client)
+    }
+
+    /// Call a method and return its result
+    ///
+    /// # Errors
+    /// Returns an error if the connection fails or the daemon answers with
+    /// an error object
+    pub fn call(&mut self, method: &str, params: Value) -> Result<Value> {
+        let mut response = self.call_raw(method, params)?;
+        if let Some(error) = response.get("error") {
+            let error = RpcError::from_value(error);
+            return Err(ConfigError::Generic(format!("Daemon error: {error}")));
+        }
+        Ok(response["result"].take())
+    }
+
+    /// Call a method and return the whole response object
+    ///
+    /// # Errors
+    /// Returns an error if the connection fails or the response isn't JSON
+    pub fn call_raw(&mut self, method: &str, params: Value) -> Result<Value> {
+        let id = self.next_id;
+        self.next_id += 1;
+        let request = json!({"jsonrpc": "2.0", "id": id, "method": method, "params": params});
+
+        let socket = PathBuf::from("daemon socket");
+        self.writer
+            .write_all(format!("{request}\n").as_bytes())
+            .map_err(|e| ConfigError::filesystem("send request to", &socket, e))?;
+
+        let mut line = String::new();
+        let read = self
+            .reader
+            .read_line(&mut line)
+            .map_err(|e| ConfigError::filesystem("read response from", &socket, e))?;
+        if read == 0 {
+            return Err(ConfigError::Generic(
+                "The daemon closed the connection".to_string(),
+            ));
+        }
+        Ok(serde_json::from_str(&line)?)
+    }
+}
@@ -0,0 +1,635 @@
+//! Long-running daemon answering config requests over a local socket
+//!
+//! The transport is a Unix domain socket ([`socket`]) on Unix-like systems
+//! and a named pipe (`pipe`) on Windows; [`serve`] and [`Client`] pick the
+//! one for the platform. Both carry the same framing, and the request
+//! [`Handler`] itself is platform-independent.
+//!
+//! Editor integrations that would otherwise start `ccm` on every keystroke
+//! keep a connection to the daemon instead. Requests and responses are
+//! JSON-RPC 2.0 objects, one per line. Every connection starts with a
+//! `handshake` naming the [`PROTOCOL_VERSION`] the client speaks.
+//!
+//! | Method         | Params                                         | Result                           |
+//! |----------------|------------------------------------------------|----------------------------------|
+//! | `handshake`    | `protocolVersion`                              | `protocolVersion`, `ccmVersion`  |
+//! | `get_config`   | `projectPath?`, `scope?`                       | config object, or `null`         |
+//! | `set_value`    | `key`, `value`, `projectPath?`                 | `written`, `path`                |
+//! | `search`       | `query`, `projectPath?`, `caseSensitive?`      | matching keys and values         |
+//! | `list_servers` | `projectPath?`, `scope?`                       | `mcpServers` object              |
+//! | `list_backups` | `projectPath?`                                 | list of backups                  |
+//! | `status`       |                                                | versions, pid, cached files      |
+//! | `shutdown`     |                                                | `stopping`                       |
+//!
+//! Parsed configs are cached per file and re-read as soon as the file's
+//! modification time or size changes, so edits made outside the daemon are
+//! picked up on the next request. `scope` is `global`, `project` or
+//! `merged` (the default when `projectPath` is given). Failures are returned as JSON-RPC error
+//! objects whose `data.kind` names the [`ConfigError`] variant (see
+//! [`codes`]).
+
+#[cfg(any(unix, windows))]
+mod connection;
+#[cfg(windows)]
+pub mod pipe;
+#[cfg(unix)]
+pub mod socket;
+
+#[cfg(any(unix, windows))]
+pub use connection::Client;
+#[cfg(windows)]
+pub use pipe::serve;
+#[cfg(unix)]
+pub use socket::serve;
+
+use crate::{
+    bundle::CancellationToken,
+    config::{
+        key_path,
+        manager::{ConfigManager, WriteOptions},
+        merge::merge_configs,
+        ClaudeConfig,
+    },
+    error::ConfigError,
+    search::{ConfigSearcher, SearchOptions},
+    types::ConfigScope,
+    validate_config,
+};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime};
+
+/// Version of the request protocol; bumped on incompatible changes
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// How often a transport waiting for connections or requests checks for shutdown
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// JSON-RPC error codes used by the daemon
+///
+/// The `-32700..=-32600` range is defined by JSON-RPC. Codes from `-32000`
+/// down are the daemon's own; config errors use one code per [`ConfigError`]
+/// variant.
+pub mod codes {
+    /// The request line is not valid JSON
+    pub const PARSE_ERROR: i64 = -32700;
+    /// The request is not a JSON-RPC request object
+    pub const INVALID_REQUEST: i64 = -32600;
+    /// No such method
+    pub const METHOD_NOT_FOUND: i64 = -32601;
+    /// Missing or mistyped parameters
+    pub const INVALID_PARAMS: i64 = -32602;
+    /// A method was called before the handshake
+    pub const HANDSHAKE_REQUIRED: i64 = -32000;
+    /// The client speaks another protocol version
+    pub const PROTOCOL_MISMATCH: i64 = -32001;
+    /// [`ConfigError::NotFound`](crate::ConfigError::NotFound)
+    pub const NOT_FOUND: i64 = -32010;
+    /// [`ConfigError::InvalidFormat`](crate::ConfigError::InvalidFormat)
+    pub const INVALID_JSON: i64 = -32011;
+    /// [`ConfigError::ValidationFailed`](crate::ConfigError::ValidationFailed)
+    pub const VALIDATION_FAILED: i64 = -32012;
+    /// Filesystem, Windows path limit and backup failures
+    pub const FILESYSTEM: i64 = -32013;
+    /// [`ConfigError::PermissionDenied`](crate::ConfigError::PermissionDenied)
+    pub const PERMISSION_DENIED: i64 = -32014;
+    /// [`ConfigError::Conflict`](crate::ConfigError::Conflict)
+    pub const CONFLICT: i64 = -32015;
+    /// Any other config error
+    pub const OTHER: i64 = -32019;
+}
+
+/// A JSON-RPC error object
+#[derive(Debug, Clone, PartialEq)]
+pub struct RpcError {
+    /// One of [`codes`]
+    pub code: i64,
+
+    /// Human-readable message
+    pub message: String,
+
+    /// Extra details (`{"kind": ...}` for config errors)
+    pub data: Option<Value>,
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            data: None,
+        }
+    }
+
+    fn invalid_params(message: impl Into<String>) -> Self {
+        Self::new(codes::INVALID_PARAMS, message)
+    }
+
+    /// Read an error object from a response
+    pub fn from_value(value: &Value) -> Self {
+        Self {
+            code: value["code"].as_i64().unwrap_or(codes::OTHER),
+            message: value["message"].as_str().unwrap_or_default().to_string(),
+            data: value.get("data").cloned(),
+        }
+    }
+
+    /// The error as a JSON-RPC error object
+    pub fn to_value(&self) -> Value {
+        let mut error = json!({"code": self.code, "message": self.message});
+        if let Some(data) = &self.data {
+            error["data"] = data.clone();
+        }
+        error
+    }
+}
+
+impl std::fmt::Display for RpcError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} (code {})", self.message, self.code)
+    }
+}
+
+impl From<ConfigError> for RpcError {
+    fn from(error: ConfigError) -> Self {
+        let (code, kind) = match &error {
+            ConfigError::NotFound { .. } => (codes::NOT_FOUND, "NotFound"),
+            ConfigError::InvalidFormat { .. } => (codes::INVALID_JSON, "InvalidJson"),
+            ConfigError::ValidationFailed { .. } => (codes::VALIDATION_FAILED, "ValidationFailed"),
+            ConfigError::Filesystem { .. } => (codes::FILESYSTEM, "Filesystem"),
+            ConfigError::WindowsPathLimit { .. } => (codes::FILESYSTEM, "WindowsPathLimit"),
+            ConfigError::BackupFailed { .. } => (codes::FILESYSTEM, "BackupFailed"),
+            ConfigError::PermissionDenied { .. } => (codes::PERMISSION_DENIED, "PermissionDenied"),
+            ConfigError::McpServerError { .. } => (codes::OTHER, "McpServerError"),
+            ConfigError::TransactionFailed { .. } => (codes::OTHER, "TransactionFailed"),
+            ConfigError::WriteVerificationFailed { .. } => {
+                (codes::OTHER, "WriteVerificationFailed")
+            }
+            ConfigError::Conflict { .. } => (codes::CONFLICT, "Conflict"),
+            ConfigError::Generic(_) => (codes::OTHER, "Generic"),
+        };
+        Self {
+            code,
+            message: error.to_string(),
+            data: Some(json!({"kind": kind})),
+        }
+    }
+}
+
+/// Modification time and size identifying a version of a file
+type FileStamp = (SystemTime, u64);
+
+/// Per-connection protocol state
+#[derive(Debug, Default)]
+pub struct Session {
+    handshake_done: bool,
+}
+
+impl Session {
+    /// A connection that hasn't sent its handshake yet
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+/// Request dispatcher shared by all connections
+///
+/// Independent of the transport, so it can be driven directly in tests.
+#[derive(Debug)]
+pub struct Handler {
+    manager: ConfigManager,
+    cache: Mutex<HashMap<PathBuf, (FileStamp, ClaudeConfig)>>,
+    token: CancellationToken,
+    started: Instant,
+}
+
+impl Handler {
+    /// Create a handler reading and writing configs through `manager`
+    ///
+    /// # Arguments
+    /// * `manager` - Manager used for all config access
+    /// * `token` - Cancelled by the `shutdown` method
+    pub fn new(manager: ConfigManager, token: CancellationToken) -> Self {
+        Self {
+            manager,
+            cache: Mutex::new(HashMap::new()),
+            token,
+            started: Instant::now(),
+        }
+    }
+
+    /// Answer one request line
+    ///
+    /// # Returns
+    /// The response line (without the trailing newline), or `None` for a
+    /// notification (a request without an `id`)
+    pub fn handle_line(&self, session: &mut Session, line: &str) -> Option<String> {
+        let request: Value = match serde_json::from_str(line) {
+            Ok(request) => request,
+            Err(e) => {
+                let error = RpcError::new(codes::PARSE_ERROR, format!("Invalid JSON: {e}"));
+                return Some(response(Value::Null, Err(error)));
+            }
+        };
+
+        let id = request.get("id").cloned();
+        let result = match request.get("method").and_then(Value::as_str) {
+            None => Err(RpcError::new(
+                codes::INVALID_REQUEST,
+                "Request has no method",
+            )),
+            Some(method) => {
+                let params = request.get("params").cloned().unwrap_or(json!({}));
+                self.dispatch(session, method, &params)
+            }
+        };
+
+        id.map(|id| response(id, result))
+    }
+
+    /// Run a method
+    fn dispatch(
+        &self,
+        session: &mut Session,
+        method: &str,
+        params: &Value,
+    ) -> Result<Value, RpcError> {
+        if method == "handshake" {
+            let version = params["protocolVersion"]
+                .as_u64()
+                .ok_or_else(|| RpcError::invalid_params("protocolVersion is required"))?;
+            if version != u64::from(PROTOCOL_VERSION) {
+                return Err(RpcError {
+                    code: codes::PROTOCOL_MISMATCH,
+                    message: format!(
+                        "Protocol version {version} is not supported; this daemon speaks version {PROTOCOL_VERSION}"
+                    ),
+                    data: Some(json!({"supported": [PROTOCOL_VERSION]})),
+                });
+            }
+            session.handshake_done = true;
+            return Ok(json!({
+                "protocolVersion": PROTOCOL_VERSION,
+                "ccmVersion": crate::VERSION,
+            }));
+        }
+
+        if !session.handshake_done {
+            return Err(RpcError::new(
+                codes::HANDSHAKE_REQUIRED,
+                "Send a handshake before any other request",
+            ));
+        }
+
+        match method {
+            "get_config" => self.get_config(params),
+            "set_value" => self.set_value(params),
+            "search" => self.search(params),
+            "list_servers" => self.list_servers(params),
+            "list_backups" => self.list_backups(params),
+            "status" => Ok(self.status()),
+            "shutdown" => {
+                self.token.cancel();
+                Ok(json!({"stopping": true}))
+            }
+            other => Err(RpcError::new(
+                codes::METHOD_NOT_FOUND,
+                format!("Unknown method '{other}'"),
+            )),
+        }
+    }
+
+    /// `get_config`: one scope's config, or the merged config
+    fn get_config(&self, params: &Value) -> Result<Value, RpcError> {
+        let project = project_param(params)?;
+        let config = match scope_param(params, project.is_some())? {
+            Scope::Global => self.cached(&self.path_for(ConfigScope::Global, None)?)?,
+            Scope::Project => self.cached(&self.path_for(ConfigScope::Project, project)?)?,
+            Scope::Merged => Some(self.merged(project)?),
+        };
+        Ok(serde_json::to_value(config).map_err(ConfigError::from)?)
+    }
+
+    /// `set_value`: set a key path and write the config (with a backup)
+    fn set_value(&self, params: &Value) -> Result<Value, RpcError> {
+        let key = str_param(params, "key")?;
+        let value = params
+            .get("value")
+            .cloned()
+            .ok_or_else(|| RpcError::invalid_params("value is required"))?;
+        let project = project_param(params)?;
+        let scope = if project.is_some() {
+            ConfigScope::Project
+        } else {
+            ConfigScope::Global
+        };
+        let path = self.path_for(scope, project)?;
+
+        let config = self.cached(&path)?.unwrap_or_default();
+        let mut root = serde_json::to_value(&config).map_err(ConfigError::from)?;
+        key_path::set(&mut root, key, value);
+        let config: ClaudeConfig = serde_json::from_value(root).map_err(|e| {
+            RpcError::invalid_params(format!("The new value doesn't fit '{key}': {e}"))
+        })?;
+        validate_config(&config)?;
+
+        let written =
+            self.manager
+                .write_config_with_options(&path, &config, WriteOptions::new())?;
+        self.forget(&path);
+
+        Ok(json!({"written": written, "path": path}))
+    }
+
+    /// `search`: matching keys and values in the global and project configs
+    fn search(&self, params: &Value) -> Result<Value, RpcError> {
+        let query = str_param(params, "query")?;
+        let project = project_param(params)?;
+        let case_sensitive = params["caseSensitive"].as_bool().unwrap_or(false);
+
+        let mut sources = vec![ConfigScope::Global];
+        if project.is_some() {
+            sources.push(ConfigScope::Project);
+        }
+
+        let searcher = ConfigSearcher::with_options(
+            SearchOptions::new()
+                .with_values(true)
+                .with_case_sensitive(case_sensitive),
+        );
+        let mut matches = Vec::new();
+        for scope in sources {
+            let path = self.path_for(scope, project)?;
+            if let Some(config) = self.cached(&path)? {
+                for found in searcher.search(query, &config, scope, path)? {
+                    matches.push(json!({
+                        "keyPath": found.key_path,
+                        "value": found.value,
+                        "source": found.source,
+                        "configPath": found.config_path,
+                    }));
+                }
+            }
+        }
+        Ok(Value::Array(matches))
+    }
+
+    /// `list_servers`: the `mcpServers` of a scope (merged by default)
+    fn list_servers(&self, params: &Value) -> Result<Value, RpcError> {
+        let config = self.get_config(params)?;
+        Ok(config
+            .get("mcpServers")
+            .cloned()
+            .unwrap_or_else(|| json!({})))
+    }
+
+    /// `list_backups`: backups of the global or project config
+    fn list_backups(&self, params: &Value) -> Result<Value, RpcError> {
+        let project = project_param(params)?;
+        let scope = if project.is_some() {
+            ConfigScope::Project
+        } else {
+            ConfigScope::Global
+        };
+        let path = self.path_for(scope, project)?;
+        let backups = self.manager.backup_manager().list_backups(&path)?;
+        Ok(serde_json::to_value(backups).map_err(ConfigError::from)?)
+    }
+
+    /// `status`: versions and cache size
+    fn status(&self) -> Value {
+        let cached = self.cache.lock().map(|cache| cache.len()).unwrap_or(0);
+        json!({
+            "protocolVersion": PROTOCOL_VERSION,
+            "ccmVersion": crate::VERSION,
+            "pid": std::process::id(),
+            "uptimeSecs": self.started.elapsed().as_secs(),
+            "cachedFiles": cached,
+        })
+    }
+
+    /// Global config overlaid with the project config
+    fn merged(&self, project: Option<&Path>) -> Result<ClaudeConfig, RpcError> {
+        let global = self
+            .cached(&self.path_for(ConfigScope::Global, None)?)?
+            .unwrap_or_default();
+        let project_config = match project {
+            Some(project) => self.cached(&self.path_for(ConfigScope::Project, Some(project))?)?,
+            None => None,
+        };
+        Ok(match project_config {
+            Some(project_config) => merge_configs(&global, &project_config),
+            None => global,
+        })
+    }
+
+    /// Config file of a scope
+    fn path_for(&self, scope: ConfigScope, project: Option<&Path>) -> Result<PathBuf, RpcError> {
+        Ok(self.manager.config_path_for_scope(&scope, project)?)
+    }
+
+    /// Config at `path` (`None` if there is no file), parsed at most once per version
+    fn cached(&self, path: &Path) -> Result<Option<ClaudeConfig>, RpcError> {
+        let Some(stamp) = file_stamp(path) else {
+            self.forget(path);
+            return Ok(None);
+        };
+
+        if let Ok(cache) = self.cache.lock() {
+            if let Some((cached_stamp, config)) = cache.get(path) {
+                if *cached_stamp == stamp {
+                    return Ok(Some(config.clone()));
+                }
+            }
+        }
+
+        let config = self.manager.read_config(path)?;
+        if let Ok(mut cache) = self.cache.lock() {
+            cache.insert(path.to_path_buf(), (stamp, config.clone()));
+        }
+        Ok(Some(config))
+    }
+
+    /// Drop the cached copy of `path`
+    fn forget(&self, path: &Path) {
+        if let Ok(mut cache) = self.cache.lock() {
+            cache.remove(path);
+        }
+    }
+}
+
+/// Which config a request reads
+enum Scope {
+    Global,
+    Project,
+    Merged,
+}
+
+/// Modification time and size of a file, if it exists
+fn file_stamp(path: &Path) -> Option<FileStamp> {
+    let metadata = fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+/// A JSON-RPC response line
+fn response(id: Value, result: Result<Value, RpcError>) -> String {
+    let response = match result {
+        Ok(result) => json!({"jsonrpc": "2.0", "id": id, "result": result}),
+        Err(error) => json!({"jsonrpc": "2.0", "id": id, "error": error.to_value()}),
+    };
+    response.to_string()
+}
+
+/// A required string parameter
+fn str_param<'a>(params: &'a Value, name: &str) -> Result<&'a str, RpcError> {
+    params[name]
+        .as_str()
+        .ok_or_else(|| RpcError::invalid_params(format!("{name} must be a string")))
+}
+
+/// The optional `projectPath` parameter
+fn project_param(params: &Value) -> Result<Option<&Path>, RpcError> {
+    match params.get("projectPath") {
+        None | Some(Value::Null) => Ok(None),
+        Some(Value::String(path)) => Ok(Some(Path::new(path))),
+        Some(_) => Err(RpcError::invalid_params("projectPath must be a string")),
+    }
+}
+
+/// The optional `scope` parameter (merged when a project is given, else global)
+fn scope_param(params: &Value, has_project: bool) -> Result<Scope, RpcError> {
+    match params.get("scope").and_then(Value::as_str) {
+        None if has_project => Ok(Scope::Merged),
+        None | Some("global") => Ok(Scope::Global),
+        Some("project") if has_project => Ok(Scope::Project),
+        Some("merged") => Ok(Scope::Merged),
+        Some("project") => Err(RpcError::invalid_params(
+            "scope 'project' requires projectPath",
+        )),
+        Some(other) => Err(RpcError::invalid_params(format!(
+            "Unknown scope '{other}'; use global, project or merged"
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn handler(temp_dir: &TempDir) -> (Handler, PathBuf) {
+        let global = temp_dir.path().join("claude").join("config.json");
+        fs::create_dir_all(global.parent().unwrap()).unwrap();
+        fs::write(&global, r#"{"allowedPaths": ["~/a"]}"#).unwrap();
+        let manager =
+            ConfigManager::with_custom_global_config(temp_dir.path().join("backups"), &global);
+        (Handler::new(manager, CancellationToken::new()), global)
+    }
+
+    fn call(handler: &Handler, session: &mut Session, method: &str, params: Value) -> Value {
+        let line = json!({"jsonrpc": "2.0", "id": 1, "method": method, "params": params});
+        serde_json::from_str(&handler.handle_line(session, &line.to_string()).unwrap()).unwrap()
+    }
+
+    // TDD Test 1: Requests need a handshake with a supported protocol version
+    #[test]
+    fn test_handshake_is_required() {
+        let temp_dir = TempDir::new().unwrap();
+        let (handler, _) = handler(&temp_dir);
+        let mut session = Session::new();
+
+        let response = call(&handler, &mut session, "get_config", json!({}));
+        assert_eq!(response["error"]["code"], codes::HANDSHAKE_REQUIRED);
+
+        let response = call(
+            &handler,
+            &mut session,
+            "handshake",
+            json!({"protocolVersion": 99}),
+        );
+        assert_eq!(response["error"]["code"], codes::PROTOCOL_MISMATCH);
+
+        let response = call(
+            &handler,
+            &mut session,
+            "handshake",
+            json!({"protocolVersion": 1}),
+        );
+        assert_eq!(response["result"]["protocolVersion"], PROTOCOL_VERSION);
+
+        let response = call(&handler, &mut session, "get_config", json!({}));
+        assert_eq!(response["result"]["allowedPaths"], json!(["~/a"]));
+    }
+
+    // TDD Test 2: Errors carry the code and kind of the config error
+    #[test]
+    fn test_error_objects() {
+        let temp_dir = TempDir::new().unwrap();
+        let (handler, global) = handler(&temp_dir);
+        let mut session = Session::new();
+        call(
+            &handler,
+            &mut session,
+            "handshake",
+            json!({"protocolVersion": 1}),
+        );
+
+        let response = call(&handler, &mut session, "frobnicate", json!({}));
+        assert_eq!(response["error"]["code"], codes::METHOD_NOT_FOUND);
+
+        let response = call(&handler, &mut session, "search", json!({}));
+        assert_eq!(response["error"]["code"], codes::INVALID_PARAMS);
+
+        fs::write(&global, "{ not json").unwrap();
+        let response = call(&handler, &mut session, "get_config", json!({}));
+        assert_eq!(response["error"]["code"], codes::INVALID_JSON);
+        assert_eq!(response["error"]["data"]["kind"], "InvalidJson");
+
+        let line = handler.handle_line(&mut session, "not json").unwrap();
+        let response: Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(response["error"]["code"], codes::PARSE_ERROR);
+        assert_eq!(response["id"], Value::Null);
+    }
+
+    // TDD Test 3: Cached configs are re-read after the file changes
+    #[test]
+    fn test_cache_follows_file_changes() {
+        let temp_dir = TempDir::new().unwrap();
+        let (handler, global) = handler(&temp_dir);
+        let mut session = Session::new();
+        call(
+            &handler,
+            &mut session,
+            "handshake",
+            json!({"protocolVersion": 1}),
+        );
+
+        call(&handler, &mut session, "get_config", json!({}));
+        assert_eq!(
+            call(&handler, &mut session, "status", json!({}))["result"]["cachedFiles"],
+            1
+        );
+
+        fs::write(&global, r#"{"allowedPaths": ["~/edited/elsewhere"]}"#).unwrap();
+        let response = call(&handler, &mut session, "get_config", json!({}));
+        assert_eq!(
+            response["result"]["allowedPaths"],
+            json!(["~/edited/elsewhere"])
+        );
+
+        let response = call(
+            &handler,
+            &mut session,
+            "set_value",
+            json!({"key": "customInstructions", "value": ["Be brief"]}),
+        );
+        assert_eq!(response["result"]["written"], true);
+        let response = call(&handler, &mut session, "search", json!({"query": "brief"}));
+        assert_eq!(response["result"][0]["keyPath"], "customInstructions[0]");
+        let response = call(&handler, &mut session, "list_backups", json!({}));
+        assert_eq!(response["result"].as_array().unwrap().len(), 1);
+    }
+}
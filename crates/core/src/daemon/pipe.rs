@@ -0,0 +1,303 @@
+//! Named pipe transport for the daemon (Windows)
+//!
+//! The pipe rejects remote clients, and its default security descriptor
+//! only lets the user who created it (plus administrators and the system)
+//! open it for writing, so other local users can't send requests. Pipe
+//! names have the form `\\.\pipe\<name>`.
+//!
+//! Waiting for clients polls a non-blocking pipe instance, like the Unix
+//! socket polls its listener. Connected instances are switched to blocking
+//! I/O; a connection idle in a read ends when its client disconnects or the
+//! process exits.
+
+use super::{connection::serve_connection, Handler, POLL_INTERVAL};
+use crate::error::{ConfigError, Result};
+use std::fs::{File, OpenOptions};
+use std::io;
+use std::os::windows::ffi::OsStrExt;
+use std::os::windows::io::{AsRawHandle, FromRawHandle, OwnedHandle};
+use std::path::Path;
+use std::ptr;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use windows_sys::Win32::Foundation::{
+    ERROR_ACCESS_DENIED, ERROR_PIPE_BUSY, ERROR_PIPE_CONNECTED, ERROR_PIPE_LISTENING,
+    INVALID_HANDLE_VALUE,
+};
+use windows_sys::Win32::Storage::FileSystem::{FILE_FLAG_FIRST_PIPE_INSTANCE, PIPE_ACCESS_DUPLEX};
+use windows_sys::Win32::System::Pipes::{
+    ConnectNamedPipe, CreateNamedPipeW, SetNamedPipeHandleState, PIPE_NOWAIT, PIPE_READMODE_BYTE,
+    PIPE_REJECT_REMOTE_CLIENTS, PIPE_TYPE_BYTE, PIPE_UNLIMITED_INSTANCES, PIPE_WAIT,
+};
+
+/// Prefix of every local named pipe
+const PIPE_PREFIX: &str = r"\\.\pipe\";
+
+/// In and out buffer size of each pipe instance
+const BUFFER_SIZE: u32 = 64 * 1024;
+
+/// How long a client waits for a free pipe instance
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Serve requests on a named pipe until shutdown
+///
+/// Stops when the handler's cancellation token is cancelled (by the
+/// `shutdown` method or the caller), or when no request arrived for
+/// `idle_timeout`.
+///
+/// # Arguments
+/// * `handler` - Dispatcher shared by every connection
+/// * `pipe_path` - Pipe to listen on (`\\.\pipe\<name>`)
+/// * `idle_timeout` - Stop after this long without requests (`None` runs until shutdown)
+///
+/// # Errors
+/// Returns an error if:
+/// - `pipe_path` is not a named pipe
+/// - Another daemon is already listening on the pipe
+/// - The pipe cannot be created
+pub fn serve(handler: Handler, pipe_path: &Path, idle_timeout: Option<Duration>) -> Result<()> {
+    let name = pipe_name(pipe_path)?;
+    let mut listener = create_instance(&name, true).map_err(|e| {
+        if e.raw_os_error() == Some(ERROR_ACCESS_DENIED as i32) {
+            ConfigError::validation_failed(
+                "DaemonSocket",
+                format!("A daemon is already listening on {}", pipe_path.display()),
+                "Stop it with 'ccm daemon stop' or choose another pipe with --socket",
+            )
+        } else {
+            ConfigError::filesystem("create pipe", pipe_path, e)
+        }
+    })?;
+
+    tracing::info!("daemon listening on {}", pipe_path.display());
+    let handler = Arc::new(handler);
+    let last_request = Arc::new(Mutex::new(Instant::now()));
+
+    while !handler.token.is_cancelled() {
+        match poll_connect(&listener) {
+            Ok(true) => {
+                let next = create_instance(&name, false)
+                    .map_err(|e| ConfigError::filesystem("create pipe", pipe_path, e))?;
+                let stream = std::mem::replace(&mut listener, next);
+                let handler = Arc::clone(&handler);
+                let last_request = Arc::clone(&last_request);
+                thread::spawn(move || {
+                    let served = set_blocking(&stream)
+                        .and_then(|()| serve_connection(&handler, stream, &last_request));
+                    if let Err(e) = served {
+                        tracing::debug!("daemon connection closed: {e}");
+                    }
+                });
+            }
+            Ok(false) => {
+                let idle = last_request
+                    .lock()
+                    .map(|last| last.elapsed())
+                    .unwrap_or_default();
+                if idle_timeout.is_some_and(|timeout| idle >= timeout) {
+                    tracing::info!("daemon idle for {}s; stopping", idle.as_secs());
+                    handler.token.cancel();
+                    break;
+                }
+                thread::sleep(POLL_INTERVAL);
+            }
+            Err(e) => {
+                // Typically a client that connected and left before being
+                // served; the instance can't be reused without disconnecting
+                tracing::debug!("daemon accept failed: {e}");
+                listener = create_instance(&name, false)
+                    .map_err(|e| ConfigError::filesystem("create pipe", pipe_path, e))?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Connect to a daemon listening on `pipe_path`
+///
+/// Waits up to [`CONNECT_TIMEOUT`] while every pipe instance is busy.
+pub(super) fn connect(pipe_path: &Path) -> io::Result<File> {
+    let deadline = Instant::now() + CONNECT_TIMEOUT;
+    loop {
+        match OpenOptions::new().read(true).write(true).open(pipe_path) {
+            Err(e)
+                if e.raw_os_error() == Some(ERROR_PIPE_BUSY as i32)
+                    && Instant::now() < deadline =>
+            {
+                thread::sleep(Duration::from_millis(10));
+            }
+            result => return result,
+        }
+    }
+}
+
+/// `pipe_path` as a NUL-terminated wide string, if it names a local pipe
+fn pipe_name(pipe_path: &Path) -> Result<Vec<u16>> {
+    let is_pipe = pipe_path
+        .to_str()
+        .and_then(|path| path.get(..PIPE_PREFIX.len()))
+        .is_some_and(|prefix| prefix.eq_ignore_ascii_case(PIPE_PREFIX));
+    if !is_pipe {
+        return Err(ConfigError::validation_failed(
+            "DaemonSocket",
+            format!("{} is not a named pipe", pipe_path.display()),
+            format!("Use a name like {PIPE_PREFIX}ccm-daemon with --socket"),
+        ));
+    }
+    Ok(pipe_path.as_os_str().encode_wide().chain(Some(0)).collect())
+}
+
+/// Create a non-blocking pipe instance waiting for a client
+///
+/// The first instance fails with `ERROR_ACCESS_DENIED` if the pipe
+/// already exists.
+fn create_instance(name: &[u16], first: bool) -> io::Result<File> {
+    let mut open_mode = PIPE_ACCESS_DUPLEX;
+    if first {
+        open_mode |= FILE_FLAG_FIRST_PIPE_INSTANCE;
+    }
+    // SAFETY: `name` is NUL-terminated and outlives the call; a null
+    // security attributes pointer selects the default descriptor
+    let handle = unsafe {
+        CreateNamedPipeW(
+            name.as_ptr(),
+            open_mode,
+            PIPE_TYPE_BYTE | PIPE_READMODE_BYTE | PIPE_NOWAIT | PIPE_REJECT_REMOTE_CLIENTS,
+            PIPE_UNLIMITED_INSTANCES,
+            BUFFER_SIZE,
+            BUFFER_SIZE,
+            0,
+            ptr::null(),
+        )
+    };
+    if handle == INVALID_HANDLE_VALUE {
+        return Err(io::Error::last_os_error());
+    }
+    // SAFETY: the handle is valid and owned by nothing else
+    Ok(File::from(unsafe { OwnedHandle::from_raw_handle(handle) }))
+}
+
+/// Check a waiting instance for a client without blocking
+///
+/// # Returns
+/// Whether a client is connected
+fn poll_connect(pipe: &File) -> io::Result<bool> {
+    // SAFETY: the handle belongs to `pipe`; a non-blocking pipe needs no
+    // OVERLAPPED structure
+    let ready = unsafe { ConnectNamedPipe(pipe.as_raw_handle(), ptr::null_mut()) } != 0;
+    if ready {
+        return Ok(false);
+    }
+    let error = io::Error::last_os_error();
+    match error.raw_os_error().map(|code| code as u32) {
+        Some(ERROR_PIPE_LISTENING) => Ok(false),
+        Some(ERROR_PIPE_CONNECTED) => Ok(true),
+        _ => Err(error),
+    }
+}
+
+/// Switch a connected instance to blocking reads and writes
+fn set_blocking(pipe: &File) -> io::Result<()> {
+    let mode = PIPE_READMODE_BYTE | PIPE_WAIT;
+    // SAFETY: the handle belongs to `pipe` and `mode` outlives the call
+    let done =
+        unsafe { SetNamedPipeHandleState(pipe.as_raw_handle(), &mode, ptr::null(), ptr::null()) };
+    if done == 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bundle::CancellationToken;
+    use crate::config::manager::ConfigManager;
+    use crate::daemon::Client;
+    use serde_json::json;
+    use std::fs;
+    use std::path::PathBuf;
+    use tempfile::TempDir;
+
+    fn start(
+        temp_dir: &TempDir,
+        idle_timeout: Option<Duration>,
+    ) -> (PathBuf, PathBuf, thread::JoinHandle<Result<()>>) {
+        let global = temp_dir.path().join("config.json");
+        fs::write(&global, r#"{"allowedPaths": ["~/a"]}"#).unwrap();
+        let manager =
+            ConfigManager::with_custom_global_config(temp_dir.path().join("backups"), &global);
+        let handler = Handler::new(manager, CancellationToken::new());
+
+        let name = crate::hash::content_hash(temp_dir.path().to_string_lossy().as_bytes());
+        let pipe = PathBuf::from(format!(r"{PIPE_PREFIX}ccm-test-{name}"));
+        let server_pipe = pipe.clone();
+        let server = thread::spawn(move || serve(handler, &server_pipe, idle_timeout));
+        while connect(&pipe).is_err() {
+            thread::sleep(Duration::from_millis(10));
+        }
+        (pipe, global, server)
+    }
+
+    // TDD Test 1: A client reads and writes configs and stops the daemon
+    #[test]
+    fn test_serve_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let (pipe, global, server) = start(&temp_dir, None);
+
+        let mut client = Client::connect(&pipe).unwrap();
+        let config = client.call("get_config", json!({})).unwrap();
+        assert_eq!(config["allowedPaths"], json!(["~/a"]));
+
+        let result = client
+            .call(
+                "set_value",
+                json!({"key": "allowedPaths[1]", "value": "~/b"}),
+            )
+            .unwrap();
+        assert_eq!(result["written"], true);
+        assert!(fs::read_to_string(&global).unwrap().contains("~/b"));
+
+        let response = client.call_raw("no_such_method", json!({})).unwrap();
+        assert_eq!(
+            response["error"]["code"],
+            super::super::codes::METHOD_NOT_FOUND
+        );
+
+        let handler = Handler::new(
+            ConfigManager::new(temp_dir.path().join("backups")),
+            CancellationToken::new(),
+        );
+        assert!(matches!(
+            serve(handler, &pipe, None),
+            Err(ConfigError::ValidationFailed { .. })
+        ));
+
+        client.call("shutdown", json!({})).unwrap();
+        server.join().unwrap().unwrap();
+    }
+
+    // TDD Test 2: The daemon stops on its own when idle
+    #[test]
+    fn test_idle_timeout() {
+        let temp_dir = TempDir::new().unwrap();
+        let (_, _, server) = start(&temp_dir, Some(Duration::from_millis(300)));
+
+        server.join().unwrap().unwrap();
+    }
+
+    // TDD Test 3: Paths that aren't named pipes are refused
+    #[test]
+    fn test_refuses_non_pipe_path() {
+        let temp_dir = TempDir::new().unwrap();
+        let handler = Handler::new(
+            ConfigManager::new(temp_dir.path().join("backups")),
+            CancellationToken::new(),
+        );
+
+        let err = serve(handler, &temp_dir.path().join("ccm.sock"), None).unwrap_err();
+        assert!(err.to_string().contains("is not a named pipe"));
+    }
+}
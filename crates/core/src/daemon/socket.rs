@@ -0,0 +1,277 @@
+//! Unix domain socket transport for the daemon
+//!
+//! The socket lives in a directory only the current user can enter, and the
+//! socket file itself is `0600`. On Linux each connection's peer credentials
+//! are checked as well, so another local user can't talk to the daemon even
+//! if the permissions are loosened later.
+
+use super::{connection::serve_connection, Handler, POLL_INTERVAL};
+use crate::error::{ConfigError, Result};
+use std::fs::{self, DirBuilder, Permissions};
+use std::io::{self, ErrorKind};
+use std::os::unix::fs::{DirBuilderExt, FileTypeExt, MetadataExt, PermissionsExt};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Serve requests on a socket until shutdown
+///
+/// Stops when the handler's cancellation token is cancelled (by the
+/// `shutdown` method or the caller), or when no request arrived for
+/// `idle_timeout`. The socket file is removed on the way out.
+///
+/// # Arguments
+/// * `handler` - Dispatcher shared by every connection
+/// * `socket_path` - Socket to listen on; its directory is created `0700`
+/// * `idle_timeout` - Stop after this long without requests (`None` runs until shutdown)
+///
+/// # Errors
+/// Returns an error if:
+/// - The socket directory is accessible to other users or owned by someone else
+/// - Another daemon is already listening on the socket
+/// - The socket cannot be created
+pub fn serve(handler: Handler, socket_path: &Path, idle_timeout: Option<Duration>) -> Result<()> {
+    let listener = bind(socket_path)?;
+    listener
+        .set_nonblocking(true)
+        .map_err(|e| ConfigError::filesystem("listen on", socket_path, e))?;
+
+    tracing::info!("daemon listening on {}", socket_path.display());
+    let handler = Arc::new(handler);
+    let last_request = Arc::new(Mutex::new(Instant::now()));
+
+    while !handler.token.is_cancelled() {
+        match listener.accept() {
+            Ok((stream, _)) => {
+                if !peer_is_current_user(&stream) {
+                    tracing::warn!("rejected a daemon connection from another user");
+                    continue;
+                }
+                let handler = Arc::clone(&handler);
+                let last_request = Arc::clone(&last_request);
+                thread::spawn(move || {
+                    let served = stream
+                        .set_nonblocking(false)
+                        .and_then(|()| stream.set_read_timeout(Some(POLL_INTERVAL)))
+                        .and_then(|()| serve_connection(&handler, stream, &last_request));
+                    if let Err(e) = served {
+                        tracing::debug!("daemon connection closed: {e}");
+                    }
+                });
+            }
+            Err(e) if e.kind() == ErrorKind::WouldBlock => {
+                let idle = last_request
+                    .lock()
+                    .map(|last| last.elapsed())
+                    .unwrap_or_default();
+                if idle_timeout.is_some_and(|timeout| idle >= timeout) {
+                    tracing::info!("daemon idle for {}s; stopping", idle.as_secs());
+                    handler.token.cancel();
+                    break;
+                }
+                thread::sleep(POLL_INTERVAL);
+            }
+            Err(e) => tracing::warn!("daemon accept failed: {e}"),
+        }
+    }
+
+    let _ = fs::remove_file(socket_path);
+    Ok(())
+}
+
+/// Create the socket, replacing a stale one left by a crashed daemon
+fn bind(socket_path: &Path) -> Result<UnixListener> {
+    let dir = socket_path
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    prepare_private_dir(dir)?;
+
+    if let Ok(metadata) = fs::symlink_metadata(socket_path) {
+        if !metadata.file_type().is_socket() {
+            return Err(ConfigError::validation_failed(
+                "DaemonSocket",
+                format!("{} exists and is not a socket", socket_path.display()),
+                "Remove the file or choose another path with --socket",
+            ));
+        }
+        if UnixStream::connect(socket_path).is_ok() {
+            return Err(ConfigError::validation_failed(
+                "DaemonSocket",
+                format!("A daemon is already listening on {}", socket_path.display()),
+                "Stop it with 'ccm daemon stop' or choose another path with --socket",
+            ));
+        }
+        fs::remove_file(socket_path)
+            .map_err(|e| ConfigError::filesystem("remove stale socket", socket_path, e))?;
+    }
+
+    let listener = UnixListener::bind(socket_path)
+        .map_err(|e| ConfigError::filesystem("bind socket", socket_path, e))?;
+    fs::set_permissions(socket_path, Permissions::from_mode(0o600))
+        .map_err(|e| ConfigError::filesystem("set permissions on", socket_path, e))?;
+    Ok(listener)
+}
+
+/// Create `dir` as `0700`, or check that an existing one is private
+fn prepare_private_dir(dir: &Path) -> Result<()> {
+    if !dir.exists() {
+        DirBuilder::new()
+            .recursive(true)
+            .mode(0o700)
+            .create(dir)
+            .map_err(|e| ConfigError::filesystem("create socket directory", dir, e))?;
+    }
+
+    let metadata = fs::metadata(dir)
+        .map_err(|e| ConfigError::filesystem("inspect socket directory", dir, e))?;
+    let uid = rustix::process::geteuid().as_raw();
+    if metadata.uid() != uid || metadata.mode() & 0o077 != 0 {
+        return Err(ConfigError::validation_failed(
+            "DaemonSocket",
+            format!(
+                "Socket directory {} is not private (owner {}, mode {:o})",
+                dir.display(),
+                metadata.uid(),
+                metadata.mode() & 0o777
+            ),
+            format!("Run 'chmod 700 {}' as its owner", dir.display()),
+        ));
+    }
+    Ok(())
+}
+
+/// Whether the process on the other end runs as the current user
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn peer_is_current_user(stream: &UnixStream) -> bool {
+    rustix::net::sockopt::socket_peercred(stream)
+        .is_ok_and(|cred| cred.uid == rustix::process::geteuid())
+}
+
+/// Whether the process on the other end runs as the current user
+///
+/// Without `SO_PEERCRED` the private socket directory is the only check.
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+fn peer_is_current_user(_stream: &UnixStream) -> bool {
+    true
+}
+
+/// Connect to a daemon listening on `socket_path`
+pub(super) fn connect(socket_path: &Path) -> io::Result<UnixStream> {
+    UnixStream::connect(socket_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bundle::CancellationToken;
+    use crate::config::manager::ConfigManager;
+    use crate::daemon::Client;
+    use serde_json::json;
+    use std::path::PathBuf;
+    use tempfile::TempDir;
+
+    fn start(
+        temp_dir: &TempDir,
+        idle_timeout: Option<Duration>,
+    ) -> (PathBuf, PathBuf, thread::JoinHandle<Result<()>>) {
+        let global = temp_dir.path().join("config.json");
+        fs::write(&global, r#"{"allowedPaths": ["~/a"]}"#).unwrap();
+        let manager =
+            ConfigManager::with_custom_global_config(temp_dir.path().join("backups"), &global);
+        let handler = Handler::new(manager, CancellationToken::new());
+
+        let socket = temp_dir.path().join("daemon").join("ccm.sock");
+        let server_socket = socket.clone();
+        let server = thread::spawn(move || serve(handler, &server_socket, idle_timeout));
+        while !socket.exists() {
+            thread::sleep(Duration::from_millis(10));
+        }
+        (socket, global, server)
+    }
+
+    // TDD Test 1: A client reads and writes configs and stops the daemon
+    #[test]
+    fn test_serve_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let (socket, global, server) = start(&temp_dir, None);
+
+        let dir_mode = fs::metadata(socket.parent().unwrap()).unwrap().mode();
+        assert_eq!(dir_mode & 0o777, 0o700);
+        let socket_mode = fs::metadata(&socket).unwrap().mode();
+        assert_eq!(socket_mode & 0o777, 0o600);
+
+        let mut client = Client::connect(&socket).unwrap();
+        let config = client.call("get_config", json!({})).unwrap();
+        assert_eq!(config["allowedPaths"], json!(["~/a"]));
+
+        let result = client
+            .call(
+                "set_value",
+                json!({"key": "allowedPaths[1]", "value": "~/b"}),
+            )
+            .unwrap();
+        assert_eq!(result["written"], true);
+        assert!(fs::read_to_string(&global).unwrap().contains("~/b"));
+
+        let response = client.call_raw("no_such_method", json!({})).unwrap();
+        assert_eq!(
+            response["error"]["code"],
+            super::super::codes::METHOD_NOT_FOUND
+        );
+
+        assert!(matches!(
+            bind(&socket),
+            Err(ConfigError::ValidationFailed { .. })
+        ));
+
+        client.call("shutdown", json!({})).unwrap();
+        server.join().unwrap().unwrap();
+        assert!(!socket.exists());
+    }
+
+    // TDD Test 2: The daemon stops on its own when idle
+    #[test]
+    fn test_idle_timeout() {
+        let temp_dir = TempDir::new().unwrap();
+        let (socket, _, server) = start(&temp_dir, Some(Duration::from_millis(300)));
+
+        server.join().unwrap().unwrap();
+        assert!(!socket.exists());
+    }
+
+    // TDD Test 3: A socket directory readable by others is refused
+    #[test]
+    fn test_refuses_shared_directory() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path().join("shared");
+        fs::create_dir(&dir).unwrap();
+        fs::set_permissions(&dir, Permissions::from_mode(0o755)).unwrap();
+
+        let err = bind(&dir.join("ccm.sock")).unwrap_err();
+        assert!(err.to_string().contains("is not private"));
+    }
+
+    // TDD Test 4: An external edit is served on the next request
+    #[test]
+    fn test_external_edit_invalidates_cache() {
+        let temp_dir = TempDir::new().unwrap();
+        let (socket, global, server) = start(&temp_dir, None);
+
+        let mut client = Client::connect(&socket).unwrap();
+        let config = client.call("get_config", json!({})).unwrap();
+        assert_eq!(config["allowedPaths"], json!(["~/a"]));
+        let status = client.call("status", json!({})).unwrap();
+        assert_eq!(status["cachedFiles"], 1);
+
+        fs::write(&global, r#"{"allowedPaths": ["~/edited", "~/outside"]}"#).unwrap();
+
+        let config = client.call("get_config", json!({})).unwrap();
+        assert_eq!(config["allowedPaths"], json!(["~/edited", "~/outside"]));
+
+        client.call("shutdown", json!({})).unwrap();
+        server.join().unwrap().unwrap();
+    }
+}
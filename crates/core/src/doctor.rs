@@ -0,0 +1,706 @@
+//! Installation self-checks
+//!
+//! This module runs quick health checks on the configuration files ccm
+//! manages and reports problems in a form suitable for display or for
+//! inclusion in bug reports.
+
+use crate::{
+    config::{schema, ClaudeConfig},
+    paths::{
+        get_backup_dir, get_global_config_path, resolve_config_dir, EnvReader, Platform,
+        CCM_HOME_VAR,
+    },
+    validate_config,
+};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+/// Outcome of a single check
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    /// Check passed
+    Ok,
+    /// Something looks off but ccm can still work
+    Warning,
+    /// Something is broken
+    Error,
+}
+
+/// Result of a single check
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DoctorCheck {
+    /// Short check name
+    pub name: String,
+
+    /// Check outcome
+    pub status: CheckStatus,
+
+    /// Human-readable explanation
+    pub message: String,
+}
+
+impl DoctorCheck {
+    fn new(name: &str, status: CheckStatus, message: impl Into<String>) -> Self {
+        Self {
+            name: name.to_string(),
+            status,
+            message: message.into(),
+        }
+    }
+}
+
+/// Results of all checks
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DoctorReport {
+    /// Individual check results, in the order they ran
+    pub checks: Vec<DoctorCheck>,
+}
+
+impl DoctorReport {
+    /// Whether any check reported an error
+    pub fn has_errors(&self) -> bool {
+        self.checks.iter().any(|c| c.status == CheckStatus::Error)
+    }
+}
+
+/// Which optional checks to run
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DoctorOptions {
+    /// Also check each config file against the bundled JSON Schema
+    pub strict: bool,
+
+    /// Repair problems that can be fixed safely (e.g. tighten the
+    /// permissions of backups holding secrets)
+    pub fix: bool,
+}
+
+impl DoctorOptions {
+    /// Create default options (no optional checks)
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Enable or disable the strict schema checks
+    pub fn with_strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Enable or disable fixing problems where possible
+    pub fn with_fix(mut self, fix: bool) -> Self {
+        self.fix = fix;
+        self
+    }
+}
+
+/// Run all checks
+///
+/// # Arguments
+/// * `global_config_path` - Path to the global config file
+/// * `project_config_path` - Path to the project config file, if any
+/// * `backup_dir` - Directory where backups are stored
+pub fn run_checks(
+    global_config_path: &Path,
+    project_config_path: Option<&Path>,
+    backup_dir: &Path,
+) -> DoctorReport {
+    run_checks_with_options(
+        global_config_path,
+        project_config_path,
+        backup_dir,
+        DoctorOptions::default(),
+    )
+}
+
+/// Run all checks, including the optional ones enabled in `options`
+pub fn run_checks_with_options(
+    global_config_path: &Path,
+    project_config_path: Option<&Path>,
+    backup_dir: &Path,
+    options: DoctorOptions,
+) -> DoctorReport {
+    // ccm cannot work without a global config; a project config is optional
+    let config_files = std::iter::once(("global config", global_config_path, true))
+        .chain(project_config_path.map(|path| ("project config", path, false)));
+
+    let mut checks = Vec::new();
+    for (name, path, required) in config_files {
+        checks.push(check_config_file(name, path, required));
+        if options.strict {
+            checks.extend(check_schema(&format!("{name} schema"), path));
+        }
+    }
+
+    checks.push(check_backup_dir(backup_dir));
+
+    // Project backups live next to the project config
+    let project_backup_dir = project_config_path
+        .and_then(Path::parent)
+        .map(|dir| dir.join("backups"));
+    let backup_dirs: Vec<&Path> = std::iter::once(backup_dir)
+        .chain(project_backup_dir.as_deref())
+        .collect();
+    checks.extend(check_backup_permissions(&backup_dirs, options.fix));
+
+    checks.extend(check_aliases(global_config_path));
+
+    DoctorReport { checks }
+}
+
+/// Check ccm's own environment, then run all checks on its default paths
+///
+/// Starts with a check of the global configuration directory (see
+/// [`check_config_dir`]), followed by [`run_checks_with_options`] on the
+/// global config and backup directory that directory resolves to.
+///
+/// # Arguments
+/// * `project_config_path` - Path to the project config file, if any
+/// * `options` - Optional checks to run
+pub fn run_self_checks(project_config_path: Option<&Path>, options: DoctorOptions) -> DoctorReport {
+    let mut report = run_checks_with_options(
+        &get_global_config_path(),
+        project_config_path,
+        &get_backup_dir(),
+        options,
+    );
+    report.checks.insert(
+        0,
+        check_config_dir(
+            Platform::current(),
+            dirs::config_dir(),
+            &EnvReader::process(),
+        ),
+    );
+    report
+}
+
+/// Check where the global configuration directory resolves to
+///
+/// Reports whether [`CCM_HOME_VAR`] overrides the platform directory. An
+/// override that is relative (and so depends on the working directory) or
+/// names something other than a directory is an error.
+///
+/// # Arguments
+/// * `platform` - Platform to resolve for
+/// * `dirs_config` - Platform config directory, if known
+/// * `env` - Environment to read overrides from
+pub fn check_config_dir(
+    platform: Platform,
+    dirs_config: Option<std::path::PathBuf>,
+    env: &EnvReader,
+) -> DoctorCheck {
+    let name = "config directory";
+    let dir = resolve_config_dir(platform, dirs_config.clone(), env);
+
+    if env.var(CCM_HOME_VAR).is_none() {
+        return DoctorCheck::new(
+            name,
+            CheckStatus::Ok,
+            format!("using the {} default {}", platform.key(), dir.display()),
+        );
+    }
+
+    if dir.is_relative() {
+        return DoctorCheck::new(
+            name,
+            CheckStatus::Error,
+            format!(
+                "{CCM_HOME_VAR} is a relative path ({}); set it to an absolute path",
+                dir.display()
+            ),
+        );
+    }
+    if dir.exists() && !dir.is_dir() {
+        return DoctorCheck::new(
+            name,
+            CheckStatus::Error,
+            format!("{CCM_HOME_VAR} ({}) is not a directory", dir.display()),
+        );
+    }
+
+    let default = resolve_config_dir(
+        platform,
+        dirs_config,
+        &EnvReader::from_vars(env_without_override(env)),
+    );
+    DoctorCheck::new(
+        name,
+        CheckStatus::Ok,
+        format!(
+            "{CCM_HOME_VAR} overrides the {} default {}: using {}",
+            platform.key(),
+            default.display(),
+            dir.display()
+        ),
+    )
+}
+
+/// The fallback variables of `env`, without [`CCM_HOME_VAR`]
+fn env_without_override(env: &EnvReader) -> Vec<(&'static str, String)> {
+    ["HOME", "APPDATA"]
+        .into_iter()
+        .filter_map(|name| env.var(name).map(|value| (name, value)))
+        .collect()
+}
+
+/// Check that a config file parses and validates
+///
+/// A missing file is an error if `required`, otherwise a warning.
+fn check_config_file(name: &str, path: &Path, required: bool) -> DoctorCheck {
+    if !path.exists() {
+        let status = if required {
+            CheckStatus::Error
+        } else {
+            CheckStatus::Warning
+        };
+        return DoctorCheck::new(name, status, format!("{} does not exist", path.display()));
+    }
+
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) => {
+            return DoctorCheck::new(
+                name,
+                CheckStatus::Error,
+                format!("cannot read {}: {e}", path.display()),
+            )
+        }
+    };
+
+    let config: ClaudeConfig = match serde_json::from_str(&content) {
+        Ok(config) => config,
+        Err(e) => {
+            return DoctorCheck::new(
+                name,
+                CheckStatus::Error,
+                format!("invalid JSON in {}: {e}", path.display()),
+            )
+        }
+    };
+
+    match validate_config(&config) {
+        Ok(()) => DoctorCheck::new(
+            name,
+            CheckStatus::Ok,
+            format!("{} is valid", path.display()),
+        ),
+        Err(e) => DoctorCheck::new(
+            name,
+            CheckStatus::Error,
+            format!("{} failed validation: {e}", path.display()),
+        ),
+    }
+}
+
+/// Check a config file against the bundled schema
+///
+/// Returns nothing for files that are missing or not JSON; the file check
+/// already reports those.
+fn check_schema(name: &str, path: &Path) -> Option<DoctorCheck> {
+    let raw: serde_json::Value = serde_json::from_str(&fs::read_to_string(path).ok()?).ok()?;
+    let violations = schema::validate_against_schema(&raw);
+
+    Some(if violations.is_empty() {
+        DoctorCheck::new(
+            name,
+            CheckStatus::Ok,
+            format!(
+                "{} matches schema version {}",
+                path.display(),
+                schema::LATEST_SCHEMA_VERSION
+            ),
+        )
+    } else {
+        let details: Vec<String> = violations.iter().map(ToString::to_string).collect();
+        DoctorCheck::new(
+            name,
+            CheckStatus::Error,
+            format!(
+                "{} does not match the schema: {}",
+                path.display(),
+                details.join("; ")
+            ),
+        )
+    })
+}
+
+/// Check that the backup directory is usable
+fn check_backup_dir(backup_dir: &Path) -> DoctorCheck {
+    let name = "backup directory";
+
+    match fs::metadata(backup_dir) {
+        Ok(metadata) if metadata.permissions().readonly() => DoctorCheck::new(
+            name,
+            CheckStatus::Error,
+            format!("{} is read-only", backup_dir.display()),
+        ),
+        Ok(metadata) if metadata.is_dir() => match probe_writable(backup_dir) {
+            Ok(()) => DoctorCheck::new(
+                name,
+                CheckStatus::Ok,
+                format!("{} is writable", backup_dir.display()),
+            ),
+            Err(e) => DoctorCheck::new(
+                name,
+                CheckStatus::Error,
+                format!("cannot write to {}: {e}", backup_dir.display()),
+            ),
+        },
+        Ok(_) => DoctorCheck::new(
+            name,
+            CheckStatus::Error,
+            format!("{} is not a directory", backup_dir.display()),
+        ),
+        Err(_) => DoctorCheck::new(
+            name,
+            CheckStatus::Ok,
+            format!(
+                "{} does not exist yet (created on first backup)",
+                backup_dir.display()
+            ),
+        ),
+    }
+}
+
+/// Create and remove a file in `dir`
+///
+/// The read-only flag doesn't cover ownership or ACLs, so writing is the
+/// only reliable test.
+fn probe_writable(dir: &Path) -> std::io::Result<()> {
+    let probe = dir.join(".ccm-doctor-probe");
+    fs::write(&probe, b"")?;
+    fs::remove_file(&probe)
+}
+
+/// Check that backups holding secrets aren't readable by other users
+///
+/// Returns nothing if no backup contains likely secrets. With `fix`, the
+/// permissions of offending backups are narrowed to the owner.
+#[cfg(unix)]
+fn check_backup_permissions(backup_dirs: &[&Path], fix: bool) -> Option<DoctorCheck> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let name = "backup permissions";
+    let mut secret_backups = 0;
+    let mut exposed = Vec::new();
+    let mut failed = Vec::new();
+
+    let files = backup_dirs
+        .iter()
+        .filter_map(|dir| fs::read_dir(dir).ok())
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file());
+
+    for path in files {
+        if !has_secrets(&path) {
+            continue;
+        }
+        secret_backups += 1;
+
+        let Ok(metadata) = fs::metadata(&path) else {
+            continue;
+        };
+        let mode = metadata.permissions().mode();
+        if mode & 0o004 == 0 {
+            continue;
+        }
+
+        if fix {
+            let tightened = fs::Permissions::from_mode(mode & 0o700);
+            if let Err(e) = fs::set_permissions(&path, tightened) {
+                failed.push(format!("{}: {e}", path.display()));
+                continue;
+            }
+        }
+        exposed.push(path.display().to_string());
+    }
+
+    if secret_backups == 0 {
+        return None;
+    }
+
+    Some(if !failed.is_empty() {
+        DoctorCheck::new(
+            name,
+            CheckStatus::Error,
+            format!("cannot tighten backup permissions: {}", failed.join("; ")),
+        )
+    } else if exposed.is_empty() {
+        DoctorCheck::new(
+            name,
+            CheckStatus::Ok,
+            format!("{secret_backups} backup(s) with secrets are private"),
+        )
+    } else if fix {
+        DoctorCheck::new(
+            name,
+            CheckStatus::Ok,
+            format!(
+                "made {} backup(s) with secrets private to the owner: {}",
+                exposed.len(),
+                exposed.join(", ")
+            ),
+        )
+    } else {
+        DoctorCheck::new(
+            name,
+            CheckStatus::Warning,
+            format!(
+                "backup(s) with secrets are world-readable: {} (run `ccm doctor --fix` to tighten them)",
+                exposed.join(", ")
+            ),
+        )
+    })
+}
+
+#[cfg(not(unix))]
+fn check_backup_permissions(_backup_dirs: &[&Path], _fix: bool) -> Option<DoctorCheck> {
+    None
+}
+
+/// Whether a backup contains values the redactor would hide
+#[cfg(unix)]
+fn has_secrets(path: &Path) -> bool {
+    use crate::{backup::read_backup, redact::Redactor};
+
+    let Ok(config) = read_backup(path) else {
+        return false;
+    };
+    let Ok(value) = serde_json::to_value(&config) else {
+        return false;
+    };
+
+    let mut redactor = Redactor::new();
+    redactor.redact_value(&value);
+    !redactor.take_redacted_paths().is_empty()
+}
+
+/// Check that project path aliases point at existing directories
+///
+/// Returns nothing if the global config defines no aliases (or can't be
+/// read; the file check reports that).
+fn check_aliases(global_config_path: &Path) -> Option<DoctorCheck> {
+    let config: ClaudeConfig =
+        serde_json::from_str(&fs::read_to_string(global_config_path).ok()?).ok()?;
+    let settings = config.ccm_settings();
+    if settings.aliases.is_empty() {
+        return None;
+    }
+
+    let stale = settings.stale_aliases();
+    Some(if stale.is_empty() {
+        DoctorCheck::new(
+            "project aliases",
+            CheckStatus::Ok,
+            format!(
+                "{} alias(es) point at existing directories",
+                settings.aliases.len()
+            ),
+        )
+    } else {
+        let details: Vec<String> = stale
+            .iter()
+            .map(|(name, target)| format!("@{name} -> {}", target.display()))
+            .collect();
+        DoctorCheck::new(
+            "project aliases",
+            CheckStatus::Warning,
+            format!(
+                "alias target(s) no longer exist: {} (update them with `ccm alias add` or remove them with `ccm alias remove`)",
+                details.join(", ")
+            ),
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    // TDD Test 1: Reports invalid JSON as an error
+    #[test]
+    fn test_run_checks_reports_invalid_json() {
+        let temp_dir = TempDir::new().unwrap();
+        let global = temp_dir.path().join("config.json");
+        fs::write(&global, "{ invalid").unwrap();
+
+        let report = run_checks(&global, None, &temp_dir.path().join("backups"));
+
+        assert!(report.has_errors());
+        assert_eq!(report.checks[0].status, CheckStatus::Error);
+    }
+
+    // TDD Test 2: Healthy setup has no errors
+    #[test]
+    fn test_run_checks_healthy() {
+        let temp_dir = TempDir::new().unwrap();
+        let global = temp_dir.path().join("config.json");
+        fs::write(&global, r#"{"mcpServers": {}}"#).unwrap();
+
+        let report = run_checks(&global, None, temp_dir.path());
+
+        assert!(!report.has_errors());
+        assert_eq!(report.checks.len(), 2);
+    }
+
+    // TDD Test 2a: A missing global config is an error, a missing project config is not
+    #[test]
+    fn test_missing_config_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let global = temp_dir.path().join("config.json");
+        let project = temp_dir.path().join("project.json");
+
+        let report = run_checks(&global, None, temp_dir.path());
+        assert!(report.has_errors());
+        assert_eq!(report.checks[0].status, CheckStatus::Error);
+
+        fs::write(&global, r#"{"mcpServers": {}}"#).unwrap();
+        let report = run_checks(&global, Some(&project), temp_dir.path());
+        assert!(!report.has_errors());
+        assert_eq!(report.checks[1].status, CheckStatus::Warning);
+    }
+
+    // TDD Test 2b: Aliases pointing at missing directories are warnings
+    #[test]
+    fn test_stale_aliases_are_reported() {
+        let temp_dir = TempDir::new().unwrap();
+        let global = temp_dir.path().join("config.json");
+        let missing = temp_dir.path().join("missing");
+        fs::write(
+            &global,
+            serde_json::json!({"ccm": {"aliases": {"gone": missing, "here": temp_dir.path()}}})
+                .to_string(),
+        )
+        .unwrap();
+
+        let report = run_checks(&global, None, temp_dir.path());
+
+        assert!(!report.has_errors());
+        let check = report.checks.last().unwrap();
+        assert_eq!(check.name, "project aliases");
+        assert_eq!(check.status, CheckStatus::Warning);
+        assert!(check.message.contains("@gone"));
+        assert!(!check.message.contains("@here"));
+    }
+
+    // TDD Test 3: Strict mode adds schema checks for config files
+    #[test]
+    fn test_strict_checks_schema() {
+        let temp_dir = TempDir::new().unwrap();
+        let global = temp_dir.path().join("config.json");
+        // The structs ignore an unknown server `type`; the schema doesn't
+        fs::write(
+            &global,
+            r#"{"mcpServers": {"fetch": {"command": "uvx", "enabled": true, "type": "pipe"}}}"#,
+        )
+        .unwrap();
+
+        let report = run_checks(&global, None, temp_dir.path());
+        assert_eq!(report.checks.len(), 2);
+
+        let strict = DoctorOptions::new().with_strict(true);
+        let report = run_checks_with_options(&global, None, temp_dir.path(), strict);
+        assert_eq!(report.checks.len(), 3);
+        assert_eq!(report.checks[0].status, CheckStatus::Ok);
+        assert_eq!(report.checks[1].name, "global config schema");
+        assert_eq!(report.checks[1].status, CheckStatus::Error);
+        assert!(report.checks[1]
+            .message
+            .contains(r#"/mcpServers/fetch/type: expected one of "stdio", "sse", "http""#));
+    }
+
+    // TDD Test 5: The config directory check reports overrides
+    #[test]
+    fn test_check_config_dir() {
+        let temp_dir = TempDir::new().unwrap();
+        let dirs_config = Some(std::path::PathBuf::from("/home/u/.config"));
+        let check = |vars: &[(&str, &str)]| {
+            check_config_dir(
+                Platform::Linux,
+                dirs_config.clone(),
+                &EnvReader::from_vars(vars.iter().copied()),
+            )
+        };
+
+        let default = check(&[]);
+        assert_eq!(default.status, CheckStatus::Ok);
+        assert_eq!(
+            default.message,
+            "using the linux default /home/u/.config/claude"
+        );
+
+        let home = temp_dir.path().display().to_string();
+        let overridden = check(&[("CCM_HOME", &home)]);
+        assert_eq!(overridden.status, CheckStatus::Ok);
+        assert!(overridden
+            .message
+            .contains("overrides the linux default /home/u/.config/claude"));
+        assert!(overridden.message.ends_with(&home));
+
+        assert_eq!(check(&[("CCM_HOME", "sandbox")]).status, CheckStatus::Error);
+
+        let file = temp_dir.path().join("file");
+        fs::write(&file, "").unwrap();
+        let file = file.display().to_string();
+        assert_eq!(check(&[("CCM_HOME", &file)]).status, CheckStatus::Error);
+    }
+
+    // TDD Test 4: World-readable backups with secrets are flagged and fixed
+    #[cfg(unix)]
+    #[test]
+    fn test_backup_permissions_check() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = TempDir::new().unwrap();
+        let global = temp_dir.path().join("config.json");
+        fs::write(&global, r#"{"mcpServers": {}}"#).unwrap();
+
+        let backup_dir = temp_dir.path().join("backups");
+        fs::create_dir(&backup_dir).unwrap();
+        let secret = backup_dir.join("config_20250120_123456.000000.json");
+        fs::write(
+            &secret,
+            r#"{"mcpServers": {"gh": {"command": "gh-mcp", "enabled": true, "env": {"GITHUB_TOKEN": "ghp_0123456789abcdef"}}}}"#,
+        )
+        .unwrap();
+        let plain = backup_dir.join("config_20250120_123457.000000.json");
+        fs::write(&plain, r#"{"allowedPaths": ["~/a"]}"#).unwrap();
+        for path in [&secret, &plain] {
+            fs::set_permissions(path, fs::Permissions::from_mode(0o644)).unwrap();
+        }
+
+        let report = run_checks(&global, None, &backup_dir);
+        let check = report
+            .checks
+            .iter()
+            .find(|c| c.name == "backup permissions")
+            .unwrap();
+        assert_eq!(check.status, CheckStatus::Warning);
+        assert!(check.message.contains("config_20250120_123456"));
+        assert!(!check.message.contains("config_20250120_123457"));
+
+        let fix = DoctorOptions::new().with_fix(true);
+        let report = run_checks_with_options(&global, None, &backup_dir, fix);
+        let check = report
+            .checks
+            .iter()
+            .find(|c| c.name == "backup permissions")
+            .unwrap();
+        assert_eq!(check.status, CheckStatus::Ok);
+
+        let mode = |path: &Path| fs::metadata(path).unwrap().permissions().mode() & 0o777;
+        assert_eq!(mode(&secret), 0o600);
+        assert_eq!(mode(&plain), 0o644);
+
+        let report = run_checks(&global, None, &backup_dir);
+        assert!(report
+            .checks
+            .iter()
+            .any(|c| c.name == "backup permissions" && c.status == CheckStatus::Ok));
+    }
+}
@@ -23,15 +23,18 @@ pub enum ConfigError {
     #[error("Configuration file not found: {path}\n\nSuggestion: Create a new config file or specify a valid path with --project")]
     NotFound { path: PathBuf },
 
-    /// Invalid JSON in configuration file
+    /// Configuration file that doesn't parse in its format (JSON, TOML)
     ///
-    /// Includes line number and specific error details
-    #[error("Invalid JSON in configuration file: {path}\nError at line {line}, column {column}: {message}\n\nSuggestion: Check JSON syntax and ensure proper quoting")]
-    InvalidJson {
+    /// Includes line number, specific error details and, for common
+    /// mistakes, a targeted suggestion
+    #[error("Invalid {format} in configuration file: {path}\nError at line {line}, column {column}: {message}\n\nSuggestion: {suggestion}")]
+    InvalidFormat {
+        format: String,
         path: PathBuf,
         line: usize,
         column: usize,
         message: String,
+        suggestion: String,
     },
 
     /// Configuration validation failed
@@ -56,6 +59,18 @@ pub enum ConfigError {
         source: std::io::Error,
     },
 
+    /// Filesystem operation failed because of a Windows path limit
+    ///
+    /// Names the limit (reserved device name or MAX_PATH) and the offending
+    /// path component
+    #[error("Filesystem error: {operation} failed for {path}\n\nWindows limitation: {limitation}\n\nOS Error: {source}\n\nSuggestion: Rename the offending directory or move the project to a shorter path")]
+    WindowsPathLimit {
+        operation: String,
+        path: PathBuf,
+        limitation: String,
+        source: std::io::Error,
+    },
+
     /// Backup creation failed
     ///
     /// Critical error - operation was aborted to protect user data
@@ -79,6 +94,35 @@ pub enum ConfigError {
         details: String,
     },
 
+    /// Multi-file transaction failed part-way
+    ///
+    /// Lists the state every file of the transaction ended in
+    #[error("Transaction failed: {message}\n\nFile states:\n{}\n\nSuggestion: Fix the cause and retry; files listed as rolled back or unchanged are consistent.", crate::config::transaction::describe_states(files))]
+    TransactionFailed {
+        message: String,
+        files: Vec<(PathBuf, crate::config::transaction::FileState)>,
+    },
+
+    /// A written config did not read back as what was written
+    ///
+    /// Indicates a bug in serialization; the previous content is restored
+    #[error("Internal error: {path} did not read back as the configuration that was written\n\nDifferences:\n{differences}\n\n{recovery}\n\nSuggestion: This is a bug in ccm. Please file a bug report and include the differences above.")]
+    WriteVerificationFailed {
+        path: PathBuf,
+        differences: String,
+        recovery: String,
+    },
+
+    /// The file changed since the caller read it
+    ///
+    /// Raised by conditional writes so external edits aren't overwritten
+    #[error("Conflict: {path} changed since it was read (expected fingerprint {expected}, found {actual})\n\nSuggestion: Reload the configuration, reapply your changes and save again.")]
+    Conflict {
+        path: PathBuf,
+        expected: String,
+        actual: String,
+    },
+
     /// Generic error with context
     #[error("{0}")]
     Generic(String),
@@ -90,14 +134,33 @@ impl ConfigError {
         Self::NotFound { path: path.into() }
     }
 
-    /// Create an InvalidJson error
+    /// Create an InvalidFormat error for a JSON file
     pub fn invalid_json(
         path: impl Into<PathBuf>,
         line: usize,
         column: usize,
         message: impl Into<String>,
     ) -> Self {
-        Self::InvalidJson {
+        Self::invalid_format("JSON", path, line, column, message)
+    }
+
+    /// Create an InvalidFormat error
+    ///
+    /// # Arguments
+    /// * `format` - Name of the format, as shown to the user (e.g. `TOML`)
+    /// * `line` - 1-based error line
+    /// * `column` - 1-based error column
+    pub fn invalid_format(
+        format: impl Into<String>,
+        path: impl Into<PathBuf>,
+        line: usize,
+        column: usize,
+        message: impl Into<String>,
+    ) -> Self {
+        let format = format.into();
+        Self::InvalidFormat {
+            suggestion: format!("Check {format} syntax and ensure proper quoting"),
+            format,
             path: path.into(),
             line,
             column,
@@ -105,6 +168,24 @@ impl ConfigError {
         }
     }
 
+    /// Add to the suggestion of an InvalidFormat or ValidationFailed error
+    ///
+    /// The new suggestion goes on its own line after the existing one. Other
+    /// errors are returned unchanged.
+    pub fn with_suggestion(mut self, new_suggestion: impl Into<String>) -> Self {
+        if let Self::InvalidFormat { suggestion, .. } | Self::ValidationFailed { suggestion, .. } =
+            &mut self
+        {
+            let new_suggestion = new_suggestion.into();
+            *suggestion = if suggestion.is_empty() {
+                new_suggestion
+            } else {
+                format!("{suggestion}\n{new_suggestion}")
+            };
+        }
+        self
+    }
+
     /// Create a ValidationFailed error
     pub fn validation_failed(
         rule: impl Into<String>,
@@ -131,6 +212,21 @@ impl ConfigError {
         }
     }
 
+    /// Create a WindowsPathLimit error
+    pub fn windows_path_limit(
+        operation: impl Into<String>,
+        path: impl Into<PathBuf>,
+        limitation: impl Into<String>,
+        source: std::io::Error,
+    ) -> Self {
+        Self::WindowsPathLimit {
+            operation: operation.into(),
+            path: path.into(),
+            limitation: limitation.into(),
+            source,
+        }
+    }
+
     /// Create a BackupFailed error
     pub fn backup_failed(path: impl Into<PathBuf>, source: std::io::Error) -> Self {
         Self::BackupFailed {
@@ -139,6 +235,19 @@ impl ConfigError {
         }
     }
 
+    /// Kind of the underlying I/O error, for errors caused by one
+    ///
+    /// The `io::Error` itself is available through
+    /// [`source()`](std::error::Error::source).
+    pub fn io_error_kind(&self) -> Option<std::io::ErrorKind> {
+        match self {
+            Self::Filesystem { source, .. }
+            | Self::WindowsPathLimit { source, .. }
+            | Self::BackupFailed { source, .. } => Some(source.kind()),
+            _ => None,
+        }
+    }
+
     /// Create a PermissionDenied error
     pub fn permission_denied(operation: impl Into<String>, path: impl Into<PathBuf>) -> Self {
         Self::PermissionDenied {
@@ -147,6 +256,19 @@ impl ConfigError {
         }
     }
 
+    /// Create a Conflict error
+    pub fn conflict(
+        path: impl Into<PathBuf>,
+        expected: impl Into<String>,
+        actual: impl Into<String>,
+    ) -> Self {
+        Self::Conflict {
+            path: path.into(),
+            expected: expected.into(),
+            actual: actual.into(),
+        }
+    }
+
     /// Create an McpServerError
     pub fn mcp_server_error(
         server: impl Into<String>,
@@ -193,8 +315,15 @@ mod tests {
     fn test_invalid_json_error() {
         let error = ConfigError::invalid_json("/test/config.json", 10, 5, "Unexpected token");
         let message = format!("{error}");
+        assert!(message.starts_with("Invalid JSON in configuration file: /test/config.json"));
         assert!(message.contains("line 10"));
         assert!(message.contains("Unexpected token"));
+        assert!(message.contains("Suggestion: Check JSON syntax"));
+
+        let message = error.with_suggestion("Use double quotes").to_string();
+        assert!(message.contains("Unexpected token"));
+        assert!(message.contains("Suggestion: Check JSON syntax"));
+        assert!(message.ends_with("\nUse double quotes"));
     }
 
     #[test]
@@ -217,4 +346,28 @@ mod tests {
         assert!(message.contains("Operation aborted"));
         assert!(message.contains("protect your data"));
     }
+
+    #[test]
+    fn test_filesystem_error_keeps_io_source() {
+        use std::error::Error;
+        use std::io::ErrorKind;
+
+        let io_error = std::io::Error::new(ErrorKind::PermissionDenied, "Access denied");
+        let error = ConfigError::filesystem("write config", "/test/config.json", io_error);
+
+        assert_eq!(
+            error.to_string(),
+            "Filesystem error: write config failed for /test/config.json\n\nOS Error: Access denied\n\nSuggestion: Check file permissions and disk space"
+        );
+        assert_eq!(error.io_error_kind(), Some(ErrorKind::PermissionDenied));
+
+        let source = error
+            .source()
+            .and_then(|source| source.downcast_ref::<std::io::Error>())
+            .expect("the io::Error is the source");
+        assert_eq!(source.kind(), ErrorKind::PermissionDenied);
+
+        assert_eq!(ConfigError::not_found("/x").io_error_kind(), None);
+        assert!(ConfigError::not_found("/x").source().is_none());
+    }
 }
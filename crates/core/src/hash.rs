@@ -0,0 +1,123 @@
+//! Stable content hashes
+//!
+//! Hashes that end up in file names (state files, exports) must not change
+//! between Rust versions or platforms, so they use FNV-1a rather than the
+//! standard library's randomized hasher. They identify content; they are
+//! not meant to resist tampering.
+
+use std::io::{self, Write};
+
+/// Number of hex digits of a content hash used in file names
+pub const SHORT_HASH_LEN: usize = 6;
+
+/// FNV-1a offset basis (the hash of no bytes)
+const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+
+/// 64-bit FNV-1a hash (stable across Rust versions and platforms)
+pub fn fnv1a_hash(bytes: &[u8]) -> u64 {
+    fnv1a_update(OFFSET_BASIS, bytes)
+}
+
+/// Continue an FNV-1a hash with more bytes
+fn fnv1a_update(hash: u64, bytes: &[u8]) -> u64 {
+    const PRIME: u64 = 0x0100_0000_01b3;
+
+    bytes.iter().fold(hash, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(PRIME)
+    })
+}
+
+/// Content hash as 16 lowercase hex digits
+pub fn content_hash(bytes: &[u8]) -> String {
+    format!("{:016x}", fnv1a_hash(bytes))
+}
+
+/// The first [`SHORT_HASH_LEN`] digits of a content hash
+pub fn short_hash(hash: &str) -> &str {
+    hash.get(..SHORT_HASH_LEN).unwrap_or(hash)
+}
+
+/// Writer that computes the [`content_hash`] of everything written through it
+///
+/// Lets large content be hashed while it streams to its destination, without
+/// holding it in memory. Wrap [`io::sink`] to only hash.
+#[derive(Debug)]
+pub struct HashWriter<W> {
+    inner: W,
+    hash: u64,
+    len: u64,
+}
+
+impl<W: Write> HashWriter<W> {
+    /// Wrap a writer
+    pub fn new(inner: W) -> Self {
+        Self {
+            inner,
+            hash: OFFSET_BASIS,
+            len: 0,
+        }
+    }
+
+    /// Content hash of the bytes written so far
+    pub fn content_hash(&self) -> String {
+        format!("{:016x}", self.hash)
+    }
+
+    /// Number of bytes written so far
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// Whether nothing has been written yet
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The wrapped writer
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W: Write> Write for HashWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.hash = fnv1a_update(self.hash, &buf[..written]);
+        self.len += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // TDD Test 1: Hashes are stable and content-sensitive
+    #[test]
+    fn test_content_hash_is_stable() {
+        assert_eq!(content_hash(b""), "cbf29ce484222325");
+        assert_eq!(content_hash(b"a"), "af63dc4c8601ec8c");
+        assert_ne!(content_hash(b"{}"), content_hash(b"{ }"));
+        assert_eq!(short_hash("af63dc4c8601ec8c"), "af63dc");
+    }
+
+    // TDD Test 2: Streaming hashes match one-shot hashes
+    #[test]
+    fn test_hash_writer_matches_content_hash() {
+        let mut writer = HashWriter::new(Vec::new());
+        writer.write_all(b"{\"mcpServers\": ").unwrap();
+        writer.write_all(b"{}}").unwrap();
+
+        assert_eq!(writer.len(), 18);
+        assert_eq!(writer.content_hash(), content_hash(b"{\"mcpServers\": {}}"));
+        assert_eq!(writer.into_inner(), b"{\"mcpServers\": {}}");
+        assert_eq!(
+            HashWriter::new(io::sink()).content_hash(),
+            content_hash(b"")
+        );
+    }
+}
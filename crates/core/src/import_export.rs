@@ -3,9 +3,22 @@
 //! Provides functionality to export configurations to files
 //! and import configurations from files with validation.
 
-use crate::{config::ClaudeConfig, error::ConfigError, error::Result};
+use crate::{
+    config::{
+        embedded::toml_item_to_json,
+        json_hints,
+        limits::error_excerpt,
+        manager::{with_excerpt, with_hint},
+        ClaudeConfig,
+    },
+    error::ConfigError,
+    error::Result,
+    hash::{short_hash, HashWriter},
+    paths::{file_name_for, filesystem_error, to_extended_length, Platform},
+};
+use chrono::Utc;
 use std::fs;
-use std::io::Write;
+use std::io::{self, BufWriter, Read, Write};
 use std::path::{Path, PathBuf};
 
 /// Supported export formats
@@ -13,8 +26,10 @@ use std::path::{Path, PathBuf};
 pub enum ExportFormat {
     /// JSON format
     Json,
-    /// TOML format (future support)
+    /// TOML format (import only)
     Toml,
+    /// YAML format (import only)
+    Yaml,
 }
 
 impl ExportFormat {
@@ -23,6 +38,7 @@ impl ExportFormat {
         match self {
             ExportFormat::Json => "json",
             ExportFormat::Toml => "toml",
+            ExportFormat::Yaml => "yaml",
         }
     }
 
@@ -33,11 +49,22 @@ impl ExportFormat {
             .and_then(|ext| match ext {
                 "json" => Some(ExportFormat::Json),
                 "toml" => Some(ExportFormat::Toml),
+                "yaml" | "yml" => Some(ExportFormat::Yaml),
                 _ => None,
             })
     }
 }
 
+/// What an export does when the destination file already exists
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OverwritePolicy {
+    /// Fail unless the existing file is byte-identical to the export
+    #[default]
+    Refuse,
+    /// Replace the existing file
+    Force,
+}
+
 /// Import/export options
 #[derive(Debug, Clone)]
 pub struct ImportExportOptions {
@@ -52,6 +79,12 @@ pub struct ImportExportOptions {
 
     /// Pretty print JSON output
     pub pretty: bool,
+
+    /// Behavior when the export destination already exists
+    pub overwrite: OverwritePolicy,
+
+    /// Append the date and a short content hash to the export file name
+    pub auto_name: bool,
 }
 
 impl Default for ImportExportOptions {
@@ -61,68 +94,118 @@ impl Default for ImportExportOptions {
             validate: true,
             backup: true,
             pretty: true,
+            overwrite: OverwritePolicy::Refuse,
+            auto_name: false,
         }
     }
 }
 
+/// Result of an export
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExportOutcome {
+    /// File the configuration was exported to
+    pub path: PathBuf,
+
+    /// Content hash of the exported file (see [`content_hash`](crate::hash::content_hash))
+    pub content_hash: String,
+
+    /// Whether the file already had this exact content (nothing was written)
+    pub unchanged: bool,
+}
+
 /// Configuration importer/exporter
 pub struct ConfigImporter;
 
 impl ConfigImporter {
     /// Export configuration to a file
     ///
+    /// An existing file is only replaced with [`OverwritePolicy::Force`];
+    /// otherwise the export fails, unless the file already has exactly the
+    /// exported content. With `auto_name`, the date and a short content hash
+    /// are appended to the file name (`claude-config-20250601-a1b2c3.json`),
+    /// so exports of different configs never collide.
+    ///
     /// # Arguments
     /// * `config` - Configuration to export
-    /// * `path` - Destination file path
+    /// * `path` - Destination file path (a directory with `auto_name`)
     /// * `options` - Export options
     ///
+    /// # Returns
+    /// The exported file and its content hash
+    ///
     /// # Errors
     /// Returns an error if:
+    /// - The destination exists with different content (without `Force`)
     /// - File cannot be created
     /// - Serialization fails
     pub fn export_config(
         config: &ClaudeConfig,
         path: &Path,
         options: &ImportExportOptions,
-    ) -> Result<PathBuf> {
+    ) -> Result<ExportOutcome> {
+        // Serialize once without keeping the output to learn its hash, then
+        // again straight into the file, so large configs are never held in
+        // memory as text
+        let value = Self::render_value(config)?;
+        let mut hasher = HashWriter::new(io::sink());
+        Self::write_rendered(&value, &mut hasher, options, path)?;
+        let content_hash = hasher.content_hash();
+
+        let path = if options.auto_name {
+            auto_export_path(path, &content_hash, options.format)
+        } else {
+            path.to_path_buf()
+        };
+
+        // Reserved device names (e.g. `aux.json`) can't be created on Windows
+        let path = match path.file_name().and_then(|name| name.to_str()) {
+            Some(name) => path.with_file_name(file_name_for(Platform::current(), name)),
+            None => path,
+        };
+
         // Create parent directory if it doesn't exist
         if let Some(parent) = path.parent() {
-            if !parent.exists() {
-                fs::create_dir_all(parent)
-                    .map_err(|e| ConfigError::filesystem("create export directory", parent, e))?;
+            if !parent.as_os_str().is_empty() && !parent.exists() {
+                fs::create_dir_all(to_extended_length(parent))
+                    .map_err(|e| filesystem_error("create export directory", parent, &[], e))?;
             }
         }
 
-        // Serialize based on format
-        let content = match options.format {
-            ExportFormat::Json => {
-                if options.pretty {
-                    serde_json::to_string_pretty(config)
-                } else {
-                    serde_json::to_string(config)
-                }
+        if path.exists() {
+            if Self::has_content(&path, hasher.len(), &content_hash)? {
+                tracing::info!("Export already up to date: {}", path.display());
+                return Ok(ExportOutcome {
+                    path,
+                    content_hash,
+                    unchanged: true,
+                });
             }
-            ExportFormat::Toml => {
-                // TOML support can be added later with the toml crate
+            if options.overwrite == OverwritePolicy::Refuse {
                 return Err(ConfigError::validation_failed(
-                    "ExportFormat",
-                    "TOML format is not yet supported",
-                    "Use JSON format instead",
+                    "ExportOverwrite",
+                    format!("{} already exists with different content", path.display()),
+                    "Pass --force to overwrite it, or --auto-name to export to a new file",
                 ));
             }
         }
-        .map_err(|e| ConfigError::Generic(format!("Serialization failed: {e}")))?;
 
-        // Write to file
-        let mut file = fs::File::create(path)
-            .map_err(|e| ConfigError::filesystem("create export file", path, e))?;
+        // Stream to file
+        let file = fs::File::create(to_extended_length(&path))
+            .map_err(|e| filesystem_error("create export file", &path, &[], e))?;
+        let mut writer = BufWriter::new(file);
 
-        file.write_all(content.as_bytes())
-            .map_err(|e| ConfigError::filesystem("write export file", path, e))?;
+        Self::write_rendered(&value, &mut writer, options, &path)?;
+        writer
+            .flush()
+            .map_err(|e| filesystem_error("write export file", &path, &[], e))?;
 
         tracing::info!("Exported configuration to: {}", path.display());
 
-        Ok(path.to_path_buf())
+        Ok(ExportOutcome {
+            path,
+            content_hash,
+            unchanged: false,
+        })
     }
 
     /// Import configuration from a file
@@ -141,6 +224,26 @@ impl ConfigImporter {
     /// - Deserialization fails
     /// - Validation fails (if enabled)
     pub fn import_config(path: &Path, options: &ImportExportOptions) -> Result<ClaudeConfig> {
+        // Detect format from path if not specified
+        let format = ExportFormat::from_path(path).unwrap_or(options.format);
+        Self::import_file(path, format, options)
+    }
+
+    /// Import configuration from a file in `options.format`, whatever its
+    /// extension
+    ///
+    /// # Errors
+    /// Returns the same errors as [`import_config`](Self::import_config)
+    pub fn import_config_as(path: &Path, options: &ImportExportOptions) -> Result<ClaudeConfig> {
+        Self::import_file(path, options.format, options)
+    }
+
+    /// Read and parse an import file in `format`
+    fn import_file(
+        path: &Path,
+        format: ExportFormat,
+        options: &ImportExportOptions,
+    ) -> Result<ClaudeConfig> {
         // Check file exists
         if !path.exists() {
             return Err(ConfigError::not_found(path));
@@ -150,20 +253,161 @@ impl ConfigImporter {
         let content = fs::read_to_string(path)
             .map_err(|e| ConfigError::filesystem("read import file", path, e))?;
 
-        // Detect format from path if not specified
-        let format = ExportFormat::from_path(path).unwrap_or(options.format);
+        let config = Self::parse(&content, format, options, path)?;
 
-        // Deserialize based on format
-        let config = match format {
-            ExportFormat::Json => serde_json::from_str(&content)
-                .map_err(|e| ConfigError::Generic(format!("Failed to parse JSON: {e}")))?,
-            ExportFormat::Toml => {
+        tracing::info!("Imported configuration from: {}", path.display());
+
+        Ok(config)
+    }
+
+    /// Export configuration to a writer (e.g. stdout)
+    ///
+    /// The serialized configuration is followed by a newline. Nothing else
+    /// is written, so the output can be piped into other tools.
+    ///
+    /// # Arguments
+    /// * `config` - Configuration to export
+    /// * `writer` - Destination
+    /// * `options` - Export options (`overwrite` and `auto_name` don't apply)
+    ///
+    /// # Returns
+    /// Content hash of the serialized configuration
+    ///
+    /// # Errors
+    /// Returns an error if serialization or writing fails
+    pub fn export_to_writer<W: Write>(
+        config: &ClaudeConfig,
+        writer: &mut W,
+        options: &ImportExportOptions,
+    ) -> Result<String> {
+        let stdout = Path::new("<stdout>");
+        let mut hashing = HashWriter::new(&mut *writer);
+        Self::write_rendered(&Self::render_value(config)?, &mut hashing, options, stdout)?;
+        let content_hash = hashing.content_hash();
+
+        writer
+            .write_all(b"\n")
+            .and_then(|()| writer.flush())
+            .map_err(|e| ConfigError::filesystem("write export", stdout, e))?;
+        Ok(content_hash)
+    }
+
+    /// Import configuration from a reader (e.g. stdin), read to the end
+    ///
+    /// There is no file extension to go by, so `options.format` decides the
+    /// format.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - Reading fails
+    /// - Deserialization fails
+    /// - Validation fails (if enabled)
+    pub fn import_from_reader<R: Read>(
+        reader: &mut R,
+        options: &ImportExportOptions,
+    ) -> Result<ClaudeConfig> {
+        let mut content = String::new();
+        reader
+            .read_to_string(&mut content)
+            .map_err(|e| ConfigError::filesystem("read import", "<stdin>", e))?;
+
+        let config = Self::parse(&content, options.format, options, Path::new("<stdin>"))?;
+        tracing::info!("Imported configuration from standard input");
+        Ok(config)
+    }
+
+    /// Convert a configuration to a JSON value with sorted keys
+    ///
+    /// The config's maps iterate in random order; going through a `Value`
+    /// makes the exported bytes, and so the content hash, depend on the
+    /// content alone.
+    fn render_value(config: &ClaudeConfig) -> Result<serde_json::Value> {
+        serde_json::to_value(config)
+            .map_err(|e| ConfigError::Generic(format!("Serialization failed: {e}")))
+    }
+
+    /// Serialize a rendered configuration in the export format, streaming
+    /// to `writer`
+    ///
+    /// `target` names the destination in write errors.
+    fn write_rendered<W: Write>(
+        config: &serde_json::Value,
+        writer: &mut W,
+        options: &ImportExportOptions,
+        target: &Path,
+    ) -> Result<()> {
+        match options.format {
+            ExportFormat::Json => {
+                if options.pretty {
+                    serde_json::to_writer_pretty(writer, config)
+                } else {
+                    serde_json::to_writer(writer, config)
+                }
+            }
+            ExportFormat::Toml | ExportFormat::Yaml => {
                 return Err(ConfigError::validation_failed(
-                    "ImportFormat",
-                    "TOML format is not yet supported",
+                    "ExportFormat",
+                    format!(
+                        "Exporting to {} is not supported",
+                        options.format.extension().to_uppercase()
+                    ),
                     "Use JSON format instead",
                 ));
             }
+        }
+        .map_err(|e| {
+            if e.is_io() {
+                filesystem_error("write export", target, &[], e.into())
+            } else {
+                ConfigError::Generic(format!("Serialization failed: {e}"))
+            }
+        })
+    }
+
+    /// Whether the file at `path` has exactly `len` bytes hashing to `content_hash`
+    fn has_content(path: &Path, len: u64, content_hash: &str) -> Result<bool> {
+        let read_error = |e| filesystem_error("read existing export", path, &[], e);
+
+        let metadata = fs::metadata(to_extended_length(path)).map_err(read_error)?;
+        if metadata.len() != len {
+            return Ok(false);
+        }
+
+        let mut file = fs::File::open(to_extended_length(path)).map_err(read_error)?;
+        let mut hasher = HashWriter::new(io::sink());
+        io::copy(&mut file, &mut hasher).map_err(read_error)?;
+        Ok(hasher.content_hash() == content_hash)
+    }
+
+    /// Deserialize imported content and validate it if requested
+    ///
+    /// `source` names the imported file in parse errors.
+    fn parse(
+        content: &str,
+        format: ExportFormat,
+        options: &ImportExportOptions,
+        source: &Path,
+    ) -> Result<ClaudeConfig> {
+        // Deserialize based on format
+        let config = match format {
+            ExportFormat::Json => {
+                serde_json::from_str(content).map_err(|e| json_error(source, content, &e))?
+            }
+            ExportFormat::Toml => {
+                let doc: toml_edit::DocumentMut = content
+                    .parse()
+                    .map_err(|e| toml_error(source, content, &e))?;
+                let value = toml_item_to_json(doc.as_item()).unwrap_or_default();
+                serde_json::from_value(value)
+                    .map_err(|e| ConfigError::Generic(format!("Failed to parse TOML: {e}")))?
+            }
+            ExportFormat::Yaml => {
+                // Typed deserialization keeps the last of duplicate keys;
+                // parsing to a value first rejects them
+                serde_yaml::from_str::<serde_yaml::Value>(content)
+                    .map_err(|e| yaml_error(source, content, &e))?;
+                serde_yaml::from_str(content).map_err(|e| yaml_error(source, content, &e))?
+            }
         };
 
         // Validate if requested
@@ -171,8 +415,6 @@ impl ConfigImporter {
             crate::validate_config(&config)?;
         }
 
-        tracing::info!("Imported configuration from: {}", path.display());
-
         Ok(config)
     }
 
@@ -181,6 +423,7 @@ impl ConfigImporter {
     /// Convenience method for common export operations
     pub fn export(config: &ClaudeConfig, path: &Path) -> Result<PathBuf> {
         Self::export_config(config, path, &ImportExportOptions::default())
+            .map(|outcome| outcome.path)
     }
 
     /// Import configuration with default options
@@ -191,9 +434,152 @@ impl ConfigImporter {
     }
 }
 
+/// Export file name with the date and a short content hash appended
+///
+/// `out.json` becomes `out-20250601-a1b2c3.json`; an existing directory gets
+/// `claude-config-20250601-a1b2c3.json` inside it.
+fn auto_export_path(path: &Path, content_hash: &str, format: ExportFormat) -> PathBuf {
+    let (dir, stem) = if path.is_dir() {
+        (path, "claude-config")
+    } else {
+        (
+            path.parent().unwrap_or_else(|| Path::new("")),
+            path.file_stem()
+                .and_then(|stem| stem.to_str())
+                .unwrap_or("claude-config"),
+        )
+    };
+    let extension = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .filter(|_| !path.is_dir())
+        .unwrap_or(format.extension());
+
+    dir.join(format!(
+        "{stem}-{}-{}.{extension}",
+        Utc::now().format("%Y%m%d"),
+        short_hash(content_hash)
+    ))
+}
+
+/// Error for TOML content that doesn't parse
+///
+/// The parser reports a byte span; it is turned into the line and column
+/// shown to the user, with an excerpt of the offending line as for JSON.
+pub(crate) fn toml_error(path: &Path, content: &str, error: &toml_edit::TomlError) -> ConfigError {
+    let offset = error.span().map_or(0, |span| span.start);
+    let (line, column) = line_column(content, offset);
+    let excerpt = error_excerpt(content.as_bytes(), line, column);
+    let message = error.message().trim_end().to_string();
+
+    ConfigError::invalid_format("TOML", path, line, column, with_excerpt(message, excerpt))
+}
+
+/// Error for JSON content that doesn't parse
+///
+/// Same location, excerpt and hint as for a config file read from disk.
+fn json_error(path: &Path, content: &str, error: &serde_json::Error) -> ConfigError {
+    let (line, column) = (error.line(), error.column());
+    let excerpt = error_excerpt(content.as_bytes(), line, column);
+    let hint = json_hints::suggest_fix(content.as_bytes(), line, column);
+
+    with_hint(
+        ConfigError::invalid_json(path, line, column, with_excerpt(error.to_string(), excerpt)),
+        hint,
+    )
+}
+
+/// Error for YAML content that doesn't parse or doesn't match the config
+///
+/// The parser counts columns in characters; they are converted to bytes as
+/// for TOML, and shown with an excerpt of the offending line as for JSON.
+/// Errors without a location (e.g. an empty document) point at the start.
+fn yaml_error(path: &Path, content: &str, error: &serde_yaml::Error) -> ConfigError {
+    let (line, column) = error.location().map_or((1, 1), |location| {
+        let text = content.lines().nth(location.line() - 1).unwrap_or("");
+        let byte = text
+            .char_indices()
+            .nth(location.column() - 1)
+            .map_or(text.len(), |(index, _)| index);
+        (location.line(), byte + 1)
+    });
+
+    // The message repeats the location; it is shown separately
+    let message = error.to_string();
+    let message = match message.rfind(" at line ") {
+        Some(index) if error.location().is_some() => message[..index].to_string(),
+        _ => message,
+    };
+
+    // Duplicate keys are reported at the start of their mapping
+    let (line, column) = duplicate_yaml_key(&message)
+        .and_then(|key| duplicate_key_location(content, line, column, key))
+        .unwrap_or((line, column));
+    let excerpt = error_excerpt(content.as_bytes(), line, column);
+
+    ConfigError::invalid_format("YAML", path, line, column, with_excerpt(message, excerpt))
+}
+
+/// Key named by a YAML duplicate key error
+fn duplicate_yaml_key(message: &str) -> Option<&str> {
+    const PREFIX: &str = "duplicate entry with key \"";
+    let start = message.find(PREFIX)? + PREFIX.len();
+    let end = start + message[start..].find('"')?;
+    Some(&message[start..end])
+}
+
+/// Location of the second `key` of the block mapping starting at `line`
+/// and `column`
+///
+/// Only keys at the mapping's indentation are considered, so keys of
+/// nested mappings and flow mappings (`{a: 1, a: 2}`) are not found.
+fn duplicate_key_location(
+    content: &str,
+    line: usize,
+    column: usize,
+    key: &str,
+) -> Option<(usize, usize)> {
+    let indent = column - 1;
+    let mut seen = false;
+    for (index, text) in content.lines().enumerate().skip(line - 1) {
+        let rest = text.trim_start_matches(' ');
+        if rest.is_empty() || rest.starts_with('#') {
+            continue;
+        }
+        let depth = text.len() - rest.len();
+        if depth < indent && index >= line {
+            break;
+        }
+        if depth != indent {
+            continue;
+        }
+        let name = rest.split(':').next().unwrap_or("").trim_end();
+        if name.trim_matches(|c| c == '"' || c == '\'') == key {
+            if seen {
+                return Some((index + 1, column));
+            }
+            seen = true;
+        }
+    }
+    None
+}
+
+/// 1-based line and column (in bytes) of a byte offset in `content`
+fn line_column(content: &str, offset: usize) -> (usize, usize) {
+    let before = &content.as_bytes()[..offset.min(content.len())];
+    let line_start = before
+        .iter()
+        .rposition(|&byte| byte == b'\n')
+        .map_or(0, |newline| newline + 1);
+    let line = before.iter().filter(|&&byte| byte == b'\n').count() + 1;
+
+    (line, before.len() - line_start + 1)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::hash::content_hash;
     use crate::McpServer;
     use tempfile::TempDir;
 
@@ -208,6 +594,7 @@ mod tests {
         let json_path = PathBuf::from("/test/config.json");
         let toml_path = PathBuf::from("/test/config.toml");
         let txt_path = PathBuf::from("/test/config.txt");
+        let yaml_path = PathBuf::from("/test/config.yml");
 
         assert_eq!(
             ExportFormat::from_path(&json_path),
@@ -217,6 +604,10 @@ mod tests {
             ExportFormat::from_path(&toml_path),
             Some(ExportFormat::Toml)
         );
+        assert_eq!(
+            ExportFormat::from_path(&yaml_path),
+            Some(ExportFormat::Yaml)
+        );
         assert_eq!(ExportFormat::from_path(&txt_path), None);
     }
 
@@ -248,6 +639,47 @@ mod tests {
         assert_eq!(instructions[0], "Test instruction");
     }
 
+    #[test]
+    fn test_export_large_config_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("large.json");
+
+        let mut config = ClaudeConfig::new();
+        for i in 0..5_000 {
+            config = config.with_mcp_server(
+                format!("server-{i:05}"),
+                McpServer::new(
+                    format!("server-{i:05}"),
+                    "npx",
+                    vec!["-y".to_string(), format!("@example/mcp-{i}")],
+                ),
+            );
+        }
+        for i in 0..1_000 {
+            config = config.with_custom_instruction(format!("Instruction number {i}"));
+        }
+
+        let outcome =
+            ConfigImporter::export_config(&config, &path, &ImportExportOptions::default()).unwrap();
+        assert!(!outcome.unchanged);
+        assert_eq!(
+            outcome.content_hash,
+            content_hash(&fs::read(&path).unwrap())
+        );
+
+        let imported = ConfigImporter::import(&path).unwrap();
+        let servers = imported.mcp_servers.unwrap();
+        assert_eq!(servers.len(), 5_000);
+        assert_eq!(servers["server-04999"].args, ["-y", "@example/mcp-4999"]);
+        assert_eq!(imported.custom_instructions, config.custom_instructions);
+
+        // Re-exporting the same config streams to the same bytes
+        let again =
+            ConfigImporter::export_config(&config, &path, &ImportExportOptions::default()).unwrap();
+        assert!(again.unchanged);
+        assert_eq!(again.content_hash, outcome.content_hash);
+    }
+
     #[test]
     fn test_export_creates_directory() {
         let temp_dir = TempDir::new().unwrap();
@@ -265,6 +697,162 @@ mod tests {
         assert!(nested_path.parent().unwrap().exists());
     }
 
+    #[test]
+    fn test_export_reserved_file_name() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("aux.json");
+
+        let exported = ConfigImporter::export(&ClaudeConfig::new(), &path).unwrap();
+
+        // Renamed on Windows only; unchanged everywhere else
+        if cfg!(windows) {
+            assert_eq!(exported, temp_dir.path().join("aux_.json"));
+        } else {
+            assert_eq!(exported, path);
+        }
+        assert!(exported.exists());
+    }
+
+    #[test]
+    fn test_export_refuses_to_overwrite_different_content() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("out.json");
+        fs::write(&path, "keep me").unwrap();
+
+        let err = ConfigImporter::export(&ClaudeConfig::new(), &path).unwrap_err();
+
+        assert!(err.to_string().contains("already exists"));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "keep me");
+    }
+
+    #[test]
+    fn test_export_identical_content_passes_through() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("out.json");
+        let config = ClaudeConfig::new().with_allowed_path("~/projects");
+        let options = ImportExportOptions::default();
+
+        let first = ConfigImporter::export_config(&config, &path, &options).unwrap();
+        let second = ConfigImporter::export_config(&config, &path, &options).unwrap();
+
+        assert!(!first.unchanged);
+        assert!(second.unchanged);
+        assert_eq!(first.content_hash, second.content_hash);
+        assert_eq!(first.content_hash, content_hash(&fs::read(&path).unwrap()));
+    }
+
+    #[test]
+    fn test_export_force_overwrites() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("out.json");
+        fs::write(&path, "old export").unwrap();
+        let options = ImportExportOptions {
+            overwrite: OverwritePolicy::Force,
+            ..Default::default()
+        };
+
+        let outcome = ConfigImporter::export_config(&ClaudeConfig::new(), &path, &options).unwrap();
+
+        assert!(!outcome.unchanged);
+        assert!(ConfigImporter::import(&path).is_ok());
+    }
+
+    #[test]
+    fn test_export_is_independent_of_map_order() {
+        let temp_dir = TempDir::new().unwrap();
+        // Each config gets its own randomly seeded maps
+        let build = || {
+            (0..32).fold(ClaudeConfig::new(), |config, i| {
+                let name = format!("server-{i}");
+                let mut server = McpServer::new(&name, "npx", vec![]);
+                server.env = (0..8)
+                    .map(|j| (format!("VAR_{j}"), j.to_string()))
+                    .collect();
+                config.with_mcp_server(name, server)
+            })
+        };
+        let options = ImportExportOptions::default();
+
+        let first_path = temp_dir.path().join("first.json");
+        let second_path = temp_dir.path().join("second.json");
+        let first = ConfigImporter::export_config(&build(), &first_path, &options).unwrap();
+        let second = ConfigImporter::export_config(&build(), &second_path, &options).unwrap();
+
+        assert_eq!(first.content_hash, second.content_hash);
+        assert_eq!(
+            fs::read(&first_path).unwrap(),
+            fs::read(&second_path).unwrap()
+        );
+
+        // Exporting an equal config again is recognized as unchanged
+        let again = ConfigImporter::export_config(&build(), &first_path, &options).unwrap();
+        assert!(again.unchanged);
+
+        let mut stdout = Vec::new();
+        let hash = ConfigImporter::export_to_writer(&build(), &mut stdout, &options).unwrap();
+        assert_eq!(hash, first.content_hash);
+    }
+
+    #[test]
+    fn test_export_auto_name_is_unique_per_content() {
+        let temp_dir = TempDir::new().unwrap();
+        let options = ImportExportOptions {
+            auto_name: true,
+            ..Default::default()
+        };
+        let first = ClaudeConfig::new().with_allowed_path("~/a");
+        let second = ClaudeConfig::new().with_allowed_path("~/b");
+
+        let a = ConfigImporter::export_config(&first, temp_dir.path(), &options).unwrap();
+        let b = ConfigImporter::export_config(&second, temp_dir.path(), &options).unwrap();
+        let again = ConfigImporter::export_config(&first, temp_dir.path(), &options).unwrap();
+
+        assert_ne!(a.path, b.path);
+        assert_eq!(again.path, a.path);
+        assert!(again.unchanged);
+
+        let name = a.path.file_name().unwrap().to_str().unwrap();
+        let expected_suffix = format!("-{}.json", short_hash(&a.content_hash));
+        assert!(name.starts_with("claude-config-"), "{name}");
+        assert!(name.ends_with(&expected_suffix), "{name}");
+
+        let named = temp_dir.path().join("out.json");
+        let c = ConfigImporter::export_config(&first, &named, &options).unwrap();
+        assert!(c
+            .path
+            .file_name()
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .starts_with("out-"));
+        assert!(!named.exists());
+    }
+
+    #[test]
+    fn test_writer_reader_round_trip() {
+        let original_config = ClaudeConfig::new()
+            .with_mcp_server("test", McpServer::new("cmd", "cmd", vec!["-y".to_string()]))
+            .with_custom_instruction("Test instruction");
+        let options = ImportExportOptions::default();
+
+        let mut output = Vec::new();
+        let hash =
+            ConfigImporter::export_to_writer(&original_config, &mut output, &options).unwrap();
+        assert!(output.ends_with(b"}\n"));
+        assert_eq!(hash, content_hash(&output[..output.len() - 1]));
+
+        let imported =
+            ConfigImporter::import_from_reader(&mut output.as_slice(), &options).unwrap();
+        assert_eq!(imported.mcp_servers.unwrap()["test"].args, ["-y"]);
+        assert_eq!(
+            imported.custom_instructions,
+            original_config.custom_instructions
+        );
+
+        let err = ConfigImporter::import_from_reader(&mut "{ oops".as_bytes(), &options);
+        assert!(err.is_err());
+    }
+
     #[test]
     fn test_import_nonexistent_file() {
         let temp_dir = TempDir::new().unwrap();
@@ -286,6 +874,172 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_import_toml() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("config.toml");
+        fs::write(
+            &path,
+            "customInstructions = [\"Be concise\"]\n\n\
+             [mcpServers.fs]\ncommand = \"npx\"\nargs = [\"-y\"]\nenabled = true\n",
+        )
+        .unwrap();
+
+        let config = ConfigImporter::import(&path).unwrap();
+
+        assert_eq!(
+            config.custom_instructions,
+            Some(vec!["Be concise".to_string()])
+        );
+        let server = &config.mcp_servers.unwrap()["fs"];
+        assert_eq!(server.command.as_deref(), Some("npx"));
+        assert!(server.enabled);
+    }
+
+    #[test]
+    fn test_import_toml_errors_report_location() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("config.toml");
+
+        // Duplicate key on line 3
+        fs::write(
+            &path,
+            "[mcpServers.fs]\ncommand = \"npx\"\ncommand = \"uvx\"\nenabled = true\n",
+        )
+        .unwrap();
+        let Err(ConfigError::InvalidFormat {
+            format,
+            line,
+            column,
+            message,
+            ..
+        }) = ConfigImporter::import(&path)
+        else {
+            panic!("expected a TOML format error");
+        };
+        assert_eq!(format, "TOML");
+        assert_eq!((line, column), (3, 1));
+        assert!(message.contains("duplicate key"));
+        assert!(message.ends_with("command = \"uvx\"\n^"));
+
+        // Invalid datetime on line 2
+        fs::write(&path, "# note\nupdated = 1979-05-27T25:00:00Z\n").unwrap();
+        let error = ConfigImporter::import(&path).unwrap_err();
+        let ConfigError::InvalidFormat { line, column, .. } = error else {
+            panic!("expected a TOML format error");
+        };
+        assert_eq!(line, 2);
+        assert!(column > "updated = ".len());
+        assert!(error.to_string().starts_with(&format!(
+            "Invalid TOML in configuration file: {}",
+            path.display()
+        )));
+    }
+
+    #[test]
+    fn test_import_yaml() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("config.yml");
+        fs::write(
+            &path,
+            "customInstructions:\n  - Be concise\nmcpServers:\n  fs:\n    command: npx\n    args: [\"-y\"]\n    enabled: true\n",
+        )
+        .unwrap();
+
+        let config = ConfigImporter::import(&path).unwrap();
+
+        assert_eq!(
+            config.custom_instructions,
+            Some(vec!["Be concise".to_string()])
+        );
+        let server = &config.mcp_servers.unwrap()["fs"];
+        assert_eq!(server.command.as_deref(), Some("npx"));
+        assert_eq!(server.args, ["-y"]);
+    }
+
+    #[test]
+    fn test_import_yaml_errors_report_location() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("config.yaml");
+
+        // Tab indentation on line 3
+        fs::write(&path, "mcpServers:\n  fs:\n\tcommand: npx\n").unwrap();
+        let error = ConfigImporter::import(&path).unwrap_err();
+        let ConfigError::InvalidFormat {
+            format,
+            line,
+            column,
+            message,
+            ..
+        } = &error
+        else {
+            panic!("expected a YAML format error");
+        };
+        assert_eq!(format, "YAML");
+        assert_eq!((*line, *column), (3, 1));
+        assert!(message.ends_with(" command: npx\n^"), "{message}");
+        assert!(error.to_string().starts_with(&format!(
+            "Invalid YAML in configuration file: {}",
+            path.display()
+        )));
+
+        // Duplicate map key on line 3
+        fs::write(
+            &path,
+            "mcpServers:\n  fs: {command: npx, enabled: true}\n  fs: {command: uvx, enabled: true}\n",
+        )
+        .unwrap();
+        let Err(ConfigError::InvalidFormat {
+            line,
+            column,
+            message,
+            ..
+        }) = ConfigImporter::import(&path)
+        else {
+            panic!("expected a YAML format error");
+        };
+        assert_eq!((line, column), (3, 3), "{message}");
+        assert!(
+            message.contains(r#"duplicate entry with key "fs""#),
+            "{message}"
+        );
+        assert!(
+            message.ends_with("  fs: {command: uvx, enabled: true}\n  ^"),
+            "{message}"
+        );
+
+        fs::write(
+            &path,
+            "customInstructions: [a]\nallowedPaths: []\ncustomInstructions: [b]\n",
+        )
+        .unwrap();
+        let Err(ConfigError::InvalidFormat { line, column, .. }) = ConfigImporter::import(&path)
+        else {
+            panic!("expected a YAML format error");
+        };
+        assert_eq!((line, column), (3, 1));
+    }
+
+    #[test]
+    fn test_import_json_errors_report_location() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("config.json");
+        fs::write(&path, "{\n  \"allowedPaths\": [\"~/a\",]\n}\n").unwrap();
+
+        let Err(ConfigError::InvalidFormat {
+            format,
+            line,
+            message,
+            ..
+        }) = ConfigImporter::import(&path)
+        else {
+            panic!("expected a JSON format error");
+        };
+        assert_eq!(format, "JSON");
+        assert_eq!(line, 2);
+        assert!(message.contains(r#""allowedPaths": ["~/a",]"#), "{message}");
+    }
+
     #[test]
     fn test_options_default() {
         let options = ImportExportOptions::default();
@@ -4,33 +4,87 @@
 //! It is designed to be frontend-agnostic and can be used by CLI, GUI, or other interfaces.
 
 // Public modules
+pub mod analysis;
+pub mod archive;
 pub mod backup;
+pub mod bundle;
+pub mod collate;
 pub mod config;
+pub mod daemon;
+pub mod doctor;
 pub mod error;
+pub mod hash;
 pub mod import_export;
 pub mod mcp;
+pub mod multi;
 pub mod paths;
 pub mod project;
+pub mod redact;
 pub mod search;
+pub mod settings;
+pub mod state;
+pub mod support;
+pub mod timings;
 pub mod types;
+pub mod usage;
+pub mod wait;
 
 // Validation is part of config module
-pub use config::validation::validate_config;
+pub use config::validation::{
+    blank_value_warnings, env_key_warnings, platform_warnings, validate_config,
+    validate_config_with_skill_schemas, validate_env_key, SkillSchemaRegistry,
+};
 
 // Private modules (will be added as we implement features)
 // mod skills;
 // mod project;
 
 // Re-exports for convenience
+pub use analysis::{
+    find_shadows, server_references, unreferenced_servers, Reference, ShadowReport, ShadowState,
+};
 pub use backup::BackupManager;
-pub use config::{manager::ConfigManager, merge::merge_configs, ClaudeConfig};
+pub use bundle::{export_all, BundleSource, CancellationToken, ExportAllOutcome};
+pub use config::{
+    edit::EditSession,
+    init::{create_project_config, save_profile, ConfigSource, CreatedConfig},
+    manager::{ConfigManager, KeyRestore, RestoreKeysOptions, WriteOptions},
+    merge::{
+        merge_all_with_sources, merge_configs, merge_configs_with_options, merge_notes, MergeNote,
+        MergeOptions,
+    },
+    schema::{validate_against_schema, validate_against_schema_version, SchemaViolation},
+    transaction::{FileState, Transaction},
+    ClaudeConfig,
+};
+pub use doctor::{CheckStatus, DoctorCheck, DoctorOptions, DoctorReport};
 pub use error::{ConfigError, Result};
-pub use import_export::{ConfigImporter, ExportFormat, ImportExportOptions};
+pub use import_export::{
+    ConfigImporter, ExportFormat, ExportOutcome, ImportExportOptions, OverwritePolicy,
+};
 pub use mcp::McpManager;
-pub use paths::{expand_tilde, find_project_config, get_global_config_dir, get_global_config_path};
-pub use project::{ProjectInfo, ProjectScanner};
-pub use search::{ConfigSearcher, SearchOptions, SearchResult, ValueType};
+pub use multi::MultiResult;
+pub use paths::{
+    expand_env, expand_tilde, find_project_config, find_project_config_files,
+    get_daemon_socket_path, get_global_config_dir, get_global_config_path, project_config_path,
+    resolve_config_dir, EnvReader, Platform,
+};
+pub use project::{
+    is_project_archived, DirListing, ProjectInfo, ProjectRegistry, ProjectScanner, PruneReason,
+    RegistryEntry, ScanIter, ScanState, ScanStats,
+};
+pub use redact::Redactor;
+pub use search::{
+    ClauseMatch, ClauseTarget, ConfigSearcher, MatchMode, QueryClause, QueryMatch, SearchOptions,
+    SearchResult, ValueType,
+};
+pub use settings::CcmSettings;
+pub use state::StateStore;
+pub use support::{BundleContents, SupportOptions};
+pub use timings::{TimingEntry, Timings};
 pub use types::*;
+pub use usage::{UsageSummary, UsageTracker};
+pub use wait::{wait_for, wait_for_with, Poller, SystemPoller, WaitOptions, WaitOutcome};
 
 /// Version information
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -0,0 +1,162 @@
+//! Operation log for MCP server changes
+//!
+//! Backups show what a config looked like, but not who changed which server
+//! and when. When enabled, [`McpManager`](super::McpManager) appends one JSON
+//! object per change to an operation log (`<config_dir>/ccm-ops.log` by
+//! default). The log is append-only; ccm never rewrites or truncates it.
+//!
+//! Environment values are never logged, only variable names.
+
+use crate::{
+    error::{ConfigError, Result},
+    types::{ConfigScope, McpServer},
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Kind of MCP change
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum McpOp {
+    /// A server was added
+    Add,
+    /// A server was removed
+    Remove,
+    /// A server was enabled
+    Enable,
+    /// A server was disabled
+    Disable,
+    /// Environment variables of a server were set or removed
+    Env,
+}
+
+/// One line of the operation log
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    /// When the change was written
+    pub timestamp: DateTime<Utc>,
+
+    /// What was done
+    pub op: McpOp,
+
+    /// Which config was changed
+    pub scope: ConfigScope,
+
+    /// Server name
+    pub server: String,
+
+    /// Operation details (config file, user, server definition)
+    pub details: Value,
+}
+
+/// Append-only JSONL log of MCP changes
+#[derive(Debug, Clone)]
+pub struct AuditLog {
+    /// Log file
+    path: PathBuf,
+}
+
+impl AuditLog {
+    /// Create a log writing to `path` (created on first write)
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Log file path
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append an entry as one JSON line
+    pub fn append(&self, entry: &AuditEntry) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            if !parent.as_os_str().is_empty() {
+                fs::create_dir_all(parent)
+                    .map_err(|e| ConfigError::filesystem("create log directory", parent, e))?;
+            }
+        }
+
+        let mut line = serde_json::to_string(entry)?;
+        line.push('\n');
+
+        // A single write per entry keeps lines intact when processes interleave
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .and_then(|mut file| file.write_all(line.as_bytes()))
+            .map_err(|e| ConfigError::filesystem("append to operation log", &self.path, e))
+    }
+
+    /// Read all entries (empty if the log doesn't exist)
+    ///
+    /// # Errors
+    /// Returns an error if the log cannot be read or a line is not a valid entry
+    pub fn entries(&self) -> Result<Vec<AuditEntry>> {
+        let content = match fs::read_to_string(&self.path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(ConfigError::filesystem("read operation log", &self.path, e)),
+        };
+
+        content
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(index, line)| {
+                serde_json::from_str(line).map_err(|e| {
+                    ConfigError::invalid_json(&self.path, index + 1, e.column(), e.to_string())
+                })
+            })
+            .collect()
+    }
+}
+
+impl AuditEntry {
+    /// Create an entry timestamped now
+    ///
+    /// # Arguments
+    /// * `op` - What was done
+    /// * `scope` - Which config was changed
+    /// * `server` - Server name
+    /// * `config_path` - Config file that was written
+    /// * `server_def` - Server definition, for adds, removes and env changes
+    pub fn new(
+        op: McpOp,
+        scope: ConfigScope,
+        server: &str,
+        config_path: &Path,
+        server_def: Option<&McpServer>,
+    ) -> Self {
+        let mut details = json!({
+            "config": config_path.to_string_lossy(),
+            "user": current_user(),
+        });
+        if let Some(def) = server_def {
+            let mut env_keys: Vec<&String> = def.env.keys().collect();
+            env_keys.sort();
+            details["command"] = json!(def.command);
+            details["args"] = json!(def.args);
+            details["envKeys"] = json!(env_keys);
+        }
+
+        Self {
+            timestamp: Utc::now(),
+            op,
+            scope,
+            server: server.to_string(),
+            details,
+        }
+    }
+}
+
+/// Name of the user running ccm, if known
+fn current_user() -> Option<String> {
+    ["USER", "USERNAME"]
+        .iter()
+        .find_map(|var| std::env::var(var).ok().filter(|user| !user.is_empty()))
+}
@@ -3,13 +3,23 @@
 //! This module provides functionality for managing MCP (Model Context Protocol) servers
 //! in Claude Code configuration files.
 
+use super::audit::{AuditEntry, AuditLog, McpOp};
+use super::snapshot::{EnabledRestore, EnabledSnapshot};
 use crate::{
+    backup::RiskyOperation,
+    collate,
+    config::{
+        manager::{atomic_write, read_config_file, WriteOptions},
+        transaction::Transaction,
+    },
     error::{ConfigError, Result},
-    paths::get_global_config_path,
+    paths::get_audit_log_path,
+    redact::{restore_redacted, Redactor},
+    timings::Timings,
     types::{ConfigScope, McpServer},
-    ConfigManager,
+    validate_env_key, ConfigManager,
 };
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::path::{Path, PathBuf};
 
 /// MCP Server Manager
@@ -20,8 +30,8 @@ use std::path::{Path, PathBuf};
 pub struct McpManager {
     /// Configuration manager for reading/writing configs
     config_manager: ConfigManager,
-    /// Optional custom global config path (for testing)
-    custom_global_config: Option<PathBuf>,
+    /// Operation log for server changes (disabled by default)
+    audit_log: Option<AuditLog>,
 }
 
 impl McpManager {
@@ -32,7 +42,49 @@ impl McpManager {
     pub fn new(backup_dir: impl Into<PathBuf>) -> Self {
         Self {
             config_manager: ConfigManager::new(backup_dir),
-            custom_global_config: None,
+            audit_log: None,
+        }
+    }
+
+    /// Record operation timings in `timings`
+    pub fn with_timings(mut self, timings: Timings) -> Self {
+        self.config_manager = self.config_manager.with_timings(timings);
+        self
+    }
+
+    /// Keep only the newest `retention` backups of each written config
+    ///
+    /// See [`ConfigManager::with_backup_retention`].
+    pub fn with_backup_retention(mut self, retention: Option<usize>) -> Self {
+        self.config_manager = self.config_manager.with_backup_retention(retention);
+        self
+    }
+
+    /// Append an entry to the operation log at `path` for every server change
+    ///
+    /// See [`audit`](super::audit) for the log format.
+    pub fn with_audit_log(mut self, path: impl Into<PathBuf>) -> Self {
+        self.audit_log = Some(AuditLog::new(path));
+        self
+    }
+
+    /// Enable the operation log at `<config_dir>/ccm-ops.log` if the ccm
+    /// settings in the global config turn it on (`"auditLog": true`)
+    pub fn with_configured_audit_log(self) -> Self {
+        let enabled = self
+            .config_manager
+            .config_path_for_scope(&ConfigScope::Global, None)
+            .is_ok_and(|global_path| {
+                global_path.exists()
+                    && read_config_file(&global_path)
+                        .map(|config| config.ccm_settings().audit_log)
+                        .unwrap_or(false)
+            });
+
+        if enabled {
+            self.with_audit_log(get_audit_log_path())
+        } else {
+            self
         }
     }
 
@@ -47,8 +99,11 @@ impl McpManager {
         custom_global_config: impl Into<PathBuf>,
     ) -> Self {
         Self {
-            config_manager: ConfigManager::new(backup_dir),
-            custom_global_config: Some(custom_global_config.into()),
+            config_manager: ConfigManager::with_custom_global_config(
+                backup_dir,
+                custom_global_config,
+            ),
+            audit_log: None,
         }
     }
 
@@ -85,6 +140,9 @@ impl McpManager {
     /// * `scope` - Configuration scope
     /// * `project_path` - Project path (required if scope is Project)
     ///
+    /// # Returns
+    /// `true` if the config changed, `false` if the server was already enabled
+    ///
     /// # Errors
     /// Returns an error if:
     /// - Server doesn't exist
@@ -94,7 +152,7 @@ impl McpManager {
         name: &str,
         scope: &ConfigScope,
         project_path: Option<&Path>,
-    ) -> Result<()> {
+    ) -> Result<bool> {
         self.set_server_enabled(name, true, scope, project_path)
     }
 
@@ -107,6 +165,9 @@ impl McpManager {
     /// * `scope` - Configuration scope
     /// * `project_path` - Project path (required if scope is Project)
     ///
+    /// # Returns
+    /// `true` if the config changed, `false` if the server was already disabled
+    ///
     /// # Errors
     /// Returns an error if:
     /// - Server doesn't exist
@@ -116,7 +177,7 @@ impl McpManager {
         name: &str,
         scope: &ConfigScope,
         project_path: Option<&Path>,
-    ) -> Result<()> {
+    ) -> Result<bool> {
         self.set_server_enabled(name, false, scope, project_path)
     }
 
@@ -129,7 +190,7 @@ impl McpManager {
         enabled: bool,
         scope: &ConfigScope,
         project_path: Option<&Path>,
-    ) -> Result<()> {
+    ) -> Result<bool> {
         let (mut config, config_path) = self.read_config_for_scope(scope, project_path)?;
 
         // Check if server exists
@@ -137,13 +198,7 @@ impl McpManager {
             ConfigError::Generic("No MCP servers configured. Use 'add' command first.".to_string())
         })?;
 
-        if !servers.contains_key(name) {
-            return Err(ConfigError::Generic(format!(
-                "MCP server '{}' not found. Available servers: {}",
-                name,
-                servers.keys().cloned().collect::<Vec<_>>().join(", ")
-            )));
-        }
+        let name = &find_server_key(servers, name).ok_or_else(|| not_found(name, servers))?;
 
         // Update enabled status
         if let Some(servers) = config.mcp_servers.as_mut() {
@@ -152,22 +207,35 @@ impl McpManager {
             }
         }
 
-        // Write back
-        self.config_manager
-            .write_config_with_backup(&config_path, &config)?;
+        // Write back (skipped if the server already had that status)
+        let changed = self.config_manager.write_config_with_options(
+            &config_path,
+            &config,
+            WriteOptions::default(),
+        )?;
+        if !changed {
+            return Ok(false);
+        }
 
         tracing::info!(
             "MCP server '{}' {}",
             name,
             if enabled { "enabled" } else { "disabled" }
         );
+        let op = if enabled {
+            McpOp::Enable
+        } else {
+            McpOp::Disable
+        };
+        self.log_operation(op, scope, name, &config_path, None);
 
-        Ok(())
+        Ok(true)
     }
 
     /// Add a new MCP server
     ///
-    /// Adds a server configuration at the specified scope.
+    /// Adds a server configuration at the specified scope. Adding a server
+    /// that already exists with the same definition is a no-op.
     ///
     /// # Arguments
     /// * `name` - Server name (will be used as HashMap key)
@@ -175,10 +243,13 @@ impl McpManager {
     /// * `scope` - Configuration scope
     /// * `project_path` - Project path (required if scope is Project)
     ///
+    /// # Returns
+    /// `true` if the server was added, `false` if it was already configured
+    ///
     /// # Errors
     /// Returns an error if:
     /// - Server name is empty
-    /// - Server with same name already exists
+    /// - Server with same name but a different definition already exists
     /// - Config file cannot be read/written
     pub fn add_server(
         &self,
@@ -186,7 +257,7 @@ impl McpManager {
         mut server: McpServer,
         scope: &ConfigScope,
         project_path: Option<&Path>,
-    ) -> Result<()> {
+    ) -> Result<bool> {
         let name = name.trim();
 
         if name.is_empty() {
@@ -209,9 +280,13 @@ impl McpManager {
 
         // Check if server already exists
         let servers = config.mcp_servers.as_mut().unwrap();
-        if servers.contains_key(name) {
+        if let Some(existing_name) = find_server_key(servers, name) {
+            if servers[&existing_name].semantically_equal(&server) {
+                tracing::info!("MCP server '{}' already configured", existing_name);
+                return Ok(false);
+            }
             return Err(ConfigError::Generic(format!(
-                "MCP server '{name}' already exists. Use 'remove' command first or 'set' to modify."
+                "MCP server '{existing_name}' already exists. Use 'remove' command first or 'set' to modify."
             )));
         }
 
@@ -223,8 +298,10 @@ impl McpManager {
             .write_config_with_backup(&config_path, &config)?;
 
         tracing::info!("MCP server '{}' added", name);
+        let added = config.mcp_servers.as_ref().and_then(|s| s.get(name));
+        self.log_operation(McpOp::Add, scope, name, &config_path, added);
 
-        Ok(())
+        Ok(true)
     }
 
     /// Remove an MCP server
@@ -256,16 +333,10 @@ impl McpManager {
         })?;
 
         // Check if server exists
-        if !servers.contains_key(name) {
-            return Err(ConfigError::Generic(format!(
-                "MCP server '{}' not found. Available servers: {}",
-                name,
-                servers.keys().cloned().collect::<Vec<_>>().join(", ")
-            )));
-        }
+        let name = &find_server_key(servers, name).ok_or_else(|| not_found(name, servers))?;
 
         // Remove server
-        servers.remove(name);
+        let removed = servers.remove(name);
 
         // Clean up empty HashMap
         if servers.is_empty() {
@@ -277,178 +348,777 @@ impl McpManager {
             .write_config_with_backup(&config_path, &config)?;
 
         tracing::info!("MCP server '{}' removed", name);
+        self.log_operation(McpOp::Remove, scope, name, &config_path, removed.as_ref());
 
         Ok(())
     }
 
-    /// Get detailed information about a specific server
+    /// Move an MCP server from one scope to another
+    ///
+    /// Both configs are written in one [`Transaction`]: either the server
+    /// ends up in the target scope and is gone from the source, or neither
+    /// config changes.
     ///
     /// # Arguments
     /// * `name` - Server name
+    /// * `from` - Scope the server is currently configured in
+    /// * `to` - Scope to move it to
+    /// * `project_path` - Project path (required if either scope is Project)
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - Both scopes are the same
+    /// - Server doesn't exist in `from`, or a server of that name exists in `to`
+    /// - Either config cannot be read/written (nothing is changed)
+    pub fn move_server(
+        &self,
+        name: &str,
+        from: &ConfigScope,
+        to: &ConfigScope,
+        project_path: Option<&Path>,
+    ) -> Result<()> {
+        if from == to {
+            return Err(ConfigError::validation_failed(
+                "Move requires two scopes",
+                format!("source and target are both {}", from.display_name()),
+                "Use different --from and --to scopes",
+            ));
+        }
+
+        let (mut source, source_path) = self.read_config_for_scope(from, project_path)?;
+        let (mut target, target_path) = self.read_config_for_scope(to, project_path)?;
+
+        let servers = source.mcp_servers.as_mut();
+        let key = servers
+            .as_deref()
+            .and_then(|servers| find_server_key(servers, name));
+        let name = key.as_deref().unwrap_or(name);
+        let Some(server) = servers.and_then(|servers| servers.remove(name)) else {
+            return Err(ConfigError::Generic(format!(
+                "MCP server '{name}' not found in {} config",
+                from.display_name()
+            )));
+        };
+        if source.mcp_servers.as_ref().is_some_and(HashMap::is_empty) {
+            source.mcp_servers = None;
+        }
+
+        let target_servers = target.mcp_servers.get_or_insert_with(HashMap::new);
+        if let Some(existing) = find_server_key(target_servers, name) {
+            return Err(ConfigError::Generic(format!(
+                "MCP server '{existing}' already exists in {} config. Remove it there first.",
+                to.display_name()
+            )));
+        }
+        target_servers.insert(name.to_string(), server.clone());
+
+        Transaction::new()
+            .stage(&source_path, source)
+            .stage(&target_path, target)
+            .commit(&self.config_manager)?;
+
+        tracing::info!(
+            "MCP server '{}' moved from {} to {}",
+            name,
+            from.display_name(),
+            to.display_name()
+        );
+        self.log_operation(McpOp::Remove, from, name, &source_path, Some(&server));
+        self.log_operation(McpOp::Add, to, name, &target_path, Some(&server));
+
+        Ok(())
+    }
+
+    /// Disable every MCP server of a scope except `except`, in one write
+    ///
+    /// The enabled flags from before the change are saved as an
+    /// [`EnabledSnapshot`] in the backup directory, so
+    /// [`restore_enabled`](Self::restore_enabled) can undo it. If all other
+    /// servers are already disabled, nothing is written or saved.
+    ///
+    /// # Arguments
+    /// * `except` - Servers to leave as they are
     /// * `scope` - Configuration scope
     /// * `project_path` - Project path (required if scope is Project)
     ///
     /// # Returns
-    /// The server configuration if found
+    /// The flags before the change; the snapshot's `path` is `None` if
+    /// nothing changed
     ///
     /// # Errors
     /// Returns an error if:
-    /// - Server doesn't exist
-    /// - Config file cannot be read
-    pub fn get_server(
+    /// - A server in `except` doesn't exist
+    /// - The snapshot or the config cannot be written (a saved snapshot is
+    ///   removed again if the config write fails)
+    pub fn disable_all(
         &self,
-        name: &str,
+        except: &[String],
         scope: &ConfigScope,
         project_path: Option<&Path>,
-    ) -> Result<McpServer> {
-        let mut servers = self.list_servers(scope, project_path)?;
+    ) -> Result<EnabledSnapshot> {
+        let (mut config, config_path) = self.read_config_for_scope(scope, project_path)?;
+        let mut servers = config.mcp_servers.take().unwrap_or_default();
+
+        let kept = except
+            .iter()
+            .map(|name| find_server_key(&servers, name).ok_or_else(|| not_found(name, &servers)))
+            .collect::<Result<HashSet<_>>>()?;
+
+        let mut snapshot = EnabledSnapshot::capture(&servers, *scope, &config_path);
+        let mut disabled = Vec::new();
+        for (name, server) in servers.iter_mut() {
+            if server.enabled && !kept.contains(name) {
+                server.enabled = false;
+                disabled.push(name.clone());
+            }
+        }
+        if disabled.is_empty() {
+            return Ok(snapshot);
+        }
+        config.mcp_servers = Some(servers);
+
+        snapshot.save(self.config_manager.backup_manager().backup_dir())?;
+        if let Err(e) = self.config_manager.write_config_with_options(
+            &config_path,
+            &config,
+            WriteOptions::default().with_risk(RiskyOperation::DisableAll),
+        ) {
+            if let Some(path) = &snapshot.path {
+                let _ = std::fs::remove_file(path);
+            }
+            return Err(e);
+        }
 
-        servers.remove(name).ok_or_else(|| {
-            ConfigError::Generic(format!(
-                "MCP server '{}' not found. Available servers: {}",
-                name,
-                servers.keys().cloned().collect::<Vec<_>>().join(", ")
-            ))
-        })
+        collate::sort(&mut disabled);
+        tracing::info!("Disabled MCP servers: {}", disabled.join(", "));
+        for name in &disabled {
+            self.log_operation(McpOp::Disable, scope, name, &config_path, None);
+        }
+
+        Ok(snapshot)
     }
 
-    /// Read configuration for the specified scope
+    /// Most recent snapshot saved by [`disable_all`](Self::disable_all) for a scope's config
     ///
-    /// Internal helper that returns both the config and its file path.
-    fn read_config_for_scope(
+    /// # Errors
+    /// Returns an error if the config path cannot be determined or the
+    /// snapshot folder cannot be read
+    pub fn latest_enabled_snapshot(
         &self,
         scope: &ConfigScope,
         project_path: Option<&Path>,
-    ) -> Result<(crate::ClaudeConfig, PathBuf)> {
-        self.read_config_for_scope_with_path(scope, project_path, None)
+    ) -> Result<Option<EnabledSnapshot>> {
+        let config_path = self
+            .config_manager
+            .config_path_for_scope(scope, project_path)?;
+        EnabledSnapshot::latest(
+            self.config_manager.backup_manager().backup_dir(),
+            &config_path,
+        )
     }
 
-    /// Read configuration with optional custom config path (for testing)
-    fn read_config_for_scope_with_path(
+    /// Put back the enabled flags recorded in a snapshot
+    ///
+    /// Only servers in the snapshot are touched: servers added since keep
+    /// their flags, and servers removed since are skipped. The config is
+    /// written once, and only if a flag changes.
+    ///
+    /// # Arguments
+    /// * `snapshot` - Flags to restore
+    /// * `scope` - Configuration scope
+    /// * `project_path` - Project path (required if scope is Project)
+    ///
+    /// # Returns
+    /// Which servers were restored, already matched, or no longer exist
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - The snapshot was taken from a different config file
+    /// - Config file cannot be read/written
+    pub fn restore_enabled(
         &self,
+        snapshot: &EnabledSnapshot,
         scope: &ConfigScope,
         project_path: Option<&Path>,
-        custom_config_path: Option<&Path>,
-    ) -> Result<(crate::ClaudeConfig, PathBuf)> {
-        let config_path = if let Some(custom) = custom_config_path {
-            custom.to_path_buf()
-        } else {
-            match scope {
-                ConfigScope::Global => {
-                    // Use custom global config if available (for testing), otherwise use default
-                    if let Some(ref custom) = self.custom_global_config {
-                        custom.clone()
-                    } else {
-                        get_global_config_path()
-                    }
-                }
-                ConfigScope::Project => {
-                    let path = project_path.ok_or_else(|| {
-                        ConfigError::Generic("Project path required for Project scope".to_string())
-                    })?;
-                    path.join(".claude").join("config.json")
+    ) -> Result<EnabledRestore> {
+        let (mut config, config_path) = self.read_config_for_scope(scope, project_path)?;
+        if !snapshot.taken_from(&config_path) {
+            return Err(ConfigError::validation_failed(
+                "RestoreEnabled",
+                format!(
+                    "The snapshot was taken from {}, not {}",
+                    snapshot.config_path.display(),
+                    config_path.display()
+                ),
+                "Use the same scope and project as when the servers were disabled",
+            ));
+        }
+
+        let mut result = EnabledRestore::default();
+        let servers = config.mcp_servers.get_or_insert_with(HashMap::new);
+        for (name, enabled) in &snapshot.enabled {
+            match servers.get_mut(name) {
+                None => result.missing.push(name.clone()),
+                Some(server) if server.enabled == *enabled => result.unchanged.push(name.clone()),
+                Some(server) => {
+                    server.enabled = *enabled;
+                    result.restored.push(name.clone());
                 }
             }
-        };
-
-        let config = if config_path.exists() {
-            self.config_manager.read_config(&config_path)?
-        } else {
-            crate::ClaudeConfig::new()
-        };
+        }
+        if result.restored.is_empty() {
+            return Ok(result);
+        }
 
-        Ok((config, config_path))
-    }
-}
+        self.config_manager
+            .write_config_with_backup(&config_path, &config)?;
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::fs;
-    use tempfile::TempDir;
+        tracing::info!("Restored enabled flags: {}", result.restored.join(", "));
+        for name in &result.restored {
+            let op = if snapshot.enabled[name] {
+                McpOp::Enable
+            } else {
+                McpOp::Disable
+            };
+            self.log_operation(op, scope, name, &config_path, None);
+        }
 
-    /// Helper to create a test McpManager with a temporary config path
-    fn create_test_manager(temp_dir: &Path) -> McpManager {
-        let backup_dir = temp_dir.join("backups");
-        let config_path = temp_dir.join("config.json");
-        McpManager::with_custom_global_config(&backup_dir, &config_path)
+        Ok(result)
     }
 
-    // TDD Test 1: List servers from empty config
-    #[test]
-    fn test_list_servers_empty_config() {
-        let temp_dir = TempDir::new().unwrap();
-        let manager = create_test_manager(temp_dir.path());
+    /// Set environment variables of an MCP server
+    ///
+    /// All variables are written at once, with a single backup. Variables
+    /// that already exist are overwritten.
+    ///
+    /// # Arguments
+    /// * `name` - Server name
+    /// * `pairs` - Variable names and values to set
+    /// * `scope` - Configuration scope
+    /// * `project_path` - Project path (required if scope is Project)
+    ///
+    /// # Returns
+    /// The variables that had a different value, with that previous value
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - A variable name is invalid (see [`validate_env_key`])
+    /// - Server doesn't exist
+    /// - Config file cannot be read/written
+    pub fn set_env_vars(
+        &self,
+        name: &str,
+        pairs: &[(String, String)],
+        scope: &ConfigScope,
+        project_path: Option<&Path>,
+    ) -> Result<Vec<(String, String)>> {
+        for (key, _) in pairs {
+            validate_env_key(key)?;
+        }
 
-        let result = manager.list_servers(&ConfigScope::Global, None);
+        let (mut config, config_path) = self.read_config_for_scope(scope, project_path)?;
+        let servers = config.mcp_servers.get_or_insert_with(HashMap::new);
+        let name = find_server_key(servers, name)
+            .ok_or_else(|| self.missing_server(name, servers, scope, project_path))?;
+
+        let server = servers.get_mut(&name).expect("server key was just found");
+        let mut replaced = Vec::new();
+        for (key, value) in pairs {
+            if let Some(old) = server.env.insert(key.clone(), value.clone()) {
+                if &old != value {
+                    replaced.push((key.clone(), old));
+                }
+            }
+        }
 
-        assert!(result.is_ok());
-        let servers = result.unwrap();
-        assert_eq!(servers.len(), 0);
+        self.write_env_change(&config, &config_path, scope, &name)?;
+        Ok(replaced)
     }
 
-    // TDD Test 2: Add and list server
-    #[test]
-    fn test_add_and_list_server() {
-        let temp_dir = TempDir::new().unwrap();
-        let manager = create_test_manager(temp_dir.path());
-
-        // Add a server
-        let server = McpServer::new("test-server", "npx", vec!["-y".to_string()]);
-        manager
-            .add_server("test-server", server, &ConfigScope::Global, None)
-            .unwrap();
-
-        // List servers
-        let servers = manager.list_servers(&ConfigScope::Global, None).unwrap();
+    /// Remove an environment variable from an MCP server
+    ///
+    /// # Arguments
+    /// * `name` - Server name
+    /// * `key` - Variable name
+    /// * `scope` - Configuration scope
+    /// * `project_path` - Project path (required if scope is Project)
+    ///
+    /// # Returns
+    /// The value the variable had
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - Server doesn't exist
+    /// - The server has no variable called `key`
+    /// - Config file cannot be read/written
+    pub fn unset_env_var(
+        &self,
+        name: &str,
+        key: &str,
+        scope: &ConfigScope,
+        project_path: Option<&Path>,
+    ) -> Result<String> {
+        let (mut config, config_path) = self.read_config_for_scope(scope, project_path)?;
+        let servers = config.mcp_servers.get_or_insert_with(HashMap::new);
+        let name = find_server_key(servers, name)
+            .ok_or_else(|| self.missing_server(name, servers, scope, project_path))?;
+
+        let server = servers.get_mut(&name).expect("server key was just found");
+        let old = server.env.remove(key).ok_or_else(|| {
+            let mut keys: Vec<&str> = server.env.keys().map(String::as_str).collect();
+            keys.sort_unstable();
+            ConfigError::Generic(format!(
+                "MCP server '{name}' has no env variable '{key}'. Defined variables: {}",
+                if keys.is_empty() {
+                    "(none)".to_string()
+                } else {
+                    keys.join(", ")
+                }
+            ))
+        })?;
 
-        assert_eq!(servers.len(), 1);
-        assert!(servers.contains_key("test-server"));
-        assert_eq!(servers["test-server"].command, Some("npx".to_string()));
+        self.write_env_change(&config, &config_path, scope, &name)?;
+        Ok(old)
     }
 
-    // TDD Test 3: Add duplicate server fails
-    #[test]
-    fn test_add_duplicate_server_fails() {
-        let temp_dir = TempDir::new().unwrap();
-        let manager = create_test_manager(temp_dir.path());
+    /// Write a config whose server `name` had its environment changed
+    fn write_env_change(
+        &self,
+        config: &crate::ClaudeConfig,
+        config_path: &Path,
+        scope: &ConfigScope,
+        name: &str,
+    ) -> Result<()> {
+        let changed = self.config_manager.write_config_with_options(
+            config_path,
+            config,
+            WriteOptions::default(),
+        )?;
+        if changed {
+            tracing::info!("MCP server '{}' environment updated", name);
+            let server = config.mcp_servers.as_ref().and_then(|s| s.get(name));
+            self.log_operation(McpOp::Env, scope, name, config_path, server);
+        }
+        Ok(())
+    }
 
-        // Add first server
-        let server = McpServer::new("test", "npx", vec![]);
-        manager
-            .add_server("test", server, &ConfigScope::Global, None)
-            .unwrap();
+    /// Error for a server missing from `scope`
+    ///
+    /// Points to the other scope if the server is defined there instead.
+    fn missing_server(
+        &self,
+        name: &str,
+        servers: &HashMap<String, McpServer>,
+        scope: &ConfigScope,
+        project_path: Option<&Path>,
+    ) -> ConfigError {
+        let other = match scope {
+            ConfigScope::Global if project_path.is_some() => ConfigScope::Project,
+            ConfigScope::Project => ConfigScope::Global,
+            _ => return not_found(name, servers),
+        };
 
-        // Try to add duplicate
-        let server2 = McpServer::new("test", "uvx", vec![]);
-        let result = manager.add_server("test", server2, &ConfigScope::Global, None);
+        let defined_there = self
+            .list_servers(&other, project_path)
+            .is_ok_and(|other_servers| find_server_key(&other_servers, name).is_some());
+        if !defined_there {
+            return not_found(name, servers);
+        }
 
-        assert!(result.is_err());
-        assert!(result.unwrap_err().to_string().contains("already exists"));
+        ConfigError::Generic(format!(
+            "MCP server '{name}' is not in the {} config, only in the {} config. Use scope {} to edit it.",
+            scope.display_name(),
+            other.display_name(),
+            other.display_name()
+        ))
     }
 
-    // TDD Test 4: Enable/disable server
-    #[test]
-    fn test_enable_disable_server() {
-        let temp_dir = TempDir::new().unwrap();
-        let manager = create_test_manager(temp_dir.path());
-
-        // Add disabled server
-        let mut server = McpServer::new("test", "npx", vec![]);
-        server.enabled = false;
-        manager
-            .add_server("test", server, &ConfigScope::Global, None)
-            .unwrap();
-
-        // Enable server
-        manager
-            .enable_server("test", &ConfigScope::Global, None)
+    /// Get detailed information about a specific server
+    ///
+    /// # Arguments
+    /// * `name` - Server name
+    /// * `scope` - Configuration scope
+    /// * `project_path` - Project path (required if scope is Project)
+    ///
+    /// # Returns
+    /// The server configuration if found
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - Server doesn't exist
+    /// - Config file cannot be read
+    pub fn get_server(
+        &self,
+        name: &str,
+        scope: &ConfigScope,
+        project_path: Option<&Path>,
+    ) -> Result<McpServer> {
+        let mut servers = self.list_servers(scope, project_path)?;
+
+        find_server_key(&servers, name)
+            .and_then(|key| servers.remove(&key))
+            .ok_or_else(|| not_found(name, &servers))
+    }
+
+    /// Export the MCP servers of a scope to a file
+    ///
+    /// The file holds just the `mcpServers` map, so server definitions can
+    /// be shared without the rest of the config. Unless `include_secrets` is
+    /// set, secrets are replaced with `<redacted>` as in support bundles
+    /// (see [`Redactor`]), which covers every `env` value.
+    ///
+    /// # Arguments
+    /// * `scope` - Configuration scope to export from
+    /// * `project_path` - Project path (required if scope is Project)
+    /// * `path` - File to write (replaced if it exists)
+    /// * `include_secrets` - Write env values and secret-looking arguments as they are
+    ///
+    /// # Returns
+    /// Number of servers exported
+    ///
+    /// # Errors
+    /// Returns an error if the config cannot be read or the file cannot be
+    /// written
+    pub fn export_servers(
+        &self,
+        scope: &ConfigScope,
+        project_path: Option<&Path>,
+        path: &Path,
+        include_secrets: bool,
+    ) -> Result<usize> {
+        let servers: BTreeMap<String, McpServer> = self
+            .list_servers(scope, project_path)?
+            .into_iter()
+            .map(|(name, mut server)| {
+                server.name = name.clone();
+                (name, server)
+            })
+            .collect();
+
+        let mut value = serde_json::json!({ "mcpServers": servers });
+        if !include_secrets {
+            value = Redactor::new().redact_value(&value);
+        }
+        let content = serde_json::to_string_pretty(&value["mcpServers"])?;
+        atomic_write(path, &content)?;
+
+        tracing::info!(
+            "Exported {} MCP server(s) to {}",
+            servers.len(),
+            path.display()
+        );
+        Ok(servers.len())
+    }
+
+    /// Merge MCP servers from a file written by [`export_servers`](Self::export_servers)
+    ///
+    /// Servers that aren't configured yet are added; identical ones are left
+    /// alone. A server configured with a different definition is replaced
+    /// only with `overwrite`, otherwise nothing is imported. The config is
+    /// written once, with a backup.
+    ///
+    /// `<redacted>` placeholders from a redacted export keep the configured
+    /// value, so re-importing an export never overwrites real secrets.
+    ///
+    /// # Arguments
+    /// * `path` - File holding an `mcpServers` map
+    /// * `scope` - Configuration scope to import into
+    /// * `project_path` - Project path (required if scope is Project)
+    /// * `overwrite` - Replace servers whose definition differs
+    ///
+    /// # Returns
+    /// Which servers were added, replaced or already configured
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - The file cannot be read or isn't an `mcpServers` map
+    /// - A redacted value has no configured value to keep
+    /// - A server differs from the configured one (without `overwrite`)
+    /// - The resulting config is invalid or cannot be written
+    pub fn import_servers(
+        &self,
+        path: &Path,
+        scope: &ConfigScope,
+        project_path: Option<&Path>,
+        overwrite: bool,
+    ) -> Result<ServerImport> {
+        if !path.exists() {
+            return Err(ConfigError::not_found(path));
+        }
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| ConfigError::filesystem("read server export", path, e))?;
+        let incoming: BTreeMap<String, McpServer> = serde_json::from_str(&content)
+            .map_err(|e| ConfigError::invalid_json(path, e.line(), e.column(), e.to_string()))?;
+
+        let (mut config, config_path) = self.read_config_for_scope(scope, project_path)?;
+        let servers = config.mcp_servers.get_or_insert_with(HashMap::new);
+
+        let mut result = ServerImport::default();
+        let mut conflicts = Vec::new();
+        for (name, server) in incoming {
+            let key = find_server_key(servers, &name);
+            let mut server = restore_secrets(&name, &server, key.as_ref().map(|k| &servers[k]))?;
+            server.name = name.clone();
+            match key {
+                None => {
+                    servers.insert(name.clone(), server);
+                    result.added.push(name);
+                }
+                Some(key) if servers[&key].semantically_equal(&server) => {
+                    result.unchanged.push(key);
+                }
+                Some(key) if overwrite => {
+                    server.name = key.clone();
+                    servers.insert(key.clone(), server);
+                    result.replaced.push(key);
+                }
+                Some(key) => conflicts.push(key),
+            }
+        }
+
+        if !conflicts.is_empty() {
+            return Err(ConfigError::validation_failed(
+                "ImportServers",
+                format!(
+                    "Already configured with a different definition: {}",
+                    conflicts.join(", ")
+                ),
+                "Pass --overwrite to replace them, or remove them first",
+            ));
+        }
+        if result.added.is_empty() && result.replaced.is_empty() {
+            return Ok(result);
+        }
+
+        crate::validate_config(&config)?;
+        self.config_manager
+            .write_config_with_backup(&config_path, &config)?;
+
+        for name in result.added.iter().chain(&result.replaced) {
+            let server = config.mcp_servers.as_ref().and_then(|s| s.get(name));
+            self.log_operation(McpOp::Add, scope, name, &config_path, server);
+        }
+        tracing::info!(
+            "Imported MCP servers from {}: {} added, {} replaced",
+            path.display(),
+            result.added.len(),
+            result.replaced.len()
+        );
+
+        Ok(result)
+    }
+
+    /// Record a completed change in the operation log, if enabled
+    fn log_operation(
+        &self,
+        op: McpOp,
+        scope: &ConfigScope,
+        name: &str,
+        config_path: &Path,
+        server: Option<&McpServer>,
+    ) {
+        // The change has already been written, so a log that can't be
+        // written is reported but doesn't fail the operation
+        if let Some(log) = &self.audit_log {
+            let entry = AuditEntry::new(op, *scope, name, config_path, server);
+            if let Err(e) = log.append(&entry) {
+                tracing::warn!(
+                    "Could not write operation log {}: {e}",
+                    log.path().display()
+                );
+            }
+        }
+    }
+
+    /// Read configuration for the specified scope
+    ///
+    /// Internal helper that returns both the config and its file path.
+    fn read_config_for_scope(
+        &self,
+        scope: &ConfigScope,
+        project_path: Option<&Path>,
+    ) -> Result<(crate::ClaudeConfig, PathBuf)> {
+        self.read_config_for_scope_with_path(scope, project_path, None)
+    }
+
+    /// Read configuration with optional custom config path (for testing)
+    fn read_config_for_scope_with_path(
+        &self,
+        scope: &ConfigScope,
+        project_path: Option<&Path>,
+        custom_config_path: Option<&Path>,
+    ) -> Result<(crate::ClaudeConfig, PathBuf)> {
+        let config_path = match custom_config_path {
+            Some(custom) => custom.to_path_buf(),
+            None => self
+                .config_manager
+                .config_path_for_scope(scope, project_path)?,
+        };
+
+        let config = if config_path.exists() {
+            self.config_manager.read_config(&config_path)?
+        } else {
+            crate::ClaudeConfig::new()
+        };
+
+        Ok((config, config_path))
+    }
+}
+
+/// Outcome of [`McpManager::import_servers`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ServerImport {
+    /// Servers that weren't configured before
+    pub added: Vec<String>,
+
+    /// Servers whose definition was replaced
+    pub replaced: Vec<String>,
+
+    /// Servers that were already configured identically
+    pub unchanged: Vec<String>,
+}
+
+/// Fill the `<redacted>` placeholders of an imported server from the
+/// configured one
+fn restore_secrets(
+    name: &str,
+    server: &McpServer,
+    existing: Option<&McpServer>,
+) -> Result<McpServer> {
+    let value = serde_json::to_value(server)?;
+    let original = existing.map(serde_json::to_value).transpose()?;
+    let restored = restore_redacted(&value, original.as_ref()).map_err(|path| {
+        ConfigError::validation_failed(
+            "ImportServers",
+            format!(
+                "Server '{name}' has a redacted value at {path} and nothing configured to keep"
+            ),
+            "Export with --include-secrets, or fill in the value before importing",
+        )
+    })?;
+    Ok(serde_json::from_value(restored)?)
+}
+
+/// Key of the server called `name`
+///
+/// An exact match wins; otherwise a single server whose name is the same
+/// under [`collate::same_name`] (e.g. differing only in case) is found.
+fn find_server_key(servers: &HashMap<String, McpServer>, name: &str) -> Option<String> {
+    if servers.contains_key(name) {
+        return Some(name.to_string());
+    }
+
+    let mut matches = servers.keys().filter(|key| collate::same_name(key, name));
+    match (matches.next(), matches.next()) {
+        (Some(key), None) => Some(key.clone()),
+        _ => None,
+    }
+}
+
+/// Error for a server that isn't configured, listing the ones that are
+fn not_found(name: &str, servers: &HashMap<String, McpServer>) -> ConfigError {
+    let mut available: Vec<&str> = servers.keys().map(String::as_str).collect();
+    collate::sort(&mut available);
+    ConfigError::Generic(format!(
+        "MCP server '{}' not found. Available servers: {}",
+        name,
+        available.join(", ")
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    /// Helper to create a test McpManager with a temporary config path
+    fn create_test_manager(temp_dir: &Path) -> McpManager {
+        let backup_dir = temp_dir.join("backups");
+        let config_path = temp_dir.join("config.json");
+        McpManager::with_custom_global_config(&backup_dir, &config_path)
+    }
+
+    // TDD Test 1: List servers from empty config
+    #[test]
+    fn test_list_servers_empty_config() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = create_test_manager(temp_dir.path());
+
+        let result = manager.list_servers(&ConfigScope::Global, None);
+
+        assert!(result.is_ok());
+        let servers = result.unwrap();
+        assert_eq!(servers.len(), 0);
+    }
+
+    // TDD Test 2: Add and list server
+    #[test]
+    fn test_add_and_list_server() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = create_test_manager(temp_dir.path());
+
+        // Add a server
+        let server = McpServer::new("test-server", "npx", vec!["-y".to_string()]);
+        manager
+            .add_server("test-server", server, &ConfigScope::Global, None)
+            .unwrap();
+
+        // List servers
+        let servers = manager.list_servers(&ConfigScope::Global, None).unwrap();
+
+        assert_eq!(servers.len(), 1);
+        assert!(servers.contains_key("test-server"));
+        assert_eq!(servers["test-server"].command, Some("npx".to_string()));
+    }
+
+    // TDD Test 3: Add duplicate server fails
+    #[test]
+    fn test_add_duplicate_server_fails() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = create_test_manager(temp_dir.path());
+
+        // Add first server
+        let server = McpServer::new("test", "npx", vec![]);
+        manager
+            .add_server("test", server, &ConfigScope::Global, None)
+            .unwrap();
+
+        // Try to add duplicate
+        let server2 = McpServer::new("test", "uvx", vec![]);
+        let result = manager.add_server("test", server2, &ConfigScope::Global, None);
+
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("already exists"));
+    }
+
+    // TDD Test 4: Enable/disable server
+    #[test]
+    fn test_enable_disable_server() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = create_test_manager(temp_dir.path());
+
+        // Add disabled server
+        let mut server = McpServer::new("test", "npx", vec![]);
+        server.enabled = false;
+        manager
+            .add_server("test", server, &ConfigScope::Global, None)
             .unwrap();
 
+        // Enable server
+        assert!(manager
+            .enable_server("test", &ConfigScope::Global, None)
+            .unwrap());
+
         // Check enabled
         let servers = manager.list_servers(&ConfigScope::Global, None).unwrap();
         assert!(servers["test"].enabled);
 
+        // Enabling again changes nothing
+        assert!(!manager
+            .enable_server("test", &ConfigScope::Global, None)
+            .unwrap());
+
         // Disable server
         manager
             .disable_server("test", &ConfigScope::Global, None)
@@ -573,4 +1243,657 @@ mod tests {
             .to_string()
             .contains("Project path required"));
     }
+
+    // TDD Test 11: Re-adding an identical server is a no-op
+    #[test]
+    fn test_add_identical_server_is_idempotent() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = create_test_manager(temp_dir.path());
+
+        let server = McpServer::new("fetch", "uvx", vec!["mcp-server-fetch".to_string()])
+            .with_env("A", "1")
+            .with_env("B", "2");
+        manager
+            .add_server("fetch", server.clone(), &ConfigScope::Global, None)
+            .unwrap();
+        let written = fs::read_to_string(temp_dir.path().join("config.json")).unwrap();
+
+        assert!(!manager
+            .add_server("fetch", server, &ConfigScope::Global, None)
+            .unwrap());
+
+        // Nothing was rewritten or backed up
+        assert_eq!(
+            fs::read_to_string(temp_dir.path().join("config.json")).unwrap(),
+            written
+        );
+        assert!(!temp_dir.path().join("backups").exists());
+    }
+
+    // TDD Test 12: Adding and removing a server writes two log lines
+    #[test]
+    fn test_audit_log_records_add_and_remove() {
+        let temp_dir = TempDir::new().unwrap();
+        let log_path = temp_dir.path().join("ccm-ops.log");
+        let manager = create_test_manager(temp_dir.path()).with_audit_log(&log_path);
+
+        let server = McpServer::new("fetch", "uvx", vec!["mcp-server-fetch".to_string()])
+            .with_env("API_KEY", "secret");
+        manager
+            .add_server("fetch", server, &ConfigScope::Global, None)
+            .unwrap();
+        manager
+            .remove_server("fetch", &ConfigScope::Global, None)
+            .unwrap();
+
+        let content = fs::read_to_string(&log_path).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(lines.len(), 2);
+        for line in &lines {
+            let value: serde_json::Value = serde_json::from_str(line).unwrap();
+            for field in ["timestamp", "op", "scope", "server", "details"] {
+                assert!(value.get(field).is_some(), "missing {field} in {line}");
+            }
+        }
+        // Env values stay out of the log
+        assert!(!content.contains("secret"));
+
+        let entries = AuditLog::new(&log_path).entries().unwrap();
+        assert_eq!(entries[0].op, McpOp::Add);
+        assert_eq!(entries[0].details["command"], "uvx");
+        assert_eq!(entries[0].details["envKeys"][0], "API_KEY");
+        assert_eq!(entries[1].op, McpOp::Remove);
+        assert!(entries.iter().all(|e| e.server == "fetch"));
+        assert!(entries.iter().all(|e| e.scope == ConfigScope::Global));
+    }
+
+    // TDD Test 13: No log is written unless enabled
+    #[test]
+    fn test_audit_log_disabled_by_default() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = create_test_manager(temp_dir.path());
+
+        manager
+            .add_server(
+                "fetch",
+                McpServer::new("fetch", "uvx", vec![]),
+                &ConfigScope::Global,
+                None,
+            )
+            .unwrap();
+
+        assert!(!temp_dir.path().join("ccm-ops.log").exists());
+    }
+
+    // TDD Test 14: Moving a server between scopes updates both configs
+    #[test]
+    fn test_move_server_global_to_project() {
+        let temp_dir = TempDir::new().unwrap();
+        let project = temp_dir.path().join("project");
+        fs::create_dir_all(&project).unwrap();
+        let manager = create_test_manager(temp_dir.path());
+
+        manager
+            .add_server(
+                "fetch",
+                McpServer::new("fetch", "uvx", vec!["mcp-server-fetch".to_string()]),
+                &ConfigScope::Global,
+                None,
+            )
+            .unwrap();
+
+        manager
+            .move_server(
+                "fetch",
+                &ConfigScope::Global,
+                &ConfigScope::Project,
+                Some(&project),
+            )
+            .unwrap();
+
+        assert!(manager
+            .list_servers(&ConfigScope::Global, None)
+            .unwrap()
+            .is_empty());
+        let moved = manager
+            .get_server("fetch", &ConfigScope::Project, Some(&project))
+            .unwrap();
+        assert_eq!(moved.args, vec!["mcp-server-fetch".to_string()]);
+
+        // Moving again fails without changing anything
+        let err = manager
+            .move_server(
+                "fetch",
+                &ConfigScope::Global,
+                &ConfigScope::Project,
+                Some(&project),
+            )
+            .unwrap_err();
+        assert!(err.to_string().contains("not found in"));
+    }
+
+    // TDD Test 15: Names that differ only in case or normalization are the same server
+    #[test]
+    fn test_server_names_compared_by_collation() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = create_test_manager(temp_dir.path());
+        let server = McpServer::new("Résumé", "uvx", vec![]);
+
+        manager
+            .add_server("Résumé", server.clone(), &ConfigScope::Global, None)
+            .unwrap();
+
+        // Same definition under another spelling is a no-op, a different one conflicts
+        manager
+            .add_server("résumé", server, &ConfigScope::Global, None)
+            .unwrap();
+        let err = manager
+            .add_server(
+                "RE\u{301}SUME\u{301}",
+                McpServer::new("x", "npx", vec![]),
+                &ConfigScope::Global,
+                None,
+            )
+            .unwrap_err();
+        assert!(err.to_string().contains("'Résumé' already exists"));
+
+        manager
+            .disable_server("résumé", &ConfigScope::Global, None)
+            .unwrap();
+        let servers = manager.list_servers(&ConfigScope::Global, None).unwrap();
+        assert_eq!(servers.len(), 1);
+        assert!(!servers["Résumé"].enabled);
+
+        manager
+            .remove_server("RÉSUMÉ", &ConfigScope::Global, None)
+            .unwrap();
+        assert!(manager
+            .list_servers(&ConfigScope::Global, None)
+            .unwrap()
+            .is_empty());
+    }
+
+    // TDD Test 16: Servers round-trip through an export file
+    #[test]
+    fn test_export_import_servers_round_trip() {
+        let source_dir = TempDir::new().unwrap();
+        let source = create_test_manager(source_dir.path());
+        fs::write(
+            source_dir.path().join("config.json"),
+            r#"{"allowedPaths": ["~/private"], "mcpServers": {
+                "fetch": {"command": "uvx", "args": ["mcp-fetch"], "enabled": true},
+                "git": {"command": "npx", "args": ["mcp-git"], "enabled": false}
+            }}"#,
+        )
+        .unwrap();
+        let export = source_dir.path().join("servers.json");
+
+        let count = source
+            .export_servers(&ConfigScope::Global, None, &export, true)
+            .unwrap();
+        assert_eq!(count, 2);
+        assert!(!fs::read_to_string(&export).unwrap().contains("private"));
+
+        let target_dir = TempDir::new().unwrap();
+        let target = create_test_manager(target_dir.path());
+        target
+            .add_server(
+                "git",
+                McpServer::new("git", "git-mcp", vec![]),
+                &ConfigScope::Global,
+                None,
+            )
+            .unwrap();
+
+        let err = target
+            .import_servers(&export, &ConfigScope::Global, None, false)
+            .unwrap_err();
+        assert!(err.to_string().contains("different definition: git"));
+        assert_eq!(
+            target
+                .list_servers(&ConfigScope::Global, None)
+                .unwrap()
+                .len(),
+            1
+        );
+
+        let imported = target
+            .import_servers(&export, &ConfigScope::Global, None, true)
+            .unwrap();
+        assert_eq!(imported.added, ["fetch"]);
+        assert_eq!(imported.replaced, ["git"]);
+        assert_eq!(
+            target.list_servers(&ConfigScope::Global, None).unwrap(),
+            source.list_servers(&ConfigScope::Global, None).unwrap()
+        );
+
+        let again = target
+            .import_servers(&export, &ConfigScope::Global, None, false)
+            .unwrap();
+        assert_eq!(again.unchanged, ["fetch", "git"]);
+    }
+
+    // TDD Test 17: Array-form mcpServers load, and the next write emits a map
+    #[test]
+    fn test_array_form_servers_round_trip_as_map() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = create_test_manager(temp_dir.path());
+        let config_path = temp_dir.path().join("config.json");
+        fs::write(
+            &config_path,
+            r#"{"mcpServers": [
+                {"name": "fetch", "command": "npx", "args": ["-y", "mcp-fetch"], "enabled": true},
+                {"name": "git", "command": "uvx", "enabled": false}
+            ]}"#,
+        )
+        .unwrap();
+
+        let servers = manager.list_servers(&ConfigScope::Global, None).unwrap();
+        assert_eq!(servers.len(), 2);
+        assert_eq!(servers["fetch"].args, ["-y", "mcp-fetch"]);
+        assert!(!servers["git"].enabled);
+
+        manager
+            .enable_server("git", &ConfigScope::Global, None)
+            .unwrap();
+        let written: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&config_path).unwrap()).unwrap();
+        assert!(written["mcpServers"].is_object());
+        assert_eq!(written["mcpServers"]["git"]["enabled"], true);
+        assert_eq!(written["mcpServers"]["fetch"]["command"], "npx");
+
+        // Duplicate names are rejected, naming the server
+        fs::write(
+            &config_path,
+            r#"{"mcpServers": [{"name": "fetch", "command": "a"}, {"name": "fetch", "command": "b"}]}"#,
+        )
+        .unwrap();
+        let err = manager
+            .list_servers(&ConfigScope::Global, None)
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("duplicate MCP server name 'fetch'"));
+    }
+
+    /// Enabled flag of each server of the global config
+    fn enabled_flags(manager: &McpManager) -> BTreeMap<String, bool> {
+        manager
+            .list_servers(&ConfigScope::Global, None)
+            .unwrap()
+            .into_iter()
+            .map(|(name, server)| (name, server.enabled))
+            .collect()
+    }
+
+    /// Manager with servers `fetch` and `git` enabled and `old` disabled
+    fn manager_with_mixed_servers(temp_dir: &Path) -> McpManager {
+        let manager = create_test_manager(temp_dir);
+        for (name, enabled) in [("fetch", true), ("git", true), ("old", false)] {
+            let mut server = McpServer::new(name, "npx", vec![]);
+            server.enabled = enabled;
+            manager
+                .add_server(name, server, &ConfigScope::Global, None)
+                .unwrap();
+        }
+        manager
+    }
+
+    // TDD Test 18: Disable all servers except the named ones
+    #[test]
+    fn test_disable_all_with_exceptions() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = manager_with_mixed_servers(temp_dir.path());
+        let before = enabled_flags(&manager);
+
+        let snapshot = manager
+            .disable_all(&["FETCH".to_string()], &ConfigScope::Global, None)
+            .unwrap();
+
+        assert_eq!(snapshot.enabled, before);
+        assert_eq!(snapshot.scope, ConfigScope::Global);
+        assert_eq!(snapshot.config_path, temp_dir.path().join("config.json"));
+        let saved = snapshot.path.as_deref().unwrap();
+        assert!(saved.starts_with(temp_dir.path().join("backups").join("mcp-enabled")));
+        assert_eq!(
+            enabled_flags(&manager),
+            BTreeMap::from([
+                ("fetch".to_string(), true),
+                ("git".to_string(), false),
+                ("old".to_string(), false),
+            ])
+        );
+        let risk = manager
+            .config_manager
+            .backup_manager()
+            .latest_risk_snapshot(&temp_dir.path().join("config.json"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(risk.tag.as_deref(), Some("auto-risk-disable-all"));
+
+        // Nothing left to disable: no write, no new snapshot
+        let again = manager
+            .disable_all(&["fetch".to_string()], &ConfigScope::Global, None)
+            .unwrap();
+        assert!(again.path.is_none());
+        let latest = manager
+            .latest_enabled_snapshot(&ConfigScope::Global, None)
+            .unwrap()
+            .unwrap();
+        assert_eq!(latest.enabled, before);
+
+        let err = manager
+            .disable_all(&["missing".to_string()], &ConfigScope::Global, None)
+            .unwrap_err();
+        assert!(err.to_string().contains("'missing' not found"));
+    }
+
+    // TDD Test 19: Reverting restores exactly the recorded flags
+    #[test]
+    fn test_restore_enabled_restores_prior_flags() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = manager_with_mixed_servers(temp_dir.path());
+        let before = enabled_flags(&manager);
+
+        manager
+            .disable_all(&[], &ConfigScope::Global, None)
+            .unwrap();
+        assert!(enabled_flags(&manager).values().all(|enabled| !enabled));
+
+        // A server added meanwhile keeps its flag
+        manager
+            .add_server(
+                "new",
+                McpServer::new("new", "uvx", vec![]),
+                &ConfigScope::Global,
+                None,
+            )
+            .unwrap();
+
+        let snapshot = manager
+            .latest_enabled_snapshot(&ConfigScope::Global, None)
+            .unwrap()
+            .unwrap();
+        let restore = manager
+            .restore_enabled(&snapshot, &ConfigScope::Global, None)
+            .unwrap();
+
+        assert_eq!(restore.restored, ["fetch", "git"]);
+        assert_eq!(restore.unchanged, ["old"]);
+        assert!(restore.missing.is_empty());
+        let mut expected = before;
+        expected.insert("new".to_string(), true);
+        assert_eq!(enabled_flags(&manager), expected);
+    }
+
+    // TDD Test 20: Servers removed since the snapshot are skipped on revert
+    #[test]
+    fn test_restore_enabled_skips_removed_servers() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = manager_with_mixed_servers(temp_dir.path());
+
+        let snapshot = manager
+            .disable_all(&[], &ConfigScope::Global, None)
+            .unwrap();
+        manager
+            .remove_server("git", &ConfigScope::Global, None)
+            .unwrap();
+
+        let restore = manager
+            .restore_enabled(&snapshot, &ConfigScope::Global, None)
+            .unwrap();
+
+        assert_eq!(restore.restored, ["fetch"]);
+        assert_eq!(restore.missing, ["git"]);
+        assert_eq!(
+            enabled_flags(&manager),
+            BTreeMap::from([("fetch".to_string(), true), ("old".to_string(), false)])
+        );
+
+        // A snapshot of another config is refused
+        let mut other = snapshot;
+        other.config_path = temp_dir.path().join("other.json");
+        assert!(manager
+            .restore_enabled(&other, &ConfigScope::Global, None)
+            .is_err());
+    }
+
+    fn env_pairs(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    // TDD Test 21: Setting several env variables writes the config once
+    #[test]
+    fn test_set_env_vars() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = create_test_manager(temp_dir.path());
+        let server = McpServer::new("github", "npx", vec![]).with_env("GITHUB_TOKEN", "old");
+        manager
+            .add_server("github", server, &ConfigScope::Global, None)
+            .unwrap();
+        let config_path = temp_dir.path().join("config.json");
+        let backups_before = manager
+            .config_manager
+            .backup_manager()
+            .list_backups(&config_path)
+            .unwrap()
+            .len();
+
+        let replaced = manager
+            .set_env_vars(
+                "GitHub",
+                &env_pairs(&[("GITHUB_TOKEN", "new"), ("GITHUB_HOST", "example.com")]),
+                &ConfigScope::Global,
+                None,
+            )
+            .unwrap();
+
+        assert_eq!(replaced, env_pairs(&[("GITHUB_TOKEN", "old")]));
+        let server = manager
+            .get_server("github", &ConfigScope::Global, None)
+            .unwrap();
+        assert_eq!(server.env["GITHUB_TOKEN"], "new");
+        assert_eq!(server.env["GITHUB_HOST"], "example.com");
+        let backups_after = manager
+            .config_manager
+            .backup_manager()
+            .list_backups(&config_path)
+            .unwrap()
+            .len();
+        assert_eq!(backups_after, backups_before + 1);
+
+        // Invalid names are rejected before anything is written
+        let err = manager
+            .set_env_vars(
+                "github",
+                &env_pairs(&[("OK", "1"), ("BAD KEY", "2")]),
+                &ConfigScope::Global,
+                None,
+            )
+            .unwrap_err();
+        assert!(err.to_string().contains("BAD KEY"));
+        let server = manager
+            .get_server("github", &ConfigScope::Global, None)
+            .unwrap();
+        assert!(!server.env.contains_key("OK"));
+    }
+
+    // TDD Test 22: Unsetting a variable the server doesn't have fails clearly
+    #[test]
+    fn test_unset_env_var() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = create_test_manager(temp_dir.path());
+        let server = McpServer::new("fetch", "uvx", vec![]).with_env("API_URL", "https://x");
+        manager
+            .add_server("fetch", server, &ConfigScope::Global, None)
+            .unwrap();
+
+        let old = manager
+            .unset_env_var("fetch", "API_URL", &ConfigScope::Global, None)
+            .unwrap();
+        assert_eq!(old, "https://x");
+
+        let err = manager
+            .unset_env_var("fetch", "API_URL", &ConfigScope::Global, None)
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("MCP server 'fetch' has no env variable 'API_URL'"));
+
+        let err = manager
+            .unset_env_var("missing", "API_URL", &ConfigScope::Global, None)
+            .unwrap_err();
+        assert!(err.to_string().contains("MCP server 'missing' not found"));
+    }
+
+    // TDD Test 23: Servers defined only in the other scope are pointed out
+    #[test]
+    fn test_env_of_project_only_server() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = create_test_manager(temp_dir.path());
+        let project = temp_dir.path().join("project");
+        fs::create_dir_all(project.join(".claude")).unwrap();
+        manager
+            .add_server(
+                "local",
+                McpServer::new("local", "node", vec![]),
+                &ConfigScope::Project,
+                Some(&project),
+            )
+            .unwrap();
+
+        let pairs = env_pairs(&[("DEBUG", "1")]);
+        let err = manager
+            .set_env_vars("local", &pairs, &ConfigScope::Global, Some(&project))
+            .unwrap_err();
+        assert!(
+            err.to_string().contains("only in the project config"),
+            "{err}"
+        );
+
+        manager
+            .set_env_vars("local", &pairs, &ConfigScope::Project, Some(&project))
+            .unwrap();
+        let server = manager
+            .get_server("local", &ConfigScope::Project, Some(&project))
+            .unwrap();
+        assert_eq!(server.env["DEBUG"], "1");
+    }
+
+    // TDD Test 24: Snapshots match the config however its path is spelled
+    #[test]
+    fn test_enabled_snapshot_matches_path_spellings() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = create_test_manager(temp_dir.path());
+        let project = temp_dir.path().join("project");
+        fs::create_dir_all(project.join(".claude")).unwrap();
+        manager
+            .add_server(
+                "local",
+                McpServer::new("local", "node", vec![]),
+                &ConfigScope::Project,
+                Some(&project),
+            )
+            .unwrap();
+
+        let respelled = project.join(".claude").join("..");
+        manager
+            .disable_all(&[], &ConfigScope::Project, Some(&respelled))
+            .unwrap();
+
+        let snapshot = manager
+            .latest_enabled_snapshot(&ConfigScope::Project, Some(&project))
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            snapshot.config_path,
+            project
+                .canonicalize()
+                .unwrap()
+                .join(".claude")
+                .join("config.json")
+        );
+        let restore = manager
+            .restore_enabled(&snapshot, &ConfigScope::Project, Some(&project))
+            .unwrap();
+        assert_eq!(restore.restored, ["local"]);
+    }
+
+    // TDD Test 25: Exports redact secrets unless asked to include them
+    #[test]
+    fn test_export_servers_redacts_secrets() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = create_test_manager(temp_dir.path());
+        manager
+            .add_server(
+                "github",
+                McpServer::new("github", "npx", vec!["--token".into(), "abc123".into()])
+                    .with_env("GITHUB_HOST", "github.example.com"),
+                &ConfigScope::Global,
+                None,
+            )
+            .unwrap();
+        let export = temp_dir.path().join("servers.json");
+
+        manager
+            .export_servers(&ConfigScope::Global, None, &export, false)
+            .unwrap();
+        let exported: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&export).unwrap()).unwrap();
+        assert_eq!(exported["github"]["env"]["GITHUB_HOST"], "<redacted>");
+        assert_eq!(exported["github"]["args"][1], "<redacted>");
+        assert_eq!(exported["github"]["command"], "npx");
+
+        manager
+            .export_servers(&ConfigScope::Global, None, &export, true)
+            .unwrap();
+        let exported = fs::read_to_string(&export).unwrap();
+        assert!(exported.contains("github.example.com"));
+        assert!(exported.contains("abc123"));
+    }
+
+    // TDD Test 26: Importing a redacted export keeps the configured secrets
+    #[test]
+    fn test_import_redacted_export_keeps_secrets() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = create_test_manager(temp_dir.path());
+        let github = McpServer::new(
+            "github",
+            "npx",
+            vec!["--token".into(), "abc123".into(), "--api-key=xyz789".into()],
+        )
+        .with_env("GITHUB_TOKEN", "ghp_1234567890abcdef");
+        manager
+            .add_server("github", github.clone(), &ConfigScope::Global, None)
+            .unwrap();
+        let export = temp_dir.path().join("servers.json");
+        manager
+            .export_servers(&ConfigScope::Global, None, &export, false)
+            .unwrap();
+
+        let imported = manager
+            .import_servers(&export, &ConfigScope::Global, None, true)
+            .unwrap();
+        assert_eq!(imported.unchanged, ["github"]);
+        let server = manager
+            .get_server("github", &ConfigScope::Global, None)
+            .unwrap();
+        assert!(server.semantically_equal(&github));
+
+        // Without a configured server there is nothing to keep
+        manager
+            .remove_server("github", &ConfigScope::Global, None)
+            .unwrap();
+        let err = manager
+            .import_servers(&export, &ConfigScope::Global, None, false)
+            .unwrap_err();
+        assert!(err.to_string().contains("redacted value"));
+        assert!(manager
+            .list_servers(&ConfigScope::Global, None)
+            .unwrap()
+            .is_empty());
+    }
 }
@@ -1,6 +1,12 @@
 //! MCP Server management module
 
+pub mod audit;
 pub mod manager;
+pub mod origin;
+pub mod snapshot;
 
 // Re-exports
-pub use manager::McpManager;
+pub use audit::{AuditEntry, AuditLog, McpOp};
+pub use manager::{McpManager, ServerImport};
+pub use origin::{MergedServer, MAX_CHANGE_SCAN};
+pub use snapshot::{EnabledRestore, EnabledSnapshot};
@@ -0,0 +1,138 @@
+//! Where effective MCP servers come from, and when they last changed
+//!
+//! The last change of a server is derived from backup history: a backup is
+//! taken right before each write, so the newest backup whose copy of the
+//! server differs from the current one was taken just before the change.
+//! Only the newest [`MAX_CHANGE_SCAN`] backups are read, so the answer is
+//! best effort: servers that didn't change within them report `None`.
+
+use crate::{
+    backup::read_backup,
+    config::ClaudeConfig,
+    hash::content_hash,
+    types::{BackupInfo, ConfigScope, McpServer},
+};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// Maximum number of backups read per config file to find last changes
+pub const MAX_CHANGE_SCAN: usize = 20;
+
+/// An effective server with the file that defines it
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MergedServer {
+    /// Server name
+    pub name: String,
+
+    /// Effective server definition
+    pub server: McpServer,
+
+    /// Scope of the file that defines the server
+    pub origin: ConfigScope,
+
+    /// Config file that defines the server
+    pub origin_path: PathBuf,
+
+    /// When the server last changed, if backup history shows it
+    pub last_changed: Option<DateTime<Utc>>,
+}
+
+/// Last change of each server of one config file, by server name
+pub type LastChanges = HashMap<String, Option<DateTime<Utc>>>;
+
+/// Cache of last-change scans
+///
+/// Keyed by config file; an entry is reused while the file's servers and
+/// its backup set stay the same. Clones share the cache.
+#[derive(Debug, Clone, Default)]
+pub struct ChangeCache {
+    entries: Arc<Mutex<HashMap<PathBuf, (String, LastChanges)>>>,
+}
+
+impl ChangeCache {
+    /// Last change of every server in `current`
+    ///
+    /// # Arguments
+    /// * `config_path` - Config file the servers are read from
+    /// * `current` - Current content of the file
+    /// * `backups` - Backups of the file, newest first
+    pub fn last_changed(
+        &self,
+        config_path: &Path,
+        current: &ClaudeConfig,
+        backups: &[BackupInfo],
+    ) -> LastChanges {
+        let servers = server_values(current);
+        let scanned = &backups[..backups.len().min(MAX_CHANGE_SCAN)];
+        let key = cache_key(&servers, scanned);
+
+        let mut entries = self
+            .entries
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if let Some((cached_key, changes)) = entries.get(config_path) {
+            if *cached_key == key {
+                return changes.clone();
+            }
+        }
+
+        let changes = scan_last_changed(&servers, scanned);
+        entries.insert(config_path.to_path_buf(), (key, changes.clone()));
+        changes
+    }
+}
+
+/// Find the newest backup in which each server differs from `servers`
+fn scan_last_changed(servers: &HashMap<String, Value>, backups: &[BackupInfo]) -> LastChanges {
+    let mut changes: LastChanges = servers.keys().map(|name| (name.clone(), None)).collect();
+
+    for backup in backups {
+        if changes.values().all(Option::is_some) {
+            break;
+        }
+        // Unreadable backups say nothing about changes; skip them
+        let Ok(config) = read_backup(Path::new(&backup.path)) else {
+            continue;
+        };
+        let previous = server_values(&config);
+
+        for (name, changed) in changes.iter_mut().filter(|(_, c)| c.is_none()) {
+            if previous.get(name) != servers.get(name) {
+                *changed = Some(backup.created_at);
+            }
+        }
+    }
+
+    changes
+}
+
+/// Servers of a config as JSON values, for comparison
+fn server_values(config: &ClaudeConfig) -> HashMap<String, Value> {
+    config
+        .mcp_servers
+        .iter()
+        .flatten()
+        .map(|(name, server)| {
+            // Names only reflect the map key, so don't compare them
+            let mut value = serde_json::to_value(server).unwrap_or(Value::Null);
+            if let Some(object) = value.as_object_mut() {
+                object.remove("name");
+            }
+            (name.clone(), value)
+        })
+        .collect()
+}
+
+/// Hash of the servers and the backups a scan would read
+fn cache_key(servers: &HashMap<String, Value>, backups: &[BackupInfo]) -> String {
+    let mut servers: Vec<_> = servers.iter().collect();
+    servers.sort_by(|a, b| a.0.cmp(b.0));
+    let backups: Vec<(&str, u64)> = backups.iter().map(|b| (b.path.as_str(), b.size)).collect();
+    let key = serde_json::to_string(&(servers, backups)).unwrap_or_default();
+    content_hash(key.as_bytes())
+}
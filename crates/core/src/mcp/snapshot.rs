@@ -0,0 +1,148 @@
+//! Snapshots of which MCP servers are enabled
+//!
+//! [`McpManager::disable_all`](super::McpManager::disable_all) records the
+//! enabled flag of every server before turning them off, so
+//! [`restore_enabled`](super::McpManager::restore_enabled) can put back
+//! exactly the previous set later.
+//!
+//! Snapshots are stored as JSON in the `mcp-enabled` subfolder of the backup
+//! directory and named `<config file stem>_<timestamp>.json`. Each records the
+//! config file it was taken from, so snapshots of different configs sharing
+//! a backup directory don't mix.
+
+use crate::{
+    config::manager::atomic_write,
+    error::{ConfigError, Result},
+    paths::canonical_path,
+    types::{ConfigScope, McpServer},
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Subfolder of the backup directory holding enabled-set snapshots
+const SNAPSHOT_DIR: &str = "mcp-enabled";
+
+/// Timestamp format of snapshot file names (microseconds avoid collisions)
+const TIMESTAMP_FORMAT: &str = "%Y%m%d_%H%M%S%.6f";
+
+/// Enabled flags of a config's MCP servers at one point in time
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EnabledSnapshot {
+    /// When the snapshot was taken
+    pub created_at: DateTime<Utc>,
+
+    /// Scope of the config the flags were read from
+    pub scope: ConfigScope,
+
+    /// Config file the flags were read from, as a canonical path
+    pub config_path: PathBuf,
+
+    /// Enabled flag of each server, by server name
+    pub enabled: BTreeMap<String, bool>,
+
+    /// File the snapshot was saved to (`None` if it wasn't saved)
+    #[serde(skip)]
+    pub path: Option<PathBuf>,
+}
+
+impl EnabledSnapshot {
+    /// Record the enabled flags of `servers`
+    pub fn capture(
+        servers: &HashMap<String, McpServer>,
+        scope: ConfigScope,
+        config_path: &Path,
+    ) -> Self {
+        Self {
+            created_at: Utc::now(),
+            scope,
+            config_path: canonical_path(config_path),
+            enabled: servers
+                .iter()
+                .map(|(name, server)| (name.clone(), server.enabled))
+                .collect(),
+            path: None,
+        }
+    }
+
+    /// Save the snapshot in `backup_dir`, recording where in [`path`](Self::path)
+    ///
+    /// # Errors
+    /// Returns an error if the snapshot folder or file cannot be written
+    pub fn save(&mut self, backup_dir: &Path) -> Result<()> {
+        let stem = self
+            .config_path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("config");
+        let name = format!("{stem}_{}.json", self.created_at.format(TIMESTAMP_FORMAT));
+        let path = backup_dir.join(SNAPSHOT_DIR).join(name);
+
+        atomic_write(&path, &serde_json::to_string_pretty(self)?)?;
+        self.path = Some(path);
+        Ok(())
+    }
+
+    /// Most recent snapshot in `backup_dir` taken from `config_path`
+    ///
+    /// Any spelling of the config's path finds its snapshots. Files that
+    /// don't parse as snapshots are skipped.
+    ///
+    /// # Errors
+    /// Returns an error if the snapshot folder exists but cannot be read
+    pub fn latest(backup_dir: &Path, config_path: &Path) -> Result<Option<Self>> {
+        let dir = backup_dir.join(SNAPSHOT_DIR);
+        if !dir.is_dir() {
+            return Ok(None);
+        }
+
+        let config_path = canonical_path(config_path);
+        let mut latest: Option<Self> = None;
+        for entry in fs::read_dir(&dir)
+            .map_err(|e| ConfigError::filesystem("read snapshot directory", &dir, e))?
+        {
+            let path = entry
+                .map_err(|e| ConfigError::filesystem("read snapshot entry", &dir, e))?
+                .path();
+            let Some(mut snapshot) = fs::read_to_string(&path)
+                .ok()
+                .and_then(|content| serde_json::from_str::<Self>(&content).ok())
+            else {
+                continue;
+            };
+            if !snapshot.taken_from(&config_path) {
+                continue;
+            }
+            if latest
+                .as_ref()
+                .map_or(true, |newest| snapshot.created_at > newest.created_at)
+            {
+                snapshot.path = Some(path);
+                latest = Some(snapshot);
+            }
+        }
+
+        Ok(latest)
+    }
+
+    /// Whether the snapshot was taken from the config at `config_path`
+    pub fn taken_from(&self, config_path: &Path) -> bool {
+        canonical_path(&self.config_path) == canonical_path(config_path)
+    }
+}
+
+/// Outcome of [`McpManager::restore_enabled`](super::McpManager::restore_enabled)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EnabledRestore {
+    /// Servers whose enabled flag was changed back
+    pub restored: Vec<String>,
+
+    /// Servers that already had their recorded flag
+    pub unchanged: Vec<String>,
+
+    /// Servers in the snapshot that no longer exist (skipped)
+    pub missing: Vec<String>,
+}
@@ -0,0 +1,116 @@
+//! Partial-failure results for bulk operations
+//!
+//! Operations that touch many files (every project under a directory, say)
+//! should not abort because one of them is unreadable. They collect a
+//! [`MultiResult`] instead, and the caller decides whether failures are
+//! fatal.
+
+use crate::error::ConfigError;
+use std::path::PathBuf;
+
+/// Outcome of an operation applied to several files
+#[derive(Debug)]
+pub struct MultiResult<T> {
+    /// Files that were processed, with their results
+    pub successes: Vec<(PathBuf, T)>,
+
+    /// Files that failed, with their errors
+    pub failures: Vec<(PathBuf, ConfigError)>,
+}
+
+impl<T> Default for MultiResult<T> {
+    fn default() -> Self {
+        Self {
+            successes: Vec::new(),
+            failures: Vec::new(),
+        }
+    }
+}
+
+impl<T> MultiResult<T> {
+    /// Create an empty result
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record the result for one file
+    pub fn push(&mut self, path: impl Into<PathBuf>, result: crate::Result<T>) {
+        match result {
+            Ok(value) => self.successes.push((path.into(), value)),
+            Err(error) => self.failures.push((path.into(), error)),
+        }
+    }
+
+    /// Whether any file failed
+    pub fn has_failures(&self) -> bool {
+        !self.failures.is_empty()
+    }
+
+    /// Total number of files processed
+    pub fn len(&self) -> usize {
+        self.successes.len() + self.failures.len()
+    }
+
+    /// Whether no files were processed
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Transform the successful values, keeping failures as they are
+    pub fn map<U>(self, mut f: impl FnMut(&PathBuf, T) -> U) -> MultiResult<U> {
+        MultiResult {
+            successes: self
+                .successes
+                .into_iter()
+                .map(|(path, value)| {
+                    let mapped = f(&path, value);
+                    (path, mapped)
+                })
+                .collect(),
+            failures: self.failures,
+        }
+    }
+
+    /// Human-readable list of failures, one per line
+    pub fn failure_summary(&self) -> String {
+        let mut summary = format!(
+            "{} of {} file(s) could not be processed:",
+            self.failures.len(),
+            self.len()
+        );
+        for (path, error) in &self.failures {
+            summary.push_str(&format!("\n  {}: {}", path.display(), error));
+        }
+        summary
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // TDD Test 1: Results are split into successes and failures
+    #[test]
+    fn test_multi_result_push_and_summary() {
+        let mut result = MultiResult::new();
+        result.push("/a/config.json", Ok(1));
+        result.push(
+            "/b/config.json",
+            Err(ConfigError::invalid_json("/b/config.json", 1, 2, "oops")),
+        );
+        result.push("/c/config.json", Ok(3));
+
+        assert_eq!(result.len(), 3);
+        assert!(result.has_failures());
+        assert_eq!(result.successes.len(), 2);
+
+        let summary = result.failure_summary();
+        assert!(summary.starts_with("1 of 3 file(s)"));
+        assert!(summary.contains("/b/config.json"));
+        assert!(!summary.contains("/a/config.json"));
+
+        let doubled = result.map(|_, value| value * 2);
+        assert_eq!(doubled.successes[1].1, 6);
+        assert_eq!(doubled.failures.len(), 1);
+    }
+}
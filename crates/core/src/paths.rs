@@ -3,54 +3,183 @@
 //! This module provides functionality for:
 //! - Resolving platform-specific configuration paths
 //! - Detecting project configuration files by searching upward
+//! - Working around Windows path limits (reserved names, MAX_PATH)
 
+use crate::error::ConfigError;
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
+/// Longest path Windows APIs accept without the extended-length prefix
+pub const WINDOWS_MAX_PATH: usize = 260;
+
+/// Prefix that lifts the MAX_PATH limit and device-name parsing on Windows
+const EXTENDED_LENGTH_PREFIX: &str = r"\\?\";
+
+/// Device names Windows reserves in every directory
+const WINDOWS_RESERVED_NAMES: [&str; 22] = [
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// Operating system family, which decides where the global config lives
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Platform {
+    /// Windows: `%APPDATA%\claude`
+    Windows,
+    /// macOS: `~/Library/Application Support/Claude`
+    MacOs,
+    /// Linux and other Unix systems: `~/.config/claude`
+    Linux,
+}
+
+impl Platform {
+    /// Every platform, in the order their names are listed in messages
+    pub const ALL: [Platform; 3] = [Platform::MacOs, Platform::Linux, Platform::Windows];
+
+    /// Platform this binary was compiled for
+    pub fn current() -> Self {
+        if cfg!(windows) {
+            Platform::Windows
+        } else if cfg!(target_os = "macos") {
+            Platform::MacOs
+        } else {
+            Platform::Linux
+        }
+    }
+
+    /// Name of the platform in config files (`macos`, `linux` or `windows`)
+    pub fn key(self) -> &'static str {
+        match self {
+            Platform::Windows => "windows",
+            Platform::MacOs => "macos",
+            Platform::Linux => "linux",
+        }
+    }
+
+    /// Platform named `key` in config files (case-insensitive)
+    pub fn from_key(key: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|platform| platform.key().eq_ignore_ascii_case(key))
+    }
+}
+
+impl std::str::FromStr for Platform {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> Result<Self, ConfigError> {
+        Self::from_key(s).ok_or_else(|| {
+            ConfigError::validation_failed(
+                "Platform",
+                format!("Unknown platform '{s}'"),
+                "Use macos, linux or windows",
+            )
+        })
+    }
+}
+
+/// Source of environment variables for path resolution
+///
+/// Reads the process environment by default; tests can supply fixed values.
+#[derive(Debug, Clone, Default)]
+pub struct EnvReader {
+    /// Fixed variables (None = read the process environment)
+    vars: Option<HashMap<String, String>>,
+}
+
+impl EnvReader {
+    /// Read variables from the process environment
+    pub fn process() -> Self {
+        Self { vars: None }
+    }
+
+    /// Read variables from a fixed set of name/value pairs
+    pub fn from_vars<K, V>(vars: impl IntoIterator<Item = (K, V)>) -> Self
+    where
+        K: Into<String>,
+        V: Into<String>,
+    {
+        Self {
+            vars: Some(
+                vars.into_iter()
+                    .map(|(k, v)| (k.into(), v.into()))
+                    .collect(),
+            ),
+        }
+    }
+
+    /// Get a variable's value, if set and non-empty
+    pub fn var(&self, name: &str) -> Option<String> {
+        let value = match &self.vars {
+            Some(vars) => vars.get(name).cloned(),
+            None => std::env::var(name).ok(),
+        };
+        value.filter(|v| !v.is_empty())
+    }
+}
+
+/// Environment variable that relocates everything ccm stores globally
+///
+/// When set, it replaces the global configuration directory, so the global
+/// config, backups, registry, state, usage data, logs and the daemon socket
+/// all live below it. Used to sandbox test runs and CI.
+pub const CCM_HOME_VAR: &str = "CCM_HOME";
+
 /// Get the default global configuration directory
 ///
-/// Returns platform-specific path:
+/// Returns `$CCM_HOME` when set, otherwise the platform-specific path:
 /// - Windows: `%APPDATA%\claude`
 /// - macOS: `~/Library/Application Support/Claude`
 /// - Linux: `~/.config/claude`
 pub fn get_global_config_dir() -> PathBuf {
-    // Use dirs crate for cross-platform path resolution
-    if cfg!(windows) {
-        // Windows: %APPDATA%\claude
-        dirs::config_dir()
-            .map(|dir| dir.join("claude"))
-            .unwrap_or_else(|| {
-                // Fallback if dirs crate fails
-                let mut path = PathBuf::new();
-                if let Ok(appdata) = std::env::var("APPDATA") {
-                    path.push(appdata);
-                }
-                path.push("claude");
-                path
-            })
-    } else if cfg!(target_os = "macos") {
-        // macOS: ~/Library/Application Support/Claude
-        dirs::config_dir()
-            .map(|dir| dir.join("Claude"))
-            .unwrap_or_else(|| {
-                // Fallback
-                let mut home = PathBuf::from("~");
-                home.push("Library");
-                home.push("Application Support");
-                home.push("Claude");
-                home
-            })
-    } else {
-        // Linux/Unix: ~/.config/claude
-        dirs::config_dir()
-            .map(|dir| dir.join("claude"))
-            .unwrap_or_else(|| {
-                // Fallback
-                let mut home = PathBuf::from("~");
-                home.push(".config");
-                home.push("claude");
-                home
-            })
+    resolve_config_dir(
+        Platform::current(),
+        dirs::config_dir(),
+        &EnvReader::process(),
+    )
+}
+
+/// Resolve the global configuration directory for a platform
+///
+/// [`CCM_HOME_VAR`] overrides everything else. Otherwise the platform config
+/// directory (as reported by the `dirs` crate) takes precedence; environment
+/// variables are only used as a fallback when it is unavailable.
+///
+/// # Arguments
+/// * `platform` - Platform to resolve for
+/// * `dirs_config` - Platform config directory, if known
+/// * `env` - Environment used for fallbacks
+///
+/// # Returns
+/// `$CCM_HOME`, or the `claude` (or `Claude` on macOS) directory inside the
+/// config directory
+pub fn resolve_config_dir(
+    platform: Platform,
+    dirs_config: Option<PathBuf>,
+    env: &EnvReader,
+) -> PathBuf {
+    if let Some(home) = env.var(CCM_HOME_VAR) {
+        return PathBuf::from(home);
     }
+
+    let subdir = match platform {
+        Platform::MacOs => "Claude",
+        Platform::Windows | Platform::Linux => "claude",
+    };
+
+    if let Some(dir) = dirs_config {
+        return dir.join(subdir);
+    }
+
+    // Fallback if dirs crate fails
+    let home = || PathBuf::from(env.var("HOME").unwrap_or_else(|| "~".to_string()));
+    let base = match platform {
+        Platform::Windows => env.var("APPDATA").map(PathBuf::from).unwrap_or_default(),
+        Platform::MacOs => home().join("Library").join("Application Support"),
+        Platform::Linux => home().join(".config"),
+    };
+
+    base.join(subdir)
 }
 
 /// Get the global configuration file path
@@ -60,6 +189,13 @@ pub fn get_global_config_path() -> PathBuf {
     get_global_config_dir().join("config.json")
 }
 
+/// Get the project configuration file path for a project root
+///
+/// Returns `<project_root>/.claude/config.json`
+pub fn project_config_path(project_root: &Path) -> PathBuf {
+    project_root.join(".claude").join("config.json")
+}
+
 /// Find project configuration by searching upward
 ///
 /// Starts from `start_dir` and searches upward for `.claude/config.json`.
@@ -88,7 +224,7 @@ pub fn find_project_config(start_dir: Option<&Path>) -> Option<PathBuf> {
 
     loop {
         // Check if .claude/config.json exists in current directory
-        let config_path = current.join(".claude").join("config.json");
+        let config_path = project_config_path(&current);
         if config_path.exists() {
             return Some(config_path);
         }
@@ -112,6 +248,63 @@ pub fn find_project_config(start_dir: Option<&Path>) -> Option<PathBuf> {
     }
 }
 
+/// Find all project configuration files by searching upward
+///
+/// Like [`find_project_config`], but also recognizes configs embedded in
+/// `package.json` (`"claude"` key) or `pyproject.toml` (`[tool.claude]`).
+/// Only the nearest directory containing any project config is used.
+///
+/// # Arguments
+/// * `start_dir` - Directory to start searching from
+///
+/// # Returns
+/// The config files found, lowest precedence first: the embedded manifest
+/// (if any), then `.claude/config.json` (if any)
+pub fn find_project_config_files(start_dir: Option<&Path>) -> Vec<PathBuf> {
+    let mut current: PathBuf = match start_dir {
+        Some(path) => path.to_path_buf(),
+        None => match std::env::current_dir() {
+            Ok(dir) => dir,
+            Err(_) => return Vec::new(),
+        },
+    };
+
+    loop {
+        let files = project_config_files_in(&current);
+        if !files.is_empty() {
+            return files;
+        }
+
+        // Check if we've hit a Git repository root (stop searching)
+        if current.join(".git").exists() {
+            return Vec::new();
+        }
+
+        match current.parent() {
+            Some(parent) if parent != current => {
+                current = parent.to_path_buf();
+            }
+            _ => return Vec::new(),
+        }
+    }
+}
+
+/// Project configuration files directly in `dir`, lowest precedence first
+pub(crate) fn project_config_files_in(dir: &Path) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+
+    if let Some(embedded) = crate::config::embedded::find_embedded_config(dir) {
+        files.push(embedded.path);
+    }
+
+    let config_path = project_config_path(dir);
+    if config_path.exists() {
+        files.push(config_path);
+    }
+
+    files
+}
+
 /// Expand tilde (~) in path to home directory
 ///
 /// # Arguments
@@ -134,6 +327,75 @@ pub fn expand_tilde(path: &Path) -> PathBuf {
     path.to_path_buf()
 }
 
+/// Expand environment variable references in a string
+///
+/// Supports `$VAR` and `${VAR}` forms. References to unset variables are
+/// left unchanged.
+///
+/// # Arguments
+/// * `value` - String that may contain variable references
+///
+/// # Returns
+/// String with set variables replaced by their values
+pub fn expand_env(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    let mut rest = value;
+
+    while let Some(start) = rest.find('$') {
+        result.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+
+        // Determine variable name and the length of the whole reference
+        let (name, consumed) = if let Some(braced) = after.strip_prefix('{') {
+            match braced.find('}') {
+                Some(end) => (&braced[..end], end + 2),
+                None => ("", 0),
+            }
+        } else {
+            let end = after
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                .unwrap_or(after.len());
+            (&after[..end], end)
+        };
+
+        match std::env::var(name) {
+            Ok(expanded) if !name.is_empty() => {
+                result.push_str(&expanded);
+                rest = &after[consumed..];
+            }
+            _ => {
+                // Leave unresolvable references as written
+                result.push('$');
+                rest = after;
+            }
+        }
+    }
+
+    result.push_str(rest);
+    result
+}
+
+/// Absolute form of `path` with symlinks resolved, as far as it exists
+///
+/// A file that doesn't exist yet resolves through its nearest existing
+/// ancestor, so a path names the same file before and after it's created.
+/// Use this to compare paths that may be spelled differently, e.g.
+/// `./.claude/config.json` and `/work/app/.claude/config.json`.
+pub fn canonical_path(path: &Path) -> PathBuf {
+    if let Ok(canonical) = path.canonicalize() {
+        return canonical;
+    }
+
+    match (path.parent(), path.file_name()) {
+        (Some(parent), Some(name)) if !parent.as_os_str().is_empty() => {
+            canonical_path(parent).join(name)
+        }
+        _ => std::env::current_dir()
+            .map(|cwd| canonical_path(&cwd).join(path))
+            .unwrap_or_else(|_| path.to_path_buf()),
+    }
+}
+
 /// Get the backup directory path
 ///
 /// Returns `<config_dir>/backups`
@@ -141,6 +403,224 @@ pub fn get_backup_dir() -> PathBuf {
     get_global_config_dir().join("backups")
 }
 
+/// Get the per-project UI state directory path
+///
+/// Returns `<config_dir>/state`
+pub fn get_state_dir() -> PathBuf {
+    get_global_config_dir().join("state")
+}
+
+/// Get the local usage statistics file path
+///
+/// Returns `<config_dir>/usage.json`
+pub fn get_usage_path() -> PathBuf {
+    get_global_config_dir().join("usage.json")
+}
+
+/// Get the project registry path
+///
+/// Returns `<config_dir>/projects.json`
+pub fn get_registry_path() -> PathBuf {
+    get_global_config_dir().join("projects.json")
+}
+
+/// Get the daemon socket path
+///
+/// Returns `<config_dir>/daemon/ccm.sock`
+#[cfg(not(windows))]
+pub fn get_daemon_socket_path() -> PathBuf {
+    get_global_config_dir().join("daemon").join("ccm.sock")
+}
+
+/// Get the daemon's named pipe
+///
+/// Returns `\\.\pipe\ccm-<hash of the config dir>`, so each config dir
+/// (and so each user and `CCM_HOME`) gets its own daemon
+#[cfg(windows)]
+pub fn get_daemon_socket_path() -> PathBuf {
+    let dir = get_global_config_dir();
+    let hash = crate::hash::content_hash(dir.to_string_lossy().as_bytes());
+    PathBuf::from(format!(r"\\.\pipe\ccm-{hash}"))
+}
+
+/// Get the MCP operation log path
+///
+/// Returns `<config_dir>/ccm-ops.log`
+pub fn get_audit_log_path() -> PathBuf {
+    get_global_config_dir().join("ccm-ops.log")
+}
+
+/// Environment variable naming a file that ccm appends its log to
+pub const CCM_LOG_FILE_VAR: &str = "CCM_LOG_FILE";
+
+/// Get the log file path, if logging to a file is configured
+///
+/// Returns the path in [`CCM_LOG_FILE_VAR`]; logs only go to stderr when
+/// it is unset or empty.
+pub fn get_log_path() -> Option<PathBuf> {
+    std::env::var_os(CCM_LOG_FILE_VAR)
+        .filter(|path| !path.is_empty())
+        .map(PathBuf::from)
+}
+
+/// Whether a file or directory name is a reserved device name on Windows
+///
+/// Windows ignores the extension and trailing dots or spaces here, so
+/// `aux.json` and `NUL ` are reserved as well.
+pub fn is_windows_reserved_name(name: &str) -> bool {
+    let base = name
+        .split('.')
+        .next()
+        .unwrap_or(name)
+        .trim_end_matches([' ', '.']);
+
+    WINDOWS_RESERVED_NAMES
+        .iter()
+        .any(|reserved| reserved.eq_ignore_ascii_case(base))
+}
+
+/// Adjust a generated file name so it can be created on `platform`
+///
+/// On Windows, reserved device names get an underscore appended to their
+/// base name (`aux.json` becomes `aux_.json`). Everything else, and every
+/// name on other platforms, is returned unchanged.
+pub fn file_name_for(platform: Platform, name: &str) -> String {
+    if platform != Platform::Windows || !is_windows_reserved_name(name) {
+        return name.to_string();
+    }
+
+    match name.find('.') {
+        Some(dot) => format!("{}_{}", &name[..dot], &name[dot..]),
+        None => format!("{name}_"),
+    }
+}
+
+/// Add the `\\?\` extended-length prefix where Windows needs it
+///
+/// On other platforms the path is returned unchanged.
+pub fn to_extended_length(path: &Path) -> PathBuf {
+    extended_length_for(Platform::current(), path)
+}
+
+/// [`to_extended_length`] for an explicit platform
+///
+/// Absolute drive (`C:\...`) and UNC (`\\server\share\...`) paths are
+/// prefixed when they reach [`WINDOWS_MAX_PATH`] or contain a reserved
+/// device name. Relative and already-prefixed paths are left alone, since
+/// the prefix only works on absolute paths.
+pub fn extended_length_for(platform: Platform, path: &Path) -> PathBuf {
+    if platform != Platform::Windows {
+        return path.to_path_buf();
+    }
+
+    let text = path.to_string_lossy();
+    let needs_prefix =
+        text.len() >= WINDOWS_MAX_PATH || windows_components(&text).any(is_windows_reserved_name);
+    if !needs_prefix || text.starts_with(EXTENDED_LENGTH_PREFIX) {
+        return path.to_path_buf();
+    }
+
+    // Prefixed paths are passed to the filesystem verbatim, so separators
+    // must already be backslashes
+    let text = text.replace('/', "\\");
+    if let Some(unc) = text.strip_prefix(r"\\") {
+        PathBuf::from(format!(r"{EXTENDED_LENGTH_PREFIX}UNC\{unc}"))
+    } else if is_drive_absolute(&text) {
+        PathBuf::from(format!("{EXTENDED_LENGTH_PREFIX}{text}"))
+    } else {
+        path.to_path_buf()
+    }
+}
+
+/// Describe the Windows path limit a path runs into, if any
+///
+/// # Arguments
+/// * `platform` - Platform the path is used on
+/// * `path` - Path to check
+///
+/// # Returns
+/// A description naming the offending component, or None on other
+/// platforms and for paths within the limits
+pub fn windows_path_limitation(platform: Platform, path: &Path) -> Option<String> {
+    if platform != Platform::Windows {
+        return None;
+    }
+
+    let text = path.to_string_lossy();
+    let text = text.strip_prefix(EXTENDED_LENGTH_PREFIX).unwrap_or(&text);
+
+    if let Some(reserved) = windows_components(text).find(|c| is_windows_reserved_name(c)) {
+        return Some(format!(
+            "'{reserved}' is a reserved device name on Windows (CON, PRN, AUX, NUL, COM1-9, LPT1-9)"
+        ));
+    }
+
+    if text.len() >= WINDOWS_MAX_PATH {
+        // Name the component that crosses the limit
+        let mut length = 0;
+        let offending = text
+            .split(['\\', '/'])
+            .find(|component| {
+                length += component.len() + 1;
+                length > WINDOWS_MAX_PATH
+            })
+            .unwrap_or_default();
+        return Some(format!(
+            "path is {} characters, over the Windows MAX_PATH limit of {WINDOWS_MAX_PATH} (exceeded at '{offending}')",
+            text.len()
+        ));
+    }
+
+    None
+}
+
+/// Convert an I/O error into a ConfigError, naming Windows path limits
+///
+/// When `path` or one of `related` hits a Windows path limit, the error
+/// explains that limit; otherwise this is [`ConfigError::filesystem`] for
+/// `path`, exactly as on other platforms.
+pub(crate) fn filesystem_error(
+    operation: &str,
+    path: &Path,
+    related: &[&Path],
+    source: std::io::Error,
+) -> ConfigError {
+    filesystem_error_for(Platform::current(), operation, path, related, source)
+}
+
+/// [`filesystem_error`] for an explicit platform
+fn filesystem_error_for(
+    platform: Platform,
+    operation: &str,
+    path: &Path,
+    related: &[&Path],
+    source: std::io::Error,
+) -> ConfigError {
+    let limited = std::iter::once(path)
+        .chain(related.iter().copied())
+        .find_map(|candidate| {
+            windows_path_limitation(platform, candidate).map(|limitation| (candidate, limitation))
+        });
+
+    match limited {
+        Some((candidate, limitation)) => {
+            ConfigError::windows_path_limit(operation, candidate, limitation, source)
+        }
+        None => ConfigError::filesystem(operation, path, source),
+    }
+}
+
+/// Path components, splitting on both Windows and Unix separators
+fn windows_components(path: &str) -> impl Iterator<Item = &str> {
+    path.split(['\\', '/']).filter(|c| !c.is_empty())
+}
+
+/// Whether a path starts with a drive letter and root, like `C:\`
+fn is_drive_absolute(path: &str) -> bool {
+    let bytes = path.as_bytes();
+    bytes.len() >= 3 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':' && bytes[2] == b'\\'
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -159,6 +639,83 @@ mod tests {
         assert!(last_component.is_some());
     }
 
+    // TDD Test 1b: Each platform gets its own subdirectory and fallbacks
+    #[test]
+    fn test_resolve_config_dir_per_platform() {
+        let env = EnvReader::from_vars([
+            ("HOME", "/home/u"),
+            ("APPDATA", "C:\\Users\\u\\AppData\\Roaming"),
+        ]);
+        let no_env = EnvReader::from_vars(Vec::<(String, String)>::new());
+        let dirs_config = || Some(PathBuf::from("/cfg"));
+
+        // The platform config dir wins over the environment
+        assert_eq!(
+            resolve_config_dir(Platform::Windows, dirs_config(), &env),
+            Path::new("/cfg/claude")
+        );
+        assert_eq!(
+            resolve_config_dir(Platform::MacOs, dirs_config(), &env),
+            Path::new("/cfg/Claude")
+        );
+        assert_eq!(
+            resolve_config_dir(Platform::Linux, dirs_config(), &env),
+            Path::new("/cfg/claude")
+        );
+
+        // Environment fallbacks
+        assert_eq!(
+            resolve_config_dir(Platform::Windows, None, &env),
+            Path::new("C:\\Users\\u\\AppData\\Roaming").join("claude")
+        );
+        assert_eq!(
+            resolve_config_dir(Platform::MacOs, None, &env),
+            Path::new("/home/u/Library/Application Support/Claude")
+        );
+        assert_eq!(
+            resolve_config_dir(Platform::Linux, None, &env),
+            Path::new("/home/u/.config/claude")
+        );
+
+        // Nothing known at all
+        assert_eq!(
+            resolve_config_dir(Platform::Windows, None, &no_env),
+            Path::new("claude")
+        );
+        assert_eq!(
+            resolve_config_dir(Platform::MacOs, None, &no_env),
+            Path::new("~/Library/Application Support/Claude")
+        );
+        assert_eq!(
+            resolve_config_dir(Platform::Linux, None, &no_env),
+            Path::new("~/.config/claude")
+        );
+    }
+
+    // TDD Test 1c: CCM_HOME overrides the platform directory and fallbacks
+    #[test]
+    fn test_ccm_home_overrides_config_dir() {
+        let env = EnvReader::from_vars([("HOME", "/home/u"), ("CCM_HOME", "/tmp/sandbox")]);
+
+        for platform in [Platform::Windows, Platform::MacOs, Platform::Linux] {
+            assert_eq!(
+                resolve_config_dir(platform, Some(PathBuf::from("/cfg")), &env),
+                Path::new("/tmp/sandbox")
+            );
+            assert_eq!(
+                resolve_config_dir(platform, None, &env),
+                Path::new("/tmp/sandbox")
+            );
+        }
+
+        // An empty value is ignored
+        let empty = EnvReader::from_vars([("CCM_HOME", "")]);
+        assert_eq!(
+            resolve_config_dir(Platform::Linux, Some(PathBuf::from("/cfg")), &empty),
+            Path::new("/cfg/claude")
+        );
+    }
+
     // TDD Test 2: Global config path has config.json
     #[test]
     fn test_get_global_config_path_ends_with_config_json() {
@@ -252,4 +809,145 @@ mod tests {
 
         assert_eq!(expanded, path);
     }
+
+    // TDD Test 9: Expand environment variables in both forms
+    #[test]
+    fn test_expand_env_replaces_variables() {
+        std::env::set_var("CCM_TEST_EXPAND_ENV", "/opt/data");
+
+        assert_eq!(expand_env("$CCM_TEST_EXPAND_ENV/logs"), "/opt/data/logs");
+        assert_eq!(expand_env("${CCM_TEST_EXPAND_ENV}-x"), "/opt/data-x");
+    }
+
+    // TDD Test 10: Unset variables are left unchanged
+    #[test]
+    fn test_expand_env_leaves_unset_variables() {
+        assert_eq!(
+            expand_env("$CCM_TEST_UNSET_VAR/a/${CCM_TEST_UNSET_VAR}"),
+            "$CCM_TEST_UNSET_VAR/a/${CCM_TEST_UNSET_VAR}"
+        );
+        assert_eq!(expand_env("cost: $5"), "cost: $5");
+        assert_eq!(expand_env("trailing $"), "trailing $");
+    }
+
+    // TDD Test 11: Reserved device names are detected and adjusted on Windows only
+    #[test]
+    fn test_windows_reserved_names() {
+        for name in ["aux", "AUX", "con.json", "nul ", "Com1.tar.gz", "lpt9"] {
+            assert!(is_windows_reserved_name(name), "{name}");
+        }
+        for name in ["auxiliary", "config.json", "com10", "aux_1.json", ""] {
+            assert!(!is_windows_reserved_name(name), "{name}");
+        }
+
+        assert_eq!(file_name_for(Platform::Windows, "aux.json"), "aux_.json");
+        assert_eq!(file_name_for(Platform::Windows, "NUL"), "NUL_");
+        assert_eq!(
+            file_name_for(Platform::Windows, "config.json"),
+            "config.json"
+        );
+        assert_eq!(file_name_for(Platform::Linux, "aux.json"), "aux.json");
+        assert_eq!(file_name_for(Platform::MacOs, "con"), "con");
+    }
+
+    // TDD Test 12: Long and reserved Windows paths get the extended-length prefix
+    #[test]
+    fn test_extended_length_for() {
+        let long = format!(r"C:\projects\{}\.claude\config.json", "n".repeat(260));
+        assert_eq!(
+            extended_length_for(Platform::Windows, Path::new(&long)),
+            PathBuf::from(format!(r"\\?\{long}"))
+        );
+
+        let unc = format!(r"\\server\share\{}", "n".repeat(260));
+        assert_eq!(
+            extended_length_for(Platform::Windows, Path::new(&unc)),
+            PathBuf::from(format!(r"\\?\UNC\server\share\{}", "n".repeat(260)))
+        );
+
+        assert_eq!(
+            extended_length_for(
+                Platform::Windows,
+                Path::new("C:/src/aux/.claude/config.json")
+            ),
+            PathBuf::from(r"\\?\C:\src\aux\.claude\config.json")
+        );
+
+        // Short, relative, already-prefixed and non-Windows paths are unchanged
+        for (platform, path) in [
+            (Platform::Windows, r"C:\src\project\config.json".to_string()),
+            (Platform::Windows, format!(r"relative\{}", "n".repeat(260))),
+            (Platform::Windows, format!(r"\\?\{long}")),
+            (Platform::Linux, long.clone()),
+            (Platform::MacOs, "/src/aux/config.json".to_string()),
+        ] {
+            assert_eq!(
+                extended_length_for(platform, Path::new(&path)),
+                PathBuf::from(&path)
+            );
+        }
+    }
+
+    // TDD Test 13: OS errors on Windows name the limitation and the component
+    #[test]
+    fn test_filesystem_error_names_windows_limitation() {
+        let io_error = || std::io::Error::other("The system cannot find the path specified");
+        let reserved = Path::new(r"C:\src\aux\.claude\config.json");
+
+        let message = filesystem_error_for(
+            Platform::Windows,
+            "copy file to backup",
+            reserved,
+            &[],
+            io_error(),
+        )
+        .to_string();
+        assert!(message.contains("'aux' is a reserved device name on Windows"));
+        assert!(message.contains("copy file to backup"));
+
+        let long = format!(r"C:\backups\{}\config.json", "n".repeat(300));
+        let message = filesystem_error_for(
+            Platform::Windows,
+            "copy file to backup",
+            Path::new(r"C:\src\config.json"),
+            &[Path::new(&long)],
+            io_error(),
+        )
+        .to_string();
+        assert!(message.contains("MAX_PATH limit of 260"));
+        assert!(message.contains(&format!("exceeded at '{}'", "n".repeat(300))));
+
+        // Other platforms keep the plain filesystem error
+        let error = filesystem_error_for(
+            Platform::Linux,
+            "copy file to backup",
+            reserved,
+            &[],
+            io_error(),
+        );
+        assert!(matches!(error, ConfigError::Filesystem { .. }));
+        assert!(windows_path_limitation(Platform::Windows, Path::new(r"C:\src\a.json")).is_none());
+    }
+
+    // TDD Test 14: Spellings of the same file resolve to the same path
+    #[test]
+    fn test_canonical_path() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let dir = temp_dir.path().join("app");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("config.json"), "{}").unwrap();
+
+        let canonical = canonical_path(&dir.join("config.json"));
+        assert!(canonical.is_absolute());
+        assert_eq!(
+            canonical_path(&dir.join(".").join("config.json")),
+            canonical
+        );
+
+        // Missing files resolve through their existing ancestors
+        assert_eq!(
+            canonical_path(&dir.join("new").join("config.json")),
+            canonical_path(&dir).join("new").join("config.json")
+        );
+    }
 }
@@ -0,0 +1,287 @@
+//! Incremental project scans
+//!
+//! A directory's mtime changes when entries are added to, removed from or
+//! renamed in it. [`ProjectScanner::scan_incremental`] records the mtime and
+//! subdirectories of every directory it reads, and on the next scan reads
+//! only directories whose mtime changed (or that are new). Unchanged
+//! directories reuse their recorded subdirectories.
+//!
+//! A change deep in a tree doesn't touch the mtimes of its ancestors, so
+//! every known directory is still checked (one `stat` each), and projects
+//! are looked up again rather than copied from the previous scan. Only the
+//! directory reads are saved, which are the expensive part of a scan.
+
+use super::{ProjectInfo, ProjectScanner, ScanStats};
+use crate::{collate, error::Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+/// Result of a scan, reusable by the next [`ProjectScanner::scan_incremental`]
+///
+/// Serializable so it can be persisted between runs.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScanState {
+    /// Directory the scan started from
+    pub root: PathBuf,
+
+    /// Projects found, sorted by name
+    pub projects: Vec<ProjectInfo>,
+
+    /// Directories read by the scan (or reused from an earlier one)
+    pub dirs: BTreeMap<PathBuf, DirListing>,
+
+    /// When the scan started (`None` if it never ran)
+    pub scanned_at: Option<DateTime<Utc>>,
+}
+
+impl ScanState {
+    /// State of a directory that was never scanned
+    ///
+    /// Scanning incrementally from it reads the whole tree.
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            projects: Vec::new(),
+            dirs: BTreeMap::new(),
+            scanned_at: None,
+        }
+    }
+
+    /// Recorded listing of `dir`, if it still matches the directory's `modified` time
+    ///
+    /// On filesystems with whole-second mtimes, a change made in the same
+    /// second the directory was read leaves its mtime unchanged. Listings
+    /// with an mtime in or after the second the scan started are therefore
+    /// never reused.
+    fn current_listing(&self, dir: &Path, modified: DateTime<Utc>) -> Option<&DirListing> {
+        let scanned_at = self.scanned_at?;
+        self.dirs
+            .get(dir)
+            .filter(|listing| listing.modified == modified)
+            .filter(|listing| listing.modified.timestamp() < scanned_at.timestamp())
+    }
+}
+
+/// Subdirectories of a directory when it was read
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DirListing {
+    /// Modification time of the directory
+    pub modified: DateTime<Utc>,
+
+    /// Names of its subdirectories
+    pub subdirs: Vec<PathBuf>,
+}
+
+impl ProjectScanner {
+    /// Rescan `prev.root`, reading only directories changed since `prev`
+    ///
+    /// Finds the same projects as [`scan_directory`](Self::scan_directory).
+    /// Projects whose config was removed are dropped. `stats.dirs_visited`
+    /// counts only the directories actually read. Start from
+    /// [`ScanState::new`] for the first scan.
+    ///
+    /// # Arguments
+    /// * `prev` - State returned by the previous scan
+    ///
+    /// # Returns
+    /// The new scan state and statistics of this scan
+    ///
+    /// # Errors
+    /// Returns an error if an entry of a directory being read can't be read
+    pub fn scan_incremental(&self, prev: &ScanState) -> Result<(ScanState, ScanStats)> {
+        self.timings.time("scan", || {
+            let started = Instant::now();
+            let scanned_at = Utc::now();
+            let mut stats = ScanStats::default();
+            let mut dirs = BTreeMap::new();
+            let mut projects = Vec::new();
+            let mut seen = HashSet::new();
+
+            let mut stack = Vec::new();
+            if self.within_depth(0) {
+                stack.push((prev.root.clone(), 0));
+            }
+
+            while let Some((dir, depth)) = stack.pop() {
+                let Some(listing) = list_dir(&dir, prev, &mut stats)? else {
+                    continue;
+                };
+
+                for name in &listing.subdirs {
+                    let path = dir.join(name);
+                    let Some(descend) = self.admit(&path, depth, &mut stats) else {
+                        continue;
+                    };
+                    if descend {
+                        stack.push((path.clone(), depth + 1));
+                    }
+
+                    if let Some(config) = self.project_config(&path) {
+                        if seen.insert(config.clone()) {
+                            projects.push(self.project_info(config));
+                        }
+                    }
+                }
+
+                dirs.insert(dir, listing);
+            }
+
+            projects.sort_by(|a, b| {
+                collate::compare(&a.name, &b.name).then_with(|| a.root.cmp(&b.root))
+            });
+            stats.projects_found = projects.len();
+            stats.elapsed = started.elapsed();
+
+            let state = ScanState {
+                root: prev.root.clone(),
+                projects,
+                dirs,
+                scanned_at: Some(scanned_at),
+            };
+            Ok((state, stats))
+        })
+    }
+}
+
+/// Listing of `dir`, reused from `prev` if the directory is unchanged
+///
+/// Returns `None` for directories that can't be read (e.g. removed since
+/// the previous scan).
+fn list_dir(dir: &Path, prev: &ScanState, stats: &mut ScanStats) -> Result<Option<DirListing>> {
+    let Some(modified) = fs::metadata(dir)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .map(DateTime::<Utc>::from)
+    else {
+        return Ok(None);
+    };
+
+    if let Some(listing) = prev.current_listing(dir, modified) {
+        return Ok(Some(listing.clone()));
+    }
+
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Ok(None);
+    };
+    stats.dirs_visited += 1;
+
+    let mut subdirs = Vec::new();
+    for entry in entries {
+        let path = entry?.path();
+        if path.is_dir() {
+            subdirs.extend(path.file_name().map(PathBuf::from));
+        }
+    }
+    subdirs.sort();
+
+    Ok(Some(DirListing { modified, subdirs }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Create a project with a config in `dir`
+    #[cfg(unix)]
+    fn create_project(dir: &Path) {
+        let claude_dir = dir.join(".claude");
+        fs::create_dir_all(&claude_dir).unwrap();
+        fs::write(claude_dir.join("config.json"), "{}").unwrap();
+    }
+
+    /// Set the mtime of `dir` and every directory below it an hour back
+    #[cfg(unix)]
+    fn backdate(dir: &Path) {
+        use std::time::{Duration, SystemTime};
+
+        let an_hour_ago = SystemTime::now() - Duration::from_secs(3600);
+        for entry in fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                backdate(&path);
+            }
+        }
+        fs::File::open(dir)
+            .unwrap()
+            .set_modified(an_hour_ago)
+            .unwrap();
+    }
+
+    #[cfg(unix)]
+    fn names(state: &ScanState) -> Vec<&str> {
+        state.projects.iter().map(|p| p.name.as_str()).collect()
+    }
+
+    // TDD Test 1: Rescans read only changed directories and drop removed projects
+    #[cfg(unix)]
+    #[test]
+    fn test_scan_incremental() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path();
+        create_project(&root.join("alpha"));
+        create_project(&root.join("beta"));
+        fs::create_dir_all(root.join("work").join("old").join("notes")).unwrap();
+        backdate(root);
+
+        let scanner = ProjectScanner::new(None, false);
+        let (state, stats) = scanner.scan_incremental(&ScanState::new(root)).unwrap();
+        assert_eq!(names(&state), vec!["alpha", "beta"]);
+        assert_eq!(stats.dirs_visited, 6);
+        assert_eq!(
+            scanner.scan_directory(root).unwrap(),
+            state.projects,
+            "incremental and full scans must agree"
+        );
+
+        // Nothing changed: every listing is reused
+        let (state, stats) = scanner.scan_incremental(&state).unwrap();
+        assert_eq!(names(&state), vec!["alpha", "beta"]);
+        assert_eq!(stats.dirs_visited, 0);
+
+        // A new project only changes the mtime of its parent
+        create_project(&root.join("work").join("gamma"));
+        let (state, stats) = scanner.scan_incremental(&state).unwrap();
+        assert_eq!(names(&state), vec!["alpha", "beta", "gamma"]);
+        assert_eq!(stats.dirs_visited, 2, "work and work/gamma");
+
+        // The state survives persistence
+        let json = serde_json::to_string(&state).unwrap();
+        let state: ScanState = serde_json::from_str(&json).unwrap();
+
+        // Removing a config doesn't touch the project directory
+        fs::remove_file(root.join("beta").join(".claude").join("config.json")).unwrap();
+        let (state, stats) = scanner.scan_incremental(&state).unwrap();
+        assert_eq!(names(&state), vec!["alpha", "gamma"]);
+        assert!(stats.dirs_visited <= 2);
+        assert_eq!(scanner.scan_directory(root).unwrap(), state.projects);
+    }
+
+    // TDD Test 2: Listings modified in the second of the scan are read again
+    #[test]
+    fn test_same_second_listing_is_not_reused() {
+        let modified = Utc::now();
+        let mut state = ScanState::new("/projects");
+        state.dirs.insert(
+            PathBuf::from("/projects"),
+            DirListing {
+                modified,
+                subdirs: Vec::new(),
+            },
+        );
+        let dir = Path::new("/projects");
+
+        state.scanned_at = Some(modified);
+        assert!(state.current_listing(dir, modified).is_none());
+
+        state.scanned_at = Some(modified + chrono::Duration::seconds(2));
+        assert!(state.current_listing(dir, modified).is_some());
+        let later = modified + chrono::Duration::milliseconds(1);
+        assert!(state.current_listing(dir, later).is_none());
+    }
+}
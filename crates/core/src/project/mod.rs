@@ -4,11 +4,25 @@
 //! with .claude directories, enabling users to discover and manage multiple
 //! Claude Code configurations.
 
-use crate::{error::Result, paths::find_project_config};
+pub mod incremental;
+pub mod registry;
+
+use crate::{
+    collate,
+    error::Result,
+    paths::{find_project_config_files, get_backup_dir, get_global_config_dir},
+    timings::Timings,
+};
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fmt;
 use std::fs;
 use std::path::{Path, PathBuf};
-use std::time::SystemTime;
+use std::time::{Duration, Instant};
+
+pub use incremental::{DirListing, ScanState};
+pub use registry::{is_project_archived, ProjectRegistry, RegistryEntry};
 
 /// Information about a discovered project
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -19,7 +33,8 @@ pub struct ProjectInfo {
     /// Path to .claude directory
     pub claude_dir: PathBuf,
 
-    /// Path to config file
+    /// Path to config file (a manifest for projects whose only config is
+    /// embedded in `package.json` or `pyproject.toml`)
     pub config_path: PathBuf,
 
     /// Whether config exists
@@ -28,22 +43,27 @@ pub struct ProjectInfo {
     /// Project name (derived from directory name)
     pub name: String,
 
-    /// Last modification time
-    pub last_modified: Option<SystemTime>,
+    /// Last modification time of the config file (serialized as RFC 3339)
+    pub last_modified: Option<DateTime<Utc>>,
+
+    /// Whether the project is archived (see [`is_project_archived`])
+    #[serde(default)]
+    pub archived: bool,
 }
 
 impl ProjectInfo {
     /// Create project info from a discovered config path
+    ///
+    /// The path is either a `.claude/config.json` or a manifest with an
+    /// embedded config in the project root.
     pub fn from_config_path(config_path: PathBuf) -> Self {
-        let claude_dir = config_path
-            .parent()
-            .unwrap_or_else(|| Path::new("."))
-            .to_path_buf();
-
-        let root = claude_dir
-            .parent()
-            .unwrap_or_else(|| Path::new("."))
-            .to_path_buf();
+        let parent = config_path.parent().unwrap_or_else(|| Path::new("."));
+        let (root, claude_dir) = if parent.file_name().is_some_and(|name| name == ".claude") {
+            let root = parent.parent().unwrap_or_else(|| Path::new("."));
+            (root.to_path_buf(), parent.to_path_buf())
+        } else {
+            (parent.to_path_buf(), parent.join(".claude"))
+        };
 
         let name = root
             .file_name()
@@ -55,7 +75,8 @@ impl ProjectInfo {
 
         let last_modified = fs::metadata(&config_path)
             .ok()
-            .and_then(|m| m.modified().ok());
+            .and_then(|m| m.modified().ok())
+            .map(DateTime::<Utc>::from);
 
         Self {
             root,
@@ -64,8 +85,70 @@ impl ProjectInfo {
             has_config,
             name,
             last_modified,
+            archived: false,
         }
     }
+
+    /// Copy of this info with paths relative to `base` (e.g. the scan root)
+    ///
+    /// Sharing relative paths avoids leaking machine-specific home
+    /// directories. Paths outside `base` stay absolute, and a project rooted
+    /// at `base` itself gets `.` as its root.
+    pub fn to_relative(&self, base: &Path) -> ProjectInfo {
+        let relative = |path: &Path| match path.strip_prefix(base) {
+            Ok(stripped) if stripped.as_os_str().is_empty() => PathBuf::from("."),
+            Ok(stripped) => stripped.to_path_buf(),
+            Err(_) => path.to_path_buf(),
+        };
+
+        ProjectInfo {
+            root: relative(&self.root),
+            claude_dir: relative(&self.claude_dir),
+            config_path: relative(&self.config_path),
+            ..self.clone()
+        }
+    }
+}
+
+/// Why a built-in rule keeps the scanner out of a directory
+///
+/// Built-in rules protect ccm's own directories and are applied before (and
+/// independently of) the user ignore list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PruneReason {
+    /// The global config directory
+    ConfigDir,
+    /// A `.claude` directory (checked for a config, but not descended into)
+    ClaudeDir,
+    /// A backup directory
+    BackupDir,
+}
+
+impl fmt::Display for PruneReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            PruneReason::ConfigDir => "ccm config directory",
+            PruneReason::ClaudeDir => ".claude directory",
+            PruneReason::BackupDir => "ccm backup directory",
+        })
+    }
+}
+
+/// Statistics of one scan, from [`ProjectScanner::scan_directory_with_stats`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScanStats {
+    /// Directories whose entries were read (including the start directory)
+    pub dirs_visited: usize,
+
+    /// Projects found
+    pub projects_found: usize,
+
+    /// Directories skipped because they match the ignore list
+    pub skipped_ignored: usize,
+
+    /// Wall-clock time of the scan
+    pub elapsed: Duration,
 }
 
 /// Project scanner for discovering Claude Code projects
@@ -83,6 +166,18 @@ pub struct ProjectScanner {
     /// Whether to use parallel traversal (reserved for future use)
     #[allow(dead_code)]
     parallel: bool,
+
+    /// Timing collector for scans (disabled by default)
+    timings: Timings,
+
+    /// ccm-owned directories that are never scanned
+    owned_dirs: Vec<(PathBuf, PruneReason)>,
+
+    /// Registry consulted for archived flags (config markers only if None)
+    registry: Option<ProjectRegistry>,
+
+    /// Only yield projects whose config is a readable file
+    config_only: bool,
 }
 
 impl ProjectScanner {
@@ -102,7 +197,50 @@ impl ProjectScanner {
                 "build".to_string(),
             ],
             parallel,
+            timings: Timings::disabled(),
+            owned_dirs: Vec::new(),
+            registry: None,
+            config_only: false,
+        }
+        .with_owned_dir(get_global_config_dir(), PruneReason::ConfigDir)
+        .with_owned_dir(get_backup_dir(), PruneReason::BackupDir)
+    }
+
+    /// Never scan `dir` (in addition to the resolved config and backup directories)
+    ///
+    /// # Arguments
+    /// * `dir` - ccm-owned directory to skip
+    /// * `reason` - Why it is skipped
+    pub fn with_owned_dir(mut self, dir: impl Into<PathBuf>, reason: PruneReason) -> Self {
+        let dir = dir.into();
+        if let Ok(canonical) = dir.canonicalize() {
+            if canonical != dir {
+                self.owned_dirs.push((canonical, reason));
+            }
         }
+        self.owned_dirs.push((dir, reason));
+        self
+    }
+
+    /// Record scan timings in `timings`
+    pub fn with_timings(mut self, timings: Timings) -> Self {
+        self.timings = timings;
+        self
+    }
+
+    /// Use `registry` when marking discovered projects as archived
+    pub fn with_registry(mut self, registry: ProjectRegistry) -> Self {
+        self.registry = Some(registry);
+        self
+    }
+
+    /// Only yield projects whose config file exists and can be read
+    ///
+    /// Without this, a project is yielded as soon as its config path exists,
+    /// even if it is a directory or can't be opened.
+    pub fn with_config_only(mut self, config_only: bool) -> Self {
+        self.config_only = config_only;
+        self
     }
 
     /// Add a path pattern to ignore
@@ -119,67 +257,180 @@ impl ProjectScanner {
     /// # Returns
     /// Vector of discovered project information
     pub fn scan_directory(&self, start_path: &Path) -> Result<Vec<ProjectInfo>> {
-        let mut projects = Vec::new();
+        self.scan_directory_filtered(start_path, |_| true)
+    }
 
-        // Scan subdirectories (don't check start_path itself, only its children)
-        self.scan_recursive(start_path, 0, &mut projects)?;
+    /// Scan a directory for projects and report what the scan did
+    ///
+    /// # Arguments
+    /// * `start_path` - Root directory to start scanning
+    ///
+    /// # Returns
+    /// Discovered projects (sorted by name) and the scan statistics
+    pub fn scan_directory_with_stats(
+        &self,
+        start_path: &Path,
+    ) -> Result<(Vec<ProjectInfo>, ScanStats)> {
+        self.scan_with_stats(start_path, |_| true)
+    }
 
-        // Remove duplicates (in case same project found multiple times)
-        projects.sort_by(|a, b| a.root.cmp(&b.root));
-        projects.dedup();
+    /// Scan a directory for projects matching a predicate
+    ///
+    /// The predicate is applied to each discovered project before it is
+    /// included, for selection logic the ignore list can't express (e.g.
+    /// "only projects whose config enables server X").
+    ///
+    /// # Arguments
+    /// * `start_path` - Root directory to start scanning
+    /// * `predicate` - Returns true for projects to include
+    ///
+    /// # Returns
+    /// Vector of matching project information, sorted by name
+    pub fn scan_directory_filtered(
+        &self,
+        start_path: &Path,
+        predicate: impl Fn(&ProjectInfo) -> bool,
+    ) -> Result<Vec<ProjectInfo>> {
+        self.scan_with_stats(start_path, predicate)
+            .map(|(projects, _)| projects)
+    }
 
-        // Sort by project name
-        projects.sort_by(|a, b| a.name.cmp(&b.name));
+    /// Buffered, sorted scan shared by the `scan_directory*` methods
+    fn scan_with_stats(
+        &self,
+        start_path: &Path,
+        predicate: impl Fn(&ProjectInfo) -> bool,
+    ) -> Result<(Vec<ProjectInfo>, ScanStats)> {
+        self.timings.time("scan", || {
+            let started = Instant::now();
+            let mut projects = Vec::new();
+            let mut walk = self.walk(start_path);
+            for project in walk.by_ref() {
+                let project = project?;
+                if predicate(&project) {
+                    projects.push(project);
+                }
+            }
+
+            // Sort by project name
+            projects.sort_by(|a, b| {
+                collate::compare(&a.name, &b.name).then_with(|| a.root.cmp(&b.root))
+            });
+
+            let stats = ScanStats {
+                projects_found: projects.len(),
+                elapsed: started.elapsed(),
+                ..walk.stats
+            };
+            Ok((projects, stats))
+        })
+    }
 
-        Ok(projects)
+    /// Scan a directory for projects, yielding each one as it is found
+    ///
+    /// Unlike [`scan_directory`](Self::scan_directory), results are not buffered
+    /// or sorted, so callers can start processing projects before the whole tree
+    /// has been walked. Each project is yielded at most once.
+    ///
+    /// # Arguments
+    /// * `start_path` - Root directory to start scanning
+    ///
+    /// # Returns
+    /// An iterator whose [`stats`](ScanIter::stats) cover the walk so far
+    pub fn scan_directory_iter(&self, start_path: &Path) -> ScanIter<'_> {
+        self.walk(start_path)
     }
 
-    /// Recursive directory scanning
-    fn scan_recursive(
-        &self,
-        dir: &Path,
-        depth: usize,
-        projects: &mut Vec<ProjectInfo>,
-    ) -> Result<()> {
-        // Check depth limit
-        if let Some(max) = self.max_depth {
-            if depth >= max {
-                return Ok(());
+    /// Start a walk of `start_path`
+    fn walk(&self, start_path: &Path) -> ScanIter<'_> {
+        let started = Instant::now();
+        let mut stack = Vec::new();
+        let mut stats = ScanStats::default();
+
+        // Scan subdirectories (don't check start_path itself, only its children)
+        if self.within_depth(0) {
+            if let Ok(entries) = fs::read_dir(start_path) {
+                stack.push((entries, 0));
+                stats.dirs_visited += 1;
             }
         }
 
-        // Read directory entries
-        let entries = match fs::read_dir(dir) {
-            Ok(entries) => entries,
-            Err(_) => return Ok(()), // Skip directories we can't read
-        };
+        ScanIter {
+            scanner: self,
+            stack,
+            seen: HashSet::new(),
+            stats,
+            started,
+        }
+    }
 
-        for entry in entries {
-            let entry = entry?;
-            let path = entry.path();
+    /// Check whether directories at `depth` are still within the depth limit
+    fn within_depth(&self, depth: usize) -> bool {
+        self.max_depth.map_or(true, |max| depth < max)
+    }
 
-            // Skip if not a directory
-            if !path.is_dir() {
-                continue;
-            }
+    /// Check whether a built-in rule applies to a directory
+    ///
+    /// Backup directories are recognized by location (the resolved backup
+    /// directory) and by layout (a `backups` directory holding ccm backups
+    /// next to a `config.json`, as in a copied ccm config directory).
+    fn builtin_prune(&self, path: &Path, name: &str) -> Option<PruneReason> {
+        if name == ".claude" {
+            return Some(PruneReason::ClaudeDir);
+        }
 
-            // Skip if in ignore list
-            let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+        if let Some((_, reason)) = self.owned_dirs.iter().find(|(dir, _)| dir == path) {
+            return Some(*reason);
+        }
 
-            if self.should_ignore(file_name) {
-                continue;
-            }
+        let ccm_backup_layout = name == "backups"
+            && path
+                .parent()
+                .is_some_and(|parent| parent.join("config.json").is_file())
+            && crate::backup::is_backup_dir(path);
+        ccm_backup_layout.then_some(PruneReason::BackupDir)
+    }
 
-            // Check if this directory contains a .claude/config.json
-            if let Some(config) = find_project_config(Some(&path)) {
-                projects.push(ProjectInfo::from_config_path(config));
-            }
+    /// Decide how a walk handles the subdirectory `path` found at `depth`
+    ///
+    /// Returns `None` if the directory is skipped entirely, otherwise whether
+    /// its entries are read too. Directories that aren't skipped are always
+    /// checked for a project.
+    fn admit(&self, path: &Path, depth: usize, stats: &mut ScanStats) -> Option<bool> {
+        let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+
+        // Skip ccm-owned directories (regardless of the ignore list)
+        let prune = self.builtin_prune(path, file_name);
+        if let Some(reason) = prune.filter(|r| *r != PruneReason::ClaudeDir) {
+            tracing::debug!("Pruned {} ({reason})", path.display());
+            return None;
+        }
 
-            // Recursively scan subdirectory
-            self.scan_recursive(&path, depth + 1, projects)?;
+        // Skip if in ignore list
+        if self.should_ignore(file_name) {
+            stats.skipped_ignored += 1;
+            return None;
         }
 
-        Ok(())
+        Some(prune.is_none() && self.within_depth(depth + 1))
+    }
+
+    /// Config file of the project `dir` belongs to, if it should be yielded
+    ///
+    /// Projects are found like everywhere else (see
+    /// [`find_project_config_files`]), so a config embedded in a manifest
+    /// counts; `.claude/config.json` is preferred when both exist.
+    fn project_config(&self, dir: &Path) -> Option<PathBuf> {
+        find_project_config_files(Some(dir))
+            .pop()
+            .filter(|config| !self.config_only || is_readable_file(config))
+    }
+
+    /// Project info for a discovered config, with its archived flag
+    fn project_info(&self, config: PathBuf) -> ProjectInfo {
+        let mut info = ProjectInfo::from_config_path(config);
+        info.archived = is_project_archived(&info, self.registry.as_ref());
+        info
     }
 
     /// Check if a path should be ignored
@@ -199,6 +450,88 @@ impl Default for ProjectScanner {
     }
 }
 
+/// Depth-first walk backing [`ProjectScanner::scan_directory_iter`]
+#[derive(Debug)]
+pub struct ScanIter<'a> {
+    /// Scanner providing depth and ignore settings
+    scanner: &'a ProjectScanner,
+
+    /// Directories currently being read, with their depth
+    stack: Vec<(fs::ReadDir, usize)>,
+
+    /// Config paths already yielded
+    seen: HashSet<PathBuf>,
+
+    /// Directories read and skipped so far
+    stats: ScanStats,
+
+    /// When the walk started
+    started: Instant,
+}
+
+impl ScanIter<'_> {
+    /// Statistics of the walk so far
+    ///
+    /// Every project yielded counts as found; after the last one these are
+    /// the statistics of the whole scan.
+    pub fn stats(&self) -> ScanStats {
+        ScanStats {
+            elapsed: self.started.elapsed(),
+            ..self.stats
+        }
+    }
+}
+
+impl Iterator for ScanIter<'_> {
+    type Item = Result<ProjectInfo>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (entries, depth) = self.stack.last_mut()?;
+            let depth = *depth;
+
+            let entry = match entries.next() {
+                Some(Ok(entry)) => entry,
+                Some(Err(e)) => return Some(Err(e.into())),
+                None => {
+                    self.stack.pop();
+                    continue;
+                }
+            };
+            let path = entry.path();
+
+            // Skip if not a directory
+            if !path.is_dir() {
+                continue;
+            }
+
+            let Some(descend) = self.scanner.admit(&path, depth, &mut self.stats) else {
+                continue;
+            };
+
+            // Queue subdirectory for scanning (skip directories we can't read)
+            if descend {
+                if let Ok(children) = fs::read_dir(&path) {
+                    self.stack.push((children, depth + 1));
+                    self.stats.dirs_visited += 1;
+                }
+            }
+
+            if let Some(config) = self.scanner.project_config(&path) {
+                if self.seen.insert(config.clone()) {
+                    self.stats.projects_found += 1;
+                    return Some(Ok(self.scanner.project_info(config)));
+                }
+            }
+        }
+    }
+}
+
+/// Whether `path` is a file that can be opened for reading
+fn is_readable_file(path: &Path) -> bool {
+    path.is_file() && fs::File::open(path).is_ok()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -276,6 +609,31 @@ mod tests {
         assert_eq!(results[0].name, "my-project");
     }
 
+    // TDD Test 3b: Scan stats count visited directories and ignored skips
+    #[test]
+    fn test_scan_stats_count_visits_and_ignored() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+
+        // root/{app, app/src, lib, node_modules/pkg, target}
+        for dir in ["app/src", "lib", "node_modules/pkg", "target"] {
+            fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        fs::create_dir_all(root.join("app").join(".claude")).unwrap();
+        fs::write(root.join("app").join(".claude").join("config.json"), "{}").unwrap();
+
+        let (projects, stats) = ProjectScanner::default()
+            .scan_directory_with_stats(root)
+            .unwrap();
+
+        assert_eq!(projects.len(), 1);
+        assert_eq!(stats.projects_found, 1);
+        // root, app, app/src, lib (.claude is checked but not read)
+        assert_eq!(stats.dirs_visited, 4);
+        // node_modules and target
+        assert_eq!(stats.skipped_ignored, 2);
+    }
+
     // TDD Test 4: Scanner returns empty when no projects found
     #[test]
     fn test_scanner_returns_empty_when_no_projects() {
@@ -309,4 +667,269 @@ mod tests {
 
         assert_eq!(results.len(), 3);
     }
+
+    // TDD Test 6: Iterator yields the same projects as the buffered scan
+    #[test]
+    fn test_scan_iter_matches_buffered_scan() {
+        let temp_dir = TempDir::new().unwrap();
+
+        for dir in ["alpha", "beta/nested", "gamma/deep/er"] {
+            let claude_dir = temp_dir.path().join(dir).join(".claude");
+            fs::create_dir_all(&claude_dir).unwrap();
+            fs::write(claude_dir.join("config.json"), "{}").unwrap();
+        }
+
+        let scanner = ProjectScanner::default();
+        let buffered = scanner.scan_directory(temp_dir.path()).unwrap();
+        let mut streamed = scanner
+            .scan_directory_iter(temp_dir.path())
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        streamed.sort_by(|a, b| collate::compare(&a.name, &b.name));
+
+        assert_eq!(buffered.len(), 3);
+        assert_eq!(streamed, buffered);
+    }
+
+    // TDD Test 7: Filtered scan only includes projects matching the predicate
+    #[test]
+    fn test_scan_directory_filtered() {
+        let temp_dir = TempDir::new().unwrap();
+
+        for (dir, config) in [
+            (
+                "alpha",
+                r#"{"mcpServers": {"github": {"command": "gh-mcp"}}}"#,
+            ),
+            ("beta", r#"{"mcpServers": {}}"#),
+            (
+                "gamma",
+                r#"{"mcpServers": {"github": {"command": "gh-mcp"}}}"#,
+            ),
+        ] {
+            let claude_dir = temp_dir.path().join(dir).join(".claude");
+            fs::create_dir_all(&claude_dir).unwrap();
+            fs::write(claude_dir.join("config.json"), config).unwrap();
+        }
+        // .claude directory without a config file
+        fs::create_dir_all(temp_dir.path().join("delta").join(".claude")).unwrap();
+
+        let scanner = ProjectScanner::default();
+        let all = scanner.scan_directory(temp_dir.path()).unwrap();
+        assert_eq!(all.len(), 3);
+
+        let configured = scanner
+            .scan_directory_filtered(temp_dir.path(), |p| p.has_config)
+            .unwrap();
+        assert_eq!(configured, all);
+
+        let with_github = scanner
+            .scan_directory_filtered(temp_dir.path(), |p| {
+                fs::read_to_string(&p.config_path).is_ok_and(|c| c.contains("\"github\""))
+            })
+            .unwrap();
+        let names: Vec<_> = with_github.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec!["alpha", "gamma"]);
+
+        let none = scanner
+            .scan_directory_filtered(temp_dir.path(), |_| false)
+            .unwrap();
+        assert!(none.is_empty());
+    }
+
+    // TDD Test 8: ccm config and backup directories are never scanned
+    #[test]
+    fn test_scanner_skips_ccm_owned_dirs() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+
+        let write_config = |dir: &Path| {
+            let claude_dir = dir.join(".claude");
+            fs::create_dir_all(&claude_dir).unwrap();
+            fs::write(claude_dir.join("config.json"), "{}").unwrap();
+        };
+
+        // A real project with its own .claude/backups
+        write_config(&root.join("real"));
+        write_config(
+            &root
+                .join("real")
+                .join(".claude")
+                .join("backups")
+                .join("copy"),
+        );
+
+        // A fake global config dir inside the scan root
+        let config_dir = root.join("home").join(".config").join("claude");
+        fs::create_dir_all(&config_dir).unwrap();
+        fs::write(config_dir.join("config.json"), "{}").unwrap();
+        write_config(&config_dir.join("stray"));
+        write_config(&config_dir.join("backups").join("home").join("proj"));
+
+        // A copied config dir is recognized by its layout
+        let copy = root.join("claude-copy");
+        fs::create_dir_all(copy.join("backups")).unwrap();
+        fs::write(copy.join("config.json"), "{}").unwrap();
+        fs::write(
+            copy.join("backups")
+                .join("config_20250120_123456.000000.json"),
+            "{}",
+        )
+        .unwrap();
+        write_config(&copy.join("backups").join("proj"));
+
+        // Any other directory named backups is scanned as usual
+        let app = root.join("app");
+        fs::create_dir_all(&app).unwrap();
+        fs::write(app.join("config.json"), "{}").unwrap();
+        write_config(&app.join("backups").join("site"));
+
+        let scanner = ProjectScanner::default()
+            .with_owned_dir(&config_dir, PruneReason::ConfigDir)
+            .ignore_path("nothing-matches");
+        let results = scanner.scan_directory(root).unwrap();
+
+        let mut roots: Vec<_> = results.iter().map(|p| p.root.clone()).collect();
+        roots.sort();
+        assert_eq!(
+            roots,
+            vec![app.join("backups").join("site"), root.join("real")]
+        );
+    }
+
+    // TDD Test 9: Project info serializes last_modified as RFC 3339
+    #[test]
+    fn test_project_info_serializes_rfc3339() {
+        let temp_dir = TempDir::new().unwrap();
+        let claude_dir = temp_dir.path().join("proj").join(".claude");
+        fs::create_dir_all(&claude_dir).unwrap();
+        fs::write(claude_dir.join("config.json"), "{}").unwrap();
+
+        let info = ProjectInfo::from_config_path(claude_dir.join("config.json"));
+        let json = serde_json::to_value(&info).unwrap();
+
+        let modified = json["last_modified"].as_str().unwrap();
+        assert!(DateTime::parse_from_rfc3339(modified).is_ok());
+        let parsed: ProjectInfo = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed, info);
+    }
+
+    // TDD Test 10: Paths are relativized against a base, except outside it
+    #[test]
+    fn test_project_info_to_relative() {
+        let temp_dir = TempDir::new().unwrap();
+        let scan_root = temp_dir.path().join("work");
+        let inside = ProjectInfo::from_config_path(
+            scan_root
+                .join("team")
+                .join("app")
+                .join(".claude")
+                .join("config.json"),
+        );
+        let outside = ProjectInfo::from_config_path(
+            temp_dir
+                .path()
+                .join("elsewhere")
+                .join(".claude")
+                .join("config.json"),
+        );
+
+        let relative = inside.to_relative(&scan_root);
+        assert_eq!(relative.root, Path::new("team/app"));
+        assert_eq!(relative.claude_dir, Path::new("team/app/.claude"));
+        assert_eq!(
+            relative.config_path,
+            Path::new("team/app/.claude/config.json")
+        );
+        assert_eq!(relative.name, "app");
+
+        assert_eq!(outside.to_relative(&scan_root), outside);
+
+        let at_root = ProjectInfo::from_config_path(scan_root.join(".claude").join("config.json"));
+        assert_eq!(at_root.to_relative(&scan_root).root, Path::new("."));
+    }
+
+    // TDD Test 11: Configs embedded in manifests are found like elsewhere
+    #[test]
+    fn test_scanner_finds_embedded_configs() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+
+        let py = root.join("py-app");
+        fs::create_dir_all(&py).unwrap();
+        fs::write(
+            py.join("pyproject.toml"),
+            "[tool.claude]\nallowedPaths = [\"~/py\"]\n",
+        )
+        .unwrap();
+
+        // .claude/config.json is preferred when both exist
+        let both = root.join("both");
+        fs::create_dir_all(both.join(".claude")).unwrap();
+        fs::write(both.join(".claude").join("config.json"), "{}").unwrap();
+        fs::write(both.join("package.json"), r#"{"claude": {}}"#).unwrap();
+
+        // A manifest without a config section is not a project
+        let plain = root.join("plain");
+        fs::create_dir_all(&plain).unwrap();
+        fs::write(plain.join("package.json"), r#"{"name": "plain"}"#).unwrap();
+
+        let mut projects = ProjectScanner::new(None, false)
+            .scan_directory(root)
+            .unwrap();
+        projects.sort_by(|a, b| a.name.cmp(&b.name));
+        let found: Vec<(&str, &Path)> = projects
+            .iter()
+            .map(|p| (p.name.as_str(), p.config_path.as_path()))
+            .collect();
+        let both_config = both.join(".claude").join("config.json");
+        let py_config = py.join("pyproject.toml");
+        assert_eq!(
+            found,
+            [
+                ("both", both_config.as_path()),
+                ("py-app", py_config.as_path())
+            ]
+        );
+        assert_eq!(projects[1].root, py);
+        assert!(projects[1].has_config);
+        assert_eq!(
+            find_project_config_files(Some(&py)).pop().as_deref(),
+            Some(py_config.as_path())
+        );
+    }
+
+    // TDD Test 12: The iterator's stats match the buffered scan's once drained
+    #[test]
+    fn test_scan_iter_stats() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::create_dir_all(temp_dir.path().join("node_modules").join("pkg")).unwrap();
+        for dir in ["alpha", "beta/nested"] {
+            let claude_dir = temp_dir.path().join(dir).join(".claude");
+            fs::create_dir_all(&claude_dir).unwrap();
+            fs::write(claude_dir.join("config.json"), "{}").unwrap();
+        }
+
+        let scanner = ProjectScanner::default();
+        let (_, buffered) = scanner.scan_directory_with_stats(temp_dir.path()).unwrap();
+
+        let mut walk = scanner.scan_directory_iter(temp_dir.path());
+        assert_eq!(walk.stats().projects_found, 0);
+        assert!(walk.next().unwrap().is_ok());
+        assert_eq!(walk.stats().projects_found, 1);
+        assert_eq!(walk.by_ref().count(), 1);
+
+        let streamed = walk.stats();
+        assert_eq!(streamed.projects_found, 2);
+        assert_eq!(
+            ScanStats {
+                elapsed: Duration::ZERO,
+                ..streamed
+            },
+            ScanStats {
+                elapsed: Duration::ZERO,
+                ..buffered
+            }
+        );
+    }
 }
@@ -0,0 +1,292 @@
+//! Project registry and archived projects
+//!
+//! The registry (`<config_dir>/projects.json`) records per-project flags
+//! keyed by project root. Projects are registered when `project scan`,
+//! `project list` or `project archive` finds them. Its only flag so far is
+//! `archived`: archived projects are hidden from `project list`, bulk
+//! operations and the GUI sidebar, but their configs are left alone.
+//!
+//! A project's config may also carry an archived marker
+//! (`"ccm": {"archived": true}`, preserved as an unknown field by
+//! [`ClaudeConfig`]). It is only read for projects without a registry entry,
+//! and is carried over into the entry when the project is registered, so
+//! each config is parsed for it at most once.
+
+use super::ProjectInfo;
+use crate::{
+    config::{
+        manager::{atomic_write, read_config_file},
+        ClaudeConfig,
+    },
+    error::{ConfigError, Result},
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Config section holding ccm's own per-project markers
+pub const MARKER_SECTION: &str = "ccm";
+
+/// Marker key for archived projects within [`MARKER_SECTION`]
+const ARCHIVED_KEY: &str = "archived";
+
+/// Registry record for one project
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RegistryEntry {
+    /// Whether the project is archived
+    #[serde(default)]
+    pub archived: bool,
+}
+
+/// Known projects and their flags, stored as JSON
+#[derive(Debug, Clone, Default)]
+pub struct ProjectRegistry {
+    /// Registry file
+    path: PathBuf,
+
+    /// Entries by canonical project root
+    entries: BTreeMap<PathBuf, RegistryEntry>,
+}
+
+impl ProjectRegistry {
+    /// Load the registry from `path` (empty if the file doesn't exist)
+    ///
+    /// # Errors
+    /// Returns an error if the file exists but cannot be read or parsed
+    pub fn load(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let entries = match fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content).map_err(|e| {
+                ConfigError::invalid_json(&path, e.line(), e.column(), e.to_string())
+            })?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => BTreeMap::new(),
+            Err(e) => return Err(ConfigError::filesystem("read project registry", &path, e)),
+        };
+
+        Ok(Self { path, entries })
+    }
+
+    /// Write the registry back to its file
+    pub fn save(&self) -> Result<()> {
+        let json = serde_json::to_string_pretty(&self.entries)?;
+        atomic_write(&self.path, &json)
+    }
+
+    /// Add a project (not archived) if it isn't registered yet
+    pub fn register(&mut self, root: &Path) {
+        self.entries.entry(registry_key(root)).or_default();
+    }
+
+    /// Register discovered projects that have no entry yet
+    ///
+    /// New entries take the project's `archived` flag, which for an
+    /// unregistered project comes from its config marker.
+    ///
+    /// # Returns
+    /// True if any entry was added
+    pub fn register_projects<'a>(
+        &mut self,
+        projects: impl IntoIterator<Item = &'a ProjectInfo>,
+    ) -> bool {
+        let mut added = false;
+        for project in projects {
+            if let std::collections::btree_map::Entry::Vacant(entry) =
+                self.entries.entry(registry_key(&project.root))
+            {
+                entry.insert(RegistryEntry {
+                    archived: project.archived,
+                });
+                added = true;
+            }
+        }
+        added
+    }
+
+    /// Whether a project has a registry entry
+    pub fn contains(&self, root: &Path) -> bool {
+        self.entries.contains_key(&registry_key(root))
+    }
+
+    /// Roots of all registered projects, sorted
+    pub fn roots(&self) -> impl Iterator<Item = &Path> {
+        self.entries.keys().map(PathBuf::as_path)
+    }
+
+    /// Registry entry for a project, if any
+    pub fn get(&self, root: &Path) -> Option<&RegistryEntry> {
+        self.entries.get(&registry_key(root))
+    }
+
+    /// Set the archived flag of a registered project
+    ///
+    /// # Returns
+    /// False if the project has no registry entry (nothing changed)
+    pub fn set_archived(&mut self, root: &Path, archived: bool) -> bool {
+        match self.entries.get_mut(&registry_key(root)) {
+            Some(entry) => {
+                entry.archived = archived;
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// Whether a project is archived
+///
+/// A registry entry decides when there is one; otherwise the marker in the
+/// project's config does, which means reading the config. Every surface
+/// that hides archived projects goes through this check, so they all agree.
+///
+/// # Arguments
+/// * `project` - Project to check
+/// * `registry` - Project registry, if loaded
+pub fn is_project_archived(project: &ProjectInfo, registry: Option<&ProjectRegistry>) -> bool {
+    if let Some(entry) = registry.and_then(|registry| registry.get(&project.root)) {
+        return entry.archived;
+    }
+
+    project.has_config
+        && read_config_file(&project.config_path)
+            .map(|config| has_archive_marker(&config))
+            .unwrap_or(false)
+}
+
+/// Whether a config carries the archived marker
+pub fn has_archive_marker(config: &ClaudeConfig) -> bool {
+    config
+        .unknown
+        .get(MARKER_SECTION)
+        .and_then(|section| section.get(ARCHIVED_KEY))
+        .and_then(Value::as_bool)
+        .unwrap_or(false)
+}
+
+/// Add or remove the archived marker in a config
+///
+/// Removing the last marker also removes the (then empty) section.
+///
+/// # Returns
+/// True if the config changed
+pub fn set_archive_marker(config: &mut ClaudeConfig, archived: bool) -> bool {
+    if has_archive_marker(config) == archived {
+        return false;
+    }
+
+    if archived {
+        let section = config
+            .unknown
+            .entry(MARKER_SECTION.to_string())
+            .or_insert_with(|| Value::Object(Default::default()));
+        if !section.is_object() {
+            *section = Value::Object(Default::default());
+        }
+        if let Some(section) = section.as_object_mut() {
+            section.insert(ARCHIVED_KEY.to_string(), Value::Bool(true));
+        }
+    } else if let Some(section) = config.unknown.get_mut(MARKER_SECTION) {
+        if let Some(map) = section.as_object_mut() {
+            map.remove(ARCHIVED_KEY);
+            if map.is_empty() {
+                config.unknown.remove(MARKER_SECTION);
+            }
+        }
+    }
+
+    true
+}
+
+/// Normalize a project root for use as a registry key
+fn registry_key(root: &Path) -> PathBuf {
+    root.canonicalize().unwrap_or_else(|_| root.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn project(temp_dir: &TempDir, name: &str, config: &str) -> ProjectInfo {
+        let config_path = temp_dir
+            .path()
+            .join(name)
+            .join(".claude")
+            .join("config.json");
+        fs::create_dir_all(config_path.parent().unwrap()).unwrap();
+        fs::write(&config_path, config).unwrap();
+        ProjectInfo::from_config_path(config_path)
+    }
+
+    // TDD Test 1: Archiving through the registry, which wins over markers
+    #[test]
+    fn test_archive_via_registry() {
+        let temp_dir = TempDir::new().unwrap();
+        let registry_path = temp_dir.path().join("projects.json");
+        let alpha = project(&temp_dir, "alpha", "{}");
+
+        let mut registry = ProjectRegistry::load(&registry_path).unwrap();
+        assert!(!registry.set_archived(&alpha.root, true));
+        registry.register(&alpha.root);
+        assert!(registry.set_archived(&alpha.root, true));
+        registry.save().unwrap();
+
+        // Relative and absolute spellings of the root find the same entry
+        let registry = ProjectRegistry::load(&registry_path).unwrap();
+        assert!(registry.contains(&alpha.root.join("..").join("alpha")));
+        assert!(is_project_archived(&alpha, Some(&registry)));
+        assert!(!is_project_archived(&alpha, None));
+
+        let mut registry = registry;
+        registry.set_archived(&alpha.root, false);
+        assert!(!is_project_archived(&alpha, Some(&registry)));
+    }
+
+    // TDD Test 3: Registering discovered projects carries over markers once
+    #[test]
+    fn test_register_projects() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut gamma = project(&temp_dir, "gamma", r#"{"ccm": {"archived": true}}"#);
+        gamma.archived = is_project_archived(&gamma, None);
+        let delta = project(&temp_dir, "delta", "{}");
+
+        let mut registry = ProjectRegistry::load(temp_dir.path().join("projects.json")).unwrap();
+        assert!(registry.register_projects([&gamma, &delta]));
+        assert!(!registry.register_projects([&gamma, &delta]));
+        assert_eq!(registry.roots().count(), 2);
+        assert!(registry.get(&gamma.root).unwrap().archived);
+        assert!(!registry.get(&delta.root).unwrap().archived);
+
+        // The entry decides from now on, without reading the config
+        fs::write(&gamma.config_path, "not json").unwrap();
+        assert!(is_project_archived(&gamma, Some(&registry)));
+        registry.set_archived(&gamma.root, false);
+        assert!(!is_project_archived(&gamma, Some(&registry)));
+    }
+
+    // TDD Test 2: Archiving through the config marker
+    #[test]
+    fn test_archive_via_config_marker() {
+        let temp_dir = TempDir::new().unwrap();
+        let beta = project(
+            &temp_dir,
+            "beta",
+            r#"{"allowedPaths": ["~"], "ccm": {"archived": true}}"#,
+        );
+        assert!(is_project_archived(&beta, None));
+        assert!(is_project_archived(
+            &beta,
+            Some(&ProjectRegistry::default())
+        ));
+
+        let mut config = ClaudeConfig::new().with_allowed_path("~");
+        assert!(set_archive_marker(&mut config, true));
+        assert!(!set_archive_marker(&mut config, true));
+        assert!(has_archive_marker(&config));
+
+        assert!(set_archive_marker(&mut config, false));
+        assert!(!has_archive_marker(&config));
+        assert!(!config.unknown.contains_key(MARKER_SECTION));
+    }
+}
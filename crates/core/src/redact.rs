@@ -0,0 +1,435 @@
+//! Secret redaction
+//!
+//! This module removes secret-looking values (API keys, tokens, passwords)
+//! from configuration data before it leaves the user's machine, e.g. in
+//! support bundles.
+
+use serde_json::{Map, Value};
+use std::collections::BTreeSet;
+
+/// Replacement text for redacted values
+pub const REDACTED: &str = "<redacted>";
+
+/// Key name fragments that mark a value as secret
+const SECRET_KEY_PATTERNS: &[&str] = &[
+    "token",
+    "secret",
+    "password",
+    "passwd",
+    "apikey",
+    "api_key",
+    "api-key",
+    "credential",
+    "private_key",
+    "privatekey",
+];
+
+/// Key name words that mark a value as secret only as a whole segment
+///
+/// `auth` would otherwise match `author` and `authority`.
+const SECRET_KEY_WORDS: &[&str] = &["auth", "oauth", "authorization"];
+
+/// Value prefixes used by well-known token formats
+const TOKEN_PREFIXES: &[&str] = &[
+    "ghp_",
+    "gho_",
+    "ghu_",
+    "ghs_",
+    "github_pat_",
+    "sk-",
+    "xoxb-",
+    "xoxp-",
+    "glpat-",
+    "AKIA",
+];
+
+/// Minimum length for a string to be considered a secret value
+const MIN_SECRET_LEN: usize = 4;
+
+/// Check whether a key name suggests its value is a secret
+///
+/// Matching is case-insensitive, e.g. `GITHUB_TOKEN`, `apiKey` and
+/// `--password` all match. Words like `auth` must make up a whole segment
+/// of the name (`X-Auth`, `auth_header`, `basicAuth`), so `author` doesn't.
+pub fn is_secret_key(name: &str) -> bool {
+    let lower = name.to_lowercase();
+    SECRET_KEY_PATTERNS
+        .iter()
+        .any(|pattern| lower.contains(pattern))
+        || key_segments(name)
+            .iter()
+            .any(|segment| SECRET_KEY_WORDS.contains(&segment.as_str()))
+}
+
+/// Lowercase segments of a key name, split at punctuation and camelCase humps
+fn key_segments(name: &str) -> Vec<String> {
+    let mut segments = Vec::new();
+    let mut current = String::new();
+    let mut prev_lower = false;
+    for c in name.chars() {
+        let boundary = !c.is_alphanumeric() || (c.is_uppercase() && prev_lower);
+        if boundary && !current.is_empty() {
+            segments.push(std::mem::take(&mut current));
+        }
+        if c.is_alphanumeric() {
+            current.extend(c.to_lowercase());
+        }
+        prev_lower = c.is_lowercase() || c.is_ascii_digit();
+    }
+    if !current.is_empty() {
+        segments.push(current);
+    }
+    segments
+}
+
+/// Check whether a value looks like a token from a well-known provider
+pub fn looks_like_token(value: &str) -> bool {
+    TOKEN_PREFIXES
+        .iter()
+        .any(|prefix| value.starts_with(prefix) && value.len() > prefix.len() + 8)
+}
+
+/// Redacts secrets from configuration values and free-form text
+///
+/// A single redactor should be used for everything that goes into one
+/// output: secrets found in structured values are remembered, so they are
+/// also scrubbed from any text redacted afterwards.
+#[derive(Debug, Default)]
+pub struct Redactor {
+    /// Secret values seen so far
+    secrets: BTreeSet<String>,
+
+    /// Key paths redacted since the last call to `take_redacted_paths`
+    redacted_paths: Vec<String>,
+}
+
+impl Redactor {
+    /// Create a new redactor
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Redact secrets from a JSON value
+    ///
+    /// The following are replaced with [`REDACTED`]:
+    /// - All values of MCP server `env` maps
+    /// - Values whose key looks secret (see [`is_secret_key`])
+    /// - Command-line arguments following a secret flag (`--token X`,
+    ///   `--api-key=X`)
+    /// - Strings that look like well-known token formats
+    ///
+    /// # Returns
+    /// A redacted copy of `value`
+    pub fn redact_value(&mut self, value: &Value) -> Value {
+        self.redact_at(value, "", false)
+    }
+
+    /// Redact secrets from free-form text (logs, journals)
+    ///
+    /// Replaces every secret previously seen by [`redact_value`](Self::redact_value)
+    /// and every word that looks like a token.
+    pub fn redact_text(&self, text: &str) -> String {
+        let mut result = text.to_string();
+
+        for secret in &self.secrets {
+            result = result.replace(secret.as_str(), REDACTED);
+        }
+
+        result
+            .split_inclusive(char::is_whitespace)
+            .map(|word| {
+                let trimmed = word.trim_end();
+                let token = trimmed.rsplit(['=', ':', '"', '\'']).next().unwrap_or("");
+                if looks_like_token(token) {
+                    word.replacen(token, REDACTED, 1)
+                } else {
+                    word.to_string()
+                }
+            })
+            .collect()
+    }
+
+    /// Take the key paths redacted since the last call
+    pub fn take_redacted_paths(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.redacted_paths)
+    }
+
+    /// Recursively redact `value` located at `path`
+    fn redact_at(&mut self, value: &Value, path: &str, force: bool) -> Value {
+        match value {
+            Value::Object(map) => {
+                let in_env = path.ends_with(".env") && path.starts_with("mcpServers.");
+                let mut redacted = Map::new();
+
+                for (key, child) in map {
+                    let child_path = join_path(path, key);
+                    let secret = force || in_env || is_secret_key(key);
+                    let child = if key == "args" {
+                        self.redact_args(child, &child_path)
+                    } else {
+                        self.redact_at(child, &child_path, secret)
+                    };
+                    redacted.insert(key.clone(), child);
+                }
+
+                Value::Object(redacted)
+            }
+            Value::Array(items) => Value::Array(
+                items
+                    .iter()
+                    .enumerate()
+                    .map(|(i, item)| self.redact_at(item, &join_path(path, &i.to_string()), force))
+                    .collect(),
+            ),
+            Value::String(s) if force || looks_like_token(s) => self.redact_string(s, path),
+            Value::Number(n) if force => self.redact_string(&n.to_string(), path),
+            other => other.clone(),
+        }
+    }
+
+    /// Redact secret values from a command-line argument list
+    fn redact_args(&mut self, value: &Value, path: &str) -> Value {
+        let Value::Array(args) = value else {
+            return self.redact_at(value, path, false);
+        };
+
+        let mut redacted = Vec::with_capacity(args.len());
+        let mut redact_next = false;
+
+        for (i, arg) in args.iter().enumerate() {
+            let arg_path = join_path(path, &i.to_string());
+
+            let Value::String(s) = arg else {
+                redacted.push(self.redact_at(arg, &arg_path, false));
+                redact_next = false;
+                continue;
+            };
+
+            if redact_next || looks_like_token(s) {
+                redacted.push(self.redact_string(s, &arg_path));
+                redact_next = false;
+            } else if let Some((flag, secret)) = s
+                .split_once('=')
+                .filter(|(flag, _)| flag.starts_with('-') && is_secret_key(flag))
+            {
+                self.record(secret, &arg_path);
+                redacted.push(Value::String(format!("{flag}={REDACTED}")));
+            } else {
+                redact_next = s.starts_with('-') && is_secret_key(s);
+                redacted.push(arg.clone());
+            }
+        }
+
+        Value::Array(redacted)
+    }
+
+    /// Replace a secret string, remembering it for text redaction
+    fn redact_string(&mut self, secret: &str, path: &str) -> Value {
+        self.record(secret, path);
+        Value::String(REDACTED.to_string())
+    }
+
+    /// Remember a redacted secret and where it was found
+    fn record(&mut self, secret: &str, path: &str) {
+        if secret.len() >= MIN_SECRET_LEN {
+            self.secrets.insert(secret.to_string());
+        }
+        self.redacted_paths.push(path.to_string());
+    }
+}
+
+/// Fill the placeholders of a redacted value from the value it was taken from
+///
+/// Every [`REDACTED`] string, and every `--flag=<redacted>` argument, is
+/// replaced with the string at the same path in `original`. Use this before
+/// writing back a value that went through [`Redactor::redact_value`], so
+/// placeholders never overwrite real secrets.
+///
+/// # Errors
+/// Returns the key path of the first placeholder `original` has no value for
+pub fn restore_redacted(value: &Value, original: Option<&Value>) -> Result<Value, String> {
+    restore_at(value, original, "")
+}
+
+/// Recursively restore `value` located at `path`
+fn restore_at(value: &Value, original: Option<&Value>, path: &str) -> Result<Value, String> {
+    match value {
+        Value::Object(map) => map
+            .iter()
+            .map(|(key, child)| {
+                let original = original.and_then(|o| o.get(key));
+                Ok((
+                    key.clone(),
+                    restore_at(child, original, &join_path(path, key))?,
+                ))
+            })
+            .collect::<Result<Map<_, _>, _>>()
+            .map(Value::Object),
+        Value::Array(items) => items
+            .iter()
+            .enumerate()
+            .map(|(i, item)| {
+                let original = original.and_then(|o| o.get(i));
+                restore_at(item, original, &join_path(path, &i.to_string()))
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map(Value::Array),
+        Value::String(s) if s == REDACTED => match original {
+            Some(original @ (Value::String(_) | Value::Number(_))) => Ok(original.clone()),
+            _ => Err(path.to_string()),
+        },
+        Value::String(s) if s.ends_with(&format!("={REDACTED}")) => {
+            let prefix = &s[..s.len() - REDACTED.len()];
+            match original.and_then(Value::as_str) {
+                Some(original) if original.starts_with(prefix) => {
+                    Ok(Value::String(original.to_string()))
+                }
+                _ => Err(path.to_string()),
+            }
+        }
+        other => Ok(other.clone()),
+    }
+}
+
+/// Join a key onto a dot-separated key path
+fn join_path(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{path}.{key}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    // TDD Test 1: MCP server env values are always redacted
+    #[test]
+    fn test_redacts_env_values() {
+        let mut redactor = Redactor::new();
+        let config = json!({
+            "mcpServers": {
+                "github": {
+                    "command": "npx",
+                    "env": { "GITHUB_TOKEN": "abc123", "REGION": "eu-west-1" }
+                }
+            }
+        });
+
+        let redacted = redactor.redact_value(&config);
+
+        assert_eq!(
+            redacted["mcpServers"]["github"]["env"]["GITHUB_TOKEN"],
+            REDACTED
+        );
+        assert_eq!(redacted["mcpServers"]["github"]["env"]["REGION"], REDACTED);
+        assert_eq!(redacted["mcpServers"]["github"]["command"], "npx");
+        assert_eq!(redactor.take_redacted_paths().len(), 2);
+    }
+
+    // TDD Test 2: Secret-looking keys and flag arguments are redacted
+    #[test]
+    fn test_redacts_secret_keys_and_args() {
+        let mut redactor = Redactor::new();
+        let config = json!({
+            "apiKey": "key-value",
+            "theme": "dark",
+            "mcpServers": {
+                "svc": {
+                    "args": ["--token", "t0k3n", "--api-key=s3cret", "--verbose"]
+                }
+            }
+        });
+
+        let redacted = redactor.redact_value(&config);
+
+        assert_eq!(redacted["apiKey"], REDACTED);
+        assert_eq!(redacted["theme"], "dark");
+        assert_eq!(
+            redacted["mcpServers"]["svc"]["args"],
+            json!([
+                "--token",
+                REDACTED,
+                format!("--api-key={REDACTED}"),
+                "--verbose"
+            ])
+        );
+    }
+
+    // TDD Test 3: Text redaction scrubs previously seen secrets and tokens
+    #[test]
+    fn test_redact_text_scrubs_known_secrets() {
+        let mut redactor = Redactor::new();
+        redactor.redact_value(&json!({ "password": "hunter22" }));
+
+        let text = "login with hunter22 and GH_TOKEN=ghp_abcdefghijklmnop done";
+        let redacted = redactor.redact_text(text);
+
+        assert!(!redacted.contains("hunter22"));
+        assert!(!redacted.contains("ghp_abcdefghijklmnop"));
+        assert!(redacted.ends_with(" done"));
+    }
+
+    // TDD Test 4: Placeholders are filled from the original, or reported
+    #[test]
+    fn test_restore_redacted() {
+        let original = json!({
+            "mcpServers": {
+                "github": {
+                    "command": "npx",
+                    "args": ["--token", "abc123", "--api-key=xyz789"],
+                    "env": {"GITHUB_TOKEN": "ghp_1234567890abcdef", "PORT": 8080}
+                }
+            }
+        });
+        let redacted = Redactor::new().redact_value(&original);
+        assert_ne!(redacted, original);
+
+        assert_eq!(
+            restore_redacted(&redacted, Some(&original)).unwrap(),
+            original
+        );
+
+        let mut missing = original.clone();
+        missing["mcpServers"]["github"]["env"]
+            .as_object_mut()
+            .unwrap()
+            .remove("PORT");
+        assert_eq!(
+            restore_redacted(&redacted, Some(&missing)).unwrap_err(),
+            "mcpServers.github.env.PORT"
+        );
+        assert_eq!(
+            restore_redacted(&redacted, None).unwrap_err(),
+            "mcpServers.github.args.1"
+        );
+    }
+
+    // TDD Test 5: `auth` matches whole segments only
+    #[test]
+    fn test_auth_matches_segments_only() {
+        for key in [
+            "auth",
+            "Authorization",
+            "X-Auth-Key",
+            "github_auth",
+            "auth_header",
+            "basicAuth",
+            "--auth",
+            "oauth",
+        ] {
+            assert!(is_secret_key(key), "{key} should be secret");
+        }
+        for key in [
+            "author",
+            "authority",
+            "AUTHOR_NAME",
+            "coAuthor",
+            "--authority",
+        ] {
+            assert!(!is_secret_key(key), "{key} should not be secret");
+        }
+    }
+}
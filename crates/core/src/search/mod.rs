@@ -3,8 +3,13 @@
 //! Provides search capabilities for finding keys and values
 //! across configuration files at different scopes.
 
+mod query;
+
+pub use query::{ClauseMatch, ClauseTarget, MatchMode, QueryClause, QueryMatch};
+
 use crate::{config::ClaudeConfig, error::Result, types::ConfigScope};
 use serde_json::Value;
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 
 /// A single search result
@@ -60,6 +65,7 @@ impl SearchResult {
         let source_label = match &self.source {
             ConfigScope::Global => "GLOBAL",
             ConfigScope::Project => "PROJECT",
+            ConfigScope::Base => "BASE",
         };
 
         format!(
@@ -98,8 +104,18 @@ pub struct SearchOptions {
     /// Use regex pattern matching (default: false)
     pub regex: bool,
 
+    /// Only match the query as a whole word (default: false)
+    ///
+    /// Applies to plain queries and `~` clauses. Hyphens count as word
+    /// characters, so `npx` doesn't match `npx-extra`.
+    pub whole_word: bool,
+
     /// Maximum depth for recursive search (default: unlimited)
     pub max_depth: Option<usize>,
+
+    /// Clauses of a structured query, all of which must match within one
+    /// subtree (see [`ConfigSearcher::search_where`])
+    pub clauses: Vec<QueryClause>,
 }
 
 impl Default for SearchOptions {
@@ -109,7 +125,9 @@ impl Default for SearchOptions {
             search_values: false,
             case_sensitive: false,
             regex: false,
+            whole_word: false,
             max_depth: None,
+            clauses: Vec::new(),
         }
     }
 }
@@ -144,11 +162,23 @@ impl SearchOptions {
         self
     }
 
+    /// Set whole-word matching
+    pub fn with_whole_word(mut self, whole_word: bool) -> Self {
+        self.whole_word = whole_word;
+        self
+    }
+
     /// Set maximum depth
     pub fn with_max_depth(mut self, depth: Option<usize>) -> Self {
         self.max_depth = depth;
         self
     }
+
+    /// Add a structured query clause
+    pub fn with_clause(mut self, clause: QueryClause) -> Self {
+        self.clauses.push(clause);
+        self
+    }
 }
 
 /// Configuration searcher
@@ -196,7 +226,44 @@ impl ConfigSearcher {
         Ok(results)
     }
 
+    /// Search several configurations and group the results by scope
+    ///
+    /// # Arguments
+    /// * `query` - Search query string
+    /// * `sources` - Configs to search, with their scope and file path
+    ///
+    /// # Returns
+    /// Results keyed by scope (global first); scopes without matches are absent
+    pub fn search_grouped<'a, I>(
+        &self,
+        query: &str,
+        sources: I,
+    ) -> Result<BTreeMap<ConfigScope, Vec<SearchResult>>>
+    where
+        I: IntoIterator<Item = (&'a ClaudeConfig, ConfigScope, PathBuf)>,
+    {
+        let mut results = Vec::new();
+        for (config, scope, config_path) in sources {
+            results.extend(self.search(query, config, scope, config_path)?);
+        }
+        Ok(Self::group_by_scope(results))
+    }
+
+    /// Group flat search results by the scope they were found in
+    ///
+    /// Results keep their original order within each scope.
+    pub fn group_by_scope(
+        results: impl IntoIterator<Item = SearchResult>,
+    ) -> BTreeMap<ConfigScope, Vec<SearchResult>> {
+        let mut grouped: BTreeMap<ConfigScope, Vec<SearchResult>> = BTreeMap::new();
+        for result in results {
+            grouped.entry(result.source).or_default().push(result);
+        }
+        grouped
+    }
+
     /// Recursively search a JSON value
+    #[allow(clippy::too_many_arguments)]
     fn search_value(
         &self,
         query: &str,
@@ -317,14 +384,38 @@ impl ConfigSearcher {
 
     /// Check if a string matches the query
     fn matches(&self, query: &str, text: &str) -> bool {
+        let find = |text: &str, query: &str| {
+            if self.options.whole_word {
+                contains_word(text, query)
+            } else {
+                text.contains(query)
+            }
+        };
+
         if self.options.case_sensitive {
-            text.contains(query)
+            find(text, query)
         } else {
-            text.to_lowercase().contains(&query.to_lowercase())
+            find(&text.to_lowercase(), &query.to_lowercase())
         }
     }
 }
 
+/// Whether `query` occurs in `text` as a whole word
+///
+/// An occurrence is a whole word if the characters around it are not word
+/// characters. Hyphens count as word characters since they join the words of
+/// server and skill names, so `npx` is not a whole word of `npx-extra`.
+pub(crate) fn contains_word(text: &str, query: &str) -> bool {
+    let is_word_char = |c: char| c.is_alphanumeric() || c == '_' || c == '-';
+
+    !query.is_empty()
+        && text.match_indices(query).any(|(start, found)| {
+            let before = text[..start].chars().next_back();
+            let after = text[start + found.len()..].chars().next();
+            !before.is_some_and(is_word_char) && !after.is_some_and(is_word_char)
+        })
+}
+
 impl Default for ConfigSearcher {
     fn default() -> Self {
         Self::new()
@@ -400,6 +491,44 @@ mod tests {
         assert!(!results.is_empty());
     }
 
+    #[test]
+    fn test_search_whole_word() {
+        let config = ClaudeConfig::new()
+            .with_mcp_server("npx", crate::McpServer::new("npx", "npx", vec![]))
+            .with_mcp_server(
+                "npx-extra",
+                crate::McpServer::new("npx-extra", "my-npx-wrapper", vec![]),
+            );
+        let search = |options: SearchOptions, query: &str| {
+            ConfigSearcher::with_options(options)
+                .search(
+                    query,
+                    &config,
+                    ConfigScope::Global,
+                    PathBuf::from("/test/config.json"),
+                )
+                .unwrap()
+                .into_iter()
+                .map(|result| result.key_path)
+                .collect::<Vec<_>>()
+        };
+
+        let options = SearchOptions::new().with_whole_word(true);
+        assert_eq!(search(options.clone(), "npx"), vec!["mcpServers.npx"]);
+        assert_eq!(search(options.clone(), "NPX"), vec!["mcpServers.npx"]);
+        assert!(search(options.clone().with_case_sensitive(true), "NPX").is_empty());
+        assert_eq!(
+            search(options.with_keys(false).with_values(true), "npx"),
+            vec!["mcpServers.npx.command", "mcpServers.npx.name"]
+        );
+        assert_eq!(search(SearchOptions::new(), "npx").len(), 2);
+
+        assert!(contains_word("run npx now", "npx"));
+        assert!(contains_word("npx-extra npx", "npx"));
+        assert!(!contains_word("npx_extra", "npx"));
+        assert!(!contains_word("anything", ""));
+    }
+
     #[test]
     fn test_search_respects_depth_limit() {
         let config = ClaudeConfig::new();
@@ -435,4 +564,177 @@ mod tests {
         assert!(formatted.contains("npx"));
         assert!(formatted.contains("string"));
     }
+
+    #[test]
+    fn test_search_grouped_by_scope() {
+        let global = ClaudeConfig::new()
+            .with_mcp_server("fetch", crate::McpServer::new("fetch", "uvx", vec![]))
+            .with_allowed_path("~/fetch-cache");
+        let project = ClaudeConfig::new().with_mcp_server(
+            "fetch-docs",
+            crate::McpServer::new("fetch-docs", "npx", vec![]),
+        );
+
+        let searcher = ConfigSearcher::with_options(SearchOptions::new().with_values(true));
+        let grouped = searcher
+            .search_grouped(
+                "fetch",
+                [
+                    (
+                        &project,
+                        ConfigScope::Project,
+                        PathBuf::from("/p/config.json"),
+                    ),
+                    (
+                        &global,
+                        ConfigScope::Global,
+                        PathBuf::from("/g/config.json"),
+                    ),
+                ],
+            )
+            .unwrap();
+
+        assert_eq!(
+            grouped.keys().copied().collect::<Vec<_>>(),
+            [ConfigScope::Global, ConfigScope::Project]
+        );
+        let global_paths: Vec<&str> = grouped[&ConfigScope::Global]
+            .iter()
+            .map(|result| result.key_path.as_str())
+            .collect();
+        assert!(global_paths.contains(&"mcpServers.fetch"));
+        assert!(global_paths.contains(&"allowedPaths[0]"));
+        assert!(grouped[&ConfigScope::Global]
+            .iter()
+            .all(|result| result.config_path == std::path::Path::new("/g/config.json")));
+        assert!(grouped[&ConfigScope::Project]
+            .iter()
+            .all(|result| result.source == ConfigScope::Project
+                && result.key_path.starts_with("mcpServers.fetch-docs")));
+
+        // Scopes without matches are left out
+        let grouped = searcher
+            .search_grouped("cache", [(&global, ConfigScope::Global, PathBuf::new())])
+            .unwrap();
+        assert_eq!(grouped.len(), 1);
+        assert!(!grouped.contains_key(&ConfigScope::Project));
+    }
+
+    fn server_config() -> ClaudeConfig {
+        let mut github = crate::McpServer::new(
+            "github",
+            "npx",
+            vec![
+                "-y".to_string(),
+                "@modelcontextprotocol/server-github".to_string(),
+            ],
+        );
+        github
+            .env
+            .insert("GITHUB_TOKEN".to_string(), "ghp_example".to_string());
+        let fetch = crate::McpServer::new("fetch", "npx", vec!["mcp-fetch".to_string()]);
+
+        ClaudeConfig::new()
+            .with_mcp_server("github", github)
+            .with_mcp_server("fetch", fetch)
+            .with_allowed_path("~/github")
+    }
+
+    fn search_where(options: SearchOptions) -> crate::error::Result<Vec<QueryMatch>> {
+        ConfigSearcher::with_options(options).search_where(
+            &server_config(),
+            ConfigScope::Global,
+            PathBuf::from("/test/config.json"),
+        )
+    }
+
+    #[test]
+    fn test_search_where_requires_all_clauses_in_one_subtree() {
+        let options = SearchOptions::new()
+            .with_clause(QueryClause::value("npx"))
+            .with_clause(QueryClause::key("GITHUB"));
+
+        let results = search_where(options).unwrap();
+
+        // Both servers run npx; only one has a GITHUB env key
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].root, "mcpServers.github");
+        let paths: Vec<&str> = results[0]
+            .matches
+            .iter()
+            .map(|m| m.key_path.as_str())
+            .collect();
+        // Clauses are case-insensitive by default, so the server name
+        // matches the key clause too
+        assert_eq!(
+            paths,
+            [
+                "mcpServers.github",
+                "mcpServers.github.command",
+                "mcpServers.github.env.GITHUB_TOKEN"
+            ]
+        );
+
+        // No AND across subtrees: the allowed path mentions github, but
+        // it is its own subtree and has no npx
+        let options = SearchOptions::new()
+            .with_clause(QueryClause::value("~/github"))
+            .with_clause(QueryClause::value("npx"));
+        assert!(search_where(options).unwrap().is_empty());
+
+        let options = SearchOptions::new().with_clause(QueryClause::value("github"));
+        let roots: Vec<String> = search_where(options)
+            .unwrap()
+            .into_iter()
+            .map(|m| m.root)
+            .collect();
+        assert_eq!(roots, ["allowedPaths[0]", "mcpServers.github"]);
+    }
+
+    #[test]
+    fn test_search_where_regex_and_exact_clauses() {
+        let options = SearchOptions::new()
+            .with_clause("value=~^ghp_[a-z]+$".parse().unwrap())
+            .with_clause("key=command".parse().unwrap());
+        let results = search_where(options).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].root, "mcpServers.github");
+
+        // Exact clauses don't match substrings
+        let options = SearchOptions::new()
+            .with_case_sensitive(true)
+            .with_clause("key=GITHUB".parse().unwrap());
+        assert!(search_where(options).unwrap().is_empty());
+
+        // Whole-word substring clauses skip hyphenated names
+        let options = SearchOptions::new().with_clause(QueryClause::value("server"));
+        assert_eq!(search_where(options.clone()).unwrap().len(), 1);
+        assert!(search_where(options.with_whole_word(true))
+            .unwrap()
+            .is_empty());
+
+        let options = SearchOptions::new().with_clause("value=~(".parse().unwrap());
+        assert!(search_where(options).is_err());
+        assert!(search_where(SearchOptions::new()).is_err());
+    }
+
+    #[test]
+    fn test_query_clause_syntax() {
+        let clause: QueryClause = "value=~^ghp_".parse().unwrap();
+        assert_eq!(
+            clause,
+            QueryClause::new(ClauseTarget::Value, MatchMode::Regex, "^ghp_")
+        );
+        assert_eq!(clause.to_string(), "value=~^ghp_");
+        assert_eq!(
+            "key~a=b".parse::<QueryClause>().unwrap(),
+            QueryClause::key("a=b")
+        );
+        assert_eq!(
+            "key=x".parse::<QueryClause>().unwrap().mode,
+            MatchMode::Exact
+        );
+        assert!("name~x".parse::<QueryClause>().is_err());
+        assert!("key".parse::<QueryClause>().is_err());
+    }
 }
@@ -0,0 +1,431 @@
+//! Structured search queries
+//!
+//! A query is a list of clauses, each matching keys or values by substring,
+//! exact text or regular expression. A query matches a subtree when every
+//! clause matches somewhere inside it. Subtrees are the second-level entries
+//! of a config: one MCP server (`mcpServers.github`), one allowed path
+//! (`allowedPaths[0]`), one skill. Matches on a top-level key or scalar form
+//! their own subtree.
+//!
+//! Clauses are always combined with AND; there is no OR. To find subtrees
+//! matching either of two conditions, run two queries.
+
+use super::{contains_word, ConfigSearcher, SearchOptions};
+use crate::{
+    config::ClaudeConfig,
+    error::{ConfigError, Result},
+    types::ConfigScope,
+};
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+/// What a clause is matched against
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ClauseTarget {
+    /// Object keys (server names, field names, env variable names)
+    Key,
+    /// Scalar values (strings, numbers, booleans)
+    Value,
+}
+
+/// How a clause compares text
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MatchMode {
+    /// The text contains the pattern
+    #[default]
+    Substring,
+    /// The pattern is a regular expression found in the text
+    Regex,
+    /// The text equals the pattern
+    Exact,
+}
+
+impl MatchMode {
+    /// Operator used for this mode in the `key~npx` clause syntax
+    fn operator(self) -> &'static str {
+        match self {
+            MatchMode::Substring => "~",
+            MatchMode::Regex => "=~",
+            MatchMode::Exact => "=",
+        }
+    }
+}
+
+/// One condition of a query
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QueryClause {
+    /// Whether keys or values are matched
+    pub target: ClauseTarget,
+
+    /// How the pattern is compared
+    #[serde(default)]
+    pub mode: MatchMode,
+
+    /// Text or regular expression to look for
+    pub pattern: String,
+}
+
+impl QueryClause {
+    /// Create a clause
+    pub fn new(target: ClauseTarget, mode: MatchMode, pattern: impl Into<String>) -> Self {
+        Self {
+            target,
+            mode,
+            pattern: pattern.into(),
+        }
+    }
+
+    /// Substring clause on keys
+    pub fn key(pattern: impl Into<String>) -> Self {
+        Self::new(ClauseTarget::Key, MatchMode::Substring, pattern)
+    }
+
+    /// Substring clause on values
+    pub fn value(pattern: impl Into<String>) -> Self {
+        Self::new(ClauseTarget::Value, MatchMode::Substring, pattern)
+    }
+
+    /// Set the match mode
+    pub fn with_mode(mut self, mode: MatchMode) -> Self {
+        self.mode = mode;
+        self
+    }
+}
+
+impl FromStr for QueryClause {
+    type Err = ConfigError;
+
+    /// Parse `<key|value><op><pattern>`, where the operator is `~`
+    /// (substring), `=` (exact) or `=~` (regex), e.g. `key~npx`,
+    /// `value=uvx` or `value=~^ghp_`
+    fn from_str(s: &str) -> Result<Self> {
+        let (target, rest) = if let Some(rest) = s.strip_prefix("key") {
+            (ClauseTarget::Key, rest)
+        } else if let Some(rest) = s.strip_prefix("value") {
+            (ClauseTarget::Value, rest)
+        } else {
+            return Err(invalid_clause(s));
+        };
+
+        // `=~` must be tried before `=`
+        let (mode, pattern) = if let Some(pattern) = rest.strip_prefix("=~") {
+            (MatchMode::Regex, pattern)
+        } else if let Some(pattern) = rest.strip_prefix('~') {
+            (MatchMode::Substring, pattern)
+        } else if let Some(pattern) = rest.strip_prefix('=') {
+            (MatchMode::Exact, pattern)
+        } else {
+            return Err(invalid_clause(s));
+        };
+
+        Ok(Self::new(target, mode, pattern))
+    }
+}
+
+impl fmt::Display for QueryClause {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let target = match self.target {
+            ClauseTarget::Key => "key",
+            ClauseTarget::Value => "value",
+        };
+        write!(f, "{target}{}{}", self.mode.operator(), self.pattern)
+    }
+}
+
+/// Error for a clause that doesn't follow the `key~npx` syntax
+fn invalid_clause(s: &str) -> ConfigError {
+    ConfigError::validation_failed(
+        "SearchQuery",
+        format!("Invalid query clause '{s}'"),
+        "Write clauses as key~TEXT, value~TEXT, key=TEXT (exact) or value=~REGEX",
+    )
+}
+
+/// Where one clause matched
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClauseMatch {
+    /// The clause that matched
+    pub clause: QueryClause,
+
+    /// Key path of the matching key or value
+    pub key_path: String,
+
+    /// The matching key or value
+    pub text: String,
+}
+
+/// A subtree in which every clause of a query matched
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryMatch {
+    /// Key path of the subtree root (e.g. `mcpServers.github`)
+    pub root: String,
+
+    /// Which config this was found in
+    pub source: ConfigScope,
+
+    /// Path to the config file
+    pub config_path: PathBuf,
+
+    /// Every match of every clause inside the subtree, in document order
+    pub matches: Vec<ClauseMatch>,
+}
+
+/// A clause compiled for matching
+enum Matcher {
+    Substring(String),
+    Exact(String),
+    Regex(Regex),
+}
+
+/// A clause and its compiled matcher
+struct CompiledClause<'a> {
+    clause: &'a QueryClause,
+    matcher: Matcher,
+    case_sensitive: bool,
+    whole_word: bool,
+}
+
+impl<'a> CompiledClause<'a> {
+    fn compile(clause: &'a QueryClause, options: &SearchOptions) -> Result<Self> {
+        let case_sensitive = options.case_sensitive;
+        let fold = |s: &str| {
+            if case_sensitive {
+                s.to_string()
+            } else {
+                s.to_lowercase()
+            }
+        };
+
+        let matcher = match clause.mode {
+            MatchMode::Substring => Matcher::Substring(fold(&clause.pattern)),
+            MatchMode::Exact => Matcher::Exact(fold(&clause.pattern)),
+            MatchMode::Regex => Matcher::Regex(
+                RegexBuilder::new(&clause.pattern)
+                    .case_insensitive(!case_sensitive)
+                    .build()
+                    .map_err(|e| {
+                        ConfigError::validation_failed(
+                            "SearchQuery",
+                            format!("Invalid regular expression in '{clause}': {e}"),
+                            "Check the regular expression syntax",
+                        )
+                    })?,
+            ),
+        };
+
+        Ok(Self {
+            clause,
+            matcher,
+            case_sensitive,
+            whole_word: options.whole_word,
+        })
+    }
+
+    fn is_match(&self, text: &str) -> bool {
+        let folded;
+        let text = if self.case_sensitive {
+            text
+        } else {
+            folded = text.to_lowercase();
+            &folded
+        };
+
+        match &self.matcher {
+            Matcher::Substring(pattern) if self.whole_word => contains_word(text, pattern),
+            Matcher::Substring(pattern) => text.contains(pattern.as_str()),
+            Matcher::Exact(pattern) => text == pattern,
+            Matcher::Regex(regex) => regex.is_match(text),
+        }
+    }
+}
+
+/// A clause hit before grouping: clause index, subtree root, match
+type Hit = (usize, String, ClauseMatch);
+
+impl ConfigSearcher {
+    /// Find the subtrees of a configuration matching all query clauses
+    ///
+    /// Uses the clauses, case sensitivity and depth limit of the searcher's
+    /// options; see the [module docs](self) for how subtrees are scoped.
+    ///
+    /// # Returns
+    /// One result per matching subtree, in document order
+    ///
+    /// # Errors
+    /// Returns an error if the options have no clauses or a regex clause
+    /// doesn't compile
+    pub fn search_where(
+        &self,
+        config: &ClaudeConfig,
+        source: ConfigScope,
+        config_path: PathBuf,
+    ) -> Result<Vec<QueryMatch>> {
+        if self.options.clauses.is_empty() {
+            return Err(ConfigError::validation_failed(
+                "SearchQuery",
+                "The query has no clauses",
+                "Add at least one clause, e.g. key~npx",
+            ));
+        }
+
+        let clauses = self
+            .options
+            .clauses
+            .iter()
+            .map(|clause| CompiledClause::compile(clause, &self.options))
+            .collect::<Result<Vec<_>>>()?;
+
+        let config_value = serde_json::to_value(config)?;
+        let mut hits = Vec::new();
+        self.collect_hits(&clauses, &config_value, "", None, 0, &mut hits);
+
+        // Group by subtree root, keeping the order roots were first seen
+        let mut groups: Vec<(String, Vec<bool>, Vec<ClauseMatch>)> = Vec::new();
+        let mut index: HashMap<String, usize> = HashMap::new();
+        for (clause, root, hit) in hits {
+            let position = *index.entry(root.clone()).or_insert_with(|| {
+                groups.push((root, vec![false; clauses.len()], Vec::new()));
+                groups.len() - 1
+            });
+            let (_, matched, matches) = &mut groups[position];
+            matched[clause] = true;
+            matches.push(hit);
+        }
+
+        Ok(groups
+            .into_iter()
+            .filter(|(_, matched, _)| matched.iter().all(|m| *m))
+            .map(|(root, _, matches)| QueryMatch {
+                root,
+                source,
+                config_path: config_path.clone(),
+                matches,
+            })
+            .collect())
+    }
+
+    /// Record every clause hit in `value`
+    ///
+    /// `root` is the subtree root of `value`, or `None` at the top level and
+    /// directly inside a top-level value, where each child starts its own
+    /// subtree.
+    fn collect_hits(
+        &self,
+        clauses: &[CompiledClause<'_>],
+        value: &Value,
+        current_path: &str,
+        root: Option<&str>,
+        depth: usize,
+        hits: &mut Vec<Hit>,
+    ) {
+        if let Some(max_depth) = self.options.max_depth {
+            if depth > max_depth {
+                return;
+            }
+        }
+
+        let top_level = current_path.is_empty();
+        let child_root = |child_path: &str| root.unwrap_or(child_path).to_string();
+
+        match value {
+            Value::Object(map) => {
+                for (key, child) in map {
+                    let child_path = if top_level {
+                        key.clone()
+                    } else {
+                        format!("{current_path}.{key}")
+                    };
+                    let entry_root = child_root(&child_path);
+
+                    record(
+                        clauses,
+                        ClauseTarget::Key,
+                        key,
+                        &child_path,
+                        &entry_root,
+                        hits,
+                    );
+
+                    let next_root = (!top_level).then_some(entry_root.as_str());
+                    self.collect_hits(clauses, child, &child_path, next_root, depth + 1, hits);
+                }
+            }
+            Value::Array(items) => {
+                for (i, child) in items.iter().enumerate() {
+                    let child_path = format!("{current_path}[{i}]");
+                    let entry_root = child_root(&child_path);
+                    self.collect_hits(
+                        clauses,
+                        child,
+                        &child_path,
+                        Some(&entry_root),
+                        depth + 1,
+                        hits,
+                    );
+                }
+            }
+            Value::String(s) => {
+                let root = child_root(current_path);
+                record(clauses, ClauseTarget::Value, s, current_path, &root, hits);
+            }
+            Value::Number(n) => {
+                let root = child_root(current_path);
+                let text = n.to_string();
+                record(
+                    clauses,
+                    ClauseTarget::Value,
+                    &text,
+                    current_path,
+                    &root,
+                    hits,
+                );
+            }
+            Value::Bool(b) => {
+                let root = child_root(current_path);
+                let text = b.to_string();
+                record(
+                    clauses,
+                    ClauseTarget::Value,
+                    &text,
+                    current_path,
+                    &root,
+                    hits,
+                );
+            }
+            Value::Null => {}
+        }
+    }
+}
+
+/// Record a hit for every clause on `target` that matches `text`
+fn record(
+    clauses: &[CompiledClause<'_>],
+    target: ClauseTarget,
+    text: &str,
+    key_path: &str,
+    root: &str,
+    hits: &mut Vec<Hit>,
+) {
+    for (i, compiled) in clauses.iter().enumerate() {
+        if compiled.clause.target == target && compiled.is_match(text) {
+            hits.push((
+                i,
+                root.to_string(),
+                ClauseMatch {
+                    clause: compiled.clause.clone(),
+                    key_path: key_path.to_string(),
+                    text: text.to_string(),
+                },
+            ));
+        }
+    }
+}
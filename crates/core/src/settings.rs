@@ -0,0 +1,252 @@
+//! ccm's own settings
+//!
+//! ccm stores its preferences in a `"ccm"` section of the global config
+//! file. Claude Code ignores the section, and ccm preserves it like any
+//! other unknown field.
+
+use crate::config::ClaudeConfig;
+use crate::error::{ConfigError, Result};
+use crate::paths::{expand_env, expand_tilde};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+/// Key of the ccm settings section in the global config
+pub const SETTINGS_KEY: &str = "ccm";
+
+/// Prefix marking a project argument as an alias (`--project @api`)
+pub const ALIAS_PREFIX: char = '@';
+
+/// Typed view of the ccm settings section
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CcmSettings {
+    /// Whether to count command usage locally (default: off)
+    #[serde(default)]
+    pub usage_tracking: bool,
+
+    /// Whether to log MCP server changes to the operation log (default: off)
+    #[serde(default)]
+    pub audit_log: bool,
+
+    /// Write this file in strict canonical form (sorted unordered arrays,
+    /// trailing newline)
+    ///
+    /// Read from the config being written, so a project opts in through the
+    /// `ccm` section of its own config.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub canonical_on_write: bool,
+
+    /// Days to keep risk snapshots of this file regardless of the backup
+    /// retention count (default: 90, see [`RiskyOperation`](crate::backup::RiskyOperation))
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub risk_snapshot_days: Option<u32>,
+
+    /// Base config the global config extends: a path (relative to the
+    /// declaring file) or URL, see [`inherit`](crate::config::inherit)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extends: Option<String>,
+
+    /// Project path aliases, used as `@name` wherever a project path is accepted
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub aliases: BTreeMap<String, String>,
+
+    /// Settings not recognized by this version
+    #[serde(flatten)]
+    pub unknown: HashMap<String, Value>,
+}
+
+/// Config file reduced to its ccm settings section
+#[derive(Deserialize)]
+struct SettingsOnly {
+    #[serde(default, rename = "ccm")]
+    section: Option<Value>,
+}
+
+impl CcmSettings {
+    /// Read ccm's settings from the text of a config file
+    ///
+    /// Cheaper than parsing a whole [`ClaudeConfig`] and calling
+    /// [`ClaudeConfig::ccm_settings`]: every other section is skipped. As
+    /// there, a malformed section yields default settings.
+    ///
+    /// # Errors
+    /// Returns an error if `text` is not valid JSON
+    pub fn from_config_json(text: &str) -> Result<Self> {
+        let config: SettingsOnly = serde_json::from_str(text)?;
+        Ok(match config.section {
+            Some(value) => serde_json::from_value(value).unwrap_or_else(|e| {
+                tracing::warn!("Ignoring invalid '{SETTINGS_KEY}' settings section: {e}");
+                CcmSettings::default()
+            }),
+            None => CcmSettings::default(),
+        })
+    }
+
+    /// Define (or redefine) a project path alias
+    ///
+    /// # Errors
+    /// Returns an error if the name is empty, starts with `@`, or contains
+    /// whitespace or path separators
+    pub fn add_alias(&mut self, name: &str, target: &Path) -> Result<()> {
+        let valid = !name.is_empty()
+            && !name.starts_with(ALIAS_PREFIX)
+            && !name.contains(|c: char| c.is_whitespace() || c == '/' || c == '\\');
+        if !valid {
+            return Err(ConfigError::validation_failed(
+                "AliasName",
+                format!("Invalid alias name: '{name}'"),
+                "Use a name without spaces, slashes or a leading @ (e.g. 'api')",
+            ));
+        }
+        self.aliases
+            .insert(name.to_string(), target.to_string_lossy().to_string());
+        Ok(())
+    }
+
+    /// Remove a project path alias
+    ///
+    /// # Returns
+    /// The alias target, if the alias existed
+    pub fn remove_alias(&mut self, name: &str) -> Option<String> {
+        self.aliases
+            .remove(name.strip_prefix(ALIAS_PREFIX).unwrap_or(name))
+    }
+
+    /// Resolve an `@name` argument through the alias table
+    ///
+    /// `~` and environment variables in the target are expanded.
+    ///
+    /// # Returns
+    /// The target directory, or None if `input` is not an alias reference
+    /// or the alias is not defined
+    pub fn resolve_alias(&self, input: &str) -> Option<PathBuf> {
+        let name = input.strip_prefix(ALIAS_PREFIX)?;
+        let target = self.aliases.get(name)?;
+        Some(expand_tilde(Path::new(&expand_env(target))))
+    }
+
+    /// Aliases whose target is not an existing directory
+    pub fn stale_aliases(&self) -> Vec<(&str, PathBuf)> {
+        self.aliases
+            .keys()
+            .filter_map(|name| {
+                let target = self.resolve_alias(&format!("{ALIAS_PREFIX}{name}"))?;
+                (!target.is_dir()).then_some((name.as_str(), target))
+            })
+            .collect()
+    }
+}
+
+impl ClaudeConfig {
+    /// Get ccm's settings from this config
+    ///
+    /// Returns default settings if the section is missing or malformed.
+    pub fn ccm_settings(&self) -> CcmSettings {
+        match self.unknown.get(SETTINGS_KEY) {
+            Some(value) => serde_json::from_value(value.clone()).unwrap_or_else(|e| {
+                tracing::warn!("Ignoring invalid '{SETTINGS_KEY}' settings section: {e}");
+                CcmSettings::default()
+            }),
+            None => CcmSettings::default(),
+        }
+    }
+
+    /// Store ccm's settings in this config
+    pub fn set_ccm_settings(&mut self, settings: &CcmSettings) {
+        // CcmSettings only contains JSON-compatible types
+        if let Ok(value) = serde_json::to_value(settings) {
+            self.unknown.insert(SETTINGS_KEY.to_string(), value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // TDD Test 1: Missing section yields defaults
+    #[test]
+    fn test_settings_default_when_missing() {
+        let config = ClaudeConfig::new();
+        assert!(!config.ccm_settings().usage_tracking);
+    }
+
+    // TDD Test 2: Settings round-trip and keep unknown keys
+    #[test]
+    fn test_settings_round_trip() {
+        let mut config: ClaudeConfig =
+            serde_json::from_str(r#"{"ccm": {"usageTracking": true, "future": 1}}"#).unwrap();
+
+        let mut settings = config.ccm_settings();
+        assert!(settings.usage_tracking);
+
+        settings.usage_tracking = false;
+        config.set_ccm_settings(&settings);
+
+        let json = serde_json::to_value(&config).unwrap();
+        assert_eq!(json["ccm"]["usageTracking"], false);
+        assert_eq!(json["ccm"]["future"], 1);
+    }
+
+    // TDD Test 2b: Aliases resolve only with the @ prefix
+    #[test]
+    fn test_aliases_resolve_and_report_stale_targets() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let mut settings = CcmSettings::default();
+        settings.add_alias("api", temp_dir.path()).unwrap();
+        settings
+            .add_alias("gone", &temp_dir.path().join("missing"))
+            .unwrap();
+
+        assert_eq!(
+            settings.resolve_alias("@api").as_deref(),
+            Some(temp_dir.path())
+        );
+        assert_eq!(settings.resolve_alias("api"), None);
+        assert_eq!(settings.resolve_alias("@unknown"), None);
+
+        let stale = settings.stale_aliases();
+        assert_eq!(stale.len(), 1);
+        assert_eq!(stale[0].0, "gone");
+
+        assert!(settings.add_alias("@api", temp_dir.path()).is_err());
+        assert!(settings.add_alias("my api", temp_dir.path()).is_err());
+        assert!(settings.remove_alias("@gone").is_some());
+        assert!(settings.remove_alias("gone").is_none());
+
+        // Empty alias tables are not written
+        let mut config = ClaudeConfig::new();
+        config.set_ccm_settings(&CcmSettings::default());
+        assert!(serde_json::to_value(&config).unwrap()["ccm"]
+            .get("aliases")
+            .is_none());
+    }
+
+    // TDD Test 3: Malformed section falls back to defaults
+    #[test]
+    fn test_malformed_settings_fall_back_to_default() {
+        let config: ClaudeConfig =
+            serde_json::from_str(r#"{"ccm": {"usageTracking": "yes"}}"#).unwrap();
+        assert_eq!(config.ccm_settings(), CcmSettings::default());
+    }
+
+    // TDD Test 4: Settings read from config text match the full parse
+    #[test]
+    fn test_settings_from_config_json() {
+        let text = r#"{"allowedPaths": ["~/a"], "ccm": {"usageTracking": true}}"#;
+        let config: ClaudeConfig = serde_json::from_str(text).unwrap();
+        assert_eq!(
+            CcmSettings::from_config_json(text).unwrap(),
+            config.ccm_settings()
+        );
+        assert!(CcmSettings::from_config_json(text).unwrap().usage_tracking);
+
+        assert_eq!(
+            CcmSettings::from_config_json(r#"{"ccm": {"usageTracking": "yes"}}"#).unwrap(),
+            CcmSettings::default()
+        );
+        assert!(CcmSettings::from_config_json("{ invalid").is_err());
+    }
+}
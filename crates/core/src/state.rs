@@ -0,0 +1,377 @@
+//! Per-project UI state storage
+//!
+//! This module stores frontend state (last opened tab, collapsed sections, etc.)
+//! outside of `.claude/config.json` so that files users commit are never dirtied.
+//! State lives in `<global_config_dir>/state/<project-hash>.json` and is never
+//! merged into a `ClaudeConfig` or included in backups.
+
+use crate::{
+    config::manager::atomic_write,
+    error::{ConfigError, Result},
+    hash::content_hash,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Default maximum size of a single state file (64 KiB)
+const DEFAULT_MAX_FILE_SIZE: usize = 64 * 1024;
+
+/// On-disk representation of a project's state file
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ProjectState {
+    /// Canonical project root this state belongs to (used by garbage collection)
+    project_root: String,
+
+    /// Stored values keyed by name
+    #[serde(default)]
+    values: BTreeMap<String, Value>,
+}
+
+/// Store for per-project UI state
+///
+/// Each project gets its own JSON file named after a stable hash of its
+/// canonical root path. Writes are atomic and each file is capped in size.
+#[derive(Debug, Clone)]
+pub struct StateStore {
+    /// Directory holding state files
+    state_dir: PathBuf,
+    /// Maximum size of a single state file in bytes
+    max_file_size: usize,
+}
+
+impl StateStore {
+    /// Create a new StateStore
+    ///
+    /// # Arguments
+    /// * `state_dir` - Directory to store state files
+    pub fn new(state_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            state_dir: state_dir.into(),
+            max_file_size: DEFAULT_MAX_FILE_SIZE,
+        }
+    }
+
+    /// Set the maximum size of a single state file in bytes
+    pub fn with_max_file_size(mut self, max_file_size: usize) -> Self {
+        self.max_file_size = max_file_size;
+        self
+    }
+
+    /// Get the state directory path
+    pub fn state_dir(&self) -> &Path {
+        &self.state_dir
+    }
+
+    /// Get the state file path for a project
+    ///
+    /// Different spellings of the same root (e.g. with a trailing slash)
+    /// map to the same file.
+    pub fn state_path(&self, project_root: &Path) -> PathBuf {
+        let root = canonical_root(project_root);
+        self.state_dir
+            .join(format!("{}.json", content_hash(root.as_bytes())))
+    }
+
+    /// Get a single value from a project's state
+    ///
+    /// # Returns
+    /// The stored value, or `None` if the key has not been set
+    ///
+    /// # Errors
+    /// Returns an error if the state file cannot be read or the stored value
+    /// cannot be deserialized as `T`
+    pub fn get<T: DeserializeOwned>(&self, project_root: &Path, key: &str) -> Result<Option<T>> {
+        let state = self.read_state(project_root)?;
+
+        state
+            .values
+            .get(key)
+            .map(|value| {
+                serde_json::from_value(value.clone()).map_err(|e| {
+                    ConfigError::Generic(format!("Failed to read UI state '{key}': {e}"))
+                })
+            })
+            .transpose()
+    }
+
+    /// Get all values stored for a project
+    pub fn get_all(&self, project_root: &Path) -> Result<BTreeMap<String, Value>> {
+        Ok(self.read_state(project_root)?.values)
+    }
+
+    /// Set a value in a project's state
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - The value cannot be serialized
+    /// - The resulting state file would exceed the size cap
+    /// - The state file cannot be written
+    pub fn set<T: Serialize>(&self, project_root: &Path, key: &str, value: &T) -> Result<()> {
+        let value = serde_json::to_value(value)?;
+
+        let mut state = self.read_state(project_root)?;
+        state.project_root = canonical_root(project_root);
+        state.values.insert(key.to_string(), value);
+
+        self.write_state(project_root, &state)
+    }
+
+    /// Remove a single value from a project's state
+    ///
+    /// # Returns
+    /// Whether the key was present
+    pub fn remove(&self, project_root: &Path, key: &str) -> Result<bool> {
+        let mut state = self.read_state(project_root)?;
+
+        if state.values.remove(key).is_none() {
+            return Ok(false);
+        }
+
+        if state.values.is_empty() {
+            self.clear(project_root)?;
+        } else {
+            self.write_state(project_root, &state)?;
+        }
+
+        Ok(true)
+    }
+
+    /// Remove all state for a project
+    ///
+    /// # Returns
+    /// Whether a state file was removed
+    pub fn clear(&self, project_root: &Path) -> Result<bool> {
+        let path = self.state_path(project_root);
+
+        if !path.exists() {
+            return Ok(false);
+        }
+
+        fs::remove_file(&path)
+            .map_err(|e| ConfigError::filesystem("remove state file", &path, e))?;
+
+        Ok(true)
+    }
+
+    /// Remove all state files
+    ///
+    /// # Returns
+    /// Number of state files removed
+    pub fn clear_all(&self) -> Result<usize> {
+        let files = self.state_files()?;
+
+        for path in &files {
+            fs::remove_file(path)
+                .map_err(|e| ConfigError::filesystem("remove state file", path, e))?;
+        }
+
+        Ok(files.len())
+    }
+
+    /// Remove state files whose project roots no longer exist
+    ///
+    /// Unreadable state files are also removed since they can never be used.
+    ///
+    /// # Returns
+    /// Number of state files removed
+    pub fn gc(&self) -> Result<usize> {
+        let mut removed = 0;
+
+        for path in self.state_files()? {
+            let stale = match fs::read_to_string(&path)
+                .ok()
+                .and_then(|content| serde_json::from_str::<ProjectState>(&content).ok())
+            {
+                Some(state) => !Path::new(&state.project_root).exists(),
+                None => true,
+            };
+
+            if stale {
+                fs::remove_file(&path)
+                    .map_err(|e| ConfigError::filesystem("remove state file", &path, e))?;
+                tracing::debug!("Removed stale state file: {}", path.display());
+                removed += 1;
+            }
+        }
+
+        Ok(removed)
+    }
+
+    /// List all state files in the state directory
+    fn state_files(&self) -> Result<Vec<PathBuf>> {
+        if !self.state_dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut files = Vec::new();
+
+        for entry in fs::read_dir(&self.state_dir)
+            .map_err(|e| ConfigError::filesystem("read state directory", &self.state_dir, e))?
+        {
+            let entry = entry
+                .map_err(|e| ConfigError::filesystem("read state entry", &self.state_dir, e))?;
+            let path = entry.path();
+
+            if path.extension().and_then(|e| e.to_str()) == Some("json") {
+                files.push(path);
+            }
+        }
+
+        Ok(files)
+    }
+
+    /// Read a project's state, returning empty state if none exists
+    fn read_state(&self, project_root: &Path) -> Result<ProjectState> {
+        let path = self.state_path(project_root);
+
+        if !path.exists() {
+            return Ok(ProjectState {
+                project_root: canonical_root(project_root),
+                values: BTreeMap::new(),
+            });
+        }
+
+        let content = fs::read_to_string(&path)
+            .map_err(|e| ConfigError::filesystem("read state file", &path, e))?;
+
+        serde_json::from_str(&content).map_err(|e| {
+            ConfigError::Generic(format!("Invalid state file {}: {e}", path.display()))
+        })
+    }
+
+    /// Atomically write a project's state, enforcing the size cap
+    fn write_state(&self, project_root: &Path, state: &ProjectState) -> Result<()> {
+        let path = self.state_path(project_root);
+        let json = serde_json::to_string_pretty(state)?;
+
+        if json.len() > self.max_file_size {
+            return Err(ConfigError::validation_failed(
+                "StateSizeLimit",
+                format!(
+                    "UI state for {} would be {} bytes (limit: {} bytes)",
+                    state.project_root,
+                    json.len(),
+                    self.max_file_size
+                ),
+                "Store less data in UI state or clear it with 'ccm state clear --project <path>'",
+            ));
+        }
+
+        atomic_write(&path, &json)
+    }
+}
+
+/// Resolve the canonical string form of a project root
+///
+/// Uses the filesystem's canonical path when the root exists, and falls back
+/// to a component-normalized path otherwise.
+fn canonical_root(project_root: &Path) -> String {
+    fs::canonicalize(project_root)
+        .unwrap_or_else(|_| project_root.components().collect())
+        .to_string_lossy()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    // TDD Test 1: Values round-trip through the store
+    #[test]
+    fn test_state_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let project = temp_dir.path().join("project");
+        fs::create_dir_all(&project).unwrap();
+        let store = StateStore::new(temp_dir.path().join("state"));
+
+        store.set(&project, "lastTab", &"mcp").unwrap();
+        store.set(&project, "collapsed", &vec!["skills"]).unwrap();
+
+        let tab: Option<String> = store.get(&project, "lastTab").unwrap();
+        let collapsed: Option<Vec<String>> = store.get(&project, "collapsed").unwrap();
+        let missing: Option<String> = store.get(&project, "missing").unwrap();
+
+        assert_eq!(tab.as_deref(), Some("mcp"));
+        assert_eq!(collapsed, Some(vec!["skills".to_string()]));
+        assert!(missing.is_none());
+        assert_eq!(store.get_all(&project).unwrap().len(), 2);
+
+        // Nothing is written next to the project's config
+        assert!(!project.join(".claude").exists());
+    }
+
+    // TDD Test 2: Different spellings of the same root share a state file
+    #[test]
+    fn test_state_path_stable_across_spellings() {
+        let temp_dir = TempDir::new().unwrap();
+        let project = temp_dir.path().join("project");
+        fs::create_dir_all(&project).unwrap();
+        let store = StateStore::new(temp_dir.path().join("state"));
+
+        let with_slash = PathBuf::from(format!("{}/", project.display()));
+        let with_dot = project.join(".");
+
+        assert_eq!(store.state_path(&project), store.state_path(&with_slash));
+        assert_eq!(store.state_path(&project), store.state_path(&with_dot));
+
+        // Also stable for roots that don't exist on disk
+        let missing = temp_dir.path().join("missing");
+        let missing_slash = PathBuf::from(format!("{}/", missing.display()));
+        assert_eq!(store.state_path(&missing), store.state_path(&missing_slash));
+    }
+
+    // TDD Test 3: Garbage collection removes state for deleted projects only
+    #[test]
+    fn test_gc_removes_missing_projects() {
+        let temp_dir = TempDir::new().unwrap();
+        let kept = temp_dir.path().join("kept");
+        let deleted = temp_dir.path().join("deleted");
+        fs::create_dir_all(&kept).unwrap();
+        fs::create_dir_all(&deleted).unwrap();
+        let store = StateStore::new(temp_dir.path().join("state"));
+
+        store.set(&kept, "tab", &1).unwrap();
+        store.set(&deleted, "tab", &2).unwrap();
+        fs::remove_dir_all(&deleted).unwrap();
+
+        assert_eq!(store.gc().unwrap(), 1);
+        assert!(store.state_path(&kept).exists());
+        assert_eq!(store.get::<i32>(&kept, "tab").unwrap(), Some(1));
+        assert_eq!(store.gc().unwrap(), 0);
+    }
+
+    // TDD Test 4: Writes exceeding the size cap are rejected
+    #[test]
+    fn test_size_cap_enforced() {
+        let temp_dir = TempDir::new().unwrap();
+        let project = temp_dir.path().join("project");
+        let store = StateStore::new(temp_dir.path().join("state")).with_max_file_size(128);
+
+        let result = store.set(&project, "big", &"x".repeat(256));
+
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("StateSizeLimit"));
+        assert!(!store.state_path(&project).exists());
+    }
+
+    // TDD Test 5: Clearing removes a project's state
+    #[test]
+    fn test_clear_and_clear_all() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = StateStore::new(temp_dir.path().join("state"));
+        let a = temp_dir.path().join("a");
+        let b = temp_dir.path().join("b");
+
+        store.set(&a, "tab", &"x").unwrap();
+        store.set(&b, "tab", &"y").unwrap();
+
+        assert!(store.clear(&a).unwrap());
+        assert!(!store.clear(&a).unwrap());
+        assert_eq!(store.clear_all().unwrap(), 1);
+    }
+}
@@ -0,0 +1,551 @@
+//! Support bundle generation
+//!
+//! This module gathers everything needed to diagnose a bug report —
+//! redacted configs, path resolution, doctor checks, backups, recent
+//! journal and log lines, and environment info — into a single archive.
+//! Every config included passes through [`Redactor`], and the manifest lists
+//! exactly which keys were redacted.
+
+use crate::{
+    backup::BackupManager,
+    doctor::{self, DoctorReport},
+    error::{ConfigError, Result},
+    paths::{find_project_config, get_backup_dir, get_global_config_path},
+    redact::Redactor,
+    types::BackupInfo,
+};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::Value;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Name of the manifest file inside the archive
+pub const MANIFEST_NAME: &str = "manifest.json";
+
+/// Options controlling what goes into a support bundle
+#[derive(Debug, Clone)]
+pub struct SupportOptions {
+    /// Path to the global config file
+    pub global_config_path: PathBuf,
+
+    /// Directory holding global config backups
+    pub backup_dir: PathBuf,
+
+    /// Project whose config should be included
+    ///
+    /// Project configs can contain private paths, so frontends should ask
+    /// the user before setting this.
+    pub project_path: Option<PathBuf>,
+
+    /// Audit/undo journal to sample, if one exists
+    pub journal_path: Option<PathBuf>,
+
+    /// Number of most recent journal entries to include
+    pub journal_entries: usize,
+
+    /// Log file to sample, if logging to a file is configured
+    pub log_path: Option<PathBuf>,
+
+    /// Number of trailing log lines to include
+    pub log_lines: usize,
+}
+
+impl Default for SupportOptions {
+    fn default() -> Self {
+        Self {
+            global_config_path: get_global_config_path(),
+            backup_dir: get_backup_dir(),
+            project_path: None,
+            journal_path: None,
+            journal_entries: 50,
+            log_path: None,
+            log_lines: 200,
+        }
+    }
+}
+
+/// A single file in a support bundle
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BundleFile {
+    /// File name inside the archive
+    pub name: String,
+
+    /// File contents
+    pub contents: Vec<u8>,
+}
+
+/// Manifest entry describing a bundled file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ManifestEntry {
+    /// File name inside the archive
+    pub name: String,
+
+    /// What the file contains
+    pub description: String,
+}
+
+/// Keys redacted from a bundled file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ManifestRedaction {
+    /// File name inside the archive
+    pub file: String,
+
+    /// Dot-separated key paths whose values were replaced
+    pub key_paths: Vec<String>,
+}
+
+/// Manifest describing a support bundle
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleManifest {
+    /// When the bundle was collected
+    pub created_at: DateTime<Utc>,
+
+    /// ccm version that produced the bundle
+    pub ccm_version: String,
+
+    /// Files included in the bundle
+    pub files: Vec<ManifestEntry>,
+
+    /// Values redacted from included files
+    pub redactions: Vec<ManifestRedaction>,
+
+    /// Items that were not included, with the reason
+    pub omitted: Vec<String>,
+}
+
+/// Collected contents of a support bundle
+#[derive(Debug, Clone)]
+pub struct BundleContents {
+    /// Bundle manifest
+    pub manifest: BundleManifest,
+
+    /// Bundled files (excluding the manifest)
+    pub files: Vec<BundleFile>,
+}
+
+impl BundleContents {
+    /// Write the bundle as a zip archive
+    ///
+    /// # Arguments
+    /// * `path` - Destination archive path
+    ///
+    /// # Errors
+    /// Returns an error if the archive cannot be written
+    pub fn write_zip(&self, path: &Path) -> Result<PathBuf> {
+        if let Some(parent) = path.parent() {
+            if !parent.as_os_str().is_empty() && !parent.exists() {
+                fs::create_dir_all(parent)
+                    .map_err(|e| ConfigError::filesystem("create bundle directory", parent, e))?;
+            }
+        }
+
+        let file = fs::File::create(path)
+            .map_err(|e| ConfigError::filesystem("create support bundle", path, e))?;
+        let mut zip = zip::ZipWriter::new(file);
+        let options = zip::write::SimpleFileOptions::default()
+            .compression_method(zip::CompressionMethod::Deflated);
+
+        let manifest = serde_json::to_vec_pretty(&self.manifest)?;
+        let entries = std::iter::once((MANIFEST_NAME, manifest.as_slice())).chain(
+            self.files
+                .iter()
+                .map(|f| (f.name.as_str(), f.contents.as_slice())),
+        );
+
+        for (name, contents) in entries {
+            zip.start_file(name, options).map_err(zip_error)?;
+            zip.write_all(contents)
+                .map_err(|e| ConfigError::filesystem("write support bundle", path, e))?;
+        }
+
+        zip.finish().map_err(zip_error)?;
+
+        tracing::info!("Wrote support bundle to: {}", path.display());
+
+        Ok(path.to_path_buf())
+    }
+
+    /// Add a file and its manifest entry
+    fn push(&mut self, name: &str, description: impl Into<String>, contents: Vec<u8>) {
+        self.manifest.files.push(ManifestEntry {
+            name: name.to_string(),
+            description: description.into(),
+        });
+        self.files.push(BundleFile {
+            name: name.to_string(),
+            contents,
+        });
+    }
+
+    /// Add a JSON file and its manifest entry
+    fn push_json<T: Serialize>(&mut self, name: &str, description: &str, value: &T) -> Result<()> {
+        let contents = serde_json::to_vec_pretty(value)?;
+        self.push(name, description, contents);
+        Ok(())
+    }
+
+    /// Record an item that was left out
+    fn omit(&mut self, reason: impl Into<String>) {
+        self.manifest.omitted.push(reason.into());
+    }
+}
+
+/// Existence of a resolved path
+#[derive(Debug, Serialize)]
+struct PathStatus {
+    path: String,
+    exists: bool,
+}
+
+impl PathStatus {
+    fn new(path: &Path) -> Self {
+        Self {
+            path: path.to_string_lossy().to_string(),
+            exists: path.exists(),
+        }
+    }
+}
+
+/// Which files and directories ccm resolved
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ResolutionReport {
+    global_config: PathStatus,
+    backup_dir: PathStatus,
+    project_root: Option<PathStatus>,
+    project_config: Option<PathStatus>,
+}
+
+/// Information about the machine ccm runs on
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct EnvironmentInfo {
+    os: &'static str,
+    os_family: &'static str,
+    arch: &'static str,
+    ccm_version: &'static str,
+}
+
+/// Collect the contents of a support bundle
+///
+/// Configs that cannot be parsed are omitted rather than included raw,
+/// since their secrets cannot be reliably redacted.
+///
+/// # Arguments
+/// * `opts` - What to include
+///
+/// # Errors
+/// Returns an error if bundle data cannot be serialized
+pub fn collect(opts: &SupportOptions) -> Result<BundleContents> {
+    let mut bundle = BundleContents {
+        manifest: BundleManifest {
+            created_at: Utc::now(),
+            ccm_version: crate::VERSION.to_string(),
+            files: Vec::new(),
+            redactions: Vec::new(),
+            omitted: Vec::new(),
+        },
+        files: Vec::new(),
+    };
+    let mut redactor = Redactor::new();
+
+    let project_config = opts
+        .project_path
+        .as_deref()
+        .and_then(|p| find_project_config(Some(p)));
+
+    // Configs go first so their secrets are known when scrubbing text
+    add_config(
+        &mut bundle,
+        &mut redactor,
+        "global-config.json",
+        "Global configuration (redacted)",
+        &opts.global_config_path,
+    );
+
+    match (&opts.project_path, &project_config) {
+        (Some(_), Some(path)) => add_config(
+            &mut bundle,
+            &mut redactor,
+            "project-config.json",
+            "Project configuration (redacted)",
+            path,
+        ),
+        (Some(root), None) => bundle.omit(format!(
+            "project config: no config found for {}",
+            root.display()
+        )),
+        (None, _) => bundle.omit("project config: not requested"),
+    }
+
+    let resolution = ResolutionReport {
+        global_config: PathStatus::new(&opts.global_config_path),
+        backup_dir: PathStatus::new(&opts.backup_dir),
+        project_root: opts.project_path.as_deref().map(PathStatus::new),
+        project_config: project_config.as_deref().map(PathStatus::new),
+    };
+    bundle.push_json(
+        "resolution.json",
+        "Resolved config files and directories",
+        &resolution,
+    )?;
+
+    let doctor: DoctorReport = doctor::run_checks(
+        &opts.global_config_path,
+        project_config.as_deref(),
+        &opts.backup_dir,
+    );
+    bundle.push_json("doctor.json", "Self-check results", &doctor)?;
+
+    let backups: Vec<BackupInfo> = BackupManager::new(&opts.backup_dir, None)
+        .list_backups(&opts.global_config_path)
+        .unwrap_or_default();
+    bundle.push_json(
+        "backups.json",
+        "Global config backups (metadata only)",
+        &backups,
+    )?;
+
+    match &opts.journal_path {
+        Some(path) if path.exists() => {
+            let lines = tail_lines(path, opts.journal_entries)?;
+            let redacted = lines
+                .iter()
+                .map(|line| match serde_json::from_str::<Value>(line) {
+                    Ok(entry) => redactor.redact_value(&entry).to_string(),
+                    Err(_) => redactor.redact_text(line),
+                })
+                .collect::<Vec<_>>();
+            let key_paths = redactor.take_redacted_paths();
+            if !key_paths.is_empty() {
+                bundle.manifest.redactions.push(ManifestRedaction {
+                    file: "journal.jsonl".to_string(),
+                    key_paths,
+                });
+            }
+            bundle.push(
+                "journal.jsonl",
+                format!(
+                    "Last {} audit/undo journal entries (redacted)",
+                    redacted.len()
+                ),
+                (redacted.join("\n") + "\n").into_bytes(),
+            );
+        }
+        _ => bundle.omit("journal: no audit/undo journal present"),
+    }
+
+    match &opts.log_path {
+        Some(path) if path.exists() => {
+            let lines = tail_lines(path, opts.log_lines)?;
+            let text = redactor.redact_text(&(lines.join("\n") + "\n"));
+            bundle.push(
+                "log.txt",
+                format!(
+                    "Last {} log lines (known secrets and token-like values scrubbed)",
+                    lines.len()
+                ),
+                text.into_bytes(),
+            );
+        }
+        _ => bundle.omit("log: no log file configured"),
+    }
+
+    let environment = EnvironmentInfo {
+        os: std::env::consts::OS,
+        os_family: std::env::consts::FAMILY,
+        arch: std::env::consts::ARCH,
+        ccm_version: crate::VERSION,
+    };
+    bundle.push_json(
+        "environment.json",
+        "Operating system and ccm version",
+        &environment,
+    )?;
+
+    Ok(bundle)
+}
+
+/// Add a redacted config file to the bundle, or record why it was omitted
+fn add_config(
+    bundle: &mut BundleContents,
+    redactor: &mut Redactor,
+    name: &str,
+    description: &str,
+    path: &Path,
+) {
+    let value = match fs::read_to_string(path) {
+        Ok(content) => match serde_json::from_str::<Value>(&content) {
+            Ok(value) => value,
+            Err(e) => {
+                bundle.omit(format!("{name}: not valid JSON, cannot be redacted ({e})"));
+                return;
+            }
+        },
+        Err(e) => {
+            bundle.omit(format!("{name}: cannot read {} ({e})", path.display()));
+            return;
+        }
+    };
+
+    let redacted = redactor.redact_value(&value);
+    let key_paths = redactor.take_redacted_paths();
+
+    if !key_paths.is_empty() {
+        bundle.manifest.redactions.push(ManifestRedaction {
+            file: name.to_string(),
+            key_paths,
+        });
+    }
+
+    match serde_json::to_vec_pretty(&redacted) {
+        Ok(contents) => bundle.push(name, description, contents),
+        Err(e) => bundle.omit(format!("{name}: serialization failed ({e})")),
+    }
+}
+
+/// Read the last `count` lines of a file
+fn tail_lines(path: &Path, count: usize) -> Result<Vec<String>> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| ConfigError::filesystem("read file for support bundle", path, e))?;
+    let lines: Vec<&str> = content.lines().collect();
+    let start = lines.len().saturating_sub(count);
+
+    Ok(lines[start..].iter().map(|l| l.to_string()).collect())
+}
+
+/// Convert a zip error into a ConfigError
+fn zip_error(e: zip::result::ZipError) -> ConfigError {
+    ConfigError::Generic(format!("Failed to write support bundle: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+    use tempfile::TempDir;
+
+    const FAKE_TOKEN: &str = "ghp_FAKEfakeFAKEfake0123456789";
+
+    fn options(temp_dir: &TempDir) -> SupportOptions {
+        SupportOptions {
+            global_config_path: temp_dir.path().join("config.json"),
+            backup_dir: temp_dir.path().join("backups"),
+            ..Default::default()
+        }
+    }
+
+    // TDD Test 1: A planted token never appears anywhere in the archive
+    #[test]
+    fn test_planted_token_never_in_archive() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut opts = options(&temp_dir);
+
+        fs::write(
+            &opts.global_config_path,
+            format!(
+                r#"{{"mcpServers": {{"github": {{"command": "npx", "args": ["--token", "{FAKE_TOKEN}"], "env": {{"GITHUB_TOKEN": "{FAKE_TOKEN}"}}}}}}}}"#
+            ),
+        )
+        .unwrap();
+
+        let project = temp_dir.path().join("project");
+        fs::create_dir_all(project.join(".claude")).unwrap();
+        fs::write(
+            project.join(".claude").join("config.json"),
+            format!(r#"{{"authToken": "{FAKE_TOKEN}"}}"#),
+        )
+        .unwrap();
+        opts.project_path = Some(project);
+
+        let log = temp_dir.path().join("ccm.log");
+        fs::write(&log, format!("starting server with {FAKE_TOKEN}\n")).unwrap();
+        opts.log_path = Some(log);
+
+        let journal = temp_dir.path().join("journal.jsonl");
+        fs::write(
+            &journal,
+            format!(r#"{{"op": "set", "value": "{FAKE_TOKEN}"}}"#),
+        )
+        .unwrap();
+        opts.journal_path = Some(journal);
+
+        let bundle = collect(&opts).unwrap();
+        let archive = temp_dir.path().join("bundle.zip");
+        bundle.write_zip(&archive).unwrap();
+
+        // Raw archive bytes
+        let bytes = fs::read(&archive).unwrap();
+        assert!(!bytes
+            .windows(FAKE_TOKEN.len())
+            .any(|w| w == FAKE_TOKEN.as_bytes()));
+
+        // Decompressed entries
+        let mut zip = zip::ZipArchive::new(fs::File::open(&archive).unwrap()).unwrap();
+        for i in 0..zip.len() {
+            let mut entry = zip.by_index(i).unwrap();
+            let mut contents = String::new();
+            entry.read_to_string(&mut contents).unwrap();
+            assert!(
+                !contents.contains(FAKE_TOKEN),
+                "token leaked into {}",
+                entry.name()
+            );
+        }
+
+        // Manifest lists what was redacted
+        let redacted_files: Vec<_> = bundle
+            .manifest
+            .redactions
+            .iter()
+            .map(|r| r.file.as_str())
+            .collect();
+        assert!(redacted_files.contains(&"global-config.json"));
+        assert!(redacted_files.contains(&"project-config.json"));
+    }
+
+    // TDD Test 2: Project config is only included when requested
+    #[test]
+    fn test_project_config_opt_in() {
+        let temp_dir = TempDir::new().unwrap();
+        let opts = options(&temp_dir);
+        fs::write(&opts.global_config_path, "{}").unwrap();
+
+        let bundle = collect(&opts).unwrap();
+        let names: Vec<_> = bundle.files.iter().map(|f| f.name.as_str()).collect();
+
+        assert!(names.contains(&"global-config.json"));
+        assert!(names.contains(&"doctor.json"));
+        assert!(names.contains(&"environment.json"));
+        assert!(!names.contains(&"project-config.json"));
+        assert!(bundle
+            .manifest
+            .omitted
+            .iter()
+            .any(|o| o.starts_with("project config")));
+    }
+
+    // TDD Test 3: Unparseable configs are omitted, not included raw
+    #[test]
+    fn test_invalid_config_omitted() {
+        let temp_dir = TempDir::new().unwrap();
+        let opts = options(&temp_dir);
+        fs::write(
+            &opts.global_config_path,
+            format!("{{ \"token\": {FAKE_TOKEN}"),
+        )
+        .unwrap();
+
+        let bundle = collect(&opts).unwrap();
+
+        assert!(!bundle.files.iter().any(|f| f.name == "global-config.json"));
+        assert!(bundle
+            .files
+            .iter()
+            .all(|f| !String::from_utf8_lossy(&f.contents).contains(FAKE_TOKEN)));
+    }
+}
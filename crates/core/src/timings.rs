@@ -0,0 +1,138 @@
+//! Operation timing instrumentation
+//!
+//! A [`Timings`] collector records how long the main operations (read,
+//! validate, backup, write, scan, merge) take. Every timed operation also runs
+//! inside a `tracing` span, but the structured summary is available without
+//! installing a subscriber. A disabled collector records nothing.
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// A single timed operation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimingEntry {
+    /// Operation name (e.g. "read", "backup")
+    pub name: &'static str,
+
+    /// Time spent in the operation
+    pub duration: Duration,
+}
+
+/// Collector for operation timings
+///
+/// Clones share the same entries, so one collector can be handed to several
+/// managers and read back once the command finishes.
+#[derive(Debug, Clone, Default)]
+pub struct Timings {
+    /// Recorded entries (None when disabled)
+    entries: Option<Arc<Mutex<Vec<TimingEntry>>>>,
+}
+
+impl Timings {
+    /// Create an enabled collector
+    pub fn new() -> Self {
+        Self {
+            entries: Some(Arc::default()),
+        }
+    }
+
+    /// Create a collector that records nothing
+    pub fn disabled() -> Self {
+        Self { entries: None }
+    }
+
+    /// Whether entries are being recorded
+    pub fn is_enabled(&self) -> bool {
+        self.entries.is_some()
+    }
+
+    /// Run `f` as the named operation and record its duration
+    pub fn time<T>(&self, name: &'static str, f: impl FnOnce() -> T) -> T {
+        let span = tracing::debug_span!("ccm_operation", operation = name);
+        let _guard = span.enter();
+
+        if !self.is_enabled() {
+            return f();
+        }
+
+        let start = Instant::now();
+        let result = f();
+        self.record(name, start.elapsed());
+        result
+    }
+
+    /// Record a duration for the named operation
+    pub fn record(&self, name: &'static str, duration: Duration) {
+        if let Some(entries) = &self.entries {
+            entries
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .push(TimingEntry { name, duration });
+        }
+    }
+
+    /// All recorded entries, in the order they finished
+    pub fn entries(&self) -> Vec<TimingEntry> {
+        self.entries
+            .as_ref()
+            .map(|entries| entries.lock().unwrap_or_else(|e| e.into_inner()).clone())
+            .unwrap_or_default()
+    }
+
+    /// Total time per operation name, in order of first appearance
+    pub fn totals(&self) -> Vec<TimingEntry> {
+        let mut totals: Vec<TimingEntry> = Vec::new();
+
+        for entry in self.entries() {
+            match totals.iter_mut().find(|total| total.name == entry.name) {
+                Some(total) => total.duration += entry.duration,
+                None => totals.push(entry),
+            }
+        }
+
+        totals
+    }
+
+    /// One-line summary, e.g. "read 2ms, backup 840ms, write 12ms"
+    pub fn summary(&self) -> String {
+        self.totals()
+            .iter()
+            .map(|total| format!("{} {}ms", total.name, total.duration.as_millis()))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // TDD Test 1: Enabled collector records entries and sums them per name
+    #[test]
+    fn test_timings_records_and_summarizes() {
+        let timings = Timings::new();
+        timings.record("read", Duration::from_millis(2));
+        timings.record("backup", Duration::from_millis(840));
+        timings.record("read", Duration::from_millis(3));
+
+        assert_eq!(timings.time("write", || 42), 42);
+        assert_eq!(timings.entries().len(), 4);
+        assert!(timings
+            .summary()
+            .starts_with("read 5ms, backup 840ms, write "));
+    }
+
+    // TDD Test 2: Clones share entries; disabled collectors record nothing
+    #[test]
+    fn test_timings_shared_and_disabled() {
+        let timings = Timings::new();
+        timings.clone().time("scan", || ());
+        assert_eq!(timings.entries()[0].name, "scan");
+
+        let disabled = Timings::disabled();
+        assert_eq!(disabled.time("read", || "ok"), "ok");
+        disabled.record("write", Duration::from_millis(1));
+        assert!(disabled.entries().is_empty());
+        assert_eq!(disabled.summary(), "");
+    }
+}
@@ -1,15 +1,19 @@
 //! Shared types used throughout the core library
 
+use crate::paths::Platform;
+use chrono::{DateTime, SecondsFormat, Utc};
 use serde::{Deserialize, Serialize};
 
 /// Configuration scope (where a config applies)
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum ConfigScope {
     /// Global/user configuration (~/.claude/config.json)
     Global,
     /// Project-specific configuration (<project>/.claude/config.json)
     Project,
+    /// Base the global configuration extends (`ccm.extends`); read-only
+    Base,
 }
 
 impl ConfigScope {
@@ -18,6 +22,7 @@ impl ConfigScope {
         match self {
             ConfigScope::Global => "global",
             ConfigScope::Project => "project",
+            ConfigScope::Base => "base",
         }
     }
 }
@@ -43,11 +48,16 @@ pub struct PathLayer {
 
 /// MCP server configuration
 ///
-/// This represents a single MCP server that can be enabled/disabled
+/// This represents a single MCP server that can be enabled/disabled.
+///
+/// `command` may also be read as an array holding the command and its
+/// arguments (`"command": ["npx", "-y", "server"]`); the first element
+/// becomes the command and the rest come before `args`. Servers are always
+/// written with separate `command` and `args`.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "RawMcpServer")]
 pub struct McpServer {
     /// Server identifier (not serialized in JSON - the key is the name)
-    #[serde(skip_deserializing)]
     pub name: String,
     /// Whether this server is enabled
     pub enabled: bool,
@@ -55,11 +65,75 @@ pub struct McpServer {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub command: Option<String>,
     /// Arguments to pass to the command
-    #[serde(default)]
     pub args: Vec<String>,
     /// Environment variables for the server
-    #[serde(default)]
     pub env: std::collections::HashMap<String, String>,
+    /// Startup grace period in milliseconds before the server is contacted
+    #[serde(rename = "timeout", skip_serializing_if = "Option::is_none")]
+    pub timeout_ms: Option<u64>,
+    /// Operating systems the server is used on (`macos`, `linux`,
+    /// `windows`); every platform when unset
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub platforms: Option<Vec<String>>,
+    /// Command per operating system, used instead of `command` there
+    #[serde(rename = "commandByPlatform", skip_serializing_if = "Option::is_none")]
+    pub command_by_platform: Option<std::collections::BTreeMap<String, String>>,
+}
+
+/// `command` as found in a config file: a string, or the command and its
+/// arguments as one array
+#[derive(Deserialize)]
+#[serde(
+    untagged,
+    expecting = "a command string or an array of the command and its arguments"
+)]
+enum CommandRepr {
+    Line(String),
+    Argv(Vec<String>),
+}
+
+/// An [`McpServer`] as found in a config file, before an array `command`
+/// is split into `command` and `args`
+#[derive(Deserialize)]
+struct RawMcpServer {
+    enabled: bool,
+    #[serde(default)]
+    command: Option<CommandRepr>,
+    #[serde(default)]
+    args: Vec<String>,
+    #[serde(default)]
+    env: std::collections::HashMap<String, String>,
+    #[serde(rename = "timeout", default)]
+    timeout_ms: Option<u64>,
+    #[serde(default)]
+    platforms: Option<Vec<String>>,
+    #[serde(rename = "commandByPlatform", default)]
+    command_by_platform: Option<std::collections::BTreeMap<String, String>>,
+}
+
+impl From<RawMcpServer> for McpServer {
+    fn from(raw: RawMcpServer) -> Self {
+        let (command, mut args) = match raw.command {
+            Some(CommandRepr::Line(command)) => (Some(command), Vec::new()),
+            Some(CommandRepr::Argv(mut argv)) if !argv.is_empty() => {
+                let command = argv.remove(0);
+                (Some(command), argv)
+            }
+            Some(CommandRepr::Argv(_)) | None => (None, Vec::new()),
+        };
+        args.extend(raw.args);
+
+        Self {
+            name: String::new(),
+            enabled: raw.enabled,
+            command,
+            args,
+            env: raw.env,
+            timeout_ms: raw.timeout_ms,
+            platforms: raw.platforms,
+            command_by_platform: raw.command_by_platform,
+        }
+    }
 }
 
 impl McpServer {
@@ -71,6 +145,9 @@ impl McpServer {
             command: Some(command.into()),
             args,
             env: std::collections::HashMap::new(),
+            timeout_ms: None,
+            platforms: None,
+            command_by_platform: None,
         }
     }
 
@@ -80,6 +157,47 @@ impl McpServer {
         self
     }
 
+    /// Set the startup timeout in milliseconds
+    pub fn with_timeout_ms(mut self, timeout_ms: u64) -> Self {
+        self.timeout_ms = Some(timeout_ms);
+        self
+    }
+
+    /// Restrict the server to these platforms
+    pub fn with_platforms(mut self, platforms: &[Platform]) -> Self {
+        self.platforms = Some(platforms.iter().map(|p| p.key().to_string()).collect());
+        self
+    }
+
+    /// Use `command` instead of the base command on `platform`
+    pub fn with_platform_command(mut self, platform: Platform, command: impl Into<String>) -> Self {
+        self.command_by_platform
+            .get_or_insert_with(Default::default)
+            .insert(platform.key().to_string(), command.into());
+        self
+    }
+
+    /// Whether the server is used on `platform` (always, if `platforms` is unset)
+    ///
+    /// Misspelled platform names match nothing.
+    pub fn runs_on(&self, platform: Platform) -> bool {
+        self.platforms.as_ref().map_or(true, |platforms| {
+            platforms
+                .iter()
+                .any(|key| Platform::from_key(key) == Some(platform))
+        })
+    }
+
+    /// Command to run on `platform`: its `commandByPlatform` entry, or the base command
+    pub fn command_for(&self, platform: Platform) -> Option<&str> {
+        self.command_by_platform
+            .iter()
+            .flatten()
+            .find(|(key, _)| Platform::from_key(key) == Some(platform))
+            .map(|(_, command)| command.as_str())
+            .or(self.command.as_deref())
+    }
+
     /// Enable this server
     pub fn enable(&mut self) {
         self.enabled = true;
@@ -89,6 +207,48 @@ impl McpServer {
     pub fn disable(&mut self) {
         self.enabled = false;
     }
+
+    /// Render the full invocation, e.g. `npx -y @mcp/server`
+    ///
+    /// Words containing spaces or quotes are quoted the same way string-form
+    /// `args` are split, so the result reads back as the same words. Other
+    /// shell metacharacters (`$`, `;`, `|`, ...) are not escaped, so this is
+    /// for display, not for running through a shell.
+    ///
+    /// # Returns
+    /// `None` when the server has no command
+    pub fn command_line(&self) -> Option<String> {
+        use crate::config::normalize::quote_arg;
+
+        let command = self.command.as_deref()?;
+        let words: Vec<String> = std::iter::once(command)
+            .chain(self.args.iter().map(String::as_str))
+            .map(quote_arg)
+            .collect();
+        Some(words.join(" "))
+    }
+
+    /// Check whether two servers have the same definition
+    ///
+    /// Compares command, args, env, enabled, timeout and the per-platform
+    /// settings, ignoring incidental ordering of args, env entries and
+    /// platforms. The name is not compared, since it only reflects where the
+    /// server is stored.
+    pub fn semantically_equal(&self, other: &McpServer) -> bool {
+        fn sorted<T: Ord + Clone>(items: impl IntoIterator<Item = T>) -> Vec<T> {
+            let mut items: Vec<T> = items.into_iter().collect();
+            items.sort();
+            items
+        }
+
+        self.command == other.command
+            && self.enabled == other.enabled
+            && self.timeout_ms == other.timeout_ms
+            && sorted(self.args.iter()) == sorted(other.args.iter())
+            && sorted(self.env.iter()) == sorted(other.env.iter())
+            && self.platforms.as_ref().map(sorted) == other.platforms.as_ref().map(sorted)
+            && self.command_by_platform == other.command_by_platform
+    }
 }
 
 /// Skill configuration
@@ -151,6 +311,25 @@ impl SourceMap {
     pub fn is_project(&self, key_path: &str) -> bool {
         self.get(key_path) == Some(&ConfigScope::Project)
     }
+
+    /// Check if a key path is inherited from a base config
+    pub fn is_base(&self, key_path: &str) -> bool {
+        self.get(key_path) == Some(&ConfigScope::Base)
+    }
+
+    /// Get the source for a key path, or for its nearest recorded ancestor
+    ///
+    /// `mcpServers.fetch.args[0]` falls back to `mcpServers.fetch.args`,
+    /// `mcpServers.fetch` and `mcpServers`, in that order.
+    pub fn lookup(&self, key_path: &str) -> Option<&ConfigScope> {
+        let mut path = key_path;
+        loop {
+            if let Some(scope) = self.get(path) {
+                return Some(scope);
+            }
+            path = &path[..path.rfind(['.', '['])?];
+        }
+    }
 }
 
 impl Default for SourceMap {
@@ -200,16 +379,90 @@ pub struct BackupInfo {
     pub path: String,
     /// Original file path
     pub original_path: String,
-    /// Creation timestamp
-    pub created_at: chrono::DateTime<chrono::Utc>,
+    /// Creation timestamp (serialized as RFC 3339)
+    pub created_at: DateTime<Utc>,
     /// Backup size in bytes
     pub size: u64,
+    /// Tag in the backup name (e.g. `auto-risk-import`), if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+}
+
+impl BackupInfo {
+    /// Whether this is a snapshot taken before a risky operation
+    ///
+    /// See [`RiskyOperation`](crate::backup::RiskyOperation).
+    pub fn is_risk_snapshot(&self) -> bool {
+        self.tag
+            .as_deref()
+            .is_some_and(|tag| tag.starts_with(crate::backup::RISK_TAG_PREFIX))
+    }
+}
+
+/// Format a timestamp for display, as RFC 3339 (e.g. `2025-01-20T14:30:45Z`)
+///
+/// All user-facing timestamps (CLI output and GUI DTOs) go through this
+/// helper, so they parse the same way as the serialized types.
+pub fn format_timestamp(timestamp: &DateTime<Utc>) -> String {
+    timestamp.to_rfc3339_opts(SecondsFormat::Secs, true)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_mcp_server_semantically_equal_ignores_env_order() {
+        let a = McpServer::new("a", "npx", vec!["-y".into(), "pkg".into()])
+            .with_env("A", "1")
+            .with_env("B", "2");
+        let b = McpServer::new("b", "npx", vec!["-y".into(), "pkg".into()])
+            .with_env("B", "2")
+            .with_env("A", "1");
+
+        assert!(a.semantically_equal(&b));
+    }
+
+    #[test]
+    fn test_mcp_server_semantically_equal_detects_different_args() {
+        let a = McpServer::new("a", "npx", vec!["-y".into(), "pkg".into()]);
+        let b = McpServer::new("a", "npx", vec!["-y".into(), "other".into()]);
+
+        assert!(!a.semantically_equal(&b));
+        assert!(!a.semantically_equal(&a.clone().with_timeout_ms(500)));
+    }
+
+    #[test]
+    fn test_backup_info_serializes_rfc3339() {
+        let backup = BackupInfo {
+            path: "/b/config_20250120_143045.json".to_string(),
+            original_path: "/c/config.json".to_string(),
+            created_at: DateTime::parse_from_rfc3339("2025-01-20T14:30:45.5Z")
+                .unwrap()
+                .with_timezone(&Utc),
+            size: 42,
+            tag: None,
+        };
+
+        let json = serde_json::to_value(&backup).unwrap();
+        assert_eq!(json["created_at"], "2025-01-20T14:30:45.500Z");
+
+        let parsed: BackupInfo = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed, backup);
+    }
+
+    #[test]
+    fn test_format_timestamp_is_rfc3339() {
+        let timestamp = DateTime::parse_from_rfc3339("2025-01-20T14:30:45.123Z")
+            .unwrap()
+            .with_timezone(&Utc);
+
+        let formatted = format_timestamp(&timestamp);
+
+        assert_eq!(formatted, "2025-01-20T14:30:45Z");
+        assert!(DateTime::parse_from_rfc3339(&formatted).is_ok());
+    }
+
     #[test]
     fn test_config_scope_display_name() {
         assert_eq!(ConfigScope::Global.display_name(), "global");
@@ -232,6 +485,37 @@ mod tests {
         assert!(server.env.is_empty());
     }
 
+    #[test]
+    fn test_mcp_server_command_line_quotes_args() {
+        let server = McpServer::new(
+            "fetch",
+            "uvx",
+            vec![
+                "mcp-server-fetch".to_string(),
+                "--ua".to_string(),
+                "ccm bot".to_string(),
+            ],
+        );
+        assert_eq!(
+            server.command_line().as_deref(),
+            Some("uvx mcp-server-fetch --ua 'ccm bot'")
+        );
+
+        let plain = McpServer::new(
+            "npx",
+            "npx",
+            vec!["-y".to_string(), "@mcp/server".to_string()],
+        );
+        assert_eq!(plain.command_line().as_deref(), Some("npx -y @mcp/server"));
+    }
+
+    #[test]
+    fn test_mcp_server_command_line_without_command() {
+        let mut server = McpServer::new("remote", "npx", vec!["-y".to_string()]);
+        server.command = None;
+        assert_eq!(server.command_line(), None);
+    }
+
     #[test]
     fn test_mcp_server_with_env() {
         let server = McpServer::new("test", "npx", vec![]).with_env("API_KEY", "secret");
@@ -252,6 +536,23 @@ mod tests {
         assert!(server.enabled);
     }
 
+    #[test]
+    fn test_mcp_server_timeout_serde() {
+        let server: McpServer =
+            serde_json::from_str(r#"{"enabled": true, "command": "npx", "timeout": 3000}"#)
+                .unwrap();
+        assert_eq!(server.timeout_ms, Some(3000));
+
+        let json = serde_json::to_value(&server).unwrap();
+        assert_eq!(json["timeout"], 3000);
+        assert!(json.get("timeout_ms").is_none());
+
+        // Omitted when unset
+        let server = McpServer::new("test", "npx", vec![]);
+        let json = serde_json::to_value(&server).unwrap();
+        assert!(json.get("timeout").is_none());
+    }
+
     #[test]
     fn test_config_layer_serialization() {
         let layer = ConfigLayer::Global;
@@ -269,4 +570,59 @@ mod tests {
         let json = serde_json::to_string(&scope).unwrap();
         assert_eq!(json, r#""project""#);
     }
+
+    #[test]
+    fn test_source_map_lookup_falls_back_to_ancestors() {
+        let mut sources = SourceMap::new();
+        sources.insert("mcpServers.fetch", ConfigScope::Base);
+        sources.insert("allowedPaths", ConfigScope::Global);
+
+        assert_eq!(
+            sources.lookup("mcpServers.fetch.args[0]"),
+            Some(&ConfigScope::Base)
+        );
+        assert_eq!(
+            sources.lookup("allowedPaths[2]"),
+            Some(&ConfigScope::Global)
+        );
+        assert_eq!(sources.lookup("mcpServers.git"), None);
+        assert_eq!(sources.lookup("skills"), None);
+    }
+
+    #[test]
+    fn test_mcp_server_command_array_form() {
+        let array: McpServer = serde_json::from_str(
+            r#"{"enabled": true, "command": ["npx", "-y", "server"], "args": ["--port", "3000"]}"#,
+        )
+        .unwrap();
+        let separated: McpServer = serde_json::from_str(
+            r#"{"enabled": true, "command": "npx", "args": ["-y", "server", "--port", "3000"]}"#,
+        )
+        .unwrap();
+
+        assert_eq!(array, separated);
+        assert_eq!(array.command.as_deref(), Some("npx"));
+        assert_eq!(array.args, ["-y", "server", "--port", "3000"]);
+
+        // Written back in the separated form
+        let value = serde_json::to_value(&array).unwrap();
+        assert_eq!(value["command"], "npx");
+        assert_eq!(
+            value["args"],
+            serde_json::json!(["-y", "server", "--port", "3000"])
+        );
+
+        let empty: McpServer =
+            serde_json::from_str(r#"{"enabled": false, "command": []}"#).unwrap();
+        assert_eq!(empty.command, None);
+        assert!(empty.args.is_empty());
+
+        // Words are kept as they are, whatever characters they contain
+        let nul: McpServer = serde_json::from_str(
+            r#"{"enabled": true, "command": ["run\u0000me", "a\u0000b"], "args": ["c"]}"#,
+        )
+        .unwrap();
+        assert_eq!(nul.command.as_deref(), Some("run\0me"));
+        assert_eq!(nul.args, ["a\0b", "c"]);
+    }
 }
@@ -0,0 +1,281 @@
+//! Local usage tracking
+//!
+//! When enabled in the ccm settings section, ccm counts how often each
+//! command is used in `<global_config_dir>/usage.json`. Nothing is ever sent
+//! anywhere; the file is plain JSON the user can inspect or delete.
+
+use crate::{
+    config::manager::atomic_write,
+    error::{ConfigError, Result},
+    settings::CcmSettings,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Age after which a leftover lock file is considered abandoned
+const STALE_LOCK_AGE: Duration = Duration::from_secs(10);
+
+/// Usage statistics for a single command
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommandUsage {
+    /// Number of times the command was run
+    pub count: u64,
+
+    /// When the command was last run
+    pub last_used: DateTime<Utc>,
+}
+
+/// Usage statistics for all commands
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UsageSummary {
+    /// Per-command usage, keyed by command name (e.g. "config get")
+    #[serde(default)]
+    pub commands: BTreeMap<String, CommandUsage>,
+}
+
+/// Records command usage to a local file
+#[derive(Debug, Clone)]
+pub struct UsageTracker {
+    /// Path to the usage file
+    path: PathBuf,
+}
+
+impl UsageTracker {
+    /// Create a new UsageTracker
+    ///
+    /// # Arguments
+    /// * `path` - Path to the usage file
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Get the usage file path
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Record a command use if tracking is enabled
+    ///
+    /// Does nothing (not even touching the filesystem) when disabled.
+    pub fn record_if_enabled(&self, settings: &CcmSettings, command: &str) -> Result<()> {
+        if !settings.usage_tracking {
+            return Ok(());
+        }
+
+        self.record(command)
+    }
+
+    /// Record a command use
+    ///
+    /// This is best effort: if another ccm process is updating the file at
+    /// the same time, the use is skipped rather than waiting.
+    pub fn record(&self, command: &str) -> Result<()> {
+        let Some(_lock) = UsageLock::try_acquire(&self.lock_path()) else {
+            tracing::debug!("Usage file is busy, skipping usage update");
+            return Ok(());
+        };
+
+        let mut summary = self.load()?;
+        let usage = summary
+            .commands
+            .entry(command.to_string())
+            .or_insert(CommandUsage {
+                count: 0,
+                last_used: Utc::now(),
+            });
+        usage.count += 1;
+        usage.last_used = Utc::now();
+
+        atomic_write(&self.path, &serde_json::to_string_pretty(&summary)?)
+    }
+
+    /// Load the usage summary
+    ///
+    /// A missing file yields an empty summary. A corrupted file is reported
+    /// with a warning and treated as empty, so the next update replaces it.
+    pub fn load(&self) -> Result<UsageSummary> {
+        if !self.path.exists() {
+            return Ok(UsageSummary::default());
+        }
+
+        let content = fs::read_to_string(&self.path)
+            .map_err(|e| ConfigError::filesystem("read usage file", &self.path, e))?;
+
+        Ok(serde_json::from_str(&content).unwrap_or_else(|e| {
+            tracing::warn!(
+                "Usage file {} is corrupted ({e}); starting over",
+                self.path.display()
+            );
+            UsageSummary::default()
+        }))
+    }
+
+    /// Delete all recorded usage
+    ///
+    /// # Returns
+    /// Whether a usage file was removed
+    pub fn reset(&self) -> Result<bool> {
+        if !self.path.exists() {
+            return Ok(false);
+        }
+
+        fs::remove_file(&self.path)
+            .map_err(|e| ConfigError::filesystem("remove usage file", &self.path, e))?;
+
+        Ok(true)
+    }
+
+    /// Path of the lock file guarding updates
+    fn lock_path(&self) -> PathBuf {
+        self.path.with_extension("json.lock")
+    }
+}
+
+/// Exclusive lock held while updating the usage file
+struct UsageLock {
+    path: PathBuf,
+}
+
+impl UsageLock {
+    /// Try to take the lock without waiting
+    fn try_acquire(path: &Path) -> Option<Self> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).ok()?;
+        }
+
+        if Self::create(path) {
+            return Some(Self {
+                path: path.to_path_buf(),
+            });
+        }
+
+        // Clear a lock left behind by a crashed process and retry once
+        let stale = fs::metadata(path)
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|modified| modified.elapsed().ok())
+            .is_some_and(|age| age > STALE_LOCK_AGE);
+
+        if stale && fs::remove_file(path).is_ok() && Self::create(path) {
+            return Some(Self {
+                path: path.to_path_buf(),
+            });
+        }
+
+        None
+    }
+
+    fn create(path: &Path) -> bool {
+        fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(path)
+            .is_ok()
+    }
+}
+
+impl Drop for UsageLock {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn enabled() -> CcmSettings {
+        CcmSettings {
+            usage_tracking: true,
+            ..Default::default()
+        }
+    }
+
+    // TDD Test 1: Disabled by default, no file created
+    #[test]
+    fn test_disabled_by_default() {
+        let temp_dir = TempDir::new().unwrap();
+        let tracker = UsageTracker::new(temp_dir.path().join("usage.json"));
+
+        tracker
+            .record_if_enabled(&CcmSettings::default(), "config get")
+            .unwrap();
+
+        assert!(!tracker.path().exists());
+        assert!(fs::read_dir(temp_dir.path()).unwrap().next().is_none());
+    }
+
+    // TDD Test 2: Enabled tracking increments across calls
+    #[test]
+    fn test_enabled_increments() {
+        let temp_dir = TempDir::new().unwrap();
+        let tracker = UsageTracker::new(temp_dir.path().join("usage.json"));
+
+        tracker.record_if_enabled(&enabled(), "config get").unwrap();
+        tracker.record_if_enabled(&enabled(), "config get").unwrap();
+        tracker.record_if_enabled(&enabled(), "mcp list").unwrap();
+
+        let summary = tracker.load().unwrap();
+        assert_eq!(summary.commands["config get"].count, 2);
+        assert_eq!(summary.commands["mcp list"].count, 1);
+        assert!(!tracker.lock_path().exists());
+    }
+
+    // TDD Test 3: Corrupted file recovers
+    #[test]
+    fn test_corrupted_file_recovers() {
+        let temp_dir = TempDir::new().unwrap();
+        let tracker = UsageTracker::new(temp_dir.path().join("usage.json"));
+        fs::write(tracker.path(), "{ not json").unwrap();
+
+        assert!(tracker.load().unwrap().commands.is_empty());
+
+        tracker.record("stats").unwrap();
+        assert_eq!(tracker.load().unwrap().commands["stats"].count, 1);
+    }
+
+    // TDD Test 4: Reset empties the summary
+    #[test]
+    fn test_reset_empties() {
+        let temp_dir = TempDir::new().unwrap();
+        let tracker = UsageTracker::new(temp_dir.path().join("usage.json"));
+        tracker.record("stats").unwrap();
+
+        assert!(tracker.reset().unwrap());
+        assert!(tracker.load().unwrap().commands.is_empty());
+        assert!(!tracker.reset().unwrap());
+    }
+
+    // TDD Test 5: Lock contention skips the update instead of blocking
+    #[test]
+    fn test_lock_contention_skips() {
+        let temp_dir = TempDir::new().unwrap();
+        let tracker = UsageTracker::new(temp_dir.path().join("usage.json"));
+        let _held = UsageLock::try_acquire(&tracker.lock_path()).unwrap();
+
+        tracker.record("stats").unwrap();
+
+        assert!(!tracker.path().exists());
+    }
+
+    // TDD Test 6: last_used round-trips as an RFC 3339 string
+    #[test]
+    fn test_command_usage_serializes_rfc3339() {
+        let usage = CommandUsage {
+            count: 3,
+            last_used: DateTime::parse_from_rfc3339("2025-01-20T14:30:45Z")
+                .unwrap()
+                .with_timezone(&Utc),
+        };
+
+        let json = serde_json::to_value(&usage).unwrap();
+        assert_eq!(json["lastUsed"], "2025-01-20T14:30:45Z");
+        assert_eq!(serde_json::from_value::<CommandUsage>(json).unwrap(), usage);
+    }
+}
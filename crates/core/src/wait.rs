@@ -0,0 +1,333 @@
+//! Waiting for a configuration file to change
+//!
+//! Provisioning scripts sometimes need to block until a setting changes,
+//! e.g. until a user enables a server through the GUI. [`wait_for`] polls a
+//! config file, re-reads it whenever its modification time or size changes,
+//! and returns once a condition holds or the timeout expires.
+//!
+//! Waiting is tolerant of how editors and ccm itself save files: the file may
+//! not exist yet, may be replaced by an atomic rename, or may briefly contain
+//! partial JSON while it is being written. None of these end the wait.
+
+use crate::{
+    config::{manager::read_config_file, ClaudeConfig},
+    error::{ConfigError, Result},
+};
+use std::fs;
+use std::io::ErrorKind;
+use std::path::Path;
+use std::time::{Duration, Instant, SystemTime};
+
+/// Default time between polls
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Time source and sleeper used while waiting
+///
+/// Tests supply their own implementation to drive a wait deterministically.
+pub trait Poller {
+    /// Time elapsed since the wait started
+    fn elapsed(&self) -> Duration;
+
+    /// Block until the next poll
+    fn sleep(&mut self, interval: Duration);
+}
+
+/// Poller backed by the system clock and `thread::sleep`
+#[derive(Debug, Clone)]
+pub struct SystemPoller {
+    /// When the wait started
+    started: Instant,
+}
+
+impl SystemPoller {
+    /// Create a poller whose clock starts now
+    pub fn new() -> Self {
+        Self {
+            started: Instant::now(),
+        }
+    }
+}
+
+impl Default for SystemPoller {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Poller for SystemPoller {
+    fn elapsed(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    fn sleep(&mut self, interval: Duration) {
+        std::thread::sleep(interval);
+    }
+}
+
+/// How long and how often to poll
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WaitOptions {
+    /// Give up after this long (None = wait forever)
+    pub timeout: Option<Duration>,
+
+    /// Time between polls
+    pub interval: Duration,
+}
+
+impl Default for WaitOptions {
+    fn default() -> Self {
+        Self {
+            timeout: None,
+            interval: DEFAULT_POLL_INTERVAL,
+        }
+    }
+}
+
+impl WaitOptions {
+    /// Create options that wait forever with the default poll interval
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the timeout
+    pub fn with_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Set the poll interval
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+}
+
+/// Result of waiting for a condition
+#[derive(Debug, Clone, PartialEq)]
+pub enum WaitOutcome {
+    /// The condition held for this configuration
+    Satisfied(ClaudeConfig),
+
+    /// The timeout expired first
+    TimedOut,
+}
+
+/// Wait until the config at `path` satisfies `condition`
+///
+/// The condition is checked against the current file first, then every
+/// time the file changes.
+///
+/// # Arguments
+/// * `path` - Config file to watch (need not exist yet)
+/// * `condition` - Returns true once the wait is over
+/// * `timeout` - Give up after this long (None = wait forever)
+///
+/// # Errors
+/// Returns an error if the file exists but cannot be read for reasons other
+/// than an in-progress save (e.g. permissions or size limits)
+pub fn wait_for(
+    path: &Path,
+    condition: impl FnMut(&ClaudeConfig) -> bool,
+    timeout: Option<Duration>,
+) -> Result<WaitOutcome> {
+    wait_for_with(
+        path,
+        condition,
+        WaitOptions::new().with_timeout(timeout),
+        &mut SystemPoller::new(),
+    )
+}
+
+/// [`wait_for`] with explicit options and poller
+pub fn wait_for_with(
+    path: &Path,
+    mut condition: impl FnMut(&ClaudeConfig) -> bool,
+    options: WaitOptions,
+    poller: &mut dyn Poller,
+) -> Result<WaitOutcome> {
+    // None forces a read on the next poll
+    let mut last_state: Option<FileState> = None;
+
+    loop {
+        let state = file_state(path);
+        if last_state != Some(state) {
+            last_state = Some(state);
+
+            if state.is_some() {
+                match read_config_file(path) {
+                    Ok(config) if condition(&config) => {
+                        return Ok(WaitOutcome::Satisfied(config));
+                    }
+                    Ok(_) => {}
+                    Err(e) if is_transient(&e) => {
+                        tracing::debug!("Config not readable yet, still waiting: {e}");
+                        last_state = None;
+                    }
+                    Err(e) => return Err(e),
+                }
+            }
+        }
+
+        if options
+            .timeout
+            .is_some_and(|timeout| poller.elapsed() >= timeout)
+        {
+            return Ok(WaitOutcome::TimedOut);
+        }
+
+        poller.sleep(options.interval);
+    }
+}
+
+/// What a poll observed: modification time and size, or None if missing
+type FileState = Option<(SystemTime, u64)>;
+
+/// Current state of the watched file
+fn file_state(path: &Path) -> FileState {
+    let metadata = fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+/// Errors expected while a file is being created or saved
+fn is_transient(error: &ConfigError) -> bool {
+    match error {
+        ConfigError::NotFound { .. } | ConfigError::InvalidFormat { .. } => true,
+        ConfigError::Filesystem { source, .. } => source.kind() == ErrorKind::NotFound,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    /// Poller that advances a fake clock and runs scripted file changes
+    struct ScriptedPoller {
+        elapsed: Duration,
+        sleeps: usize,
+        actions: Vec<(usize, Box<dyn FnOnce()>)>,
+    }
+
+    impl ScriptedPoller {
+        fn new() -> Self {
+            Self {
+                elapsed: Duration::ZERO,
+                sleeps: 0,
+                actions: Vec::new(),
+            }
+        }
+
+        /// Run `action` during the given sleep (1-based)
+        fn at(mut self, sleep: usize, action: impl FnOnce() + 'static) -> Self {
+            self.actions.push((sleep, Box::new(action)));
+            self
+        }
+    }
+
+    impl Poller for ScriptedPoller {
+        fn elapsed(&self) -> Duration {
+            self.elapsed
+        }
+
+        fn sleep(&mut self, interval: Duration) {
+            self.elapsed += interval;
+            self.sleeps += 1;
+            let (due, pending) = std::mem::take(&mut self.actions)
+                .into_iter()
+                .partition(|(sleep, _)| *sleep == self.sleeps);
+            self.actions = pending;
+            for (_, action) in due {
+                action();
+            }
+        }
+    }
+
+    fn has_instruction(config: &ClaudeConfig) -> bool {
+        config
+            .custom_instructions
+            .as_ref()
+            .is_some_and(|instructions| instructions.iter().any(|i| i == "ready"))
+    }
+
+    fn options() -> WaitOptions {
+        WaitOptions::new()
+            .with_timeout(Some(Duration::from_secs(10)))
+            .with_interval(Duration::from_secs(1))
+    }
+
+    // TDD Test 1: Waits for a missing file to be created
+    #[test]
+    fn test_wait_for_file_creation() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("config.json");
+        let target = path.clone();
+        let mut poller = ScriptedPoller::new().at(3, move || {
+            fs::write(target, r#"{"customInstructions": ["ready"]}"#).unwrap();
+        });
+
+        let outcome = wait_for_with(&path, has_instruction, options(), &mut poller).unwrap();
+
+        assert!(matches!(outcome, WaitOutcome::Satisfied(_)));
+        assert_eq!(poller.elapsed(), Duration::from_secs(3));
+    }
+
+    // TDD Test 2: Partial JSON during a save does not end the wait
+    #[test]
+    fn test_wait_survives_transient_invalid_json() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("config.json");
+        fs::write(&path, "{}").unwrap();
+        let (partial, complete) = (path.clone(), path.clone());
+        let mut poller = ScriptedPoller::new()
+            .at(1, move || fs::write(partial, r#"{"customInstr"#).unwrap())
+            .at(2, move || {
+                fs::write(complete, r#"{"customInstructions": ["ready"]}"#).unwrap()
+            });
+
+        let outcome = wait_for_with(&path, has_instruction, options(), &mut poller).unwrap();
+
+        assert!(matches!(outcome, WaitOutcome::Satisfied(_)));
+        assert_eq!(poller.elapsed(), Duration::from_secs(2));
+    }
+
+    // TDD Test 3: Atomic rename updates are picked up
+    #[test]
+    fn test_wait_detects_atomic_rename() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("config.json");
+        fs::write(&path, r#"{"customInstructions": ["not yet"]}"#).unwrap();
+        let target = path.clone();
+        let mut poller = ScriptedPoller::new().at(2, move || {
+            let temp = target.with_extension("tmp");
+            fs::write(&temp, r#"{"customInstructions": ["ready"]}"#).unwrap();
+            fs::rename(&temp, &target).unwrap();
+        });
+
+        let outcome = wait_for_with(&path, has_instruction, options(), &mut poller).unwrap();
+
+        match outcome {
+            WaitOutcome::Satisfied(config) => assert!(has_instruction(&config)),
+            WaitOutcome::TimedOut => panic!("expected the rename to satisfy the wait"),
+        }
+    }
+
+    // TDD Test 4: Already-satisfied conditions return without polling;
+    // unsatisfied ones time out
+    #[test]
+    fn test_wait_immediate_and_timeout() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("config.json");
+        fs::write(&path, r#"{"customInstructions": ["ready"]}"#).unwrap();
+
+        let mut poller = ScriptedPoller::new();
+        let outcome = wait_for_with(&path, has_instruction, options(), &mut poller).unwrap();
+        assert!(matches!(outcome, WaitOutcome::Satisfied(_)));
+        assert_eq!(poller.sleeps, 0);
+
+        let mut poller = ScriptedPoller::new();
+        let outcome = wait_for_with(&path, |_| false, options(), &mut poller).unwrap();
+        assert_eq!(outcome, WaitOutcome::TimedOut);
+        assert_eq!(poller.elapsed(), Duration::from_secs(10));
+    }
+}
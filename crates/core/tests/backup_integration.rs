@@ -2,7 +2,7 @@
 //!
 //! These tests verify backup behavior in realistic scenarios.
 
-use claude_config_manager_core::BackupManager;
+use claude_config_manager_core::{backup::FixedClock, BackupManager};
 use std::fs::{self, File};
 use std::io::Write;
 use std::path::PathBuf;
@@ -179,11 +179,14 @@ fn test_cleanup_removes_correct_backups() {
     let manager = BackupManager::new(&backup_dir, Some(3));
 
     // Create 5 backups
+    let clock = FixedClock::new(chrono::Utc::now());
     let mut backup_paths = Vec::new();
     for _ in 0..5 {
-        let path = manager.create_backup(&config_file).unwrap();
+        let path = manager
+            .create_backup_with_clock(&config_file, &clock)
+            .unwrap();
         backup_paths.push(path.clone());
-        std::thread::sleep(std::time::Duration::from_millis(50));
+        clock.advance(chrono::Duration::seconds(1));
     }
 
     // Cleanup
@@ -231,10 +234,9 @@ fn test_error_handling_on_permission_denied() {
     // Make backup directory read-only (platform-specific)
     #[cfg(unix)]
     {
-        use std::os::unix::fs::PermissionsExt;
         let mut perms = fs::metadata(&backup_dir).unwrap().permissions();
         perms.set_readonly(true);
-        fs::set_permissions(&backup_dir, perms).unwrap();
+        fs::set_permissions(&backup_dir, perms.clone()).unwrap();
 
         // Try to create another backup - should fail with helpful error
         let result = manager.create_backup(&config_file);
@@ -245,11 +247,38 @@ fn test_error_handling_on_permission_denied() {
         assert!(message.contains("Permission denied") || message.contains("filesystem"));
 
         // Restore permissions for cleanup
+        #[allow(clippy::permissions_set_readonly_false)]
         perms.set_readonly(false);
         fs::set_permissions(&backup_dir, perms).unwrap();
     }
 }
 
+#[test]
+#[cfg(unix)]
+fn test_error_handling_on_read_only_mode() {
+    // Same as above, with explicit modes instead of the read-only flag
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp_dir = TempDir::new().unwrap();
+    let backup_dir = temp_dir.path().join("backups");
+    let config_file = temp_dir.path().join("config.json");
+    fs::write(&config_file, "{}").unwrap();
+
+    let manager = BackupManager::new(&backup_dir, None);
+    manager.create_backup(&config_file).unwrap();
+
+    fs::set_permissions(&backup_dir, fs::Permissions::from_mode(0o555)).unwrap();
+    if fs::write(backup_dir.join("probe"), "").is_ok() {
+        // Running with privileges that ignore the mode (e.g. as root)
+        return;
+    }
+    let result = manager.create_backup(&config_file);
+    fs::set_permissions(&backup_dir, fs::Permissions::from_mode(0o755)).unwrap();
+
+    let message = result.unwrap_err().to_string();
+    assert!(message.contains("Permission denied") || message.contains("filesystem"));
+}
+
 #[test]
 fn test_concurrent_backup_safety() {
     // Test that multiple backups of the same file work correctly
@@ -329,13 +358,11 @@ fn test_restore_selective_backup() {
     let v1 = b"{\"version\": 1}";
     File::create(&config_file).unwrap().write_all(v1).unwrap();
     let backup1 = manager.create_backup(&config_file).unwrap();
-    std::thread::sleep(std::time::Duration::from_millis(100));
 
     // Create second version and backup
     let v2 = b"{\"version\": 2}";
     File::create(&config_file).unwrap().write_all(v2).unwrap();
     let _backup2 = manager.create_backup(&config_file).unwrap();
-    std::thread::sleep(std::time::Duration::from_millis(100));
 
     // Create third version and backup
     let v3 = b"{\"version\": 3}";
@@ -413,15 +440,18 @@ fn test_backup_list_order() {
     let manager = BackupManager::new(&backup_dir, None);
 
     // Create multiple backups
+    let clock = FixedClock::new(chrono::Utc::now());
     let mut backup_paths = Vec::new();
     for i in 0..3 {
         File::create(&config_file)
             .unwrap()
             .write_all(format!("{{\"v\":{i}}}").as_bytes())
             .unwrap();
-        let path = manager.create_backup(&config_file).unwrap();
+        let path = manager
+            .create_backup_with_clock(&config_file, &clock)
+            .unwrap();
         backup_paths.push(path);
-        std::thread::sleep(std::time::Duration::from_millis(100));
+        clock.advance(chrono::Duration::seconds(1));
     }
 
     // List backups
@@ -449,13 +479,16 @@ fn test_backup_restore_with_cleanup() {
     let manager = BackupManager::new(&backup_dir, Some(2)); // Keep only 2
 
     // Create 5 backups
+    let clock = FixedClock::new(chrono::Utc::now());
     for i in 0..5 {
         File::create(&config_file)
             .unwrap()
             .write_all(format!("{{\"v\":{i}}}").as_bytes())
             .unwrap();
-        manager.create_backup(&config_file).unwrap();
-        std::thread::sleep(std::time::Duration::from_millis(50));
+        manager
+            .create_backup_with_clock(&config_file, &clock)
+            .unwrap();
+        clock.advance(chrono::Duration::seconds(1));
     }
 
     // Cleanup should remove 3 oldest backups
@@ -474,3 +507,69 @@ fn test_backup_restore_with_cleanup() {
         "Restore from recent backup should work after cleanup"
     );
 }
+
+#[test]
+fn test_dir_snapshot_restores_nested_files() {
+    let temp_dir = TempDir::new().unwrap();
+    let claude_dir = temp_dir.path().join("project").join(".claude");
+    let agents_dir = claude_dir.join("agents").join("review");
+    fs::create_dir_all(&agents_dir).unwrap();
+
+    let files = [
+        (claude_dir.join("config.json"), b"{\"version\": 1}".to_vec()),
+        (claude_dir.join("settings.local.json"), b"{}".to_vec()),
+        (agents_dir.join("agent.md"), vec![0, 159, 146, 150, b'\n']),
+    ];
+    for (path, contents) in &files {
+        fs::write(path, contents).unwrap();
+    }
+
+    let manager = BackupManager::new(temp_dir.path().join("backups"), None);
+    let snapshot = manager.create_dir_backup(&claude_dir).unwrap();
+    assert!(snapshot.to_string_lossy().ends_with(".tar.gz"));
+
+    // Break the directory
+    fs::write(&files[0].0, "{ broken").unwrap();
+    fs::remove_file(&files[2].0).unwrap();
+
+    let restored = manager.restore_dir_backup(&snapshot, &claude_dir).unwrap();
+    assert_eq!(restored.files, 3);
+    for (path, contents) in &files {
+        assert_eq!(&fs::read(path).unwrap(), contents, "{}", path.display());
+    }
+
+    // The broken state was snapshotted before restoring
+    let safety = restored.safety_snapshot.unwrap();
+    assert!(safety.exists());
+    let snapshots = manager.list_dir_backups(&claude_dir).unwrap();
+    assert_eq!(snapshots.len(), 2);
+    assert_eq!(PathBuf::from(&snapshots[0].path), safety);
+
+    let undo = manager.restore_dir_backup(&safety, &claude_dir).unwrap();
+    assert_eq!(undo.files, 2);
+    assert_eq!(fs::read(&files[0].0).unwrap(), b"{ broken");
+}
+
+#[cfg(windows)]
+#[test]
+fn test_backup_of_reserved_and_long_paths_on_windows() {
+    use claude_config_manager_core::paths::to_extended_length;
+
+    let temp_dir = TempDir::new().unwrap();
+
+    // A project directory named `aux`, only creatable with the prefix
+    let reserved_dir = temp_dir.path().join("aux").join(".claude");
+    fs::create_dir_all(to_extended_length(&reserved_dir)).unwrap();
+    let reserved_config = reserved_dir.join("config.json");
+    fs::write(to_extended_length(&reserved_config), "{}").unwrap();
+
+    // A backup directory nested beyond MAX_PATH
+    let long_backup_dir = (0..12).fold(temp_dir.path().join("backups"), |dir, i| {
+        dir.join(format!("nested_directory_{i:02}"))
+    });
+    assert!(long_backup_dir.as_os_str().len() > 260);
+
+    let manager = BackupManager::new(&long_backup_dir, None);
+    let backup = manager.create_backup(&reserved_config).unwrap();
+    assert!(fs::metadata(to_extended_length(&backup)).unwrap().is_file());
+}
@@ -0,0 +1,68 @@
+//! Integration tests that run from inside a project directory
+//!
+//! The working directory and environment are shared by the whole test
+//! process, so tests that change them live in this binary and run one at a
+//! time.
+
+use claude_config_manager_core::{paths::CCM_HOME_VAR, ConfigManager, ConfigScope};
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+use tempfile::TempDir;
+
+/// Serializes the tests of this binary, which all change the working directory
+static CWD: Mutex<()> = Mutex::new(());
+
+/// Run `test` with `dir` as the working directory
+fn in_dir(dir: &Path, test: impl FnOnce()) {
+    let _guard = CWD.lock().unwrap_or_else(|e| e.into_inner());
+    let previous = std::env::current_dir().unwrap();
+    std::env::set_current_dir(dir).unwrap();
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(test));
+    std::env::set_current_dir(previous).unwrap();
+    if let Err(panic) = result {
+        std::panic::resume_unwind(panic);
+    }
+}
+
+#[test]
+fn test_list_servers_merged_finds_enclosing_project() {
+    let temp_dir = TempDir::new().unwrap();
+    let global_path = temp_dir.path().join("config.json");
+    fs::write(
+        &global_path,
+        r#"{"mcpServers": {"fetch": {"command": "uvx", "enabled": true}}}"#,
+    )
+    .unwrap();
+    let project = temp_dir.path().join("project");
+    fs::create_dir_all(project.join(".git")).unwrap();
+    fs::create_dir_all(project.join(".claude")).unwrap();
+    fs::create_dir_all(project.join("src")).unwrap();
+    let project_config = project.join(".claude").join("config.json");
+    fs::write(
+        &project_config,
+        r#"{"mcpServers": {"local": {"command": "node", "enabled": true}}}"#,
+    )
+    .unwrap();
+
+    std::env::set_var(CCM_HOME_VAR, temp_dir.path());
+    let manager = ConfigManager::new(temp_dir.path().join("backups"));
+    in_dir(&project.join("src"), || {
+        let servers = manager.list_servers_merged(None).unwrap();
+        let origins: Vec<(&str, ConfigScope)> = servers
+            .iter()
+            .map(|s| (s.name.as_str(), s.origin))
+            .collect();
+        assert_eq!(
+            origins,
+            [
+                ("fetch", ConfigScope::Global),
+                ("local", ConfigScope::Project)
+            ]
+        );
+        assert_eq!(
+            servers[1].origin_path.canonicalize().unwrap(),
+            project_config.canonicalize().unwrap()
+        );
+    });
+}
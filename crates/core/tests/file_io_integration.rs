@@ -53,10 +53,11 @@ fn test_concurrent_write_safety() {
     let backup_dir = temp_dir.path().join("backups");
 
     let manager = ConfigManager::new(&backup_dir);
-    let config = claude_config_manager_core::ClaudeConfig::new();
 
     // Create multiple files in sequence (simulating concurrent access)
-    for _ in 0..3 {
+    for i in 0..3 {
+        let config = claude_config_manager_core::ClaudeConfig::new()
+            .with_custom_instruction(format!("Write {i}"));
         manager
             .write_config_with_backup(&config_path, &config)
             .unwrap();
@@ -181,31 +182,31 @@ fn test_backup_cleanup_after_many_writes() {
     let backup_dir = temp_dir.path().join("backs");
 
     let manager = ConfigManager::new(&backup_dir);
-    let config = claude_config_manager_core::ClaudeConfig::new();
 
     // Write many times to exceed retention
-    for _ in 0..15 {
+    for i in 0..15 {
+        let config = claude_config_manager_core::ClaudeConfig::new()
+            .with_custom_instruction(format!("Write {i}"));
         manager
             .write_config_with_backup(&config_path, &config)
             .unwrap();
         std::thread::sleep(std::time::Duration::from_millis(10));
     }
 
-    // Verify all backups exist (cleanup is not automatic)
+    // Each write prunes to the retention count (10 of the 14 backups)
     let backups = manager.backup_manager().list_backups(&config_path).unwrap();
-    assert_eq!(backups.len(), 14); // First write has no existing file
+    assert_eq!(backups.len(), 10);
+
+    // The newest backup holds the content before the last write
+    let newest =
+        claude_config_manager_core::backup::read_backup(std::path::Path::new(&backups[0].path))
+            .unwrap();
+    assert_eq!(newest.custom_instructions.unwrap(), vec!["Write 13"]);
 
-    // Now trigger cleanup
+    // Nothing is left for a manual cleanup
     let removed = manager
         .backup_manager()
         .cleanup_old_backups(&config_path)
         .unwrap();
-    assert_eq!(removed, 4); // Should remove 4 to keep 10
-
-    // Verify cleanup worked
-    let backups = manager.backup_manager().list_backups(&config_path).unwrap();
-    assert!(
-        backups.len() <= 10,
-        "Should not exceed retention count after cleanup"
-    );
+    assert_eq!(removed, 0);
 }
@@ -0,0 +1,69 @@
+//! Peak allocation test for large configuration files
+//!
+//! Installs a counting global allocator, so it lives in its own test binary
+//! and only runs with `--features alloc-tests`.
+
+#![cfg(feature = "alloc-tests")]
+
+use claude_config_manager_core::{ClaudeConfig, ConfigManager};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::fs;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tempfile::TempDir;
+
+struct CountingAllocator;
+
+static CURRENT: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            let current = CURRENT.fetch_add(layout.size(), Ordering::SeqCst) + layout.size();
+            PEAK.fetch_max(current, Ordering::SeqCst);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        CURRENT.fetch_sub(layout.size(), Ordering::SeqCst);
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// Measure the peak heap growth while running `f`
+fn peak_allocation<T>(f: impl FnOnce() -> T) -> usize {
+    let baseline = CURRENT.load(Ordering::SeqCst);
+    PEAK.store(baseline, Ordering::SeqCst);
+    let result = f();
+    let peak = PEAK.load(Ordering::SeqCst) - baseline;
+    drop(result);
+    peak
+}
+
+#[test]
+fn test_large_config_streaming_reduces_peak_allocation() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_path = temp_dir.path().join("config.json");
+    let instructions: Vec<String> = (0..20_000).map(|_| "a".repeat(1024)).collect();
+    let json = serde_json::json!({ "customInstructions": instructions });
+    fs::write(&config_path, serde_json::to_string_pretty(&json).unwrap()).unwrap();
+    drop(json);
+
+    let manager = ConfigManager::new(temp_dir.path().join("backups"));
+
+    let streamed = peak_allocation(|| manager.read_config(&config_path).unwrap());
+    let buffered = peak_allocation(|| {
+        let content = fs::read_to_string(&config_path).unwrap();
+        serde_json::from_str::<ClaudeConfig>(&content).unwrap()
+    });
+
+    assert!(
+        streamed < buffered * 3 / 4,
+        "streamed peak {streamed} bytes, buffered peak {buffered} bytes"
+    );
+}
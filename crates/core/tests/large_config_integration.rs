@@ -0,0 +1,86 @@
+//! Integration tests for large configuration files
+//!
+//! These tests verify that multi-megabyte configs are parsed from a stream
+//! and that parse errors still point at the right location.
+
+use claude_config_manager_core::{config::limits, ConfigError, ConfigManager};
+use std::fs;
+use std::io::Write;
+use tempfile::TempDir;
+
+/// Length of each generated instruction string
+const INSTRUCTION_LEN: usize = 1024;
+
+/// Write a ~20 MB config with one instruction per line
+///
+/// If `corrupt_at` is set, garbage is inserted after the instruction with
+/// that index. Returns the line number of that instruction.
+fn write_large_config(path: &std::path::Path, corrupt_at: Option<usize>) -> usize {
+    let mut file = std::io::BufWriter::new(fs::File::create(path).unwrap());
+    let instruction = "a".repeat(INSTRUCTION_LEN);
+    let count = 20_000;
+
+    writeln!(file, "{{").unwrap();
+    writeln!(file, "  \"customInstructions\": [").unwrap();
+    for i in 0..count {
+        let separator = if i + 1 == count { "" } else { "," };
+        if Some(i) == corrupt_at {
+            writeln!(file, "    \"{instruction}\" garbage{separator}").unwrap();
+        } else {
+            writeln!(file, "    \"{instruction}\"{separator}").unwrap();
+        }
+    }
+    writeln!(file, "  ]").unwrap();
+    writeln!(file, "}}").unwrap();
+
+    // Instructions start on line 3
+    corrupt_at.map_or(0, |i| i + 3)
+}
+
+#[test]
+fn test_large_valid_config_is_read() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_path = temp_dir.path().join("config.json");
+    write_large_config(&config_path, None);
+    assert!(limits::is_large_config(
+        fs::metadata(&config_path).unwrap().len()
+    ));
+
+    let manager = ConfigManager::new(temp_dir.path().join("backups"));
+    let config = manager.read_config(&config_path).unwrap();
+
+    assert_eq!(config.custom_instructions.unwrap().len(), 20_000);
+}
+
+#[test]
+fn test_large_corrupted_config_reports_location() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_path = temp_dir.path().join("config.json");
+    let expected_line = write_large_config(&config_path, Some(15_000));
+
+    let manager = ConfigManager::new(temp_dir.path().join("backups"));
+    let err = manager.read_config(&config_path).unwrap_err();
+
+    match err {
+        ConfigError::InvalidFormat {
+            line,
+            column,
+            message,
+            ..
+        } => {
+            // 4 spaces, the quoted string, a space, then the garbage
+            assert_eq!(line, expected_line);
+            assert_eq!(column, 4 + INSTRUCTION_LEN + 2 + 2);
+
+            let (text, caret) = message
+                .split_once("\n\n")
+                .unwrap()
+                .1
+                .split_once('\n')
+                .unwrap();
+            assert!(text.contains("garbage"));
+            assert_eq!(&text[caret.len() - 1..caret.len()], "g");
+        }
+        other => panic!("expected InvalidFormat, got: {other}"),
+    }
+}
@@ -3,7 +3,8 @@
 //! These tests verify real-world filesystem operations for configuration path handling.
 
 use claude_config_manager_core::{
-    expand_tilde, find_project_config, get_global_config_dir, get_global_config_path,
+    expand_tilde, find_project_config, find_project_config_files, get_global_config_dir,
+    get_global_config_path,
 };
 use std::fs;
 use std::path::{Path, PathBuf};
@@ -216,3 +217,36 @@ fn test_empty_craude_directory_handling() {
 
     assert!(found.is_none());
 }
+
+#[test]
+fn test_find_project_config_files_includes_embedded_manifests() {
+    let temp_dir = TempDir::new().unwrap();
+
+    let project = temp_dir.path().join("project");
+    let nested = project.join("src");
+    fs::create_dir_all(&nested).unwrap();
+    fs::create_dir_all(project.join(".git")).unwrap();
+
+    // Manifest without a claude section is not a project config
+    fs::write(project.join("package.json"), r#"{"name": "web"}"#).unwrap();
+    assert!(find_project_config_files(Some(&nested)).is_empty());
+
+    fs::write(
+        project.join("pyproject.toml"),
+        "[tool.claude]\nallowedPaths = []\n",
+    )
+    .unwrap();
+    assert_eq!(
+        find_project_config_files(Some(&nested)),
+        vec![project.join("pyproject.toml")]
+    );
+
+    // The real config file comes last (highest precedence)
+    let config_path = project.join(".claude").join("config.json");
+    fs::create_dir_all(config_path.parent().unwrap()).unwrap();
+    fs::write(&config_path, "{}").unwrap();
+    assert_eq!(
+        find_project_config_files(Some(&nested)),
+        vec![project.join("pyproject.toml"), config_path]
+    );
+}
@@ -0,0 +1,67 @@
+//! Integration tests for waiting on config changes
+//!
+//! These tests modify a config file from another thread while the main
+//! thread waits on it with the real system clock.
+
+use claude_config_manager_core::{wait_for, ConfigManager, WaitOutcome};
+use std::fs;
+use std::thread;
+use std::time::Duration;
+use tempfile::TempDir;
+
+fn npx_enabled(config: &claude_config_manager_core::ClaudeConfig) -> bool {
+    config
+        .mcp_servers
+        .as_ref()
+        .and_then(|servers| servers.get("npx"))
+        .is_some_and(|server| server.enabled)
+}
+
+#[test]
+fn test_wait_for_change_from_another_thread() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_path = temp_dir.path().join("claude").join("config.json");
+    fs::create_dir_all(config_path.parent().unwrap()).unwrap();
+    fs::write(
+        &config_path,
+        r#"{"mcpServers": {"npx": {"command": "npx", "enabled": false}}}"#,
+    )
+    .unwrap();
+
+    // Enable the server through the manager (atomic write) after a delay
+    let writer_path = config_path.clone();
+    let backup_dir = temp_dir.path().join("backups");
+    let writer = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(300));
+        let manager = ConfigManager::new(backup_dir);
+        let mut config = manager.read_config(&writer_path).unwrap();
+        config
+            .mcp_servers
+            .as_mut()
+            .unwrap()
+            .get_mut("npx")
+            .unwrap()
+            .enabled = true;
+        manager
+            .write_config_with_backup(&writer_path, &config)
+            .unwrap();
+    });
+
+    let outcome = wait_for(&config_path, npx_enabled, Some(Duration::from_secs(10))).unwrap();
+    writer.join().unwrap();
+
+    match outcome {
+        WaitOutcome::Satisfied(config) => assert!(npx_enabled(&config)),
+        WaitOutcome::TimedOut => panic!("expected the change to be seen before the timeout"),
+    }
+}
+
+#[test]
+fn test_wait_for_times_out_on_missing_file() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_path = temp_dir.path().join("missing").join("config.json");
+
+    let outcome = wait_for(&config_path, |_| true, Some(Duration::from_millis(300))).unwrap();
+
+    assert_eq!(outcome, WaitOutcome::TimedOut);
+}
@@ -1,7 +1,7 @@
 //! Tauri commands for configuration management
 
 use crate::types::*;
-use claude_config_manager_core::{ConfigManager, MergeResult};
+use claude_config_manager_core::{paths::get_backup_dir, ConfigManager, MergeResult};
 use serde_json::Value;
 use std::path::PathBuf;
 use tauri::State;
@@ -15,14 +15,8 @@ pub struct ConfigState {
 
 impl ConfigState {
     pub fn new() -> Self {
-        // Get default backup directory
-        let backup_dir = dirs::config_dir()
-            .unwrap_or_else(|| PathBuf::from("."))
-            .join("claude")
-            .join("backups");
-
         Self {
-            manager: ConfigManager::new(&backup_dir),
+            manager: ConfigManager::new(get_backup_dir()),
         }
     }
 }
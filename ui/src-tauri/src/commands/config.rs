@@ -1,9 +1,9 @@
 //! Tauri commands for configuration management
 
 use crate::types::*;
-use claude_config_manager_core::ConfigManager;
+use claude_config_manager_core::{paths::get_backup_dir, ConfigManager, ConfigSource, Timings};
 use serde_json::Value;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use tauri::State;
 
 /// Application state for ConfigManager
@@ -13,14 +13,17 @@ pub struct ConfigState {
 
 impl ConfigState {
     pub fn new() -> Self {
-        // Get default backup directory
-        let backup_dir = dirs::config_dir()
-            .unwrap_or_else(|| PathBuf::from("."))
-            .join("claude")
-            .join("backups");
-
         Self {
-            manager: ConfigManager::new(&backup_dir),
+            manager: ConfigManager::new(get_backup_dir()),
+        }
+    }
+
+    /// Manager for one command, recording timings if requested
+    pub fn manager_for_request(&self, include_timings: Option<bool>) -> ConfigManager {
+        if include_timings.unwrap_or(false) {
+            self.manager.clone().with_timings(Timings::new())
+        } else {
+            self.manager.clone()
         }
     }
 }
@@ -29,9 +32,10 @@ impl ConfigState {
 #[tauri::command]
 pub async fn get_config(
     project_path: Option<String>,
+    include_timings: Option<bool>,
     state: State<'_, ConfigState>,
 ) -> Result<ClaudeConfigData, String> {
-    let manager = &state.manager;
+    let manager = &state.manager_for_request(include_timings);
 
     let config = if let Some(path) = project_path {
         manager
@@ -42,7 +46,22 @@ pub async fn get_config(
         manager.get_global_config().map_err(|e| e.to_string())?
     };
 
-    Ok(ClaudeConfigData::from(config))
+    let mut data = ClaudeConfigData::from(config);
+    data.timings = TimingData::from_timings(manager.timings());
+    Ok(data)
+}
+
+/// Notes on values the project's merged config discards from the global one
+#[tauri::command]
+pub async fn get_merge_notes(
+    project_path: String,
+    state: State<'_, ConfigState>,
+) -> Result<Vec<MergeNoteData>, String> {
+    let (_, notes) = state
+        .manager
+        .get_merged_config_with_notes(Some(PathBuf::from(project_path).as_path()))
+        .map_err(|e| e.to_string())?;
+    Ok(notes.into_iter().map(MergeNoteData::from).collect())
 }
 
 /// Set a configuration value by key path (simplified)
@@ -51,13 +70,14 @@ pub async fn set_config_value(
     key: String,
     value: Value,
     project_path: Option<String>,
+    include_timings: Option<bool>,
     state: State<'_, ConfigState>,
-) -> Result<(), String> {
-    let manager = &state.manager;
+) -> Result<Option<Vec<TimingData>>, String> {
+    let manager = &state.manager_for_request(include_timings);
 
     // Determine config file path
     let config_path = if let Some(project) = project_path {
-        PathBuf::from(project).join(".claude").join("config.json")
+        claude_config_manager_core::project_config_path(Path::new(&project))
     } else {
         claude_config_manager_core::get_global_config_path()
     };
@@ -79,7 +99,68 @@ pub async fn set_config_value(
         .write_config_with_backup(&config_path, &config)
         .map_err(|e| e.to_string())?;
 
-    Ok(())
+    Ok(TimingData::from_timings(manager.timings()))
+}
+
+/// Create `<path>/.claude/config.json` for the "New project setup" flow
+///
+/// `source` is `{"kind": "project", "value": "<project path>"}`,
+/// `{"kind": "profile", "value": "<name>"}` or `{"kind": "empty"}` (the
+/// default). `sections` limits what is copied (empty: everything). An
+/// existing config is only replaced when `overwrite` is true.
+#[tauri::command]
+pub async fn create_project_config(
+    path: String,
+    source: Option<ConfigSource>,
+    sections: Vec<String>,
+    overwrite: Option<bool>,
+    state: State<'_, ConfigState>,
+) -> Result<CreatedConfigData, String> {
+    claude_config_manager_core::create_project_config(
+        &state.manager,
+        &PathBuf::from(path),
+        &source.unwrap_or(ConfigSource::Empty),
+        &sections,
+        overwrite.unwrap_or(false),
+    )
+    .map(CreatedConfigData::from)
+    .map_err(|e| e.to_string())
+}
+
+/// Save the global config, or a project's, as a profile for
+/// `create_project_config`
+///
+/// `sections` limits what is saved (empty: everything). An existing profile
+/// is only replaced when `overwrite` is true. Returns the profile path.
+#[tauri::command]
+pub async fn save_profile(
+    name: String,
+    project_path: Option<String>,
+    sections: Vec<String>,
+    overwrite: Option<bool>,
+    state: State<'_, ConfigState>,
+) -> Result<String, String> {
+    let config = match project_path {
+        Some(project) => state
+            .manager
+            .get_project_config(Some(Path::new(&project)))
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| format!("{project} has no project config"))?,
+        None => state
+            .manager
+            .get_global_config()
+            .map_err(|e| e.to_string())?,
+    };
+
+    claude_config_manager_core::save_profile(
+        &state.manager,
+        &name,
+        &config,
+        &sections,
+        overwrite.unwrap_or(false),
+    )
+    .map(|(path, _)| path.to_string_lossy().to_string())
+    .map_err(|e| e.to_string())
 }
 
 /// Helper function to set value by key path
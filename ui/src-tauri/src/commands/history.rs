@@ -2,8 +2,8 @@
 
 use crate::commands::config::ConfigState;
 use crate::commands::types::*;
-use claude_config_manager_core::BackupInfo;
-use std::path::PathBuf;
+use claude_config_manager_core::{backup::RetentionPolicy, BackupInfo};
+use std::path::{Path, PathBuf};
 use tauri::State;
 
 /// List all backups
@@ -15,7 +15,7 @@ pub async fn list_backups(
     let manager = &state.manager.backup_manager();
 
     let config_file = if let Some(project) = project_path {
-        PathBuf::from(project).join(".claude").join("config.json")
+        claude_config_manager_core::project_config_path(Path::new(&project))
     } else {
         claude_config_manager_core::get_global_config_path()
     };
@@ -30,6 +30,42 @@ pub async fn list_backups(
         .collect())
 }
 
+/// Preview which backups a prune would keep or delete
+#[tauri::command]
+pub async fn plan_backup_cleanup(
+    project_path: Option<String>,
+    keep_count: Option<usize>,
+    max_age_days: Option<i64>,
+    max_total_size: Option<u64>,
+    state: State<'_, ConfigState>,
+) -> Result<Vec<BackupPlanEntryData>, String> {
+    let manager = &state.manager.backup_manager();
+
+    let config_file = if let Some(project) = project_path {
+        claude_config_manager_core::project_config_path(Path::new(&project))
+    } else {
+        claude_config_manager_core::get_global_config_path()
+    };
+
+    let mut policy = keep_count
+        .map(RetentionPolicy::new)
+        .unwrap_or_else(|| manager.retention_policy());
+    if let Some(days) = max_age_days {
+        policy = policy
+            .with_max_age_days(days)
+            .map_err(|e| e.to_string())?;
+    }
+    if let Some(max_total_size) = max_total_size {
+        policy = policy.with_max_total_size(max_total_size);
+    }
+
+    let plan = manager
+        .cleanup_plan(&config_file, &policy)
+        .map_err(|e| e.to_string())?;
+
+    Ok(plan.into_iter().map(BackupPlanEntryData::from).collect())
+}
+
 /// Restore from a backup
 #[tauri::command]
 pub async fn restore_backup(
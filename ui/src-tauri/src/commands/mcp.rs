@@ -2,7 +2,7 @@
 
 use crate::commands::types::*;
 use crate::commands::config::ConfigState;
-use claude_config_manager_core::{ConfigScope, McpManager, McpServer};
+use claude_config_manager_core::{paths::get_backup_dir, ConfigScope, McpManager, McpServer};
 use std::path::PathBuf;
 use tauri::State;
 
@@ -13,12 +13,7 @@ pub async fn list_servers(
     project_path: Option<String>,
     _state: State<'_, ConfigState>,
 ) -> Result<Vec<McpServerData>, String> {
-    let backup_dir = dirs::config_dir()
-        .unwrap_or_else(|| PathBuf::from("."))
-        .join("claude")
-        .join("backups");
-
-    let manager = McpManager::new(&backup_dir);
+    let manager = McpManager::new(get_backup_dir());
     let config_scope = parse_scope(&scope, &project_path)?;
 
     let project_path_buf = project_path.map(PathBuf::from);
@@ -35,6 +30,21 @@ pub async fn list_servers(
         .collect())
 }
 
+/// List the effective MCP servers with their origin file and last change
+#[tauri::command]
+pub async fn list_servers_detailed(
+    project_path: Option<String>,
+    state: State<'_, ConfigState>,
+) -> Result<Vec<McpServerDetailData>, String> {
+    let project_path_buf = project_path.map(PathBuf::from);
+    let servers = state
+        .manager
+        .list_servers_merged(project_path_buf.as_deref())
+        .map_err(|e| e.to_string())?;
+
+    Ok(servers.into_iter().map(McpServerDetailData::from).collect())
+}
+
 /// Add a new MCP server
 #[tauri::command]
 pub async fn add_server(
@@ -46,12 +56,7 @@ pub async fn add_server(
     project_path: Option<String>,
     _state: State<'_, ConfigState>,
 ) -> Result<(), String> {
-    let backup_dir = dirs::config_dir()
-        .unwrap_or_else(|| PathBuf::from("."))
-        .join("claude")
-        .join("backups");
-
-    let manager = McpManager::new(&backup_dir);
+    let manager = McpManager::new(get_backup_dir()).with_configured_audit_log();
     let config_scope = parse_scope(&scope, &project_path)?;
 
     let mut server = McpServer::new(&name, &command, args.unwrap_or_default());
@@ -80,12 +85,7 @@ pub async fn remove_server(
     project_path: Option<String>,
     _state: State<'_, ConfigState>,
 ) -> Result<(), String> {
-    let backup_dir = dirs::config_dir()
-        .unwrap_or_else(|| PathBuf::from("."))
-        .join("claude")
-        .join("backups");
-
-    let manager = McpManager::new(&backup_dir);
+    let manager = McpManager::new(get_backup_dir()).with_configured_audit_log();
     let config_scope = parse_scope(&scope, &project_path)?;
 
     let project_path_buf = project_path.map(PathBuf::from);
@@ -104,12 +104,7 @@ pub async fn enable_server(
     project_path: Option<String>,
     _state: State<'_, ConfigState>,
 ) -> Result<(), String> {
-    let backup_dir = dirs::config_dir()
-        .unwrap_or_else(|| PathBuf::from("."))
-        .join("claude")
-        .join("backups");
-
-    let manager = McpManager::new(&backup_dir);
+    let manager = McpManager::new(get_backup_dir()).with_configured_audit_log();
     let config_scope = parse_scope(&scope, &project_path)?;
 
     let project_path_buf = project_path.map(PathBuf::from);
@@ -128,12 +123,7 @@ pub async fn disable_server(
     project_path: Option<String>,
     _state: State<'_, ConfigState>,
 ) -> Result<(), String> {
-    let backup_dir = dirs::config_dir()
-        .unwrap_or_else(|| PathBuf::from("."))
-        .join("claude")
-        .join("backups");
-
-    let manager = McpManager::new(&backup_dir);
+    let manager = McpManager::new(get_backup_dir()).with_configured_audit_log();
     let config_scope = parse_scope(&scope, &project_path)?;
 
     let project_path_buf = project_path.map(PathBuf::from);
@@ -152,12 +142,7 @@ pub async fn get_server(
     project_path: Option<String>,
     _state: State<'_, ConfigState>,
 ) -> Result<McpServerData, String> {
-    let backup_dir = dirs::config_dir()
-        .unwrap_or_else(|| PathBuf::from("."))
-        .join("claude")
-        .join("backups");
-
-    let manager = McpManager::new(&backup_dir);
+    let manager = McpManager::new(get_backup_dir());
     let config_scope = parse_scope(&scope, &project_path)?;
 
     let project_path_buf = project_path.map(PathBuf::from);
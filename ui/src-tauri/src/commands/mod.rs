@@ -5,6 +5,8 @@ pub mod history;
 pub mod mcp;
 pub mod project;
 pub mod search;
+pub mod state;
+pub mod support;
 pub mod types;
 pub mod utils;
 
@@ -2,27 +2,74 @@
 
 use crate::commands::config::ConfigState;
 use crate::commands::types::*;
-use claude_config_manager_core::ProjectScanner;
+use claude_config_manager_core::{
+    export_all, paths::get_registry_path, BundleSource, CancellationToken, ProjectRegistry,
+    ProjectScanner, Timings,
+};
 use std::path::PathBuf;
+use std::sync::Mutex;
 use tauri::State;
 
+/// Cancellation token of the export-all run in progress, if any
+#[derive(Default)]
+pub struct ExportState {
+    pub token: Mutex<Option<CancellationToken>>,
+}
+
 /// Scan directory for projects
+///
+/// Archived projects are left out unless `include_archived` is set.
 #[tauri::command]
 pub async fn scan_projects(
     path: String,
     max_depth: Option<usize>,
+    include_archived: Option<bool>,
     state: State<'_, ConfigState>,
 ) -> Result<Vec<ProjectData>, String> {
     let scanner = ProjectScanner::new(max_depth, false);
+    scan_with(scanner, &path, include_archived)
+}
+
+/// Scan directory for projects, reporting how long the scan took
+///
+/// Like [`scan_projects`], with the projects wrapped in a
+/// [`ScanResultData`] together with the operation timings.
+#[tauri::command]
+pub async fn scan_projects_timed(
+    path: String,
+    max_depth: Option<usize>,
+    include_archived: Option<bool>,
+    state: State<'_, ConfigState>,
+) -> Result<ScanResultData, String> {
+    let timings = Timings::new();
+    let scanner = ProjectScanner::new(max_depth, false).with_timings(timings.clone());
+    let projects = scan_with(scanner, &path, include_archived)?;
+
+    Ok(ScanResultData {
+        projects,
+        timings: TimingData::from_timings(&timings),
+    })
+}
 
-    let projects = scanner
+/// Scan `path` with `scanner`, leaving out archived projects unless asked
+fn scan_with(
+    scanner: ProjectScanner,
+    path: &str,
+    include_archived: Option<bool>,
+) -> Result<Vec<ProjectData>, String> {
+    let registry = ProjectRegistry::load(get_registry_path()).map_err(|e| e.to_string())?;
+    let mut projects = scanner
+        .with_registry(registry)
         .scan_directory(&PathBuf::from(path))
         .map_err(|e| e.to_string())?;
+    if !include_archived.unwrap_or(false) {
+        projects.retain(|project| !project.archived);
+    }
 
     Ok(projects.into_iter().map(ProjectData::from).collect())
 }
 
-/// List all discovered projects
+/// List all discovered projects (archived projects are left out)
 #[tauri::command]
 pub async fn list_projects(
     state: State<'_, ConfigState>,
@@ -30,10 +77,13 @@ pub async fn list_projects(
     // Get user's home directory
     let home = dirs::home_dir().ok_or("Could not find home directory")?;
 
-    let scanner = ProjectScanner::new(Some(3), false); // Scan up to 3 levels deep
-    let projects = scanner
+    let registry = ProjectRegistry::load(get_registry_path()).map_err(|e| e.to_string())?;
+    // Scan up to 3 levels deep
+    let scanner = ProjectScanner::new(Some(3), false).with_registry(registry);
+    let mut projects = scanner
         .scan_directory(&home)
         .map_err(|e| e.to_string())?;
+    projects.retain(|project| !project.archived);
 
     Ok(projects.into_iter().map(ProjectData::from).collect())
 }
@@ -52,3 +102,59 @@ pub async fn get_project_config(
 
     Ok(config.map(|c| ClaudeConfigData::from(c)))
 }
+
+/// Export every project config under `path` into one zip
+///
+/// Configs are staged in `<out_path>.staging` (or `resume_staging_dir` to
+/// continue an interrupted export). [`cancel_export_all`] stops the run and
+/// leaves the staging directory resumable.
+#[tauri::command]
+pub async fn export_all_projects(
+    out_path: String,
+    path: String,
+    resume_staging_dir: Option<String>,
+    include_archived: Option<bool>,
+    export_state: State<'_, ExportState>,
+) -> Result<ExportAllData, String> {
+    let registry = ProjectRegistry::load(get_registry_path()).map_err(|e| e.to_string())?;
+    let scanner = ProjectScanner::new(None, false).with_registry(registry);
+    let mut projects = scanner
+        .scan_directory(&PathBuf::from(path))
+        .map_err(|e| e.to_string())?;
+    if !include_archived.unwrap_or(false) {
+        projects.retain(|project| !project.archived);
+    }
+
+    let resume = resume_staging_dir.is_some();
+    let staging_dir = resume_staging_dir.unwrap_or_else(|| format!("{out_path}.staging"));
+
+    let token = CancellationToken::new();
+    *export_state.token.lock().map_err(|e| e.to_string())? = Some(token.clone());
+    let outcome = export_all(
+        &BundleSource::from_projects(&projects),
+        &PathBuf::from(out_path),
+        &PathBuf::from(staging_dir),
+        resume,
+        &token,
+        |_| {},
+    );
+    *export_state.token.lock().map_err(|e| e.to_string())? = None;
+
+    outcome.map(ExportAllData::from).map_err(|e| e.to_string())
+}
+
+/// Cancel the export-all run in progress
+///
+/// # Returns
+/// Whether an export was running
+#[tauri::command]
+pub async fn cancel_export_all(export_state: State<'_, ExportState>) -> Result<bool, String> {
+    let token = export_state.token.lock().map_err(|e| e.to_string())?;
+    match token.as_ref() {
+        Some(token) => {
+            token.cancel();
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
@@ -2,7 +2,7 @@
 
 use crate::commands::config::ConfigState;
 use crate::commands::types::*;
-use claude_config_manager_core::{ConfigSearcher, ConfigScope, SearchOptions, ValueType};
+use claude_config_manager_core::{QueryClause, SearchOptions};
 use std::path::PathBuf;
 use tauri::State;
 
@@ -25,26 +25,12 @@ pub async fn search_config(
         case_sensitive: case_sensitive.unwrap_or(false),
         regex: regex.unwrap_or(false),
         max_depth: None,
+        clauses: Vec::new(),
     };
 
-    let searcher = ConfigSearcher::with_options(options);
-
-    let (config, scope, config_path) = if let Some(path) = project_path {
-        let path_buf = PathBuf::from(&path);
-        let config_path = path_buf.join(".claude").join("config.json");
-        let config = manager
-            .get_project_config(Some(&path_buf))
-            .map_err(|e| e.to_string())?
-            .unwrap_or_default();
-        (config, ConfigScope::Project, config_path)
-    } else {
-        let config_path = claude_config_manager_core::get_global_config_path();
-        let config = manager.get_global_config().map_err(|e| e.to_string())?;
-        (config, ConfigScope::Global, config_path)
-    };
-
-    let results = searcher
-        .search(&query, &config, scope, config_path)
+    let project_path = project_path.map(PathBuf::from);
+    let results = manager
+        .search_all(&query, project_path.as_deref(), options)
         .map_err(|e| e.to_string())?;
 
     Ok(results
@@ -52,3 +38,26 @@ pub async fn search_config(
         .map(SearchResultData::from)
         .collect())
 }
+
+/// Find config entries (servers, skills, ...) matching all query clauses
+#[tauri::command]
+pub async fn search_config_where(
+    clauses: Vec<QueryClause>,
+    case_sensitive: Option<bool>,
+    project_path: Option<String>,
+    state: State<'_, ConfigState>,
+) -> Result<Vec<QueryMatchData>, String> {
+    let manager = &state.manager;
+
+    let options = clauses.into_iter().fold(
+        SearchOptions::new().with_case_sensitive(case_sensitive.unwrap_or(false)),
+        SearchOptions::with_clause,
+    );
+
+    let project_path = project_path.map(PathBuf::from);
+    let results = manager
+        .search_all_where(project_path.as_deref(), options)
+        .map_err(|e| e.to_string())?;
+
+    Ok(results.into_iter().map(QueryMatchData::from).collect())
+}
@@ -0,0 +1,25 @@
+//! Tauri commands for per-project UI state
+//!
+//! UI state is stored outside of `.claude/config.json`, so saving layout
+//! preferences never modifies files users commit.
+
+use claude_config_manager_core::{paths::get_state_dir, StateStore};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+/// Get all stored UI state for a project
+#[tauri::command]
+pub async fn get_ui_state(project_path: String) -> Result<BTreeMap<String, Value>, String> {
+    StateStore::new(get_state_dir())
+        .get_all(&PathBuf::from(project_path))
+        .map_err(|e| e.to_string())
+}
+
+/// Store a UI state value for a project
+#[tauri::command]
+pub async fn set_ui_state(project_path: String, key: String, value: Value) -> Result<(), String> {
+    StateStore::new(get_state_dir())
+        .set(&PathBuf::from(project_path), &key, &value)
+        .map_err(|e| e.to_string())
+}
@@ -0,0 +1,31 @@
+//! Tauri commands for the "report a problem" flow
+
+use claude_config_manager_core::{
+    paths::{get_audit_log_path, get_log_path},
+    support, SupportOptions,
+};
+use std::path::PathBuf;
+
+/// Write a redacted support bundle and return its path
+///
+/// The frontend is responsible for asking the user before passing
+/// `project_path`, since it adds the project's config to the bundle.
+#[tauri::command]
+pub async fn create_support_bundle(
+    out_path: String,
+    project_path: Option<String>,
+) -> Result<String, String> {
+    let options = SupportOptions {
+        project_path: project_path.map(PathBuf::from),
+        journal_path: Some(get_audit_log_path()),
+        log_path: get_log_path(),
+        ..Default::default()
+    };
+
+    let bundle = support::collect(&options).map_err(|e| e.to_string())?;
+    let written = bundle
+        .write_zip(&PathBuf::from(out_path))
+        .map_err(|e| e.to_string())?;
+
+    Ok(written.to_string_lossy().to_string())
+}
@@ -1,7 +1,7 @@
 //! Data types for Tauri commands
 
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 /// Configuration data for display in GUI
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -11,6 +11,9 @@ pub struct ClaudeConfigData {
     pub allowed_paths: Option<Vec<String>>,
     pub custom_instructions: Option<Vec<String>>,
     pub unknown: HashMap<String, serde_json::Value>,
+    /// Operation timings (only when requested with `include_timings`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timings: Option<Vec<TimingData>>,
 }
 
 impl From<claude_config_manager_core::ClaudeConfig> for ClaudeConfigData {
@@ -31,10 +34,34 @@ impl From<claude_config_manager_core::ClaudeConfig> for ClaudeConfigData {
             allowed_paths: config.allowed_paths,
             custom_instructions: config.custom_instructions,
             unknown: config.unknown,
+            timings: None,
         }
     }
 }
 
+/// Time spent in one operation (e.g. "read", "backup")
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimingData {
+    pub name: String,
+    pub duration_ms: u64,
+}
+
+impl TimingData {
+    /// Per-operation totals of an enabled collector, or None if disabled
+    pub fn from_timings(timings: &claude_config_manager_core::Timings) -> Option<Vec<Self>> {
+        timings.is_enabled().then(|| {
+            timings
+                .totals()
+                .into_iter()
+                .map(|total| Self {
+                    name: total.name.to_string(),
+                    duration_ms: total.duration.as_millis() as u64,
+                })
+                .collect()
+        })
+    }
+}
+
 /// MCP server data
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct McpServerData {
@@ -43,6 +70,7 @@ pub struct McpServerData {
     pub command: String,
     pub args: Vec<String>,
     pub env: HashMap<String, String>,
+    pub timeout_ms: Option<u64>,
 }
 
 impl From<claude_config_manager_core::McpServer> for McpServerData {
@@ -53,6 +81,39 @@ impl From<claude_config_manager_core::McpServer> for McpServerData {
             command: server.command.unwrap_or_default(),
             args: server.args,
             env: server.env,
+            timeout_ms: server.timeout_ms,
+        }
+    }
+}
+
+/// Effective MCP server with the file that defines it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct McpServerDetailData {
+    pub name: String,
+    pub enabled: bool,
+    /// "global" or "project"
+    pub origin_scope: String,
+    pub origin_path: String,
+    /// RFC 3339 timestamp, or None when backup history doesn't show a change
+    pub last_changed: Option<String>,
+}
+
+impl From<claude_config_manager_core::mcp::MergedServer> for McpServerDetailData {
+    fn from(merged: claude_config_manager_core::mcp::MergedServer) -> Self {
+        Self {
+            name: merged.name,
+            enabled: merged.server.enabled,
+            origin_scope: match merged.origin {
+                claude_config_manager_core::ConfigScope::Global => "global",
+                claude_config_manager_core::ConfigScope::Project => "project",
+                claude_config_manager_core::ConfigScope::Base => "base",
+            }
+            .to_string(),
+            origin_path: merged.origin_path.to_string_lossy().to_string(),
+            last_changed: merged
+                .last_changed
+                .as_ref()
+                .map(claude_config_manager_core::format_timestamp),
         }
     }
 }
@@ -83,6 +144,9 @@ pub struct ProjectData {
     pub root: String,
     pub claude_dir: String,
     pub has_config: bool,
+    /// RFC 3339 modification time of the config file
+    pub last_modified: Option<String>,
+    pub archived: bool,
 }
 
 impl From<claude_config_manager_core::ProjectInfo> for ProjectData {
@@ -93,10 +157,24 @@ impl From<claude_config_manager_core::ProjectInfo> for ProjectData {
             root: info.root.to_string_lossy().to_string(),
             claude_dir: info.claude_dir.to_string_lossy().to_string(),
             has_config: info.has_config,
+            last_modified: info
+                .last_modified
+                .as_ref()
+                .map(claude_config_manager_core::format_timestamp),
+            archived: info.archived,
         }
     }
 }
 
+/// Result of a project scan
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScanResultData {
+    pub projects: Vec<ProjectData>,
+    /// Operation timings (only when requested with `include_timings`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timings: Option<Vec<TimingData>>,
+}
+
 /// Search result
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SearchResultData {
@@ -119,6 +197,42 @@ impl From<claude_config_manager_core::SearchResult> for SearchResultData {
     }
 }
 
+/// Entry matching a structured search query
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueryMatchData {
+    pub root: String,
+    pub source: String,
+    pub config_path: String,
+    pub matches: Vec<ClauseMatchData>,
+}
+
+/// Where one query clause matched
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClauseMatchData {
+    pub clause: String,
+    pub path: String,
+    pub text: String,
+}
+
+impl From<claude_config_manager_core::QueryMatch> for QueryMatchData {
+    fn from(result: claude_config_manager_core::QueryMatch) -> Self {
+        Self {
+            root: result.root,
+            source: format!("{:?}", result.source),
+            config_path: result.config_path.to_string_lossy().to_string(),
+            matches: result
+                .matches
+                .into_iter()
+                .map(|hit| ClauseMatchData {
+                    clause: hit.clause.to_string(),
+                    path: hit.key_path,
+                    text: hit.text,
+                })
+                .collect(),
+        }
+    }
+}
+
 /// Backup information
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BackupInfoData {
@@ -133,8 +247,116 @@ impl From<claude_config_manager_core::BackupInfo> for BackupInfoData {
         Self {
             path: info.path,
             original_path: info.original_path,
-            created_at: info.created_at.to_rfc3339(),
+            created_at: claude_config_manager_core::format_timestamp(&info.created_at),
             size: info.size,
         }
     }
 }
+
+/// Retention decision for a backup ("keep" or "delete", with the reason)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupPlanEntryData {
+    pub backup: BackupInfoData,
+    pub action: String,
+    pub reason: String,
+}
+
+impl
+    From<(
+        claude_config_manager_core::BackupInfo,
+        claude_config_manager_core::backup::Decision,
+    )> for BackupPlanEntryData
+{
+    fn from(
+        (info, decision): (
+            claude_config_manager_core::BackupInfo,
+            claude_config_manager_core::backup::Decision,
+        ),
+    ) -> Self {
+        use claude_config_manager_core::backup::Decision;
+
+        let (action, reason) = match decision {
+            Decision::Keep(reason) => ("keep", reason.to_string()),
+            Decision::Delete(reason) => ("delete", reason.to_string()),
+        };
+
+        Self {
+            backup: BackupInfoData::from(info),
+            action: action.to_string(),
+            reason,
+        }
+    }
+}
+
+/// Advisory about values the merged config discards
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MergeNoteData {
+    pub key_path: String,
+    pub message: String,
+    pub suggestion: String,
+}
+
+impl From<claude_config_manager_core::MergeNote> for MergeNoteData {
+    fn from(note: claude_config_manager_core::MergeNote) -> Self {
+        Self {
+            key_path: note.key_path,
+            message: note.message,
+            suggestion: note.suggestion,
+        }
+    }
+}
+
+/// A newly created project config
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreatedConfigData {
+    pub path: String,
+    /// Number of entries per section (e.g. "mcpServers" => 3)
+    pub section_counts: BTreeMap<String, usize>,
+    pub warnings: Vec<String>,
+}
+
+impl From<claude_config_manager_core::CreatedConfig> for CreatedConfigData {
+    fn from(created: claude_config_manager_core::CreatedConfig) -> Self {
+        Self {
+            path: created.path.to_string_lossy().to_string(),
+            section_counts: created.section_counts,
+            warnings: created.warnings,
+        }
+    }
+}
+
+/// Result of exporting every project config into one bundle
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportAllData {
+    /// False when the export was cancelled
+    pub completed: bool,
+    /// The bundle, or the staging directory to resume when cancelled
+    pub path: String,
+    /// Configs staged so far
+    pub done: usize,
+    pub total: usize,
+}
+
+impl From<claude_config_manager_core::ExportAllOutcome> for ExportAllData {
+    fn from(outcome: claude_config_manager_core::ExportAllOutcome) -> Self {
+        use claude_config_manager_core::ExportAllOutcome;
+        match outcome {
+            ExportAllOutcome::Completed { path, entries, .. } => Self {
+                completed: true,
+                path: path.to_string_lossy().to_string(),
+                done: entries,
+                total: entries,
+            },
+            ExportAllOutcome::Cancelled {
+                staging_dir,
+                done,
+                total,
+            } => Self {
+                completed: false,
+                path: staging_dir.to_string_lossy().to_string(),
+                done,
+                total,
+            },
+        }
+    }
+}
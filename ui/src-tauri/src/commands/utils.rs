@@ -15,3 +15,63 @@ pub async fn get_global_config_path() -> Result<String, String> {
 pub async fn get_version() -> String {
     env!("CARGO_PKG_VERSION").to_string()
 }
+
+/// Get locally recorded command usage for the stats view
+#[tauri::command]
+pub async fn get_usage_summary() -> Result<claude_config_manager_core::UsageSummary, String> {
+    claude_config_manager_core::UsageTracker::new(
+        claude_config_manager_core::paths::get_usage_path(),
+    )
+    .load()
+    .map_err(|e| e.to_string())
+}
+
+/// Show a config file in the OS file manager
+///
+/// Opens the folder containing the global config (`scope` = "global") or the
+/// project's `.claude/config.json` (`scope` = "project"), selecting the file
+/// where the platform supports it. Returns the config path.
+#[tauri::command]
+pub async fn reveal_config(scope: String, project: Option<String>) -> Result<String, String> {
+    let config_path = match (scope.as_str(), project) {
+        ("global", _) => claude_config_manager_core::get_global_config_path(),
+        ("project", Some(project)) => {
+            claude_config_manager_core::project_config_path(std::path::Path::new(&project))
+        }
+        ("project", None) => return Err("A project path is required".to_string()),
+        _ => return Err(format!("Invalid scope: {scope}")),
+    };
+    let folder = config_path
+        .parent()
+        .ok_or_else(|| format!("{} has no parent folder", config_path.display()))?;
+    if !folder.exists() {
+        return Err(format!("{} does not exist", folder.display()));
+    }
+
+    let mut command = if cfg!(target_os = "macos") {
+        let mut command = std::process::Command::new("open");
+        if config_path.exists() {
+            command.arg("-R").arg(&config_path);
+        } else {
+            command.arg(folder);
+        }
+        command
+    } else if cfg!(windows) {
+        let mut command = std::process::Command::new("explorer");
+        if config_path.exists() {
+            command.arg(format!("/select,{}", config_path.display()));
+        } else {
+            command.arg(folder);
+        }
+        command
+    } else {
+        let mut command = std::process::Command::new("xdg-open");
+        command.arg(folder);
+        command
+    };
+    command
+        .spawn()
+        .map_err(|e| format!("Failed to open the file manager: {e}"))?;
+
+    Ok(config_path.to_string_lossy().to_string())
+}
@@ -3,6 +3,7 @@
 //! Tauri-based desktop application for managing Claude Code configurations.
 
 use crate::commands::config::ConfigState;
+use crate::commands::project::ExportState;
 use crate::commands::*;
 
 mod commands;
@@ -16,17 +17,26 @@ pub fn run() {
 
     tauri::Builder::default()
         .manage(config_state)
+        .manage(ExportState::default())
         .invoke_handler(tauri::generate_handler![
             // Configuration commands
             commands::config::get_config,
             commands::config::set_config_value,
+            commands::config::create_project_config,
+            commands::config::save_profile,
+            commands::config::get_merge_notes,
 
             // Project commands
+            commands::project::scan_projects,
+            commands::project::scan_projects_timed,
             commands::project::list_projects,
             commands::project::get_project_config,
+            commands::project::export_all_projects,
+            commands::project::cancel_export_all,
 
             // MCP server commands
             commands::mcp::list_servers,
+            commands::mcp::list_servers_detailed,
             commands::mcp::add_server,
             commands::mcp::remove_server,
             commands::mcp::enable_server,
@@ -36,9 +46,23 @@ pub fn run() {
             // History commands
             commands::history::list_backups,
             commands::history::restore_backup,
+            commands::history::plan_backup_cleanup,
+
+            // Search commands
+            commands::search::search_config,
+            commands::search::search_config_where,
+
+            // UI state commands
+            commands::state::get_ui_state,
+            commands::state::set_ui_state,
+
+            // Support commands
+            commands::support::create_support_bundle,
 
             // Utility commands
             commands::utils::get_global_config_path,
+            commands::utils::get_usage_summary,
+            commands::utils::reveal_config,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -12,12 +12,19 @@ interface McpServer {
   env: Record<string, string>
 }
 
+interface Timing {
+  name: string
+  duration_ms: number
+}
+
 interface ClaudeConfig {
   mcp_servers?: Record<string, McpServer>
   skills?: Record<string, unknown>
   allowed_paths?: string[]
   custom_instructions?: string[]
   unknown: Record<string, unknown>
+  // Only present when requested with includeTimings
+  timings?: Timing[]
 }
 
 interface Project {
@@ -26,6 +33,8 @@ interface Project {
   root: string
   claude_dir: string
   has_config: boolean
+  last_modified: string | null
+  archived: boolean
 }
 
 interface Backup {